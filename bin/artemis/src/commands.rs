@@ -0,0 +1,375 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::ErrorKind,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use artemis_core::collectors::config_collector::ConfigCollector;
+use ethers::{
+    abi::{
+        token::{LenientTokenizer, Tokenizer},
+        Abi, Token,
+    },
+    contract::ContractFactory,
+    middleware::SignerMiddleware,
+    providers::{Ipc, JsonRpcClient, Middleware, Provider, Ws},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, U256},
+};
+use mev_share_bindings::{balancer_flashloan::BalancerFlashloan, iweth::IWETH};
+use mev_share_uni_arb::{
+    config::StrategyConfig,
+    constants::WETH_ADDRESS,
+    pool_store::{self, CsvPoolStore, PoolStore},
+    types::{TriangularRouteRecord, V2V3PoolRecord},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{registry, Args, DeployArgs};
+
+/// MEV-share SSE endpoint hints are streamed from.
+pub const MEV_SHARE_EVENTS_URL: &str = "https://mev-share.flashbots.net";
+
+/// Relay endpoints the bot talks to, by name.
+pub const RELAYS: [(&str, &str); 2] = [
+    ("mev-share", "https://relay.flashbots.net"),
+    ("mev-share-events", MEV_SHARE_EVENTS_URL),
+];
+
+/// How long to wait for an endpoint to answer.
+const DIAL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of the checks of a command, printed as they run.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    /// Print the outcome of the check `name`.
+    fn check<T: Display, E: Display>(&mut self, name: &str, result: Result<T, E>) {
+        match result {
+            Ok(detail) => println!("ok      {:<20} {}", name, detail),
+            Err(e) => {
+                self.failures += 1;
+                println!("FAILED  {:<20} {}", name, e);
+            }
+        }
+    }
+
+    /// Fail if any check failed.
+    fn finish(self) -> Result<()> {
+        match self.failures {
+            0 => Ok(()),
+            n => Err(anyhow!("{} checks failed", n)),
+        }
+    }
+}
+
+/// Parse the configuration of `run`, then dial the node and relays it uses, reporting
+/// every check. Fails if any check failed.
+pub async fn validate_config(args: Args) -> Result<()> {
+    let mut report = Report::default();
+    report.check(
+        "private-key",
+        args.private_key
+            .parse::<LocalWallet>()
+            .map(|wallet| format!("{:?}", wallet.address())),
+    );
+    for key in &args.arb_private_keys {
+        report.check(
+            "arb-private-key",
+            key.parse::<LocalWallet>()
+                .map(|wallet| format!("{:?}", wallet.address())),
+        );
+    }
+    report.check(
+        "flashbots-signer",
+        args.flashbots_signer
+            .parse::<LocalWallet>()
+            .map(|wallet| format!("{:?}", wallet.address())),
+    );
+    if let Some(path) = &args.config_path {
+        report.check(
+            "config",
+            ConfigCollector::new(path)
+                .load::<StrategyConfig>()
+                .map(|_| path.display().to_string()),
+        );
+    }
+    if let Some(path) = &args.strategy_params {
+        report.check(
+            "strategy-params",
+            registry::load_params(path).map(|params| format!("{} strategies", params.len())),
+        );
+    }
+    report.check(
+        "pool-store",
+        load_pools(args.pool_store.as_deref())
+            .await
+            .map(|(pools, routes)| {
+                format!("{} pools, {} triangular routes", pools.len(), routes.len())
+            }),
+    );
+
+    match (&args.ipc, &args.wss) {
+        (Some(path), _) => match Ipc::connect(path).await {
+            Ok(ipc) => check_node(&mut report, Provider::new(ipc), &args).await,
+            Err(e) => report.check::<String, _>("node", Err(e)),
+        },
+        (None, Some(wss)) => match Ws::connect(wss).await {
+            Ok(ws) => check_node(&mut report, Provider::new(ws), &args).await,
+            Err(e) => report.check::<String, _>("node", Err(e)),
+        },
+        (None, None) => unreachable!("clap requires either --wss or --ipc"),
+    }
+
+    for (name, url) in RELAYS {
+        report.check(name, dial(url).await);
+    }
+    report.finish()
+}
+
+/// Check the node answers, and that the arb contract is deployed on its chain.
+async fn check_node<P: JsonRpcClient + 'static>(
+    report: &mut Report,
+    provider: Provider<P>,
+    args: &Args,
+) {
+    let head = async {
+        let chain_id = provider.get_chainid().await?;
+        let block = provider.get_block_number().await?;
+        Ok::<_, anyhow::Error>(format!("chain {}, block {}", chain_id, block))
+    };
+    report.check("node", head.await);
+    let code = provider.get_code(args.arb_contract_address, None).await;
+    report.check(
+        "arb-contract",
+        code.map_err(anyhow::Error::from)
+            .and_then(|code| match code.is_empty() {
+                true => Err(anyhow!("no code at {:?}", args.arb_contract_address)),
+                false => Ok(format!("{:?}", args.arb_contract_address)),
+            }),
+    );
+}
+
+/// Show the relay endpoints the bot talks to, and whether they can be reached. Fails if
+/// any can't.
+pub async fn list_relays() -> Result<()> {
+    let mut report = Report::default();
+    for (name, url) in RELAYS {
+        report.check(
+            name,
+            dial(url)
+                .await
+                .map(|status| format!("{} ({})", url, status)),
+        );
+    }
+    report.finish()
+}
+
+/// Send a request to `url`, returning the status and latency of the response. Any
+/// response counts, since relays answer plain requests with errors.
+async fn dial(url: &str) -> Result<String> {
+    let start = Instant::now();
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(DIAL_TIMEOUT)
+        .send()
+        .await?;
+    Ok(format!("{} in {:?}", response.status(), start.elapsed()))
+}
+
+/// A compiled contract, as written by forge or hardhat.
+#[derive(Deserialize)]
+struct Artifact {
+    abi: Abi,
+    bytecode: ArtifactBytecode,
+}
+
+/// Creation bytecode of an artifact: an object holding it for forge, the bytecode itself
+/// for hardhat.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArtifactBytecode {
+    Forge { object: Bytes },
+    Hardhat(Bytes),
+}
+
+/// What `deploy` reads from its config. Other keys of the config are left as they are.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DeployConfig {
+    /// Args of the constructor, e.g. `["0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"]`,
+    /// parsed as the types of its inputs.
+    constructor_args: Vec<String>,
+    /// Address of the arb contract, as written by a previous deploy.
+    arb_contract_address: Option<Address>,
+}
+
+/// Deploy the arb contract of `args.artifact`, check the wallet owns it, fund it if
+/// asked to, and write its address to the config.
+pub async fn deploy(args: DeployArgs) -> Result<()> {
+    match (&args.ipc, &args.wss) {
+        (Some(path), _) => deploy_with(Provider::new(Ipc::connect(path).await?), args).await,
+        (None, Some(wss)) => deploy_with(Provider::new(Ws::connect(wss).await?), args).await,
+        (None, None) => unreachable!("clap requires either --wss or --ipc"),
+    }
+}
+
+async fn deploy_with<P: JsonRpcClient + 'static>(
+    provider: Provider<P>,
+    args: DeployArgs,
+) -> Result<()> {
+    let path = &args.config;
+    let mut config = match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse config {}", path.display()))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => json!({}),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read config {}", path.display()))
+        }
+    };
+    if !config.is_object() {
+        bail!("config {} isn't a JSON object", path.display());
+    }
+    let deploy_config: DeployConfig = serde_json::from_value(config.clone())
+        .with_context(|| format!("failed to parse config {}", path.display()))?;
+    if let Some(address) = deploy_config.arb_contract_address {
+        if !args.redeploy && !provider.get_code(address, None).await?.is_empty() {
+            bail!(
+                "the config already names the arb contract at {:?}, pass --redeploy to replace it",
+                address
+            );
+        }
+    }
+
+    let artifact = File::open(&args.artifact)
+        .with_context(|| format!("failed to open artifact {}", args.artifact.display()))?;
+    let artifact: Artifact = serde_json::from_reader(artifact)
+        .with_context(|| format!("failed to parse artifact {}", args.artifact.display()))?;
+    let bytecode = match artifact.bytecode {
+        ArtifactBytecode::Forge { object } => object,
+        ArtifactBytecode::Hardhat(bytecode) => bytecode,
+    };
+    if bytecode.is_empty() {
+        bail!("artifact {} has no bytecode", args.artifact.display());
+    }
+    let constructor_args = constructor_args(&artifact.abi, &deploy_config.constructor_args)?;
+
+    let chain_id = provider.get_chainid().await?;
+    let wallet = args
+        .private_key
+        .parse::<LocalWallet>()?
+        .with_chain_id(chain_id.as_u64());
+    let owner = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let factory = ContractFactory::new(artifact.abi, bytecode, client.clone());
+    let (contract, receipt) = factory
+        .deploy_tokens(constructor_args)?
+        .send_with_receipt()
+        .await?;
+    let address = contract.address();
+    println!(
+        "deployed the arb contract at {:?} in tx {:?}",
+        address, receipt.transaction_hash
+    );
+
+    let contract_owner = BalancerFlashloan::new(address, client.clone())
+        .owner()
+        .call()
+        .await?;
+    if contract_owner != owner {
+        bail!(
+            "the arb contract at {:?} is owned by {:?}, not the deployer {:?}",
+            address,
+            contract_owner,
+            owner
+        );
+    }
+    println!("checked the arb contract is owned by {:?}", owner);
+
+    if let Some(amount) = args.fund_wei {
+        let amount = U256::from(amount);
+        let weth = IWETH::new(*WETH_ADDRESS, client.clone());
+        weth.deposit().value(amount).send().await?.await?;
+        weth.transfer(address, amount).send().await?.await?;
+        println!("funded the arb contract with {} wei of WETH", amount);
+    }
+
+    config["arb_contract_address"] = json!(format!("{:?}", address));
+    std::fs::write(path, serde_json::to_string_pretty(&config)?)
+        .with_context(|| format!("failed to write config {}", path.display()))?;
+    println!(
+        "wrote the address of the arb contract to {}",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Parse `args` as the inputs of the constructor of `abi`.
+fn constructor_args(abi: &Abi, args: &[String]) -> Result<Vec<Token>> {
+    let inputs = abi
+        .constructor()
+        .map(|constructor| constructor.inputs.as_slice())
+        .unwrap_or_default();
+    if inputs.len() != args.len() {
+        bail!(
+            "the constructor takes {} args, but the config has {}",
+            inputs.len(),
+            args.len()
+        );
+    }
+    inputs
+        .iter()
+        .zip(args)
+        .map(|(input, arg)| {
+            LenientTokenizer::tokenize(&input.kind, arg)
+                .with_context(|| format!("invalid constructor arg {}: {}", input.name, arg))
+        })
+        .collect()
+}
+
+/// The pools the strategy loads, as printed by `dump-pools`.
+#[derive(Serialize)]
+struct PoolMap {
+    /// The v2 pools arbed against each v3 pool.
+    v2_v3_pools: BTreeMap<String, Vec<V2V3PoolRecord>>,
+    triangular_routes: Vec<TriangularRouteRecord>,
+}
+
+/// Print the pools the strategy loads from `pool_store` as JSON, defaulting to the
+/// bundled csv files.
+pub async fn dump_pools(pool_store: Option<&str>) -> Result<()> {
+    let (pools, triangular_routes) = load_pools(pool_store).await?;
+    let mut v2_v3_pools: BTreeMap<String, Vec<V2V3PoolRecord>> = BTreeMap::new();
+    for pool in pools {
+        v2_v3_pools
+            .entry(format!("{:?}", pool.v3_pool))
+            .or_default()
+            .push(pool);
+    }
+    let map = PoolMap {
+        v2_v3_pools,
+        triangular_routes,
+    };
+    println!("{}", serde_json::to_string_pretty(&map)?);
+    Ok(())
+}
+
+/// Read the pools and routes of a pool store.
+async fn load_pools(
+    pool_store: Option<&str>,
+) -> Result<(Vec<V2V3PoolRecord>, Vec<TriangularRouteRecord>)> {
+    let store: Arc<dyn PoolStore> = match pool_store {
+        Some(url) => pool_store::open(url).await?,
+        None => Arc::new(CsvPoolStore::bundled()),
+    };
+    Ok((store.v2_v3_pools().await?, store.triangular_routes().await?))
+}
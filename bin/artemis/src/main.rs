@@ -1,100 +1,519 @@
-use std::sync::Arc;
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use artemis_core::{
+    admin::{AdminServer, LogLevelHandler},
+    collectors::block_collector::BlockCollector,
+    collectors::inventory_collector::InventoryCollector,
+    collectors::log_collector::LogCollector,
     collectors::mevshare_collector::MevShareCollector,
-    engine::Engine,
-    executors::mev_share_executor::{MevshareExecutor, self},
+    engine::{Engine, EventTimeout, RestartPolicy},
+    health::HealthServer,
+    executors::mev_share_executor::{Bundles, MevshareExecutor},
+    executors::bundle_merging_executor::BundleMergingExecutor,
     executors::flashbots_executor::{FlashbotsExecutor, self},
-    types::{CollectorMap, ExecutorMap},
+    executors::noop_executor::NoopExecutor,
+    executors::circuit_breaker_executor::CircuitBreakerExecutor,
+    types::{CollectorMap, Executor, ExecutorMap, RestartableCollectorMap},
+    utilities::chain_state::ChainState,
 };
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use ethers::{
     prelude::MiddlewareBuilder,
-    providers::{Provider, Ws},
+    providers::{Ipc, Provider, PubsubClient, Ws},
     signers::{LocalWallet, Signer},
     types::{Address, Chain},
 };
+use matchmaker::builders::BuilderDirectory;
 use mev_share_uni_arb::{
-    strategy::MevShareUniArb,
+    constants::WETH_ADDRESS,
+    pool_store::{self, CsvPoolStore},
+    reserves::ReserveTracker,
     types::{Action, Event},
 };
-use tracing::{info, Level};
-use tracing_subscriber::{filter, prelude::*};
+use registry::{StrategyContext, StrategyRegistry};
+use tracing::warn;
 
-/// CLI Options.
+mod commands;
+mod registry;
+mod telemetry;
+
+/// CLI of the bot, e.g. `artemis run --wss <WSS> ...`.
 #[derive(Parser, Debug)]
+#[command(name = "artemis")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Options of the bot.
+#[derive(clap::Args, Debug)]
 pub struct Args {
     /// Ethereum node WS endpoint.
+    #[arg(long, required_unless_present = "ipc", conflicts_with = "ipc")]
+    pub wss: Option<String>,
+    /// Path to the IPC socket of a co-located Ethereum node.
     #[arg(long)]
-    pub wss: String,
+    pub ipc: Option<PathBuf>,
     /// Private key for sending txs.
     #[arg(long)]
     pub private_key: String,
+    /// Private key of another wallet signing arb txs, in rotation with the wallet of
+    /// `--private-key`. Can be repeated.
+    #[arg(long = "arb-private-key")]
+    pub arb_private_keys: Vec<String>,
     /// MEV share signer
     #[arg(long)]
     pub flashbots_signer: String,
     /// Address of the arb contract.
     #[arg(long)]
     pub arb_contract_address: Address,
+    /// Print logs as JSON lines.
+    #[arg(long)]
+    pub log_json: bool,
+    /// OTLP/gRPC endpoint to export tracing spans to, e.g. http://localhost:4317.
+    #[arg(long)]
+    pub otlp_endpoint: Option<String>,
+    /// Drop MEV-share hints older than this many milliseconds. Defaults to one block.
+    #[arg(long, default_value_t = 12_000)]
+    pub max_event_age_ms: u64,
+    /// Warn when the strategy takes longer than this many milliseconds to process
+    /// several hints in a row.
+    #[arg(long, default_value_t = 100)]
+    pub strategy_deadline_ms: u64,
+    /// Give up on a hint the strategy takes longer than this many milliseconds to
+    /// process, e.g. because an RPC call hangs.
+    #[arg(long)]
+    pub event_timeout_ms: Option<u64>,
+    /// Process hints which timed out again, up to this many times.
+    #[arg(long, default_value_t = 0)]
+    pub event_timeout_retries: u32,
+    /// Keep this many of the last events of each collector, and replay them to
+    /// strategies restarted after a panic.
+    #[arg(long)]
+    pub event_replay_capacity: Option<usize>,
+    /// Restart a strategy which panics up to this many times, with an exponential
+    /// backoff, before stopping it.
+    #[arg(long, default_value_t = 0)]
+    pub max_strategy_restarts: u32,
+    /// Another MEV-share SSE endpoint to stream hints from, alongside the default one.
+    /// Each hint is taken from the endpoint delivering it first. Can be repeated.
+    #[arg(long = "hint-url")]
+    pub hint_urls: Vec<String>,
+    /// Resubscribe to MEV-share hints when none arrived for this many seconds, in case
+    /// the stream silently stalled.
+    #[arg(long)]
+    pub hint_stall_secs: Option<u64>,
+    /// File to record the strategy's decision about every hint to, as JSON lines.
+    #[arg(long)]
+    pub journal_path: Option<PathBuf>,
+    /// JSON file of strategy parameters, reloaded when it changes or on SIGHUP.
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+    /// Log actions instead of submitting them.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Address to serve `/healthz` and `/readyz` on, e.g. 0.0.0.0:8080.
+    #[arg(long)]
+    pub health_addr: Option<SocketAddr>,
+    /// Local address to serve the admin JSON-RPC interface on, e.g. 127.0.0.1:8081.
+    /// It is unauthenticated, so don't expose it publicly.
+    #[arg(long)]
+    pub admin_addr: Option<SocketAddr>,
+    /// Disable the relay executor after this many consecutive failures, probing it
+    /// again every 30 seconds.
+    #[arg(long, default_value_t = 5)]
+    pub relay_failure_threshold: u32,
+    /// Merge independent arb bundles targeting the same block into bundles of up to this
+    /// many txs. Arb bundles hold 2 txs, so they aren't merged by default.
+    #[arg(long, default_value_t = 2)]
+    pub max_bundle_txs: usize,
+    /// Stop signing arb txs with a wallet while it holds less than this much ETH, in wei.
+    /// Bidding stops while every wallet does.
+    #[arg(long, default_value_t = 0)]
+    pub min_wallet_balance_wei: u128,
+    /// Stop bidding while the arb contract holds less than this much WETH, in wei.
+    #[arg(long, default_value_t = 0)]
+    pub min_contract_weth_wei: u128,
+    /// Withdraw the WETH of the arb contract to its owner whenever it holds more than
+    /// this much, in wei. Profits aren't swept if unset, unless the params of `auto-sweep`
+    /// set a threshold.
+    #[arg(long)]
+    pub sweep_threshold_wei: Option<u128>,
+    /// Minimum time between two sweeps, in seconds.
+    #[arg(long, default_value_t = 600)]
+    pub sweep_cooldown_secs: u64,
+    /// Don't sweep while the gas price is above this, in wei.
+    #[arg(long)]
+    pub sweep_max_gas_price_wei: Option<u128>,
+    /// Store to read the pools to arb from: a directory of csv files, `sqlite://<path>`
+    /// or a `postgres://` url. Defaults to the csv files bundled with the strategy.
+    #[arg(long)]
+    pub pool_store: Option<String>,
+    /// Strategy to run, by name, e.g. `mev-share-uni-arb`. Can be repeated. Defaults to
+    /// the arb strategy, along with `auto-sweep` if `--sweep-threshold-wei` is set.
+    #[arg(long = "strategy")]
+    pub strategies: Vec<String>,
+    /// JSON file of the parameters of strategies, by name, e.g.
+    /// `{"auto-sweep": {"threshold_wei": 1000000000000000000}}`.
+    #[arg(long)]
+    pub strategy_params: Option<PathBuf>,
+    /// Only share the hashes of the txs of arb bundles, so they can't be sandwiched from
+    /// their calldata.
+    #[arg(long)]
+    pub hash_only_hints: bool,
+    /// Only send arb bundles to this builder, by name or address. Can be repeated.
+    /// Defaults to every builder in the builder registry.
+    #[arg(long = "trusted-builder")]
+    pub trusted_builders: Vec<String>,
+    /// Registry the builders bundles are sent to are fetched from. Defaults to the
+    /// public builder registry.
+    #[arg(long)]
+    pub builder_registry_url: Option<String>,
+    /// Time between two fetches of the builder registry, in seconds.
+    #[arg(long, default_value_t = 3_600)]
+    pub builder_refresh_secs: u64,
+    /// Margin added to the gas estimates of arb txs, in basis points.
+    #[arg(long, default_value_t = 2_000)]
+    pub gas_safety_margin_bps: u32,
+    /// Time after which the gas of an arb route is estimated again, in seconds.
+    #[arg(long, default_value_t = 600)]
+    pub gas_refresh_secs: u64,
+    /// Simulate arb txs at the latest block before submitting them, reusing outcomes
+    /// within a block for this many milliseconds. Arbs aren't simulated if unset.
+    #[arg(long)]
+    pub arb_simulation_ttl_ms: Option<u64>,
+    /// Track the reserves of the v2 pools arbed from their `Sync` logs, instead of
+    /// fetching them for every hint.
+    #[arg(long)]
+    pub track_v2_reserves: bool,
+    /// Also backrun the cycles of up to this many hops, 2 or 3, through the pools hints
+    /// touch, searched in the graph of the pools of the pool store.
+    #[arg(long)]
+    pub route_search_max_hops: Option<usize>,
+    /// Only search cycles through this token, besides WETH and the tokens of the touched
+    /// pool. Can be repeated. Defaults to every token.
+    #[arg(long = "route-token")]
+    pub route_tokens: Vec<Address>,
+}
+
+/// Options of `deploy`.
+#[derive(clap::Args, Debug)]
+pub struct DeployArgs {
+    /// Ethereum node WS endpoint.
+    #[arg(long, required_unless_present = "ipc", conflicts_with = "ipc")]
+    pub wss: Option<String>,
+    /// Path to the IPC socket of a co-located Ethereum node.
+    #[arg(long)]
+    pub ipc: Option<PathBuf>,
+    /// Private key of the wallet deploying the contract, which owns it.
+    #[arg(long)]
+    pub private_key: String,
+    /// Compiled arb contract, as a forge or hardhat artifact holding its ABI and
+    /// bytecode, e.g. `contracts/out/BlindArb.sol/BlindArb.json`.
+    #[arg(long)]
+    pub artifact: PathBuf,
+    /// JSON file the constructor args are read from, as `constructor_args`, and the
+    /// address of the contract is written to, as `arb_contract_address`.
+    #[arg(long)]
+    pub config: PathBuf,
+    /// Fund the contract with this much WETH once deployed, in wei, wrapped from ETH.
+    #[arg(long)]
+    pub fund_wei: Option<u128>,
+    /// Deploy even if the config already names a deployed contract.
+    #[arg(long)]
+    pub redeploy: bool,
+}
+
+/// Subcommands of the CLI.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the bot.
+    Run(Args),
+    /// Parse the options of `run`, then dial the node and relays they name, reporting
+    /// every check. Exits with an error if any failed.
+    ValidateConfig(Args),
+    /// Show the relay endpoints the bot talks to, and whether they can be reached.
+    ListRelays,
+    /// Print the pools the strategy loads, as JSON.
+    DumpPools {
+        /// Store to read the pools from, in the format of `--pool-store`. Defaults to
+        /// the bundled csv files.
+        #[arg(long)]
+        pool_store: Option<String>,
+    },
+    /// Import or refresh the pools of a pool store from a directory of csv files,
+    /// replacing the pools it held.
+    ImportPools {
+        /// Directory of the csv files to import. Defaults to the bundled ones.
+        #[arg(long)]
+        from: Option<PathBuf>,
+        /// Store to import into, in the format of `--pool-store`.
+        #[arg(long)]
+        to: String,
+    },
+    /// Deploy the arb contract, check the wallet owns it, optionally fund it, and write
+    /// its address to the config.
+    Deploy(DeployArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Set up tracing and parse args.
-    let filter = filter::Targets::new()
-        .with_target("mev_share_uni_arb", Level::INFO)
-        .with_target("artemis_core", Level::INFO);
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(filter)
-        .init();
-
-    let args = Args::parse();
+    match Cli::parse().command {
+        Command::Run(args) => {
+            // Set up tracing.
+            let set_log_level = telemetry::init(args.log_json, args.otlp_endpoint.as_deref())?;
+            connect(args, set_log_level).await
+        }
+        Command::ValidateConfig(args) => commands::validate_config(args).await,
+        Command::Deploy(args) => commands::deploy(args).await,
+        Command::ListRelays => commands::list_relays().await,
+        Command::DumpPools { pool_store } => commands::dump_pools(pool_store.as_deref()).await,
+        Command::ImportPools { from, to } => {
+            telemetry::init(false, None)?;
+            let from = match from {
+                Some(dir) => CsvPoolStore::new(dir),
+                None => CsvPoolStore::bundled(),
+            };
+            let store = pool_store::open(&to).await?;
+            let (pools, routes) = pool_store::import(&from, store.as_ref()).await?;
+            println!("imported {} pools and {} triangular routes into {}", pools, routes, to);
+            Ok(())
+        }
+    }
+}
 
+/// Connect to the node given by `args`, then run the bot on top of it.
+async fn connect(args: Args, set_log_level: LogLevelHandler) -> Result<()> {
     //  Set up providers and signers.
-    let ws = Ws::connect(args.wss).await?;
-    let provider = Provider::new(ws);
+    match (&args.ipc, &args.wss) {
+        (Some(path), _) => {
+            let provider = Provider::new(Ipc::connect(path).await?);
+            run(provider, args, set_log_level).await
+        }
+        (None, Some(wss)) => {
+            let provider = Provider::new(Ws::connect(wss).await?);
+            run(provider, args, set_log_level).await
+        }
+        (None, None) => unreachable!("clap requires either --wss or --ipc"),
+    }
+}
 
+/// Set up and run the engine on top of the given provider.
+async fn run<P: PubsubClient + 'static>(
+    provider: Provider<P>,
+    args: Args,
+    set_log_level: LogLevelHandler,
+) -> Result<()> {
     let wallet: LocalWallet = args.private_key.parse().unwrap();
     let address = wallet.address();
 
     let provider = Arc::new(provider.nonce_manager(address).with_signer(wallet.clone()));
     let fb_signer: LocalWallet = args.flashbots_signer.parse().unwrap();
+    let arb_wallets = args
+        .arb_private_keys
+        .iter()
+        .map(|key| key.parse())
+        .collect::<Result<Vec<LocalWallet>, _>>()?;
 
     // Set up engine.
-    let mut engine: Engine<Event, Action> = Engine::default();
+    let mut engine: Engine<Event, Action> = Engine::default()
+        .with_max_event_age(Duration::from_millis(args.max_event_age_ms))
+        .with_strategy_deadline(Duration::from_millis(args.strategy_deadline_ms))
+        .with_restart_policy(RestartPolicy::new(args.max_strategy_restarts))
+        .with_receipts(Event::SubmissionReceipt)
+        .with_action_expiry(Action::expires_at, |event| match event {
+            Event::NewBlock(block) => Some(block.clone()),
+            _ => None,
+        });
+    if let Some(timeout_ms) = args.event_timeout_ms {
+        engine = engine.with_event_timeout(
+            EventTimeout::new(Duration::from_millis(timeout_ms))
+                .with_retries(args.event_timeout_retries),
+        );
+    }
+    if args.dry_run {
+        engine = engine.with_dry_run(NoopExecutor::new());
+    }
+    if let Some(capacity) = args.event_replay_capacity {
+        engine = engine.with_event_replay(capacity);
+    }
+    if let Some(addr) = args.health_addr {
+        let server = HealthServer::bind(addr, engine.health()).await?;
+        engine = engine.with_health_server(server);
+    }
+    if let Some(addr) = args.admin_addr {
+        let server = AdminServer::bind(addr, engine.control())
+            .await?
+            .with_log_level_handler(set_log_level);
+        engine = engine.with_admin_server(server);
+    }
 
     // Set up collector.
-    let mevshare_collector = Box::new(MevShareCollector::new(String::from(
-        "https://mev-share.flashbots.net",
-    )));
-    let mevshare_collector = CollectorMap::new(mevshare_collector, Event::MEVShareEvent);
-    engine.add_collector(Box::new(mevshare_collector));
+    let mevshare_collector = args.hint_urls.iter().fold(
+        MevShareCollector::new(String::from(commands::MEV_SHARE_EVENTS_URL)),
+        |collector, url| collector.with_url(url.clone()),
+    );
+    let mevshare_collector = Box::new(mevshare_collector);
+    let mevshare_collector = RestartableCollectorMap::new(mevshare_collector, Event::MEVShareEvent);
+    match args.hint_stall_secs {
+        Some(stall_secs) => engine.add_restartable_collector(
+            Box::new(mevshare_collector),
+            Duration::from_secs(stall_secs),
+        ),
+        None => engine.add_collector(Box::new(mevshare_collector)),
+    }
+    // Every wallet signing arb txs has its balance watched.
+    let wallet_addresses =
+        std::iter::once(address).chain(arb_wallets.iter().map(LocalWallet::address));
+    for wallet_address in wallet_addresses {
+        let inventory_collector = InventoryCollector::new(
+            Arc::new(provider.clone()),
+            wallet_address,
+            args.arb_contract_address,
+            *WETH_ADDRESS,
+        );
+        let inventory_collector =
+            CollectorMap::new(Box::new(inventory_collector), Event::InventoryUpdate);
+        engine.add_collector(Box::new(inventory_collector));
+    }
+    // New blocks expire bundles targeting them, and simulation outcomes, and keep the
+    // latest block and fees strategies read up to date.
+    let chain_state = Arc::new(ChainState::new());
+    let block_collector =
+        BlockCollector::new(Arc::new(provider.clone())).with_chain_state(chain_state.clone());
+    let block_collector = CollectorMap::new(Box::new(block_collector), Event::NewBlock);
+    engine.add_collector(Box::new(block_collector));
+    // Sync logs keep the reserves of v2 pools up to date, if they are tracked.
+    let reserves = args.track_v2_reserves.then(|| Arc::new(ReserveTracker::new()));
+    if reserves.is_some() {
+        let sync_collector =
+            LogCollector::new(Arc::new(provider.clone()), ReserveTracker::filter());
+        let sync_collector = CollectorMap::new(Box::new(sync_collector), Event::V2SyncLog);
+        engine.add_collector(Box::new(sync_collector));
+    }
     
 
-    // Set up strategy.
-    let strategy = MevShareUniArb::new(
-        Arc::new(provider.clone()),
-        wallet.clone(),
-        args.arb_contract_address,
-    );
-    engine.add_strategy(Box::new(strategy));
+    // Set up strategies.
+    let context = StrategyContext {
+        client: provider.clone(),
+        wallet: wallet.clone(),
+        arb_wallets,
+        fb_signer: fb_signer.clone(),
+        reserves,
+        chain_state,
+        args: &args,
+    };
+    let mut params = match &args.strategy_params {
+        Some(path) => registry::load_params(path)?,
+        None => HashMap::new(),
+    };
+    let strategies = StrategyRegistry::default();
+    for name in registry::selected(&args) {
+        strategies
+            .build(&name, &context, params.remove(&name), &mut engine)
+            .await?;
+    }
+    for name in params.keys() {
+        warn!("ignoring the params of strategy {}, which isn't run", name);
+    }
     
 
         // Set up executor
-    let mev_share_executor = Box::new(MevshareExecutor::new(fb_signer, Chain::Mainnet));
-    let mev_share_executor = ExecutorMap::new(mev_share_executor, |action| match action {
-        Action::SubmitBundles(bundles) => Some(bundles),
-    });
-    
-    
+    let mut builder_directory = BuilderDirectory::new()
+        .with_refresh_interval(Duration::from_secs(args.builder_refresh_secs));
+    if let Some(url) = &args.builder_registry_url {
+        builder_directory = builder_directory.with_registry_url(url);
+    }
+    let builder_directory = Arc::new(builder_directory);
+    builder_directory.spawn_refresh();
+    let mev_share_executor = MevshareExecutor::new(fb_signer.clone(), Chain::Mainnet)
+        .with_client(Arc::new(provider.clone()))
+        .with_builder_directory(builder_directory);
+    let mev_share_executor =
+        BundleMergingExecutor::new(mev_share_executor).with_max_body_len(args.max_bundle_txs);
+    let mev_share_executor =
+        CircuitBreakerExecutor::new(mev_share_executor, args.relay_failure_threshold)
+            .with_name("mev-share")
+            .with_timeout(Duration::from_secs(5));
+    add_bundle_executor(&mut engine, Box::new(mev_share_executor));
+
     // Start engine.
-    if let Ok(mut set) = engine.run().await {
-        while let Some(res) = set.join_next().await {
-            info!("res: {:?}", res);
+    match engine.run().await {
+        Ok(running) => {
+            if let Err(report) = running.wait().await {
+                warn!("engine stopped: {}", report);
+            }
         }
+        Err(e) => warn!("error starting engine: {}", e),
     }
 
     Ok(())
 }
+
+/// Add an executor of the bundles the strategies submit, ignoring their other actions.
+fn add_bundle_executor(engine: &mut Engine<Event, Action>, executor: Box<dyn Executor<Bundles>>) {
+    let executor = ExecutorMap::new(executor, |action| match action {
+        Action::SubmitBundles(bundles) => Some(bundles),
+        Action::Sweep(_) | Action::SubmitFlashbotsBundle(_) => None,
+    });
+    engine.add_executor(Box::new(executor));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use artemis_core::{
+        collectors::block_collector::NewBlock,
+        test_utils::{wait_for_actions, CapturingExecutor, MockCollector},
+        types::Strategy,
+    };
+    use async_trait::async_trait;
+    use ethers::types::{H256, U64};
+    use matchmaker::types::BundleRequest;
+
+    /// Strategy submitting a bundle targeting every new block.
+    struct BundlePerBlock;
+
+    #[async_trait]
+    impl Strategy<Event, Action> for BundlePerBlock {
+        async fn sync_state(&mut self) -> artemis_core::error::Result<()> {
+            Ok(())
+        }
+
+        async fn process_event(&mut self, event: Event) -> Option<Action> {
+            match event {
+                Event::NewBlock(block) => Some(Action::SubmitBundles(vec![
+                    BundleRequest::make_simple(block.number + 1, vec![]),
+                ])),
+                _ => None,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn submits_bundles_to_the_bundle_executor() {
+        let collector = MockCollector::new();
+        let sender = collector.sender();
+        let executor = CapturingExecutor::new();
+        let mut engine: Engine<Event, Action> = Engine::new();
+        engine.add_collector(Box::new(collector));
+        engine.add_strategy(Box::new(BundlePerBlock));
+        add_bundle_executor(&mut engine, Box::new(executor.clone()));
+        let running = engine.run().await.unwrap();
+
+        sender
+            .send(Event::NewBlock(NewBlock {
+                hash: H256::zero(),
+                number: U64::from(1),
+                parent_hash: H256::zero(),
+            }))
+            .unwrap();
+        let bundles = wait_for_actions(&executor, 1, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0][0].inclusion.block, U64::from(2));
+        running.shutdown();
+    }
+}
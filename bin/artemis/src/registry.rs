@@ -0,0 +1,387 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use artemis_core::{
+    collectors::{config_collector::ConfigCollector, log_collector::LogCollector},
+    engine::Engine,
+    executors::{flashbots_executor::FlashbotsExecutor, mev_share_executor::MevshareExecutor},
+    types::{CollectorMap, ExecutorMap, Reconfigurable, StrategyMap},
+    utilities::{chain_state::ChainState, decision_journal::FileJournal},
+};
+use comet_liquidator::{
+    constants::{COMET_USDC_ADDRESS, COMET_USDC_DEPLOYMENT_BLOCK},
+    strategy::{price_update_filter, CometLiquidator},
+    types::{Action as CometAction, Config as CometConfig, Event as CometEvent},
+};
+use ethers::{
+    providers::{Middleware, PubsubClient},
+    signers::LocalWallet,
+    types::{Address, Chain, U256},
+};
+use futures::future::{FutureExt, LocalBoxFuture};
+use mev_share_uni_arb::{
+    gas::GasEstimator,
+    pool_store,
+    reserves::ReserveTracker,
+    routing::RouteSearch,
+    strategy::MevShareUniArb,
+    sweep::{AutoSweep, SweepExecutor},
+    types::{Action, Event, SubmissionPrivacy},
+};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::Args;
+
+/// Name of the MEV-share arb strategy.
+pub const MEV_SHARE_UNI_ARB: &str = "mev-share-uni-arb";
+
+/// Name of the strategy sweeping the profits of the arb contract.
+pub const AUTO_SWEEP: &str = "auto-sweep";
+
+/// Name of the strategy liquidating Comet accounts.
+pub const COMET_LIQUIDATOR: &str = "comet-liquidator";
+
+/// Relay the liquidation bundles of the Comet liquidator are sent to.
+const FLASHBOTS_RELAY_URL: &str = "https://relay.flashbots.net";
+
+/// Priority fee paid by liquidation txs, unless set in the Comet liquidator's parameters.
+const DEFAULT_LIQUIDATION_PRIORITY_FEE_WEI: u128 = 1_000_000_000;
+
+/// What strategies are built with.
+pub struct StrategyContext<'a, M> {
+    /// Client of the node the bot runs on.
+    pub client: Arc<M>,
+    /// Wallet signing the txs strategies send.
+    pub wallet: LocalWallet,
+    /// Wallets signing arb txs in rotation with `wallet`.
+    pub arb_wallets: Vec<LocalWallet>,
+    /// Signer authenticating bundles with relays.
+    pub fb_signer: LocalWallet,
+    /// Reserves of v2 pools kept in sync from their logs, if they are tracked.
+    pub reserves: Option<Arc<ReserveTracker>>,
+    /// Latest block and fees, kept up to date by the block collector.
+    pub chain_state: Arc<ChainState>,
+    /// Options the bot runs with.
+    pub args: &'a Args,
+}
+
+/// Builds a strategy from its parameters, if any were given, and adds it to the engine
+/// along with the collectors and executors it needs.
+pub type StrategyConstructor<M> = for<'a> fn(
+    &'a StrategyContext<'a, M>,
+    Option<Value>,
+    &'a mut Engine<Event, Action>,
+) -> LocalBoxFuture<'a, Result<()>>;
+
+/// The strategies the binary can run, by name. The default registry holds the built-in
+/// strategies.
+pub struct StrategyRegistry<M> {
+    constructors: BTreeMap<&'static str, StrategyConstructor<M>>,
+}
+
+impl<M: Middleware + 'static> StrategyRegistry<M> {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            constructors: BTreeMap::new(),
+        }
+    }
+
+    /// Register `constructor` under `name`, replacing the strategy registered under it.
+    pub fn register(&mut self, name: &'static str, constructor: StrategyConstructor<M>) {
+        self.constructors.insert(name, constructor);
+    }
+
+    /// Names of the registered strategies.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.constructors.keys().copied().collect()
+    }
+
+    /// Build the strategy registered under `name` with `params`, and add it to `engine`.
+    pub async fn build(
+        &self,
+        name: &str,
+        context: &StrategyContext<'_, M>,
+        params: Option<Value>,
+        engine: &mut Engine<Event, Action>,
+    ) -> Result<()> {
+        let constructor = self.constructors.get(name).ok_or_else(|| {
+            anyhow!(
+                "unknown strategy {}, expected one of {}",
+                name,
+                self.names().join(", ")
+            )
+        })?;
+        constructor(context, params, engine)
+            .await
+            .with_context(|| format!("failed to build strategy {}", name))
+    }
+}
+
+impl<M> Default for StrategyRegistry<M>
+where
+    M: Middleware + 'static,
+    M::Provider: PubsubClient,
+    M::Error: 'static,
+{
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(MEV_SHARE_UNI_ARB, mev_share_uni_arb);
+        registry.register(AUTO_SWEEP, auto_sweep);
+        registry.register(COMET_LIQUIDATOR, comet_liquidator);
+        registry
+    }
+}
+
+/// Names of the strategies to run: the ones selected with `--strategy`, or else the arb
+/// strategy, along with profit sweeping if a sweep threshold is set.
+pub fn selected(args: &Args) -> Vec<String> {
+    if !args.strategies.is_empty() {
+        return args.strategies.clone();
+    }
+    let mut strategies = vec![MEV_SHARE_UNI_ARB.to_string()];
+    if args.sweep_threshold_wei.is_some() {
+        strategies.push(AUTO_SWEEP.to_string());
+    }
+    strategies
+}
+
+/// Read the parameters of each strategy, by name, from the JSON file at `path`.
+pub fn load_params(path: &Path) -> Result<HashMap<String, Value>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("failed to open strategy params {}", path.display()))?;
+    serde_json::from_reader(file)
+        .with_context(|| format!("failed to parse strategy params {}", path.display()))
+}
+
+/// Build the MEV-share arb strategy. Its parameters are a
+/// [StrategyConfig](mev_share_uni_arb::config::StrategyConfig), which `--config-path`
+/// overrides.
+fn mev_share_uni_arb<'a, M: Middleware + 'static>(
+    context: &'a StrategyContext<'a, M>,
+    params: Option<Value>,
+    engine: &'a mut Engine<Event, Action>,
+) -> LocalBoxFuture<'a, Result<()>> {
+    async move {
+        let args = context.args;
+        let mut strategy = MevShareUniArb::new(
+            context.client.clone(),
+            context.wallet.clone(),
+            args.arb_contract_address,
+        )
+        .with_signers(
+            std::iter::once(context.wallet.clone())
+                .chain(context.arb_wallets.iter().cloned())
+                .collect(),
+        )
+        .with_min_wallet_balance(U256::from(args.min_wallet_balance_wei))
+        .with_min_contract_weth(U256::from(args.min_contract_weth_wei))
+        .with_gas_estimator(
+            GasEstimator::new(context.client.clone())
+                .with_safety_margin_bps(args.gas_safety_margin_bps)
+                .with_refresh_interval(Duration::from_secs(args.gas_refresh_secs)),
+        );
+        let mut privacy = SubmissionPrivacy::default();
+        if args.hash_only_hints {
+            privacy = privacy.with_hash_only_hints();
+        }
+        if !args.trusted_builders.is_empty() {
+            privacy =
+                privacy.with_trusted_builders(args.trusted_builders.iter().map(String::as_str));
+        }
+        strategy = strategy
+            .with_submission_privacy(privacy)
+            .with_chain_state(context.chain_state.clone());
+        if let Some(reserves) = &context.reserves {
+            strategy = strategy.with_reserve_tracker(reserves.clone());
+        }
+        if let Some(ttl) = args.arb_simulation_ttl_ms {
+            strategy = strategy.with_arb_simulation(Duration::from_millis(ttl));
+        }
+        if let Some(url) = &args.pool_store {
+            strategy = strategy.with_pool_store(pool_store::open(url).await?);
+        }
+        if let Some(max_hops) = args.route_search_max_hops {
+            let mut search = RouteSearch::new(max_hops);
+            if !args.route_tokens.is_empty() {
+                search = search.with_tokens(args.route_tokens.iter().copied());
+            }
+            strategy = strategy.with_route_search(search);
+        }
+        if let Some(path) = &args.journal_path {
+            strategy = strategy.with_journal(Arc::new(FileJournal::open(path)?));
+        }
+        if let Some(params) = params {
+            strategy.reconfigure(serde_json::from_value(params)?)?;
+        }
+        if let Some(path) = &args.config_path {
+            let config_collector = ConfigCollector::new(path);
+            strategy.reconfigure(config_collector.load()?)?;
+            let config_collector =
+                CollectorMap::new(Box::new(config_collector), Event::ConfigUpdated);
+            engine.add_collector(Box::new(config_collector));
+        }
+        let concurrency = strategy.concurrency();
+        engine.add_concurrent_strategy(Box::new(strategy), concurrency);
+        Ok(())
+    }
+    .boxed_local()
+}
+
+/// Parameters of the `auto-sweep` strategy, defaulting to the `--sweep-*` options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SweepParams {
+    threshold_wei: Option<u128>,
+    cooldown_secs: Option<u64>,
+    max_gas_price_wei: Option<u128>,
+}
+
+/// Build the strategy sweeping the WETH of the arb contract to its owner, along with the
+/// executor sending the sweeps.
+fn auto_sweep<'a, M: Middleware + 'static>(
+    context: &'a StrategyContext<'a, M>,
+    params: Option<Value>,
+    engine: &'a mut Engine<Event, Action>,
+) -> LocalBoxFuture<'a, Result<()>> {
+    async move {
+        let args = context.args;
+        let params: SweepParams = match params {
+            Some(params) => serde_json::from_value(params)?,
+            None => SweepParams::default(),
+        };
+        let threshold = params
+            .threshold_wei
+            .or(args.sweep_threshold_wei)
+            .ok_or_else(|| anyhow!("no sweep threshold set"))?;
+        let cooldown = params.cooldown_secs.unwrap_or(args.sweep_cooldown_secs);
+        let auto_sweep =
+            AutoSweep::new(U256::from(threshold)).with_cooldown(Duration::from_secs(cooldown));
+        engine.add_strategy(Box::new(auto_sweep));
+
+        let sweep_executor = SweepExecutor::new(
+            context.client.clone(),
+            context.wallet.clone(),
+            MevshareExecutor::new(context.fb_signer.clone(), Chain::Mainnet)
+                .with_client(context.client.clone()),
+        );
+        let sweep_executor = match params.max_gas_price_wei.or(args.sweep_max_gas_price_wei) {
+            Some(max_gas_price) => sweep_executor.with_max_gas_price(U256::from(max_gas_price)),
+            None => sweep_executor,
+        };
+        let sweep_executor = ExecutorMap::new(Box::new(sweep_executor), |action| match action {
+            Action::Sweep(request) => Some(request),
+            Action::SubmitBundles(_) | Action::SubmitFlashbotsBundle(_) => None,
+        });
+        engine.add_executor(Box::new(sweep_executor));
+        Ok(())
+    }
+    .boxed_local()
+}
+
+/// Parameters of the `comet-liquidator` strategy, defaulting to the mainnet cUSDCv3
+/// market.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct CometParams {
+    comet_address: Address,
+    deployment_block: u64,
+    min_profit: u128,
+    max_base_amount: Option<u128>,
+    collateral_preference: Vec<Address>,
+    priority_fee_wei: u128,
+    /// Chainlink aggregators whose price updates trigger liquidity checks. Updates of
+    /// every aggregator do if none are listed.
+    price_aggregators: Vec<Address>,
+}
+
+impl Default for CometParams {
+    fn default() -> Self {
+        Self {
+            comet_address: *COMET_USDC_ADDRESS,
+            deployment_block: COMET_USDC_DEPLOYMENT_BLOCK,
+            min_profit: 0,
+            max_base_amount: None,
+            collateral_preference: vec![],
+            priority_fee_wei: DEFAULT_LIQUIDATION_PRIORITY_FEE_WEI,
+            price_aggregators: vec![],
+        }
+    }
+}
+
+/// Build the strategy liquidating the underwater accounts of a Comet market, along with
+/// the collectors of its market logs and price updates, and the executor sending its
+/// bundles to the Flashbots relay.
+fn comet_liquidator<'a, M>(
+    context: &'a StrategyContext<'a, M>,
+    params: Option<Value>,
+    engine: &'a mut Engine<Event, Action>,
+) -> LocalBoxFuture<'a, Result<()>>
+where
+    M: Middleware + 'static,
+    M::Provider: PubsubClient,
+    M::Error: 'static,
+{
+    async move {
+        let params: CometParams = match params {
+            Some(params) => serde_json::from_value(params)?,
+            None => CometParams::default(),
+        };
+        let config = CometConfig {
+            comet_address: params.comet_address,
+            deployment_block: params.deployment_block,
+            min_profit: U256::from(params.min_profit),
+            max_base_amount: params.max_base_amount.map_or(U256::MAX, U256::from),
+            collateral_preference: params.collateral_preference,
+            priority_fee: U256::from(params.priority_fee_wei),
+        };
+        let strategy = CometLiquidator::new(context.client.clone(), context.wallet.clone(), config);
+
+        let comet_collector =
+            LogCollector::new(context.client.clone(), strategy.comet_log_filter());
+        let comet_collector = CollectorMap::new(Box::new(comet_collector), Event::CometLog);
+        engine.add_collector(Box::new(comet_collector));
+        let price_collector = LogCollector::new(
+            context.client.clone(),
+            price_update_filter(params.price_aggregators),
+        );
+        let price_collector = CollectorMap::new(Box::new(price_collector), Event::PriceUpdate);
+        engine.add_collector(Box::new(price_collector));
+
+        let strategy = StrategyMap::new(
+            Box::new(strategy),
+            |event: Event| match event {
+                Event::CometLog(log) => Some(CometEvent::CometLog(log)),
+                Event::PriceUpdate(log) => Some(CometEvent::PriceUpdate(log)),
+                _ => None,
+            },
+            |action: CometAction| match action {
+                CometAction::SubmitBundle(bundle) => Action::SubmitFlashbotsBundle(bundle),
+            },
+        );
+        engine.add_strategy(Box::new(strategy));
+
+        let flashbots_executor = FlashbotsExecutor::new(
+            context.client.clone(),
+            context.wallet.clone(),
+            context.fb_signer.clone(),
+            Url::parse(FLASHBOTS_RELAY_URL)?,
+            "flashbots",
+        );
+        let flashbots_executor =
+            ExecutorMap::new(Box::new(flashbots_executor), |action| match action {
+                Action::SubmitFlashbotsBundle(bundle) => Some(bundle),
+                Action::SubmitBundles(_) | Action::Sweep(_) => None,
+            });
+        engine.add_executor(Box::new(flashbots_executor));
+        Ok(())
+    }
+    .boxed_local()
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use artemis_core::admin::LogLevelHandler;
+use tracing::Level;
+use tracing_subscriber::{filter, fmt, prelude::*, reload};
+
+/// Install the global tracing subscriber. Logs are printed as JSON lines carrying the
+/// current span fields (e.g. `event_id`) when `json` is set, and spans are exported to
+/// an OTLP collector such as Jaeger or Tempo when an endpoint is given.
+///
+/// Returns a handler replacing the log filter at runtime, given directives such as
+/// `artemis_core=debug,info`.
+pub fn init(json: bool, otlp_endpoint: Option<&str>) -> Result<LogLevelHandler> {
+    let filter = filter::Targets::new()
+        .with_target("mev_share_uni_arb", Level::INFO)
+        .with_target("artemis_core", Level::INFO);
+    let (filter, handle) = reload::Layer::new(filter);
+    let (json_layer, text_layer) = match json {
+        true => (
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            ),
+            None,
+        ),
+        false => (None, Some(fmt::layer())),
+    };
+
+    tracing_subscriber::registry()
+        .with(json_layer)
+        .with(text_layer)
+        .with(otlp_layer(otlp_endpoint)?)
+        .with(filter)
+        .init();
+    Ok(Box::new(move |directives| {
+        let filter: filter::Targets = directives.parse()?;
+        handle.reload(filter)?;
+        Ok(())
+    }))
+}
+
+/// Returns a layer exporting spans over OTLP/gRPC to `endpoint`, if given.
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>(endpoint: Option<&str>) -> Result<Option<impl tracing_subscriber::Layer<S>>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{runtime, trace, Resource};
+
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                "artemis",
+            )])),
+        )
+        .install_batch(runtime::Tokio)?;
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// Without the `otlp` feature, asking for an export endpoint is an error.
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer(endpoint: Option<&str>) -> Result<Option<tracing_subscriber::layer::Identity>> {
+    match endpoint {
+        Some(_) => Err(anyhow::anyhow!(
+            "--otlp-endpoint requires building with the `otlp` feature"
+        )),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,11 @@
+fn main() {
+    // Generate the gRPC service of the GrpcCollector, with a vendored protoc so builds
+    // don't depend on one being installed.
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/events.proto").expect("failed to compile protos");
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+    time::timeout,
+};
+use tracing::{debug, info};
+
+use crate::{
+    health::{CollectorReport, EngineHealth, ExecutorReport},
+    risk::{KillSwitch, KillSwitchReport},
+    utilities::http::{read_request, write_json_response},
+};
+
+/// Time allowed to read a request and write the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Changes the log filter of the process, given directives such as
+/// `artemis_core=debug,info`. Installed by the binary, which owns the subscriber.
+pub type LogLevelHandler = Box<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+/// Runtime controls of a single strategy, checked by the engine between events.
+#[derive(Debug, Default)]
+pub struct StrategyControl {
+    paused: AtomicBool,
+    resync: Notify,
+}
+
+impl StrategyControl {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pause or resume the strategy. Events received while paused are dropped.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Ask the engine to sync the strategy's state again before its next event.
+    pub fn request_resync(&self) {
+        self.resync.notify_one();
+    }
+
+    /// Resolves once a resync is requested.
+    pub async fn resync_requested(&self) {
+        self.resync.notified().await
+    }
+}
+
+/// Runtime controls of the [Engine](crate::engine::Engine), exposed by the
+/// [AdminServer](AdminServer).
+pub struct EngineControl {
+    health: Arc<EngineHealth>,
+    strategies: Mutex<Vec<Arc<StrategyControl>>>,
+    kill_switches: Mutex<Vec<Arc<KillSwitch>>>,
+}
+
+/// The components of a running engine, in the order they were added.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Components {
+    pub collectors: Vec<CollectorReport>,
+    pub strategies: Vec<StrategyReport>,
+    pub executors: Vec<ExecutorReport>,
+    pub kill_switches: Vec<KillSwitchReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StrategyReport {
+    pub paused: bool,
+}
+
+impl EngineControl {
+    pub fn new(health: Arc<EngineHealth>) -> Self {
+        Self {
+            health,
+            strategies: Mutex::new(vec![]),
+            kill_switches: Mutex::new(vec![]),
+        }
+    }
+
+    /// Track a new strategy, in the order strategies were added to the engine.
+    pub fn add_strategy(&self) -> Arc<StrategyControl> {
+        let strategy = Arc::new(StrategyControl::default());
+        self.strategies.lock().unwrap().push(strategy.clone());
+        strategy
+    }
+
+    /// Returns the controls of the strategy at `index`, once the engine is running.
+    pub fn strategy(&self, index: usize) -> Option<Arc<StrategyControl>> {
+        self.strategies.lock().unwrap().get(index).cloned()
+    }
+
+    /// Create the [kill switch](KillSwitch) of a risk managed strategy, re-armed with
+    /// `admin_rearmStrategy [name]`. Replaces the kill switch named `name`, if any.
+    pub fn add_kill_switch(&self, name: impl Into<String>) -> Arc<KillSwitch> {
+        let kill_switch = Arc::new(KillSwitch::new(name));
+        let mut kill_switches = self.kill_switches.lock().unwrap();
+        kill_switches.retain(|existing| existing.name() != kill_switch.name());
+        kill_switches.push(kill_switch.clone());
+        kill_switch
+    }
+
+    /// Returns the kill switch named `name`.
+    pub fn kill_switch(&self, name: &str) -> Option<Arc<KillSwitch>> {
+        self.kill_switches
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|kill_switch| kill_switch.name() == name)
+            .cloned()
+    }
+
+    pub fn components(&self) -> Components {
+        let health = self.health.report();
+        let strategies = self
+            .strategies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|strategy| StrategyReport {
+                paused: strategy.is_paused(),
+            })
+            .collect();
+        let kill_switches = self
+            .kill_switches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|kill_switch| kill_switch.report())
+            .collect();
+        Components {
+            collectors: health.collectors,
+            strategies,
+            executors: health.executors,
+            kill_switches,
+        }
+    }
+}
+
+/// A JSON-RPC 2.0 server over HTTP for inspecting and controlling a running engine.
+/// It has no authentication, so bind it to a local address. Supported methods:
+///
+/// - `admin_listComponents`: the collectors, strategies and executors, and their status.
+/// - `admin_pauseStrategy [index]` / `admin_resumeStrategy [index]`: stop or restart
+///   passing events to a strategy.
+/// - `admin_resyncStrategy [index]`: sync a strategy's state again.
+/// - `admin_rearmStrategy [name]`: restart a strategy stopped by its
+///   [kill switch](KillSwitch) after breaching a risk limit.
+/// - `admin_setLogLevel [directives]`: change the log filter, if a handler is set.
+pub struct AdminServer {
+    listener: TcpListener,
+    control: Arc<EngineControl>,
+    log_level: Option<Arc<LogLevelHandler>>,
+}
+
+impl AdminServer {
+    pub async fn bind(addr: SocketAddr, control: Arc<EngineControl>) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener,
+            control,
+            log_level: None,
+        })
+    }
+
+    /// Handle `admin_setLogLevel` with `handler`.
+    pub fn with_log_level_handler(mut self, handler: LogLevelHandler) -> Self {
+        self.log_level = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve requests until the task is dropped.
+    pub async fn serve(self) {
+        info!("serving admin rpc on {:?}", self.listener.local_addr());
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    debug!("error accepting admin connection: {}", e);
+                    continue;
+                }
+            };
+            let control = self.control.clone();
+            let log_level = self.log_level.clone();
+            tokio::spawn(async move {
+                let response = handle(stream, &control, log_level.as_deref());
+                match timeout(REQUEST_TIMEOUT, response).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => debug!("error serving admin request: {}", e),
+                    Err(_) => debug!("admin request timed out"),
+                }
+            });
+        }
+    }
+}
+
+/// Respond to a single request.
+async fn handle(
+    mut stream: TcpStream,
+    control: &EngineControl,
+    log_level: Option<&LogLevelHandler>,
+) -> Result<()> {
+    let request = read_request(&mut stream).await?;
+    if request.method != "POST" {
+        return write_json_response(&mut stream, "405 Method Not Allowed", "{}").await;
+    }
+    let response = match serde_json::from_slice::<Value>(&request.body) {
+        Ok(call) => dispatch(control, log_level, &call),
+        Err(e) => error_response(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+    write_json_response(&mut stream, "200 OK", &response.to_string()).await
+}
+
+/// Run a JSON-RPC call, returning the response.
+fn dispatch(control: &EngineControl, log_level: Option<&LogLevelHandler>, call: &Value) -> Value {
+    let id = call.get("id").cloned().unwrap_or(Value::Null);
+    let method = call
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let param = call.get("params").and_then(|params| params.get(0));
+
+    let strategy = || {
+        param
+            .and_then(Value::as_u64)
+            .and_then(|index| control.strategy(index as usize))
+            .ok_or((
+                INVALID_PARAMS,
+                "expected the index of a strategy".to_string(),
+            ))
+    };
+    let result = match method {
+        "admin_listComponents" => Ok(json!(control.components())),
+        "admin_pauseStrategy" => strategy().map(|strategy| {
+            strategy.set_paused(true);
+            json!(true)
+        }),
+        "admin_resumeStrategy" => strategy().map(|strategy| {
+            strategy.set_paused(false);
+            json!(true)
+        }),
+        "admin_resyncStrategy" => strategy().map(|strategy| {
+            strategy.request_resync();
+            json!(true)
+        }),
+        "admin_rearmStrategy" => param
+            .and_then(Value::as_str)
+            .and_then(|name| control.kill_switch(name))
+            .ok_or((
+                INVALID_PARAMS,
+                "expected the name of a kill switch".to_string(),
+            ))
+            .map(|kill_switch| {
+                kill_switch.rearm();
+                json!(true)
+            }),
+        "admin_setLogLevel" => match (log_level, param.and_then(Value::as_str)) {
+            (None, _) => Err((INTERNAL_ERROR, "log level can't be changed".to_string())),
+            (Some(_), None) => Err((INVALID_PARAMS, "expected log directives".to_string())),
+            (Some(handler), Some(directives)) => handler(directives)
+                .map(|_| json!(true))
+                .map_err(|e| (INVALID_PARAMS, e.to_string())),
+        },
+        _ => Err((METHOD_NOT_FOUND, format!("method {:?} not found", method))),
+    };
+
+    match result {
+        Ok(result) => {
+            info!(method, "handled admin call");
+            json!({ "jsonrpc": "2.0", "id": id, "result": result })
+        }
+        Err((code, message)) => error_response(id, code, message),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskBreach;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn call(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+    }
+
+    #[test]
+    fn pauses_and_resumes_strategies() {
+        let control = EngineControl::new(Arc::new(EngineHealth::new()));
+        let strategy = control.add_strategy();
+
+        let response = dispatch(&control, None, &call("admin_pauseStrategy", json!([0])));
+        assert_eq!(response["result"], json!(true));
+        assert!(strategy.is_paused());
+        assert_eq!(
+            control.components().strategies,
+            vec![StrategyReport { paused: true }]
+        );
+
+        dispatch(&control, None, &call("admin_resumeStrategy", json!([0])));
+        assert!(!strategy.is_paused());
+
+        let response = dispatch(&control, None, &call("admin_pauseStrategy", json!([1])));
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+        let response = dispatch(&control, None, &call("admin_unknown", json!([])));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+    }
+
+    #[test]
+    fn rearms_kill_switches() {
+        let control = EngineControl::new(Arc::new(EngineHealth::new()));
+        let kill_switch = control.add_kill_switch("arb");
+        kill_switch.trip(RiskBreach::ConsecutiveLosses {
+            losses: 3,
+            limit: 2,
+        });
+        assert!(control.components().kill_switches[0].breach.is_some());
+
+        let response = dispatch(&control, None, &call("admin_rearmStrategy", json!(["arb"])));
+        assert_eq!(response["result"], json!(true));
+        assert!(!kill_switch.is_tripped());
+
+        let response = dispatch(
+            &control,
+            None,
+            &call("admin_rearmStrategy", json!(["sweep"])),
+        );
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+    }
+
+    #[test]
+    fn sets_log_level_through_handler() {
+        let control = EngineControl::new(Arc::new(EngineHealth::new()));
+        let set = Arc::new(Mutex::new(String::new()));
+        let handler: LogLevelHandler = {
+            let set = set.clone();
+            Box::new(move |directives| {
+                *set.lock().unwrap() = directives.to_string();
+                Ok(())
+            })
+        };
+
+        let response = dispatch(
+            &control,
+            Some(&handler),
+            &call("admin_setLogLevel", json!(["artemis_core=debug"])),
+        );
+        assert_eq!(response["result"], json!(true));
+        assert_eq!(*set.lock().unwrap(), "artemis_core=debug");
+
+        let response = dispatch(&control, None, &call("admin_setLogLevel", json!(["info"])));
+        assert_eq!(response["error"]["code"], json!(INTERNAL_ERROR));
+    }
+
+    #[tokio::test]
+    async fn serves_json_rpc_over_http() {
+        let control = Arc::new(EngineControl::new(Arc::new(EngineHealth::new())));
+        let strategy = control.add_strategy();
+        let server = AdminServer::bind("127.0.0.1:0".parse().unwrap(), control)
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let body = call("admin_resyncStrategy", json!([0])).to_string();
+        let request = format!(
+            "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with(r#""result":true}"#));
+        timeout(Duration::from_secs(1), strategy.resync_requested())
+            .await
+            .unwrap();
+    }
+}
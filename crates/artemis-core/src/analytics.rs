@@ -0,0 +1,204 @@
+//! Benchmarks of our inclusion against competing searchers, built from the
+//! [fills](CompetitorFill) of watched pools a
+//! [BlockTraceCollector](crate::collectors::block_trace_collector::BlockTraceCollector)
+//! finds in landed blocks.
+
+use std::collections::{HashMap, HashSet};
+
+use ethers::types::{Address, U256, U64};
+use serde::Serialize;
+
+use crate::collectors::block_trace_collector::CompetitorFill;
+
+/// Fills landed by a searcher.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SearcherStats {
+    /// Number of fills.
+    pub fills: u64,
+    /// Number of fills which backran the previous transaction of their block.
+    pub backruns: u64,
+    /// Ether paid to coinbases by the fills.
+    pub coinbase_payments: U256,
+    /// Gas used by the fills.
+    pub gas_used: U256,
+    /// The last block the searcher landed a fill in.
+    pub last_block: U64,
+}
+
+/// Fills of a watched pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    /// Number of fills which swapped through the pool.
+    pub fills: u64,
+    /// Number of those fills which were ours.
+    pub ours: u64,
+}
+
+/// Summary of an [InclusionBenchmark](InclusionBenchmark).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    /// Number of fills recorded.
+    pub fills: u64,
+    /// Number of those fills which were ours.
+    pub our_fills: u64,
+    /// Share of fills which were ours, or `None` if no fill was recorded.
+    pub inclusion_rate: Option<f64>,
+    /// The competitors with the most fills, and their stats.
+    pub top_competitors: Vec<(Address, SearcherStats)>,
+    /// The pools with the most fills, and their stats.
+    pub top_pools: Vec<(Address, PoolStats)>,
+}
+
+/// Benchmarks how often we land fills of watched pools compared to competitors, per
+/// searcher and per pool.
+#[derive(Debug, Clone, Default)]
+pub struct InclusionBenchmark {
+    /// Our senders and contracts.
+    ours: HashSet<Address>,
+    ours_stats: SearcherStats,
+    competitors: HashMap<Address, SearcherStats>,
+    pools: HashMap<Address, PoolStats>,
+}
+
+impl InclusionBenchmark {
+    /// Create a benchmark counting fills sent by, or calling, any of `ours` as ours.
+    pub fn new(ours: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            ours: ours.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether `fill` was ours.
+    pub fn is_ours(&self, fill: &CompetitorFill) -> bool {
+        self.ours.contains(&fill.from) || fill.to.is_some_and(|to| self.ours.contains(&to))
+    }
+
+    /// Record a fill.
+    pub fn record(&mut self, fill: &CompetitorFill) {
+        let ours = self.is_ours(fill);
+        let stats = if ours {
+            &mut self.ours_stats
+        } else {
+            self.competitors.entry(fill.searcher()).or_default()
+        };
+        stats.fills += 1;
+        stats.backruns += u64::from(fill.backrun_of.is_some());
+        stats.coinbase_payments = stats
+            .coinbase_payments
+            .saturating_add(fill.coinbase_payment);
+        stats.gas_used = stats.gas_used.saturating_add(fill.gas_used);
+        stats.last_block = stats.last_block.max(fill.block);
+
+        for pool in &fill.pools {
+            let stats = self.pools.entry(*pool).or_default();
+            stats.fills += 1;
+            stats.ours += u64::from(ours);
+        }
+    }
+
+    /// Stats of our own fills.
+    pub fn ours(&self) -> &SearcherStats {
+        &self.ours_stats
+    }
+
+    /// Stats of the fills of the competitor `searcher`.
+    pub fn competitor(&self, searcher: Address) -> Option<&SearcherStats> {
+        self.competitors.get(&searcher)
+    }
+
+    /// Share of fills which were ours, or `None` if no fill was recorded.
+    pub fn inclusion_rate(&self) -> Option<f64> {
+        let competitor_fills: u64 = self.competitors.values().map(|stats| stats.fills).sum();
+        let fills = self.ours_stats.fills + competitor_fills;
+        (fills > 0).then(|| self.ours_stats.fills as f64 / fills as f64)
+    }
+
+    /// Summarize the benchmark, with the `top` competitors and pools by fills.
+    pub fn report(&self, top: usize) -> BenchmarkReport {
+        let top_competitors = top_by(&self.competitors, top, |stats| stats.fills);
+        let top_pools = top_by(&self.pools, top, |stats| stats.fills);
+        let competitor_fills: u64 = self.competitors.values().map(|stats| stats.fills).sum();
+        BenchmarkReport {
+            fills: self.ours_stats.fills + competitor_fills,
+            our_fills: self.ours_stats.fills,
+            inclusion_rate: self.inclusion_rate(),
+            top_competitors,
+            top_pools,
+        }
+    }
+}
+
+/// Returns the `top` entries of `stats` with the most `fills`, ties broken by address.
+fn top_by<T: Clone>(
+    stats: &HashMap<Address, T>,
+    top: usize,
+    fills: impl Fn(&T) -> u64,
+) -> Vec<(Address, T)> {
+    let mut entries: Vec<_> = stats
+        .iter()
+        .map(|(address, stats)| (*address, stats.clone()))
+        .collect();
+    entries.sort_by_key(|(address, stats)| (std::cmp::Reverse(fills(stats)), *address));
+    entries.truncate(top);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    fn fill(block: u64, from: Address, to: Address, pools: Vec<Address>) -> CompetitorFill {
+        CompetitorFill {
+            block: U64::from(block),
+            tx_hash: H256::random(),
+            index: 0,
+            from,
+            to: Some(to),
+            pools,
+            profit: vec![],
+            coinbase_payment: U256::from(100),
+            gas_used: U256::from(150_000),
+            gas_price: U256::zero(),
+            backrun_of: None,
+        }
+    }
+
+    #[test]
+    fn benchmarks_our_fills_against_competitors() {
+        let address = Address::from_low_u64_be;
+        let (our_bot, our_contract) = (address(1), address(2));
+        let (rival_a, rival_b) = (address(3), address(4));
+        let (pool_a, pool_b) = (address(10), address(11));
+
+        let mut benchmark = InclusionBenchmark::new([our_bot, our_contract]);
+        assert_eq!(benchmark.inclusion_rate(), None);
+
+        benchmark.record(&fill(1, our_bot, our_contract, vec![pool_a]));
+        // Competitors are attributed by contract, whichever address sends their txs.
+        benchmark.record(&fill(2, address(20), rival_a, vec![pool_a, pool_b]));
+        benchmark.record(&CompetitorFill {
+            backrun_of: Some(H256::zero()),
+            ..fill(3, address(21), rival_a, vec![pool_b])
+        });
+        benchmark.record(&fill(4, address(22), rival_b, vec![pool_a]));
+
+        assert_eq!(benchmark.ours().fills, 1);
+        assert_eq!(benchmark.inclusion_rate(), Some(0.25));
+        let rival = benchmark.competitor(rival_a).unwrap();
+        assert_eq!(rival.fills, 2);
+        assert_eq!(rival.backruns, 1);
+        assert_eq!(rival.coinbase_payments, U256::from(200));
+        assert_eq!(rival.last_block, U64::from(3));
+
+        let report = benchmark.report(1);
+        assert_eq!((report.fills, report.our_fills), (4, 1));
+        assert_eq!(report.top_competitors.len(), 1);
+        assert_eq!(report.top_competitors[0].0, rival_a);
+        assert_eq!(
+            report.top_pools,
+            vec![(pool_a, PoolStats { fills: 3, ours: 1 })]
+        );
+    }
+}
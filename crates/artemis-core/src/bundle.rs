@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Result};
+use ethers::{
+    types::{Bytes, H256, U64},
+    utils::{keccak256, rlp::Rlp},
+};
+use matchmaker::types::{BundleTx, ProtocolVersion};
+
+/// Type of EIP-4844 blob transactions.
+const BLOB_TX_TYPE: u8 = 3;
+
+/// Hash of the signed transaction `raw`. Blob transactions are sent in their network
+/// form, which wraps the signed transaction with its blobs, commitments and proofs, so
+/// only the wrapped transaction is hashed.
+pub fn signed_tx_hash(raw: &[u8]) -> H256 {
+    if raw.first() == Some(&BLOB_TX_TYPE) {
+        // The network form is a list whose first item is the transaction, while the
+        // transaction itself starts with its chain id.
+        if let Ok(tx) = Rlp::new(&raw[1..]).at(0) {
+            if tx.is_list() {
+                let mut payload = vec![BLOB_TX_TYPE];
+                payload.extend_from_slice(tx.as_raw());
+                return H256::from(keccak256(payload));
+            }
+        }
+    }
+    H256::from(keccak256(raw))
+}
+
+/// A transaction of a [UnifiedBundle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleTransaction {
+    /// A pending transaction shared by the MEV-share matchmaker, such as the one a
+    /// bundle backruns. Only MEV-share bundles can include them.
+    Pending(H256),
+    /// A signed transaction.
+    Signed {
+        /// Bytes of the signed transaction.
+        raw: Bytes,
+        /// If true, the transaction can revert without the bundle being dropped.
+        can_revert: bool,
+    },
+}
+
+impl BundleTransaction {
+    /// Hash of the transaction.
+    pub fn hash(&self) -> H256 {
+        match self {
+            BundleTransaction::Pending(hash) => *hash,
+            BundleTransaction::Signed { raw, .. } => signed_tx_hash(raw),
+        }
+    }
+}
+
+/// A bundle of signed transactions which can be sent both to Flashbots relays and to
+/// the MEV-share matchmaker, so a strategy can fan a single action out to both kinds of
+/// executor. Converted with [to_flashbots](Self::to_flashbots) and
+/// [to_mev_share](Self::to_mev_share).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnifiedBundle {
+    /// The transactions of the bundle, in order.
+    pub transactions: Vec<BundleTransaction>,
+    /// The first block the bundle is valid for.
+    pub block: U64,
+    /// The last block the bundle is valid for, the first one if unset.
+    pub max_block: Option<U64>,
+}
+
+impl UnifiedBundle {
+    /// Create an empty bundle for `block`.
+    pub fn new(block: U64) -> Self {
+        Self {
+            transactions: vec![],
+            block,
+            max_block: None,
+        }
+    }
+
+    /// Backrun the pending transaction `hash`.
+    pub fn with_pending(mut self, hash: H256) -> Self {
+        self.transactions.push(BundleTransaction::Pending(hash));
+        self
+    }
+
+    /// Add the signed transaction `raw`, which must not revert.
+    pub fn with_signed(mut self, raw: Bytes) -> Self {
+        self.transactions.push(BundleTransaction::Signed {
+            raw,
+            can_revert: false,
+        });
+        self
+    }
+
+    /// Add the signed transaction `raw`, which may revert.
+    pub fn with_revertible(mut self, raw: Bytes) -> Self {
+        self.transactions.push(BundleTransaction::Signed {
+            raw,
+            can_revert: true,
+        });
+        self
+    }
+
+    /// Keep the bundle valid until `max_block`, inclusive.
+    pub fn with_max_block(mut self, max_block: U64) -> Self {
+        self.max_block = Some(max_block);
+        self
+    }
+
+    /// The last block the bundle is valid for.
+    pub fn last_block(&self) -> U64 {
+        self.max_block.unwrap_or(self.block).max(self.block)
+    }
+
+    /// Hashes of the transactions of the bundle, including the ones it backruns.
+    pub fn tx_hashes(&self) -> Vec<H256> {
+        self.transactions.iter().map(|tx| tx.hash()).collect()
+    }
+
+    /// Whether the bundle includes pending transactions, which can only be sent to the
+    /// MEV-share matchmaker.
+    pub fn has_pending(&self) -> bool {
+        self.transactions
+            .iter()
+            .any(|tx| matches!(tx, BundleTransaction::Pending(_)))
+    }
+
+    /// Convert to a bundle for the MEV-share matchmaker.
+    pub fn to_mev_share(&self) -> matchmaker::types::BundleRequest {
+        let body = self
+            .transactions
+            .iter()
+            .map(|tx| match tx {
+                BundleTransaction::Pending(hash) => BundleTx::TxHash { hash: *hash },
+                BundleTransaction::Signed { raw, can_revert } => BundleTx::Tx {
+                    tx: raw.clone(),
+                    can_revert: *can_revert,
+                },
+            })
+            .collect();
+        matchmaker::types::BundleRequest::new(
+            self.block,
+            Some(self.last_block()),
+            ProtocolVersion::Beta1,
+            body,
+        )
+    }
+
+    /// Convert to a bundle for Flashbots relays, targeting the first block of the
+    /// bundle. Fails if the bundle includes pending transactions.
+    pub fn to_flashbots(&self) -> Result<ethers_flashbots::BundleRequest> {
+        let mut bundle = ethers_flashbots::BundleRequest::new();
+        for tx in &self.transactions {
+            match tx {
+                BundleTransaction::Pending(hash) => {
+                    return Err(anyhow!(
+                        "bundle includes pending tx {:?}, which only MEV-share bundles can",
+                        hash
+                    ))
+                }
+                BundleTransaction::Signed {
+                    raw,
+                    can_revert: false,
+                } => bundle.add_transaction(raw.clone()),
+                BundleTransaction::Signed {
+                    raw,
+                    can_revert: true,
+                } => bundle.add_revertible_transaction(raw.clone()),
+            }
+        }
+        Ok(bundle.set_block(self.block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::rlp::RlpStream;
+
+    #[test]
+    fn converts_to_mev_share_and_flashbots_bundles() {
+        let raw = Bytes::from(vec![1, 2, 3]);
+        let bundle = UnifiedBundle::new(U64::from(100))
+            .with_signed(raw.clone())
+            .with_revertible(raw.clone())
+            .with_max_block(U64::from(102));
+        assert!(!bundle.has_pending());
+        assert_eq!(bundle.to_flashbots().unwrap().transactions().len(), 2);
+
+        let bundle = bundle.with_pending(H256::repeat_byte(1));
+        assert!(bundle.to_flashbots().is_err());
+        assert_eq!(bundle.tx_hashes()[2], H256::repeat_byte(1));
+
+        let request = bundle.to_mev_share();
+        assert_eq!(request.inclusion.block, U64::from(100));
+        assert_eq!(request.inclusion.max_block, Some(U64::from(102)));
+        assert!(matches!(
+            request.body[1],
+            BundleTx::Tx {
+                can_revert: true,
+                ..
+            }
+        ));
+        assert!(matches!(request.body[2], BundleTx::TxHash { .. }));
+    }
+
+    #[test]
+    fn hashes_blob_txs_without_their_sidecar() {
+        let mut tx = RlpStream::new_list(2);
+        tx.append(&1u64).append(&H256::repeat_byte(2));
+        let tx = tx.out();
+        let mut canonical = vec![BLOB_TX_TYPE];
+        canonical.extend_from_slice(&tx);
+        let hash = H256::from(keccak256(&canonical));
+        assert_eq!(signed_tx_hash(&canonical), hash);
+
+        let mut network = RlpStream::new_list(4);
+        network.append_raw(&tx, 1);
+        for item in [vec![7u8; 64], vec![8; 48], vec![9; 48]] {
+            network.begin_list(1).append(&item);
+        }
+        let mut raw = vec![BLOB_TX_TYPE];
+        raw.extend_from_slice(&network.out());
+        assert_eq!(signed_tx_hash(&raw), hash);
+
+        let bundle = UnifiedBundle::new(U64::from(1)).with_signed(raw.into());
+        assert_eq!(bundle.tx_hashes(), vec![hash]);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::collectors::block_collector::NewBlock;
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use crate::utilities::alloy_compat::ToEthers;
+use alloy::providers::Provider;
+use async_trait::async_trait;
+use ethers::types::U64;
+use std::sync::Arc;
+use tokio_stream::StreamExt;
+
+/// A collector that listens for new blocks through an alloy provider, and generates a
+/// stream of the same [events](NewBlock) as the
+/// [BlockCollector](super::block_collector::BlockCollector).
+pub struct AlloyBlockCollector<P> {
+    provider: Arc<P>,
+}
+
+impl<P> AlloyBlockCollector<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [AlloyBlockCollector](AlloyBlockCollector). This implementation subscribes to new block
+/// headers, so the provider must use a pubsub transport, e.g. a websocket.
+#[async_trait]
+impl<P: Provider> Collector<NewBlock> for AlloyBlockCollector<P> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
+        let subscription = self.provider.subscribe_blocks().await?;
+        let stream = subscription.into_stream().map(|header| NewBlock {
+            hash: header.hash.to_ethers(),
+            number: U64::from(header.number),
+            parent_hash: header.parent_hash.to_ethers(),
+        });
+        Ok(Box::pin(stream))
+    }
+}
@@ -0,0 +1,41 @@
+use alloy::{providers::Provider, rpc::types::Transaction};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+
+/// Maximum number of pending transactions fetched concurrently.
+const MAX_CONCURRENT_FETCHES: usize = 256;
+
+/// A collector that listens for new transactions in the mempool through an alloy provider,
+/// and generates a stream of [events](Transaction) which contain the alloy transaction.
+pub struct AlloyMempoolCollector<P> {
+    provider: Arc<P>,
+}
+
+impl<P> AlloyMempoolCollector<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [AlloyMempoolCollector](AlloyMempoolCollector). This implementation subscribes to the
+/// hashes of pending transactions, like the
+/// [MempoolCollector](super::mempool_collector::MempoolCollector), and fetches each of them.
+/// Transactions which left the mempool before they were fetched are skipped.
+#[async_trait]
+impl<P: Provider> Collector<Transaction> for AlloyMempoolCollector<P> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Transaction>> {
+        let subscription = self.provider.subscribe_pending_transactions().await?;
+        let provider = &self.provider;
+        let stream = subscription
+            .into_stream()
+            .map(move |hash| async move { provider.get_transaction_by_hash(hash).await })
+            .buffer_unordered(MAX_CONCURRENT_FETCHES)
+            .filter_map(|res| async move { res.ok().flatten() });
+        Ok(Box::pin(stream))
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use ethers::types::{Address, Bytes};
+use futures::stream;
+use serde::{de, de::DeserializeOwned, Deserialize};
+use tokio::time::sleep;
+use tracing::warn;
+
+/// Duration of a beacon chain slot, in seconds.
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+/// Number of slots in a beacon chain epoch.
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+/// How long before a slot starts its [SlotInfo] is emitted by default: one slot, so
+/// strategies know about the next slot as soon as the current one starts.
+const DEFAULT_LEAD_TIME: Duration = Duration::from_secs(SECONDS_PER_SLOT);
+
+/// An upcoming slot of the beacon chain, and its proposer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotInfo {
+    pub slot: u64,
+    pub epoch: u64,
+    /// Time the slot starts, when its proposer publishes its block.
+    pub start: SystemTime,
+    /// Index of the validator proposing the slot.
+    pub proposer_index: u64,
+    /// BLS public key of the validator proposing the slot.
+    pub proposer_pubkey: Bytes,
+    /// Whether the proposer registered with the watched relays for the slot, meaning
+    /// it runs MEV-boost and builds its block from bids of builders. `None` if no
+    /// relay is watched, or none could be reached.
+    pub mev_boost: Option<bool>,
+    /// Address the proposer registered with the relays to receive fees at, if any.
+    pub fee_recipient: Option<Address>,
+}
+
+impl SlotInfo {
+    /// Time left until the slot starts, zero once it started.
+    pub fn time_until_start(&self) -> Duration {
+        self.start
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+
+    /// Whether bundles targeting the slot can land through builders: false for slots of
+    /// vanilla proposers, which build their own blocks, and true if the registration of
+    /// the proposer couldn't be checked.
+    pub fn accepts_bundles(&self) -> bool {
+        self.mev_boost != Some(false)
+    }
+}
+
+/// The duty of a validator to propose the block of a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProposerDuty {
+    pub pubkey: Bytes,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub validator_index: u64,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub slot: u64,
+}
+
+/// The registration of a validator with a relay, for a slot it proposes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RelayRegistration {
+    #[serde(deserialize_with = "u64_from_str")]
+    pub slot: u64,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub validator_index: u64,
+    pub entry: RegistrationEntry,
+}
+
+/// A signed validator registration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RegistrationEntry {
+    pub message: RegistrationMessage,
+}
+
+/// The preferences a validator registered with a relay.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RegistrationMessage {
+    pub fee_recipient: Address,
+    pub pubkey: Bytes,
+}
+
+/// Responses of the beacon node API wrap their data.
+#[derive(Deserialize)]
+struct BeaconResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct Genesis {
+    #[serde(deserialize_with = "u64_from_str")]
+    genesis_time: u64,
+}
+
+/// Deserialize a `u64` the beacon and relay APIs encode as a decimal string.
+pub(crate) fn u64_from_str<'de, D>(deserializer: D) -> std::result::Result<u64, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let val = String::deserialize(deserializer)?;
+    val.parse().map_err(de::Error::custom)
+}
+
+/// A collector which reads the proposers of upcoming slots from the API of a beacon
+/// node, and generates a stream of [SlotInfo] events, one per slot, shortly before
+/// each slot starts. Strategies and executors can use them to time submissions close
+/// to slot boundaries, and to skip slots of vanilla proposers, which don't run
+/// MEV-boost, if MEV-boost relays are watched.
+pub struct BeaconCollector {
+    http_client: reqwest::Client,
+    beacon_url: String,
+    relay_urls: Vec<String>,
+    lead_time: Duration,
+}
+
+/// Proposer duties and relay registrations fetched so far, and the next slot to emit.
+#[derive(Default)]
+struct SlotCursor {
+    next_slot: u64,
+    /// Proposer duties of recent epochs, by slot.
+    duties: HashMap<u64, HashMap<u64, ProposerDuty>>,
+    /// The epoch relay registrations were last fetched in, and the registrations by
+    /// slot, if any relay could be reached.
+    registrations: Option<(u64, Option<HashMap<u64, RelayRegistration>>)>,
+}
+
+impl BeaconCollector {
+    /// Read slots from the beacon node API at `beacon_url`.
+    pub fn new(beacon_url: &str) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            beacon_url: beacon_url.trim_end_matches('/').to_string(),
+            relay_urls: vec![],
+            lead_time: DEFAULT_LEAD_TIME,
+        }
+    }
+
+    /// Check which proposers registered with the MEV-boost relay at `relay_url`, e.g.
+    /// `https://boost-relay.flashbots.net`. Can be called for several relays.
+    pub fn with_relay(mut self, relay_url: &str) -> Self {
+        self.relay_urls
+            .push(relay_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Emit the info of each slot `lead_time` before it starts. Defaults to one slot.
+    pub fn with_lead_time(mut self, lead_time: Duration) -> Self {
+        self.lead_time = lead_time;
+        self
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> anyhow::Result<T> {
+        let body = self
+            .http_client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Returns the unix timestamp of the first slot of the chain.
+    pub async fn genesis_time(&self) -> anyhow::Result<u64> {
+        let url = format!("{}/eth/v1/beacon/genesis", self.beacon_url);
+        let genesis: BeaconResponse<Genesis> = self.get_json(&url).await?;
+        Ok(genesis.data.genesis_time)
+    }
+
+    /// Returns the proposers of the slots of `epoch`.
+    pub async fn proposer_duties(&self, epoch: u64) -> anyhow::Result<Vec<ProposerDuty>> {
+        let url = format!(
+            "{}/eth/v1/validator/duties/proposer/{}",
+            self.beacon_url, epoch
+        );
+        let duties: BeaconResponse<Vec<ProposerDuty>> = self.get_json(&url).await?;
+        Ok(duties.data)
+    }
+
+    /// Returns the registrations of the proposers of the current and next epoch with the
+    /// watched relays, by slot, or `None` if no relay could be reached.
+    pub async fn relay_registrations(&self) -> Option<HashMap<u64, RelayRegistration>> {
+        let mut registrations = None;
+        for relay_url in &self.relay_urls {
+            let url = format!("{}/relay/v1/builder/validators", relay_url);
+            match self.get_json::<Vec<RelayRegistration>>(&url).await {
+                Ok(relay_registrations) => registrations.get_or_insert_with(HashMap::new).extend(
+                    relay_registrations
+                        .into_iter()
+                        .map(|registration| (registration.slot, registration)),
+                ),
+                Err(e) => warn!("Error fetching registrations from {}: {:#}", relay_url, e),
+            }
+        }
+        registrations
+    }
+
+    /// Returns the info of `slot`, fetching the proposer duties of its epoch and the
+    /// relay registrations if they weren't yet.
+    async fn slot_info(
+        &self,
+        cursor: &mut SlotCursor,
+        genesis_time: u64,
+        slot: u64,
+    ) -> anyhow::Result<SlotInfo> {
+        let epoch = slot / SLOTS_PER_EPOCH;
+        if !cursor.duties.contains_key(&epoch) {
+            let duties = self.proposer_duties(epoch).await?;
+            cursor.duties.retain(|cached, _| *cached >= epoch);
+            cursor.duties.insert(
+                epoch,
+                duties.into_iter().map(|duty| (duty.slot, duty)).collect(),
+            );
+        }
+        let duty = cursor.duties[&epoch]
+            .get(&slot)
+            .ok_or_else(|| anyhow::anyhow!("no proposer duty for slot {}", slot))?;
+
+        if !self.relay_urls.is_empty()
+            && cursor.registrations.as_ref().map(|(fetched, _)| *fetched) != Some(epoch)
+        {
+            cursor.registrations = Some((epoch, self.relay_registrations().await));
+        }
+        let registrations = cursor
+            .registrations
+            .as_ref()
+            .and_then(|(_, registrations)| registrations.as_ref());
+        Ok(to_slot_info(genesis_time, duty, registrations))
+    }
+}
+
+/// Returns the time `slot` starts at.
+fn slot_start(genesis_time: u64, slot: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(genesis_time + slot * SECONDS_PER_SLOT)
+}
+
+/// Returns the slot in progress at `time`.
+fn slot_at(genesis_time: u64, time: SystemTime) -> u64 {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    since_epoch.as_secs().saturating_sub(genesis_time) / SECONDS_PER_SLOT
+}
+
+/// Returns the info of the slot of `duty`, given the relay registrations by slot if
+/// they could be fetched.
+fn to_slot_info(
+    genesis_time: u64,
+    duty: &ProposerDuty,
+    registrations: Option<&HashMap<u64, RelayRegistration>>,
+) -> SlotInfo {
+    let registration = registrations.map(|registrations| {
+        registrations
+            .get(&duty.slot)
+            .filter(|registration| registration.validator_index == duty.validator_index)
+    });
+    SlotInfo {
+        slot: duty.slot,
+        epoch: duty.slot / SLOTS_PER_EPOCH,
+        start: slot_start(genesis_time, duty.slot),
+        proposer_index: duty.validator_index,
+        proposer_pubkey: duty.pubkey.clone(),
+        mev_boost: registration.map(|registration| registration.is_some()),
+        fee_recipient: registration
+            .flatten()
+            .map(|registration| registration.entry.message.fee_recipient),
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [BeaconCollector](BeaconCollector). Fails if the genesis time of the chain can't be
+/// read. Slots whose proposer can't be read are logged and skipped, and slots which
+/// started while the collector was busy aren't emitted.
+#[async_trait]
+impl Collector<SlotInfo> for BeaconCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, SlotInfo>> {
+        let genesis_time = self.genesis_time().await?;
+
+        let stream = stream::unfold(SlotCursor::default(), move |mut cursor| async move {
+            loop {
+                let slot = cursor
+                    .next_slot
+                    .max(slot_at(genesis_time, SystemTime::now()) + 1);
+                cursor.next_slot = slot + 1;
+                let emit_at = slot_start(genesis_time, slot)
+                    .checked_sub(self.lead_time)
+                    .unwrap_or(UNIX_EPOCH);
+                if let Ok(wait) = emit_at.duration_since(SystemTime::now()) {
+                    sleep(wait).await;
+                }
+                match self.slot_info(&mut cursor, genesis_time, slot).await {
+                    Ok(info) => return Some((info, cursor)),
+                    Err(e) => warn!("Error reading slot {}: {:#}", slot, e),
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_slot_info_from_duties_and_relay_registrations() {
+        let duties: BeaconResponse<Vec<ProposerDuty>> = serde_json::from_str(
+            r#"{"dependent_root": "0x00", "execution_optimistic": false, "data": [
+                {"pubkey": "0xaa", "validator_index": "7", "slot": "64"},
+                {"pubkey": "0xbb", "validator_index": "8", "slot": "65"}
+            ]}"#,
+        )
+        .unwrap();
+        let registrations: Vec<RelayRegistration> = serde_json::from_str(
+            r#"[{"slot": "64", "validator_index": "7", "entry": {
+                "message": {
+                    "fee_recipient": "0x0000000000000000000000000000000000000042",
+                    "gas_limit": "30000000",
+                    "timestamp": "1700000000",
+                    "pubkey": "0xaa"
+                },
+                "signature": "0x00"
+            }}]"#,
+        )
+        .unwrap();
+        let registrations: HashMap<_, _> = registrations
+            .into_iter()
+            .map(|registration| (registration.slot, registration))
+            .collect();
+
+        let genesis_time = 1_606_824_023;
+        let boosted = to_slot_info(genesis_time, &duties.data[0], Some(&registrations));
+        assert_eq!(boosted.epoch, 2);
+        assert_eq!(boosted.proposer_index, 7);
+        assert_eq!(boosted.mev_boost, Some(true));
+        assert_eq!(boosted.fee_recipient, Some(Address::from_low_u64_be(0x42)));
+        assert_eq!(
+            boosted.start,
+            UNIX_EPOCH + Duration::from_secs(genesis_time + 64 * 12)
+        );
+
+        let vanilla = to_slot_info(genesis_time, &duties.data[1], Some(&registrations));
+        assert_eq!(vanilla.mev_boost, Some(false));
+        assert!(!vanilla.accepts_bundles());
+        let unchecked = to_slot_info(genesis_time, &duties.data[1], None);
+        assert_eq!(unchecked.mev_boost, None);
+        assert!(unchecked.accepts_bundles());
+
+        assert_eq!(slot_at(genesis_time, boosted.start), 64);
+        assert_eq!(
+            slot_at(genesis_time, boosted.start + Duration::from_secs(11)),
+            64
+        );
+        assert_eq!(slot_at(genesis_time, UNIX_EPOCH), 0);
+    }
+}
@@ -1,30 +1,46 @@
-use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
+use crate::error::{ArtemisError, Result};
+use crate::types::{Collector, CollectorStream, RestartableCollector};
+use crate::utilities::chain_state::{ChainHead, ChainState};
 use async_trait::async_trait;
 use ethers::{
     prelude::Middleware,
     providers::PubsubClient,
-    types::{H256, U64},
+    types::{H256, U256, U64},
 };
 use std::sync::Arc;
 use tokio_stream::StreamExt;
+use tracing::warn;
 
 /// A collector that listens for new blocks, and generates a stream of
-/// [events](NewBlock) which contain the block number and hash.
+/// [events](NewBlock) which contain the block number, hash and parent hash.
 pub struct BlockCollector<M> {
     provider: Arc<M>,
+    /// State updated with every new block, and the priority fee suggested after it.
+    chain_state: Option<Arc<ChainState>>,
 }
 
-/// A new block event, containing the block number and hash.
+/// A new block event, containing the block number, hash and parent hash.
 #[derive(Debug, Clone)]
 pub struct NewBlock {
     pub hash: H256,
     pub number: U64,
+    /// Hash of the previous block, used to detect reorgs.
+    pub parent_hash: H256,
 }
 
 impl<M> BlockCollector<M> {
     pub fn new(provider: Arc<M>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            chain_state: None,
+        }
+    }
+
+    /// Publish the number, timestamp and base fee of every new block to `chain_state`,
+    /// along with the priority fee the node suggests after it.
+    pub fn with_chain_state(mut self, chain_state: Arc<ChainState>) -> Self {
+        self.chain_state = Some(chain_state);
+        self
     }
 }
 
@@ -33,16 +49,50 @@ impl<M> BlockCollector<M> {
 #[async_trait]
 impl<M> Collector<NewBlock> for BlockCollector<M>
 where
-    M: Middleware,
+    M: Middleware + 'static,
     M::Provider: PubsubClient,
     M::Error: 'static,
 {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
-        let stream = self.provider.subscribe_blocks().await?;
-        let stream = stream.filter_map(|block| match block.hash {
-            Some(hash) => block.number.map(|number| NewBlock { hash, number }),
-            None => None,
+        let stream = self
+            .provider
+            .subscribe_blocks()
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        let stream = stream.filter_map(|block| {
+            if let (Some(chain_state), Some(head)) = (&self.chain_state, ChainHead::of(&block)) {
+                chain_state.update_head(head);
+                // The priority fee is read off the event's path, so blocks aren't delayed.
+                let (provider, chain_state) = (self.provider.clone(), chain_state.clone());
+                tokio::spawn(async move {
+                    match provider
+                        .provider()
+                        .request::<_, U256>("eth_maxPriorityFeePerGas", ())
+                        .await
+                    {
+                        Ok(priority_fee) => chain_state.update_priority_fee(priority_fee),
+                        Err(e) => warn!("error getting the suggested priority fee: {}", e),
+                    }
+                });
+            }
+            match block.hash {
+                Some(hash) => block.number.map(|number| NewBlock {
+                    hash,
+                    number,
+                    parent_hash: block.parent_hash,
+                }),
+                None => None,
+            }
         });
         Ok(Box::pin(stream))
     }
 }
+
+/// A stalled block subscription is restarted by subscribing again.
+impl<M> RestartableCollector<NewBlock> for BlockCollector<M>
+where
+    M: Middleware + 'static,
+    M::Provider: PubsubClient,
+    M::Error: 'static,
+{
+}
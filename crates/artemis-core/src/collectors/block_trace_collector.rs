@@ -0,0 +1,370 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use crate::collectors::block_collector::NewBlock;
+use crate::collectors::enriched_mempool_collector::{
+    apply_call_frame, call_tracer_options, EnrichedTransaction, TokenTransfer,
+};
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use ethers::{
+    prelude::Middleware,
+    types::{
+        Address, Block, BlockNumber, CallFrame, GethTrace, GethTraceFrame, NameOrAddress,
+        Transaction, H256, I256, U256, U64,
+    },
+};
+use futures::{stream, StreamExt};
+use tracing::debug;
+
+/// A landed transaction which swapped through watched pools and left its sender with a
+/// profit, e.g. an arb or a backrun, whether ours or a competitor's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompetitorFill {
+    /// The block the transaction landed in.
+    pub block: U64,
+    /// Hash of the transaction.
+    pub tx_hash: H256,
+    /// Position of the transaction in the block.
+    pub index: usize,
+    /// Sender of the transaction.
+    pub from: Address,
+    /// Contract the transaction called, usually the searcher's executor contract.
+    pub to: Option<Address>,
+    /// Watched pools the transaction swapped through, in address order.
+    pub pools: Vec<Address>,
+    /// Net amount of each token the sender and the called contract received, in token
+    /// order. Profits are positive.
+    pub profit: Vec<(Address, I256)>,
+    /// Ether paid to the block's coinbase by the transaction's calls.
+    pub coinbase_payment: U256,
+    /// Gas used by the transaction.
+    pub gas_used: U256,
+    /// Gas price the transaction paid.
+    pub gas_price: U256,
+    /// Hash of the previous transaction of the block, if it swapped through one of the
+    /// same pools, meaning the fill backran it.
+    pub backrun_of: Option<H256>,
+}
+
+impl CompetitorFill {
+    /// Address the fill is attributed to: the called contract, since searchers rotate
+    /// sender addresses, or the sender.
+    pub fn searcher(&self) -> Address {
+        self.to.unwrap_or(self.from)
+    }
+}
+
+/// A collector which wraps a new block collector, traces each new block with
+/// `debug_traceBlockByNumber`, and generates a stream of the [fills](CompetitorFill) of
+/// watched pools in the block, for analysing which searchers land what, e.g. with an
+/// [InclusionBenchmark](crate::analytics::InclusionBenchmark).
+pub struct BlockTraceCollector<M> {
+    inner: Box<dyn Collector<NewBlock>>,
+    provider: Arc<M>,
+    watched_pools: HashSet<Address>,
+}
+
+impl<M> BlockTraceCollector<M> {
+    pub fn new(
+        inner: Box<dyn Collector<NewBlock>>,
+        provider: Arc<M>,
+        watched_pools: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            inner,
+            provider,
+            watched_pools: watched_pools.into_iter().collect(),
+        }
+    }
+}
+
+impl<M> BlockTraceCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Trace `block`, returning its fills. Failures are logged, and the block skipped.
+    async fn fills(&self, block: NewBlock) -> Vec<CompetitorFill> {
+        let number = BlockNumber::Number(block.number);
+        let (txs, traces) = futures::join!(
+            self.provider.get_block_with_txs(number),
+            self.provider
+                .debug_trace_block_by_number(Some(number), call_tracer_options().tracing_options)
+        );
+        match (txs, traces) {
+            (Ok(Some(txs)), Ok(traces)) => extract_fills(&txs, &traces, &self.watched_pools),
+            (Ok(None), _) => {
+                debug!("block {} not found", block.number);
+                vec![]
+            }
+            (Err(e), _) => {
+                debug!("error fetching block {}: {}", block.number, e);
+                vec![]
+            }
+            (_, Err(e)) => {
+                debug!("error tracing block {}: {}", block.number, e);
+                vec![]
+            }
+        }
+    }
+}
+
+/// Returns the fills of `watched_pools` in `block`, given the call traces of its
+/// transactions, in order.
+fn extract_fills(
+    block: &Block<Transaction>,
+    traces: &[GethTrace],
+    watched_pools: &HashSet<Address>,
+) -> Vec<CompetitorFill> {
+    if block.transactions.len() != traces.len() {
+        debug!(
+            "block {:?} has {} transactions but {} traces",
+            block.number,
+            block.transactions.len(),
+            traces.len()
+        );
+        return vec![];
+    }
+    let coinbase = block.author.unwrap_or_default();
+
+    let mut fills = Vec::new();
+    let mut previous: Option<(H256, Vec<Address>)> = None;
+    for (index, (tx, trace)) in block.transactions.iter().zip(traces).enumerate() {
+        let GethTrace::Known(GethTraceFrame::CallTracer(frame)) = trace else {
+            debug!("unexpected trace format for tx {:?}", tx.hash);
+            previous = None;
+            continue;
+        };
+        let mut enriched = EnrichedTransaction {
+            tx: tx.clone(),
+            ..Default::default()
+        };
+        if frame.error.is_none() {
+            apply_call_frame(&mut enriched, frame);
+        }
+        let pools: Vec<Address> = enriched
+            .touched_pools
+            .into_iter()
+            .filter(|pool| watched_pools.contains(pool))
+            .collect();
+
+        let mut accounts = vec![tx.from];
+        accounts.extend(tx.to);
+        let profit = net_flows(&enriched.transfers, &accounts);
+        let profitable =
+            !profit.is_empty() && profit.iter().all(|(_, amount)| amount.is_positive());
+        if !pools.is_empty() && profitable {
+            let backrun_of = previous
+                .as_ref()
+                .filter(|(_, previous_pools)| {
+                    pools.iter().any(|pool| previous_pools.contains(pool))
+                })
+                .map(|(hash, _)| *hash);
+            fills.push(CompetitorFill {
+                block: block.number.unwrap_or_default(),
+                tx_hash: tx.hash,
+                index,
+                from: tx.from,
+                to: tx.to,
+                pools: pools.clone(),
+                profit,
+                coinbase_payment: coinbase_payment(frame, coinbase),
+                gas_used: frame.gas_used,
+                gas_price: tx.gas_price.unwrap_or_default(),
+                backrun_of,
+            });
+        }
+        previous = Some((tx.hash, pools));
+    }
+    fills
+}
+
+/// Returns the net amount of each token `accounts` received in `transfers`, omitting
+/// tokens they broke even on. Transfers between the accounts cancel out.
+fn net_flows(transfers: &[TokenTransfer], accounts: &[Address]) -> Vec<(Address, I256)> {
+    let mut flows = BTreeMap::new();
+    for transfer in transfers {
+        let received = accounts.contains(&transfer.to);
+        if received == accounts.contains(&transfer.from) {
+            continue;
+        }
+        let amount = I256::try_from(transfer.amount).unwrap_or(I256::MAX);
+        let flow = flows.entry(transfer.token).or_insert(I256::zero());
+        *flow = if received {
+            flow.saturating_add(amount)
+        } else {
+            flow.saturating_sub(amount)
+        };
+    }
+    flows
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .collect()
+}
+
+/// Returns the ether sent to `coinbase` by the calls of `root` which didn't revert.
+fn coinbase_payment(root: &CallFrame, coinbase: Address) -> U256 {
+    let mut payment = U256::zero();
+    let mut frames = vec![root];
+    while let Some(frame) = frames.pop() {
+        if frame.error.is_some() {
+            continue;
+        }
+        if frame.to == Some(NameOrAddress::Address(coinbase)) {
+            payment = payment.saturating_add(frame.value.unwrap_or_default());
+        }
+        frames.extend(frame.calls.iter().flatten());
+    }
+    payment
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [BlockTraceCollector](BlockTraceCollector). Blocks are traced one at a time, in the
+/// order the inner collector emits them, and their fills are emitted in block order.
+#[async_trait]
+impl<M> Collector<CompetitorFill> for BlockTraceCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, CompetitorFill>> {
+        let stream = self.inner.get_event_stream().await?;
+        let stream = stream
+            .then(move |block| self.fills(block))
+            .flat_map(stream::iter);
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        types::{Bytes, CallLogFrame},
+        utils::keccak256,
+    };
+
+    fn transfer(token: Address, from: Address, to: Address, amount: u64) -> CallLogFrame {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+        CallLogFrame {
+            address: Some(token),
+            topics: Some(vec![
+                H256::from(keccak256("Transfer(address,address,uint256)")),
+                H256::from(from),
+                H256::from(to),
+            ]),
+            data: Some(Bytes::from(data.to_vec())),
+        }
+    }
+
+    fn sync(pool: Address) -> CallLogFrame {
+        CallLogFrame {
+            address: Some(pool),
+            topics: Some(vec![H256::from(keccak256("Sync(uint112,uint112)"))]),
+            data: None,
+        }
+    }
+
+    fn trace(to: Address, logs: Vec<CallLogFrame>, calls: Vec<CallFrame>) -> GethTrace {
+        GethTrace::Known(GethTraceFrame::CallTracer(CallFrame {
+            to: Some(NameOrAddress::Address(to)),
+            gas_used: U256::from(120_000),
+            logs: Some(logs),
+            calls: Some(calls),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn extracts_profitable_swaps_of_watched_pools() {
+        let address = Address::from_low_u64_be;
+        let (weth, usdc) = (address(1), address(2));
+        let (pool_a, pool_b, unwatched) = (address(10), address(11), address(12));
+        let (user, searcher, contract, coinbase) =
+            (address(20), address(21), address(22), address(23));
+
+        let tx = |hash: u8, from: Address, to: Address| Transaction {
+            hash: H256::repeat_byte(hash),
+            from,
+            to: Some(to),
+            gas_price: Some(U256::from(30)),
+            ..Default::default()
+        };
+        let block = Block {
+            number: Some(U64::from(100)),
+            author: Some(coinbase),
+            transactions: vec![
+                tx(1, user, pool_a),
+                tx(2, searcher, contract),
+                tx(3, searcher, contract),
+            ],
+            ..Default::default()
+        };
+        let traces = vec![
+            // A user swap: sends one token and receives another.
+            trace(
+                pool_a,
+                vec![
+                    transfer(weth, user, pool_a, 10),
+                    transfer(usdc, pool_a, user, 500),
+                    sync(pool_a),
+                ],
+                vec![],
+            ),
+            // A backrun through both pools, paying the coinbase.
+            trace(
+                contract,
+                vec![
+                    transfer(weth, contract, pool_b, 5),
+                    transfer(usdc, pool_b, pool_a, 260),
+                    transfer(weth, pool_a, contract, 7),
+                    sync(pool_a),
+                    sync(pool_b),
+                ],
+                vec![CallFrame {
+                    to: Some(NameOrAddress::Address(coinbase)),
+                    value: Some(U256::from(1_000)),
+                    ..Default::default()
+                }],
+            ),
+            // An arb through an unwatched pool.
+            trace(
+                contract,
+                vec![
+                    transfer(weth, contract, unwatched, 5),
+                    transfer(weth, unwatched, contract, 6),
+                    sync(unwatched),
+                ],
+                vec![],
+            ),
+        ];
+
+        let watched = HashSet::from([pool_a, pool_b]);
+        let fills = extract_fills(&block, &traces, &watched);
+        assert_eq!(
+            fills,
+            vec![CompetitorFill {
+                block: U64::from(100),
+                tx_hash: H256::repeat_byte(2),
+                index: 1,
+                from: searcher,
+                to: Some(contract),
+                pools: vec![pool_a, pool_b],
+                profit: vec![(weth, I256::from(2))],
+                coinbase_payment: U256::from(1_000),
+                gas_used: U256::from(120_000),
+                gas_price: U256::from(30),
+                backrun_of: Some(H256::repeat_byte(1)),
+            }]
+        );
+        assert_eq!(fills[0].searcher(), contract);
+
+        // Traces which don't match the block's transactions are skipped.
+        assert!(extract_fills(&block, &traces[..2], &watched).is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use ethers::{providers::Middleware, signers::Signer};
+use matchmaker::status::{BundleStatusTracker, StatusUpdate};
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+/// A collector that polls the status of sent MEV-share bundles, and generates
+/// [status updates](StatusUpdate) as they are received, simulated, sealed by a builder
+/// and land on chain. Bundles are tracked with the
+/// [tracked bundles](BundleStatusTracker::bundles) of the tracker.
+pub struct BundleStatusCollector<S, M> {
+    tracker: BundleStatusTracker<S, M>,
+}
+
+impl<S, M> BundleStatusCollector<S, M> {
+    pub fn new(tracker: BundleStatusTracker<S, M>) -> Self {
+        Self { tracker }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [BundleStatusCollector](BundleStatusCollector). Polling errors are logged and
+/// skipped.
+#[async_trait]
+impl<S, M> Collector<StatusUpdate> for BundleStatusCollector<S, M>
+where
+    S: Signer + Clone + 'static,
+    M: Middleware + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, StatusUpdate>> {
+        let stream = self.tracker.subscribe().filter_map(|update| match update {
+            Ok(update) => Some(update),
+            Err(e) => {
+                warn!("Error polling bundle status: {}", e);
+                None
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
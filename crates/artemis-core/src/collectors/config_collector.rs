@@ -0,0 +1,182 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::stream;
+use serde::de::DeserializeOwned;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::{info, warn};
+
+/// How often the config file is checked for changes by default.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An event carrying the new parameters of a [Reconfigurable](crate::types::Reconfigurable)
+/// strategy, read from its config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigUpdated<C> {
+    pub config: C,
+}
+
+/// A collector that watches a JSON config file, and emits a
+/// [ConfigUpdated](ConfigUpdated) event whenever its contents change, or the process
+/// receives SIGHUP. Files which fail to parse are logged and skipped, so a bad edit
+/// leaves strategies on their current parameters.
+pub struct ConfigCollector {
+    path: PathBuf,
+    poll_interval: Duration,
+}
+
+impl ConfigCollector {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Check the file for changes every `poll_interval`. Defaults to 1 second.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Read and parse the config file, e.g. to configure strategies before the engine
+    /// starts. Only later changes are emitted as events.
+    pub fn load<C: DeserializeOwned>(&self) -> anyhow::Result<C> {
+        parse(&self.read()?)
+    }
+
+    fn read(&self) -> anyhow::Result<String> {
+        std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Error reading config file {}", self.path.display()))
+    }
+}
+
+/// State of the stream of config updates.
+struct Watch {
+    /// Contents of the file as of the last check.
+    contents: Option<String>,
+    poll: Interval,
+    hangup: Hangup,
+}
+
+/// Implementation of the [Collector](Collector) trait for the [ConfigCollector](ConfigCollector).
+#[async_trait]
+impl<C> Collector<ConfigUpdated<C>> for ConfigCollector
+where
+    C: DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, ConfigUpdated<C>>> {
+        let mut poll = interval(self.poll_interval);
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let watch = Watch {
+            contents: self.read().ok(),
+            poll,
+            hangup: listen_for_hangup()?,
+        };
+
+        let stream = stream::unfold(watch, move |mut watch| async move {
+            loop {
+                let forced = tokio::select! {
+                    _ = watch.poll.tick() => false,
+                    _ = hangup(&mut watch.hangup) => true,
+                };
+                let contents = match self.read() {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        warn!("{:#}", e);
+                        continue;
+                    }
+                };
+                if !forced && watch.contents.as_ref() == Some(&contents) {
+                    continue;
+                }
+                watch.contents = Some(contents.clone());
+                match parse(&contents) {
+                    Ok(config) => {
+                        info!("reloaded config from {}", self.path.display());
+                        return Some((ConfigUpdated { config }, watch));
+                    }
+                    Err(e) => warn!("{:#}, keeping the current config", e),
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+fn parse<C: DeserializeOwned>(contents: &str) -> anyhow::Result<C> {
+    serde_json::from_str(contents).context("Error parsing config file")
+}
+
+#[cfg(unix)]
+type Hangup = tokio::signal::unix::Signal;
+
+#[cfg(unix)]
+fn listen_for_hangup() -> anyhow::Result<Hangup> {
+    use tokio::signal::unix::{signal, SignalKind};
+    Ok(signal(SignalKind::hangup())?)
+}
+
+/// Resolves once the process receives SIGHUP.
+#[cfg(unix)]
+async fn hangup(signal: &mut Hangup) {
+    signal.recv().await;
+}
+
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(not(unix))]
+fn listen_for_hangup() -> anyhow::Result<Hangup> {
+    Ok(())
+}
+
+/// There is no SIGHUP outside of unix, so this never resolves.
+#[cfg(not(unix))]
+async fn hangup(_: &mut Hangup) {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use tokio::time::timeout;
+    use tokio_stream::StreamExt;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct TestConfig {
+        value: u64,
+    }
+
+    #[tokio::test]
+    async fn emits_changed_configs_and_skips_invalid_ones() {
+        let path = std::env::temp_dir().join(format!("artemis-config-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"value": 1}"#).unwrap();
+        let collector = ConfigCollector::new(&path).with_poll_interval(Duration::from_millis(10));
+        assert_eq!(
+            collector.load::<TestConfig>().unwrap(),
+            TestConfig { value: 1 }
+        );
+
+        let mut stream = collector.get_event_stream().await.unwrap();
+        let wait = Duration::from_secs(1);
+
+        std::fs::write(&path, r#"{"value": 2}"#).unwrap();
+        let update: ConfigUpdated<TestConfig> =
+            timeout(wait, stream.next()).await.unwrap().unwrap();
+        assert_eq!(update.config, TestConfig { value: 2 });
+
+        std::fs::write(&path, r#"{"value": "#).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        std::fs::write(&path, r#"{"value": 3}"#).unwrap();
+        let update = timeout(wait, stream.next()).await.unwrap().unwrap();
+        assert_eq!(update.config, TestConfig { value: 3 });
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use crate::utilities::coordination::Coordinator;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tracing::{debug, warn};
+
+/// Default time an event is claimed for, about one block.
+pub const DEFAULT_EVENT_CLAIM_TTL: Duration = Duration::from_secs(12);
+
+/// Returns the key identifying an event across instances, e.g. the hash of a hint.
+type EventKeyFn<E> = Box<dyn Fn(&E) -> String + Send + Sync>;
+
+/// CoordinatedCollector is a wrapper around a [Collector](Collector) which deduplicates
+/// events across the instances of a bot, through a [Coordinator](Coordinator): only the
+/// first instance to claim an event emits it, so replicas sharing a coordinator, e.g.
+/// in several regions, split the events they all collect instead of processing each of
+/// them. Events are emitted if the coordinator can't be reached, since missing an
+/// opportunity is worse than processing it twice.
+pub struct CoordinatedCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    coordinator: Arc<dyn Coordinator>,
+    key: EventKeyFn<E>,
+    ttl: Duration,
+}
+
+impl<E> CoordinatedCollector<E> {
+    /// Deduplicate the events of `collector` by `key` through `coordinator`.
+    pub fn new(
+        collector: Box<dyn Collector<E>>,
+        coordinator: Arc<dyn Coordinator>,
+        key: impl Fn(&E) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            collector,
+            coordinator,
+            key: Box::new(key),
+            ttl: DEFAULT_EVENT_CLAIM_TTL,
+        }
+    }
+
+    /// Claim events for `ttl`. Duplicates delivered later are emitted again.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+#[async_trait]
+impl<E: Send + Sync + 'static> Collector<E> for CoordinatedCollector<E> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let stream = stream.filter_map(move |event| async move {
+            let key = format!("event:{}", (self.key)(&event));
+            match self.coordinator.claim(&key, self.ttl).await {
+                Ok(true) => Some(event),
+                Ok(false) => {
+                    debug!("skipping event {} claimed by another instance", key);
+                    None
+                }
+                Err(e) => {
+                    warn!("failed to claim event {}: {}", key, e);
+                    Some(event)
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::coordination::LocalCoordinator;
+    use futures::stream;
+
+    struct Numbers(Vec<u64>);
+
+    #[async_trait]
+    impl Collector<u64> for Numbers {
+        async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+            Ok(Box::pin(stream::iter(self.0.clone())))
+        }
+    }
+
+    #[tokio::test]
+    async fn instances_split_shared_events() {
+        let coordinator: Arc<dyn Coordinator> = Arc::new(LocalCoordinator::new());
+        let replica = |numbers: Vec<u64>| {
+            CoordinatedCollector::new(
+                Box::new(Numbers(numbers)),
+                coordinator.clone(),
+                |n: &u64| n.to_string(),
+            )
+        };
+        let (first, second) = (replica(vec![1, 2, 2, 3]), replica(vec![2, 3, 4]));
+
+        let first: Vec<_> = first.get_event_stream().await.unwrap().collect().await;
+        let second: Vec<_> = second.get_event_stream().await.unwrap().collect().await;
+        assert_eq!(first, vec![1, 2, 3]);
+        assert_eq!(second, vec![4]);
+    }
+}
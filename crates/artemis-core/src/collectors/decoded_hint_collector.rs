@@ -0,0 +1,41 @@
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use crate::utilities::abi_registry::{AbiRegistry, DecodedHint};
+use async_trait::async_trait;
+use matchmaker::types::Hint;
+use tokio_stream::StreamExt;
+
+/// A collector which wraps a MEV-Share hint collector, such as a
+/// [MevShareCollector](super::mevshare_collector::MevShareCollector), and generates
+/// [decoded hints](DecodedHint), with the swaps of their logs decoded by an
+/// [AbiRegistry].
+pub struct DecodedHintCollector {
+    inner: Box<dyn Collector<Hint>>,
+    registry: AbiRegistry,
+}
+
+impl DecodedHintCollector {
+    pub fn new(inner: Box<dyn Collector<Hint>>) -> Self {
+        Self {
+            inner,
+            registry: AbiRegistry::default(),
+        }
+    }
+
+    /// Decode logs with `registry`, instead of the default one.
+    pub fn with_registry(mut self, registry: AbiRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [DecodedHintCollector](DecodedHintCollector).
+#[async_trait]
+impl Collector<DecodedHint> for DecodedHintCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, DecodedHint>> {
+        let stream = self.inner.get_event_stream().await?;
+        let stream = stream.map(move |hint| self.registry.decode(hint));
+        Ok(Box::pin(stream))
+    }
+}
@@ -0,0 +1,293 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use ethers::{
+    prelude::Middleware,
+    types::{
+        Address, BlockId, BlockNumber, CallConfig, CallFrame, GethDebugBuiltInTracerConfig,
+        GethDebugBuiltInTracerType, GethDebugTracerConfig, GethDebugTracerType,
+        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, GethTraceFrame,
+        NameOrAddress, Transaction, TransactionRequest, H256, U256,
+    },
+    utils::keccak256,
+};
+use futures::StreamExt;
+use tracing::debug;
+
+/// Default number of transactions simulated concurrently.
+const DEFAULT_CONCURRENCY: usize = 16;
+
+/// How much work the [EnrichedMempoolCollector](EnrichedMempoolCollector) does per transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SimulationMode {
+    /// Pass transactions through without simulating them.
+    #[default]
+    None,
+    /// Simulate with `eth_call`, which only tells whether the transaction reverts.
+    Call,
+    /// Simulate with `debug_traceCall` and the call tracer, which also yields the
+    /// touched contracts, pools and token transfers.
+    Trace,
+}
+
+/// An ERC20 transfer emitted during simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenTransfer {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+/// A pending transaction, along with the result of simulating it against latest state.
+#[derive(Debug, Clone, Default)]
+pub struct EnrichedTransaction {
+    pub tx: Transaction,
+    /// Whether the simulation reverted, or `None` if it wasn't simulated.
+    pub reverted: Option<bool>,
+    /// Every contract called during the simulation, in address order.
+    pub touched_contracts: Vec<Address>,
+    /// Pools which emitted a Uniswap V2 or V3 style swap or sync event.
+    pub touched_pools: Vec<Address>,
+    pub transfers: Vec<TokenTransfer>,
+}
+
+/// A collector which wraps a pending transaction collector, simulates each transaction
+/// against latest state, and generates a stream of [events](EnrichedTransaction) with the
+/// simulation results attached.
+pub struct EnrichedMempoolCollector<M> {
+    inner: Box<dyn Collector<Transaction>>,
+    provider: Arc<M>,
+    mode: SimulationMode,
+    concurrency: usize,
+}
+
+impl<M> EnrichedMempoolCollector<M> {
+    pub fn new(
+        inner: Box<dyn Collector<Transaction>>,
+        provider: Arc<M>,
+        mode: SimulationMode,
+    ) -> Self {
+        Self {
+            inner,
+            provider,
+            mode,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+impl<M: Middleware> EnrichedMempoolCollector<M> {
+    /// Simulate `tx` according to the configured mode. Simulation failures are logged, and
+    /// the transaction is passed through without enrichment.
+    async fn enrich(&self, tx: Transaction) -> EnrichedTransaction {
+        // Fee fields are dropped, since a pending tx may bid below the current base fee.
+        let request = TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to.map(NameOrAddress::Address),
+            gas: Some(tx.gas),
+            value: Some(tx.value),
+            data: Some(tx.input.clone()),
+            ..Default::default()
+        };
+        let block = Some(BlockId::Number(BlockNumber::Latest));
+        let mut enriched = EnrichedTransaction {
+            tx,
+            ..Default::default()
+        };
+
+        match self.mode {
+            SimulationMode::None => {}
+            SimulationMode::Call => {
+                let result = self.provider.call(&request.into(), block).await;
+                enriched.reverted = Some(result.is_err());
+            }
+            SimulationMode::Trace => {
+                match self
+                    .provider
+                    .debug_trace_call(request, block, call_tracer_options())
+                    .await
+                {
+                    Ok(GethTrace::Known(GethTraceFrame::CallTracer(frame))) => {
+                        enriched.reverted = Some(frame.error.is_some());
+                        apply_call_frame(&mut enriched, &frame);
+                    }
+                    Ok(_) => debug!("unexpected trace format for tx {:?}", enriched.tx.hash),
+                    Err(e) => debug!("error tracing tx {:?}: {}", enriched.tx.hash, e),
+                }
+            }
+        }
+        enriched
+    }
+}
+
+/// Tracing options for the call tracer, with logs included.
+pub(crate) fn call_tracer_options() -> GethDebugTracingCallOptions {
+    GethDebugTracingCallOptions {
+        tracing_options: GethDebugTracingOptions {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::CallTracer,
+            )),
+            tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+                GethDebugBuiltInTracerConfig::CallTracer(CallConfig {
+                    only_top_call: Some(false),
+                    with_log: Some(true),
+                }),
+            )),
+            ..Default::default()
+        },
+        state_overrides: None,
+        block_overrides: None,
+    }
+}
+
+/// Walk the call tree, recording touched contracts, pools and transfers. Calls which
+/// reverted are skipped, since their logs were discarded.
+pub(crate) fn apply_call_frame(enriched: &mut EnrichedTransaction, root: &CallFrame) {
+    let transfer_topic = H256::from(keccak256("Transfer(address,address,uint256)"));
+    let pool_topics = [
+        H256::from(keccak256(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+        )),
+        H256::from(keccak256(
+            "Swap(address,address,int256,int256,uint160,uint128,int24)",
+        )),
+        H256::from(keccak256("Sync(uint112,uint112)")),
+    ];
+
+    let mut contracts = BTreeSet::new();
+    let mut pools = BTreeSet::new();
+    let mut frames = vec![root];
+    while let Some(frame) = frames.pop() {
+        if frame.error.is_some() {
+            continue;
+        }
+        if let Some(NameOrAddress::Address(to)) = frame.to {
+            contracts.insert(to);
+        }
+        for log in frame.logs.iter().flatten() {
+            let (Some(address), Some(topics)) = (log.address, log.topics.as_ref()) else {
+                continue;
+            };
+            match topics.first() {
+                Some(topic) if pool_topics.contains(topic) => {
+                    pools.insert(address);
+                }
+                // ERC721 transfers index the token id, so have a fourth topic.
+                Some(topic) if *topic == transfer_topic && topics.len() == 3 => {
+                    let amount = log
+                        .data
+                        .as_ref()
+                        .filter(|data| data.len() >= 32)
+                        .map(|data| U256::from_big_endian(&data[..32]))
+                        .unwrap_or_default();
+                    enriched.transfers.push(TokenTransfer {
+                        token: address,
+                        from: Address::from(topics[1]),
+                        to: Address::from(topics[2]),
+                        amount,
+                    });
+                }
+                _ => {}
+            }
+        }
+        // Push in reverse, so frames are visited in execution order.
+        frames.extend(frame.calls.iter().flatten().rev());
+    }
+    enriched.touched_contracts = contracts.into_iter().collect();
+    enriched.touched_pools = pools.into_iter().collect();
+}
+
+/// Implementation of the [Collector](Collector) trait for the [EnrichedMempoolCollector](EnrichedMempoolCollector).
+/// Up to `concurrency` transactions are simulated at once, and are emitted as soon as their
+/// simulation completes, so ordering is not preserved.
+#[async_trait]
+impl<M> Collector<EnrichedTransaction> for EnrichedMempoolCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, EnrichedTransaction>> {
+        let stream = self.inner.get_event_stream().await?;
+        let stream = stream
+            .map(move |tx| self.enrich(tx))
+            .buffer_unordered(self.concurrency);
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bytes, CallLogFrame};
+
+    fn topic(address: Address) -> H256 {
+        H256::from(address)
+    }
+
+    #[test]
+    fn apply_call_frame_collects_pools_and_transfers() {
+        let token = Address::from_low_u64_be(1);
+        let pool = Address::from_low_u64_be(2);
+        let user = Address::from_low_u64_be(3);
+        let reverted = Address::from_low_u64_be(4);
+
+        let mut amount = [0u8; 32];
+        U256::from(1000).to_big_endian(&mut amount);
+        let transfer = CallLogFrame {
+            address: Some(token),
+            topics: Some(vec![
+                H256::from(keccak256("Transfer(address,address,uint256)")),
+                topic(pool),
+                topic(user),
+            ]),
+            data: Some(Bytes::from(amount.to_vec())),
+        };
+        let sync = CallLogFrame {
+            address: Some(pool),
+            topics: Some(vec![H256::from(keccak256("Sync(uint112,uint112)"))]),
+            data: None,
+        };
+
+        let root = CallFrame {
+            to: Some(NameOrAddress::Address(pool)),
+            logs: Some(vec![sync]),
+            calls: Some(vec![
+                CallFrame {
+                    to: Some(NameOrAddress::Address(token)),
+                    logs: Some(vec![transfer.clone()]),
+                    ..Default::default()
+                },
+                CallFrame {
+                    to: Some(NameOrAddress::Address(reverted)),
+                    error: Some("execution reverted".to_string()),
+                    logs: Some(vec![transfer]),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let mut enriched = EnrichedTransaction::default();
+        apply_call_frame(&mut enriched, &root);
+
+        assert_eq!(enriched.touched_contracts, vec![token, pool]);
+        assert_eq!(enriched.touched_pools, vec![pool]);
+        assert_eq!(
+            enriched.transfers,
+            vec![TokenTransfer {
+                token,
+                from: pool,
+                to: user,
+                amount: U256::from(1000),
+            }]
+        );
+    }
+}
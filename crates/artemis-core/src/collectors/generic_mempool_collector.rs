@@ -1,66 +1,120 @@
 use async_trait::async_trait;
 
-use ethers::{prelude::Middleware, types::Transaction};
-use futures::stream::{iter, StreamExt};
-use std::sync::Arc;
-
-
+use ethers::{
+    prelude::Middleware,
+    types::{Transaction, H256},
+};
+use futures::stream::{self, iter, StreamExt};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::error;
 
+use crate::error::Result;
 use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
 
-/// A collector that listens for new transactions in the mempool, and generates a stream of
-/// [events](Transaction) which contain the transaction.
+/// Default interval between two `txpool_content` polls.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A collector that polls the `txpool` namespace of a node for pending transactions, and
+/// generates a stream of [events](Transaction) which contain the transactions not seen in
+/// the previous poll. Useful for nodes which don't support pending transaction subscriptions.
 pub struct GenericMempoolCollector<M> {
-    
     provider: Arc<M>,
+    poll_interval: Duration,
 }
 
 impl<M> GenericMempoolCollector<M> {
     pub fn new(provider: Arc<M>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
     }
 }
 
+/// Returns the transactions in `pending` whose hashes are not in `seen`, and replaces `seen`
+/// with the hashes of `pending`, so transactions which left the pool are forgotten.
+fn diff_pending(seen: &mut HashSet<H256>, pending: Vec<Transaction>) -> Vec<Transaction> {
+    let mut current = HashSet::with_capacity(pending.len());
+    let new_txs = pending
+        .into_iter()
+        .filter(|tx| current.insert(tx.hash) && !seen.contains(&tx.hash))
+        .collect();
+    *seen = current;
+    new_txs
+}
+
 /// Implementation of the [Collector](Collector) trait for the [GenericMempoolCollector](GenericMempoolCollector).
-/// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new transactions.
+/// This implementation polls `txpool_content` on an interval, and never ends on its own. Failed
+/// polls are logged and retried on the next tick.
 #[async_trait]
 impl<M> Collector<Transaction> for GenericMempoolCollector<M>
 where
     M: Middleware,
     M::Error: 'static,
-    
 {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Transaction>> {
-        let stream = self.provider.txpool_content()
-                                                                .await
-                                                                .map_err(|_| anyhow::anyhow!("Failed to create mempool stream"))?;
-
-        let mut pending_txs = Vec::new();
-
-        let _z: () = stream.pending.into_values().
-                     map( |tx_treemap| {
-                                                        
-                         let txs: Vec<Transaction> = tx_treemap.into_values()
-                            .map(|tx| {
-                                                                                            
-                                    tx
-                                                        
-                            }) 
+        let mut ticker = interval(self.poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let stream = stream::unfold(
+            (ticker, HashSet::new()),
+            move |(mut ticker, mut seen)| async move {
+                ticker.tick().await;
+                let new_txs = match self.provider.txpool_content().await {
+                    Ok(content) => {
+                        let pending = content
+                            .pending
+                            .into_values()
+                            .flat_map(|txs| txs.into_values())
                             .collect();
-                                                        
-                    pending_txs.push(txs);
-                                                        
-                    }).collect();
-                                                        
-        let pending_txs: Vec<Transaction> = pending_txs.into_iter().flatten().collect();
+                        diff_pending(&mut seen, pending)
+                    }
+                    Err(e) => {
+                        error!("error polling txpool content: {}", e);
+                        Vec::new()
+                    }
+                };
+                Some((iter(new_txs), (ticker, seen)))
+            },
+        )
+        .flatten();
 
-        let pending_tx = iter(pending_txs).boxed();
-        
+        Ok(Box::pin(stream))
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn tx(hash: u64) -> Transaction {
+        Transaction {
+            hash: H256::from_low_u64_be(hash),
+            ..Default::default()
+        }
+    }
 
-        Ok(pending_tx)
+    fn hashes(txs: &[Transaction]) -> Vec<H256> {
+        txs.iter().map(|tx| tx.hash).collect()
+    }
 
+    #[test]
+    fn diff_pending_emits_only_new_transactions() {
+        let mut seen = HashSet::new();
+        let first = diff_pending(&mut seen, vec![tx(1), tx(2)]);
+        assert_eq!(hashes(&first), hashes(&[tx(1), tx(2)]));
+
+        let second = diff_pending(&mut seen, vec![tx(2), tx(3)]);
+        assert_eq!(hashes(&second), hashes(&[tx(3)]));
+
+        // tx 1 left the pool, so it is emitted again if it comes back.
+        let third = diff_pending(&mut seen, vec![tx(1), tx(3)]);
+        assert_eq!(hashes(&third), hashes(&[tx(1)]));
     }
-}
\ No newline at end of file
+}
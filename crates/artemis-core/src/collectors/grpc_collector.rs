@@ -0,0 +1,199 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use futures::stream;
+use serde::de::DeserializeOwned;
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{error, info};
+
+/// The protobuf messages and service of the [GrpcCollector](GrpcCollector), generated
+/// from `proto/events.proto`, including a client external systems can push events with.
+pub mod proto {
+    tonic::include_proto!("artemis.v1");
+}
+
+use proto::event_ingestion_server::{EventIngestion, EventIngestionServer};
+use proto::{EventEnvelope, PushResponse};
+
+/// Default capacity of the channel of pushed events not yet collected.
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Decodes the event of an envelope.
+type Decoder<E> = Arc<dyn Fn(&EventEnvelope) -> anyhow::Result<E> + Send + Sync>;
+
+/// A collector exposing the `EventIngestion` gRPC service, through which external
+/// systems, e.g. custom indexers or signal services, push events into the engine.
+/// Events are wrapped in an [EventEnvelope](EventEnvelope), whose payload is decoded
+/// into an event by the collector. Pushes of events which can't be decoded fail with
+/// `INVALID_ARGUMENT`, and pushes the engine can't keep up with wait for it.
+pub struct GrpcCollector<E> {
+    listener: Arc<TcpListener>,
+    decode: Decoder<E>,
+    channel_capacity: usize,
+}
+
+impl<E> GrpcCollector<E> {
+    /// Serve on `addr`, decoding the envelopes pushed with `decode`.
+    pub async fn bind(
+        addr: SocketAddr,
+        decode: impl Fn(&EventEnvelope) -> anyhow::Result<E> + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await.map_err(anyhow::Error::from)?;
+        Ok(Self {
+            listener: Arc::new(listener),
+            decode: Arc::new(decode),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+        })
+    }
+
+    /// Buffer up to `capacity` pushed events before pushes wait for the engine.
+    pub fn with_channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity.max(1);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr().map_err(anyhow::Error::from)?)
+    }
+}
+
+impl<E: DeserializeOwned> GrpcCollector<E> {
+    /// Serve on `addr`, decoding the payloads of envelopes as JSON.
+    pub async fn bind_json(addr: SocketAddr) -> Result<Self> {
+        Self::bind(addr, |envelope| {
+            Ok(serde_json::from_slice(&envelope.payload)?)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl<E: Send + Sync + 'static> Collector<E> for GrpcCollector<E> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let (sender, receiver) = mpsc::channel(self.channel_capacity);
+        let closed = sender.clone();
+        let service = EventIngestionServer::new(Ingestion {
+            decode: self.decode.clone(),
+            events: sender,
+        });
+        let incoming = stream::unfold(self.listener.clone(), |listener| async move {
+            let connection = listener.accept().await.map(|(stream, _)| stream);
+            Some((connection, listener))
+        });
+        info!(
+            "serving event ingestion on {:?}",
+            self.listener.local_addr()
+        );
+        tokio::spawn(async move {
+            // Stop serving once the stream of events is dropped.
+            let served = Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(incoming, closed.closed())
+                .await;
+            if let Err(e) = served {
+                error!("event ingestion server failed: {}", e);
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(receiver)))
+    }
+}
+
+struct Ingestion<E> {
+    decode: Decoder<E>,
+    events: mpsc::Sender<E>,
+}
+
+impl<E> Ingestion<E> {
+    async fn ingest(&self, envelope: EventEnvelope) -> std::result::Result<(), Status> {
+        let event = (self.decode)(&envelope).map_err(|e| {
+            Status::invalid_argument(format!(
+                "invalid {} event from {}: {}",
+                envelope.kind, envelope.source, e
+            ))
+        })?;
+        self.events
+            .send(event)
+            .await
+            .map_err(|_| Status::unavailable("engine stopped collecting events"))
+    }
+}
+
+#[tonic::async_trait]
+impl<E: Send + 'static> EventIngestion for Ingestion<E> {
+    async fn push(
+        &self,
+        request: Request<EventEnvelope>,
+    ) -> std::result::Result<Response<PushResponse>, Status> {
+        self.ingest(request.into_inner()).await?;
+        Ok(Response::new(PushResponse { accepted: 1 }))
+    }
+
+    async fn push_stream(
+        &self,
+        request: Request<Streaming<EventEnvelope>>,
+    ) -> std::result::Result<Response<PushResponse>, Status> {
+        let mut envelopes = request.into_inner();
+        let mut accepted = 0;
+        while let Some(envelope) = envelopes.message().await? {
+            self.ingest(envelope).await?;
+            accepted += 1;
+        }
+        Ok(Response::new(PushResponse { accepted }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::event_ingestion_client::EventIngestionClient;
+    use serde::Deserialize;
+    use tokio_stream::StreamExt;
+    use tonic::Code;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Signal {
+        pool: String,
+        score: u32,
+    }
+
+    fn envelope(payload: &str) -> EventEnvelope {
+        EventEnvelope {
+            source: String::from("ml-signals"),
+            kind: String::from("signal"),
+            observed_at_ms: 0,
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn collects_pushed_events() {
+        let collector = GrpcCollector::<Signal>::bind_json("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let url = format!("http://{}", collector.local_addr().unwrap());
+        let events = collector.get_event_stream().await.unwrap();
+        let mut client = EventIngestionClient::connect(url).await.unwrap();
+
+        let pushed = client
+            .push(envelope(r#"{"pool":"0x01","score":7}"#))
+            .await
+            .unwrap();
+        assert_eq!(pushed.into_inner().accepted, 1);
+        let invalid = client.push(envelope("not json")).await.unwrap_err();
+        assert_eq!(invalid.code(), Code::InvalidArgument);
+
+        let batch = futures::stream::iter(vec![
+            envelope(r#"{"pool":"0x02","score":1}"#),
+            envelope(r#"{"pool":"0x03","score":2}"#),
+        ]);
+        let pushed = client.push_stream(batch).await.unwrap();
+        assert_eq!(pushed.into_inner().accepted, 2);
+
+        let scores: Vec<_> = events.take(3).map(|e| e.score).collect().await;
+        assert_eq!(scores, vec![7, 1, 2]);
+    }
+}
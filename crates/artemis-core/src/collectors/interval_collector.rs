@@ -0,0 +1,175 @@
+use std::{str::FromStr, time::Duration, time::SystemTime};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use futures::stream;
+use tokio::time::{interval_at, sleep, Instant, MissedTickBehavior};
+
+/// An event emitted every time a schedule of an [IntervalCollector] fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tick {
+    /// Name of the schedule, e.g. `refresh-reserves`.
+    pub name: String,
+    /// Number of times the schedule fired, including this one.
+    pub count: u64,
+    /// The time the schedule fired at.
+    pub at: SystemTime,
+}
+
+/// When a named schedule fires.
+#[derive(Debug, Clone)]
+enum Trigger {
+    Interval(Duration),
+    Cron(Box<Schedule>),
+}
+
+/// A collector which emits [Tick] events on a set of named schedules, either fixed
+/// intervals or cron expressions, so strategies can do periodic work such as refreshing
+/// reserves, sweeping profits or rotating endpoints from within the engine.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalCollector {
+    schedules: Vec<(String, Trigger)>,
+}
+
+impl IntervalCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tick `name` every `period`, starting one period after the event stream is
+    /// opened. Ticks missed while the engine is busy are skipped rather than bunched.
+    pub fn with_interval(mut self, name: impl Into<String>, period: Duration) -> Self {
+        self.schedules
+            .push((name.into(), Trigger::Interval(period)));
+        self
+    }
+
+    /// Tick `name` on the cron `expression`, in UTC, e.g. `0 */5 * * * *` every five
+    /// minutes. Expressions may start with a seconds field and end with a years field;
+    /// expressions of five fields fire at second 0 of the minutes they match.
+    pub fn with_cron(mut self, name: impl Into<String>, expression: &str) -> anyhow::Result<Self> {
+        let schedule = parse_cron(expression)?;
+        self.schedules
+            .push((name.into(), Trigger::Cron(Box::new(schedule))));
+        Ok(self)
+    }
+}
+
+/// Parse a cron expression, with or without a seconds field.
+fn parse_cron(expression: &str) -> anyhow::Result<Schedule> {
+    let expression = match expression.split_whitespace().count() {
+        5 => format!("0 {}", expression),
+        _ => expression.to_string(),
+    };
+    Schedule::from_str(&expression)
+        .with_context(|| format!("Invalid cron expression {:?}", expression))
+}
+
+/// Returns the next time `schedule` fires strictly after both the time it last fired
+/// at and `now`, so a late timer neither fires a tick twice nor catches up on missed
+/// ones.
+fn next_fire(
+    schedule: &Schedule,
+    last: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    schedule.after(&last.max(now)).next()
+}
+
+/// Returns the stream of ticks of the schedule `name`.
+fn ticks(name: String, trigger: Trigger) -> CollectorStream<'static, Tick> {
+    match trigger {
+        Trigger::Interval(period) => {
+            let mut timer = interval_at(Instant::now() + period, period);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let stream = stream::unfold((timer, 0), move |(mut timer, count)| {
+                let name = name.clone();
+                async move {
+                    timer.tick().await;
+                    let tick = Tick {
+                        name,
+                        count: count + 1,
+                        at: SystemTime::now(),
+                    };
+                    Some((tick, (timer, count + 1)))
+                }
+            });
+            Box::pin(stream)
+        }
+        Trigger::Cron(schedule) => {
+            let stream = stream::unfold((Utc::now(), 0), move |(last, count)| {
+                let (name, schedule) = (name.clone(), schedule.clone());
+                async move {
+                    let next = next_fire(&schedule, last, Utc::now())?;
+                    sleep((next - Utc::now()).to_std().unwrap_or_default()).await;
+                    let tick = Tick {
+                        name,
+                        count: count + 1,
+                        at: next.into(),
+                    };
+                    Some((tick, (next, count + 1)))
+                }
+            });
+            Box::pin(stream)
+        }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [IntervalCollector](IntervalCollector). Ticks of every schedule are merged into a
+/// single stream. Cron schedules which won't fire again end their part of the stream.
+#[async_trait]
+impl Collector<Tick> for IntervalCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Tick>> {
+        let streams = self
+            .schedules
+            .iter()
+            .map(|(name, trigger)| ticks(name.clone(), trigger.clone()));
+        Ok(Box::pin(stream::select_all(streams)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn ticks_every_interval() {
+        let collector = IntervalCollector::new()
+            .with_interval("fast", Duration::from_millis(20))
+            .with_interval("slow", Duration::from_secs(60));
+        let stream = collector.get_event_stream().await.unwrap();
+        let ticks: Vec<_> = stream.take(3).collect().await;
+
+        let names: Vec<_> = ticks.iter().map(|tick| tick.name.as_str()).collect();
+        assert_eq!(names, vec!["fast"; 3]);
+        let counts: Vec<_> = ticks.iter().map(|tick| tick.count).collect();
+        assert_eq!(counts, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn fires_cron_schedules_once_per_match() {
+        let schedule = parse_cron("*/5 * * * *").unwrap();
+        let at = |minute, second| {
+            Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, second)
+                .unwrap()
+        };
+
+        assert_eq!(next_fire(&schedule, at(0, 0), at(3, 0)), Some(at(5, 0)));
+        // A timer firing slightly early doesn't fire the same tick again.
+        assert_eq!(next_fire(&schedule, at(5, 0), at(4, 59)), Some(at(10, 0)));
+        // Ticks missed while late are skipped.
+        assert_eq!(next_fire(&schedule, at(5, 0), at(17, 0)), Some(at(20, 0)));
+
+        assert!(parse_cron("0 0 12 * * Mon-Fri").is_ok());
+        assert!(IntervalCollector::new()
+            .with_cron("sweep", "every tuesday")
+            .is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use ethers::{
+    abi::{self, Token},
+    prelude::Middleware,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest, U256, U64,
+    },
+};
+use futures::stream;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::warn;
+
+/// How often balances are read by default.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Selector of the ERC20 `balanceOf(address)` function.
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// An event carrying the balances of the bot wallet and the inventory of the arb
+/// contract, as of `block`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InventoryUpdate {
+    pub block: U64,
+    pub wallet: Address,
+    /// ETH balance of the wallet, which pays for gas.
+    pub wallet_eth: U256,
+    /// WETH balance of the wallet.
+    pub wallet_weth: U256,
+    pub contract: Address,
+    /// ETH balance of the contract.
+    pub contract_eth: U256,
+    /// Balance of the contract in each watched token, WETH included.
+    pub contract_tokens: HashMap<Address, U256>,
+}
+
+impl InventoryUpdate {
+    /// Returns the balance of the contract in `token`, zero if it isn't watched.
+    pub fn contract_balance(&self, token: Address) -> U256 {
+        self.contract_tokens
+            .get(&token)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// A collector that periodically reads the ETH and WETH balances of the bot wallet,
+/// and the balances of the arb contract in a set of tokens, emitting an
+/// [InventoryUpdate](InventoryUpdate) event after every read. Strategies can use it
+/// to stop bidding when underfunded, or to withdraw profits. Reads which fail are
+/// logged and skipped.
+pub struct InventoryCollector<M> {
+    provider: Arc<M>,
+    wallet: Address,
+    contract: Address,
+    weth: Address,
+    tokens: Vec<Address>,
+    poll_interval: Duration,
+}
+
+impl<M> InventoryCollector<M> {
+    /// Watch the balances of `wallet`, and the inventory of `contract` in `weth`.
+    pub fn new(provider: Arc<M>, wallet: Address, contract: Address, weth: Address) -> Self {
+        Self {
+            provider,
+            wallet,
+            contract,
+            weth,
+            tokens: vec![],
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Also watch the balance of the contract in `token`.
+    pub fn with_token(mut self, token: Address) -> Self {
+        if token != self.weth && !self.tokens.contains(&token) {
+            self.tokens.push(token);
+        }
+        self
+    }
+
+    /// Read balances every `poll_interval`. Defaults to 12 seconds, about once a block.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+impl<M: Middleware> InventoryCollector<M>
+where
+    M::Error: 'static,
+{
+    /// Read the balances of the wallet and the contract as of the latest block.
+    pub async fn read(&self) -> anyhow::Result<InventoryUpdate> {
+        let block = self.provider.get_block_number().await?;
+        let mut update = InventoryUpdate {
+            block,
+            wallet: self.wallet,
+            wallet_eth: self.eth_balance(self.wallet, block).await?,
+            wallet_weth: self.token_balance(self.weth, self.wallet, block).await?,
+            contract: self.contract,
+            contract_eth: self.eth_balance(self.contract, block).await?,
+            contract_tokens: HashMap::new(),
+        };
+        for token in std::iter::once(self.weth).chain(self.tokens.iter().copied()) {
+            let balance = self.token_balance(token, self.contract, block).await?;
+            update.contract_tokens.insert(token, balance);
+        }
+        Ok(update)
+    }
+
+    async fn eth_balance(&self, owner: Address, block: U64) -> anyhow::Result<U256> {
+        Ok(self.provider.get_balance(owner, Some(block.into())).await?)
+    }
+
+    async fn token_balance(
+        &self,
+        token: Address,
+        owner: Address,
+        block: U64,
+    ) -> anyhow::Result<U256> {
+        let mut data = BALANCE_OF_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::Address(owner)]));
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(token)
+            .data(Bytes::from(data))
+            .into();
+        let output = self.provider.call(&tx, Some(block.into())).await?;
+        if output.len() < 32 {
+            anyhow::bail!("invalid balanceOf output from token {:?}", token);
+        }
+        Ok(U256::from_big_endian(&output[..32]))
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [InventoryCollector](InventoryCollector).
+#[async_trait]
+impl<M> Collector<InventoryUpdate> for InventoryCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, InventoryUpdate>> {
+        let mut poll = interval(self.poll_interval);
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let stream = stream::unfold(poll, move |mut poll| async move {
+            loop {
+                poll.tick().await;
+                match self.read().await {
+                    Ok(update) => return Some((update, poll)),
+                    Err(e) => warn!("Error reading inventory: {:#}", e),
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{MockProvider, Provider};
+    use tokio_stream::StreamExt;
+
+    fn balance_output(balance: u64) -> Bytes {
+        Bytes::from(abi::encode(&[Token::Uint(balance.into())]))
+    }
+
+    #[tokio::test]
+    async fn reads_wallet_and_contract_balances() {
+        let (provider, mock) = Provider::mocked();
+        let weth = Address::from_low_u64_be(1);
+        let token = Address::from_low_u64_be(2);
+        let collector = InventoryCollector::new(
+            Arc::new(provider),
+            Address::from_low_u64_be(10),
+            Address::from_low_u64_be(20),
+            weth,
+        )
+        .with_token(token)
+        .with_token(weth)
+        .with_poll_interval(Duration::from_millis(10));
+
+        // The mock answers requests last pushed first.
+        push_all(
+            &mock,
+            &[
+                serde_json::json!(U64::from(100)),
+                serde_json::json!(U256::from(1)),
+                serde_json::json!(balance_output(2)),
+                serde_json::json!(U256::from(3)),
+                serde_json::json!(balance_output(4)),
+                serde_json::json!(balance_output(5)),
+            ],
+        );
+        let mut stream = collector.get_event_stream().await.unwrap();
+        let update = stream.next().await.unwrap();
+        assert_eq!(update.block, U64::from(100));
+        assert_eq!(update.wallet_eth, U256::from(1));
+        assert_eq!(update.wallet_weth, U256::from(2));
+        assert_eq!(update.contract_eth, U256::from(3));
+        assert_eq!(update.contract_balance(weth), U256::from(4));
+        assert_eq!(update.contract_balance(token), U256::from(5));
+        assert_eq!(update.contract_tokens.len(), 2);
+        assert_eq!(
+            update.contract_balance(Address::from_low_u64_be(3)),
+            U256::zero()
+        );
+    }
+
+    fn push_all(mock: &MockProvider, responses: &[serde_json::Value]) {
+        for response in responses.iter().rev() {
+            mock.push::<serde_json::Value, _>(response.clone()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use crate::utilities::router_classifier::{LikelySwap, RouterClassifier};
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use matchmaker::types::Hint;
+
+/// A collector which wraps a MEV-Share hint collector, such as a
+/// [MevShareCollector](super::mevshare_collector::MevShareCollector), and generates the
+/// [swaps](LikelySwap) the hinted transactions likely make through known routers, as
+/// classified by a [RouterClassifier]. Hints calling no known router generate nothing.
+pub struct LikelySwapCollector {
+    inner: Box<dyn Collector<Hint>>,
+    classifier: RouterClassifier,
+}
+
+impl LikelySwapCollector {
+    pub fn new(inner: Box<dyn Collector<Hint>>) -> Self {
+        Self {
+            inner,
+            classifier: RouterClassifier::default(),
+        }
+    }
+
+    /// Classify hints with `classifier`, instead of the default one.
+    pub fn with_classifier(mut self, classifier: RouterClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [LikelySwapCollector](LikelySwapCollector).
+#[async_trait]
+impl Collector<LikelySwap> for LikelySwapCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, LikelySwap>> {
+        let stream = self.inner.get_event_stream().await?;
+        let stream = stream.flat_map(move |hint| stream::iter(self.classifier.classify(&hint)));
+        Ok(Box::pin(stream))
+    }
+}
@@ -1,5 +1,5 @@
+use crate::error::{ArtemisError, Result};
 use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
 use async_trait::async_trait;
 use ethers::{
     prelude::Middleware,
@@ -32,7 +32,11 @@ where
     M::Error: 'static,
 {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Log>> {
-        let stream = self.provider.subscribe_logs(&self.filter).await?;
+        let stream = self
+            .provider
+            .subscribe_logs(&self.filter)
+            .await
+            .map_err(ArtemisError::from_middleware)?;
         let stream = stream.filter_map(Some);
         Ok(Box::pin(stream))
     }
@@ -1,32 +1,102 @@
-use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream, RestartableCollector};
+use crate::utilities::dedup_cache::DedupCache;
 use async_trait::async_trait;
-use mev_share::sse::{Event, EventClient};
+use futures::stream::select_all;
+use matchmaker::{events::EventClient, types::Hint};
 use tokio_stream::StreamExt;
+use tracing::warn;
 
 /// A collector that streams from MEV-Share SSE endpoint
-/// and generates [events](Event), which return tx hash, logs, and bundled txs.
+/// and generates [hints](Hint), which return tx hash, logs, bundled txs, gas used and
+/// mev gas price, as far as they are shared.
+///
+/// The collector can stream from several endpoints at once, e.g. the redundant hosts of
+/// the matchmaker, so hints keep flowing while one of them flaps. Each hint is emitted
+/// as soon as the first endpoint delivers it, and its copies from the other endpoints
+/// are dropped.
 pub struct MevShareCollector {
-    mevshare_sse_url: String,
+    mevshare_sse_urls: Vec<String>,
 }
 
 impl MevShareCollector {
     pub fn new(mevshare_sse_url: String) -> Self {
-        Self { mevshare_sse_url }
+        Self {
+            mevshare_sse_urls: vec![mevshare_sse_url],
+        }
+    }
+
+    /// Also stream hints from `mevshare_sse_url`.
+    pub fn with_url(mut self, mevshare_sse_url: String) -> Self {
+        self.mevshare_sse_urls.push(mevshare_sse_url);
+        self
     }
 }
 
 /// Implementation of the [Collector](Collector) trait for the
-/// [MevShareCollector](MevShareCollector).
+/// [MevShareCollector](MevShareCollector). The streams reconnect if their connection
+/// drops; connection and parse errors are logged and skipped. Hints are deduplicated
+/// by hash.
 #[async_trait]
-impl Collector<Event> for MevShareCollector {
-    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
-        let client = EventClient::default();
-        let stream = client.events(&self.mevshare_sse_url).await.unwrap();
-        let stream = stream.filter_map(|event| match event {
-            Ok(evt) => Some(evt),
-            Err(_) => None,
+impl Collector<Hint> for MevShareCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Hint>> {
+        let subscriptions = self.mevshare_sse_urls.iter().map(|url| {
+            let subscription = EventClient::from_url(url).subscribe();
+            Box::pin(subscription.map(move |event| (url, event)))
+        });
+        let seen = DedupCache::default();
+        let stream = select_all(subscriptions).filter_map(move |(url, event)| match event {
+            Ok(hint) => seen.insert(hint.hash).then_some(hint),
+            Err(e) => {
+                warn!("Error receiving mev-share event from {}: {}", url, e);
+                None
+            }
         });
         Ok(Box::pin(stream))
     }
 }
+
+/// Every stream subscribes to the SSE endpoint over a connection of its own, so a
+/// stalled stream is restarted by subscribing again.
+impl RestartableCollector<Hint> for MevShareCollector {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    /// Serves every connection the hints with the given hash bytes, then closes it.
+    async fn serve_hints(hashes: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let body: String = hashes
+                    .iter()
+                    .map(|byte| {
+                        format!("data: {{\"hash\":\"{:?}\"}}\n\n", H256::repeat_byte(*byte))
+                    })
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn deduplicates_hints_across_endpoints() {
+        let collector = MevShareCollector::new(serve_hints(vec![1, 2, 3]).await)
+            .with_url(serve_hints(vec![2, 3, 4]).await);
+        let stream = collector.get_event_stream().await.unwrap();
+        let mut hashes: Vec<_> = stream.take(4).map(|hint| hint.hash.0[0]).collect().await;
+        hashes.sort();
+        assert_eq!(hashes, vec![1, 2, 3, 4]);
+    }
+}
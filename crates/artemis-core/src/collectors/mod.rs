@@ -2,18 +2,93 @@
 //! turning them into internal events. For example, a collector might listen to
 //! a stream of new blocks, and turn them into a stream of `NewBlock` events.
 
+/// This collector listens to a stream of new blocks through an alloy provider.
+#[cfg(feature = "alloy")]
+pub mod alloy_block_collector;
+
+/// This collector listens to a stream of new pending transactions through an alloy
+/// provider.
+#[cfg(feature = "alloy")]
+pub mod alloy_mempool_collector;
+
+/// This collector reads the proposers of upcoming beacon chain slots, and whether
+/// they run MEV-boost.
+pub mod beacon_collector;
+
 /// This collector listens to a stream of new blocks.
 pub mod block_collector;
 
+/// This collector traces new blocks, and emits the arbs and backruns searchers landed
+/// through watched pools.
+pub mod block_trace_collector;
+
+/// This collector polls the status of sent MEV-share bundles, from being received
+/// by the relay to landing on chain.
+pub mod bundle_status_collector;
+
+/// This collector watches a config file, emitting the new parameters of
+/// reconfigurable strategies when it changes.
+pub mod config_collector;
+
+/// This collector deduplicates the events of a wrapped collector across the instances
+/// of a bot.
+pub mod coordinated_collector;
+
+/// This collector decodes the swaps in the logs of hints from a wrapped MEV-Share
+/// collector.
+pub mod decoded_hint_collector;
+
+/// This collector simulates pending transactions from a wrapped collector, and
+/// attaches the touched pools and token transfers.
+pub mod enriched_mempool_collector;
+
+/// This collector exposes a gRPC service through which external systems push events
+/// into the engine.
+#[cfg(feature = "grpc")]
+pub mod grpc_collector;
+
+/// This collector emits ticks on fixed intervals or cron schedules, for periodic
+/// work in strategies.
+pub mod interval_collector;
+
+/// This collector periodically reads the balances of the bot wallet and the
+/// inventory of the arb contract.
+pub mod inventory_collector;
+
+/// This collector classifies the hints from a wrapped MEV-Share collector calling known
+/// routers, emitting the swaps they likely make.
+pub mod likely_swap_collector;
+
 /// This collector listens to a stream of new event logs.
 pub mod log_collector;
 
 /// This collector listens to a stream of new pending transactions.
 pub mod mempool_collector;
 
-/// This collector listens to a stream of new Opensea orders.
+/// These collectors listen to L2 sequencer feeds, emitting transactions before
+/// they are confirmed on L1.
+pub mod sequencer_feed_collector;
+
+/// This collector listens to a stream of new Opensea listings, optionally
+/// filtered by collection and listing kind.
 pub mod opensea_order_collector;
 
+/// This collector receives the events or actions other processes publish to a message
+/// queue.
+pub mod queue_collector;
+
+/// This collector re-establishes the stream of a wrapped collector across
+/// disconnects, failing over between node endpoints.
+pub mod reconnecting_collector;
+
+/// This collector replays events previously recorded to a file, recorded by wrapping
+/// another collector in a recording collector.
+pub mod replay_collector;
+
+/// This collector receives authenticated webhooks, converting their JSON payloads into
+/// events.
+pub mod webhook_collector;
+
 //This collector listens to a stream of from MEV-Share SSE endpoint 
 //(backrunnable events which apply to this project )
 pub mod mevshare_collector;
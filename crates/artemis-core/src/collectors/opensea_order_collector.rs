@@ -1,24 +1,202 @@
+use std::time::Duration;
+
+use crate::error::Result;
 use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
 use async_trait::async_trait;
-use opensea_stream::{
-    client,
-    schema::{self, ItemListedData},
-    subscribe_to, Collection, Network,
-};
-use tokio_stream::wrappers::BroadcastStream;
-use tokio_stream::StreamExt;
-
-/// A collector that listens for new orders on OpenSea, and generates a stream of
-/// [events](OpenseaOrder) which contain the order.
-#[derive(Default)]
+use ethers::types::{H160, H256, U256};
+use futures::{stream, SinkExt, StreamExt};
+use opensea_stream::schema::{self, Chain, ItemListedData};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::{interval_at, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error};
+
+/// Endpoint of the OpenSea Stream API on mainnet.
+pub const OPENSEA_STREAM_URL: &str = "wss://stream.openseabeta.com/socket/websocket";
+
+/// Interval of the heartbeats keeping the connection to the Stream API alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A collector that listens for new Seaport listings on the OpenSea Stream API, and
+/// generates a stream of [events](OpenseaOrder) which contain the order. Listings can be
+/// filtered by collection slug and by [kind](ListingKind).
 pub struct OpenseaOrderCollector {
     api_key: String,
+    url: String,
+    /// Collection slugs to subscribe to. Empty means all collections.
+    collections: Vec<String>,
+    /// Listing kinds to emit. Empty means all kinds.
+    listing_kinds: Vec<ListingKind>,
 }
 
 impl OpenseaOrderCollector {
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        Self {
+            api_key,
+            url: OPENSEA_STREAM_URL.to_string(),
+            collections: vec![],
+            listing_kinds: vec![],
+        }
+    }
+
+    /// Connect to the Stream API at `url` rather than the mainnet endpoint.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    pub fn with_collections(mut self, collections: Vec<String>) -> Self {
+        self.collections = collections;
+        self
+    }
+
+    pub fn with_listing_kinds(mut self, listing_kinds: Vec<ListingKind>) -> Self {
+        self.listing_kinds = listing_kinds;
+        self
+    }
+
+    /// Returns the channel topics to join, one per collection, or the wildcard topic
+    /// of every collection.
+    fn topics(&self) -> Vec<String> {
+        if self.collections.is_empty() {
+            return vec!["collection:*".to_string()];
+        }
+        self.collections
+            .iter()
+            .map(|slug| format!("collection:{}", slug))
+            .collect()
+    }
+
+    /// Returns true if listings of `kind` pass the listing kind filter.
+    fn emits(&self, kind: ListingKind) -> bool {
+        self.listing_kinds.is_empty() || self.listing_kinds.contains(&kind)
+    }
+
+    /// Join the channels of the configured collections, and return a stream of the
+    /// listings which pass the listing kind filter. Heartbeats are sent while the
+    /// stream is polled, and the stream ends when the connection closes.
+    async fn listing_stream(&self) -> Result<CollectorStream<'_, Listed>> {
+        let url = format!("{}?token={}&vsn=1.0.0", self.url, self.api_key);
+        let (mut socket, _) = connect_async(url.as_str()).await?;
+
+        let mut reference = 0u64;
+        for topic in self.topics() {
+            reference += 1;
+            let join = PhoenixMessage::new(topic, "phx_join", reference);
+            socket.send(Message::Text(join.to_text())).await?;
+        }
+
+        let heartbeats = interval_at(Instant::now() + HEARTBEAT_INTERVAL, HEARTBEAT_INTERVAL);
+        let stream = stream::unfold(
+            (socket, heartbeats, reference),
+            move |(mut socket, mut heartbeats, mut reference)| async move {
+                loop {
+                    tokio::select! {
+                        _ = heartbeats.tick() => {
+                            reference += 1;
+                            let heartbeat = PhoenixMessage::new("phoenix", "heartbeat", reference);
+                            if let Err(e) = socket.send(Message::Text(heartbeat.to_text())).await {
+                                error!("error sending heartbeat to the OpenSea stream: {}", e);
+                                return None;
+                            }
+                        }
+                        message = socket.next() => match message? {
+                            Ok(Message::Text(text)) => match parse_listing(&text) {
+                                Some(listed) if self.emits(listed.kind) => {
+                                    return Some((listed, (socket, heartbeats, reference)));
+                                }
+                                _ => {}
+                            },
+                            Ok(_) => {}
+                            Err(e) => {
+                                error!("error reading the OpenSea stream: {}", e);
+                                return None;
+                            }
+                        },
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A message of the Phoenix channels protocol the Stream API is served over.
+#[derive(Debug, Serialize, Deserialize)]
+struct PhoenixMessage {
+    topic: String,
+    event: String,
+    payload: Value,
+    #[serde(rename = "ref")]
+    reference: Option<String>,
+}
+
+impl PhoenixMessage {
+    fn new(topic: impl Into<String>, event: &str, reference: u64) -> Self {
+        Self {
+            topic: topic.into(),
+            event: event.to_string(),
+            payload: Value::Object(Default::default()),
+            reference: Some(reference.to_string()),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Fields of an `item_listed` payload which the stream schema doesn't cover.
+#[derive(Debug, Default, Deserialize)]
+struct SeaportFields {
+    listing_type: Option<String>,
+    protocol_address: Option<H160>,
+    protocol_data: Option<Value>,
+}
+
+/// A listing read from the stream, with its Seaport order.
+#[derive(Debug, Clone)]
+struct Listed {
+    listing: ItemListedData,
+    kind: ListingKind,
+    protocol_address: Option<H160>,
+    protocol_data: Option<Value>,
+}
+
+/// Parse a message of the stream, returning the listing if it is an `item_listed` event.
+fn parse_listing(text: &str) -> Option<Listed> {
+    let message: PhoenixMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            debug!("error parsing OpenSea stream message: {}", e);
+            return None;
+        }
+    };
+    if message.event != "item_listed" {
+        return None;
+    }
+    let fields: SeaportFields = message
+        .payload
+        .get("payload")
+        .and_then(|payload| serde_json::from_value(payload.clone()).ok())
+        .unwrap_or_default();
+    let event: schema::StreamEvent = match serde_json::from_value(message.payload) {
+        Ok(event) => event,
+        Err(e) => {
+            debug!("error parsing OpenSea listing: {}", e);
+            return None;
+        }
+    };
+    match event.payload {
+        schema::Payload::ItemListed(listing) => Some(Listed {
+            listing,
+            kind: ListingKind::from_listing_type(fields.listing_type.as_deref()),
+            protocol_address: fields.protocol_address,
+            protocol_data: fields.protocol_data,
+        }),
+        _ => None,
     }
 }
 
@@ -28,27 +206,228 @@ pub struct OpenseaOrder {
     pub listing: ItemListedData,
 }
 
+/// Whether a listing sells at a fixed price, or through an auction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingKind {
+    FixedPrice,
+    /// An auction whose price declines until the listing expires.
+    DutchAuction,
+    /// An auction selling to the highest bid.
+    EnglishAuction,
+}
+
+impl ListingKind {
+    /// Map the `listing_type` of a listing to its kind. Basic listings, which have no
+    /// listing type, sell at a fixed price.
+    fn from_listing_type(listing_type: Option<&str>) -> Self {
+        match listing_type {
+            Some("dutch") => ListingKind::DutchAuction,
+            Some("english") => ListingKind::EnglishAuction,
+            _ => ListingKind::FixedPrice,
+        }
+    }
+}
+
+/// A normalized listing event, with the fields NFT strategies usually act on. The full
+/// stream payload is kept in `listing`. The Seaport order needed to fulfill the listing
+/// is in `protocol_data` when the stream shares it, and can otherwise be fetched by
+/// `order_hash`.
+#[derive(Debug, Clone)]
+pub struct NftListing {
+    pub collection_slug: String,
+    pub chain: Chain,
+    pub nft_address: H160,
+    pub token_id: U256,
+    pub maker: H160,
+    /// Total price, in units of `payment_token`. The zero address means ETH.
+    pub price: U256,
+    pub payment_token: H160,
+    pub quantity: u64,
+    pub kind: ListingKind,
+    /// Unix timestamp, in seconds, after which the listing can no longer be filled.
+    pub expiration: u64,
+    pub is_private: bool,
+    pub order_hash: H256,
+    /// Address of the Seaport contract the order is fulfilled through, if shared.
+    pub protocol_address: Option<H160>,
+    /// Parameters and signature of the Seaport order, as shared by the stream.
+    pub protocol_data: Option<Value>,
+    pub listing: ItemListedData,
+}
+
+impl NftListing {
+    fn from_listed(listed: Listed) -> Self {
+        let listing = listed.listing;
+        Self {
+            collection_slug: listing.context.collection.slug.clone(),
+            chain: listing.context.item.nft_id.network.clone(),
+            nft_address: listing.context.item.nft_id.address,
+            token_id: listing.context.item.nft_id.id,
+            maker: listing.maker.address,
+            price: listing.base_price,
+            payment_token: listing.payment_token.address,
+            quantity: listing.quantity,
+            kind: listed.kind,
+            expiration: listing.expiration_date.timestamp().max(0) as u64,
+            is_private: listing.is_private,
+            order_hash: listing.order_hash,
+            protocol_address: listed.protocol_address,
+            protocol_data: listed.protocol_data,
+            listing,
+        }
+    }
+}
+
 /// Implementation of the [Collector](Collector) trait for the [OpenseaOrderCollector](OpenseaOrderCollector).
 #[async_trait]
 impl Collector<OpenseaOrder> for OpenseaOrderCollector {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, OpenseaOrder>> {
-        let mut client = client(Network::Mainnet, &self.api_key).await;
+        let stream = self.listing_stream().await?;
+        Ok(Box::pin(stream.map(|listed| OpenseaOrder {
+            listing: listed.listing,
+        })))
+    }
+}
 
-        let collection = Collection::All;
+/// Implementation of the [Collector](Collector) trait for the [OpenseaOrderCollector](OpenseaOrderCollector),
+/// which emits normalized [NftListing](NftListing) events.
+#[async_trait]
+impl Collector<NftListing> for OpenseaOrderCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, NftListing>> {
+        let stream = self.listing_stream().await?;
+        Ok(Box::pin(stream.map(NftListing::from_listed)))
+    }
+}
 
-        let (_, subscription) = subscribe_to(&mut client, collection).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let stream = BroadcastStream::new(subscription);
+    const MAKER: &str = "0x1111111111111111111111111111111111111111";
+    const NFT: &str = "0x2222222222222222222222222222222222222222";
+    const SEAPORT: &str = "0x00000000000000adc04c56bf30ac9d3c0aaf14dc";
 
-        let stream = stream.filter_map(|event| {
-            let event = event.ok()?.into_custom_payload()?;
-            if let schema::Payload::ItemListed(listing) = event.payload {
-                Some(OpenseaOrder { listing })
-            } else {
-                None
+    /// An `item_listed` message of the stream, of the given listing type.
+    fn item_listed(listing_type: Option<&str>) -> String {
+        serde_json::json!({
+            "topic": "collection:doodles-official",
+            "event": "item_listed",
+            "ref": null,
+            "payload": {
+                "event_type": "item_listed",
+                "sent_at": "2023-06-01T12:00:00.000000+00:00",
+                "payload": {
+                    "base_price": "1500000000000000000",
+                    "collection": { "slug": "doodles-official" },
+                    "event_timestamp": "2023-06-01T12:00:00.000000+00:00",
+                    "expiration_date": "2023-06-02T12:00:00.000000+00:00",
+                    "is_private": false,
+                    "item": {
+                        "chain": { "name": "ethereum" },
+                        "metadata": {
+                            "animation_url": null,
+                            "image_url": null,
+                            "metadata_url": null,
+                            "name": "Doodle #42"
+                        },
+                        "nft_id": format!("ethereum/{}/42", NFT),
+                        "permalink": format!("https://opensea.io/assets/ethereum/{}/42", NFT)
+                    },
+                    "listing_date": "2023-06-01T12:00:00.000000+00:00",
+                    "listing_type": listing_type,
+                    "maker": { "address": MAKER },
+                    "order_hash": format!("0x{}", "ab".repeat(32)),
+                    "payment_token": {
+                        "address": "0x0000000000000000000000000000000000000000",
+                        "decimals": 18,
+                        "eth_price": "1.000000000000000",
+                        "name": "Ether",
+                        "symbol": "ETH",
+                        "usd_price": "1900.000000000000000000"
+                    },
+                    "quantity": 1,
+                    "taker": null,
+                    "protocol_address": SEAPORT,
+                    "protocol_data": {
+                        "parameters": { "offerer": MAKER, "orderType": 0 },
+                        "signature": null
+                    }
+                }
             }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn maps_listing_types_to_kinds() {
+        assert_eq!(
+            ListingKind::from_listing_type(None),
+            ListingKind::FixedPrice
+        );
+        assert_eq!(
+            ListingKind::from_listing_type(Some("basic")),
+            ListingKind::FixedPrice
+        );
+        assert_eq!(
+            ListingKind::from_listing_type(Some("dutch")),
+            ListingKind::DutchAuction
+        );
+        assert_eq!(
+            ListingKind::from_listing_type(Some("english")),
+            ListingKind::EnglishAuction
+        );
+    }
+
+    #[test]
+    fn filters_by_collection_and_listing_kind() {
+        let collector = OpenseaOrderCollector::new("key".to_string());
+        assert_eq!(collector.topics(), vec!["collection:*"]);
+        assert!(collector.emits(ListingKind::EnglishAuction));
+
+        let collector = collector
+            .with_collections(vec!["doodles-official".to_string(), "azuki".to_string()])
+            .with_listing_kinds(vec![ListingKind::FixedPrice]);
+        assert_eq!(
+            collector.topics(),
+            vec!["collection:doodles-official", "collection:azuki"]
+        );
+        assert!(collector.emits(ListingKind::FixedPrice));
+        assert!(!collector.emits(ListingKind::DutchAuction));
+    }
+
+    #[test]
+    fn parses_only_item_listed_events() {
+        let listed = parse_listing(&item_listed(Some("dutch"))).unwrap();
+        assert_eq!(listed.kind, ListingKind::DutchAuction);
+
+        let reply = serde_json::json!({
+            "topic": "collection:doodles-official",
+            "event": "phx_reply",
+            "ref": "1",
+            "payload": { "status": "ok", "response": {} }
         });
+        assert!(parse_listing(&reply.to_string()).is_none());
+        assert!(parse_listing("not json").is_none());
+    }
 
-        Ok(Box::pin(stream))
+    #[test]
+    fn normalizes_listings() {
+        let listing = NftListing::from_listed(parse_listing(&item_listed(None)).unwrap());
+        assert_eq!(listing.collection_slug, "doodles-official");
+        assert_eq!(listing.nft_address, NFT.parse::<H160>().unwrap());
+        assert_eq!(listing.token_id, U256::from(42));
+        assert_eq!(listing.maker, MAKER.parse::<H160>().unwrap());
+        assert_eq!(listing.price, U256::from(1_500_000_000_000_000_000u64));
+        assert_eq!(listing.payment_token, H160::zero());
+        assert_eq!(listing.quantity, 1);
+        assert_eq!(listing.kind, ListingKind::FixedPrice);
+        assert_eq!(listing.expiration, 1_685_707_200);
+        assert!(!listing.is_private);
+        assert_eq!(listing.order_hash, H256::repeat_byte(0xab));
+        assert_eq!(listing.protocol_address, Some(SEAPORT.parse().unwrap()));
+        assert_eq!(
+            listing.protocol_data.unwrap()["parameters"]["offerer"],
+            MAKER
+        );
     }
 }
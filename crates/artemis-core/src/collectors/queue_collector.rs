@@ -0,0 +1,87 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::error::Result;
+use crate::transport::{Envelope, Transport};
+use crate::types::{Collector, CollectorStream};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+/// QueueCollector receives the events or actions another process published to a topic
+/// of a [Transport](Transport), e.g. through a
+/// [QueueExecutor](crate::executors::queue_executor::QueueExecutor), and emits their
+/// payloads. Messages which can't be decoded are skipped.
+pub struct QueueCollector<E> {
+    transport: Arc<dyn Transport>,
+    topic: String,
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E> QueueCollector<E> {
+    pub fn new(transport: Arc<dyn Transport>, topic: impl Into<String>) -> Self {
+        Self {
+            transport,
+            topic: topic.into(),
+            _event: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<E> for QueueCollector<E>
+where
+    E: DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let messages = self.transport.subscribe(&self.topic).await?;
+        let topic = self.topic.clone();
+        let stream = messages.filter_map(move |message| {
+            let envelope = serde_json::from_slice::<Envelope<E>>(&message);
+            let topic = topic.clone();
+            async move {
+                match envelope {
+                    Ok(envelope) => {
+                        debug!(
+                            source = %envelope.source,
+                            sequence = envelope.sequence,
+                            "received message from {} after {:?}",
+                            topic,
+                            envelope.age()
+                        );
+                        Some(envelope.payload)
+                    }
+                    Err(e) => {
+                        warn!("skipping undecodable message from {}: {}", topic, e);
+                        None
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{MemoryTransport, Publisher};
+
+    #[tokio::test]
+    async fn emits_payloads_and_skips_invalid_messages() {
+        let transport: Arc<dyn Transport> = Arc::new(MemoryTransport::new());
+        let collector = QueueCollector::<u64>::new(transport.clone(), "events");
+        let mut stream = collector.get_event_stream().await.unwrap();
+
+        let publisher = Publisher::new(transport.clone(), "events", "collectors");
+        publisher.publish(&1).await.unwrap();
+        transport
+            .publish("events", b"garbage".to_vec())
+            .await
+            .unwrap();
+        publisher.publish(&2).await.unwrap();
+
+        assert_eq!(stream.next().await, Some(1));
+        assert_eq!(stream.next().await, Some(2));
+    }
+}
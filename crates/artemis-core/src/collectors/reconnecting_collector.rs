@@ -0,0 +1,86 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::error::Result;
+use crate::{
+    types::{Collector, CollectorStream},
+    utilities::failover_provider::FailoverProvider,
+};
+use async_trait::async_trait;
+use ethers::providers::{Provider, Ws};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
+use tracing::{error, warn};
+
+/// Delay before the first reconnection attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Builds a collector on top of a freshly connected provider.
+pub type CollectorFactory<E> =
+    Arc<dyn Fn(Arc<Provider<Ws>>) -> Box<dyn Collector<E>> + Send + Sync>;
+
+/// A collector which wraps a provider-backed collector, and re-establishes its
+/// stream whenever it ends, failing over between the endpoints of a
+/// [FailoverProvider](FailoverProvider). The engine sees a single stream which
+/// keeps flowing across disconnects.
+pub struct ReconnectingCollector<E> {
+    provider: Arc<FailoverProvider>,
+    make_collector: CollectorFactory<E>,
+    max_backoff: Duration,
+}
+
+impl<E> ReconnectingCollector<E> {
+    pub fn new(provider: Arc<FailoverProvider>, make_collector: CollectorFactory<E>) -> Self {
+        Self {
+            provider,
+            make_collector,
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [ReconnectingCollector](ReconnectingCollector).
+/// The inner collector runs in a background task, which forwards events until the
+/// returned stream is dropped.
+#[async_trait]
+impl<E: Send + 'static> Collector<E> for ReconnectingCollector<E> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let provider = self.provider.clone();
+        let make_collector = self.make_collector.clone();
+        let max_backoff = self.max_backoff;
+
+        tokio::spawn(async move {
+            let mut backoff = INITIAL_BACKOFF;
+            while !sender.is_closed() {
+                match provider.connect_ws().await {
+                    Ok(ws) => {
+                        let collector = make_collector(Arc::new(ws));
+                        match collector.get_event_stream().await {
+                            Ok(mut stream) => {
+                                backoff = INITIAL_BACKOFF;
+                                while let Some(event) = stream.next().await {
+                                    if sender.send(event).is_err() {
+                                        return;
+                                    }
+                                }
+                                warn!("collector stream ended, reconnecting");
+                            }
+                            Err(e) => error!("error creating collector stream: {}", e),
+                        };
+                    }
+                    Err(e) => error!("error connecting provider: {}", e),
+                }
+                provider.rotate();
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(max_backoff);
+            }
+        });
+
+        Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+    }
+}
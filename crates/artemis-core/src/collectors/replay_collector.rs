@@ -0,0 +1,238 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+use tracing::warn;
+
+/// A single recorded event, as written to a replay file. Replay files are
+/// newline-delimited JSON, with one [RecordedEvent](RecordedEvent) per line, as
+/// written by a [RecordingCollector](RecordingCollector).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent<E> {
+    /// Unix timestamp (in milliseconds) at which the event was originally observed.
+    pub timestamp_ms: u64,
+    /// The recorded event.
+    pub event: E,
+}
+
+/// Controls the pace at which a [ReplayCollector](ReplayCollector) emits events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// Emit events with the same spacing as when they were recorded.
+    #[default]
+    Original,
+    /// Emit all events immediately, one after the other.
+    AsFastAsPossible,
+}
+
+/// A collector that reads recorded events from a file, and replays them as a
+/// stream of [events](RecordedEvent). This is useful for exercising the full
+/// engine pipeline deterministically in tests.
+pub struct ReplayCollector {
+    path: PathBuf,
+    speed: ReplaySpeed,
+}
+
+impl ReplayCollector {
+    pub fn new(path: impl Into<PathBuf>, speed: ReplaySpeed) -> Self {
+        Self {
+            path: path.into(),
+            speed,
+        }
+    }
+
+    /// Read and parse all recorded events from the replay file.
+    async fn read_events<E: DeserializeOwned>(&self) -> anyhow::Result<Vec<RecordedEvent<E>>> {
+        let contents = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("Error reading replay file {}", self.path.display()))?;
+
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Error parsing replay file line {}", idx + 1))
+            })
+            .collect()
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [ReplayCollector](ReplayCollector).
+/// Events are read eagerly when the stream is created, and emitted according to the
+/// configured [ReplaySpeed](ReplaySpeed).
+#[async_trait]
+impl<E> Collector<E> for ReplayCollector
+where
+    E: DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let events = self.read_events::<E>().await?;
+        let speed = self.speed;
+
+        let stream = stream::unfold(
+            (events.into_iter(), None::<u64>),
+            move |(mut events, last_timestamp)| async move {
+                let recorded = events.next()?;
+                if let (ReplaySpeed::Original, Some(last)) = (speed, last_timestamp) {
+                    let delay = recorded.timestamp_ms.saturating_sub(last);
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                }
+                Some((recorded.event, (events, Some(recorded.timestamp_ms))))
+            },
+        );
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A collector that records the events of another collector to a replay file as they
+/// are emitted, for a [ReplayCollector](ReplayCollector) to replay later. Events are
+/// appended to the file, and still emitted if they can't be recorded.
+pub struct RecordingCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    path: PathBuf,
+}
+
+impl<E> RecordingCollector<E> {
+    pub fn new(collector: Box<dyn Collector<E>>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            collector,
+            path: path.into(),
+        }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [RecordingCollector](RecordingCollector). Each event is written to the replay file
+/// before it is emitted.
+#[async_trait]
+impl<E> Collector<E> for RecordingCollector<E>
+where
+    E: Serialize + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Error opening replay file {}", self.path.display()))?;
+        let file = Arc::new(Mutex::new(file));
+        let stream = self.collector.get_event_stream().await?;
+
+        let stream = stream.then(move |event| {
+            let file = file.clone();
+            async move {
+                if let Err(e) = record(&file, &event).await {
+                    warn!("Error recording event: {}", e);
+                }
+                event
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Append `event` to the replay `file`, as a [RecordedEvent](RecordedEvent) observed now.
+async fn record<E: Serialize>(file: &Mutex<File>, event: &E) -> anyhow::Result<()> {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let mut line = serde_json::to_vec(&RecordedEvent {
+        timestamp_ms,
+        event,
+    })?;
+    line.push(b'\n');
+    let mut file = file.lock().await;
+    file.write_all(&line).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockCollector;
+    use tokio::time::Instant;
+
+    fn replay_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "artemis-replay-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn write_events(path: &PathBuf, timestamps_ms: &[u64]) {
+        let lines: Vec<String> = timestamps_ms
+            .iter()
+            .enumerate()
+            .map(|(event, timestamp_ms)| {
+                serde_json::to_string(&RecordedEvent {
+                    timestamp_ms: *timestamp_ms,
+                    event,
+                })
+                .unwrap()
+            })
+            .collect();
+        std::fs::write(path, lines.join("\n")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_events_with_their_original_spacing() {
+        let path = replay_file("original");
+        write_events(&path, &[1_000, 1_100, 1_300]);
+
+        let collector = ReplayCollector::new(&path, ReplaySpeed::Original);
+        let start = Instant::now();
+        let events: Vec<usize> = collector.get_event_stream().await.unwrap().collect().await;
+        assert_eq!(events, vec![0, 1, 2]);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_events_as_fast_as_possible() {
+        let path = replay_file("fast");
+        write_events(&path, &[1_000, 61_000, 121_000]);
+
+        let collector = ReplayCollector::new(&path, ReplaySpeed::AsFastAsPossible);
+        let start = Instant::now();
+        let events: Vec<usize> = collector.get_event_stream().await.unwrap().collect().await;
+        assert_eq!(events, vec![0, 1, 2]);
+        assert!(start.elapsed() < Duration::from_secs(60));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn records_events_for_replay() {
+        let path = replay_file("recorded");
+        let mock = MockCollector::new();
+        let sender = mock.sender();
+        let recorder = RecordingCollector::new(Box::new(mock), &path);
+        let mut stream = recorder.get_event_stream().await.unwrap();
+        for event in [3u64, 5, 8] {
+            sender.send(event).unwrap();
+            assert_eq!(stream.next().await, Some(event));
+        }
+        drop(stream);
+
+        let replay = ReplayCollector::new(&path, ReplaySpeed::AsFastAsPossible);
+        let events: Vec<u64> = replay.get_event_stream().await.unwrap().collect().await;
+        assert_eq!(events, vec![3, 5, 8]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
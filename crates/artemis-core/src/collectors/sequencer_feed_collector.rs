@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use crate::error::{ArtemisError, Result};
+use crate::types::{Collector, CollectorStream};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ethers::{
+    prelude::Middleware,
+    providers::PubsubClient,
+    types::{Chain, Transaction},
+    utils::rlp,
+};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error};
+
+/// Public Arbitrum One sequencer feed.
+pub const ARBITRUM_ONE_FEED_URL: &str = "wss://arb1.arbitrum.io/feed";
+
+/// L1 message kind of messages produced by the sequencer.
+const L1_MESSAGE_KIND_L2_MESSAGE: u8 = 3;
+/// L2 message kind of a batch of nested L2 messages.
+const L2_MESSAGE_KIND_BATCH: u8 = 3;
+/// L2 message kind of a single signed transaction.
+const L2_MESSAGE_KIND_SIGNED_TX: u8 = 4;
+/// Maximum nesting of batches, matching the Arbitrum node.
+const MAX_BATCH_DEPTH: usize = 16;
+
+/// A transaction ordered by an L2 sequencer, before it is confirmed on L1.
+#[derive(Debug, Clone)]
+pub struct SequencedTransaction {
+    pub chain: Chain,
+    /// Position in the sequencer feed for Arbitrum, or the unsafe block number for OP Stack chains.
+    pub sequence_number: u64,
+    pub tx: Transaction,
+}
+
+/// A collector that listens to the Arbitrum sequencer feed, and generates a stream of
+/// [events](SequencedTransaction) for every transaction the sequencer orders.
+pub struct ArbitrumFeedCollector {
+    url: String,
+    chain: Chain,
+}
+
+impl ArbitrumFeedCollector {
+    pub fn new(url: impl Into<String>, chain: Chain) -> Self {
+        Self {
+            url: url.into(),
+            chain,
+        }
+    }
+}
+
+impl Default for ArbitrumFeedCollector {
+    fn default() -> Self {
+        Self::new(ARBITRUM_ONE_FEED_URL, Chain::Arbitrum)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BroadcastMessage {
+    #[serde(default)]
+    messages: Vec<BroadcastFeedMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastFeedMessage {
+    sequence_number: u64,
+    message: MessageWithMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageWithMetadata {
+    message: L1IncomingMessage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L1IncomingMessage {
+    header: L1IncomingMessageHeader,
+    l2_msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct L1IncomingMessageHeader {
+    kind: u8,
+}
+
+/// Decode the signed transactions in an L2 message, unpacking nested batches.
+fn parse_l2_message(data: &[u8], depth: usize) -> Vec<Transaction> {
+    match data.split_first() {
+        Some((&L2_MESSAGE_KIND_SIGNED_TX, raw)) => match rlp::decode::<Transaction>(raw) {
+            Ok(mut tx) => {
+                // The sender isn't part of the encoding, so recover it from the signature.
+                if let Ok(from) = tx.recover_from() {
+                    tx.from = from;
+                }
+                vec![tx]
+            }
+            Err(e) => {
+                debug!("error decoding sequenced transaction: {}", e);
+                vec![]
+            }
+        },
+        Some((&L2_MESSAGE_KIND_BATCH, mut rest)) if depth < MAX_BATCH_DEPTH => {
+            // Each nested message is prefixed by its length as a big endian u64.
+            let mut txs = Vec::new();
+            while rest.len() >= 8 {
+                let (len, tail) = rest.split_at(8);
+                let len = u64::from_be_bytes(len.try_into().unwrap()) as usize;
+                if len > tail.len() {
+                    break;
+                }
+                let (message, tail) = tail.split_at(len);
+                txs.extend(parse_l2_message(message, depth + 1));
+                rest = tail;
+            }
+            txs
+        }
+        _ => vec![],
+    }
+}
+
+/// Decode the sequenced transactions in a feed message. Messages which aren't sequencer
+/// L2 messages, such as delayed inbox messages, are skipped.
+fn parse_feed_message(text: &str, chain: Chain) -> anyhow::Result<Vec<SequencedTransaction>> {
+    let broadcast: BroadcastMessage = serde_json::from_str(text)?;
+    let mut txs = Vec::new();
+    for feed_message in broadcast.messages {
+        let message = feed_message.message.message;
+        if message.header.kind != L1_MESSAGE_KIND_L2_MESSAGE {
+            continue;
+        }
+        let data = STANDARD
+            .decode(message.l2_msg)
+            .map_err(|e| anyhow!("invalid l2 message encoding: {}", e))?;
+        txs.extend(
+            parse_l2_message(&data, 0)
+                .into_iter()
+                .map(|tx| SequencedTransaction {
+                    chain,
+                    sequence_number: feed_message.sequence_number,
+                    tx,
+                }),
+        );
+    }
+    Ok(txs)
+}
+
+/// Implementation of the [Collector](Collector) trait for the [ArbitrumFeedCollector](ArbitrumFeedCollector).
+/// The stream ends when the feed connection closes.
+#[async_trait]
+impl Collector<SequencedTransaction> for ArbitrumFeedCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, SequencedTransaction>> {
+        let (socket, _) = connect_async(self.url.as_str()).await?;
+        let chain = self.chain;
+
+        let stream = socket
+            .filter_map(move |message| async move {
+                match message {
+                    Ok(Message::Text(text)) => match parse_feed_message(&text, chain) {
+                        Ok(txs) => Some(stream::iter(txs)),
+                        Err(e) => {
+                            error!("error parsing sequencer feed message: {}", e);
+                            None
+                        }
+                    },
+                    Ok(_) => None,
+                    Err(e) => {
+                        error!("error reading sequencer feed: {}", e);
+                        None
+                    }
+                }
+            })
+            .flatten();
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A collector that listens for unsafe blocks on an OP Stack chain, and generates a stream
+/// of [events](SequencedTransaction) for their transactions. Unsafe blocks are gossiped by
+/// the sequencer before their batch is posted to L1, so the provider should point at a node
+/// following the unsafe head.
+pub struct OpStackUnsafeBlockCollector<M> {
+    provider: Arc<M>,
+    chain: Chain,
+}
+
+impl<M> OpStackUnsafeBlockCollector<M> {
+    pub fn new(provider: Arc<M>, chain: Chain) -> Self {
+        Self { provider, chain }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [OpStackUnsafeBlockCollector](OpStackUnsafeBlockCollector).
+/// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new heads, and
+/// fetches each block with its transactions.
+#[async_trait]
+impl<M> Collector<SequencedTransaction> for OpStackUnsafeBlockCollector<M>
+where
+    M: Middleware,
+    M::Provider: PubsubClient,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, SequencedTransaction>> {
+        let stream = self
+            .provider
+            .subscribe_blocks()
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        let chain = self.chain;
+
+        let stream = stream
+            .filter_map(move |block| async move {
+                let hash = block.hash?;
+                let number = block.number?.as_u64();
+                match self.provider.get_block_with_txs(hash).await {
+                    Ok(Some(block)) => Some(stream::iter(block.transactions.into_iter().map(
+                        move |tx| SequencedTransaction {
+                            chain,
+                            sequence_number: number,
+                            tx,
+                        },
+                    ))),
+                    Ok(None) => None,
+                    Err(e) => {
+                        error!("error fetching unsafe block {}: {}", number, e);
+                        None
+                    }
+                }
+            })
+            .flatten();
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        signers::{LocalWallet, Signer},
+        types::{transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, H160},
+    };
+
+    async fn signed_tx(nonce: u64) -> (Vec<u8>, H160) {
+        let wallet = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(42161u64);
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(H160::from_low_u64_be(1))
+            .nonce(nonce)
+            .chain_id(42161u64)
+            .into();
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        (tx.rlp_signed(&signature).to_vec(), wallet.address())
+    }
+
+    #[tokio::test]
+    async fn parses_signed_txs_and_nested_batches() {
+        let (raw_a, from) = signed_tx(0).await;
+        let (raw_b, _) = signed_tx(1).await;
+
+        let mut message_a = vec![L2_MESSAGE_KIND_SIGNED_TX];
+        message_a.extend(&raw_a);
+        let mut message_b = vec![L2_MESSAGE_KIND_SIGNED_TX];
+        message_b.extend(&raw_b);
+
+        let mut batch = vec![L2_MESSAGE_KIND_BATCH];
+        for message in [&message_a, &message_b] {
+            batch.extend((message.len() as u64).to_be_bytes());
+            batch.extend(message);
+        }
+
+        let txs = parse_l2_message(&batch, 0);
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].from, from);
+        assert_eq!(txs[1].nonce, 1.into());
+    }
+
+    #[tokio::test]
+    async fn parses_feed_messages() {
+        let (raw, _) = signed_tx(0).await;
+        let mut message = vec![L2_MESSAGE_KIND_SIGNED_TX];
+        message.extend(&raw);
+
+        let text = format!(
+            r#"{{"version":1,"messages":[
+                {{"sequenceNumber":7,"message":{{"message":{{"header":{{"kind":3}},"l2Msg":"{}"}},"delayedMessagesRead":1}},"signature":null}},
+                {{"sequenceNumber":8,"message":{{"message":{{"header":{{"kind":12}},"l2Msg":""}},"delayedMessagesRead":2}},"signature":null}}
+            ]}}"#,
+            STANDARD.encode(&message)
+        );
+        let txs = parse_feed_message(&text, Chain::Arbitrum).unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].sequence_number, 7);
+        assert_eq!(txs[0].chain, Chain::Arbitrum);
+    }
+}
@@ -0,0 +1,257 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use crate::error::Result;
+use crate::types::{Collector, CollectorStream};
+use crate::utilities::http::{read_request, write_json_response};
+use async_trait::async_trait;
+use ethers::utils::hex;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::timeout,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, info, warn};
+
+/// Default header carrying the signature of a webhook.
+const DEFAULT_SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Default capacity of the channel of received events not yet collected.
+const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Time allowed to read a request and respond to it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Converts the JSON payload of a webhook into an event.
+type Converter<E> = Arc<dyn Fn(Value) -> anyhow::Result<E> + Send + Sync>;
+
+/// A collector receiving webhooks, e.g. alerts of an external depeg detector, and
+/// converting their JSON payloads into events. Webhooks are authenticated with an
+/// HMAC-SHA256 of their body keyed with a shared secret, sent hex encoded in the
+/// signature header as `sha256=<signature>`, the format GitHub and most alerting tools
+/// use. Webhooks are POSTed to any path, and answered with:
+///
+/// - `202 Accepted` once the event is queued for strategies.
+/// - `401 Unauthorized` if the signature is missing or invalid.
+/// - `400 Bad Request` if the payload isn't JSON or can't be converted into an event.
+pub struct WebhookCollector<E> {
+    listener: Arc<TcpListener>,
+    secret: Arc<Vec<u8>>,
+    convert: Converter<E>,
+    signature_header: String,
+    channel_capacity: usize,
+}
+
+impl<E> WebhookCollector<E> {
+    /// Serve on `addr`, authenticating webhooks with `secret` and converting their
+    /// payloads with `convert`.
+    pub async fn bind(
+        addr: SocketAddr,
+        secret: impl Into<Vec<u8>>,
+        convert: impl Fn(Value) -> anyhow::Result<E> + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await.map_err(anyhow::Error::from)?;
+        Ok(Self {
+            listener: Arc::new(listener),
+            secret: Arc::new(secret.into()),
+            convert: Arc::new(convert),
+            signature_header: String::from(DEFAULT_SIGNATURE_HEADER),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+        })
+    }
+
+    /// Read signatures from `header`. Defaults to `X-Signature-256`.
+    pub fn with_signature_header(mut self, header: impl Into<String>) -> Self {
+        self.signature_header = header.into();
+        self
+    }
+
+    /// Buffer up to `capacity` received events before webhooks wait for the engine.
+    pub fn with_channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity.max(1);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr().map_err(anyhow::Error::from)?)
+    }
+}
+
+impl<E: DeserializeOwned> WebhookCollector<E> {
+    /// Serve on `addr`, authenticating webhooks with `secret` and deserializing their
+    /// payloads into events.
+    pub async fn bind_json(addr: SocketAddr, secret: impl Into<Vec<u8>>) -> Result<Self> {
+        Self::bind(addr, secret, |payload| Ok(serde_json::from_value(payload)?)).await
+    }
+}
+
+/// Returns the signature of `body` with `secret`, as sent in the signature header.
+pub fn sign_webhook(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts any key size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether `signature` is the signature of `body` with `secret`, compared in constant
+/// time.
+fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts any key size");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[async_trait]
+impl<E: Send + Sync + 'static> Collector<E> for WebhookCollector<E> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let (sender, receiver) = mpsc::channel(self.channel_capacity);
+        let listener = self.listener.clone();
+        let webhook = Arc::new(Webhook {
+            secret: self.secret.clone(),
+            convert: self.convert.clone(),
+            signature_header: self.signature_header.clone(),
+            events: sender,
+        });
+        info!("serving webhooks on {:?}", listener.local_addr());
+        tokio::spawn(async move {
+            loop {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    // Stop serving once the stream of events is dropped.
+                    _ = webhook.events.closed() => break,
+                };
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        debug!("error accepting webhook connection: {}", e);
+                        continue;
+                    }
+                };
+                let webhook = webhook.clone();
+                tokio::spawn(async move {
+                    match timeout(REQUEST_TIMEOUT, webhook.handle(stream)).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => debug!("error serving webhook: {}", e),
+                        Err(_) => debug!("webhook timed out"),
+                    }
+                });
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(receiver)))
+    }
+}
+
+struct Webhook<E> {
+    secret: Arc<Vec<u8>>,
+    convert: Converter<E>,
+    signature_header: String,
+    events: mpsc::Sender<E>,
+}
+
+impl<E> Webhook<E> {
+    /// Respond to a single webhook.
+    async fn handle(&self, mut stream: TcpStream) -> anyhow::Result<()> {
+        let request = read_request(&mut stream).await?;
+        if request.method != "POST" {
+            return write_json_response(&mut stream, "405 Method Not Allowed", "{}").await;
+        }
+        let signature = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.signature_header))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        if !verify_signature(&self.secret, &request.body, signature) {
+            warn!(
+                "rejecting webhook to {} with invalid signature",
+                request.path
+            );
+            return write_json_response(&mut stream, "401 Unauthorized", "{}").await;
+        }
+        let event = serde_json::from_slice(&request.body)
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| (self.convert)(payload));
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                let body = serde_json::json!({ "error": e.to_string() }).to_string();
+                return write_json_response(&mut stream, "400 Bad Request", &body).await;
+            }
+        };
+        if self.events.send(event).await.is_err() {
+            return write_json_response(&mut stream, "503 Service Unavailable", "{}").await;
+        }
+        write_json_response(&mut stream, "202 Accepted", "{}").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_stream::StreamExt;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Depeg {
+        token: String,
+        price: f64,
+    }
+
+    async fn post(addr: SocketAddr, body: &str, signature: &str) -> String {
+        let request = format!(
+            "POST /depeg HTTP/1.1\r\nHost: localhost\r\nX-Signature-256: {}\r\n\
+             Content-Length: {}\r\n\r\n{}",
+            signature,
+            body.len(),
+            body
+        );
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn collects_authenticated_webhooks() {
+        let collector =
+            WebhookCollector::<Depeg>::bind_json("127.0.0.1:0".parse().unwrap(), "s3cret")
+                .await
+                .unwrap();
+        let addr = collector.local_addr().unwrap();
+        let mut events = collector.get_event_stream().await.unwrap();
+
+        let body = r#"{"token":"USDC","price":0.97}"#;
+        let forged = sign_webhook(b"guess", body.as_bytes());
+        assert!(post(addr, body, &forged).await.starts_with("HTTP/1.1 401"));
+        assert!(post(addr, body, "").await.starts_with("HTTP/1.1 401"));
+
+        let invalid = r#"{"token":"USDC"}"#;
+        let signature = sign_webhook(b"s3cret", invalid.as_bytes());
+        assert!(post(addr, invalid, &signature)
+            .await
+            .starts_with("HTTP/1.1 400"));
+
+        let signature = sign_webhook(b"s3cret", body.as_bytes());
+        assert!(post(addr, body, &signature)
+            .await
+            .starts_with("HTTP/1.1 202"));
+        assert_eq!(
+            events.next().await,
+            Some(Depeg {
+                token: String::from("USDC"),
+                price: 0.97
+            })
+        );
+    }
+}
@@ -1,19 +1,553 @@
-use tokio::sync::broadcast::{self, Sender};
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ethers::types::U64;
+use futures::FutureExt;
+use tokio::sync::broadcast::{self, error::RecvError, Sender};
+use tokio::sync::{watch, RwLock};
 use tokio::task::JoinSet;
+use tokio::time::sleep;
 use tokio_stream::StreamExt;
-use tracing::{error, info};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+use crate::admin::{AdminServer, EngineControl};
+use crate::collectors::block_collector::NewBlock;
+use crate::executors::noop_executor::NoopExecutor;
+use crate::health::{EngineHealth, HealthServer, StrategyHealth};
+use crate::reorg::{Invalidation, ReorgTracker};
+use crate::types::{
+    Collector, CollectorStream, ConcurrentStrategy, Executor, Expiry, RestartableCollector,
+    Strategy, SubmissionReceipt,
+};
+
+/// An event or action flowing through the engine, tagged with the id of the event
+/// it originated from and the time that event was collected. The id is attached to
+/// the tracing spans of every stage, so a single event can be followed end-to-end.
+#[derive(Debug, Clone)]
+struct Traced<T> {
+    event_id: u64,
+    collected_at: Instant,
+    inner: T,
+}
+
+impl<T> Traced<T> {
+    /// Whether the originating event was collected longer than `max_age` ago.
+    fn is_stale(&self, max_age: Option<Duration>) -> bool {
+        max_age.is_some_and(|max_age| self.collected_at.elapsed() > max_age)
+    }
+}
+
+/// Number of events in a row a strategy must take longer than the processing deadline
+/// to process before a warning is logged, so a single slow event isn't reported. The
+/// warning is logged once per run of slow events.
+const OVERRUNS_BEFORE_WARNING: u64 = 3;
+
+/// Turns the receipt of an executed action into an event.
+type ReceiptEvent<E> = Arc<dyn Fn(SubmissionReceipt) -> E + Send + Sync>;
+
+/// Returns the new block an event announces, if any.
+type BlockOf<E> = Arc<dyn Fn(&E) -> Option<NewBlock> + Send + Sync>;
+
+/// Turns the restart of a strategy into an event.
+type RestartEvent<E> = Arc<dyn Fn(StrategyRestart) -> E + Send + Sync>;
+
+/// Turns the restart of a stalled collector into an event.
+type CollectorRestartEvent<E> = Arc<dyn Fn(CollectorRestart) -> E + Send + Sync>;
+
+/// Turns the submissions invalidated by a reorg into an event.
+type InvalidationEvent<E> = Arc<dyn Fn(Invalidation) -> E + Send + Sync>;
+
+/// Returns when an action expires, if it does.
+type ExpiryOf<A> = Arc<dyn Fn(&A) -> Option<Expiry> + Send + Sync>;
+
+/// How the engine expires actions, and tracks the latest block to expire them by.
+struct ActionExpiry<E, A> {
+    expiry_of: ExpiryOf<A>,
+    block_of: BlockOf<E>,
+    /// Number of the latest block seen, 0 until one is.
+    latest_block: Arc<AtomicU64>,
+}
+
+impl<E, A> Clone for ActionExpiry<E, A> {
+    fn clone(&self) -> Self {
+        Self {
+            expiry_of: self.expiry_of.clone(),
+            block_of: self.block_of.clone(),
+            latest_block: self.latest_block.clone(),
+        }
+    }
+}
+
+impl<E, A> ActionExpiry<E, A> {
+    fn is_expired(&self, action: &A) -> bool {
+        let latest_block = match self.latest_block.load(Ordering::Relaxed) {
+            0 => None,
+            number => Some(U64::from(number)),
+        };
+        (self.expiry_of)(action).is_some_and(|expiry| expiry.is_expired(latest_block))
+    }
+}
+
+/// How the engine finds reorgs in events, and tells strategies about them.
+struct ReorgInvalidation<E> {
+    block_of: BlockOf<E>,
+    invalidation_event: InvalidationEvent<E>,
+    tracker: Arc<Mutex<ReorgTracker>>,
+}
+
+impl<E> Clone for ReorgInvalidation<E> {
+    fn clone(&self) -> Self {
+        Self {
+            block_of: self.block_of.clone(),
+            invalidation_event: self.invalidation_event.clone(),
+            tracker: self.tracker.clone(),
+        }
+    }
+}
+
+/// How the engine restarts a strategy which panics while processing an event. The
+/// strategy's state is synced again before it processes more events, after a backoff
+/// doubling with each restart. Once it panicked more than `max_restarts` times, the
+/// strategy is stopped, and the engine is no longer
+/// [live](crate::health::HealthReport::is_live).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    max_restarts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// Restart a panicking strategy up to `max_restarts` times, after waiting 100ms
+    /// before the first restart, and at most 30s before later ones.
+    pub fn new(max_restarts: u32) -> Self {
+        Self {
+            max_restarts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    /// Wait `initial` before the first restart, doubling with every restart up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// The time to wait before the `restart`th restart, counting from 1.
+    fn backoff(&self, restart: u32) -> Duration {
+        let factor = 2u32.saturating_pow(restart.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RestartPolicy {
+    /// Never restart a panicking strategy.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// How long a strategy may take to process an event before the engine gives up on it,
+/// e.g. because an RPC call the strategy awaits hangs, so a single event can't block the
+/// strategy's event loop. Timed-out events are counted in the engine's
+/// [health](Engine::health), and retried up to `retries` times.
+///
+/// Processing is cancelled at an await point, so strategies processing events with a
+/// timeout should keep their state consistent across the points they await.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventTimeout {
+    timeout: Duration,
+    retries: u32,
+}
+
+impl EventTimeout {
+    /// Give up on events taking longer than `timeout` to process, without retrying them.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            retries: 0,
+        }
+    }
+
+    /// Process events which timed out again, up to `retries` times.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+/// A collector of the engine. Restartable collectors have their stream restarted once
+/// it goes longer than their stall timeout without an event.
+enum EngineCollector<E> {
+    Plain(Box<dyn Collector<E>>),
+    Restartable(Box<dyn RestartableCollector<E>>, Duration),
+}
+
+impl<E> EngineCollector<E> {
+    async fn get_event_stream(&self) -> crate::error::Result<CollectorStream<'_, E>> {
+        match self {
+            EngineCollector::Plain(collector) => collector.get_event_stream().await,
+            EngineCollector::Restartable(collector, _) => collector.get_event_stream().await,
+        }
+    }
+
+    async fn restart(&self) -> crate::error::Result<()> {
+        match self {
+            EngineCollector::Plain(_) => Ok(()),
+            EngineCollector::Restartable(collector, _) => collector.restart().await,
+        }
+    }
+
+    fn stall_timeout(&self) -> Option<Duration> {
+        match self {
+            EngineCollector::Plain(_) => None,
+            EngineCollector::Restartable(_, stall_timeout) => Some(*stall_timeout),
+        }
+    }
+}
+
+/// A collector whose stream stalled and was restarted, sent to strategies as an event
+/// if [collector restart alerts](Engine::with_collector_restart_alerts) are enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectorRestart {
+    /// Index of the collector, in the order collectors were added.
+    pub collector: usize,
+    /// Number of times the collector was restarted, including this one.
+    pub restarts: u32,
+    /// Time the stream went without an event before it was restarted.
+    pub stalled_for: Duration,
+}
+
+/// A strategy restarted after panicking, sent to strategies as an event if
+/// [restart alerts](Engine::with_restart_alerts) are enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyRestart {
+    /// Index of the strategy, as in the engine's [control](Engine::control).
+    pub strategy: usize,
+    /// Number of times the strategy was restarted, including this one.
+    pub restarts: u32,
+    /// Message of the panic.
+    pub panic: String,
+}
+
+/// A task run by the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineTask {
+    /// The collector at this index, in the order collectors were added.
+    Collector(usize),
+    /// The strategy at this index, as in the engine's [control](Engine::control).
+    Strategy(usize),
+    /// The executor at this index, in the order executors were added.
+    Executor(usize),
+    HealthServer,
+    AdminServer,
+}
+
+impl fmt::Display for EngineTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineTask::Collector(index) => write!(f, "collector {}", index),
+            EngineTask::Strategy(index) => write!(f, "strategy {}", index),
+            EngineTask::Executor(index) => write!(f, "executor {}", index),
+            EngineTask::HealthServer => write!(f, "health server"),
+            EngineTask::AdminServer => write!(f, "admin server"),
+        }
+    }
+}
+
+/// Why a task of the engine exited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskExit {
+    /// The task gave up, e.g. a collector whose event stream ended, or a strategy
+    /// which panicked more times than it could be restarted.
+    Stopped(String),
+    /// The task panicked, with this message.
+    Panicked(String),
+    /// The task was [stopped](RunningEngine::stop), or the engine
+    /// [shut down](RunningEngine::shutdown).
+    Cancelled,
+}
 
-use crate::types::{Collector, Executor, Strategy};
+impl fmt::Display for TaskExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskExit::Stopped(reason) => write!(f, "stopped: {}", reason),
+            TaskExit::Panicked(panic) => write!(f, "panicked: {}", panic),
+            TaskExit::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// The tasks of an engine which exited without being cancelled, and why, returned by
+/// [wait](RunningEngine::wait).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineReport {
+    pub failures: Vec<(EngineTask, TaskExit)>,
+}
+
+impl fmt::Display for EngineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (task, exit)) in self.failures.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{} {}", task, exit)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EngineReport {}
+
+/// The tasks of a running [Engine], returned by [run](Engine::run). Dropping it aborts
+/// the tasks.
+pub struct RunningEngine {
+    tasks: JoinSet<(EngineTask, TaskExit)>,
+    /// The tasks still running, and the senders cancelling each of them.
+    running: Vec<(EngineTask, watch::Sender<bool>)>,
+    exits: Vec<(EngineTask, TaskExit)>,
+    health: Arc<EngineHealth>,
+}
+
+impl RunningEngine {
+    fn new(health: Arc<EngineHealth>) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            running: vec![],
+            exits: vec![],
+            health,
+        }
+    }
+
+    /// Spawn `future` as `task`, until it exits or is cancelled.
+    fn spawn<F>(&mut self, task: EngineTask, future: F)
+    where
+        F: Future<Output = TaskExit> + Send + 'static,
+    {
+        let (cancel, cancelled) = watch::channel(false);
+        self.running.push((task, cancel));
+        self.tasks.spawn(async move {
+            let exit = tokio::select! {
+                exit = AssertUnwindSafe(future).catch_unwind() => exit
+                    .unwrap_or_else(|payload| TaskExit::Panicked(panic_message(&*payload))),
+                _ = cancellation(cancelled) => TaskExit::Cancelled,
+            };
+            (task, exit)
+        });
+    }
+
+    /// Returns the tasks still running.
+    pub fn tasks(&self) -> Vec<EngineTask> {
+        self.running.iter().map(|(task, _)| *task).collect()
+    }
+
+    /// Returns the tasks which exited so far, and why, in the order they exited.
+    pub fn exits(&self) -> &[(EngineTask, TaskExit)] {
+        &self.exits
+    }
+
+    /// Cancel `task`. Returns false if it isn't running.
+    pub fn stop(&self, task: EngineTask) -> bool {
+        match self.running.iter().find(|(running, _)| *running == task) {
+            Some((_, cancel)) => cancel.send(true).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Cancel every task. [wait](RunningEngine::wait) returns once they exited.
+    pub fn shutdown(&self) {
+        info!("shutting down engine");
+        for (_, cancel) in &self.running {
+            let _ = cancel.send(true);
+        }
+    }
+
+    /// Wait for the next task to exit, and returns it and why. Returns `None` once
+    /// every task exited.
+    pub async fn join_next(&mut self) -> Option<(EngineTask, TaskExit)> {
+        loop {
+            match self.tasks.join_next().await {
+                Some(Ok((task, exit))) => {
+                    match &exit {
+                        TaskExit::Cancelled => info!(%task, "task cancelled"),
+                        exit => error!(%task, "task exited, {}", exit),
+                    }
+                    self.running.retain(|(running, _)| *running != task);
+                    self.exits.push((task, exit.clone()));
+                    return Some((task, exit));
+                }
+                // Panics are caught, so tasks only fail to join if they were aborted.
+                Some(Err(e)) => error!("error joining engine task: {}", e),
+                None => {
+                    self.health.set_running(false);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Wait for every task to exit. Returns the tasks which exited without being
+    /// cancelled, if any, e.g. once the engine is [shut down](RunningEngine::shutdown)
+    /// after a collector stopped.
+    pub async fn wait(mut self) -> Result<(), EngineReport> {
+        while self.join_next().await.is_some() {}
+        let failures: Vec<_> = self
+            .exits
+            .into_iter()
+            .filter(|(_, exit)| *exit != TaskExit::Cancelled)
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineReport { failures })
+        }
+    }
+}
+
+/// Resolves once `cancelled` is set. Never resolves if the sender is dropped, as the
+/// task is then aborted with the engine.
+async fn cancellation(mut cancelled: watch::Receiver<bool>) {
+    while !*cancelled.borrow_and_update() {
+        if cancelled.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Ring buffers of the last events of each collector, so a strategy which starts late,
+/// or is restarted after a panic, can catch up on recent context such as the latest
+/// block or gas price instead of starting blind. Enabled with
+/// [with_event_replay](Engine::with_event_replay).
+pub struct EventReplay<E> {
+    /// Number of events kept per collector.
+    capacity: usize,
+    /// The last events of each collector, oldest first, indexed like the collectors.
+    collectors: Mutex<Vec<VecDeque<Traced<E>>>>,
+}
+
+impl<E> EventReplay<E> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            collectors: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<E: Clone> EventReplay<E> {
+    /// Keep `event` of the collector at `index`, evicting its oldest event if full.
+    fn record(&self, index: usize, event: &Traced<E>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut collectors = self.collectors.lock().unwrap();
+        if collectors.len() <= index {
+            collectors.resize_with(index + 1, VecDeque::new);
+        }
+        let events = &mut collectors[index];
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+    }
+
+    /// The kept events collected before the event `event_id`, in collection order.
+    fn traced_before(&self, event_id: u64) -> Vec<Traced<E>> {
+        let mut events: Vec<_> = self
+            .collectors
+            .lock()
+            .unwrap()
+            .iter()
+            .flatten()
+            .filter(|event| event.event_id < event_id)
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| event.event_id);
+        events
+    }
+
+    /// The kept events of every collector, in the order they were collected, to pass
+    /// to a strategy started while the engine runs.
+    pub fn events(&self) -> Vec<E> {
+        self.traced_before(u64::MAX)
+            .into_iter()
+            .map(|event| event.inner)
+            .collect()
+    }
+
+    /// Number of events kept, across collectors.
+    pub fn len(&self) -> usize {
+        self.collectors
+            .lock()
+            .unwrap()
+            .iter()
+            .map(VecDeque::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The order the actions of a [ConcurrentStrategy] are sent to executors in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionOrder {
+    /// As soon as the event producing them is processed.
+    #[default]
+    Completion,
+    /// In the order of the events producing them. Actions of events processed before
+    /// earlier ones are held back until the earlier ones are processed.
+    Event,
+}
+
+/// How many events a [ConcurrentStrategy] processes at once, and the order its
+/// actions are sent to executors in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Concurrency {
+    max_in_flight: usize,
+    action_order: ActionOrder,
+}
+
+impl Concurrency {
+    /// Process up to `max_in_flight` events at once, sending actions as soon as they
+    /// are produced.
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            action_order: ActionOrder::default(),
+        }
+    }
+
+    pub fn with_action_order(mut self, action_order: ActionOrder) -> Self {
+        self.action_order = action_order;
+        self
+    }
+}
 
 /// The main engine of Artemis. This struct is responsible for orchestrating the
 /// data flow between collectors, strategies, and executors.
 pub struct Engine<E, A> {
     /// The set of collectors that the engine will use to collect events.
-    collectors: Vec<Box<dyn Collector<E>>>,
+    collectors: Vec<EngineCollector<E>>,
 
     /// The set of strategies that the engine will use to process events.
     strategies: Vec<Box<dyn Strategy<E, A>>>,
 
+    /// The set of strategies whose events are processed concurrently.
+    concurrent_strategies: Vec<(Box<dyn ConcurrentStrategy<E, A>>, Concurrency)>,
+
     /// The set of executors that the engine will use to execute actions.
     executors: Vec<Box<dyn Executor<A>>>,
 
@@ -22,16 +556,84 @@ pub struct Engine<E, A> {
 
     /// The capacity of the action channel.
     action_channel_capacity: usize,
+
+    /// The maximum age of an event, measured from when it was collected. Older events
+    /// are dropped before reaching strategies, and actions derived from them are dropped
+    /// before reaching executors.
+    max_event_age: Option<Duration>,
+
+    /// The time a strategy is expected to process an event in. Strategies which
+    /// consistently take longer are reported as slow.
+    strategy_deadline: Option<Duration>,
+
+    /// If set, actions are passed to this executor instead of the registered executors,
+    /// so nothing is submitted.
+    dry_run: Option<NoopExecutor<A>>,
+
+    /// Health of the engine's components, updated as data flows through the engine.
+    health: Arc<EngineHealth>,
+
+    /// Server exposing the health of the engine, if any.
+    health_server: Option<HealthServer>,
+
+    /// Runtime controls of the engine, e.g. to pause strategies.
+    control: Arc<EngineControl>,
+
+    /// Server exposing the controls of the engine, if any.
+    admin_server: Option<AdminServer>,
+
+    /// If set, receipts of executed actions are sent back to strategies as events.
+    receipts: Option<ReceiptEvent<E>>,
+
+    /// If set, reorgs are detected from new blocks, and the submissions they invalidate
+    /// are sent back to strategies as events.
+    reorgs: Option<ReorgInvalidation<E>>,
+
+    /// If set, actions are dropped once expired instead of being executed.
+    expiry: Option<ActionExpiry<E, A>>,
+
+    /// If set, the last events of each collector are kept, and replayed to strategies
+    /// restarted after a panic.
+    replay: Option<Arc<EventReplay<E>>>,
+
+    /// How strategies which panic are restarted.
+    restart_policy: RestartPolicy,
+
+    /// If set, restarts of strategies are sent to strategies as events.
+    restart_alerts: Option<RestartEvent<E>>,
+
+    /// If set, restarts of stalled collectors are sent to strategies as events.
+    collector_restart_alerts: Option<CollectorRestartEvent<E>>,
+
+    /// If set, strategies give up on events taking longer than the timeout to process.
+    event_timeout: Option<EventTimeout>,
 }
 
 impl<E, A> Engine<E, A> {
     pub fn new() -> Self {
+        let health = Arc::new(EngineHealth::new());
         Self {
             collectors: vec![],
             strategies: vec![],
+            concurrent_strategies: vec![],
             executors: vec![],
             event_channel_capacity: 512,
             action_channel_capacity: 512,
+            max_event_age: None,
+            strategy_deadline: None,
+            dry_run: None,
+            control: Arc::new(EngineControl::new(health.clone())),
+            health,
+            health_server: None,
+            admin_server: None,
+            receipts: None,
+            reorgs: None,
+            expiry: None,
+            replay: None,
+            restart_policy: RestartPolicy::default(),
+            restart_alerts: None,
+            collector_restart_alerts: None,
+            event_timeout: None,
         }
     }
 
@@ -44,6 +646,156 @@ impl<E, A> Engine<E, A> {
         self.action_channel_capacity = capacity;
         self
     }
+
+    /// Drop events, and the actions they produce, once they are older than `max_age`.
+    /// Useful when opportunities expire quickly, e.g. MEV-share hints older than a block,
+    /// so a backlog doesn't waste strategy and executor time on bundles that can't land.
+    pub fn with_max_event_age(mut self, max_age: Duration) -> Self {
+        self.max_event_age = Some(max_age);
+        self
+    }
+
+    /// Warn when a strategy takes longer than `deadline` to process several events in a
+    /// row. A slow strategy falls behind the event channel and misses events, and holds
+    /// up the actions of the events queued behind the slow one. Processing times and
+    /// deadline overruns are reported in the engine's [health](Engine::health).
+    pub fn with_strategy_deadline(mut self, deadline: Duration) -> Self {
+        self.strategy_deadline = Some(deadline);
+        self
+    }
+
+    /// Run in dry-run mode: every registered executor is replaced by `executor`, which
+    /// logs, and optionally simulates, actions without submitting them.
+    pub fn with_dry_run(mut self, executor: NoopExecutor<A>) -> Self {
+        self.dry_run = Some(executor);
+        self
+    }
+
+    /// Serve health checks with `server`, which should be bound to the engine's
+    /// [health](Engine::health), while the engine runs.
+    pub fn with_health_server(mut self, server: HealthServer) -> Self {
+        self.health_server = Some(server);
+        self
+    }
+
+    /// Serve admin commands with `server`, which should be bound to the engine's
+    /// [control](Engine::control), while the engine runs.
+    pub fn with_admin_server(mut self, server: AdminServer) -> Self {
+        self.admin_server = Some(server);
+        self
+    }
+
+    /// Send the [receipt](SubmissionReceipt) of every executed action which submitted
+    /// something back to strategies, as the event `f` returns, so they can correlate
+    /// submissions with relay acknowledgements. Receipt events keep the id of the event
+    /// the action originated from.
+    pub fn with_receipts<F>(mut self, f: F) -> Self
+    where
+        F: Fn(SubmissionReceipt) -> E + Send + Sync + 'static,
+    {
+        self.receipts = Some(Arc::new(f));
+        self
+    }
+
+    /// Detect reorgs from the new blocks `block_of` finds in events, and send strategies
+    /// the event `f` returns for each, with the submissions it invalidated, so they can
+    /// resubmit or cancel them. A submission is invalidated when its
+    /// [required block](crate::types::Submission::required_block), or else the latest
+    /// block when it was made, is dropped from the canonical chain.
+    pub fn with_reorg_invalidation<B, F>(mut self, block_of: B, f: F) -> Self
+    where
+        B: Fn(&E) -> Option<NewBlock> + Send + Sync + 'static,
+        F: Fn(Invalidation) -> E + Send + Sync + 'static,
+    {
+        self.reorgs = Some(ReorgInvalidation {
+            block_of: Arc::new(block_of),
+            invalidation_event: Arc::new(f),
+            tracker: Arc::new(Mutex::new(ReorgTracker::default())),
+        });
+        self
+    }
+
+    /// Drop actions once they expired, as `expiry_of` returns, instead of executing them,
+    /// e.g. bundles which sat in the action channel past the last block they target.
+    /// Expiry is checked right before each executor executes an action, and expired
+    /// actions are counted in the executor's [health](Engine::health). Block expiries
+    /// are checked against the latest of the new blocks `block_of` finds in events.
+    pub fn with_action_expiry<X, B>(mut self, expiry_of: X, block_of: B) -> Self
+    where
+        X: Fn(&A) -> Option<Expiry> + Send + Sync + 'static,
+        B: Fn(&E) -> Option<NewBlock> + Send + Sync + 'static,
+    {
+        self.expiry = Some(ActionExpiry {
+            expiry_of: Arc::new(expiry_of),
+            block_of: Arc::new(block_of),
+            latest_block: Arc::new(AtomicU64::new(0)),
+        });
+        self
+    }
+
+    /// Keep the last `capacity` events of each collector. When a strategy is
+    /// [restarted](Engine::with_restart_policy) after panicking, the kept events
+    /// collected before the one it panicked on are replayed to it. Strategies started
+    /// while the engine runs can catch up with the
+    /// [replay buffer](Engine::event_replay)'s events.
+    pub fn with_event_replay(mut self, capacity: usize) -> Self {
+        self.replay = Some(Arc::new(EventReplay::new(capacity)));
+        self
+    }
+
+    /// Returns the replay buffer of the last events of each collector, if enabled.
+    pub fn event_replay(&self) -> Option<Arc<EventReplay<E>>> {
+        self.replay.clone()
+    }
+
+    /// Restart strategies which panic while processing an event according to `policy`,
+    /// instead of stopping them. Panics of [concurrent](Engine::add_concurrent_strategy)
+    /// strategies only fail the event being processed, so they aren't restarted.
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Send every [restart](StrategyRestart) of a strategy to strategies, as the event
+    /// `f` returns, e.g. to alert on it. Restart events keep the id of the event the
+    /// strategy panicked on.
+    pub fn with_restart_alerts<F>(mut self, f: F) -> Self
+    where
+        F: Fn(StrategyRestart) -> E + Send + Sync + 'static,
+    {
+        self.restart_alerts = Some(Arc::new(f));
+        self
+    }
+
+    /// Send every [restart](CollectorRestart) of a stalled collector to strategies, as the
+    /// event `f` returns, e.g. to alert on it.
+    pub fn with_collector_restart_alerts<F>(mut self, f: F) -> Self
+    where
+        F: Fn(CollectorRestart) -> E + Send + Sync + 'static,
+    {
+        self.collector_restart_alerts = Some(Arc::new(f));
+        self
+    }
+
+    /// Give up on events a strategy takes longer than the `timeout` to process, retrying
+    /// them as many times as it allows. Unlike the
+    /// [strategy deadline](Engine::with_strategy_deadline), which only reports slow
+    /// strategies, timed-out events produce no action.
+    pub fn with_event_timeout(mut self, timeout: EventTimeout) -> Self {
+        self.event_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the health of the engine's components.
+    pub fn health(&self) -> Arc<EngineHealth> {
+        self.health.clone()
+    }
+
+    /// Returns the runtime controls of the engine. Strategies can be controlled once
+    /// the engine runs.
+    pub fn control(&self) -> Arc<EngineControl> {
+        self.control.clone()
+    }
 }
 
 impl<E, A> Default for Engine<E, A> {
@@ -59,7 +811,20 @@ where
 {
     /// Adds a collector to be used by the engine.
     pub fn add_collector(&mut self, collector: Box<dyn Collector<E>>) {
-        self.collectors.push(collector);
+        self.collectors.push(EngineCollector::Plain(collector));
+    }
+
+    /// Adds a collector whose stream is torn down and created again whenever it goes
+    /// longer than `stall_timeout` without an event, e.g. because its subscription
+    /// silently died. The timeout should be well above the usual time between events.
+    /// Restarts are counted in the collector's [health](Engine::health).
+    pub fn add_restartable_collector(
+        &mut self,
+        collector: Box<dyn RestartableCollector<E>>,
+        stall_timeout: Duration,
+    ) {
+        self.collectors
+            .push(EngineCollector::Restartable(collector, stall_timeout));
     }
 
     /// Adds a strategy to be used by the engine.
@@ -67,6 +832,25 @@ where
         self.strategies.push(strategy);
     }
 
+    /// Adds a strategy whose events are each processed in a task of their own, up to
+    /// `concurrency` events at once, so a slow event doesn't hold up the ones after it.
+    /// Strategies added this way are indexed after the ones added with
+    /// [add_strategy](Engine::add_strategy), e.g. to pause them.
+    ///
+    /// Events are started in the order they are received, but may finish in any order,
+    /// unless the strategy marks them [sequential](ConcurrentStrategy::is_sequential):
+    /// a sequential event is processed once every earlier event was, and no later
+    /// event is processed until it was. Syncing the strategy's state also waits for
+    /// the events being processed. Actions are sent in the
+    /// [order](Concurrency::with_action_order) the concurrency asks for.
+    pub fn add_concurrent_strategy(
+        &mut self,
+        strategy: Box<dyn ConcurrentStrategy<E, A>>,
+        concurrency: Concurrency,
+    ) {
+        self.concurrent_strategies.push((strategy, concurrency));
+    }
+
     /// Adds an executor to be used by the engine.
     pub fn add_executor(&mut self, executor: Box<dyn Executor<A>>) {
         self.executors.push(executor);
@@ -74,69 +858,716 @@ where
 
     /// The core run loop of the engine. This function will spawn a thread for
     /// each collector, strategy, and executor. It will then orchestrate the
-    /// data flow between them.
-    pub async fn run(self) -> Result<JoinSet<()>, Box<dyn std::error::Error>> {
-        let (event_sender, _): (Sender<E>, _) = broadcast::channel(self.event_channel_capacity);
-        let (action_sender, _): (Sender<A>, _) = broadcast::channel(self.action_channel_capacity);
+    /// data flow between them, until the returned [RunningEngine] is shut down.
+    pub async fn run(self) -> Result<RunningEngine, Box<dyn std::error::Error>> {
+        let (event_sender, _): (Sender<Traced<E>>, _) =
+            broadcast::channel(self.event_channel_capacity);
+        let (action_sender, _): (Sender<Traced<A>>, _) =
+            broadcast::channel(self.action_channel_capacity);
+        let next_event_id = Arc::new(AtomicU64::new(0));
+        let max_event_age = self.max_event_age;
+        let strategy_deadline = self.strategy_deadline;
+        let event_timeout = self.event_timeout;
+        let health = self.health;
+        let (events, actions) = (event_sender.clone(), action_sender.clone());
+        health.set_channel_depths(Box::new(move || (events.len(), actions.len())));
 
-        let mut set = JoinSet::new();
+        let mut running = RunningEngine::new(health.clone());
+
+        let executors: Vec<Box<dyn Executor<A>>> = match self.dry_run {
+            Some(executor) => {
+                info!(
+                    "dry run, replacing {} executors with a noop executor",
+                    self.executors.len()
+                );
+                vec![Box::new(executor)]
+            }
+            None => self.executors,
+        };
 
         // Spawn executors in separate threads.
-        for executor in self.executors {
+        for (index, executor) in executors.into_iter().enumerate() {
             let mut receiver = action_sender.subscribe();
-            set.spawn(async move {
+            let executor_health = health.add_executor();
+            let event_sender = event_sender.clone();
+            let receipts = self.receipts.clone();
+            let reorgs = self.reorgs.clone();
+            let expiry = self.expiry.clone();
+            running.spawn(EngineTask::Executor(index), async move {
                 info!("starting executor... ");
                 loop {
                     match receiver.recv().await {
-                        Ok(action) => match executor.execute(action).await {
-                            Ok(_) => {}
-                            Err(e) => error!("error executing action: {}", e),
-                        },
-                        Err(e) => error!("error receiving action: {}", e),
+                        Ok(action) if action.is_stale(max_event_age) => warn!(
+                            executor = index,
+                            event_id = action.event_id,
+                            "dropping stale action"
+                        ),
+                        Ok(action)
+                            if expiry
+                                .as_ref()
+                                .is_some_and(|expiry| expiry.is_expired(&action.inner)) =>
+                        {
+                            executor_health.record_expired();
+                            warn!(
+                                executor = index,
+                                event_id = action.event_id,
+                                "dropping expired action"
+                            );
+                        }
+                        Ok(action) => {
+                            let span = info_span!(
+                                "executor",
+                                executor = index,
+                                event_id = action.event_id
+                            );
+                            let result = executor
+                                .execute(action.inner)
+                                .instrument(span.clone())
+                                .await;
+                            executor_health.record_result(&result);
+                            let _enter = span.enter();
+                            let receipt = match result {
+                                Ok(receipt) => {
+                                    debug!(
+                                        latency_ms =
+                                            action.collected_at.elapsed().as_millis() as u64,
+                                        submissions = receipt.submissions.len(),
+                                        "executed action"
+                                    );
+                                    receipt
+                                }
+                                Err(e) => {
+                                    error!("error executing action: {}", e);
+                                    continue;
+                                }
+                            };
+                            if let Some(reorgs) = &reorgs {
+                                reorgs.tracker.lock().unwrap().register(&receipt);
+                            }
+                            if let (Some(receipts), false) = (&receipts, receipt.is_empty()) {
+                                let event = Traced {
+                                    event_id: action.event_id,
+                                    collected_at: Instant::now(),
+                                    inner: receipts(receipt),
+                                };
+                                if let Err(e) = event_sender.send(event) {
+                                    error!("error sending receipt: {}", e);
+                                }
+                            }
+                        }
+                        Err(RecvError::Lagged(missed)) => {
+                            executor_health.record_lag(missed);
+                            warn!(executor = index, missed, "executor lagging, missed actions");
+                        }
+                        Err(RecvError::Closed) => {
+                            return TaskExit::Stopped("action channel closed".to_string())
+                        }
                     }
                 }
             });
         }
 
         // Spawn strategies in separate threads.
-        for mut strategy in self.strategies {
+        let strategy_count = self.strategies.len();
+        for (index, mut strategy) in self.strategies.into_iter().enumerate() {
             let mut event_receiver = event_sender.subscribe();
             let action_sender = action_sender.clone();
+            let strategy_control = self.control.add_strategy();
+            let strategy_health = health.add_strategy();
+            let replay = self.replay.clone();
+            let restart_policy = self.restart_policy;
+            let restart_alerts = self.restart_alerts.clone();
+            let event_sender = event_sender.clone();
             strategy.sync_state().await?;
 
-            set.spawn(async move {
+            running.spawn(EngineTask::Strategy(index), async move {
                 info!("starting strategy... ");
+                let mut restarts = 0;
                 loop {
-                    match event_receiver.recv().await {
+                    let received = tokio::select! {
+                        _ = strategy_control.resync_requested() => {
+                            info!(strategy = index, "resyncing strategy state");
+                            if let Err(e) = strategy.sync_state().await {
+                                error!(strategy = index, "error resyncing strategy state: {}", e);
+                            }
+                            continue;
+                        }
+                        received = event_receiver.recv() => received,
+                    };
+                    match received {
+                        Ok(event) if event.is_stale(max_event_age) => warn!(
+                            strategy = index,
+                            event_id = event.event_id,
+                            "dropping stale event"
+                        ),
+                        Ok(event) if strategy_control.is_paused() => debug!(
+                            strategy = index,
+                            event_id = event.event_id,
+                            "strategy paused, dropping event"
+                        ),
                         Ok(event) => {
-                            if let Some(action) = strategy.process_event(event).await {
+                            let span =
+                                info_span!("strategy", strategy = index, event_id = event.event_id);
+                            let started_at = Instant::now();
+                            let processed = process_event(
+                                &mut strategy,
+                                event.inner,
+                                event_timeout,
+                                &strategy_health,
+                            )
+                            .instrument(span.clone())
+                            .await;
+                            let action = match processed {
+                                Ok(action) => action,
+                                Err(payload) => {
+                                    let panic = panic_message(&*payload);
+                                    error!(
+                                        strategy = index,
+                                        event_id = event.event_id,
+                                        "strategy panicked processing event: {}",
+                                        panic
+                                    );
+                                    if restarts >= restart_policy.max_restarts {
+                                        error!(strategy = index, restarts, "stopping strategy");
+                                        strategy_health.set_stopped();
+                                        return TaskExit::Stopped(format!(
+                                            "panicked after {} restarts: {}",
+                                            restarts, panic
+                                        ));
+                                    }
+                                    restarts += 1;
+                                    sleep(restart_policy.backoff(restarts)).await;
+                                    let events = replay
+                                        .as_ref()
+                                        .map(|replay| replay.traced_before(event.event_id))
+                                        .unwrap_or_default();
+                                    restart_strategy(
+                                        &mut strategy,
+                                        events,
+                                        index,
+                                        &action_sender,
+                                        event_timeout,
+                                        &strategy_health,
+                                    )
+                                    .await;
+                                    strategy_health.record_restart();
+
+                                    let Some(restart_alerts) = &restart_alerts else {
+                                        continue;
+                                    };
+                                    let restart = StrategyRestart {
+                                        strategy: index,
+                                        restarts,
+                                        panic,
+                                    };
+                                    let alert = Traced {
+                                        event_id: event.event_id,
+                                        collected_at: Instant::now(),
+                                        inner: restart_alerts(restart),
+                                    };
+                                    if let Err(e) = event_sender.send(alert) {
+                                        error!("error sending restart alert: {}", e);
+                                    }
+                                    continue;
+                                }
+                            };
+                            let _enter = span.enter();
+                            record_processed(
+                                &strategy_health,
+                                started_at.elapsed(),
+                                strategy_deadline,
+                                action.is_some(),
+                            );
+                            if let Some(action) = action {
+                                let action = Traced {
+                                    event_id: event.event_id,
+                                    collected_at: event.collected_at,
+                                    inner: action,
+                                };
                                 match action_sender.send(action) {
                                     Ok(_) => {}
                                     Err(e) => error!("error sending action: {}", e),
                                 }
                             }
                         }
-                        Err(e) => error!("error receiving event: {}", e),
+                        Err(RecvError::Lagged(missed)) => {
+                            strategy_health.record_lag(missed);
+                            warn!(strategy = index, missed, "strategy lagging, missed events");
+                        }
+                        Err(RecvError::Closed) => {
+                            return TaskExit::Stopped("event channel closed".to_string())
+                        }
+                    }
+                }
+            });
+        }
+
+        // Spawn concurrent strategies in separate threads, which process each event in a
+        // task of its own.
+        for (offset, (mut strategy, concurrency)) in
+            self.concurrent_strategies.into_iter().enumerate()
+        {
+            let index = strategy_count + offset;
+            let mut event_receiver = event_sender.subscribe();
+            let strategy_control = self.control.add_strategy();
+            let strategy_health = health.add_strategy();
+            strategy.sync_state().await?;
+            let strategy = Arc::new(RwLock::new(strategy));
+            let mut in_flight = InFlight::new(concurrency.action_order, action_sender.clone());
+
+            running.spawn(EngineTask::Strategy(index), async move {
+                info!("starting concurrent strategy... ");
+                loop {
+                    let received = tokio::select! {
+                        _ = strategy_control.resync_requested() => {
+                            info!(strategy = index, "resyncing strategy state");
+                            if let Err(e) = strategy.write().await.sync_state().await {
+                                error!(strategy = index, "error resyncing strategy state: {}", e);
+                            }
+                            continue;
+                        }
+                        _ = in_flight.join_next(), if !in_flight.is_empty() => continue,
+                        received = event_receiver.recv(),
+                            if in_flight.len() < concurrency.max_in_flight => received,
+                    };
+                    match received {
+                        Ok(event) if event.is_stale(max_event_age) => warn!(
+                            strategy = index,
+                            event_id = event.event_id,
+                            "dropping stale event"
+                        ),
+                        Ok(event) if strategy_control.is_paused() => debug!(
+                            strategy = index,
+                            event_id = event.event_id,
+                            "strategy paused, dropping event"
+                        ),
+                        Ok(event) => {
+                            let health = strategy_health.clone();
+                            // Take the lock before spawning, so events hold it in the
+                            // order they were received.
+                            let guard = strategy.clone().read_owned().await;
+                            if guard.is_sequential(&event.inner) {
+                                drop(guard);
+                                let guard = strategy.clone().write_owned().await;
+                                in_flight.spawn(process_concurrently(
+                                    guard,
+                                    event,
+                                    index,
+                                    health,
+                                    strategy_deadline,
+                                    event_timeout,
+                                ));
+                            } else {
+                                in_flight.spawn(process_concurrently(
+                                    guard,
+                                    event,
+                                    index,
+                                    health,
+                                    strategy_deadline,
+                                    event_timeout,
+                                ));
+                            }
+                        }
+                        Err(RecvError::Lagged(missed)) => {
+                            strategy_health.record_lag(missed);
+                            warn!(strategy = index, missed, "strategy lagging, missed events");
+                        }
+                        Err(RecvError::Closed) => {
+                            return TaskExit::Stopped("event channel closed".to_string())
+                        }
                     }
                 }
             });
         }
 
         // Spawn collectors in separate threads.
-        for collector in self.collectors {
+        for (index, collector) in self.collectors.into_iter().enumerate() {
             let event_sender = event_sender.clone();
-            set.spawn(async move {
+            let next_event_id = next_event_id.clone();
+            let collector_health = health.add_collector();
+            let reorgs = self.reorgs.clone();
+            let expiry = self.expiry.clone();
+            let replay = self.replay.clone();
+            let restart_alerts = self.collector_restart_alerts.clone();
+            running.spawn(EngineTask::Collector(index), async move {
                 info!("starting collector... ");
-                let mut event_stream = collector.get_event_stream().await.unwrap();
-                while let Some(event) = event_stream.next().await {
-                    match event_sender.send(event) {
-                        Ok(_) => {}
-                        Err(e) => error!("error sending event: {}", e),
+                let stall_timeout = collector.stall_timeout();
+                let mut restarts = 0;
+                loop {
+                    let mut event_stream = match collector.get_event_stream().await {
+                        Ok(event_stream) => event_stream,
+                        Err(e) => {
+                            error!(collector = index, "error getting event stream: {}", e);
+                            return TaskExit::Stopped(format!("error getting event stream: {}", e));
+                        }
+                    };
+                    collector_health.set_connected(true);
+                    loop {
+                        let next = match stall_timeout {
+                            Some(stall_timeout) => {
+                                tokio::time::timeout(stall_timeout, event_stream.next()).await
+                            }
+                            None => Ok(event_stream.next().await),
+                        };
+                        let event = match next {
+                            Ok(Some(event)) => event,
+                            Ok(None) => {
+                                collector_health.set_connected(false);
+                                warn!(collector = index, "collector event stream ended");
+                                return TaskExit::Stopped("event stream ended".to_string());
+                            }
+                            // The stream stalled, so it is restarted.
+                            Err(_) => break,
+                        };
+                        collector_health.record_event();
+                        if let Some(expiry) = &expiry {
+                            if let Some(block) = (expiry.block_of)(&event) {
+                                let number = block.number.as_u64();
+                                expiry.latest_block.fetch_max(number, Ordering::Relaxed);
+                            }
+                        }
+                        let block = reorgs.as_ref().and_then(|reorgs| (reorgs.block_of)(&event));
+                        let event_id = next_event_id.fetch_add(1, Ordering::Relaxed);
+                        debug!(collector = index, event_id, "collected event");
+                        let event = Traced {
+                            event_id,
+                            collected_at: Instant::now(),
+                            inner: event,
+                        };
+                        if let Some(replay) = &replay {
+                            replay.record(index, &event);
+                        }
+                        match event_sender.send(event) {
+                            Ok(_) => {}
+                            Err(e) => error!("error sending event: {}", e),
+                        }
+
+                        // Tell strategies about the submissions a reorg invalidated, after
+                        // the block reorging the chain.
+                        let (Some(reorgs), Some(block)) = (&reorgs, block) else {
+                            continue;
+                        };
+                        let Some(invalidation) = reorgs.tracker.lock().unwrap().on_block(&block)
+                        else {
+                            continue;
+                        };
+                        warn!(
+                            collector = index,
+                            dropped = invalidation.reorg.dropped.len(),
+                            invalidated = invalidation.submissions.len(),
+                            "chain reorged at block {}",
+                            block.number
+                        );
+                        let event = Traced {
+                            event_id: next_event_id.fetch_add(1, Ordering::Relaxed),
+                            collected_at: Instant::now(),
+                            inner: (reorgs.invalidation_event)(invalidation),
+                        };
+                        if let Err(e) = event_sender.send(event) {
+                            error!("error sending invalidation: {}", e);
+                        }
+                    }
+
+                    drop(event_stream);
+                    collector_health.set_connected(false);
+                    restarts += 1;
+                    let stalled_for = stall_timeout.unwrap_or_default();
+                    warn!(
+                        collector = index,
+                        restarts, "collector stalled for {:?}, restarting", stalled_for
+                    );
+                    if let Err(e) = collector.restart().await {
+                        error!(collector = index, "error restarting collector: {}", e);
+                        return TaskExit::Stopped(format!("error restarting: {}", e));
+                    }
+                    collector_health.record_restart();
+
+                    let Some(restart_alerts) = &restart_alerts else {
+                        continue;
+                    };
+                    let restart = CollectorRestart {
+                        collector: index,
+                        restarts,
+                        stalled_for,
+                    };
+                    let alert = Traced {
+                        event_id: next_event_id.fetch_add(1, Ordering::Relaxed),
+                        collected_at: Instant::now(),
+                        inner: restart_alerts(restart),
+                    };
+                    if let Err(e) = event_sender.send(alert) {
+                        error!("error sending collector restart alert: {}", e);
                     }
                 }
             });
         }
 
-        Ok(set)
+        if let Some(server) = self.health_server {
+            running.spawn(EngineTask::HealthServer, async move {
+                server.serve().await;
+                TaskExit::Stopped("server stopped".to_string())
+            });
+        }
+        if let Some(server) = self.admin_server {
+            running.spawn(EngineTask::AdminServer, async move {
+                server.serve().await;
+                TaskExit::Stopped("server stopped".to_string())
+            });
+        }
+        health.set_running(true);
+
+        Ok(running)
+    }
+}
+
+/// Record an event a strategy processed in `elapsed`, warning if the strategy keeps
+/// exceeding its `deadline`.
+fn record_processed(
+    health: &StrategyHealth,
+    elapsed: Duration,
+    deadline: Option<Duration>,
+    produced_action: bool,
+) {
+    let overruns = health.record_processed(elapsed, deadline);
+    debug!(
+        latency_ms = elapsed.as_millis() as u64,
+        produced_action, "processed event"
+    );
+    if overruns == OVERRUNS_BEFORE_WARNING {
+        warn!(
+            latency_ms = elapsed.as_millis() as u64,
+            overruns, "strategy exceeding its processing deadline"
+        );
+    }
+}
+
+/// Returns the message of a panic.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+/// Process `event` with `strategy`, catching panics, and giving up on it once it takes
+/// longer than `timeout` to process, as many times as the timeout retries it. An event
+/// which timed out every time produces no action.
+async fn process_event<E: Clone, A>(
+    strategy: &mut Box<dyn Strategy<E, A>>,
+    event: E,
+    timeout: Option<EventTimeout>,
+    health: &StrategyHealth,
+) -> std::thread::Result<Option<A>> {
+    let Some(timeout) = timeout else {
+        return AssertUnwindSafe(strategy.process_event(event))
+            .catch_unwind()
+            .await;
+    };
+    for attempt in 0..=timeout.retries {
+        let processing = AssertUnwindSafe(strategy.process_event(event.clone())).catch_unwind();
+        match tokio::time::timeout(timeout.timeout, processing).await {
+            Ok(processed) => return processed,
+            Err(_) => {
+                health.record_timeout();
+                warn!(
+                    attempt,
+                    timeout_ms = timeout.timeout.as_millis() as u64,
+                    "timed out processing event"
+                );
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Restart `strategy` after it panicked: sync its state again, and replay `events` to
+/// it so it catches up on recent context. Actions produced from replayed events keep
+/// the time their event was collected, so stale ones are dropped before executors.
+/// Replaying stops if the strategy panics again.
+async fn restart_strategy<E, A>(
+    strategy: &mut Box<dyn Strategy<E, A>>,
+    events: Vec<Traced<E>>,
+    index: usize,
+    action_sender: &Sender<Traced<A>>,
+    timeout: Option<EventTimeout>,
+    health: &StrategyHealth,
+) where
+    E: Clone,
+{
+    warn!(
+        strategy = index,
+        replayed = events.len(),
+        "restarting strategy"
+    );
+    if let Err(e) = strategy.sync_state().await {
+        error!(strategy = index, "error resyncing strategy state: {}", e);
+    }
+    for event in events {
+        let span = info_span!("strategy", strategy = index, event_id = event.event_id);
+        let processed = process_event(strategy, event.inner, timeout, health)
+            .instrument(span)
+            .await;
+        match processed {
+            Ok(Some(action)) => {
+                let action = Traced {
+                    event_id: event.event_id,
+                    collected_at: event.collected_at,
+                    inner: action,
+                };
+                if let Err(e) = action_sender.send(action) {
+                    error!("error sending action: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(_) => {
+                return error!(strategy = index, "strategy panicked replaying events");
+            }
+        }
+    }
+}
+
+/// Process `event` with the concurrent strategy behind `strategy`, a lock guard held
+/// until the event is processed.
+async fn process_concurrently<E, A, G>(
+    strategy: G,
+    event: Traced<E>,
+    index: usize,
+    health: Arc<StrategyHealth>,
+    deadline: Option<Duration>,
+    timeout: Option<EventTimeout>,
+) -> Option<Traced<A>>
+where
+    E: Clone,
+    G: Deref<Target = Box<dyn ConcurrentStrategy<E, A>>>,
+{
+    let span = info_span!("strategy", strategy = index, event_id = event.event_id);
+    let started_at = Instant::now();
+    let action = match timeout {
+        Some(timeout) => {
+            let mut action = None;
+            for attempt in 0..=timeout.retries {
+                let processing = strategy.process_event(event.inner.clone());
+                match tokio::time::timeout(timeout.timeout, processing)
+                    .instrument(span.clone())
+                    .await
+                {
+                    Ok(processed) => {
+                        action = processed;
+                        break;
+                    }
+                    Err(_) => {
+                        health.record_timeout();
+                        warn!(
+                            strategy = index,
+                            event_id = event.event_id,
+                            attempt,
+                            "timed out processing event"
+                        );
+                    }
+                }
+            }
+            action
+        }
+        None => {
+            strategy
+                .process_event(event.inner)
+                .instrument(span.clone())
+                .await
+        }
+    };
+    drop(strategy);
+    let _enter = span.enter();
+    record_processed(&health, started_at.elapsed(), deadline, action.is_some());
+    action.map(|action| Traced {
+        event_id: event.event_id,
+        collected_at: event.collected_at,
+        inner: action,
+    })
+}
+
+/// The events a concurrent strategy is processing, each in a task of its own. Actions
+/// are sent as tasks are joined, in the strategy's [ActionOrder].
+struct InFlight<A> {
+    tasks: JoinSet<(u64, Option<Traced<A>>)>,
+    action_order: ActionOrder,
+    action_sender: Sender<Traced<A>>,
+    /// Sequence number of the next task spawned.
+    next_spawned: u64,
+    /// Sequence number of the next task whose action is sent, in event order.
+    next_sent: u64,
+    /// Actions of tasks finished before earlier ones, in event order.
+    finished: BTreeMap<u64, Option<Traced<A>>>,
+}
+
+impl<A: Send + 'static> InFlight<A> {
+    fn new(action_order: ActionOrder, action_sender: Sender<Traced<A>>) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            action_order,
+            action_sender,
+            next_spawned: 0,
+            next_sent: 0,
+            finished: BTreeMap::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    fn spawn<F>(&mut self, task: F)
+    where
+        F: Future<Output = Option<Traced<A>>> + Send + 'static,
+    {
+        let seq = self.next_spawned;
+        self.next_spawned += 1;
+        self.tasks.spawn(async move {
+            // A panicking task yields no action, so later actions aren't held back.
+            match AssertUnwindSafe(task).catch_unwind().await {
+                Ok(action) => (seq, action),
+                Err(payload) => {
+                    error!(
+                        "strategy panicked processing event: {}",
+                        panic_message(&*payload)
+                    );
+                    (seq, None)
+                }
+            }
+        });
+    }
+
+    /// Wait for the next task to finish, and send the actions which can be sent.
+    /// Cancel safe.
+    async fn join_next(&mut self) {
+        let (seq, action) = match self.tasks.join_next().await {
+            Some(Ok(finished)) => finished,
+            Some(Err(e)) => return error!("error joining strategy task: {}", e),
+            None => return,
+        };
+        match self.action_order {
+            ActionOrder::Completion => self.send(action),
+            ActionOrder::Event => {
+                self.finished.insert(seq, action);
+                while let Some(action) = self.finished.remove(&self.next_sent) {
+                    self.next_sent += 1;
+                    self.send(action);
+                }
+            }
+        }
+    }
+
+    fn send(&self, action: Option<Traced<A>>) {
+        if let Some(action) = action {
+            if let Err(e) = self.action_sender.send(action) {
+                error!("error sending action: {}", e);
+            }
+        }
     }
 }
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use ethers::{
+    contract::ContractError,
+    providers::{Middleware, MiddlewareError, ProviderError, RpcError},
+};
+use thiserror::Error;
+
+/// Result of the [Collector](crate::types::Collector), [Strategy](crate::types::Strategy)
+/// and [Executor](crate::types::Executor) traits.
+pub type Result<T, E = ArtemisError> = std::result::Result<T, E>;
+
+/// JSON-RPC error codes nodes and relays use when throttling requests.
+const RATE_LIMIT_CODES: [i64; 2] = [429, -32005];
+
+/// JSON-RPC error code of a reverted call, whose data is the revert reason.
+const REVERT_CODE: i64 = 3;
+
+/// Error of a collector, strategy or executor, categorized so callers can decide
+/// whether to retry, back off, or give up, e.g. in a
+/// [CircuitBreakerExecutor](crate::executors::circuit_breaker_executor::CircuitBreakerExecutor).
+#[derive(Debug, Error)]
+pub enum ArtemisError {
+    /// The node or relay is throttling requests.
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    /// A call or transaction reverted, or would revert.
+    #[error("reverted: {0}")]
+    Reverted(String),
+    /// The node, relay or sequencer rejected a request, e.g. a bundle or transaction.
+    #[error("rejected: {0}")]
+    Rejected(String),
+    /// The node, relay or feed couldn't be reached, or the connection dropped.
+    #[error("network error: {0}")]
+    Network(String),
+    /// A request took longer than allowed.
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+    /// Any other error.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ArtemisError {
+    /// Categorize a JSON-RPC error response by its `code` and `message`.
+    pub fn from_rpc_error(code: i64, message: &str) -> Self {
+        let lowercase = message.to_lowercase();
+        if RATE_LIMIT_CODES.contains(&code)
+            || lowercase.contains("rate limit")
+            || lowercase.contains("too many requests")
+        {
+            Self::RateLimited(message.to_string())
+        } else if code == REVERT_CODE || lowercase.contains("revert") {
+            Self::Reverted(message.to_string())
+        } else {
+            Self::Rejected(message.to_string())
+        }
+    }
+
+    /// Categorize the error of a [Middleware](Middleware), for errors of generic
+    /// middlewares, which can't be converted with `?`.
+    pub fn from_middleware<E: MiddlewareError + 'static>(e: E) -> Self {
+        if let Some(response) = e.as_error_response() {
+            return Self::from_rpc_error(response.code, &response.message);
+        }
+        match e.as_provider_error() {
+            Some(ProviderError::HTTPError(http))
+                if http.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) =>
+            {
+                Self::RateLimited(e.to_string())
+            }
+            Some(ProviderError::HTTPError(_) | ProviderError::JsonRpcClientError(_)) => {
+                Self::Network(e.to_string())
+            }
+            _ => Self::Other(e.into()),
+        }
+    }
+
+    /// Whether the request may succeed if retried, possibly after backing off.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited(_) | Self::Network(_) | Self::Timeout(_)
+        )
+    }
+}
+
+impl From<ProviderError> for ArtemisError {
+    fn from(e: ProviderError) -> Self {
+        if let Some(response) = RpcError::as_error_response(&e) {
+            return Self::from_rpc_error(response.code, &response.message);
+        }
+        match e {
+            ProviderError::HTTPError(e) => e.into(),
+            // Errors of the transport which aren't error responses.
+            ProviderError::JsonRpcClientError(e) => Self::Network(e.to_string()),
+            e => Self::Other(e.into()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ArtemisError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+            Self::RateLimited(e.to_string())
+        } else {
+            Self::Network(e.to_string())
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ArtemisError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::Network(e.to_string())
+    }
+}
+
+#[cfg(feature = "alloy")]
+impl From<alloy::transports::TransportError> for ArtemisError {
+    fn from(e: alloy::transports::TransportError) -> Self {
+        use alloy::transports::{RpcError as AlloyRpcError, TransportErrorKind};
+        match e {
+            AlloyRpcError::ErrorResp(response) => {
+                Self::from_rpc_error(response.code, &response.message)
+            }
+            AlloyRpcError::Transport(TransportErrorKind::HttpError(http))
+                if http.status == reqwest::StatusCode::TOO_MANY_REQUESTS.as_u16() =>
+            {
+                Self::RateLimited(http.to_string())
+            }
+            AlloyRpcError::Transport(kind) => Self::Network(kind.to_string()),
+            e => Self::Other(e.into()),
+        }
+    }
+}
+
+impl<M: Middleware + 'static> From<ContractError<M>> for ArtemisError {
+    fn from(e: ContractError<M>) -> Self {
+        match e {
+            ContractError::Revert(data) => Self::Reverted(data.to_string()),
+            ContractError::MiddlewareError { e } => Self::from_middleware(e),
+            ContractError::ProviderError { e } => e.into(),
+            e => Self::Other(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::JsonRpcError;
+
+    fn rpc_error(code: i64, message: &str) -> ProviderError {
+        ProviderError::JsonRpcClientError(Box::new(
+            ethers::providers::HttpClientError::JsonRpcError(JsonRpcError {
+                code,
+                message: message.to_string(),
+                data: None,
+            }),
+        ))
+    }
+
+    #[test]
+    fn categorizes_rpc_errors() {
+        let error = ArtemisError::from(rpc_error(-32005, "daily request count exceeded"));
+        assert!(matches!(error, ArtemisError::RateLimited(_)));
+        assert!(error.is_transient());
+
+        let error = ArtemisError::from(rpc_error(3, "execution reverted: too little received"));
+        assert!(matches!(error, ArtemisError::Reverted(_)));
+        assert!(!error.is_transient());
+
+        let error = ArtemisError::from(rpc_error(-32000, "nonce too low"));
+        assert!(matches!(error, ArtemisError::Rejected(_)));
+
+        let error = ArtemisError::from(anyhow::anyhow!("invalid bundle"));
+        assert_eq!(error.to_string(), "invalid bundle");
+    }
+}
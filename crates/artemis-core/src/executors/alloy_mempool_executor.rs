@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::executors::mempool_executor::SubmitTxToMempool;
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use crate::utilities::alloy_compat::{to_alloy_request, ToEthers};
+use alloy::providers::Provider;
+use anyhow::Context;
+use async_trait::async_trait;
+use ethers::types::U256;
+
+/// An executor that sends transactions to the mempool through an alloy provider. It
+/// executes the same [actions](SubmitTxToMempool) as the
+/// [MempoolExecutor](super::mempool_executor::MempoolExecutor), so strategies can switch
+/// between them without changes.
+pub struct AlloyMempoolExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P: Provider> AlloyMempoolExecutor<P> {
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Executor<SubmitTxToMempool> for AlloyMempoolExecutor<P> {
+    /// Send a transaction to the mempool.
+    async fn execute(&self, action: SubmitTxToMempool) -> Result<SubmissionReceipt> {
+        let mut tx = to_alloy_request(&action.tx)?;
+        let gas_usage = self
+            .provider
+            .estimate_gas(tx.clone())
+            .await
+            .context("Error estimating gas usage")?;
+
+        let bid_gas_price = if let Some(gas_bid_info) = action.gas_bid_info {
+            // gas price at which we'd break even, meaning 100% of profit goes to validator
+            let breakeven_gas_price = gas_bid_info.total_profit / U256::from(gas_usage);
+            // gas price corresponding to bid percentage
+            let bid_gas_price = breakeven_gas_price * gas_bid_info.bid_percentage / 100;
+            u128::try_from(bid_gas_price).context("Gas bid overflows u128")?
+        } else {
+            self.provider
+                .get_gas_price()
+                .await
+                .context("Error getting gas price")?
+        };
+        // Like ethers' `set_gas_price`, EIP-1559 transactions bid the price as both fees.
+        if tx.max_fee_per_gas.is_some() || tx.max_priority_fee_per_gas.is_some() {
+            tx.max_fee_per_gas = Some(bid_gas_price);
+            tx.max_priority_fee_per_gas = Some(bid_gas_price);
+        } else {
+            tx.gas_price = Some(bid_gas_price);
+        }
+        let pending = self.provider.send_transaction(tx).await?;
+        Ok(SubmissionReceipt::new(vec![Submission::new(
+            "mempool",
+            vec![(*pending.tx_hash()).to_ethers()],
+        )]))
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use crate::utilities::alloy_compat::ToEthers;
+use alloy::{
+    consensus::{SidecarBuilder, SimpleCoder},
+    eips::eip2718::Encodable2718,
+    network::{
+        Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder, TransactionBuilder4844,
+    },
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use ethers::types::{Bytes, H256};
+
+/// Default number of blocks blob fee bids stay above the blob base fee for.
+pub const DEFAULT_BLOB_FEE_BLOCKS: u64 = 3;
+
+/// An executor that constructs, signs and sends EIP-4844 blob transactions, e.g. the
+/// batches of a rollup, through an alloy provider. Blob transactions can also be
+/// [signed](BlobExecutor::sign_blob_tx) without being sent, and added to a
+/// [UnifiedBundle](crate::bundle::UnifiedBundle) for relays which accept them.
+pub struct BlobExecutor<P> {
+    provider: Arc<P>,
+    wallet: EthereumWallet,
+    fee_blocks: u64,
+}
+
+/// A blob transaction to send.
+#[derive(Debug, Clone)]
+pub struct SubmitBlobTx {
+    /// The transaction carrying the blobs, e.g. a call to the batch inbox of a rollup.
+    /// Its fees, gas limit, nonce and chain id are filled in unless set.
+    pub tx: TransactionRequest,
+    /// Data posted in the blobs, encoded into as many blobs as it needs.
+    pub data: Bytes,
+}
+
+/// Fees of a blob transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobFees {
+    /// Most the transaction pays per unit of blob gas.
+    pub max_fee_per_blob_gas: u128,
+    /// Most the transaction pays per unit of gas.
+    pub max_fee_per_gas: u128,
+    /// Tip paid to the builder per unit of gas.
+    pub max_priority_fee_per_gas: u128,
+}
+
+/// Returns the blob fee bid which stays above the blob base fee for `blocks` blocks,
+/// since the blob base fee rises by at most 12.5% per block.
+pub fn blob_fee_cap(blob_base_fee: u128, blocks: u64) -> u128 {
+    (0..blocks).fold(blob_base_fee.max(1), |fee, _| {
+        fee.saturating_add(fee.div_ceil(8))
+    })
+}
+
+impl<P: Provider> BlobExecutor<P> {
+    /// Create an executor signing blob transactions with `wallet`.
+    pub fn new(provider: Arc<P>, wallet: EthereumWallet) -> Self {
+        Self {
+            provider,
+            wallet,
+            fee_blocks: DEFAULT_BLOB_FEE_BLOCKS,
+        }
+    }
+
+    /// Bid blob fees which stay above the blob base fee for `blocks` blocks, so the
+    /// transaction can still be included if the next blocks are full of blobs.
+    pub fn with_fee_blocks(mut self, blocks: u64) -> Self {
+        self.fee_blocks = blocks;
+        self
+    }
+
+    /// Estimate the fees of a blob transaction from the current blob base fee and the
+    /// node's EIP-1559 fee estimate.
+    pub async fn estimate_blob_fees(&self) -> Result<BlobFees> {
+        let blob_base_fee = self
+            .provider
+            .get_blob_base_fee()
+            .await
+            .context("Error getting blob base fee")?;
+        let fees = self
+            .provider
+            .estimate_eip1559_fees()
+            .await
+            .context("Error estimating fees")?;
+        Ok(BlobFees {
+            max_fee_per_blob_gas: blob_fee_cap(blob_base_fee, self.fee_blocks),
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+        })
+    }
+
+    /// Encode the data of `action` into blobs, fill in the transaction and sign it.
+    /// Returns the hash of the transaction and its network form, which includes the
+    /// blobs, commitments and proofs nodes and relays need to accept it.
+    pub async fn sign_blob_tx(&self, action: SubmitBlobTx) -> Result<(H256, Bytes)> {
+        let sidecar = SidecarBuilder::<SimpleCoder>::from_slice(&action.data)
+            .build()
+            .context("Error encoding blobs")?;
+        let from = NetworkWallet::<Ethereum>::default_signer_address(&self.wallet);
+        let mut tx = action.tx.with_from(from).with_blob_sidecar(sidecar);
+
+        if tx.max_fee_per_blob_gas.is_none()
+            || tx.max_fee_per_gas.is_none()
+            || tx.max_priority_fee_per_gas.is_none()
+        {
+            let fees = self.estimate_blob_fees().await?;
+            tx.max_fee_per_blob_gas
+                .get_or_insert(fees.max_fee_per_blob_gas);
+            tx.max_fee_per_gas.get_or_insert(fees.max_fee_per_gas);
+            tx.max_priority_fee_per_gas
+                .get_or_insert(fees.max_priority_fee_per_gas);
+        }
+        if tx.nonce.is_none() {
+            let nonce = self
+                .provider
+                .get_transaction_count(from)
+                .pending()
+                .await
+                .context("Error getting nonce")?;
+            tx.set_nonce(nonce);
+        }
+        if tx.chain_id.is_none() {
+            let chain_id = self
+                .provider
+                .get_chain_id()
+                .await
+                .context("Error getting chain id")?;
+            tx.set_chain_id(chain_id);
+        }
+        if tx.gas.is_none() {
+            let gas = self
+                .provider
+                .estimate_gas(tx.clone())
+                .await
+                .context("Error estimating gas usage")?;
+            tx.set_gas_limit(gas);
+        }
+
+        let signed = tx
+            .build(&self.wallet)
+            .await
+            .context("Error signing blob transaction")?;
+        Ok((
+            (*signed.tx_hash()).to_ethers(),
+            signed.encoded_2718().into(),
+        ))
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Executor<SubmitBlobTx> for BlobExecutor<P> {
+    /// Sign a blob transaction and send it to the mempool.
+    async fn execute(&self, action: SubmitBlobTx) -> Result<SubmissionReceipt> {
+        let (hash, raw) = self.sign_blob_tx(action).await?;
+        self.provider.send_raw_transaction(&raw).await?;
+        Ok(SubmissionReceipt::new(vec![Submission::new(
+            "mempool",
+            vec![hash],
+        )]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bids_blob_fees_above_base_fee_growth() {
+        assert_eq!(blob_fee_cap(1_000, 0), 1_000);
+        assert_eq!(blob_fee_cap(1_000, 1), 1_125);
+        assert_eq!(blob_fee_cap(1_000, 3), 1_425);
+        // The blob base fee is at least 1 wei, and always rises when blobs are full.
+        assert_eq!(blob_fee_cap(0, 0), 1);
+        assert_eq!(blob_fee_cap(1, 2), 3);
+        assert_eq!(blob_fee_cap(u128::MAX, 1), u128::MAX);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use crate::error::Result;
+use crate::executors::mev_share_executor::Bundles;
+use crate::types::{Executor, SubmissionReceipt};
+use async_trait::async_trait;
+use ethers::{
+    types::{transaction::eip2718::TypedTransaction, Address, H256, U256},
+    utils::rlp::Rlp,
+};
+use matchmaker::types::{BundleRequest, BundleTx};
+use tracing::debug;
+
+/// Maximum number of transactions in a merged bundle by default.
+const DEFAULT_MAX_BODY_LEN: usize = 10;
+
+/// BundleMergingExecutor is a wrapper around an [Executor](Executor) of bundles which
+/// merges independent backruns targeting the same blocks into a single bundle before
+/// passing them on, so their bids add up and fewer bundles are submitted.
+///
+/// Bundles are merged in the order they are given, each into the first merged bundle
+/// it is independent of, as long as the merged bundle stays within the maximum body
+/// length. Bundles are independent if they target the same blocks with the same
+/// version and privacy, backrun different transactions, and none of their signed
+/// transactions share a sender and nonce; alternatives of the same backrun at
+/// different sizes are therefore never merged. Bundles with refunds, and bundles whose
+/// transactions can't be decoded, are passed on unmerged.
+///
+/// A merged bundle is only included if none of its non-revertible transactions
+/// revert, so a single failing backrun drops the others it was merged with.
+pub struct BundleMergingExecutor<E> {
+    executor: E,
+    max_body_len: usize,
+}
+
+impl<E> BundleMergingExecutor<E> {
+    /// Merge the bundles passed to `executor`, into bundles of up to 10 transactions.
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            max_body_len: DEFAULT_MAX_BODY_LEN,
+        }
+    }
+
+    /// Merge bundles into bundles of up to `max_body_len` transactions, e.g. the
+    /// maximum body length the relay accepts.
+    pub fn with_max_body_len(mut self, max_body_len: usize) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+}
+
+#[async_trait]
+impl<E> Executor<Bundles> for BundleMergingExecutor<E>
+where
+    E: Executor<Bundles>,
+{
+    /// Merge the independent bundles of the action, then execute the merged bundles.
+    async fn execute(&self, action: Bundles) -> Result<SubmissionReceipt> {
+        let received = action.len();
+        let merged = merge_bundles(action, self.max_body_len);
+        if merged.len() < received {
+            debug!("merged {} bundles into {}", received, merged.len());
+        }
+        self.executor.execute(merged).await
+    }
+}
+
+/// A bundle being merged into, along with what the bundles merged into it touch.
+struct MergedBundle {
+    bundle: BundleRequest,
+    /// Hashes of the pending transactions backrun.
+    pending: HashSet<H256>,
+    /// Senders and nonces of the signed transactions.
+    nonces: HashSet<(Address, U256)>,
+}
+
+impl MergedBundle {
+    /// Returns whether `other` can be merged into the bundle.
+    fn accepts(&self, other: &MergedBundle, max_body_len: usize) -> bool {
+        let (bundle, other_bundle) = (&self.bundle, &other.bundle);
+        bundle.body.len() + other_bundle.body.len() <= max_body_len
+            && bundle.inclusion == other_bundle.inclusion
+            && bundle.version == other_bundle.version
+            && bundle.privacy == other_bundle.privacy
+            && self.pending.is_disjoint(&other.pending)
+            && self.nonces.is_disjoint(&other.nonces)
+    }
+
+    fn merge(&mut self, other: MergedBundle) {
+        self.bundle.body.extend(other.bundle.body);
+        self.pending.extend(other.pending);
+        self.nonces.extend(other.nonces);
+    }
+}
+
+/// Merge the independent bundles of `bundles` into bundles of up to `max_body_len`
+/// transactions, as described on [BundleMergingExecutor]. Bundles which can't be merged
+/// are returned unchanged, after the merged ones.
+pub fn merge_bundles(bundles: Vec<BundleRequest>, max_body_len: usize) -> Vec<BundleRequest> {
+    let mut merged: Vec<MergedBundle> = Vec::new();
+    let mut unmerged = Vec::new();
+    for bundle in bundles {
+        let Some(candidate) = mergeable(bundle.clone()) else {
+            unmerged.push(bundle);
+            continue;
+        };
+        match merged
+            .iter_mut()
+            .find(|merged| merged.accepts(&candidate, max_body_len))
+        {
+            Some(merged) => merged.merge(candidate),
+            None => merged.push(candidate),
+        }
+    }
+    merged
+        .into_iter()
+        .map(|merged| merged.bundle)
+        .chain(unmerged)
+        .collect()
+}
+
+/// Returns the bundle along with what it touches, or `None` if it can't be merged.
+fn mergeable(bundle: BundleRequest) -> Option<MergedBundle> {
+    if let Some(validity) = &bundle.validity {
+        if validity.refund.is_some() || validity.refund_config.is_some() {
+            return None;
+        }
+    }
+    let mut pending = HashSet::new();
+    let mut nonces = HashSet::new();
+    for tx in &bundle.body {
+        match tx {
+            BundleTx::TxHash { hash } => {
+                pending.insert(*hash);
+            }
+            BundleTx::Tx { tx, .. } => {
+                let (decoded, signature) = TypedTransaction::decode_signed(&Rlp::new(tx)).ok()?;
+                let sender = signature.recover(decoded.sighash()).ok()?;
+                nonces.insert((sender, *decoded.nonce()?));
+            }
+        }
+    }
+    Some(MergedBundle {
+        bundle,
+        pending,
+        nonces,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::CapturingExecutor;
+    use ethers::{
+        signers::{LocalWallet, Signer},
+        types::{Eip1559TransactionRequest, U64},
+    };
+    use matchmaker::types::{Refund, Validity};
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    /// A bundle backrunning `pending` with a tx signed by `wallet` with `nonce`.
+    async fn backrun(wallet: &LocalWallet, pending: u64, nonce: u64) -> BundleRequest {
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::zero())
+            .nonce(nonce)
+            .chain_id(1u64)
+            .into();
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        BundleRequest::make_simple(
+            U64::from(100),
+            vec![
+                BundleTx::TxHash {
+                    hash: H256::from_low_u64_be(pending),
+                },
+                BundleTx::Tx {
+                    tx: tx.rlp_signed(&signature),
+                    can_revert: false,
+                },
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn merges_independent_backruns() {
+        let wallet = wallet();
+        let other = LocalWallet::from_bytes(&[7u8; 32]).unwrap();
+        let bundles = vec![
+            backrun(&wallet, 1, 0).await,
+            backrun(&other, 2, 0).await,
+            // Another size of the first backrun.
+            backrun(&wallet, 1, 0).await,
+            // A backrun of another tx, with the same nonce as the first.
+            backrun(&wallet, 3, 0).await,
+        ];
+
+        let merged = merge_bundles(bundles.clone(), 10);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].body.len(), 4);
+        assert_eq!(merged[1].body.len(), 2);
+        assert_eq!(merged[2].body.len(), 2);
+
+        assert_eq!(merge_bundles(bundles, 3).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn passes_bundles_with_refunds_on_unmerged() {
+        let wallet = wallet();
+        let mut refunded = backrun(&wallet, 1, 0).await;
+        refunded.validity = Some(Validity {
+            refund: Some(vec![Refund {
+                body_idx: 0,
+                percent: 50,
+            }]),
+            refund_config: None,
+        });
+        let inner = CapturingExecutor::new();
+        let executor = BundleMergingExecutor::new(inner.clone());
+
+        executor
+            .execute(vec![refunded, backrun(&wallet, 2, 1).await])
+            .await
+            .unwrap();
+        let bundles = &inner.actions()[0];
+        assert_eq!(bundles.len(), 2);
+        assert!(bundles[1].validity.is_some());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::error::{ArtemisError, Result};
+use crate::types::{Executor, SubmissionReceipt};
+use async_trait::async_trait;
+use tokio::{sync::broadcast, time::timeout};
+use tracing::{debug, info, warn};
+
+/// How long the circuit stays open before an action is let through as a probe.
+const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Capacity of the channel of state changes.
+const STATE_CHANGE_CAPACITY: usize = 16;
+
+/// State of a [CircuitBreakerExecutor](CircuitBreakerExecutor).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Actions are executed.
+    Closed,
+    /// The executor is considered down, and actions are dropped.
+    Open,
+    /// A single action is being executed to probe whether the executor recovered.
+    HalfOpen,
+}
+
+/// Emitted whenever a [CircuitBreakerExecutor](CircuitBreakerExecutor) changes state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitStateChange {
+    /// Name of the wrapped executor.
+    pub name: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Instant,
+}
+
+/// CircuitBreakerExecutor is a wrapper around an [Executor](Executor) which disables it
+/// after `failure_threshold` consecutive failures, e.g. when a relay is down. Errors,
+/// timeouts and receipts whose every submission was rejected count as failures, except
+/// [reverts](ArtemisError::Reverted), which are the action's fault, not the executor's.
+/// While disabled, actions are dropped instead of waiting on the executor. Once the
+/// probe interval has passed, the next action is executed as a probe: the executor is
+/// re-enabled if it succeeds, and disabled for another interval otherwise.
+pub struct CircuitBreakerExecutor<E> {
+    executor: E,
+    name: String,
+    failure_threshold: u32,
+    probe_interval: Duration,
+    timeout: Option<Duration>,
+    breaker: Mutex<Breaker>,
+    state_changes: broadcast::Sender<CircuitStateChange>,
+}
+
+impl<E> CircuitBreakerExecutor<E> {
+    /// Disable `executor` after `failure_threshold` consecutive failures.
+    pub fn new(executor: E, failure_threshold: u32) -> Self {
+        let (state_changes, _) = broadcast::channel(STATE_CHANGE_CAPACITY);
+        Self {
+            executor,
+            name: String::from("executor"),
+            failure_threshold: failure_threshold.max(1),
+            probe_interval: DEFAULT_PROBE_INTERVAL,
+            timeout: None,
+            breaker: Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: Instant::now(),
+            }),
+            state_changes,
+        }
+    }
+
+    /// Name the executor in logs and state changes, e.g. after the relay it submits to.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Probe the executor once it has been disabled for `probe_interval`. Defaults to
+    /// 30 seconds.
+    pub fn with_probe_interval(mut self, probe_interval: Duration) -> Self {
+        self.probe_interval = probe_interval;
+        self
+    }
+
+    /// Count executions taking longer than `timeout` as failures, abandoning them.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.breaker.lock().unwrap().state
+    }
+
+    /// Subscribe to the state changes of the circuit.
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitStateChange> {
+        self.state_changes.subscribe()
+    }
+
+    /// Whether an action should be executed, moving to half-open if it's a probe.
+    fn admit(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap();
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::HalfOpen => false,
+            CircuitState::Open if breaker.opened_at.elapsed() >= self.probe_interval => {
+                self.transition(&mut breaker, CircuitState::HalfOpen);
+                true
+            }
+            CircuitState::Open => false,
+        }
+    }
+
+    /// Record the outcome of an execution.
+    fn record(&self, succeeded: bool) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if succeeded {
+            breaker.consecutive_failures = 0;
+            if breaker.state != CircuitState::Closed {
+                self.transition(&mut breaker, CircuitState::Closed);
+            }
+            return;
+        }
+        breaker.consecutive_failures += 1;
+        let trips = match breaker.state {
+            CircuitState::Closed => breaker.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if trips {
+            breaker.opened_at = Instant::now();
+            self.transition(&mut breaker, CircuitState::Open);
+        }
+    }
+
+    fn transition(&self, breaker: &mut Breaker, to: CircuitState) {
+        let from = breaker.state;
+        breaker.state = to;
+        match to {
+            CircuitState::Open => warn!(
+                executor = %self.name,
+                failures = breaker.consecutive_failures,
+                "disabling executor for {:?}",
+                self.probe_interval
+            ),
+            CircuitState::HalfOpen => info!(executor = %self.name, "probing disabled executor"),
+            CircuitState::Closed => info!(executor = %self.name, "re-enabling executor"),
+        }
+        // Nobody may be subscribed, which is fine.
+        let _ = self.state_changes.send(CircuitStateChange {
+            name: self.name.clone(),
+            from,
+            to,
+        });
+    }
+}
+
+#[async_trait]
+impl<E, A> Executor<A> for CircuitBreakerExecutor<E>
+where
+    E: Executor<A>,
+    A: Send + 'static,
+{
+    /// Execute the action unless the circuit is open, tracking whether it succeeds.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        if !self.admit() {
+            debug!(executor = %self.name, "executor disabled, dropping action");
+            return Ok(SubmissionReceipt::default());
+        }
+        let result = match self.timeout {
+            Some(limit) => timeout(limit, self.executor.execute(action))
+                .await
+                .unwrap_or_else(|_| Err(ArtemisError::Timeout(limit))),
+            None => self.executor.execute(action).await,
+        };
+        self.record(match &result {
+            Ok(receipt) => !receipt.is_rejected(),
+            Err(e) => matches!(e, ArtemisError::Reverted(_)),
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Submission;
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Executor failing while `failing` is set, counting its calls.
+    #[derive(Clone, Default)]
+    struct FlakyExecutor {
+        failing: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Executor<u64> for FlakyExecutor {
+        async fn execute(&self, _action: u64) -> Result<SubmissionReceipt> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            let submission = Submission::new("relay", vec![]);
+            let submission = match self.failing.load(Ordering::Relaxed) {
+                true => submission.with_error("relay down"),
+                false => submission,
+            };
+            Ok(SubmissionReceipt::new(vec![submission]))
+        }
+    }
+
+    #[tokio::test]
+    async fn opens_after_consecutive_failures_and_recovers_after_probe() {
+        let flaky = FlakyExecutor::default();
+        flaky.failing.store(true, Ordering::Relaxed);
+        let executor = CircuitBreakerExecutor::new(flaky.clone(), 2)
+            .with_name("relay")
+            .with_probe_interval(Duration::from_millis(20));
+        let mut state_changes = executor.subscribe();
+
+        assert!(executor.execute(1).await.unwrap().is_rejected());
+        assert!(executor.execute(2).await.unwrap().is_rejected());
+        assert_eq!(executor.state(), CircuitState::Open);
+
+        // Dropped without reaching the executor.
+        assert!(executor.execute(3).await.unwrap().is_empty());
+        assert_eq!(flaky.calls.load(Ordering::Relaxed), 2);
+
+        // A failed probe disables the executor again.
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(executor.execute(4).await.unwrap().is_rejected());
+        assert_eq!(executor.state(), CircuitState::Open);
+
+        flaky.failing.store(false, Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(!executor.execute(5).await.unwrap().is_rejected());
+        assert_eq!(executor.state(), CircuitState::Closed);
+        assert_eq!(flaky.calls.load(Ordering::Relaxed), 4);
+
+        let mut states = vec![];
+        while let Ok(change) = state_changes.try_recv() {
+            states.push(change.to);
+        }
+        assert_eq!(
+            states,
+            vec![
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Closed
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_timeouts_as_failures() {
+        #[derive(Clone)]
+        struct SlowExecutor;
+
+        #[async_trait]
+        impl Executor<u64> for SlowExecutor {
+            async fn execute(&self, _action: u64) -> Result<SubmissionReceipt> {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(SubmissionReceipt::default())
+            }
+        }
+
+        let executor =
+            CircuitBreakerExecutor::new(SlowExecutor, 1).with_timeout(Duration::from_millis(10));
+        assert!(matches!(
+            executor.execute(1).await,
+            Err(ArtemisError::Timeout(_))
+        ));
+        assert_eq!(executor.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn ignores_reverts() {
+        struct RevertingExecutor;
+
+        #[async_trait]
+        impl Executor<u64> for RevertingExecutor {
+            async fn execute(&self, _action: u64) -> Result<SubmissionReceipt> {
+                Err(ArtemisError::Reverted(String::from("too little received")))
+            }
+        }
+
+        let executor = CircuitBreakerExecutor::new(RevertingExecutor, 1);
+        assert!(executor.execute(1).await.is_err());
+        assert_eq!(executor.state(), CircuitState::Closed);
+    }
+}
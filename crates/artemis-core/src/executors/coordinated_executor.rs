@@ -0,0 +1,117 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::error::Result;
+use crate::types::{Executor, SubmissionReceipt};
+use crate::utilities::coordination::Coordinator;
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+/// Default time an opportunity is claimed for, about one block.
+pub const DEFAULT_OPPORTUNITY_CLAIM_TTL: Duration = Duration::from_secs(12);
+
+/// CoordinatedExecutor is a wrapper around an [Executor](Executor) which locks the
+/// opportunities of actions across the instances of a bot, through a
+/// [Coordinator](Coordinator): an action is only executed by the first instance to claim
+/// its opportunity, e.g. the hint it backruns, so replicas don't outbid each other for
+/// it. The claim is released when the execution fails or nothing is accepted, letting
+/// another instance try. Actions are executed if the coordinator can't be reached.
+pub struct CoordinatedExecutor<E, F> {
+    executor: E,
+    coordinator: Arc<dyn Coordinator>,
+    key: F,
+    ttl: Duration,
+}
+
+impl<E, F> CoordinatedExecutor<E, F> {
+    /// Lock the opportunities of the actions of `executor`, identified by `key`, through
+    /// `coordinator`.
+    pub fn new(executor: E, coordinator: Arc<dyn Coordinator>, key: F) -> Self {
+        Self {
+            executor,
+            coordinator,
+            key,
+            ttl: DEFAULT_OPPORTUNITY_CLAIM_TTL,
+        }
+    }
+
+    /// Claim opportunities for `ttl`. Should cover the blocks actions target.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+#[async_trait]
+impl<E, F, A> Executor<A> for CoordinatedExecutor<E, F>
+where
+    E: Executor<A>,
+    F: Fn(&A) -> String + Send + Sync,
+    A: Send + 'static,
+{
+    /// Execute the action if this instance claims its opportunity.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        let key = format!("opportunity:{}", (self.key)(&action));
+        let claimed = match self.coordinator.claim(&key, self.ttl).await {
+            Ok(true) => true,
+            Ok(false) => {
+                debug!(
+                    "opportunity {} claimed by another instance, dropping action",
+                    key
+                );
+                return Ok(SubmissionReceipt::default());
+            }
+            Err(e) => {
+                warn!("failed to claim opportunity {}: {}", key, e);
+                false
+            }
+        };
+        let result = self.executor.execute(action).await;
+        let failed = match &result {
+            Ok(receipt) => receipt.is_empty() || receipt.is_rejected(),
+            Err(_) => true,
+        };
+        if claimed && failed {
+            if let Err(e) = self.coordinator.release(&key).await {
+                warn!("failed to release opportunity {}: {}", key, e);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Submission;
+    use crate::utilities::coordination::LocalCoordinator;
+
+    /// Executor accepting even actions and rejecting odd ones.
+    struct EvenExecutor;
+
+    #[async_trait]
+    impl Executor<u64> for EvenExecutor {
+        async fn execute(&self, action: u64) -> Result<SubmissionReceipt> {
+            let submission = Submission::new("relay", vec![]);
+            let submission = match action % 2 {
+                0 => submission,
+                _ => submission.with_error("bundle rejected"),
+            };
+            Ok(SubmissionReceipt::new(vec![submission]))
+        }
+    }
+
+    #[tokio::test]
+    async fn first_claimant_executes_and_failures_release() {
+        let coordinator: Arc<dyn Coordinator> = Arc::new(LocalCoordinator::new());
+        let replica =
+            || CoordinatedExecutor::new(EvenExecutor, coordinator.clone(), |n: &u64| n.to_string());
+        let (first, second) = (replica(), replica());
+
+        assert!(!first.execute(2).await.unwrap().is_empty());
+        assert!(second.execute(2).await.unwrap().is_empty());
+
+        // Rejected actions release their opportunity for other instances.
+        assert!(first.execute(3).await.unwrap().is_rejected());
+        assert!(second.execute(3).await.unwrap().is_rejected());
+    }
+}
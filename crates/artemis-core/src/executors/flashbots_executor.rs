@@ -1,15 +1,23 @@
-use std::sync::Arc;
+use std::{ops::RangeInclusive, sync::Arc, time::Duration};
 
-use anyhow::Result;
+use crate::error::{ArtemisError, Result};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use ethers::{
-    providers::Middleware, signers::Signer, types::transaction::eip2718::TypedTransaction,
+    providers::Middleware,
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Bytes, H256, U256, U64},
+    utils::keccak256,
 };
-use ethers_flashbots::{BundleRequest, FlashbotsMiddleware};
+use ethers_flashbots::{BundleRequest, FlashbotsMiddleware, SimulatedBundle};
 use reqwest::Url;
-use tracing::error;
+use tracing::{debug, error, warn};
 
-use crate::types::Executor;
+use crate::{
+    bundle::{signed_tx_hash, BundleTransaction, UnifiedBundle},
+    types::{Executor, Submission, SubmissionReceipt},
+    utilities::dedup_cache::DedupCache,
+};
 
 /// A Flashbots executor that sends transactions to the Flashbots relay.
 pub struct FlashbotsExecutor<M, S> {
@@ -21,6 +29,88 @@ pub struct FlashbotsExecutor<M, S> {
 
     //Relay name
     client_name: String,
+
+    /// Hashes of recently sent bundles, so duplicates aren't sent again.
+    sent_bundles: DedupCache,
+
+    /// Number of consecutive blocks each bundle is submitted for.
+    target_blocks: u64,
+
+    /// How bundles are simulated before being sent.
+    simulation: SimulationMode,
+
+    /// Least profit a simulated bundle must make to be sent.
+    min_profit: Option<U256>,
+}
+
+/// How the [FlashbotsExecutor](FlashbotsExecutor) simulates bundles before sending them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SimulationMode {
+    /// Send bundles without simulating them, saving a round trip to the relay on
+    /// latency-critical paths.
+    Skip,
+    /// Simulate bundles and log failures, but send them regardless.
+    #[default]
+    LogOnly,
+    /// Simulate bundles, and don't send those with a reverting transaction.
+    AbortOnRevert,
+}
+
+/// Outcome of simulating a bundle with the relay's `eth_callBundle`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleSimulation {
+    /// Balance change of the block's coinbase, including the gas fees paid.
+    pub coinbase_diff: U256,
+    /// Gas used by the bundle.
+    pub gas_used: U256,
+    /// Gas fees paid by the bundle.
+    pub gas_cost: U256,
+    /// Hash and revert reason, or error, of each transaction which failed.
+    pub reverts: Vec<(H256, String)>,
+}
+
+impl BundleSimulation {
+    /// What the bundle pays the builder on top of its gas fees.
+    pub fn profit(&self) -> U256 {
+        self.coinbase_diff.saturating_sub(self.gas_cost)
+    }
+
+    /// Returns why the bundle shouldn't be sent, if it reverts and `abort_on_revert`, or
+    /// makes less than `min_profit`.
+    fn rejection(&self, abort_on_revert: bool, min_profit: Option<U256>) -> Option<String> {
+        if let Some((hash, reason)) = self.reverts.first() {
+            if abort_on_revert {
+                return Some(format!("reverts at tx {:?}: {}", hash, reason));
+            }
+        }
+        match min_profit {
+            Some(min_profit) if self.profit() < min_profit => Some(format!(
+                "profit {} is below the minimum of {}",
+                self.profit(),
+                min_profit
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl From<&SimulatedBundle> for BundleSimulation {
+    fn from(simulated: &SimulatedBundle) -> Self {
+        let reverts = simulated
+            .transactions
+            .iter()
+            .filter_map(|tx| {
+                let reason = tx.revert.as_ref().or(tx.error.as_ref())?;
+                Some((tx.hash, reason.clone()))
+            })
+            .collect();
+        Self {
+            coinbase_diff: simulated.coinbase_diff,
+            gas_used: simulated.gas_used,
+            gas_cost: simulated.gas_fees,
+            reverts,
+        }
+    }
 }
 
 /// A bundle of transactions to send to the Flashbots relay.
@@ -33,53 +123,223 @@ impl<M: Middleware, S: Signer> FlashbotsExecutor<M, S> {
             fb_client,
             tx_signer,
             client_name: relay_name.into(),
-
+            sent_bundles: DedupCache::default(),
+            target_blocks: 1,
+            simulation: SimulationMode::default(),
+            min_profit: None,
         }
     }
+
+    /// Submit each bundle for the next `target_blocks` blocks, instead of only the next
+    /// one, so it can still land if the next block is missed.
+    pub fn with_target_blocks(mut self, target_blocks: u64) -> Self {
+        self.target_blocks = target_blocks.max(1);
+        self
+    }
+
+    /// Simulate bundles according to `simulation`. Defaults to
+    /// [LogOnly](SimulationMode::LogOnly).
+    pub fn with_simulation(mut self, simulation: SimulationMode) -> Self {
+        self.simulation = simulation;
+        self
+    }
+
+    /// Only send bundles which pay the builder at least `min_profit` wei on top of their
+    /// gas fees in simulation. Bundles are simulated even with
+    /// [Skip](SimulationMode::Skip), and aren't sent if the simulation fails.
+    pub fn with_min_profit(mut self, min_profit: U256) -> Self {
+        self.min_profit = Some(min_profit);
+        self
+    }
+
+    /// Remember sent bundles for `ttl`, instead of the default of one block.
+    pub fn with_dedup_ttl(mut self, ttl: Duration) -> Self {
+        self.sent_bundles = DedupCache::new(ttl);
+        self
+    }
 }
 
-#[async_trait]
-impl<M, S> Executor<FlashbotsBundle> for FlashbotsExecutor<M, S>
+impl<M, S> FlashbotsExecutor<M, S>
 where
     M: Middleware + 'static,
     M::Error: 'static,
     S: Signer + 'static,
 {
-    /// Send a bundle to transactions to the Flashbots relay.
-    async fn execute(&self, action: FlashbotsBundle) -> Result<()> {
-        // Add txs to bundle.
-        let mut bundle = BundleRequest::new();
+    /// Returns the number of the latest block.
+    async fn block_number(&self) -> Result<U64> {
+        self.fb_client
+            .get_block_number()
+            .await
+            .map_err(ArtemisError::from_middleware)
+    }
 
-        // Sign each transaction in bundle.
-        for tx in action {
-            let signature = self.tx_signer.sign_transaction(&tx).await?;
-            bundle.add_transaction(tx.rlp_signed(&signature));
+    /// Simulate `bundle`, made of `raw_txs`, on top of `block_number`, and send it for
+    /// each of `target_blocks`, unless it was already sent or fails simulation.
+    async fn submit(
+        &self,
+        bundle: BundleRequest,
+        raw_txs: Vec<Bytes>,
+        block_number: U64,
+        target_blocks: RangeInclusive<u64>,
+    ) -> SubmissionReceipt {
+        let tx_hashes: Vec<H256> = raw_txs
+            .iter()
+            .map(|raw| signed_tx_hash(raw))
+            .collect();
+
+        // Skip bundles already sent for the same block.
+        let mut bundle_bytes: Vec<u8> = raw_txs.iter().flat_map(|raw| raw.to_vec()).collect();
+        bundle_bytes.extend_from_slice(&block_number.as_u64().to_be_bytes());
+        let bundle_hash = H256::from(keccak256(bundle_bytes));
+        if !self.sent_bundles.insert(bundle_hash) {
+            debug!(
+                "skipping duplicate bundle {:?} to {}",
+                bundle_hash, self.client_name
+            );
+            return SubmissionReceipt::default();
         }
 
         // Simulate bundle.
-        let block_number = self.fb_client.get_block_number().await?;
         let bundle = bundle
-            .set_block(block_number + 1)
+            .set_block(U64::from(*target_blocks.start()))
             .set_simulation_block(block_number)
             .set_simulation_timestamp(0);
 
-        let simulated_bundle = self.fb_client.simulate_bundle(&bundle).await;
+        if self.simulation != SimulationMode::Skip || self.min_profit.is_some() {
+            match self.fb_client.simulate_bundle(&bundle).await {
+                Ok(simulated_bundle) => {
+                    let simulation = BundleSimulation::from(&simulated_bundle);
+                    debug!(
+                        "bundle {:?} to {} simulated: {:?}",
+                        bundle_hash, self.client_name, simulation
+                    );
+                    for (hash, reason) in &simulation.reverts {
+                        warn!(
+                            "bundle {:?} to {} reverts at tx {:?}: {}",
+                            bundle_hash, self.client_name, hash, reason
+                        );
+                    }
+                    let abort_on_revert = self.simulation == SimulationMode::AbortOnRevert;
+                    if let Some(reason) = simulation.rejection(abort_on_revert, self.min_profit) {
+                        warn!(
+                            "not sending bundle {:?} to {}: {}",
+                            bundle_hash, self.client_name, reason
+                        );
+                        return SubmissionReceipt::default();
+                    }
+                }
+                Err(simulate_error) => {
+                    error!("Error simulating bundle: {:?}", simulate_error);
+                    if self.min_profit.is_some() {
+                        return SubmissionReceipt::default();
+                    }
+                }
+            }
+        }
 
-        if let Err(simulate_error) = simulated_bundle {
-            error!("Error simulating bundle: {:?}", simulate_error);
+        // Send bundle for each target block.
+        let mut submissions = Vec::new();
+        for target_block in target_blocks {
+            let target_block = U64::from(target_block);
+            let bundle = bundle.clone().set_block(target_block);
+            let submission = Submission::new(self.client_name.clone(), tx_hashes.clone())
+                .with_target_block(target_block);
+            let submission = match self.fb_client.send_bundle(&bundle).await {
+                Ok(pending_bundle) => {
+                    submission.with_bundle_hash(pending_bundle.bundle_hash.into())
+                }
+                Err(send_error) => {
+                    error!(
+                        "Error sending bundle for block {}: {:?}",
+                        target_block, send_error
+                    );
+                    submission.with_error(send_error)
+                }
+            };
+            submissions.push(submission);
         }
 
-        // Send bundle.
-        let pending_bundle = self.fb_client.send_bundle(&bundle).await;
+        SubmissionReceipt::new(submissions)
+    }
+}
+
+#[async_trait]
+impl<M, S> Executor<FlashbotsBundle> for FlashbotsExecutor<M, S>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+    S: Signer + 'static,
+{
+    /// Send a bundle to transactions to the Flashbots relay, once for each target block.
+    async fn execute(&self, action: FlashbotsBundle) -> Result<SubmissionReceipt> {
+        // Add txs to bundle.
+        let mut bundle = BundleRequest::new();
 
-        if let Err(send_error) = pending_bundle {
-            error!("Error sending bundle: {:?}", send_error);
+        // Sign each transaction in bundle.
+        let mut raw_txs = Vec::new();
+        for tx in action {
+            let signature = self
+                .tx_signer
+                .sign_transaction(&tx)
+                .await
+                .map_err(|e| anyhow!("Error signing transaction: {}", e))?;
+            let raw = tx.rlp_signed(&signature);
+            raw_txs.push(raw.clone());
+            bundle.add_transaction(raw);
         }
 
-        Ok(())
+        let block_number = self.block_number().await?;
+        let block = block_number.as_u64();
+        let target_blocks = block + 1..=block + self.target_blocks;
+        Ok(self
+            .submit(bundle, raw_txs, block_number, target_blocks)
+            .await)
     }
 }
 
+#[async_trait]
+impl<M, S> Executor<UnifiedBundle> for FlashbotsExecutor<M, S>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+    S: Signer + 'static,
+{
+    /// Send a bundle to the Flashbots relay, once for each block it is valid for after
+    /// the latest one. Bundles with pending transactions are skipped, since only the
+    /// MEV-share matchmaker can include them.
+    async fn execute(&self, action: UnifiedBundle) -> Result<SubmissionReceipt> {
+        let bundle = match action.to_flashbots() {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                debug!("skipping bundle to {}: {}", self.client_name, e);
+                return Ok(SubmissionReceipt::default());
+            }
+        };
+        let raw_txs = action
+            .transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                BundleTransaction::Signed { raw, .. } => Some(raw.clone()),
+                BundleTransaction::Pending(_) => None,
+            })
+            .collect();
+
+        let block_number = self.block_number().await?;
+        let first_block = action.block.max(block_number + 1).as_u64();
+        let target_blocks = first_block..=action.last_block().as_u64();
+        if target_blocks.is_empty() {
+            debug!(
+                "skipping bundle to {} for past block {}",
+                self.client_name,
+                action.last_block()
+            );
+            return Ok(SubmissionReceipt::default());
+        }
+        Ok(self
+            .submit(bundle, raw_txs, block_number, target_blocks)
+            .await)
+    }
+}
 
 pub async fn get_all_relay_endpoints<M, S>(client: Arc<M>, tx_signer: S, relay_signer: S) -> Vec<Arc<Box<FlashbotsExecutor<M, S>>>> 
 where
@@ -112,4 +372,31 @@ where
 
     relays
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unprofitable_and_reverting_bundles() {
+        let simulation = BundleSimulation {
+            coinbase_diff: U256::from(1_500),
+            gas_used: U256::from(100),
+            gas_cost: U256::from(1_000),
+            reverts: vec![],
+        };
+        assert_eq!(simulation.profit(), U256::from(500));
+        assert_eq!(simulation.rejection(true, Some(U256::from(500))), None);
+        assert!(simulation.rejection(false, Some(U256::from(501))).is_some());
+
+        let simulation = BundleSimulation {
+            reverts: vec![(H256::zero(), "too little received".to_string())],
+            ..simulation
+        };
+        assert_eq!(simulation.rejection(false, None), None);
+        assert!(simulation
+            .rejection(true, None)
+            .unwrap()
+            .contains("too little received"));
+    }
+}
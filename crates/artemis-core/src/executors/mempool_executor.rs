@@ -3,8 +3,9 @@ use std::{
     sync::Arc,
 };
 
-use crate::types::Executor;
-use anyhow::{Context, Result};
+use crate::error::{ArtemisError, Result};
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use anyhow::Context;
 use async_trait::async_trait;
 use ethers::{
     providers::Middleware,
@@ -45,7 +46,7 @@ where
     M::Error: 'static,
 {
     /// Send a transaction to the mempool.
-    async fn execute(&self, mut action: SubmitTxToMempool) -> Result<()> {
+    async fn execute(&self, mut action: SubmitTxToMempool) -> Result<SubmissionReceipt> {
         let gas_usage = self
             .client
             .estimate_gas(&action.tx, None)
@@ -68,7 +69,14 @@ where
                 .context("Error getting gas price: {}")?;
         }
         action.tx.set_gas_price(bid_gas_price);
-        self.client.send_transaction(action.tx, None).await?;
-        Ok(())
+        let pending = self
+            .client
+            .send_transaction(action.tx, None)
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        Ok(SubmissionReceipt::new(vec![Submission::new(
+            "mempool",
+            vec![pending.tx_hash()],
+        )]))
     }
 }
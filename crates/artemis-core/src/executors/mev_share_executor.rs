@@ -1,16 +1,40 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use crate::types::Executor;
-use anyhow::Result;
+use crate::error::Result;
+use crate::{
+    bundle::UnifiedBundle,
+    types::{Executor, Submission, SubmissionReceipt},
+    utilities::dedup_cache::DedupCache,
+};
 use async_trait::async_trait;
-use ethers::{signers::Signer, types::Chain};
-use futures::{stream, StreamExt};
-use matchmaker::{client::Client, types::BundleRequest};
-use tracing::{error, info};
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{Chain, H256, U64},
+    utils::keccak256,
+};
+use futures::{future::BoxFuture, stream, FutureExt, StreamExt};
+use matchmaker::{
+    builders::BuilderDirectory,
+    client::Client,
+    status::TrackedBundles,
+    types::{BundleRequest, BundleTx},
+};
+use tracing::{debug, error, info};
 
-/// An executor that sends bundles to the MEV-share Matchmaker.
+/// Returns the number of the latest block, to validate bundles against.
+type BlockNumberFn = Box<dyn Fn() -> BoxFuture<'static, Option<U64>> + Send + Sync>;
+
+/// An executor that sends bundles to the MEV-share Matchmaker. Bundles are
+/// [validated](BundleRequest::validate) first, and invalid ones aren't sent. Duplicates
+/// of recently sent bundles are skipped, unless the matchmaker didn't accept them.
 pub struct MevshareExecutor<S> {
     matchmaker_client: Client<S>,
+    /// Hashes of recently sent bundles, so duplicates aren't sent again.
+    sent_bundles: DedupCache,
+    block_number: Option<BlockNumberFn>,
+    /// Bundles whose status is tracked once sent, if any.
+    tracked_bundles: Option<TrackedBundles>,
 }
 
 /// List of bundles to send to the Matchmaker.
@@ -20,31 +44,152 @@ impl<S: Signer + Clone + 'static> MevshareExecutor<S> {
     pub fn new(signer: S, chain: Chain) -> Self {
         Self {
             matchmaker_client: Client::new(signer, chain),
+            sent_bundles: DedupCache::default(),
+            block_number: None,
+            tracked_bundles: None,
         }
     }
+
+    /// Send bundles to the matchmaker at `url`, e.g. a local relay in tests, instead of
+    /// the Flashbots relay.
+    pub fn from_url(signer: S, url: &str) -> Self {
+        Self {
+            matchmaker_client: Client::from_url(signer, url),
+            sent_bundles: DedupCache::default(),
+            block_number: None,
+            tracked_bundles: None,
+        }
+    }
+
+    /// Reject bundles targeting blocks before the latest block of `client`. Without a
+    /// client, only the contents of bundles are validated.
+    pub fn with_client<M: Middleware + 'static>(mut self, client: Arc<M>) -> Self {
+        self.block_number = Some(Box::new(move || {
+            let client = client.clone();
+            async move { client.get_block_number().await.ok() }.boxed()
+        }));
+        self
+    }
+
+    /// Share bundles which don't name builders with the builders in `directory`, e.g. one
+    /// [refreshed](BuilderDirectory::spawn_refresh) from the public builder registry.
+    pub fn with_builder_directory(mut self, directory: Arc<BuilderDirectory>) -> Self {
+        self.matchmaker_client = self.matchmaker_client.with_builder_directory(directory);
+        self
+    }
+
+    /// Returns the block bundles must target, or zero if it isn't known.
+    async fn current_block(&self) -> U64 {
+        match &self.block_number {
+            Some(block_number) => block_number().await.unwrap_or_default(),
+            None => U64::zero(),
+        }
+    }
+
+    /// Track the status of the bundles the matchmaker accepts with `bundles`, e.g.
+    /// the [bundles](matchmaker::status::BundleStatusTracker::bundles) of a tracker.
+    pub fn with_tracked_bundles(mut self, bundles: TrackedBundles) -> Self {
+        self.tracked_bundles = Some(bundles);
+        self
+    }
+
+    /// Remember sent bundles for `ttl`, instead of the default of one block.
+    pub fn with_dedup_ttl(mut self, ttl: Duration) -> Self {
+        self.sent_bundles = DedupCache::new(ttl);
+        self
+    }
 }
 
 #[async_trait]
 impl<S: Signer + Clone + 'static> Executor<Bundles> for MevshareExecutor<S> {
-    /// Send bundles to the matchmaker.
-    async fn execute(&self, action: Bundles) -> Result<()> {
-        let bodies = stream::iter(action)
+    /// Send bundles to the matchmaker, returning the bundle hash it acknowledged, or
+    /// its error, for each.
+    async fn execute(&self, action: Bundles) -> Result<SubmissionReceipt> {
+        let current_block = self.current_block().await;
+        let action: Vec<(H256, BundleRequest)> = action
+            .into_iter()
             .map(|bundle| {
-                let client = &self.matchmaker_client;
-                async move { client.send_bundle(&bundle).await }
+                let hash = H256::from(keccak256(serde_json::to_vec(&bundle).unwrap_or_default()));
+                (hash, bundle)
             })
-            .buffer_unordered(5);
+            .filter(|(hash, _)| {
+                let is_new = self.sent_bundles.insert(*hash);
+                if !is_new {
+                    debug!("skipping duplicate bundle {:?}", hash);
+                }
+                is_new
+            })
+            .collect();
 
-        bodies
-            .for_each(|b| async {
-                match b {
-                    Ok(b) => info!("Bundle response: {:?}", b),
-                    Err(e) => error!("Bundle error: {}", e),
+        let submissions = stream::iter(action)
+            .map(|(hash, bundle)| {
+                let client = &self.matchmaker_client;
+                let tracked_bundles = &self.tracked_bundles;
+                let sent_bundles = &self.sent_bundles;
+                async move {
+                    let submission = Submission::new("mev-share", tx_hashes(&bundle))
+                        .with_target_block(bundle.inclusion.block);
+                    if let Err(e) = bundle.validate(current_block) {
+                        error!("Invalid bundle: {}", e);
+                        return submission.with_error(e);
+                    }
+                    match client.send_bundle(&bundle).await {
+                        Ok(b) => {
+                            info!("Bundle response: {:?}", b);
+                            if let Some(bundles) = tracked_bundles {
+                                bundles.track(
+                                    b.bundle_hash,
+                                    bundle.inclusion.max_block.unwrap_or(bundle.inclusion.block),
+                                    signed_tx_hashes(&bundle),
+                                );
+                            }
+                            submission.with_bundle_hash(Some(b.bundle_hash))
+                        }
+                        Err(e) => {
+                            error!("Bundle error: {}", e);
+                            // The matchmaker didn't accept the bundle, so it may be resent.
+                            sent_bundles.remove(&hash);
+                            submission.with_error(e)
+                        }
+                    }
                 }
             })
+            .buffer_unordered(5)
+            .collect()
             .await;
-        Ok(())
+        Ok(SubmissionReceipt::new(submissions))
     }
 }
 
+#[async_trait]
+impl<S: Signer + Clone + 'static> Executor<UnifiedBundle> for MevshareExecutor<S> {
+    /// Send the bundle to the matchmaker.
+    async fn execute(&self, action: UnifiedBundle) -> Result<SubmissionReceipt> {
+        Executor::<Bundles>::execute(self, vec![action.to_mev_share()]).await
+    }
+}
 
+/// Returns the hashes of the transactions in a bundle, including those it backruns.
+fn tx_hashes(bundle: &BundleRequest) -> Vec<H256> {
+    bundle
+        .body
+        .iter()
+        .map(|tx| match tx {
+            BundleTx::TxHash { hash } => *hash,
+            BundleTx::Tx { tx, .. } => H256::from(keccak256(tx)),
+        })
+        .collect()
+}
+
+/// Returns the hashes of the signed transactions in a bundle, not including those it
+/// backruns.
+fn signed_tx_hashes(bundle: &BundleRequest) -> Vec<H256> {
+    bundle
+        .body
+        .iter()
+        .filter_map(|tx| match tx {
+            BundleTx::TxHash { .. } => None,
+            BundleTx::Tx { tx, .. } => Some(H256::from(keccak256(tx))),
+        })
+        .collect()
+}
@@ -2,6 +2,27 @@
 //! executing them in different domains. For example, an executor might take a
 //! `SubmitTx` action and submit it to the mempool.
 
+/// This executor submits transactions to the public mempool through an alloy provider.
+#[cfg(feature = "alloy")]
+pub mod alloy_mempool_executor;
+
+/// This executor constructs, signs and sends EIP-4844 blob transactions through an
+/// alloy provider.
+#[cfg(feature = "alloy")]
+pub mod blob_executor;
+
+/// This executor merges independent bundles targeting the same block before passing
+/// them to another executor.
+pub mod bundle_merging_executor;
+
+/// This executor disables another executor after consecutive failures, until it
+/// recovers.
+pub mod circuit_breaker_executor;
+
+/// This executor executes actions only if this instance claims their opportunity first,
+/// across the instances of a bot.
+pub mod coordinated_executor;
+
 /// This executor submits transactions to the flashbots relay.
 pub mod flashbots_executor;
 
@@ -10,3 +31,23 @@ pub mod mempool_executor;
 
 /// This executor submits bundles to the flashbots matchmaker.
 pub mod mev_share_executor;
+
+/// This executor logs actions instead of executing them, for dry runs.
+pub mod noop_executor;
+
+/// This executor publishes actions to a message queue, to be executed by another
+/// process.
+pub mod queue_executor;
+
+/// This executor throttles submissions of another executor.
+pub mod rate_limited_executor;
+
+/// This executor sends actions to the best performing of several relays.
+pub mod relay_selection_executor;
+
+/// This executor submits transactions directly to an L2 sequencer.
+pub mod sequencer_executor;
+
+/// This executor sends actions to fast relays first, and to slower relays staggered
+/// after them.
+pub mod staggered_executor;
@@ -0,0 +1,49 @@
+use std::fmt::Debug;
+
+use crate::error::Result;
+use crate::types::{Executor, SubmissionReceipt};
+use async_trait::async_trait;
+use tracing::info;
+
+/// An executor which logs actions instead of executing them, used by the
+/// [Engine](crate::engine::Engine) in dry-run mode to validate configuration and
+/// strategy logic against live data without submitting anything. Actions can also
+/// be passed to a simulator, e.g. an executor which `eth_call`s transactions instead
+/// of sending them.
+pub struct NoopExecutor<A> {
+    simulator: Option<Box<dyn Executor<A>>>,
+}
+
+impl<A> NoopExecutor<A> {
+    pub fn new() -> Self {
+        Self { simulator: None }
+    }
+
+    /// Pass every action to `simulator` after logging it. The simulator must not
+    /// submit anything. Failed simulations are reported as execution errors.
+    pub fn with_simulator(mut self, simulator: Box<dyn Executor<A>>) -> Self {
+        self.simulator = Some(simulator);
+        self
+    }
+}
+
+impl<A> Default for NoopExecutor<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for NoopExecutor<A>
+where
+    A: Debug + Send + 'static,
+{
+    /// Log the action, and simulate it if a simulator is set.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        info!(?action, "dry run, not executing action");
+        match &self.simulator {
+            Some(simulator) => simulator.execute(action).await,
+            None => Ok(SubmissionReceipt::default()),
+        }
+    }
+}
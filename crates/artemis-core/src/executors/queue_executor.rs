@@ -0,0 +1,33 @@
+use crate::error::Result;
+use crate::transport::Publisher;
+use crate::types::{Executor, SubmissionReceipt};
+use async_trait::async_trait;
+use serde::Serialize;
+
+/// QueueExecutor publishes actions to a topic of a
+/// [Transport](crate::transport::Transport), to be executed by another process running
+/// a [QueueCollector](crate::collectors::queue_collector::QueueCollector), e.g. next to
+/// the relays. Processes which only run collectors publish their events the same way,
+/// through a [ForwardingStrategy](crate::transport::ForwardingStrategy). Nothing is
+/// submitted by this process, so receipts are empty.
+pub struct QueueExecutor<A> {
+    publisher: Publisher<A>,
+}
+
+impl<A> QueueExecutor<A> {
+    pub fn new(publisher: Publisher<A>) -> Self {
+        Self { publisher }
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for QueueExecutor<A>
+where
+    A: Serialize + Send + Sync + 'static,
+{
+    /// Publish the action to the topic.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        self.publisher.publish(&action).await?;
+        Ok(SubmissionReceipt::default())
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::error::{ArtemisError, Result};
+use crate::types::{Executor, SubmissionReceipt};
+use async_trait::async_trait;
+use tokio::time::sleep;
+use tracing::debug;
+
+/// A token bucket rate limiter. The bucket holds up to `burst` tokens and refills at
+/// `rate_per_second`; every submission takes one token. Wrap it in an [Arc](Arc) to share
+/// a limit between the executors of endpoints run by the same operator.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `rate_per_second` submissions on average, and bursts of
+    /// up to `burst` submissions. The bucket starts full.
+    pub fn new(rate_per_second: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token if one is available. Otherwise returns how long until one will be.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate_per_second).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        if self.rate_per_second <= 0.0 {
+            return Err(Duration::MAX);
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - bucket.tokens) / self.rate_per_second,
+        ))
+    }
+
+    /// Wait for a token, giving up if none is available by `deadline`.
+    async fn acquire(&self, deadline: Option<Instant>) -> Result<()> {
+        loop {
+            let wait = match self.try_acquire() {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+            if let Some(deadline) = deadline {
+                match Instant::now().checked_add(wait) {
+                    Some(at) if at <= deadline => {}
+                    _ => {
+                        return Err(ArtemisError::RateLimited(String::from(
+                            "rate limit exceeded",
+                        )))
+                    }
+                }
+            }
+            sleep(wait).await;
+        }
+    }
+}
+
+/// RateLimitedExecutor is a wrapper around an [Executor](Executor) which throttles
+/// submissions with its own [RateLimiter](RateLimiter), and optionally with limiters
+/// shared with other executors. Actions wait for a token, up to `max_wait` if set,
+/// after which they are rejected.
+pub struct RateLimitedExecutor<E> {
+    executor: E,
+    limiters: Vec<Arc<RateLimiter>>,
+    max_wait: Option<Duration>,
+}
+
+impl<E> RateLimitedExecutor<E> {
+    /// Limit `executor` to `rate_per_second` submissions, with bursts of up to `burst`.
+    pub fn new(executor: E, rate_per_second: f64, burst: u32) -> Self {
+        Self {
+            executor,
+            limiters: vec![Arc::new(RateLimiter::new(rate_per_second, burst))],
+            max_wait: None,
+        }
+    }
+
+    /// Additionally limit submissions by a limiter shared with other executors, e.g.
+    /// for endpoints pointing to the same operator.
+    pub fn with_shared_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiters.push(limiter);
+        self
+    }
+
+    /// Reject actions which can't be submitted within `max_wait`, instead of queueing them.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+}
+
+#[async_trait]
+impl<E, A> Executor<A> for RateLimitedExecutor<E>
+where
+    E: Executor<A>,
+    A: Send + 'static,
+{
+    /// Wait for a token from every limiter, then execute the action.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        let deadline = self
+            .max_wait
+            .and_then(|wait| Instant::now().checked_add(wait));
+        for limiter in &self.limiters {
+            limiter.acquire(deadline).await?;
+        }
+        debug!("rate limiter passed action to executor");
+        self.executor.execute(action).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::CapturingExecutor;
+
+    #[test]
+    fn bucket_allows_burst_then_throttles() {
+        let limiter = RateLimiter::new(10.0, 3);
+        for _ in 0..3 {
+            assert!(limiter.try_acquire().is_ok());
+        }
+        let wait = limiter.try_acquire().unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn shared_limiter_throttles_every_executor() {
+        let shared = Arc::new(RateLimiter::new(0.0, 2));
+        let a = CapturingExecutor::new();
+        let b = CapturingExecutor::new();
+        let limited_a = RateLimitedExecutor::new(a.clone(), 100.0, 10)
+            .with_shared_limiter(shared.clone())
+            .with_max_wait(Duration::from_millis(10));
+        let limited_b = RateLimitedExecutor::new(b.clone(), 100.0, 10)
+            .with_shared_limiter(shared)
+            .with_max_wait(Duration::from_millis(10));
+
+        assert!(limited_a.execute(1u64).await.is_ok());
+        assert!(limited_b.execute(2u64).await.is_ok());
+        assert!(limited_a.execute(3u64).await.is_err());
+        assert_eq!(a.actions(), vec![1]);
+        assert_eq!(b.actions(), vec![2]);
+    }
+}
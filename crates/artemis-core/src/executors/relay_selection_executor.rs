@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::error::Result;
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use async_trait::async_trait;
+use ethers::types::{H256, U64};
+use futures::future::join_all;
+use serde::Serialize;
+use tracing::debug;
+
+/// Submission counts of a single relay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelayStats {
+    /// Bundles submitted to the relay.
+    pub submitted: u64,
+    /// Bundles the relay acknowledged.
+    pub accepted: u64,
+    /// Bundles the relay rejected, or which failed to reach it.
+    pub errors: u64,
+    /// Acknowledged bundles which landed on chain.
+    pub included: u64,
+    /// Landed bundles whose block was built by the relay's builder, according to the
+    /// data APIs of MEV-boost relays.
+    pub delivered: u64,
+}
+
+impl RelayStats {
+    pub fn acceptance_rate(&self) -> f64 {
+        ratio(self.accepted, self.submitted)
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        ratio(self.errors, self.submitted)
+    }
+
+    /// Share of acknowledged bundles which landed on chain.
+    pub fn inclusion_rate(&self) -> f64 {
+        ratio(self.included, self.accepted)
+    }
+
+    /// Share of landed bundles whose block was built by the relay's builder.
+    pub fn delivery_rate(&self) -> f64 {
+        ratio(self.delivered, self.included)
+    }
+
+    /// How likely a bundle submitted to the relay is to land, used to rank relays. Rates
+    /// are smoothed, so relays without history rank between good and bad ones rather
+    /// than at either end.
+    pub fn score(&self) -> f64 {
+        let acceptance = (self.accepted as f64 + 1.0) / (self.submitted as f64 + 2.0);
+        let inclusion = (self.included as f64 + 1.0) / (self.accepted as f64 + 2.0);
+        acceptance * inclusion
+    }
+}
+
+fn ratio(count: u64, total: u64) -> f64 {
+    match total {
+        0 => 0.0,
+        total => count as f64 / total as f64,
+    }
+}
+
+/// A bundle acknowledged by relays, waiting to be seen on chain.
+#[derive(Debug)]
+struct PendingBundle {
+    /// Last block the bundle targets.
+    last_block: U64,
+    relays: Vec<String>,
+}
+
+/// Tracks how each relay responds to submissions, and which of the bundles it
+/// acknowledged land on chain. Fed with the [receipts](SubmissionReceipt) of
+/// submissions, and the transactions of new blocks.
+#[derive(Debug, Default)]
+pub struct RelayScoreboard {
+    stats: Mutex<HashMap<String, RelayStats>>,
+    /// Acknowledged bundles, by the hash of their last transaction.
+    pending: Mutex<HashMap<H256, PendingBundle>>,
+}
+
+impl RelayScoreboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the response of each relay in `receipt`, by the destination of its
+    /// submissions.
+    pub fn record(&self, receipt: &SubmissionReceipt) {
+        let mut stats = self.stats.lock().unwrap();
+        let mut pending = self.pending.lock().unwrap();
+        for submission in &receipt.submissions {
+            let relay = stats.entry(submission.destination.clone()).or_default();
+            relay.submitted += 1;
+            if !submission.is_accepted() {
+                relay.errors += 1;
+                continue;
+            }
+            relay.accepted += 1;
+
+            // Only bundles targeting a block can be found to have landed or not.
+            let (Some(tx_hash), Some(target_block)) =
+                (submission.tx_hashes.last(), submission.target_block)
+            else {
+                continue;
+            };
+            let bundle = pending.entry(*tx_hash).or_insert_with(|| PendingBundle {
+                last_block: target_block,
+                relays: vec![],
+            });
+            bundle.last_block = bundle.last_block.max(target_block);
+            if !bundle.relays.contains(&submission.destination) {
+                bundle.relays.push(submission.destination.clone());
+            }
+        }
+    }
+
+    /// Record the bundles which landed in block `number`, given the hashes of its
+    /// transactions. A landed bundle counts as included for every relay which
+    /// acknowledged it, since the relay which delivered it isn't known. Bundles whose
+    /// target blocks have all passed are forgotten.
+    pub fn record_block(&self, number: U64, tx_hashes: &[H256]) {
+        let mut stats = self.stats.lock().unwrap();
+        let mut pending = self.pending.lock().unwrap();
+        for tx_hash in tx_hashes {
+            if let Some(bundle) = pending.remove(tx_hash) {
+                for relay in bundle.relays {
+                    stats.entry(relay).or_default().included += 1;
+                }
+            }
+        }
+        pending.retain(|_, bundle| bundle.last_block > number);
+    }
+
+    /// Record that a bundle acknowledged by `relay` landed in a block built by its
+    /// builder, as correlated by a
+    /// [RelayInclusionTracker](crate::relay_data::RelayInclusionTracker).
+    pub fn record_delivered(&self, relay: &str) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.entry(relay.to_string()).or_default().delivered += 1;
+    }
+
+    /// Returns the stats of `relay`, empty if nothing was submitted to it yet.
+    pub fn stats(&self, relay: &str) -> RelayStats {
+        self.stats
+            .lock()
+            .unwrap()
+            .get(relay)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the stats of every relay submitted to, best first.
+    pub fn ranking(&self) -> Vec<(String, RelayStats)> {
+        let mut ranking: Vec<_> = self
+            .stats
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(relay, stats)| (relay.clone(), *stats))
+            .collect();
+        ranking.sort_by(|(_, a), (_, b)| b.score().total_cmp(&a.score()));
+        ranking
+    }
+
+    /// Returns the `k` best of `relays`. Ties keep the order of `relays`.
+    pub fn top<'a>(&self, relays: impl IntoIterator<Item = &'a str>, k: usize) -> Vec<&'a str> {
+        let stats = self.stats.lock().unwrap();
+        let score = |relay: &str| stats.get(relay).copied().unwrap_or_default().score();
+        let mut relays: Vec<&str> = relays.into_iter().collect();
+        relays.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        relays.truncate(k);
+        relays
+    }
+}
+
+/// RelaySelectionExecutor sends each action to the `top_k` best performing of a set
+/// of relay executors, as ranked by a [RelayScoreboard](RelayScoreboard), instead of
+/// broadcasting it to all of them. This leaks opportunities to fewer parties, and
+/// waits on fewer relays. The receipts of the relays are merged, and recorded to the
+/// scoreboard.
+pub struct RelaySelectionExecutor<E> {
+    relays: Vec<(String, E)>,
+    scoreboard: Arc<RelayScoreboard>,
+    top_k: usize,
+}
+
+impl<E> RelaySelectionExecutor<E> {
+    /// Send actions to the `top_k` best relays, as ranked by `scoreboard`.
+    pub fn new(scoreboard: Arc<RelayScoreboard>, top_k: usize) -> Self {
+        Self {
+            relays: vec![],
+            scoreboard,
+            top_k: top_k.max(1),
+        }
+    }
+
+    /// Add a relay executor. `name` should be the destination of its submissions, so
+    /// their outcomes are attributed to it.
+    pub fn with_relay(mut self, name: impl Into<String>, executor: E) -> Self {
+        self.relays.push((name.into(), executor));
+        self
+    }
+
+    pub fn scoreboard(&self) -> Arc<RelayScoreboard> {
+        self.scoreboard.clone()
+    }
+}
+
+#[async_trait]
+impl<E, A> Executor<A> for RelaySelectionExecutor<E>
+where
+    E: Executor<A>,
+    A: Clone + Send + Sync + 'static,
+{
+    /// Send the action to the best relays concurrently, recording their responses.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        let selected = self.scoreboard.top(
+            self.relays.iter().map(|(name, _)| name.as_str()),
+            self.top_k,
+        );
+        debug!("submitting to relays {:?}", selected);
+
+        let executions = self
+            .relays
+            .iter()
+            .filter(|(name, _)| selected.contains(&name.as_str()))
+            .map(|(name, executor)| {
+                let action = action.clone();
+                async move {
+                    match executor.execute(action).await {
+                        Ok(receipt) => receipt.submissions,
+                        // Count errors against the relay, as if it had rejected the action.
+                        Err(e) => vec![Submission::new(name.clone(), vec![]).with_error(e)],
+                    }
+                }
+            });
+        let submissions = join_all(executions).await.into_iter().flatten().collect();
+
+        let receipt = SubmissionReceipt::new(submissions);
+        self.scoreboard.record(&receipt);
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ArtemisError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn submission(relay: &str, tx_hash: u64, error: Option<&str>) -> Submission {
+        let submission = Submission::new(relay, vec![H256::from_low_u64_be(tx_hash)])
+            .with_target_block(U64::from(10));
+        match error {
+            Some(error) => submission.with_error(error),
+            None => submission,
+        }
+    }
+
+    #[test]
+    fn scores_relays_by_acceptance_and_inclusion() {
+        let scoreboard = RelayScoreboard::new();
+        scoreboard.record(&SubmissionReceipt::new(vec![
+            submission("landing", 1, None),
+            submission("acking", 1, None),
+            submission("failing", 1, Some("bundle rejected")),
+        ]));
+        scoreboard.record(&SubmissionReceipt::new(vec![
+            submission("landing", 2, None),
+            submission("acking", 3, None),
+        ]));
+        scoreboard.record_block(U64::from(10), &[H256::from_low_u64_be(2)]);
+
+        let landing = scoreboard.stats("landing");
+        assert_eq!(
+            landing,
+            RelayStats {
+                submitted: 2,
+                accepted: 2,
+                errors: 0,
+                included: 1,
+                delivered: 0,
+            }
+        );
+        assert_eq!(landing.inclusion_rate(), 0.5);
+        assert_eq!(scoreboard.stats("failing").error_rate(), 1.0);
+        assert_eq!(scoreboard.stats("acking").included, 0);
+
+        let ranking: Vec<_> = scoreboard
+            .ranking()
+            .into_iter()
+            .map(|(relay, _)| relay)
+            .collect();
+        assert_eq!(ranking, vec!["landing", "acking", "failing"]);
+        // Relays without history rank above those whose bundles don't land.
+        assert_eq!(
+            scoreboard.top(["unknown", "failing", "acking"], 2),
+            vec!["unknown", "acking"]
+        );
+
+        // Bundles whose target blocks passed can't land anymore.
+        scoreboard.record_block(U64::from(11), &[H256::from_low_u64_be(1)]);
+        assert_eq!(scoreboard.stats("acking").included, 0);
+    }
+
+    /// Relay accepting or rejecting every action, counting its calls.
+    #[derive(Clone)]
+    struct Relay {
+        name: &'static str,
+        accepts: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Relay {
+        fn new(name: &'static str, accepts: bool) -> Self {
+            Self {
+                name,
+                accepts,
+                calls: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Executor<u64> for Relay {
+        async fn execute(&self, action: u64) -> Result<SubmissionReceipt> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if !self.accepts {
+                return Err(ArtemisError::Network(String::from("relay down")));
+            }
+            Ok(SubmissionReceipt::new(vec![submission(
+                self.name, action, None,
+            )]))
+        }
+    }
+
+    #[tokio::test]
+    async fn submits_to_top_relays_only() {
+        let good = Relay::new("good", true);
+        let bad = Relay::new("bad", false);
+        let executor = RelaySelectionExecutor::new(Arc::new(RelayScoreboard::new()), 1)
+            .with_relay("bad", bad.clone())
+            .with_relay("good", good.clone());
+
+        // Without history the first relay is picked, and its failure demotes it.
+        let receipt = executor.execute(1).await.unwrap();
+        assert!(receipt.is_rejected());
+        assert_eq!(receipt.submissions[0].destination, "bad");
+
+        let receipt = executor.execute(2).await.unwrap();
+        assert!(!receipt.is_rejected());
+        executor.execute(3).await.unwrap();
+        assert_eq!(bad.calls.load(Ordering::Relaxed), 1);
+        assert_eq!(good.calls.load(Ordering::Relaxed), 2);
+        assert_eq!(executor.scoreboard().stats("good").accepted, 2);
+    }
+}
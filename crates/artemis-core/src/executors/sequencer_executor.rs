@@ -0,0 +1,243 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::{ArtemisError, Result};
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use ethers::{
+    abi::{encode_packed, Token},
+    providers::{Http, Middleware, Provider},
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, Chain, H256, U256},
+    utils::keccak256,
+};
+use serde::Serialize;
+use tracing::info;
+
+/// A transaction to send directly to an L2 sequencer.
+#[derive(Debug, Clone)]
+pub struct SubmitTxToSequencer {
+    pub tx: TypedTransaction,
+}
+
+/// Arbitrum Timeboost express lane configuration. Only useful while the signer controls
+/// the express lane, i.e. won the auction for the current round.
+#[derive(Debug, Clone)]
+pub struct ExpressLaneConfig {
+    /// Address of the express lane auction contract.
+    pub auction_contract: Address,
+    /// Unix timestamp at which round 0 started.
+    pub round_offset: u64,
+    /// Duration of each round, in seconds.
+    pub round_duration: u64,
+}
+
+impl ExpressLaneConfig {
+    /// Returns the auction round at the given unix timestamp.
+    pub fn round_at(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_sub(self.round_offset) / self.round_duration.max(1)
+    }
+}
+
+/// Per-chain configuration of the [SequencerExecutor](SequencerExecutor).
+#[derive(Debug, Clone)]
+pub struct SequencerConfig {
+    pub chain: Chain,
+    /// Sequencer (or FlashBlocks) RPC endpoint accepting raw transactions.
+    pub endpoint: String,
+    /// Priority fee to set on every transaction, overriding the strategy's.
+    pub priority_fee: Option<U256>,
+    /// Submit through the Timeboost express lane instead of `eth_sendRawTransaction`.
+    pub express_lane: Option<ExpressLaneConfig>,
+}
+
+/// An executor that signs transactions and sends them straight to an L2 sequencer,
+/// skipping the public mempool of the chain's RPC nodes.
+pub struct SequencerExecutor<M, S> {
+    /// Client for the L2, used to fill transactions.
+    client: Arc<M>,
+    /// Client for the sequencer endpoint.
+    sequencer: Provider<Http>,
+    /// The signer to sign transactions before sending them.
+    tx_signer: S,
+    config: SequencerConfig,
+    /// Express lane round of the last submission.
+    express_lane_round: AtomicU64,
+    /// Next express lane sequence number within the round.
+    express_lane_sequence: AtomicU64,
+}
+
+impl<M: Middleware, S: Signer> SequencerExecutor<M, S> {
+    pub fn new(client: Arc<M>, tx_signer: S, config: SequencerConfig) -> anyhow::Result<Self> {
+        let sequencer = Provider::<Http>::try_from(config.endpoint.as_str())?;
+        Ok(Self {
+            client,
+            sequencer,
+            tx_signer,
+            config,
+            express_lane_round: AtomicU64::new(u64::MAX),
+            express_lane_sequence: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the next express lane sequence number for the round, which restarts
+    /// at zero every round.
+    fn next_sequence_number(&self, round: u64) -> u64 {
+        if self.express_lane_round.swap(round, Ordering::SeqCst) != round {
+            self.express_lane_sequence.store(0, Ordering::SeqCst);
+        }
+        self.express_lane_sequence.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// Express lane submission, as expected by `timeboost_sendExpressLaneTransaction`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExpressLaneSubmission {
+    chain_id: U256,
+    round: u64,
+    auction_contract_address: Address,
+    transaction: Bytes,
+    sequence_number: u64,
+    signature: Bytes,
+}
+
+/// Message signed by the express lane controller for a submission.
+fn express_lane_message(
+    chain_id: U256,
+    auction_contract: Address,
+    round: u64,
+    sequence_number: u64,
+    transaction: &Bytes,
+) -> H256 {
+    let domain = keccak256("TIMEBOOST_BID");
+    let mut chain_id_bytes = [0u8; 32];
+    chain_id.to_big_endian(&mut chain_id_bytes);
+    let message = encode_packed(&[
+        Token::FixedBytes(domain.to_vec()),
+        Token::FixedBytes(chain_id_bytes.to_vec()),
+        Token::Address(auction_contract),
+        Token::FixedBytes(round.to_be_bytes().to_vec()),
+        Token::FixedBytes(sequence_number.to_be_bytes().to_vec()),
+        Token::Bytes(transaction.to_vec()),
+    ])
+    // Packed encoding of these tokens can't fail.
+    .unwrap();
+    H256::from(keccak256(message))
+}
+
+#[async_trait]
+impl<M, S> Executor<SubmitTxToSequencer> for SequencerExecutor<M, S>
+where
+    M: Middleware,
+    M::Error: 'static,
+    S: Signer + 'static,
+{
+    /// Fill, sign and send a transaction to the sequencer.
+    async fn execute(&self, action: SubmitTxToSequencer) -> Result<SubmissionReceipt> {
+        let mut tx = action.tx;
+        tx.set_from(self.tx_signer.address());
+        tx.set_chain_id(self.config.chain as u64);
+        if let (Some(priority_fee), TypedTransaction::Eip1559(inner)) =
+            (self.config.priority_fee, &mut tx)
+        {
+            inner.max_priority_fee_per_gas = Some(priority_fee);
+        }
+        self.client
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+
+        let signature = self
+            .tx_signer
+            .sign_transaction(&tx)
+            .await
+            .map_err(|e| anyhow!("Error signing transaction: {}", e))?;
+        let raw = tx.rlp_signed(&signature);
+        let tx_hash = H256::from(keccak256(&raw));
+
+        let destination = match &self.config.express_lane {
+            None => {
+                let pending = self.sequencer.send_raw_transaction(raw).await?;
+                info!(
+                    "sent tx {:?} to {:?} sequencer",
+                    pending.tx_hash(),
+                    self.config.chain
+                );
+                format!("{:?} sequencer", self.config.chain)
+            }
+            Some(express_lane) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .context("Error reading the time")?
+                    .as_secs();
+                let round = express_lane.round_at(now);
+                let sequence_number = self.next_sequence_number(round);
+                let chain_id = U256::from(self.config.chain as u64);
+                let message = express_lane_message(
+                    chain_id,
+                    express_lane.auction_contract,
+                    round,
+                    sequence_number,
+                    &raw,
+                );
+                let signature = self
+                    .tx_signer
+                    .sign_message(message.as_bytes())
+                    .await
+                    .map_err(|e| anyhow!("Error signing express lane submission: {}", e))?;
+                let submission = ExpressLaneSubmission {
+                    chain_id,
+                    round,
+                    auction_contract_address: express_lane.auction_contract,
+                    transaction: raw,
+                    sequence_number,
+                    signature: signature.to_vec().into(),
+                };
+                self.sequencer
+                    .request::<_, ()>("timeboost_sendExpressLaneTransaction", [submission])
+                    .await?;
+                info!(
+                    "sent express lane tx for round {} with sequence number {}",
+                    round, sequence_number
+                );
+                format!("{:?} express lane", self.config.chain)
+            }
+        };
+        Ok(SubmissionReceipt::new(vec![Submission::new(
+            destination,
+            vec![tx_hash],
+        )]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_start_at_offset() {
+        let config = ExpressLaneConfig {
+            auction_contract: Address::zero(),
+            round_offset: 1_000,
+            round_duration: 60,
+        };
+        assert_eq!(config.round_at(999), 0);
+        assert_eq!(config.round_at(1_059), 0);
+        assert_eq!(config.round_at(1_060), 1);
+    }
+
+    #[test]
+    fn express_lane_message_commits_to_sequence_number() {
+        let tx = Bytes::from(vec![1, 2, 3]);
+        let a = express_lane_message(U256::from(42161), Address::zero(), 1, 0, &tx);
+        let b = express_lane_message(U256::from(42161), Address::zero(), 1, 1, &tx);
+        assert_ne!(a, b);
+    }
+}
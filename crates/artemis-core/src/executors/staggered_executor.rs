@@ -0,0 +1,204 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::error::Result;
+use crate::types::{Executor, Submission, SubmissionReceipt};
+use async_trait::async_trait;
+use futures::future::join_all;
+use tokio::time::sleep;
+use tracing::debug;
+
+/// Weight of the latest submission in the latency of a relay.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Learns how long each relay takes to respond to submissions, as an exponentially
+/// weighted moving average.
+#[derive(Debug, Default)]
+pub struct RelayLatencies {
+    latencies: Mutex<HashMap<String, Duration>>,
+}
+
+impl RelayLatencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `relay` responded to a submission in `elapsed`.
+    pub fn record(&self, relay: &str, elapsed: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        let latency = latencies.entry(relay.to_string()).or_insert(elapsed);
+        *latency = latency.mul_f64(1.0 - LATENCY_SMOOTHING) + elapsed.mul_f64(LATENCY_SMOOTHING);
+    }
+
+    /// Returns the latency learned for `relay`, or `None` if nothing was submitted to it.
+    pub fn latency(&self, relay: &str) -> Option<Duration> {
+        self.latencies.lock().unwrap().get(relay).copied()
+    }
+}
+
+/// StaggeredExecutor races an action to several relay executors, sending it right away
+/// to the relays which respond within `fast_latency`, and to slower relays one after
+/// the other, `delay` apart, fastest first. Relays without a latency yet count as fast,
+/// so their latency is learned from the first submissions.
+///
+/// Fast relays get the action as early as possible, which gives it the best chance to
+/// be included, while holding back slow relays leaks it to fewer parties before the
+/// fast ones have had it. The receipts of the relays are merged.
+pub struct StaggeredExecutor<E> {
+    relays: Vec<(String, E)>,
+    latencies: RelayLatencies,
+    fast_latency: Duration,
+    delay: Duration,
+}
+
+impl<E> StaggeredExecutor<E> {
+    /// Send actions right away to relays responding within `fast_latency`, and to the
+    /// others `delay` apart.
+    pub fn new(fast_latency: Duration, delay: Duration) -> Self {
+        Self {
+            relays: vec![],
+            latencies: RelayLatencies::new(),
+            fast_latency,
+            delay,
+        }
+    }
+
+    /// Add a relay executor. `name` identifies the relay in its latency, and in the
+    /// submissions of executors which fail.
+    pub fn with_relay(mut self, name: impl Into<String>, executor: E) -> Self {
+        self.relays.push((name.into(), executor));
+        self
+    }
+
+    pub fn latencies(&self) -> &RelayLatencies {
+        &self.latencies
+    }
+
+    /// Returns how long to wait before sending an action to each relay, indexed like
+    /// the relays.
+    fn schedule(&self) -> Vec<Duration> {
+        let mut slow: Vec<(usize, Duration)> = self
+            .relays
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (name, _))| {
+                let latency = self.latencies.latency(name)?;
+                (latency > self.fast_latency).then_some((index, latency))
+            })
+            .collect();
+        slow.sort_by_key(|(_, latency)| *latency);
+
+        let mut schedule = vec![Duration::ZERO; self.relays.len()];
+        for (rank, (index, _)) in slow.into_iter().enumerate() {
+            schedule[index] = self.delay * (rank as u32 + 1);
+        }
+        schedule
+    }
+}
+
+#[async_trait]
+impl<E, A> Executor<A> for StaggeredExecutor<E>
+where
+    E: Executor<A>,
+    A: Clone + Send + Sync + 'static,
+{
+    /// Send the action to every relay on its schedule, learning their latencies.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        let schedule = self.schedule();
+        debug!("submitting to relays after {:?}", schedule);
+
+        let executions = self
+            .relays
+            .iter()
+            .zip(schedule)
+            .map(|((name, executor), wait)| {
+                let action = action.clone();
+                async move {
+                    if !wait.is_zero() {
+                        sleep(wait).await;
+                    }
+                    let started_at = Instant::now();
+                    let result = executor.execute(action).await;
+                    self.latencies.record(name, started_at.elapsed());
+                    match result {
+                        Ok(receipt) => receipt.submissions,
+                        Err(e) => vec![Submission::new(name.clone(), vec![]).with_error(e)],
+                    }
+                }
+            });
+        let submissions = join_all(executions).await.into_iter().flatten().collect();
+        Ok(SubmissionReceipt::new(submissions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Relay taking `latency` to accept every action, recording when it was called.
+    #[derive(Clone)]
+    struct Relay {
+        name: &'static str,
+        latency: Duration,
+        calls: Arc<Mutex<Vec<Instant>>>,
+    }
+
+    impl Relay {
+        fn new(name: &'static str, latency: Duration) -> Self {
+            Self {
+                name,
+                latency,
+                calls: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        fn last_call(&self) -> Instant {
+            *self.calls.lock().unwrap().last().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Executor<u64> for Relay {
+        async fn execute(&self, _action: u64) -> Result<SubmissionReceipt> {
+            self.calls.lock().unwrap().push(Instant::now());
+            sleep(self.latency).await;
+            Ok(SubmissionReceipt::new(vec![Submission::new(
+                self.name,
+                vec![],
+            )]))
+        }
+    }
+
+    #[test]
+    fn smooths_latencies() {
+        let latencies = RelayLatencies::new();
+        assert_eq!(latencies.latency("relay"), None);
+        latencies.record("relay", Duration::from_millis(100));
+        latencies.record("relay", Duration::from_millis(200));
+        let latency = latencies.latency("relay").unwrap();
+        assert!((latency.as_secs_f64() - 0.12).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn staggers_slow_relays() {
+        let fast = Relay::new("fast", Duration::ZERO);
+        let slow = Relay::new("slow", Duration::from_millis(30));
+        let executor = StaggeredExecutor::new(Duration::from_millis(10), Duration::from_millis(50))
+            .with_relay("slow", slow.clone())
+            .with_relay("fast", fast.clone());
+
+        // Without latencies every relay is sent to right away.
+        let receipt = executor.execute(1).await.unwrap();
+        assert_eq!(receipt.submissions.len(), 2);
+        assert!(executor.latencies().latency("slow").unwrap() >= Duration::from_millis(30));
+
+        let started_at = Instant::now();
+        executor.execute(2).await.unwrap();
+        assert!(fast.last_call() - started_at < Duration::from_millis(10));
+        assert!(slow.last_call() - started_at >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,544 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::Serialize;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tracing::{debug, info};
+
+use crate::utilities::http::{read_request, write_json_response};
+
+/// Time allowed to read a request and write the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the number of events and actions queued in the engine's channels.
+type ChannelDepths = Box<dyn Fn() -> (usize, usize) + Send + Sync>;
+
+/// Health of the engine's components, updated by the [Engine](crate::engine::Engine)
+/// as data flows through it, and reported by the [HealthServer](HealthServer).
+pub struct EngineHealth {
+    started_at: Instant,
+    /// Whether every strategy synced its state and the engine is running.
+    running: AtomicBool,
+    collectors: Mutex<Vec<Arc<CollectorHealth>>>,
+    executors: Mutex<Vec<Arc<ExecutorHealth>>>,
+    strategies: Mutex<Vec<Arc<StrategyHealth>>>,
+    channel_depths: Mutex<Option<ChannelDepths>>,
+}
+
+/// Health of a single collector.
+#[derive(Debug, Default)]
+pub struct CollectorHealth {
+    connected: AtomicBool,
+    events: AtomicU64,
+    /// Unix timestamp (in milliseconds) of the last event, or 0 if none was collected.
+    last_event_ms: AtomicU64,
+    restarts: AtomicU64,
+}
+
+impl CollectorHealth {
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn record_event(&self) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+        self.last_event_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Record that the collector's stream stalled, and was restarted.
+    pub fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Health of a single executor.
+#[derive(Debug, Default)]
+pub struct ExecutorHealth {
+    executed: AtomicU64,
+    failed: AtomicU64,
+    /// Actions missed because the executor fell behind the action channel.
+    lagged: AtomicU64,
+    /// Actions dropped because they expired before the executor got to them.
+    expired: AtomicU64,
+}
+
+impl ExecutorHealth {
+    pub fn record_result<T, E>(&self, result: &Result<T, E>) {
+        match result {
+            Ok(_) => self.executed.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.failed.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Record `missed` actions dropped from the channel before the executor read them.
+    pub fn record_lag(&self, missed: u64) {
+        self.lagged.fetch_add(missed, Ordering::Relaxed);
+    }
+
+    /// Record an action dropped because it expired before the executor got to it.
+    pub fn record_expired(&self) {
+        self.expired.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Health of a single strategy: how long it takes to process events, and how many
+/// it missed by falling behind the event channel.
+#[derive(Debug, Default)]
+pub struct StrategyHealth {
+    events: AtomicU64,
+    /// Events missed because the strategy fell behind the event channel.
+    lagged: AtomicU64,
+    total_processing_us: AtomicU64,
+    max_processing_us: AtomicU64,
+    /// Events which took longer than the processing deadline.
+    overruns: AtomicU64,
+    /// Events in a row which took longer than the processing deadline.
+    consecutive_overruns: AtomicU64,
+    /// Events the strategy gave up on after they timed out, counting each attempt.
+    timeouts: AtomicU64,
+    /// Times the strategy was restarted after panicking.
+    restarts: AtomicU64,
+    /// Whether the strategy panicked and wasn't restarted.
+    stopped: AtomicBool,
+}
+
+impl StrategyHealth {
+    /// Record an event processed in `elapsed`. Returns the number of events in a row,
+    /// including this one, which exceeded `deadline`, or 0 if this one didn't.
+    pub fn record_processed(&self, elapsed: Duration, deadline: Option<Duration>) -> u64 {
+        let elapsed_us = elapsed.as_micros() as u64;
+        self.events.fetch_add(1, Ordering::Relaxed);
+        self.total_processing_us
+            .fetch_add(elapsed_us, Ordering::Relaxed);
+        self.max_processing_us
+            .fetch_max(elapsed_us, Ordering::Relaxed);
+        if deadline.is_some_and(|deadline| elapsed > deadline) {
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+            self.consecutive_overruns.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            self.consecutive_overruns.store(0, Ordering::Relaxed);
+            0
+        }
+    }
+
+    /// Record `missed` events dropped from the channel before the strategy read them.
+    pub fn record_lag(&self, missed: u64) {
+        self.lagged.fetch_add(missed, Ordering::Relaxed);
+    }
+
+    /// Record an attempt to process an event which timed out.
+    pub fn record_timeout(&self) {
+        self.timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a restart of the strategy after it panicked.
+    pub fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that the strategy panicked and won't be restarted.
+    pub fn set_stopped(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of the health of the engine, served as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// Whether every strategy synced its state and the engine is running.
+    pub running: bool,
+    pub uptime_secs: u64,
+    pub collectors: Vec<CollectorReport>,
+    pub executors: Vec<ExecutorReport>,
+    pub strategies: Vec<StrategyMetrics>,
+    /// Number of events queued for the slowest strategy.
+    pub event_channel_depth: usize,
+    /// Number of actions queued for the slowest executor.
+    pub action_channel_depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CollectorReport {
+    /// Whether the collector's event stream is open.
+    pub connected: bool,
+    pub events: u64,
+    /// Unix timestamp (in milliseconds) of the last event collected.
+    pub last_event_ms: Option<u64>,
+    /// Number of times the collector's stream stalled, and was restarted.
+    pub restarts: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutorReport {
+    pub executed: u64,
+    pub failed: u64,
+    /// Share of actions which failed, between 0 and 1.
+    pub error_rate: f64,
+    /// Actions missed because the executor fell behind the action channel.
+    pub lagged: u64,
+    /// Actions dropped because they expired before the executor got to them.
+    pub expired: u64,
+}
+
+/// Event processing metrics of a strategy. A strategy with a high mean processing
+/// time or many lagged events is slowing the engine down.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyMetrics {
+    pub events: u64,
+    /// Events missed because the strategy fell behind the event channel.
+    pub lagged: u64,
+    pub mean_processing_ms: f64,
+    pub max_processing_ms: f64,
+    /// Events which took longer than the processing deadline.
+    pub deadline_overruns: u64,
+    /// Attempts to process an event which timed out.
+    pub timeouts: u64,
+    /// Times the strategy was restarted after panicking.
+    pub restarts: u64,
+    /// Whether the strategy panicked and wasn't restarted.
+    pub stopped: bool,
+}
+
+impl EngineHealth {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            running: AtomicBool::new(false),
+            collectors: Mutex::new(vec![]),
+            executors: Mutex::new(vec![]),
+            strategies: Mutex::new(vec![]),
+            channel_depths: Mutex::new(None),
+        }
+    }
+
+    /// Track a new collector, in the order collectors were added to the engine.
+    pub fn add_collector(&self) -> Arc<CollectorHealth> {
+        let collector = Arc::new(CollectorHealth::default());
+        self.collectors.lock().unwrap().push(collector.clone());
+        collector
+    }
+
+    /// Track a new executor, in the order executors were added to the engine.
+    pub fn add_executor(&self) -> Arc<ExecutorHealth> {
+        let executor = Arc::new(ExecutorHealth::default());
+        self.executors.lock().unwrap().push(executor.clone());
+        executor
+    }
+
+    /// Track a new strategy, in the order strategies were added to the engine.
+    pub fn add_strategy(&self) -> Arc<StrategyHealth> {
+        let strategy = Arc::new(StrategyHealth::default());
+        self.strategies.lock().unwrap().push(strategy.clone());
+        strategy
+    }
+
+    pub fn set_channel_depths(&self, depths: ChannelDepths) {
+        *self.channel_depths.lock().unwrap() = Some(depths);
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.running.store(running, Ordering::Relaxed);
+    }
+
+    pub fn report(&self) -> HealthReport {
+        let collectors = self
+            .collectors
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|collector| {
+                let last_event_ms = collector.last_event_ms.load(Ordering::Relaxed);
+                CollectorReport {
+                    connected: collector.connected.load(Ordering::Relaxed),
+                    events: collector.events.load(Ordering::Relaxed),
+                    last_event_ms: (last_event_ms > 0).then_some(last_event_ms),
+                    restarts: collector.restarts.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        let executors = self
+            .executors
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|executor| {
+                let executed = executor.executed.load(Ordering::Relaxed);
+                let failed = executor.failed.load(Ordering::Relaxed);
+                let total = executed + failed;
+                ExecutorReport {
+                    executed,
+                    failed,
+                    error_rate: if total == 0 {
+                        0.0
+                    } else {
+                        failed as f64 / total as f64
+                    },
+                    lagged: executor.lagged.load(Ordering::Relaxed),
+                    expired: executor.expired.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        let strategies = self
+            .strategies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|strategy| {
+                let events = strategy.events.load(Ordering::Relaxed);
+                let total_us = strategy.total_processing_us.load(Ordering::Relaxed);
+                StrategyMetrics {
+                    events,
+                    lagged: strategy.lagged.load(Ordering::Relaxed),
+                    mean_processing_ms: if events == 0 {
+                        0.0
+                    } else {
+                        total_us as f64 / events as f64 / 1000.0
+                    },
+                    max_processing_ms: strategy.max_processing_us.load(Ordering::Relaxed) as f64
+                        / 1000.0,
+                    deadline_overruns: strategy.overruns.load(Ordering::Relaxed),
+                    timeouts: strategy.timeouts.load(Ordering::Relaxed),
+                    restarts: strategy.restarts.load(Ordering::Relaxed),
+                    stopped: strategy.stopped.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        let (event_channel_depth, action_channel_depth) =
+            match &*self.channel_depths.lock().unwrap() {
+                Some(depths) => depths(),
+                None => (0, 0),
+            };
+        HealthReport {
+            running: self.running.load(Ordering::Relaxed),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            collectors,
+            executors,
+            strategies,
+            event_channel_depth,
+            action_channel_depth,
+        }
+    }
+}
+
+impl Default for EngineHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthReport {
+    /// Live while the engine runs, every collector stream is open, and no strategy
+    /// stopped after panicking.
+    pub fn is_live(&self) -> bool {
+        self.running
+            && self.collectors.iter().all(|collector| collector.connected)
+            && !self.strategies.iter().any(|strategy| strategy.stopped)
+    }
+
+    /// Ready once live, and every collector has collected an event within
+    /// `max_event_silence` if set.
+    pub fn is_ready(&self, max_event_silence: Option<Duration>) -> bool {
+        let Some(max_event_silence) = max_event_silence else {
+            return self.is_live();
+        };
+        let cutoff = now_ms().saturating_sub(max_event_silence.as_millis() as u64);
+        self.is_live()
+            && self
+                .collectors
+                .iter()
+                .all(|collector| collector.last_event_ms.is_some_and(|last| last >= cutoff))
+    }
+}
+
+/// An HTTP server exposing the health of the engine for liveness and readiness
+/// probes. `GET /healthz` and `GET /readyz` respond with a JSON
+/// [HealthReport](HealthReport), with status 200 if the engine is live, respectively
+/// ready, and 503 otherwise.
+pub struct HealthServer {
+    listener: TcpListener,
+    health: Arc<EngineHealth>,
+    max_event_silence: Option<Duration>,
+}
+
+impl HealthServer {
+    pub async fn bind(addr: SocketAddr, health: Arc<EngineHealth>) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener,
+            health,
+            max_event_silence: None,
+        })
+    }
+
+    /// Only report ready if every collector collected an event within `max_silence`.
+    pub fn with_max_event_silence(mut self, max_silence: Duration) -> Self {
+        self.max_event_silence = Some(max_silence);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve requests until the task is dropped.
+    pub async fn serve(self) {
+        info!("serving health checks on {:?}", self.listener.local_addr());
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    debug!("error accepting health check connection: {}", e);
+                    continue;
+                }
+            };
+            let health = self.health.clone();
+            let max_event_silence = self.max_event_silence;
+            tokio::spawn(async move {
+                let response = handle(stream, &health, max_event_silence);
+                match timeout(REQUEST_TIMEOUT, response).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => debug!("error serving health check: {}", e),
+                    Err(_) => debug!("health check request timed out"),
+                }
+            });
+        }
+    }
+}
+
+/// Respond to a single request.
+async fn handle(
+    mut stream: TcpStream,
+    health: &EngineHealth,
+    max_event_silence: Option<Duration>,
+) -> Result<()> {
+    let request = read_request(&mut stream).await?;
+    let (status, body) = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", path @ ("/healthz" | "/readyz")) => {
+            let report = health.report();
+            let healthy = if path == "/healthz" {
+                report.is_live()
+            } else {
+                report.is_ready(max_event_silence)
+            };
+            let status = if healthy {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            (status, serde_json::to_string(&report)?)
+        }
+        _ => ("404 Not Found", String::from("{}")),
+    };
+    write_json_response(&mut stream, status, &body).await
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn readiness_requires_recent_events_from_every_collector() {
+        let health = EngineHealth::new();
+        let a = health.add_collector();
+        let b = health.add_collector();
+        let executor = health.add_executor();
+        health.set_running(true);
+        a.set_connected(true);
+        b.set_connected(true);
+
+        let silence = Some(Duration::from_secs(60));
+        assert!(health.report().is_live());
+        assert!(!health.report().is_ready(silence));
+
+        a.record_event();
+        b.record_event();
+        assert!(health.report().is_ready(silence));
+
+        b.set_connected(false);
+        assert!(!health.report().is_live());
+
+        executor.record_result::<(), ()>(&Ok(()));
+        executor.record_result::<(), ()>(&Err(()));
+        assert_eq!(health.report().executors[0].error_rate, 0.5);
+    }
+
+    #[test]
+    fn counts_consecutive_deadline_overruns() {
+        let health = EngineHealth::new();
+        let strategy = health.add_strategy();
+        let ms = Duration::from_millis;
+        let deadline = Some(ms(10));
+
+        assert_eq!(strategy.record_processed(ms(20), deadline), 1);
+        assert_eq!(strategy.record_processed(ms(30), deadline), 2);
+        assert_eq!(strategy.record_processed(ms(10), deadline), 0);
+        assert_eq!(strategy.record_processed(ms(20), None), 0);
+        strategy.record_lag(7);
+
+        let metrics = &health.report().strategies[0];
+        assert_eq!(metrics.events, 4);
+        assert_eq!(metrics.lagged, 7);
+        assert_eq!(metrics.mean_processing_ms, 20.0);
+        assert_eq!(metrics.max_processing_ms, 30.0);
+        assert_eq!(metrics.deadline_overruns, 2);
+    }
+
+    #[test]
+    fn stopped_strategies_fail_liveness() {
+        let health = EngineHealth::new();
+        let strategy = health.add_strategy();
+        health.set_running(true);
+
+        strategy.record_restart();
+        let report = health.report();
+        assert!(report.is_live());
+        assert_eq!(report.strategies[0].restarts, 1);
+
+        strategy.set_stopped();
+        assert!(!health.report().is_live());
+    }
+
+    #[tokio::test]
+    async fn serves_probes_over_http() {
+        let health = Arc::new(EngineHealth::new());
+        let server = HealthServer::bind("127.0.0.1:0".parse().unwrap(), health.clone())
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        assert!(get("/healthz").await.starts_with("HTTP/1.1 503"));
+        health.set_running(true);
+        let response = get("/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("\"running\":true"));
+        assert!(get("/metrics").await.starts_with("HTTP/1.1 404"));
+    }
+}
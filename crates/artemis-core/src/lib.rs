@@ -26,13 +26,45 @@
 //! These components are tied together by the [Engine](engine::Engine), which is responsible for
 //! orchestrating the flow of data between them.
 
+/// This module contains the runtime controls of the [Engine](engine::Engine), and
+/// the JSON-RPC server exposing them.
+pub mod admin;
+/// This module contains the [benchmark](analytics::InclusionBenchmark) of our inclusion
+/// against competing searchers.
+pub mod analytics;
+/// This module contains the [UnifiedBundle](bundle::UnifiedBundle) type, which can be
+/// sent to both Flashbots and MEV-share executors.
+pub mod bundle;
 /// This module contains [collector](types::Collector) implementations.
 pub mod collectors;
 /// This module contains the [Engine](engine::Engine) struct, which is responsible
 /// for orchestrating data flows between components
 pub mod engine;
+/// This module contains the [error](error::ArtemisError) type of collectors, strategies
+/// and executors.
+pub mod error;
 /// This module contains [executor](types::Executor) implementations.
 pub mod executors;
+/// This module contains the health checks of the [Engine](engine::Engine), and the
+/// HTTP server exposing them.
+pub mod health;
+/// This module contains the [client](relay_data::RelayDataClient) of the data APIs of
+/// MEV-boost relays, and the [tracker](relay_data::RelayInclusionTracker) correlating our
+/// bundles with the payloads relays delivered.
+pub mod relay_data;
+/// This module contains the [ReorgTracker](reorg::ReorgTracker), which detects reorgs
+/// and the submissions they invalidate.
+pub mod reorg;
+/// This module contains the [risk limits](risk::RiskLimits) of strategies, and the
+/// [kill switch](risk::KillSwitch) stopping strategies which breach them.
+pub mod risk;
+/// This module contains mock collectors and executors for testing strategies, enabled
+/// with the `test-utils` feature.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+/// This module contains the [transports](transport::Transport) connecting collectors,
+/// strategies and executors running in different processes through message queues.
+pub mod transport;
 /// This module contains the core type definitions for Artemis.
 pub mod types;
 /// This module contains utilities for working with Artemis.
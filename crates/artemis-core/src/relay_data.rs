@@ -0,0 +1,368 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use crate::collectors::beacon_collector::u64_from_str;
+use crate::executors::relay_selection_executor::RelayScoreboard;
+use crate::types::SubmissionReceipt;
+use ethers::types::{Address, Bytes, H256, U256, U64};
+use serde::{de, Deserialize, Serialize};
+use tracing::warn;
+
+/// A payload a MEV-boost relay delivered to a proposer, as reported by the
+/// `proposer_payload_delivered` endpoint of its data API.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeliveredPayload {
+    #[serde(deserialize_with = "u64_from_str")]
+    pub slot: u64,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub block_number: u64,
+    pub block_hash: H256,
+    /// Public key of the builder which built the block.
+    pub builder_pubkey: Bytes,
+    pub proposer_pubkey: Bytes,
+    pub proposer_fee_recipient: Address,
+    /// Value of the block to the proposer, in wei.
+    #[serde(deserialize_with = "u256_from_dec_str")]
+    pub value: U256,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub gas_used: u64,
+    #[serde(deserialize_with = "u64_from_str")]
+    pub num_tx: u64,
+}
+
+fn u256_from_dec_str<'de, D>(deserializer: D) -> std::result::Result<U256, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let val = String::deserialize(deserializer)?;
+    U256::from_dec_str(&val).map_err(de::Error::custom)
+}
+
+/// A payload delivered by a named relay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub relay: String,
+    pub payload: DeliveredPayload,
+}
+
+/// Client of the data APIs of a set of MEV-boost relays, reading which relay delivered
+/// the payload of each block, and which builder built it.
+#[derive(Debug, Clone, Default)]
+pub struct RelayDataClient {
+    http_client: reqwest::Client,
+    relays: Vec<(String, String)>,
+}
+
+impl RelayDataClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Query the data API of the relay at `relay_url`, e.g.
+    /// `https://boost-relay.flashbots.net`, naming its deliveries `name`.
+    pub fn with_relay(mut self, name: impl Into<String>, relay_url: &str) -> Self {
+        self.relays
+            .push((name.into(), relay_url.trim_end_matches('/').to_string()));
+        self
+    }
+
+    /// Returns the payloads the relay at `relay_url` delivered for block `block_number`.
+    pub async fn delivered_payloads(
+        &self,
+        relay_url: &str,
+        block_number: U64,
+    ) -> anyhow::Result<Vec<DeliveredPayload>> {
+        let url = format!(
+            "{}/relay/v1/data/bidtraces/proposer_payload_delivered?block_number={}",
+            relay_url, block_number
+        );
+        let body = self
+            .http_client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Returns the payloads every relay delivered for block `block_number`. Relays
+    /// which can't be reached are skipped.
+    pub async fn deliveries(&self, block_number: U64) -> Vec<Delivery> {
+        let mut deliveries = vec![];
+        for (name, relay_url) in &self.relays {
+            match self.delivered_payloads(relay_url, block_number).await {
+                Ok(payloads) => deliveries.extend(payloads.into_iter().map(|payload| Delivery {
+                    relay: name.clone(),
+                    payload,
+                })),
+                Err(e) => warn!("failed to read payloads delivered by {}: {}", name, e),
+            }
+        }
+        deliveries
+    }
+}
+
+/// Inclusion of our bundles in the blocks a MEV-boost relay delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelayInclusion {
+    /// Blocks the relay delivered.
+    pub blocks: u64,
+    /// Our bundles which landed in those blocks.
+    pub landed: u64,
+}
+
+/// Inclusion of our bundles in the blocks of a builder.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BuilderInclusion {
+    /// Destination our bundles are submitted to the builder by, if known.
+    pub destination: Option<String>,
+    /// Blocks of the builder delivered by relays.
+    pub blocks: u64,
+    /// Our bundles which landed in those blocks.
+    pub landed: u64,
+    /// Of those, bundles submitted to the builder's destination, rather than shared
+    /// with it by another builder or relay.
+    pub submitted: u64,
+}
+
+/// Inclusion statistics of every relay and builder seen so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InclusionReport {
+    pub relays: HashMap<String, RelayInclusion>,
+    /// Statistics of builders, by public key.
+    pub builders: HashMap<Bytes, BuilderInclusion>,
+    /// Our bundles which landed in blocks no watched relay delivered.
+    pub undelivered: u64,
+}
+
+/// A bundle acknowledged by destinations, waiting to be seen on chain.
+#[derive(Debug)]
+struct PendingBundle {
+    last_block: U64,
+    destinations: Vec<String>,
+}
+
+/// Correlates our submitted bundles with the payloads MEV-boost relays delivered, to
+/// tell which relay and builder each landed bundle went through. Fed with the
+/// [receipts](SubmissionReceipt) of submissions, and with the transactions and
+/// [deliveries](Delivery) of new blocks.
+///
+/// Builders are mapped to the destinations our bundles are submitted by with
+/// [with_builder](RelayInclusionTracker::with_builder). When a scoreboard is set, a
+/// landed bundle counts as delivered for the destination of the builder which built
+/// its block, unlike [record_block](RelayScoreboard::record_block) which credits every
+/// destination that acknowledged it.
+#[derive(Debug, Default)]
+pub struct RelayInclusionTracker {
+    destinations: HashMap<Bytes, String>,
+    scoreboard: Option<Arc<RelayScoreboard>>,
+    report: Mutex<InclusionReport>,
+    /// Acknowledged bundles, by the hash of their last transaction.
+    pending: Mutex<HashMap<H256, PendingBundle>>,
+}
+
+impl RelayInclusionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the builder with public key `builder_pubkey` to `destination`, the
+    /// destination of the submissions sent to it.
+    pub fn with_builder(mut self, builder_pubkey: Bytes, destination: impl Into<String>) -> Self {
+        self.destinations.insert(builder_pubkey, destination.into());
+        self
+    }
+
+    /// Feed the destinations of builders which built blocks our bundles landed in to
+    /// `scoreboard`.
+    pub fn with_scoreboard(mut self, scoreboard: Arc<RelayScoreboard>) -> Self {
+        self.scoreboard = Some(scoreboard);
+        self
+    }
+
+    /// Record the bundles acknowledged in `receipt`, to look for in later blocks.
+    pub fn record_receipt(&self, receipt: &SubmissionReceipt) {
+        let mut pending = self.pending.lock().unwrap();
+        for submission in receipt
+            .submissions
+            .iter()
+            .filter(|submission| submission.is_accepted())
+        {
+            let (Some(tx_hash), Some(target_block)) =
+                (submission.tx_hashes.last(), submission.target_block)
+            else {
+                continue;
+            };
+            let bundle = pending.entry(*tx_hash).or_insert_with(|| PendingBundle {
+                last_block: target_block,
+                destinations: vec![],
+            });
+            bundle.last_block = bundle.last_block.max(target_block);
+            if !bundle.destinations.contains(&submission.destination) {
+                bundle.destinations.push(submission.destination.clone());
+            }
+        }
+    }
+
+    /// Record block `number` with hash `block_hash`, given the hashes of its
+    /// transactions and the payloads relays reported delivering for its number.
+    /// Payloads of other blocks, such as ones reorged out, are ignored. Bundles whose
+    /// target blocks have all passed are forgotten.
+    pub fn record_block(
+        &self,
+        number: U64,
+        block_hash: H256,
+        tx_hashes: &[H256],
+        deliveries: &[Delivery],
+    ) {
+        let deliveries: Vec<_> = deliveries
+            .iter()
+            .filter(|delivery| delivery.payload.block_hash == block_hash)
+            .collect();
+        // Several relays may deliver the same payload, built by a single builder.
+        let relays: HashSet<_> = deliveries.iter().map(|d| d.relay.as_str()).collect();
+        let builder = deliveries.first().map(|d| &d.payload.builder_pubkey);
+        let destination = builder.and_then(|builder| self.destinations.get(builder));
+
+        let mut pending = self.pending.lock().unwrap();
+        let landed: Vec<_> = tx_hashes
+            .iter()
+            .filter_map(|tx_hash| pending.remove(tx_hash))
+            .collect();
+        pending.retain(|_, bundle| bundle.last_block > number);
+        drop(pending);
+
+        let mut report = self.report.lock().unwrap();
+        for relay in &relays {
+            let stats = report.relays.entry(relay.to_string()).or_default();
+            stats.blocks += 1;
+            stats.landed += landed.len() as u64;
+        }
+        let Some(builder) = builder else {
+            report.undelivered += landed.len() as u64;
+            return;
+        };
+        let stats = report.builders.entry(builder.clone()).or_default();
+        stats.destination = destination.cloned();
+        stats.blocks += 1;
+        stats.landed += landed.len() as u64;
+        let Some(destination) = destination else {
+            return;
+        };
+        let submitted = landed
+            .iter()
+            .filter(|bundle| bundle.destinations.contains(destination))
+            .count();
+        stats.submitted += submitted as u64;
+        if let Some(scoreboard) = &self.scoreboard {
+            for _ in 0..submitted {
+                scoreboard.record_delivered(destination);
+            }
+        }
+    }
+
+    /// Returns the inclusion statistics of every relay and builder seen so far.
+    pub fn report(&self) -> InclusionReport {
+        self.report.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Submission;
+
+    fn delivery(relay: &str, block_hash: H256, builder: u8) -> Delivery {
+        Delivery {
+            relay: relay.to_string(),
+            payload: DeliveredPayload {
+                slot: 100,
+                block_number: 10,
+                block_hash,
+                builder_pubkey: Bytes::from(vec![builder; 48]),
+                proposer_pubkey: Bytes::from(vec![0; 48]),
+                proposer_fee_recipient: Address::zero(),
+                value: U256::exp10(17),
+                gas_used: 15_000_000,
+                num_tx: 150,
+            },
+        }
+    }
+
+    #[test]
+    fn parses_delivered_payloads() {
+        let payloads: Vec<DeliveredPayload> = serde_json::from_str(
+            r#"[{"slot":"7000000","parent_hash":"0x0000000000000000000000000000000000000000000000000000000000000001","block_hash":"0x0000000000000000000000000000000000000000000000000000000000000002","builder_pubkey":"0xaa","proposer_pubkey":"0xbb","proposer_fee_recipient":"0x0000000000000000000000000000000000000003","gas_limit":"30000000","gas_used":"12000000","value":"123456789012345678901","block_number":"18000000","num_tx":"140"}]"#,
+        )
+        .unwrap();
+        assert_eq!(payloads[0].block_number, 18_000_000);
+        assert_eq!(payloads[0].builder_pubkey, Bytes::from(vec![0xaa]));
+        assert_eq!(
+            payloads[0].value,
+            U256::from_dec_str("123456789012345678901").unwrap()
+        );
+    }
+
+    #[test]
+    fn attributes_landed_bundles_to_relays_and_builders() {
+        let scoreboard = Arc::new(RelayScoreboard::new());
+        let tracker = RelayInclusionTracker::new()
+            .with_builder(Bytes::from(vec![1; 48]), "titan")
+            .with_scoreboard(scoreboard.clone());
+        let submission = |destination: &str, tx: u64| {
+            Submission::new(destination.to_string(), vec![H256::from_low_u64_be(tx)])
+                .with_target_block(U64::from(10))
+        };
+        tracker.record_receipt(&SubmissionReceipt::new(vec![
+            submission("titan", 1),
+            submission("flashbots", 1),
+            submission("flashbots", 2),
+        ]));
+
+        let block_hash = H256::from_low_u64_be(10);
+        let deliveries = [
+            delivery("ultrasound", block_hash, 1),
+            delivery("agnostic", block_hash, 1),
+            // A payload of a block which was reorged out.
+            delivery("bloxroute", H256::from_low_u64_be(11), 2),
+        ];
+        let tx_hashes = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        tracker.record_block(U64::from(10), block_hash, &tx_hashes, &deliveries);
+
+        let report = tracker.report();
+        let relay = RelayInclusion {
+            blocks: 1,
+            landed: 2,
+        };
+        assert_eq!(report.relays["ultrasound"], relay);
+        assert_eq!(report.relays["agnostic"], relay);
+        assert!(!report.relays.contains_key("bloxroute"));
+        assert_eq!(
+            report.builders[&Bytes::from(vec![1; 48])],
+            BuilderInclusion {
+                destination: Some("titan".to_string()),
+                blocks: 1,
+                landed: 2,
+                submitted: 1,
+            }
+        );
+        assert_eq!(scoreboard.stats("titan").delivered, 1);
+        assert_eq!(scoreboard.stats("flashbots").delivered, 0);
+
+        // Bundles landing in blocks no watched relay delivered aren't attributed.
+        tracker.record_receipt(&SubmissionReceipt::new(vec![
+            submission("titan", 3).with_target_block(U64::from(11))
+        ]));
+        tracker.record_block(
+            U64::from(11),
+            H256::zero(),
+            &[H256::from_low_u64_be(3)],
+            &[],
+        );
+        assert_eq!(tracker.report().undelivered, 1);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use ethers::types::{H256, U64};
+use serde::Serialize;
+
+use crate::collectors::block_collector::NewBlock;
+use crate::types::{Submission, SubmissionReceipt};
+
+/// Default number of recent blocks a [ReorgTracker] keeps. Reorgs deeper than this
+/// aren't detected, and submissions requiring older blocks are forgotten.
+pub const DEFAULT_REORG_DEPTH: usize = 64;
+
+/// A block, identified by its number and hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct BlockRef {
+    pub number: U64,
+    pub hash: H256,
+}
+
+impl From<&NewBlock> for BlockRef {
+    fn from(block: &NewBlock) -> Self {
+        Self {
+            number: block.number,
+            hash: block.hash,
+        }
+    }
+}
+
+/// A reorg of the chain, found by a [ReorgTracker].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// Blocks which are no longer canonical, in ascending order.
+    pub dropped: Vec<BlockRef>,
+    /// The block which reorged the chain.
+    pub head: BlockRef,
+}
+
+/// Submissions invalidated by a reorg, sent to strategies so they can resubmit, or
+/// cancel, the bundles they were made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invalidation {
+    pub reorg: Reorg,
+    /// Submissions which required one of the dropped blocks.
+    pub submissions: Vec<Submission>,
+}
+
+/// Tracks the canonical chain from new blocks, and the submissions which are only
+/// valid while one of its blocks stays canonical. A submission requires its
+/// [required block](Submission::required_block) if it has one, or else the latest
+/// block when it was [registered](ReorgTracker::register).
+#[derive(Debug)]
+pub struct ReorgTracker {
+    depth: usize,
+    /// Hashes of the recent canonical blocks, by number.
+    canonical: BTreeMap<U64, H256>,
+    pending: Vec<(BlockRef, Submission)>,
+}
+
+impl ReorgTracker {
+    /// Track the last `depth` blocks.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            canonical: BTreeMap::new(),
+            pending: vec![],
+        }
+    }
+
+    /// The latest canonical block, if a block was seen.
+    pub fn head(&self) -> Option<BlockRef> {
+        self.canonical
+            .last_key_value()
+            .map(|(number, hash)| BlockRef {
+                number: *number,
+                hash: *hash,
+            })
+    }
+
+    /// Register the accepted submissions of `receipt`. Submissions without a required
+    /// block are dropped if no block was seen yet.
+    pub fn register(&mut self, receipt: &SubmissionReceipt) {
+        let head = self.head();
+        for submission in &receipt.submissions {
+            if !submission.is_accepted() {
+                continue;
+            }
+            if let Some(block) = submission.required_block.or(head) {
+                self.pending.push((block, submission.clone()));
+            }
+        }
+    }
+
+    /// Record a new block, returning the submissions it invalidates if it reorgs the
+    /// chain.
+    pub fn on_block(&mut self, block: &NewBlock) -> Option<Invalidation> {
+        let head = self.head();
+        // A block already seen, e.g. after a collector reconnects, isn't a reorg.
+        if self.canonical.get(&block.number) == Some(&block.hash)
+            && head.is_some_and(|head| head.number > block.number)
+        {
+            return None;
+        }
+
+        let mut dropped = vec![];
+        if block.number > U64::zero() {
+            let parent = block.number - 1;
+            if let Some(hash) = self.canonical.insert(parent, block.parent_hash) {
+                if hash != block.parent_hash {
+                    dropped.push(BlockRef {
+                        number: parent,
+                        hash,
+                    });
+                }
+            }
+        }
+        // Blocks at the height of the new block, or above it, were replaced.
+        for (number, hash) in self.canonical.split_off(&block.number) {
+            if hash != block.hash {
+                dropped.push(BlockRef { number, hash });
+            }
+        }
+        self.canonical.insert(block.number, block.hash);
+        while self.canonical.len() > self.depth {
+            self.canonical.pop_first();
+        }
+        if let Some((oldest, _)) = self.canonical.first_key_value() {
+            let oldest = *oldest;
+            self.pending
+                .retain(|(required, _)| required.number >= oldest);
+        }
+
+        if dropped.is_empty() {
+            return None;
+        }
+        let (invalidated, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(required, _)| dropped.contains(required));
+        self.pending = pending;
+        Some(Invalidation {
+            reorg: Reorg {
+                dropped,
+                head: BlockRef::from(block),
+            },
+            submissions: invalidated
+                .into_iter()
+                .map(|(_, submission)| submission)
+                .collect(),
+        })
+    }
+}
+
+impl Default for ReorgTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORG_DEPTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, hash: u8, parent_hash: u8) -> NewBlock {
+        NewBlock {
+            hash: H256::repeat_byte(hash),
+            number: U64::from(number),
+            parent_hash: H256::repeat_byte(parent_hash),
+        }
+    }
+
+    fn receipt(destination: &str) -> SubmissionReceipt {
+        SubmissionReceipt::new(vec![Submission::new(destination, vec![])])
+    }
+
+    #[test]
+    fn invalidates_submissions_of_reorged_blocks() {
+        let mut tracker = ReorgTracker::new(3);
+        assert!(tracker.on_block(&block(1, 1, 0)).is_none());
+        tracker.register(&receipt("on 1"));
+        assert!(tracker.on_block(&block(2, 2, 1)).is_none());
+        tracker.register(&receipt("on 2"));
+
+        // Seeing block 1 again isn't a reorg.
+        assert!(tracker.on_block(&block(1, 1, 0)).is_none());
+
+        // A new block 2 replaces the old one.
+        let invalidation = tracker.on_block(&block(2, 3, 1)).unwrap();
+        assert_eq!(
+            invalidation.reorg.dropped,
+            vec![BlockRef::from(&block(2, 2, 1))]
+        );
+        assert_eq!(invalidation.submissions.len(), 1);
+        assert_eq!(invalidation.submissions[0].destination, "on 2");
+
+        // A block 3 on top of another block 2 replaces block 2 again.
+        let invalidation = tracker.on_block(&block(3, 5, 4)).unwrap();
+        assert_eq!(invalidation.reorg.dropped[0].hash, H256::repeat_byte(3));
+        assert!(invalidation.submissions.is_empty());
+        assert_eq!(tracker.head().unwrap().number, U64::from(3));
+
+        // Submissions requiring blocks no longer tracked are forgotten.
+        tracker.on_block(&block(4, 6, 5));
+        assert!(tracker.pending.is_empty());
+    }
+}
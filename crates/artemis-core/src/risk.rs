@@ -0,0 +1,461 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use ethers::types::U256;
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+use crate::error::Result;
+use crate::types::Strategy;
+
+/// Window over which the gas spend of a strategy is limited.
+const GAS_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Limits a [RiskManagedStrategy] enforces. Unset limits aren't enforced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskLimits {
+    /// Gas, in wei, the bundles of the strategy may spend in any hour.
+    pub max_gas_per_hour: Option<U256>,
+    /// Bundles in a row which may lose money.
+    pub max_consecutive_losses: Option<u32>,
+    /// Notional, in wei, a single bundle may trade.
+    pub max_notional: Option<U256>,
+}
+
+impl RiskLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_gas_per_hour(mut self, max_gas: U256) -> Self {
+        self.max_gas_per_hour = Some(max_gas);
+        self
+    }
+
+    pub fn with_max_consecutive_losses(mut self, max_losses: u32) -> Self {
+        self.max_consecutive_losses = Some(max_losses);
+        self
+    }
+
+    pub fn with_max_notional(mut self, max_notional: U256) -> Self {
+        self.max_notional = Some(max_notional);
+        self
+    }
+}
+
+/// A limit a strategy breached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskBreach {
+    /// The bundles of the last hour spent `spent` wei of gas.
+    GasPerHour { spent: U256, limit: U256 },
+    /// The last `losses` bundles lost money.
+    ConsecutiveLosses { losses: u32, limit: u32 },
+    /// An action traded `notional` wei.
+    Notional { notional: U256, limit: U256 },
+}
+
+impl fmt::Display for RiskBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskBreach::GasPerHour { spent, limit } => {
+                write!(f, "spent {} wei of gas in an hour, limit {}", spent, limit)
+            }
+            RiskBreach::ConsecutiveLosses { losses, limit } => {
+                write!(f, "{} losing bundles in a row, limit {}", losses, limit)
+            }
+            RiskBreach::Notional { notional, limit } => {
+                write!(f, "bundle notional of {} wei, limit {}", notional, limit)
+            }
+        }
+    }
+}
+
+/// Outcome of a bundle of a strategy, e.g. once it landed or expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleOutcome {
+    /// Gas, in wei, the bundle spent. Zero if it didn't land.
+    pub gas_spent: U256,
+    /// Whether the bundle lost money.
+    pub lost: bool,
+}
+
+/// Emitted, as an action, when a strategy breaches a limit and is stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskAlert {
+    /// Name of the [kill switch](KillSwitch) of the strategy.
+    pub strategy: String,
+    pub breach: RiskBreach,
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    breach: Option<RiskBreach>,
+    consecutive_losses: u32,
+    /// Gas spent by recent bundles, oldest first.
+    gas_spent: VecDeque<(Instant, U256)>,
+}
+
+/// Stops a [RiskManagedStrategy] once it breaches a limit, until an operator re-arms
+/// it, e.g. with the `admin_rearmStrategy` call of the
+/// [AdminServer](crate::admin::AdminServer). Kill switches are created with
+/// [add_kill_switch](crate::admin::EngineControl::add_kill_switch) so the admin server
+/// can find them by name.
+#[derive(Debug)]
+pub struct KillSwitch {
+    name: String,
+    state: Mutex<RiskState>,
+}
+
+/// A kill switch, as listed by the [AdminServer](crate::admin::AdminServer).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KillSwitchReport {
+    pub name: String,
+    /// The limit the strategy breached, if it is stopped.
+    pub breach: Option<String>,
+}
+
+impl KillSwitch {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            state: Mutex::new(RiskState::default()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the limit the strategy breached, if it is stopped.
+    pub fn breach(&self) -> Option<RiskBreach> {
+        self.state.lock().unwrap().breach.clone()
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.state.lock().unwrap().breach.is_some()
+    }
+
+    /// Restart the strategy, forgetting its losses and gas spend so far.
+    pub fn rearm(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(breach) = state.breach.take() {
+            info!(strategy = self.name, %breach, "re-arming strategy");
+        }
+        *state = RiskState::default();
+    }
+
+    pub fn report(&self) -> KillSwitchReport {
+        KillSwitchReport {
+            name: self.name.clone(),
+            breach: self.breach().map(|breach| breach.to_string()),
+        }
+    }
+
+    /// Stop the strategy for `breach`, unless it is already stopped. Returns whether
+    /// the switch tripped.
+    pub(crate) fn trip(&self, breach: RiskBreach) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.breach.is_some() {
+            return false;
+        }
+        warn!(strategy = self.name, %breach, "risk limit breached, stopping strategy");
+        state.breach = Some(breach);
+        true
+    }
+
+    /// Account for the outcome of a bundle, returning the limit it breached, if any.
+    fn record(
+        &self,
+        limits: &RiskLimits,
+        outcome: BundleOutcome,
+        now: Instant,
+    ) -> Option<RiskBreach> {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_losses = match outcome.lost {
+            true => state.consecutive_losses + 1,
+            false => 0,
+        };
+        if !outcome.gas_spent.is_zero() {
+            state.gas_spent.push_back((now, outcome.gas_spent));
+        }
+        while let Some((spent_at, _)) = state.gas_spent.front() {
+            match now.duration_since(*spent_at) > GAS_WINDOW {
+                true => state.gas_spent.pop_front(),
+                false => break,
+            };
+        }
+
+        if let Some(limit) = limits.max_consecutive_losses {
+            if state.consecutive_losses > limit {
+                return Some(RiskBreach::ConsecutiveLosses {
+                    losses: state.consecutive_losses,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = limits.max_gas_per_hour {
+            let spent = state
+                .gas_spent
+                .iter()
+                .fold(U256::zero(), |total, (_, gas)| total.saturating_add(*gas));
+            if spent > limit {
+                return Some(RiskBreach::GasPerHour { spent, limit });
+            }
+        }
+        None
+    }
+}
+
+type NotionalOf<A> = Box<dyn Fn(&A) -> Option<U256> + Send + Sync>;
+type OutcomeOf<E> = Box<dyn Fn(&E) -> Option<BundleOutcome> + Send + Sync>;
+type AlertAction<A> = Box<dyn Fn(RiskAlert) -> A + Send + Sync>;
+
+/// RiskManagedStrategy is a wrapper around a [Strategy](Strategy) which enforces
+/// [risk limits](RiskLimits) on it. The notional of its actions is checked before they
+/// are returned, and the outcomes of its bundles are read from the events it receives.
+/// Once a limit is breached, the strategy is stopped: it receives no more events, and
+/// the [alert](RiskAlert) action is returned instead, until its [KillSwitch] is
+/// re-armed.
+pub struct RiskManagedStrategy<S, E, A> {
+    strategy: S,
+    limits: RiskLimits,
+    kill_switch: Arc<KillSwitch>,
+    notional_of: Option<NotionalOf<A>>,
+    outcome_of: Option<OutcomeOf<E>>,
+    alert: AlertAction<A>,
+}
+
+impl<S, E, A> RiskManagedStrategy<S, E, A> {
+    /// Enforce `limits` on `strategy`, stopping it with `kill_switch`, and returning the
+    /// action `alert` makes when a limit is breached.
+    pub fn new<F>(strategy: S, limits: RiskLimits, kill_switch: Arc<KillSwitch>, alert: F) -> Self
+    where
+        F: Fn(RiskAlert) -> A + Send + Sync + 'static,
+    {
+        Self {
+            strategy,
+            limits,
+            kill_switch,
+            notional_of: None,
+            outcome_of: None,
+            alert: Box::new(alert),
+        }
+    }
+
+    /// Read the notional of actions with `f`, which returns `None` for actions which
+    /// don't trade. Required to enforce the max notional.
+    pub fn with_notional<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&A) -> Option<U256> + Send + Sync + 'static,
+    {
+        self.notional_of = Some(Box::new(f));
+        self
+    }
+
+    /// Read the outcomes of bundles from events with `f`, which returns `None` for
+    /// other events. Required to enforce the gas spend and consecutive losses.
+    pub fn with_outcomes<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&E) -> Option<BundleOutcome> + Send + Sync + 'static,
+    {
+        self.outcome_of = Some(Box::new(f));
+        self
+    }
+
+    pub fn kill_switch(&self) -> Arc<KillSwitch> {
+        self.kill_switch.clone()
+    }
+
+    /// Stop the strategy for `breach`, returning the alert if the switch tripped.
+    fn stop(&self, breach: RiskBreach) -> Option<A> {
+        self.kill_switch.trip(breach.clone()).then(|| {
+            (self.alert)(RiskAlert {
+                strategy: self.kill_switch.name.clone(),
+                breach,
+            })
+        })
+    }
+}
+
+#[async_trait]
+impl<S, E, A> Strategy<E, A> for RiskManagedStrategy<S, E, A>
+where
+    S: Strategy<E, A>,
+    E: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Option<A> {
+        let outcome = self
+            .outcome_of
+            .as_ref()
+            .and_then(|outcome_of| outcome_of(&event));
+        if let Some(outcome) = outcome {
+            let breach = self
+                .kill_switch
+                .record(&self.limits, outcome, Instant::now());
+            if let Some(breach) = breach {
+                return self.stop(breach);
+            }
+        }
+        if self.kill_switch.is_tripped() {
+            debug!(
+                strategy = self.kill_switch.name,
+                "strategy stopped, dropping event"
+            );
+            return None;
+        }
+
+        let action = self.strategy.process_event(event).await?;
+        let notional = self
+            .notional_of
+            .as_ref()
+            .and_then(|notional_of| notional_of(&action));
+        match (notional, self.limits.max_notional) {
+            (Some(notional), Some(limit)) if notional > limit => {
+                self.stop(RiskBreach::Notional { notional, limit })
+            }
+            _ => Some(action),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Action {
+        Trade(u64),
+        Alert(RiskAlert),
+    }
+
+    enum Event {
+        Opportunity(u64),
+        Outcome(BundleOutcome),
+    }
+
+    /// Trades the notional of every opportunity.
+    struct Trader;
+
+    #[async_trait]
+    impl Strategy<Event, Action> for Trader {
+        async fn sync_state(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn process_event(&mut self, event: Event) -> Option<Action> {
+            match event {
+                Event::Opportunity(notional) => Some(Action::Trade(notional)),
+                Event::Outcome(_) => None,
+            }
+        }
+    }
+
+    fn managed(limits: RiskLimits) -> RiskManagedStrategy<Trader, Event, Action> {
+        RiskManagedStrategy::new(
+            Trader,
+            limits,
+            Arc::new(KillSwitch::new("trader")),
+            Action::Alert,
+        )
+        .with_notional(|action| match action {
+            Action::Trade(notional) => Some(U256::from(*notional)),
+            Action::Alert(_) => None,
+        })
+        .with_outcomes(|event| match event {
+            Event::Outcome(outcome) => Some(*outcome),
+            Event::Opportunity(_) => None,
+        })
+    }
+
+    fn outcome(gas_spent: u64, lost: bool) -> Event {
+        Event::Outcome(BundleOutcome {
+            gas_spent: U256::from(gas_spent),
+            lost,
+        })
+    }
+
+    #[tokio::test]
+    async fn stops_on_notional_until_rearmed() {
+        let mut strategy = managed(RiskLimits::new().with_max_notional(U256::from(100)));
+        let kill_switch = strategy.kill_switch();
+
+        assert_eq!(
+            strategy.process_event(Event::Opportunity(100)).await,
+            Some(Action::Trade(100))
+        );
+        let breach = RiskBreach::Notional {
+            notional: U256::from(101),
+            limit: U256::from(100),
+        };
+        assert_eq!(
+            strategy.process_event(Event::Opportunity(101)).await,
+            Some(Action::Alert(RiskAlert {
+                strategy: "trader".to_string(),
+                breach: breach.clone(),
+            }))
+        );
+        assert_eq!(kill_switch.breach(), Some(breach));
+        assert_eq!(strategy.process_event(Event::Opportunity(1)).await, None);
+
+        kill_switch.rearm();
+        assert_eq!(
+            strategy.process_event(Event::Opportunity(1)).await,
+            Some(Action::Trade(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_on_consecutive_losses_and_gas_spend() {
+        let mut strategy = managed(RiskLimits::new().with_max_consecutive_losses(2));
+        assert_eq!(strategy.process_event(outcome(0, true)).await, None);
+        assert_eq!(strategy.process_event(outcome(0, false)).await, None);
+        assert_eq!(strategy.process_event(outcome(0, true)).await, None);
+        assert_eq!(strategy.process_event(outcome(0, true)).await, None);
+        assert!(matches!(
+            strategy.process_event(outcome(0, true)).await,
+            Some(Action::Alert(RiskAlert {
+                breach: RiskBreach::ConsecutiveLosses {
+                    losses: 3,
+                    limit: 2
+                },
+                ..
+            }))
+        ));
+        // The alert is only emitted once.
+        assert_eq!(strategy.process_event(outcome(0, true)).await, None);
+
+        let mut strategy = managed(RiskLimits::new().with_max_gas_per_hour(U256::from(100)));
+        assert_eq!(strategy.process_event(outcome(60, false)).await, None);
+        assert!(matches!(
+            strategy.process_event(outcome(60, false)).await,
+            Some(Action::Alert(RiskAlert {
+                breach: RiskBreach::GasPerHour { .. },
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn forgets_gas_spent_over_an_hour_ago() {
+        let limits = RiskLimits::new().with_max_gas_per_hour(U256::from(100));
+        let kill_switch = KillSwitch::new("trader");
+        let start = Instant::now();
+        let spend = BundleOutcome {
+            gas_spent: U256::from(60),
+            lost: false,
+        };
+        assert_eq!(kill_switch.record(&limits, spend, start), None);
+        let later = start + GAS_WINDOW + Duration::from_secs(1);
+        assert_eq!(kill_switch.record(&limits, spend, later), None);
+    }
+}
@@ -0,0 +1,350 @@
+//! Utilities for testing strategies without hitting real relays or nodes.
+//!
+//! A typical test pushes events into a [MockCollector](MockCollector), runs the
+//! engine with a [CapturingExecutor](CapturingExecutor), and then asserts on the
+//! actions that were captured. Executors are tested against a [MockRelay](MockRelay),
+//! which records the requests they send.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::error::Result;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use ethers::{
+    types::{Address, Signature, H256},
+    utils::keccak256,
+};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::debug;
+
+use crate::{
+    engine::Engine,
+    types::{Collector, CollectorStream, Executor, SubmissionReceipt},
+    utilities::http::{read_request, write_json_response},
+};
+
+/// A collector whose events are pushed programmatically.
+pub struct MockCollector<E> {
+    sender: UnboundedSender<E>,
+    receiver: Mutex<Option<UnboundedReceiver<E>>>,
+}
+
+impl<E> MockCollector<E> {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Returns a sender which can be used to push events after the collector
+    /// has been handed to the engine.
+    pub fn sender(&self) -> UnboundedSender<E> {
+        self.sender.clone()
+    }
+
+    /// Push an event into the collector's stream.
+    pub fn push(&self, event: E) {
+        // The receiver lives as long as the collector, so this cannot fail.
+        let _ = self.sender.send(event);
+    }
+}
+
+impl<E> Default for MockCollector<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [MockCollector](MockCollector).
+/// The event stream can only be taken once.
+#[async_trait]
+impl<E: Send + 'static> Collector<E> for MockCollector<E> {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let receiver = self
+            .receiver
+            .lock()
+            .map_err(|_| anyhow!("mock collector lock poisoned"))?
+            .take()
+            .ok_or_else(|| anyhow!("mock collector stream already taken"))?;
+        Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+    }
+}
+
+/// An executor that records every action it receives. Clones share the same
+/// underlying record, so keep a clone around before handing one to the engine.
+#[derive(Clone)]
+pub struct CapturingExecutor<A> {
+    actions: Arc<Mutex<Vec<A>>>,
+}
+
+impl<A: Clone> CapturingExecutor<A> {
+    pub fn new() -> Self {
+        Self {
+            actions: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Returns a copy of all actions executed so far.
+    pub fn actions(&self) -> Vec<A> {
+        self.actions.lock().unwrap().clone()
+    }
+
+    /// Returns the number of actions executed so far.
+    pub fn len(&self) -> usize {
+        self.actions.lock().unwrap().len()
+    }
+
+    /// Returns true if no actions have been executed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<A: Clone> Default for CapturingExecutor<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<A: Send + Sync + 'static> Executor<A> for CapturingExecutor<A> {
+    /// Record the action.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt> {
+        self.actions
+            .lock()
+            .map_err(|_| anyhow!("capturing executor lock poisoned"))?
+            .push(action);
+        Ok(SubmissionReceipt::default())
+    }
+}
+
+/// Wait until `executor` has captured at least `count` actions, returning them.
+/// Errors if `timeout` elapses first.
+pub async fn wait_for_actions<A: Clone>(
+    executor: &CapturingExecutor<A>,
+    count: usize,
+    timeout: Duration,
+) -> Result<Vec<A>> {
+    let deadline = Instant::now() + timeout;
+    while executor.len() < count {
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "timed out waiting for {} actions, got {}",
+                count,
+                executor.len()
+            )
+            .into());
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    Ok(executor.actions())
+}
+
+/// Run `engine` until the number of actions captured by `executor` has not
+/// changed for `idle`, then shut the engine down and return the captured actions.
+/// Fails if a task of the engine exited before it was shut down.
+pub async fn run_to_quiescence<E, A>(
+    engine: Engine<E, A>,
+    executor: &CapturingExecutor<A>,
+    idle: Duration,
+) -> Result<Vec<A>>
+where
+    E: Send + Clone + 'static + Debug,
+    A: Send + Clone + 'static + Debug,
+{
+    let running = engine
+        .run()
+        .await
+        .map_err(|e| anyhow!("error starting engine: {}", e))?;
+
+    let mut last_count = executor.len();
+    loop {
+        sleep(idle).await;
+        let count = executor.len();
+        if count == last_count {
+            break;
+        }
+        last_count = count;
+    }
+
+    running.shutdown();
+    running
+        .wait()
+        .await
+        .map_err(|e| anyhow!("engine stopped: {}", e))?;
+    Ok(executor.actions())
+}
+
+/// A request received by a [MockRelay](MockRelay).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayRequest {
+    /// Header names and values, in the order they were sent.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RelayRequest {
+    /// Returns the value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body, parsed as JSON.
+    pub fn json(&self) -> Result<Value> {
+        Ok(serde_json::from_slice(&self.body).map_err(anyhow::Error::from)?)
+    }
+
+    /// Returns the JSON-RPC method called, if the body is a JSON-RPC call.
+    pub fn method(&self) -> Option<String> {
+        let body = self.json().ok()?;
+        body.get("method")?.as_str().map(String::from)
+    }
+
+    /// Verify the `X-Flashbots-Signature` header, `<address>:<signature>`, is a
+    /// signature of the hash of the body by the address, returning the address.
+    pub fn flashbots_signer(&self) -> Result<Address> {
+        let header = self
+            .header("x-flashbots-signature")
+            .ok_or_else(|| anyhow!("no flashbots signature header"))?;
+        let (address, signature) = header
+            .split_once(':')
+            .ok_or_else(|| anyhow!("malformed flashbots signature {}", header))?;
+        let address: Address = address.parse().map_err(anyhow::Error::from)?;
+        let signature: Signature = signature.parse().map_err(anyhow::Error::from)?;
+        let message = format!("0x{:x}", H256::from(keccak256(&self.body)));
+        signature
+            .verify(message, address)
+            .map_err(|e| anyhow!("invalid flashbots signature: {}", e))?;
+        Ok(address)
+    }
+}
+
+#[derive(Debug, Default)]
+struct RelayState {
+    /// Result of each JSON-RPC method.
+    results: HashMap<String, Value>,
+    /// Number of upcoming requests answered with an HTTP error.
+    failures: usize,
+    requests: Vec<RelayRequest>,
+}
+
+/// A JSON-RPC relay served over HTTP on a local port, for testing executors without
+/// hitting real relays. Every request is recorded, and answered with the result set
+/// for its method with [respond](MockRelay::respond), or an error if none was set.
+/// The relay stops serving once dropped.
+pub struct MockRelay {
+    addr: SocketAddr,
+    state: Arc<Mutex<RelayState>>,
+    server: JoinHandle<()>,
+}
+
+impl MockRelay {
+    /// Serve a relay on a free local port.
+    pub async fn spawn() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(anyhow::Error::from)?;
+        let addr = listener.local_addr().map_err(anyhow::Error::from)?;
+        let state = Arc::new(Mutex::new(RelayState::default()));
+        let server = tokio::spawn(serve_relay(listener, state.clone()));
+        Ok(Self {
+            addr,
+            state,
+            server,
+        })
+    }
+
+    /// Returns the url of the relay.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Answer calls of `method` with `result`.
+    pub fn respond(&self, method: &str, result: Value) {
+        let mut state = self.state.lock().unwrap();
+        state.results.insert(method.to_string(), result);
+    }
+
+    /// Answer the next `count` requests with `503 Service Unavailable`, as an
+    /// overloaded relay would. They are still recorded.
+    pub fn fail_next(&self, count: usize) {
+        self.state.lock().unwrap().failures = count;
+    }
+
+    /// Returns the requests received so far.
+    pub fn requests(&self) -> Vec<RelayRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for MockRelay {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Serve the requests of a [MockRelay](MockRelay), one connection at a time.
+async fn serve_relay(listener: TcpListener, state: Arc<Mutex<RelayState>>) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let request = match read_request(&mut stream).await {
+            Ok(request) => request,
+            Err(e) => {
+                debug!("error reading relay request: {}", e);
+                continue;
+            }
+        };
+        let (status, response) = {
+            let mut state = state.lock().unwrap();
+            state.requests.push(RelayRequest {
+                headers: request.headers,
+                body: request.body.clone(),
+            });
+            if state.failures > 0 {
+                state.failures -= 1;
+                ("503 Service Unavailable", json!({}))
+            } else {
+                ("200 OK", relay_response(&state.results, &request.body))
+            }
+        };
+        if let Err(e) = write_json_response(&mut stream, status, &response.to_string()).await {
+            debug!("error writing relay response: {}", e);
+        }
+    }
+}
+
+/// Returns the JSON-RPC response to `body`.
+fn relay_response(results: &HashMap<String, Value>, body: &[u8]) -> Value {
+    let call: Value = serde_json::from_slice(body).unwrap_or_default();
+    let id = call.get("id").cloned().unwrap_or(Value::Null);
+    let method = call
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match results.get(method) {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("method {:?} not found", method) },
+        }),
+    }
+}
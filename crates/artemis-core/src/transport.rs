@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::Result;
+use crate::types::Strategy;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+
+/// Capacity of the channel of each topic of a [MemoryTransport](MemoryTransport).
+const MEMORY_TOPIC_CAPACITY: usize = 1024;
+
+/// An event or action published to a [Transport](Transport), with where and when it was
+/// published, so consumers can measure how stale it is. Envelopes are serialized as
+/// JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    /// Name of the process which published the message, e.g. `collectors-eu`.
+    pub source: String,
+    /// Position of the message among those its source published to the topic.
+    pub sequence: u64,
+    /// Unix timestamp (in milliseconds) at which the message was published.
+    pub published_at_ms: u64,
+    pub payload: T,
+}
+
+impl<T> Envelope<T> {
+    /// Time elapsed since the message was published, zero if the clocks of the
+    /// publisher and consumer disagree.
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(now_ms().saturating_sub(self.published_at_ms))
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A message queue connecting the components of a bot running in different processes,
+/// e.g. collectors running next to nodes, publishing events to strategies running in
+/// another process, whose actions are published to executors running next to relays.
+/// Messages are delivered to every subscriber of their topic.
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    /// Publish `message` to `topic`.
+    async fn publish(&self, topic: &str, message: Vec<u8>) -> anyhow::Result<()>;
+
+    /// Subscribe to the messages published to `topic` from now on.
+    async fn subscribe(&self, topic: &str) -> anyhow::Result<BoxStream<'static, Vec<u8>>>;
+}
+
+/// Publishes [envelopes](Envelope) of events or actions to a topic of a
+/// [Transport](Transport).
+#[derive(Debug)]
+pub struct Publisher<T> {
+    transport: Arc<dyn Transport>,
+    topic: String,
+    source: String,
+    sequence: AtomicU64,
+    _payload: PhantomData<fn(T)>,
+}
+
+impl<T: Serialize> Publisher<T> {
+    /// Publish to `topic` of `transport`, as `source`, the name of this process.
+    pub fn new(
+        transport: Arc<dyn Transport>,
+        topic: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        Self {
+            transport,
+            topic: topic.into(),
+            source: source.into(),
+            sequence: AtomicU64::new(0),
+            _payload: PhantomData,
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Wrap `payload` in an envelope and publish it.
+    pub async fn publish(&self, payload: &T) -> anyhow::Result<()> {
+        let envelope = Envelope {
+            source: self.source.clone(),
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            published_at_ms: now_ms(),
+            payload,
+        };
+        let message = serde_json::to_vec(&envelope)?;
+        self.transport.publish(&self.topic, message).await
+    }
+}
+
+/// A transport delivering messages within the process, e.g. to test a split topology
+/// in a single process.
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+    topics: Mutex<HashMap<String, broadcast::Sender<Vec<u8>>>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn topic(&self, topic: &str) -> broadcast::Sender<Vec<u8>> {
+        self.topics
+            .lock()
+            .unwrap()
+            .entry(topic.to_string())
+            .or_insert_with(|| broadcast::channel(MEMORY_TOPIC_CAPACITY).0)
+            .clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn publish(&self, topic: &str, message: Vec<u8>) -> anyhow::Result<()> {
+        // Nobody may be subscribed, which is fine.
+        let _ = self.topic(topic).send(message);
+        Ok(())
+    }
+
+    async fn subscribe(&self, topic: &str) -> anyhow::Result<BoxStream<'static, Vec<u8>>> {
+        let receiver = self.topic(topic).subscribe();
+        Ok(Box::pin(
+            BroadcastStream::new(receiver).filter_map(|message| message.ok()),
+        ))
+    }
+}
+
+/// A transport publishing messages to subjects of a NATS server. Subscribers joining a
+/// [queue group](NatsTransport::with_queue_group) share the messages of a subject
+/// instead of each receiving all of them, e.g. to load balance strategies.
+#[cfg(feature = "nats")]
+#[derive(Debug, Clone)]
+pub struct NatsTransport {
+    client: async_nats::Client,
+    queue_group: Option<String>,
+}
+
+#[cfg(feature = "nats")]
+impl NatsTransport {
+    /// Connect to the NATS server at `url`, e.g. `nats://10.0.0.1:4222`.
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: async_nats::connect(url).await?,
+            queue_group: None,
+        })
+    }
+
+    /// Subscribe as a member of `queue_group`, so each message is delivered to a
+    /// single member of the group.
+    pub fn with_queue_group(mut self, queue_group: impl Into<String>) -> Self {
+        self.queue_group = Some(queue_group.into());
+        self
+    }
+}
+
+#[cfg(feature = "nats")]
+#[async_trait]
+impl Transport for NatsTransport {
+    async fn publish(&self, topic: &str, message: Vec<u8>) -> anyhow::Result<()> {
+        self.client
+            .publish(topic.to_string(), message.into())
+            .await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, topic: &str) -> anyhow::Result<BoxStream<'static, Vec<u8>>> {
+        let subscriber = match &self.queue_group {
+            Some(group) => {
+                self.client
+                    .queue_subscribe(topic.to_string(), group.clone())
+                    .await?
+            }
+            None => self.client.subscribe(topic.to_string()).await?,
+        };
+        Ok(Box::pin(subscriber.map(|message| message.payload.to_vec())))
+    }
+}
+
+/// A transport publishing messages to Kafka topics. Subscribers of the same consumer
+/// group share the partitions of a topic, so each message is delivered to a single
+/// member of the group.
+#[cfg(feature = "kafka")]
+#[derive(Clone)]
+pub struct KafkaTransport {
+    brokers: String,
+    group_id: String,
+    producer: rdkafka::producer::FutureProducer,
+    send_timeout: Duration,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaTransport {
+    /// Publish to the Kafka cluster of `brokers`, e.g. `10.0.0.1:9092,10.0.0.2:9092`,
+    /// subscribing as a member of the consumer group `group_id`.
+    pub fn new(brokers: impl Into<String>, group_id: impl Into<String>) -> anyhow::Result<Self> {
+        let brokers = brokers.into();
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .set("linger.ms", "0")
+            .create()?;
+        Ok(Self {
+            brokers,
+            group_id: group_id.into(),
+            producer,
+            send_timeout: Duration::from_secs(1),
+        })
+    }
+
+    /// Fail publishing messages the producer couldn't queue within `send_timeout`.
+    /// Defaults to one second.
+    pub fn with_send_timeout(mut self, send_timeout: Duration) -> Self {
+        self.send_timeout = send_timeout;
+        self
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl Debug for KafkaTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaTransport")
+            .field("brokers", &self.brokers)
+            .field("group_id", &self.group_id)
+            .finish()
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl Transport for KafkaTransport {
+    async fn publish(&self, topic: &str, message: Vec<u8>) -> anyhow::Result<()> {
+        let record = rdkafka::producer::FutureRecord::<(), _>::to(topic).payload(&message);
+        self.producer
+            .send(record, self.send_timeout)
+            .await
+            .map_err(|(e, _)| e)?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, topic: &str) -> anyhow::Result<BoxStream<'static, Vec<u8>>> {
+        use rdkafka::consumer::{Consumer, StreamConsumer};
+        use rdkafka::Message;
+
+        let consumer: StreamConsumer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", &self.brokers)
+            .set("group.id", &self.group_id)
+            .set("auto.offset.reset", "latest")
+            .create()?;
+        consumer.subscribe(&[topic])?;
+        let stream = futures::stream::unfold(consumer, |consumer| async move {
+            loop {
+                let payload = match consumer.recv().await {
+                    Ok(message) => message.payload().map(<[u8]>::to_vec),
+                    Err(e) => {
+                        tracing::warn!("error receiving kafka message: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(payload) = payload {
+                    return Some((payload, consumer));
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A strategy emitting every event as an action, for processes which only run
+/// collectors: events are forwarded to a
+/// [QueueExecutor](crate::executors::queue_executor::QueueExecutor) publishing them to
+/// the strategies running in other processes.
+#[derive(Debug, Default)]
+pub struct ForwardingStrategy;
+
+#[async_trait]
+impl<E: Send + 'static> Strategy<E, E> for ForwardingStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: E) -> Option<E> {
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_transport_delivers_envelopes_to_every_subscriber() {
+        let transport = Arc::new(MemoryTransport::new());
+        let mut first = transport.subscribe("events").await.unwrap();
+        let mut second = transport.subscribe("events").await.unwrap();
+        let publisher = Publisher::new(transport.clone(), "events", "collectors-eu");
+
+        publisher.publish(&7u64).await.unwrap();
+        publisher.publish(&8u64).await.unwrap();
+        for subscriber in [&mut first, &mut second] {
+            for (sequence, payload) in [(0, 7), (1, 8)] {
+                let message = subscriber.next().await.unwrap();
+                let envelope: Envelope<u64> = serde_json::from_slice(&message).unwrap();
+                assert_eq!(envelope.source, "collectors-eu");
+                assert_eq!(envelope.sequence, sequence);
+                assert_eq!(envelope.payload, payload);
+                assert!(envelope.age() < Duration::from_secs(1));
+            }
+        }
+    }
+}
@@ -1,14 +1,20 @@
-use anyhow::Result;
 use async_trait::async_trait;
-use ethers::types::Transaction;
+use ethers::types::{Chain, Transaction, H256, U64};
+use futures::stream::FuturesUnordered;
+use serde::Serialize;
+use std::future::Future;
 use std::pin::Pin;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
+use tracing::warn;
 
 use crate::collectors::block_collector::NewBlock;
 use crate::collectors::opensea_order_collector::OpenseaOrder;
+use crate::error::Result;
 use crate::executors::flashbots_executor::FlashbotsBundle;
 use crate::executors::mempool_executor::SubmitTxToMempool;
+use crate::reorg::BlockRef;
 
 /// A stream of events emitted by a [Collector](Collector).
 pub type CollectorStream<'a, E> = Pin<Box<dyn Stream<Item = E> + Send + 'a>>;
@@ -20,6 +26,20 @@ pub trait Collector<E>: Send + Sync {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>>;
 }
 
+/// Collector whose event stream can be torn down and created again, e.g. when it
+/// stalls because its subscription silently died. Added to the engine with
+/// [add_restartable_collector](crate::engine::Engine::add_restartable_collector),
+/// which drops a stalled stream, and calls [get_event_stream](Collector::get_event_stream)
+/// again after [restart](RestartableCollector::restart).
+#[async_trait]
+pub trait RestartableCollector<E>: Collector<E> {
+    /// Prepare to create a new event stream, e.g. by reconnecting to the node.
+    /// Collectors whose streams open a connection of their own need nothing more.
+    async fn restart(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
 /// Strategy trait, which defines the core logic for each opportunity.
 #[async_trait]
 pub trait Strategy<E, A>: Send + Sync {
@@ -31,11 +51,158 @@ pub trait Strategy<E, A>: Send + Sync {
     async fn process_event(&mut self, event: E) -> Option<A>;
 }
 
+/// Strategy whose events can be processed concurrently, added to the engine with
+/// [add_concurrent_strategy](crate::engine::Engine::add_concurrent_strategy). Events are
+/// processed through a shared reference, so state updated while processing events
+/// needs interior mutability, unless the events updating it are
+/// [sequential](ConcurrentStrategy::is_sequential).
+#[async_trait]
+pub trait ConcurrentStrategy<E, A>: Send + Sync {
+    /// Sync the initial state of the strategy if needed, usually by fetching
+    /// onchain data. No event is processed while syncing.
+    async fn sync_state(&mut self) -> Result<()>;
+
+    /// Process an event, and return an action if needed.
+    async fn process_event(&self, event: E) -> Option<A>;
+
+    /// Whether `event` must be processed on its own: after every earlier event was
+    /// processed, and before any later one is, e.g. a new block the strategy updates
+    /// its state on. Defaults to false.
+    fn is_sequential(&self, _event: &E) -> bool {
+        false
+    }
+}
+
+/// Reconfigurable trait, implemented by strategies whose parameters can change while
+/// they run, e.g. when a [ConfigCollector](crate::collectors::config_collector::ConfigCollector)
+/// delivers a [ConfigUpdated](crate::collectors::config_collector::ConfigUpdated) event.
+pub trait Reconfigurable {
+    /// The parameters of the strategy.
+    type Config;
+
+    /// Replace the parameters of the strategy. An invalid config is rejected, and the
+    /// current parameters kept.
+    fn reconfigure(&mut self, config: Self::Config) -> Result<()>;
+}
+
 /// Executor trait, responsible for executing actions returned by strategies.
 #[async_trait]
 pub trait Executor<A>: Send + Sync {
-    /// Execute an action.
-    async fn execute(&self, action: A) -> Result<()>;
+    /// Execute an action, returning what was submitted for it.
+    async fn execute(&self, action: A) -> Result<SubmissionReceipt>;
+}
+
+/// What an [Executor](Executor) submitted for an action, e.g. the bundles sent to a
+/// relay and whether the relay accepted them. The [Engine](crate::engine::Engine) can
+/// route receipts back to strategies as events, see
+/// [with_receipts](crate::engine::Engine::with_receipts).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SubmissionReceipt {
+    pub submissions: Vec<Submission>,
+}
+
+impl SubmissionReceipt {
+    pub fn new(submissions: Vec<Submission>) -> Self {
+        Self { submissions }
+    }
+
+    /// Whether nothing was submitted, e.g. because the action was dropped.
+    pub fn is_empty(&self) -> bool {
+        self.submissions.is_empty()
+    }
+
+    /// Whether something was submitted, and every destination rejected it.
+    pub fn is_rejected(&self) -> bool {
+        !self.is_empty() && !self.submissions.iter().any(Submission::is_accepted)
+    }
+}
+
+/// A single bundle or transaction submitted by an [Executor](Executor).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Submission {
+    /// Where the submission was sent, e.g. the name of a relay.
+    pub destination: String,
+    /// Hash of the bundle acknowledged by the destination, if a bundle was submitted.
+    pub bundle_hash: Option<H256>,
+    /// Hashes of the transactions submitted, including the transactions a bundle
+    /// backruns.
+    pub tx_hashes: Vec<H256>,
+    /// First block the submission can be included in, if it targets one.
+    pub target_block: Option<U64>,
+    /// Error returned by the destination, if it rejected the submission.
+    pub error: Option<String>,
+    /// Block which must stay canonical for the submission to be valid, e.g. the block
+    /// its bundle was simulated on. The engine assumes the latest block when the
+    /// submission was made if unset, see
+    /// [with_reorg_invalidation](crate::engine::Engine::with_reorg_invalidation).
+    pub required_block: Option<BlockRef>,
+}
+
+impl Submission {
+    pub fn new(destination: impl Into<String>, tx_hashes: Vec<H256>) -> Self {
+        Self {
+            destination: destination.into(),
+            bundle_hash: None,
+            tx_hashes,
+            target_block: None,
+            error: None,
+            required_block: None,
+        }
+    }
+
+    pub fn with_bundle_hash(mut self, bundle_hash: Option<H256>) -> Self {
+        self.bundle_hash = bundle_hash;
+        self
+    }
+
+    pub fn with_target_block(mut self, target_block: U64) -> Self {
+        self.target_block = Some(target_block);
+        self
+    }
+
+    /// Only consider the submission valid while `block` stays canonical.
+    pub fn with_required_block(mut self, block: BlockRef) -> Self {
+        self.required_block = Some(block);
+        self
+    }
+
+    /// Record that the destination rejected the submission.
+    pub fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// When an action stops being worth executing, e.g. once the last block a bundle
+/// targets was built. The [Engine](crate::engine::Engine) drops expired actions before
+/// executing them, see [with_action_expiry](crate::engine::Engine::with_action_expiry).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// The action expires once this block was seen.
+    Block(U64),
+    /// The action expires at this unix timestamp, in seconds.
+    Timestamp(u64),
+}
+
+impl Expiry {
+    /// Whether the action expired, given the latest block seen, if any. Block expiries
+    /// never expire before a block was seen.
+    pub fn is_expired(&self, latest_block: Option<U64>) -> bool {
+        match self {
+            Self::Block(block) => latest_block.is_some_and(|latest| latest >= *block),
+            Self::Timestamp(timestamp) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                now >= *timestamp
+            }
+        }
+    }
 }
 
 /// CollectorMap is a wrapper around a [Collector](Collector) that maps outgoing
@@ -65,6 +232,47 @@ where
     }
 }
 
+/// RestartableCollectorMap is a wrapper around a [RestartableCollector](RestartableCollector)
+/// that maps outgoing events to a different type, like a [CollectorMap](CollectorMap),
+/// and stays restartable.
+pub struct RestartableCollectorMap<E, F> {
+    collector: Box<dyn RestartableCollector<E>>,
+    f: F,
+}
+
+impl<E, F> RestartableCollectorMap<E, F> {
+    pub fn new(collector: Box<dyn RestartableCollector<E>>, f: F) -> Self {
+        Self { collector, f }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, F> Collector<E2> for RestartableCollectorMap<E1, F>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    F: Fn(E1) -> E2 + Send + Sync + Clone + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E2>> {
+        let stream = self.collector.get_event_stream().await?;
+        let f = self.f.clone();
+        let stream = stream.map(f);
+        Ok(Box::pin(stream))
+    }
+}
+
+#[async_trait]
+impl<E1, E2, F> RestartableCollector<E2> for RestartableCollectorMap<E1, F>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    F: Fn(E1) -> E2 + Send + Sync + Clone + 'static,
+{
+    async fn restart(&self) -> Result<()> {
+        self.collector.restart().await
+    }
+}
+
 /// ExecutorMap is a wrapper around an [Executor](Executor) that maps incoming
 /// actions to a different type.
 pub struct ExecutorMap<A, F> {
@@ -85,12 +293,300 @@ where
     A2: Send + Sync + 'static,
     F: Fn(A1) -> Option<A2> + Send + Sync + Clone + 'static,
 {
-    async fn execute(&self, action: A1) -> Result<()> {
+    async fn execute(&self, action: A1) -> Result<SubmissionReceipt> {
         let action = (self.f)(action);
         match action {
             Some(action) => self.executor.execute(action).await,
-            None => Ok(()),
+            None => Ok(SubmissionReceipt::default()),
+        }
+    }
+}
+
+/// TryCollectorMap is a wrapper around a [Collector](Collector) that maps outgoing
+/// events to a different type with an async, fallible mapper, e.g. to enrich events
+/// with an RPC lookup. Events the mapper fails on are dropped, logging why.
+pub struct TryCollectorMap<E, F> {
+    collector: Box<dyn Collector<E>>,
+    f: F,
+}
+
+impl<E, F> TryCollectorMap<E, F> {
+    pub fn new(collector: Box<dyn Collector<E>>, f: F) -> Self {
+        Self { collector, f }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, F, Fut> Collector<E2> for TryCollectorMap<E1, F>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    F: Fn(E1) -> Fut + Send + Sync + Clone + 'static,
+    Fut: Future<Output = Result<E2>> + Send + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E2>> {
+        let stream = self.collector.get_event_stream().await?;
+        let f = self.f.clone();
+        let stream = futures::StreamExt::filter_map(stream, move |event| {
+            let mapped = f(event);
+            async move {
+                mapped
+                    .await
+                    .map_err(|e| warn!("dropping event which failed to map: {:#}", e))
+                    .ok()
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// AsyncExecutorMap is a wrapper around an [Executor](Executor) that maps incoming
+/// actions to a different type with an async, fallible mapper, e.g. to fill in the gas
+/// price of an action. Actions the mapper fails on, e.g. malformed ones, are dropped,
+/// logging why.
+pub struct AsyncExecutorMap<A, F> {
+    executor: Box<dyn Executor<A>>,
+    f: F,
+}
+
+impl<A, F> AsyncExecutorMap<A, F> {
+    pub fn new(executor: Box<dyn Executor<A>>, f: F) -> Self {
+        Self { executor, f }
+    }
+}
+
+#[async_trait]
+impl<A1, A2, F, Fut> Executor<A1> for AsyncExecutorMap<A2, F>
+where
+    A1: Send + Sync + 'static,
+    A2: Send + Sync + 'static,
+    F: Fn(A1) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<A2>> + Send,
+{
+    async fn execute(&self, action: A1) -> Result<SubmissionReceipt> {
+        match (self.f)(action).await {
+            Ok(action) => self.executor.execute(action).await,
+            Err(e) => {
+                warn!("dropping action which failed to map: {:#}", e);
+                Ok(SubmissionReceipt::default())
+            }
+        }
+    }
+}
+
+/// StrategyMap is a wrapper around a [Strategy](Strategy) that maps incoming events
+/// from, and outgoing actions to, different types, e.g. to run a strategy with its own
+/// event and action types in an engine shared with other strategies. Events the event
+/// mapper returns `None` for aren't passed to the strategy.
+pub struct StrategyMap<E, A, F, G> {
+    strategy: Box<dyn Strategy<E, A>>,
+    event_f: F,
+    action_f: G,
+}
+
+impl<E, A, F, G> StrategyMap<E, A, F, G> {
+    pub fn new(strategy: Box<dyn Strategy<E, A>>, event_f: F, action_f: G) -> Self {
+        Self {
+            strategy,
+            event_f,
+            action_f,
+        }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, A1, A2, F, G> Strategy<E1, A1> for StrategyMap<E2, A2, F, G>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    A1: Send + Sync + 'static,
+    A2: Send + Sync + 'static,
+    F: Fn(E1) -> Option<E2> + Send + Sync,
+    G: Fn(A2) -> A1 + Send + Sync,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E1) -> Option<A1> {
+        let event = (self.event_f)(event)?;
+        self.strategy.process_event(event).await.map(&self.action_f)
+    }
+}
+
+/// FilterStrategy is a wrapper around a [Strategy](Strategy) which only passes it the
+/// events matching a predicate, e.g. hints touching pools the strategy trades.
+pub struct FilterStrategy<E, A, F> {
+    strategy: Box<dyn Strategy<E, A>>,
+    predicate: F,
+}
+
+impl<E, A, F> FilterStrategy<E, A, F> {
+    pub fn new(strategy: Box<dyn Strategy<E, A>>, predicate: F) -> Self {
+        Self {
+            strategy,
+            predicate,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, A, F> Strategy<E, A> for FilterStrategy<E, A, F>
+where
+    E: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+    F: Fn(&E) -> bool + Send + Sync,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Option<A> {
+        if !(self.predicate)(&event) {
+            return None;
+        }
+        self.strategy.process_event(event).await
+    }
+}
+
+/// ChainStrategy pipes the actions of a first [Strategy](Strategy) as events into a
+/// second one, e.g. a strategy detecting opportunities into a strategy sizing and
+/// bidding for them. Events the first strategy emits no action for stop there.
+pub struct ChainStrategy<E, M, A> {
+    first: Box<dyn Strategy<E, M>>,
+    second: Box<dyn Strategy<M, A>>,
+}
+
+impl<E, M, A> ChainStrategy<E, M, A> {
+    pub fn new(first: Box<dyn Strategy<E, M>>, second: Box<dyn Strategy<M, A>>) -> Self {
+        Self { first, second }
+    }
+}
+
+#[async_trait]
+impl<E, M, A> Strategy<E, A> for ChainStrategy<E, M, A>
+where
+    E: Send + Sync + 'static,
+    M: Send + Sync + 'static,
+    A: Send + Sync + 'static,
+{
+    /// Sync the first strategy, then the second.
+    async fn sync_state(&mut self) -> Result<()> {
+        self.first.sync_state().await?;
+        self.second.sync_state().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Option<A> {
+        let intermediate = self.first.process_event(event).await?;
+        self.second.process_event(intermediate).await
+    }
+}
+
+/// RaceStrategy passes every event to several [strategies](Strategy) concurrently, and
+/// emits the action of the first to respond with one, e.g. to race a fast heuristic
+/// against an exhaustive search. Strategies still processing the event when one
+/// responds are cancelled, so racing strategies must tolerate being interrupted.
+pub struct RaceStrategy<E, A> {
+    strategies: Vec<Box<dyn Strategy<E, A>>>,
+}
+
+impl<E, A> RaceStrategy<E, A> {
+    pub fn new(strategies: Vec<Box<dyn Strategy<E, A>>>) -> Self {
+        Self { strategies }
+    }
+}
+
+#[async_trait]
+impl<E, A> Strategy<E, A> for RaceStrategy<E, A>
+where
+    E: Clone + Send + Sync + 'static,
+    A: Send + Sync + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        for strategy in &mut self.strategies {
+            strategy.sync_state().await?;
+        }
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: E) -> Option<A> {
+        let mut responses: FuturesUnordered<_> = self
+            .strategies
+            .iter_mut()
+            .map(|strategy| strategy.process_event(event.clone()))
+            .collect();
+        while let Some(response) = responses.next().await {
+            if response.is_some() {
+                return response;
+            }
         }
+        None
+    }
+}
+
+/// An event or action tagged with the chain it belongs to, so a single engine can run
+/// collectors and executors connected to different chains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTagged<T> {
+    pub chain: Chain,
+    pub inner: T,
+}
+
+impl<T> ChainTagged<T> {
+    pub fn new(chain: Chain, inner: T) -> Self {
+        Self { chain, inner }
+    }
+}
+
+/// ChainCollector is a wrapper around a [Collector](Collector) connected to a single
+/// chain, which tags outgoing events with that chain.
+pub struct ChainCollector<E> {
+    chain: Chain,
+    collector: Box<dyn Collector<E>>,
+}
+
+impl<E> ChainCollector<E> {
+    pub fn new(chain: Chain, collector: Box<dyn Collector<E>>) -> Self {
+        Self { chain, collector }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<ChainTagged<E>> for ChainCollector<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, ChainTagged<E>>> {
+        let stream = self.collector.get_event_stream().await?;
+        let chain = self.chain;
+        let stream = stream.map(move |event| ChainTagged::new(chain, event));
+        Ok(Box::pin(stream))
+    }
+}
+
+/// ChainExecutor is a wrapper around an [Executor](Executor) connected to a single
+/// chain, which only executes actions tagged with that chain.
+pub struct ChainExecutor<A> {
+    chain: Chain,
+    executor: Box<dyn Executor<A>>,
+}
+
+impl<A> ChainExecutor<A> {
+    pub fn new(chain: Chain, executor: Box<dyn Executor<A>>) -> Self {
+        Self { chain, executor }
+    }
+}
+
+#[async_trait]
+impl<A> Executor<ChainTagged<A>> for ChainExecutor<A>
+where
+    A: Send + Sync + 'static,
+{
+    async fn execute(&self, action: ChainTagged<A>) -> Result<SubmissionReceipt> {
+        if action.chain != self.chain {
+            return Ok(SubmissionReceipt::default());
+        }
+        self.executor.execute(action.inner).await
     }
 }
 
@@ -99,13 +595,10 @@ pub enum Events {
     NewBlock(NewBlock),
     Transaction(Transaction),
     OpenseaOrder(Box<OpenseaOrder>),
-
 }
 
 /// Convenience enum containing all the actions that can be executed by executors.
 pub enum Actions {
     FlashbotsBundle(FlashbotsBundle),
     SubmitTxToMempool(SubmitTxToMempool),
-
-
 }
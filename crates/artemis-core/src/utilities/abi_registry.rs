@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use ethers::{
+    types::{Address, H256, I256, U256},
+    utils::keccak256,
+};
+use matchmaker::types::{Hint, HintLog};
+
+/// Protocol of a decoded event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    UniswapV2,
+    UniswapV3,
+    Curve,
+    Balancer,
+}
+
+/// Which way a swap traded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// Token0 of the pool was sold for token1.
+    ZeroForOne,
+    /// Token1 of the pool was sold for token0.
+    OneForZero,
+    /// Coin `sold` of a Curve pool was sold for coin `bought`, by index.
+    Coins { sold: u64, bought: u64 },
+    /// `token_in` was sold for `token_out`.
+    Tokens {
+        token_in: Address,
+        token_out: Address,
+    },
+}
+
+/// A swap decoded from a log of a MEV-Share hint. The matchmaker usually strips log
+/// data, so the direction and amounts are only known when the hint discloses them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedSwap {
+    /// Index of the log in the hint.
+    pub log_index: usize,
+    pub protocol: Protocol,
+    /// Pool which traded. For Balancer, the pool of the swap rather than the vault
+    /// which emitted the log.
+    pub pool: Address,
+    pub direction: Option<SwapDirection>,
+    /// Amount of the sold token the pool received.
+    pub amount_in: Option<U256>,
+    /// Amount of the bought token the pool paid out.
+    pub amount_out: Option<U256>,
+}
+
+/// A MEV-Share hint, along with the swaps decoded from its logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedHint {
+    pub hint: Hint,
+    /// Swaps of the hint, in log order. Logs of unknown events are skipped.
+    pub swaps: Vec<DecodedSwap>,
+}
+
+/// Decodes a log whose topic matched, given the index of the log in its hint.
+pub type LogDecoder = fn(usize, &HintLog) -> Option<DecodedSwap>;
+
+/// An event known to an [AbiRegistry].
+#[derive(Debug, Clone)]
+pub struct EventAbi {
+    /// Signature of the event, e.g. `Sync(uint112,uint112)`.
+    pub signature: &'static str,
+    pub protocol: Protocol,
+    decoder: LogDecoder,
+}
+
+/// Registry of the swap events of known protocols, by topic, decoding the logs of
+/// MEV-Share hints so strategies don't each match log topics. The default registry
+/// knows the swap events of Uniswap V2 and V3 pools, Curve pools and the Balancer
+/// vault.
+#[derive(Debug, Clone)]
+pub struct AbiRegistry {
+    events: HashMap<H256, EventAbi>,
+}
+
+impl AbiRegistry {
+    /// Create a registry knowing no events.
+    pub fn empty() -> Self {
+        Self {
+            events: HashMap::new(),
+        }
+    }
+
+    /// Decode the logs of the event `signature` with `decoder`, replacing the decoder
+    /// of the event if it was known.
+    pub fn register(&mut self, signature: &'static str, protocol: Protocol, decoder: LogDecoder) {
+        let event = EventAbi {
+            signature,
+            protocol,
+            decoder,
+        };
+        self.events.insert(topic(signature), event);
+    }
+
+    /// Returns the event whose signature hashes to `topic`, if it is known.
+    pub fn event(&self, topic: &H256) -> Option<&EventAbi> {
+        self.events.get(topic)
+    }
+
+    /// Decode the log at `log_index` of a hint, if it is a known event.
+    pub fn decode_log(&self, log_index: usize, log: &HintLog) -> Option<DecodedSwap> {
+        let event = self.event(log.topics.first()?)?;
+        (event.decoder)(log_index, log)
+    }
+
+    /// Decode the swaps of `hint`.
+    pub fn decode(&self, hint: Hint) -> DecodedHint {
+        let swaps = hint
+            .logs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, log)| self.decode_log(index, log))
+            .collect();
+        DecodedHint { hint, swaps }
+    }
+}
+
+impl Default for AbiRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+            Protocol::UniswapV2,
+            decode_v2_swap,
+        );
+        registry.register(
+            "Swap(address,address,int256,int256,uint160,uint128,int24)",
+            Protocol::UniswapV3,
+            decode_v3_swap,
+        );
+        // Stableswap pools index coins with int128, cryptoswap pools with uint256.
+        for signature in [
+            "TokenExchange(address,int128,uint256,int128,uint256)",
+            "TokenExchangeUnderlying(address,int128,uint256,int128,uint256)",
+            "TokenExchange(address,uint256,uint256,uint256,uint256)",
+        ] {
+            registry.register(signature, Protocol::Curve, decode_curve_exchange);
+        }
+        registry.register(
+            "Swap(bytes32,address,address,uint256,uint256)",
+            Protocol::Balancer,
+            decode_balancer_swap,
+        );
+        registry
+    }
+}
+
+fn topic(signature: &str) -> H256 {
+    H256::from(keccak256(signature))
+}
+
+/// Returns the `i`th word of the data of `log`, if it was disclosed.
+fn word(log: &HintLog, i: usize) -> Option<U256> {
+    log.data
+        .get(i * 32..(i + 1) * 32)
+        .map(U256::from_big_endian)
+}
+
+fn swap(log_index: usize, protocol: Protocol, pool: Address) -> DecodedSwap {
+    DecodedSwap {
+        log_index,
+        protocol,
+        pool,
+        direction: None,
+        amount_in: None,
+        amount_out: None,
+    }
+}
+
+/// `Swap(address indexed sender, uint amount0In, uint amount1In, uint amount0Out,
+/// uint amount1Out, address indexed to)`
+fn decode_v2_swap(log_index: usize, log: &HintLog) -> Option<DecodedSwap> {
+    let mut decoded = swap(log_index, Protocol::UniswapV2, log.address);
+    if let (Some(amount0_in), Some(amount1_in), Some(amount0_out), Some(amount1_out)) =
+        (word(log, 0), word(log, 1), word(log, 2), word(log, 3))
+    {
+        let (direction, amount_in, amount_out) = match amount0_in.is_zero() {
+            false => (SwapDirection::ZeroForOne, amount0_in, amount1_out),
+            true => (SwapDirection::OneForZero, amount1_in, amount0_out),
+        };
+        decoded.direction = Some(direction);
+        decoded.amount_in = Some(amount_in);
+        decoded.amount_out = Some(amount_out);
+    }
+    Some(decoded)
+}
+
+/// `Swap(address indexed sender, address indexed recipient, int256 amount0, int256
+/// amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick)`, whose amounts are
+/// the balance changes of the pool.
+fn decode_v3_swap(log_index: usize, log: &HintLog) -> Option<DecodedSwap> {
+    let mut decoded = swap(log_index, Protocol::UniswapV3, log.address);
+    if let (Some(amount0), Some(amount1)) = (word(log, 0), word(log, 1)) {
+        let (amount0, amount1) = (I256::from_raw(amount0), I256::from_raw(amount1));
+        let (direction, amount_in, amount_out) = match amount0.is_positive() {
+            true => (SwapDirection::ZeroForOne, amount0, amount1),
+            false => (SwapDirection::OneForZero, amount1, amount0),
+        };
+        decoded.direction = Some(direction);
+        decoded.amount_in = Some(amount_in.unsigned_abs());
+        decoded.amount_out = Some(amount_out.unsigned_abs());
+    }
+    Some(decoded)
+}
+
+/// `TokenExchange(address indexed buyer, int128 sold_id, uint256 tokens_sold, int128
+/// bought_id, uint256 tokens_bought)`, and its variants.
+fn decode_curve_exchange(log_index: usize, log: &HintLog) -> Option<DecodedSwap> {
+    let mut decoded = swap(log_index, Protocol::Curve, log.address);
+    if let (Some(sold), Some(amount_in), Some(bought), Some(amount_out)) =
+        (word(log, 0), word(log, 1), word(log, 2), word(log, 3))
+    {
+        decoded.direction = Some(SwapDirection::Coins {
+            sold: sold.low_u64(),
+            bought: bought.low_u64(),
+        });
+        decoded.amount_in = Some(amount_in);
+        decoded.amount_out = Some(amount_out);
+    }
+    Some(decoded)
+}
+
+/// `Swap(bytes32 indexed poolId, address indexed tokenIn, address indexed tokenOut,
+/// uint256 amountIn, uint256 amountOut)`, emitted by the vault. The pool id starts with
+/// the address of the pool.
+fn decode_balancer_swap(log_index: usize, log: &HintLog) -> Option<DecodedSwap> {
+    let pool_id = log.topics.get(1)?;
+    let mut decoded = swap(
+        log_index,
+        Protocol::Balancer,
+        Address::from_slice(&pool_id[..20]),
+    );
+    if let (Some(token_in), Some(token_out)) = (log.topics.get(2), log.topics.get(3)) {
+        decoded.direction = Some(SwapDirection::Tokens {
+            token_in: Address::from(*token_in),
+            token_out: Address::from(*token_out),
+        });
+    }
+    decoded.amount_in = word(log, 0);
+    decoded.amount_out = word(log, 1);
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+
+    fn log(signature: &str, topics: Vec<H256>, words: &[U256]) -> HintLog {
+        let mut data = vec![0; words.len() * 32];
+        for (i, word) in words.iter().enumerate() {
+            word.to_big_endian(&mut data[i * 32..(i + 1) * 32]);
+        }
+        HintLog {
+            address: Address::from_low_u64_be(1),
+            topics: [vec![topic(signature)], topics].concat(),
+            data: Bytes::from(data),
+        }
+    }
+
+    #[test]
+    fn decodes_swaps_of_known_protocols() {
+        let registry = AbiRegistry::default();
+        let amount = U256::from(1_000);
+
+        // Uniswap v3 amounts are the balance changes of the pool.
+        let v3 = log(
+            "Swap(address,address,int256,int256,uint160,uint128,int24)",
+            vec![],
+            &[
+                I256::from(-500).into_raw(),
+                amount,
+                U256::one(),
+                U256::one(),
+                U256::zero(),
+            ],
+        );
+        let swap = registry.decode_log(0, &v3).unwrap();
+        assert_eq!(swap.protocol, Protocol::UniswapV3);
+        assert_eq!(swap.direction, Some(SwapDirection::OneForZero));
+        assert_eq!(swap.amount_in, Some(amount));
+        assert_eq!(swap.amount_out, Some(U256::from(500)));
+
+        let curve = log(
+            "TokenExchange(address,int128,uint256,int128,uint256)",
+            vec![],
+            &[U256::from(2), amount, U256::zero(), amount],
+        );
+        let swap = registry.decode_log(0, &curve).unwrap();
+        assert_eq!(swap.protocol, Protocol::Curve);
+        assert_eq!(
+            swap.direction,
+            Some(SwapDirection::Coins { sold: 2, bought: 0 })
+        );
+
+        // Balancer swaps disclose the pool and tokens in topics, even without data.
+        let pool = Address::from_low_u64_be(7);
+        let (token_in, token_out) = (Address::from_low_u64_be(8), Address::from_low_u64_be(9));
+        let mut pool_id = H256::zero();
+        pool_id[..20].copy_from_slice(pool.as_bytes());
+        let balancer = log(
+            "Swap(bytes32,address,address,uint256,uint256)",
+            vec![pool_id, H256::from(token_in), H256::from(token_out)],
+            &[],
+        );
+        let swap = registry.decode_log(0, &balancer).unwrap();
+        assert_eq!(swap.pool, pool);
+        assert_eq!(
+            swap.direction,
+            Some(SwapDirection::Tokens {
+                token_in,
+                token_out
+            })
+        );
+        assert_eq!(swap.amount_in, None);
+    }
+
+    #[test]
+    fn decodes_hints_without_log_data() {
+        let v2 = log(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+            vec![],
+            &[],
+        );
+        let unknown = log("Sync(uint112,uint112)", vec![], &[]);
+        let hint = Hint {
+            hash: H256::zero(),
+            txs: vec![],
+            logs: vec![unknown, v2],
+            gas_used: None,
+            mev_gas_price: None,
+        };
+        let decoded = AbiRegistry::default().decode(hint);
+        assert_eq!(decoded.swaps.len(), 1);
+        let swap = &decoded.swaps[0];
+        assert_eq!(swap.log_index, 1);
+        assert_eq!(swap.protocol, Protocol::UniswapV2);
+        assert_eq!(swap.direction, None);
+    }
+}
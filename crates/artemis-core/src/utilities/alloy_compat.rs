@@ -0,0 +1,133 @@
+//! Conversions between ethers and alloy types, so collectors and executors built on
+//! alloy providers emit and consume the same events and actions as their ethers
+//! counterparts. Strategies don't need to know which library a component uses, and
+//! components can be migrated to alloy one at a time.
+
+use alloy::{
+    primitives::{Address, B256, U256 as AlloyU256},
+    rpc::types::TransactionRequest,
+};
+use anyhow::{Context, Result};
+use ethers::types::{transaction::eip2718::TypedTransaction, H160, H256, U256};
+
+/// Conversion of an alloy type into its ethers counterpart.
+pub trait ToEthers {
+    /// The ethers type.
+    type Ethers;
+
+    /// Convert into the ethers type.
+    fn to_ethers(self) -> Self::Ethers;
+}
+
+/// Conversion of an ethers type into its alloy counterpart.
+pub trait ToAlloy {
+    /// The alloy type.
+    type Alloy;
+
+    /// Convert into the alloy type.
+    fn to_alloy(self) -> Self::Alloy;
+}
+
+impl ToEthers for B256 {
+    type Ethers = H256;
+
+    fn to_ethers(self) -> H256 {
+        H256(self.0)
+    }
+}
+
+impl ToAlloy for H256 {
+    type Alloy = B256;
+
+    fn to_alloy(self) -> B256 {
+        B256::new(self.0)
+    }
+}
+
+impl ToEthers for Address {
+    type Ethers = H160;
+
+    fn to_ethers(self) -> H160 {
+        H160(self.into_array())
+    }
+}
+
+impl ToAlloy for H160 {
+    type Alloy = Address;
+
+    fn to_alloy(self) -> Address {
+        Address::new(self.0)
+    }
+}
+
+impl ToEthers for AlloyU256 {
+    type Ethers = U256;
+
+    fn to_ethers(self) -> U256 {
+        // Both store little endian 64 bit limbs.
+        U256(self.into_limbs())
+    }
+}
+
+impl ToAlloy for U256 {
+    type Alloy = AlloyU256;
+
+    fn to_alloy(self) -> AlloyU256 {
+        AlloyU256::from_limbs(self.0)
+    }
+}
+
+/// Convert an ethers transaction into an alloy transaction request. Both serialize to
+/// the JSON-RPC representation of transactions, so the conversion goes through it.
+/// Fails if the recipient is an ENS name, which alloy requests can't hold.
+pub fn to_alloy_request(tx: &TypedTransaction) -> Result<TransactionRequest> {
+    let json = serde_json::to_value(tx)?;
+    serde_json::from_value(json).context("Error converting transaction to an alloy request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest as EthersRequest};
+
+    #[test]
+    fn converts_primitives() {
+        let hash = H256::random();
+        assert_eq!(hash.to_alloy().to_ethers(), hash);
+
+        let address = H160::random();
+        assert_eq!(address.to_alloy().to_ethers(), address);
+
+        let value = U256::MAX - U256::from(u64::MAX) * 3;
+        assert_eq!(value.to_alloy().to_ethers(), value);
+        assert_eq!(U256::from(7).to_alloy(), AlloyU256::from(7));
+    }
+
+    #[test]
+    fn converts_transactions_to_requests() {
+        let to = H160::random();
+        let legacy: TypedTransaction = EthersRequest::new()
+            .to(to)
+            .value(100)
+            .gas_price(5)
+            .data(vec![1, 2, 3])
+            .into();
+        let request = to_alloy_request(&legacy).unwrap();
+        assert_eq!(request.to, Some(to.to_alloy().into()));
+        assert_eq!(request.value, Some(AlloyU256::from(100)));
+        assert_eq!(request.gas_price, Some(5));
+        assert_eq!(request.input.input().unwrap().as_ref(), &[1, 2, 3]);
+
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(to)
+            .max_fee_per_gas(10)
+            .max_priority_fee_per_gas(2)
+            .chain_id(1)
+            .into();
+        let request = to_alloy_request(&eip1559).unwrap();
+        assert_eq!(request.transaction_type, Some(2));
+        assert_eq!(request.max_fee_per_gas, Some(10));
+        assert_eq!(request.max_priority_fee_per_gas, Some(2));
+        assert_eq!(request.chain_id, Some(1));
+    }
+}
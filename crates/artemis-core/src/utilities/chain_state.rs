@@ -0,0 +1,188 @@
+use std::{
+    hint,
+    sync::atomic::{fence, AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ethers::types::{Block, U256, U64};
+
+/// Fee stored until it is known, or for blocks without a base fee, i.e. before London.
+const UNKNOWN_FEE: u64 = u64::MAX;
+
+/// The latest block, as seen by a [BlockCollector](crate::collectors::block_collector::BlockCollector).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainHead {
+    pub number: U64,
+    /// Unix timestamp of the block, in seconds.
+    pub timestamp: u64,
+    /// Base fee of the block, if it has one.
+    pub base_fee: Option<U256>,
+}
+
+impl ChainHead {
+    /// Returns the head of `block`, or `None` if it is pending.
+    pub fn of<T>(block: &Block<T>) -> Option<Self> {
+        Some(Self {
+            number: block.number?,
+            timestamp: block.timestamp.low_u64(),
+            base_fee: block.base_fee_per_gas,
+        })
+    }
+
+    /// Time elapsed since the block was produced.
+    pub fn age(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Duration::from_secs(now.saturating_sub(self.timestamp))
+    }
+}
+
+/// The latest block number, base fee, and suggested priority fee, updated by the block
+/// collector as blocks arrive, so strategies can read them without an RPC call per
+/// event. Reads are lock-free: the head is published behind a sequence number, and
+/// readers retry the rare read racing an update.
+#[derive(Debug)]
+pub struct ChainState {
+    /// Odd while the head is being updated, incremented twice per update.
+    seq: AtomicU64,
+    number: AtomicU64,
+    timestamp: AtomicU64,
+    base_fee: AtomicU64,
+    /// Suggested priority fee, in wei.
+    priority_fee: AtomicU64,
+}
+
+impl ChainState {
+    pub fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            number: AtomicU64::new(0),
+            timestamp: AtomicU64::new(0),
+            base_fee: AtomicU64::new(UNKNOWN_FEE),
+            priority_fee: AtomicU64::new(UNKNOWN_FEE),
+        }
+    }
+
+    /// Publish `head`, unless a more recent block was already published.
+    pub fn update_head(&self, head: ChainHead) {
+        // Take the sequence number, in case several collectors update the state.
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match self
+                .seq
+                .compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => seq = current,
+            }
+        }
+        fence(Ordering::Release);
+        if head.number.as_u64() > self.number.load(Ordering::Relaxed) {
+            let base_fee = head.base_fee.map_or(UNKNOWN_FEE, |fee| {
+                fee.min(U256::from(UNKNOWN_FEE - 1)).as_u64()
+            });
+            self.number.store(head.number.as_u64(), Ordering::Relaxed);
+            self.timestamp.store(head.timestamp, Ordering::Relaxed);
+            self.base_fee.store(base_fee, Ordering::Relaxed);
+        }
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+
+    /// Publish the suggested priority fee, in wei.
+    pub fn update_priority_fee(&self, priority_fee: U256) {
+        let priority_fee = priority_fee.min(U256::from(UNKNOWN_FEE - 1)).as_u64();
+        self.priority_fee.store(priority_fee, Ordering::Relaxed);
+    }
+
+    /// Returns the latest block, or `None` until one is published.
+    pub fn head(&self) -> Option<ChainHead> {
+        loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq % 2 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            let number = self.number.load(Ordering::Relaxed);
+            let timestamp = self.timestamp.load(Ordering::Relaxed);
+            let base_fee = self.base_fee.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) != seq {
+                continue;
+            }
+            if number == 0 {
+                return None;
+            }
+            return Some(ChainHead {
+                number: number.into(),
+                timestamp,
+                base_fee: (base_fee != UNKNOWN_FEE).then_some(base_fee.into()),
+            });
+        }
+    }
+
+    /// Returns the latest block if it was produced at most `max_age` ago. An older head
+    /// suggests the block subscription stalled.
+    pub fn fresh_head(&self, max_age: Duration) -> Option<ChainHead> {
+        self.head().filter(|head| head.age() <= max_age)
+    }
+
+    /// Returns the suggested priority fee, or `None` until it is known.
+    pub fn priority_fee(&self) -> Option<U256> {
+        match self.priority_fee.load(Ordering::Relaxed) {
+            UNKNOWN_FEE => None,
+            fee => Some(fee.into()),
+        }
+    }
+
+    /// Returns the gas price a legacy tx should pay to be included in the next block:
+    /// the latest base fee plus the suggested priority fee. `None` until both are known.
+    pub fn gas_price(&self) -> Option<U256> {
+        let base_fee = self.head()?.base_fee?;
+        Some(base_fee + self.priority_fee()?)
+    }
+}
+
+impl Default for ChainState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(number: u64, base_fee: u64) -> ChainHead {
+        ChainHead {
+            number: number.into(),
+            timestamp: 1_700_000_000 + number * 12,
+            base_fee: Some(base_fee.into()),
+        }
+    }
+
+    #[test]
+    fn keeps_the_latest_head() {
+        let state = ChainState::new();
+        assert_eq!(state.head(), None);
+        assert_eq!(state.gas_price(), None);
+
+        state.update_head(head(10, 30));
+        state.update_head(head(12, 40));
+        // A collector lagging behind doesn't roll the head back.
+        state.update_head(head(11, 50));
+        assert_eq!(state.head(), Some(head(12, 40)));
+        assert_eq!(state.gas_price(), None);
+
+        state.update_priority_fee(U256::from(2));
+        assert_eq!(state.gas_price(), Some(U256::from(42)));
+        // The head is long past.
+        assert_eq!(state.fresh_head(Duration::from_secs(24)), None);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+#[cfg(feature = "redis")]
+use crate::error::ArtemisError;
+use crate::error::Result;
+
+/// Coordinates the instances of a bot running side by side, e.g. in several regions,
+/// so they don't process the same events, or compete with each other for the same
+/// opportunities. Instances claim keys, e.g. event or opportunity ids, and only the
+/// first instance to claim a key acts on it. Failures to reach the shared store are
+/// [Network](crate::error::ArtemisError::Network) errors.
+#[async_trait]
+pub trait Coordinator: Debug + Send + Sync {
+    /// Claim `key` for `ttl`, returning whether this call claimed it. Returns false if
+    /// the key is already claimed, by this instance or any other.
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<bool>;
+
+    /// Release the claim of this instance on `key`, e.g. because acting on it failed,
+    /// so it can be claimed again. Claims of other instances are left as they are.
+    async fn release(&self, key: &str) -> Result<()>;
+}
+
+/// A coordinator for a single instance, keeping claims in memory.
+#[derive(Debug, Default)]
+pub struct LocalCoordinator {
+    /// Expiry of each claim.
+    claims: Mutex<HashMap<String, Instant>>,
+}
+
+impl LocalCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Coordinator for LocalCoordinator {
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let now = Instant::now();
+        let mut claims = self.claims.lock().unwrap();
+        claims.retain(|_, expires_at| *expires_at > now);
+        if claims.contains_key(key) {
+            return Ok(false);
+        }
+        claims.insert(key.to_string(), now + ttl);
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        self.claims.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Deletes a claim only if it is held by the instance releasing it.
+#[cfg(feature = "redis")]
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// A coordinator sharing claims between instances through Redis. Claims are keys set
+/// only if absent, holding the id of the instance which claimed them, and expiring
+/// after their ttl, so claims of instances which died are eventually released.
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisCoordinator {
+    connection: redis::aio::ConnectionManager,
+    instance: String,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCoordinator {
+    /// Connect to the Redis server at `url`, e.g. `redis://10.0.0.1:6379`, claiming keys
+    /// as `instance`, a name unique to this instance such as its region and host.
+    pub async fn connect(url: &str, instance: impl Into<String>) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        Ok(Self {
+            connection: redis::aio::ConnectionManager::new(client)
+                .await
+                .map_err(redis_error)?,
+            instance: instance.into(),
+            prefix: String::from("artemis"),
+        })
+    }
+
+    /// Namespace keys with `prefix`, so several bots can share a server. Defaults to
+    /// `artemis`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:claim:{}", self.prefix, key)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl Debug for RedisCoordinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCoordinator")
+            .field("instance", &self.instance)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl Coordinator for RedisCoordinator {
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(self.key(key))
+            .arg(&self.instance)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(redis_error)?;
+        Ok(claimed.is_some())
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        redis::Script::new(RELEASE_SCRIPT)
+            .key(self.key(key))
+            .arg(&self.instance)
+            .invoke_async::<_, i64>(&mut self.connection.clone())
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+}
+
+/// Categorize a failed Redis command as the coordination store being unreachable.
+#[cfg(feature = "redis")]
+fn redis_error(e: redis::RedisError) -> ArtemisError {
+    ArtemisError::Network(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn first_claimant_wins_until_release_or_expiry() {
+        let coordinator = LocalCoordinator::new();
+        let ttl = Duration::from_millis(20);
+        assert!(coordinator.claim("hint-1", ttl).await.unwrap());
+        assert!(!coordinator.claim("hint-1", ttl).await.unwrap());
+        assert!(coordinator.claim("hint-2", ttl).await.unwrap());
+
+        coordinator.release("hint-1").await.unwrap();
+        assert!(coordinator.claim("hint-1", ttl).await.unwrap());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(coordinator.claim("hint-2", ttl).await.unwrap());
+    }
+}
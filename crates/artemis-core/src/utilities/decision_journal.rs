@@ -0,0 +1,256 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{LineWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Whether a strategy acted on an event, or why it didn't.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum DecisionOutcome {
+    /// The strategy produced actions for the event.
+    Acted,
+    /// The strategy produced no actions for the event.
+    Skipped { reason: String },
+}
+
+/// A record of what a strategy decided about an event, and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    /// Unix timestamp (in milliseconds) at which the decision was made.
+    pub timestamp_ms: u64,
+    /// Name of the strategy which made the decision.
+    pub strategy: String,
+    /// Identifier of the event decided on, e.g. a tx or hint hash.
+    pub subject: String,
+    #[serde(flatten)]
+    pub outcome: DecisionOutcome,
+    /// Strategy specific details, e.g. the pools matched, sizes computed and profit
+    /// expected.
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+impl Decision {
+    /// Record a decision made now.
+    pub fn new(
+        strategy: impl Into<String>,
+        subject: impl Into<String>,
+        outcome: DecisionOutcome,
+    ) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            timestamp_ms,
+            strategy: strategy.into(),
+            subject: subject.into(),
+            outcome,
+            details: serde_json::Value::Null,
+        }
+    }
+
+    /// Attach strategy specific details. Details which can't be serialized are left out.
+    pub fn with_details(mut self, details: &impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).unwrap_or_default();
+        self
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        matches!(self.outcome, DecisionOutcome::Skipped { .. })
+    }
+}
+
+/// Filters decisions read back from a [DecisionJournal](DecisionJournal). Unset
+/// fields match every decision.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecisionQuery {
+    pub strategy: Option<String>,
+    pub subject: Option<String>,
+    /// Only match skipped decisions.
+    pub skipped_only: bool,
+    /// Only match decisions made at or after this unix timestamp, in milliseconds.
+    pub since_ms: Option<u64>,
+    /// Only return the most recent `limit` matching decisions.
+    pub limit: Option<usize>,
+}
+
+impl DecisionQuery {
+    pub fn matches(&self, decision: &Decision) -> bool {
+        self.strategy
+            .iter()
+            .all(|strategy| *strategy == decision.strategy)
+            && self
+                .subject
+                .iter()
+                .all(|subject| *subject == decision.subject)
+            && (!self.skipped_only || decision.is_skipped())
+            && self
+                .since_ms
+                .iter()
+                .all(|since| decision.timestamp_ms >= *since)
+    }
+
+    /// Returns the decisions matching the query, oldest first.
+    fn apply(&self, decisions: impl Iterator<Item = Decision>) -> Vec<Decision> {
+        let mut matching: Vec<Decision> = decisions.filter(|d| self.matches(d)).collect();
+        if let Some(limit) = self.limit {
+            matching.drain(..matching.len().saturating_sub(limit));
+        }
+        matching
+    }
+}
+
+/// A store strategies record their decisions to, so operators can later find out
+/// why an opportunity was or wasn't acted on.
+pub trait DecisionJournal: Debug + Send + Sync {
+    /// Record a decision.
+    fn record(&self, decision: Decision) -> Result<()>;
+
+    /// Returns the recorded decisions matching `query`, oldest first.
+    fn query(&self, query: &DecisionQuery) -> Result<Vec<Decision>>;
+}
+
+/// A journal keeping decisions in memory, dropping the oldest once it holds
+/// `capacity` of them.
+#[derive(Debug)]
+pub struct MemoryJournal {
+    capacity: usize,
+    decisions: Mutex<VecDeque<Decision>>,
+}
+
+impl MemoryJournal {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            decisions: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl DecisionJournal for MemoryJournal {
+    fn record(&self, decision: Decision) -> Result<()> {
+        let mut decisions = self.decisions.lock().unwrap();
+        if decisions.len() == self.capacity {
+            decisions.pop_front();
+        }
+        decisions.push_back(decision);
+        Ok(())
+    }
+
+    fn query(&self, query: &DecisionQuery) -> Result<Vec<Decision>> {
+        let decisions = self.decisions.lock().unwrap();
+        Ok(query.apply(decisions.iter().cloned()))
+    }
+}
+
+/// A journal appending decisions to a file as newline-delimited JSON, one
+/// [Decision](Decision) per line, which survives restarts and can be inspected
+/// with standard tools.
+#[derive(Debug)]
+pub struct FileJournal {
+    path: PathBuf,
+    writer: Mutex<LineWriter<File>>,
+}
+
+impl FileJournal {
+    /// Open the journal at `path`, creating it if it doesn't exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Error opening journal {}", path.display()))?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(LineWriter::new(file)),
+        })
+    }
+}
+
+impl DecisionJournal for FileJournal {
+    fn record(&self, decision: Decision) -> Result<()> {
+        let line = serde_json::to_string(&decision)?;
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{}", line)
+            .with_context(|| format!("Error writing journal {}", self.path.display()))
+    }
+
+    /// Reads the whole journal. Lines which can't be parsed, e.g. a line cut short by
+    /// a crash, are skipped.
+    fn query(&self, query: &DecisionQuery) -> Result<Vec<Decision>> {
+        let contents = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Error reading journal {}", self.path.display()))?;
+        let decisions = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Decision>(line).ok());
+        Ok(query.apply(decisions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skipped(subject: &str, reason: &str) -> Decision {
+        Decision::new(
+            "test",
+            subject,
+            DecisionOutcome::Skipped {
+                reason: reason.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn memory_journal_filters_and_evicts() {
+        let journal = MemoryJournal::new(3);
+        journal.record(skipped("a", "no candidates")).unwrap();
+        journal
+            .record(Decision::new("test", "b", DecisionOutcome::Acted))
+            .unwrap();
+        journal.record(skipped("c", "no candidates")).unwrap();
+        journal.record(skipped("d", "unsafe token")).unwrap();
+
+        let all = journal.query(&DecisionQuery::default()).unwrap();
+        let subjects: Vec<&str> = all.iter().map(|d| d.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["b", "c", "d"]);
+
+        let query = DecisionQuery {
+            skipped_only: true,
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(journal.query(&query).unwrap()[0].subject, "d");
+    }
+
+    #[test]
+    fn file_journal_round_trips_decisions() {
+        let path =
+            std::env::temp_dir().join(format!("artemis-journal-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let journal = FileJournal::open(&path).unwrap();
+        let decision = skipped("0xabc", "no candidates").with_details(&vec![1, 2]);
+        journal.record(decision.clone()).unwrap();
+        journal
+            .record(Decision::new("other", "0xdef", DecisionOutcome::Acted))
+            .unwrap();
+
+        let query = DecisionQuery {
+            strategy: Some("test".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(journal.query(&query).unwrap(), vec![decision]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use ethers::types::H256;
+
+/// Default time a submission is remembered for, about one block.
+pub const DEFAULT_DEDUP_TTL: Duration = Duration::from_secs(12);
+
+/// A cache of recently seen hashes, used by executors to avoid submitting the same
+/// bundle more than once, e.g. when an opportunity is delivered twice.
+#[derive(Debug)]
+pub struct DedupCache {
+    ttl: Duration,
+    seen: Mutex<HashMap<H256, Instant>>,
+}
+
+impl DedupCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record `hash`, returning false if it was already seen within the ttl.
+    pub fn insert(&self, hash: H256) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, seen_at| now.duration_since(*seen_at) < self.ttl);
+        match seen.get(&hash) {
+            Some(_) => false,
+            None => {
+                seen.insert(hash, now);
+                true
+            }
+        }
+    }
+
+    /// Forget `hash`, e.g. because submitting it failed, so it can be submitted again.
+    pub fn remove(&self, hash: &H256) {
+        self.seen.lock().unwrap().remove(hash);
+    }
+}
+
+impl Default for DedupCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_duplicates_until_ttl_expires() {
+        let cache = DedupCache::new(Duration::from_millis(20));
+        let hash = H256::repeat_byte(1);
+        assert!(cache.insert(hash));
+        assert!(!cache.insert(hash));
+        assert!(cache.insert(H256::repeat_byte(2)));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.insert(hash));
+
+        cache.remove(&hash);
+        assert!(cache.insert(hash));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::{anyhow, Result};
+use ethers::providers::{Http, Ipc, Provider, Ws};
+use tracing::warn;
+
+/// Number of times the underlying WS transport retries a dropped connection
+/// before giving up on an endpoint.
+const DEFAULT_WS_RECONNECTS: usize = 5;
+
+/// A node endpoint, which is either a websocket url, an HTTP url, or the path to
+/// the IPC socket of a co-located node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Ws(String),
+    Http(String),
+    Ipc(PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        if url.starts_with("ws://") || url.starts_with("wss://") {
+            Ok(Endpoint::Ws(url.to_string()))
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(Endpoint::Http(url.to_string()))
+        } else if let Some(path) = url.strip_prefix("ipc://") {
+            Ok(Endpoint::Ipc(PathBuf::from(path)))
+        } else if url.ends_with(".ipc") {
+            Ok(Endpoint::Ipc(PathBuf::from(url)))
+        } else {
+            Err(anyhow!("unsupported endpoint scheme: {}", url))
+        }
+    }
+}
+
+/// Holds an ordered list of node endpoints, and hands out providers connected to
+/// the first healthy one. When an endpoint fails, [rotate](FailoverProvider::rotate)
+/// moves on to the next, wrapping around to the primary.
+#[derive(Debug)]
+pub struct FailoverProvider {
+    endpoints: Vec<Endpoint>,
+    /// Index of the endpoint to try first.
+    active: AtomicUsize,
+    /// Number of reconnects the WS transport attempts per endpoint.
+    ws_reconnects: usize,
+}
+
+impl FailoverProvider {
+    pub fn new(endpoints: Vec<Endpoint>) -> Self {
+        Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+            ws_reconnects: DEFAULT_WS_RECONNECTS,
+        }
+    }
+
+    pub fn with_ws_reconnects(mut self, ws_reconnects: usize) -> Self {
+        self.ws_reconnects = ws_reconnects;
+        self
+    }
+
+    /// Returns the endpoints in the order they should be tried, starting at the active one.
+    fn ordered_endpoints(&self) -> impl Iterator<Item = &Endpoint> {
+        let active = self.active.load(Ordering::Relaxed) % self.endpoints.len().max(1);
+        self.endpoints[active..]
+            .iter()
+            .chain(self.endpoints[..active].iter())
+    }
+
+    /// Mark the active endpoint as failed, so the next connection attempt starts
+    /// at the following endpoint.
+    pub fn rotate(&self) {
+        self.active.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Connect to the first reachable websocket endpoint.
+    pub async fn connect_ws(&self) -> Result<Provider<Ws>> {
+        for endpoint in self.ordered_endpoints() {
+            if let Endpoint::Ws(url) = endpoint {
+                match Ws::connect_with_reconnects(url.as_str(), self.ws_reconnects).await {
+                    Ok(ws) => return Ok(Provider::new(ws)),
+                    Err(e) => warn!("error connecting to {}: {}", url, e),
+                }
+            }
+        }
+        Err(anyhow!("no websocket endpoint reachable"))
+    }
+
+    /// Connect to the first reachable IPC endpoint.
+    pub async fn connect_ipc(&self) -> Result<Provider<Ipc>> {
+        for endpoint in self.ordered_endpoints() {
+            if let Endpoint::Ipc(path) = endpoint {
+                match Ipc::connect(path).await {
+                    Ok(ipc) => return Ok(Provider::new(ipc)),
+                    Err(e) => warn!("error connecting to {}: {}", path.display(), e),
+                }
+            }
+        }
+        Err(anyhow!("no ipc endpoint reachable"))
+    }
+
+    /// Returns a provider for the first HTTP endpoint. HTTP providers connect
+    /// lazily, so this doesn't check the endpoint is reachable.
+    pub fn http(&self) -> Result<Provider<Http>> {
+        self.ordered_endpoints()
+            .find_map(|endpoint| match endpoint {
+                Endpoint::Http(url) => Some(url),
+                Endpoint::Ws(_) | Endpoint::Ipc(_) => None,
+            })
+            .ok_or_else(|| anyhow!("no http endpoint configured"))
+            .and_then(|url| Ok(Provider::<Http>::try_from(url.as_str())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_endpoints_by_scheme() {
+        assert_eq!(
+            "wss://node".parse::<Endpoint>().unwrap(),
+            Endpoint::Ws("wss://node".to_string())
+        );
+        assert_eq!(
+            "https://node".parse::<Endpoint>().unwrap(),
+            Endpoint::Http("https://node".to_string())
+        );
+        assert_eq!(
+            "ipc:///tmp/geth.ipc".parse::<Endpoint>().unwrap(),
+            Endpoint::Ipc(PathBuf::from("/tmp/geth.ipc"))
+        );
+        assert_eq!(
+            "/data/reth.ipc".parse::<Endpoint>().unwrap(),
+            Endpoint::Ipc(PathBuf::from("/data/reth.ipc"))
+        );
+        assert!("ftp://node".parse::<Endpoint>().is_err());
+    }
+
+    #[test]
+    fn rotate_wraps_around() {
+        let provider = FailoverProvider::new(vec![
+            Endpoint::Http("http://a".to_string()),
+            Endpoint::Http("http://b".to_string()),
+        ]);
+        provider.rotate();
+        let order: Vec<_> = provider.ordered_endpoints().cloned().collect();
+        assert_eq!(order[0], Endpoint::Http("http://b".to_string()));
+        provider.rotate();
+        let order: Vec<_> = provider.ordered_endpoints().cloned().collect();
+        assert_eq!(order[0], Endpoint::Http("http://a".to_string()));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, Result};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// Largest request read. The health and admin servers, and the mock relay, only
+/// receive probes and small JSON-RPC calls.
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+/// A parsed HTTP/1.1 request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names and values, in the order they were sent.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Read a single request from `stream`, including a body of `Content-Length` bytes.
+pub(crate) async fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 1024];
+    let header_end = loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if data.len() >= MAX_REQUEST_SIZE {
+            return Err(anyhow!("request headers too large"));
+        }
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            return Err(anyhow!("connection closed before end of headers"));
+        }
+        data.extend_from_slice(&buf[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or_default();
+    if content_length > MAX_REQUEST_SIZE {
+        return Err(anyhow!("request body too large"));
+    }
+
+    let mut body = data.split_off(header_end);
+    while body.len() < content_length {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            return Err(anyhow!("connection closed before end of body"));
+        }
+        body.extend_from_slice(&buf[..read]);
+    }
+    body.truncate(content_length);
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Write a JSON response with the given status line, e.g. `200 OK`, and close the
+/// connection.
+pub(crate) async fn write_json_response(
+    stream: &mut TcpStream,
+    status: &str,
+    body: &str,
+) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -1,4 +1,42 @@
 //! Utilities for working with Artemis.
 
+/// This module implements conversions between ethers and alloy types.
+#[cfg(feature = "alloy")]
+pub mod alloy_compat;
+
+/// This module implements a registry of the swap events of known protocols, decoding
+/// the logs of MEV-Share hints.
+pub mod abi_registry;
+
+/// This module implements the latest block and fees, shared with strategies as blocks
+/// arrive.
+pub mod chain_state;
+
+/// This module implements the coordination of several instances of a bot, in memory
+/// or through Redis.
+pub mod coordination;
+
+/// This module implements a cache of recently submitted bundle hashes.
+pub mod dedup_cache;
+
+/// This module implements journals strategies record their decisions to.
+pub mod decision_journal;
+
+/// This module implements the minimal HTTP handling of the health and admin servers.
+pub(crate) mod http;
+
+/// This module implements a provider which fails over between node endpoints.
+pub mod failover_provider;
+
+/// This module implements the conversion of token amounts into ETH and USD.
+pub mod price_service;
+
+/// This module implements the classification of the router calls of MEV-Share hints,
+/// inferring the pools they likely swap through from their calldata.
+pub mod router_classifier;
+
+/// This module implements a block scoped cache of simulation outcomes.
+pub mod simulation_cache;
+
 /// This module implements state overriding middleware.
 pub mod state_override_middleware;
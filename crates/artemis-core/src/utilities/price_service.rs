@@ -0,0 +1,462 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail};
+use ethers::{
+    abi::{self, ParamType, Token},
+    prelude::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest},
+    types::{I256, U256},
+};
+use tracing::warn;
+
+/// Selector of the Chainlink aggregator `latestRoundData()` function.
+const LATEST_ROUND_DATA_SELECTOR: [u8; 4] = [0xfe, 0xaf, 0x96, 0x8c];
+
+/// Selector of the `decimals()` function of ERC20 tokens and Chainlink aggregators.
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// Selector of the Uniswap V3 pool `token0()` function.
+const TOKEN0_SELECTOR: [u8; 4] = [0x0d, 0xfe, 0x16, 0x81];
+
+/// Selector of the Uniswap V3 pool `observe(uint32[])` function.
+const OBSERVE_SELECTOR: [u8; 4] = [0x88, 0x3b, 0xdb, 0xfd];
+
+/// Wei in an ETH.
+const WEI_PER_ETH: f64 = 1e18;
+
+/// What a Chainlink feed prices a token in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quote {
+    /// The feed prices the token in ETH, e.g. `USDC / ETH`.
+    Eth,
+    /// The feed prices the token in USD, e.g. `LINK / USD`.
+    Usd,
+}
+
+/// A minimum profit, in a common unit whatever token the profit is made in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinProfit {
+    /// Minimum profit in wei.
+    Wei(U256),
+    /// Minimum profit in USD.
+    Usd(f64),
+}
+
+/// Converts amounts of tokens, e.g. the profits of strategies, into ETH and USD, so
+/// they can be compared and aggregated in a common unit.
+///
+/// Tokens are priced by a Chainlink feed when one is configured and fresh, falling back
+/// to the time weighted average price of a Uniswap V3 pool trading the token against
+/// WETH. USD values are derived from the ETH value of tokens and a Chainlink `ETH / USD`
+/// feed. Prices are cached for a short while, as strategies convert profits for every
+/// opportunity.
+#[derive(Debug)]
+pub struct PriceService<M> {
+    provider: Arc<M>,
+    weth: Address,
+    eth_usd_feed: Option<Address>,
+    /// Chainlink feeds of tokens, and what they price tokens in.
+    feeds: HashMap<Address, (Address, Quote)>,
+    /// Uniswap V3 pools trading tokens against WETH.
+    twap_pools: HashMap<Address, Address>,
+    twap_window: Duration,
+    max_feed_age: Duration,
+    cache_ttl: Duration,
+    /// Prices read recently, by token or feed.
+    prices: Mutex<HashMap<Address, (Instant, f64)>>,
+    /// Decimals of tokens and feeds, which don't change.
+    decimals: Mutex<HashMap<Address, u8>>,
+}
+
+impl<M> PriceService<M> {
+    /// Price tokens in `weth`, which is worth one wei per unit.
+    pub fn new(provider: Arc<M>, weth: Address) -> Self {
+        Self {
+            provider,
+            weth,
+            eth_usd_feed: None,
+            feeds: HashMap::new(),
+            twap_pools: HashMap::new(),
+            twap_window: Duration::from_secs(30 * 60),
+            max_feed_age: Duration::from_secs(60 * 60),
+            cache_ttl: Duration::from_secs(60),
+            prices: Mutex::new(HashMap::new()),
+            decimals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Read the price of ETH in USD from the Chainlink `ETH / USD` feed at `feed`.
+    /// Required for USD values.
+    pub fn with_eth_usd_feed(mut self, feed: Address) -> Self {
+        self.eth_usd_feed = Some(feed);
+        self
+    }
+
+    /// Price `token` with the Chainlink feed at `feed`, which quotes it in `quote`.
+    pub fn with_chainlink_feed(mut self, token: Address, feed: Address, quote: Quote) -> Self {
+        self.feeds.insert(token, (feed, quote));
+        self
+    }
+
+    /// Price `token` with the TWAP of the Uniswap V3 `pool` trading it against WETH,
+    /// when it has no Chainlink feed or its feed can't be read.
+    pub fn with_twap_pool(mut self, token: Address, pool: Address) -> Self {
+        self.twap_pools.insert(token, pool);
+        self
+    }
+
+    /// Average pool prices over `window`. Defaults to 30 minutes.
+    pub fn with_twap_window(mut self, window: Duration) -> Self {
+        self.twap_window = window;
+        self
+    }
+
+    /// Ignore Chainlink answers older than `max_age`. Defaults to an hour, the
+    /// heartbeat of most feeds.
+    pub fn with_max_feed_age(mut self, max_age: Duration) -> Self {
+        self.max_feed_age = max_age;
+        self
+    }
+
+    /// Reuse prices for `ttl` before reading them again. Defaults to a minute.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    fn cached(&self, key: Address) -> Option<f64> {
+        let prices = self.prices.lock().unwrap();
+        let (read_at, price) = prices.get(&key)?;
+        (read_at.elapsed() < self.cache_ttl).then_some(*price)
+    }
+
+    fn cache(&self, key: Address, price: f64) {
+        self.prices
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), price));
+    }
+}
+
+impl<M: Middleware> PriceService<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the value, in wei, of `amount` units of `token`.
+    pub async fn to_eth(&self, token: Address, amount: U256) -> anyhow::Result<U256> {
+        let wei = u256_to_f64(amount) * self.wei_per_unit(token).await?;
+        Ok(f64_to_u256(wei))
+    }
+
+    /// Returns the value, in USD, of `amount` units of `token`.
+    pub async fn to_usd(&self, token: Address, amount: U256) -> anyhow::Result<f64> {
+        let wei = u256_to_f64(amount) * self.wei_per_unit(token).await?;
+        Ok(wei / WEI_PER_ETH * self.eth_usd().await?)
+    }
+
+    /// Returns whether a profit of `amount` units of `token` is at least `min_profit`.
+    pub async fn meets(
+        &self,
+        min_profit: MinProfit,
+        token: Address,
+        amount: U256,
+    ) -> anyhow::Result<bool> {
+        Ok(match min_profit {
+            MinProfit::Wei(min) => self.to_eth(token, amount).await? >= min,
+            MinProfit::Usd(min) => self.to_usd(token, amount).await? >= min,
+        })
+    }
+
+    /// Returns the price of ETH in USD.
+    pub async fn eth_usd(&self) -> anyhow::Result<f64> {
+        let feed = self
+            .eth_usd_feed
+            .ok_or_else(|| anyhow!("no ETH / USD feed configured"))?;
+        if let Some(price) = self.cached(feed) {
+            return Ok(price);
+        }
+        let price = self.chainlink_answer(feed).await?;
+        self.cache(feed, price);
+        Ok(price)
+    }
+
+    /// Returns the value, in wei, of a single unit of `token`.
+    pub async fn wei_per_unit(&self, token: Address) -> anyhow::Result<f64> {
+        if token == self.weth {
+            return Ok(1.0);
+        }
+        if let Some(price) = self.cached(token) {
+            return Ok(price);
+        }
+
+        let mut price = None;
+        if let Some((feed, quote)) = self.feeds.get(&token) {
+            match self.chainlink_price(token, *feed, *quote).await {
+                Ok(feed_price) => price = Some(feed_price),
+                Err(e) => warn!(
+                    "failed to read the price of {:?} from Chainlink: {}",
+                    token, e
+                ),
+            }
+        }
+        if price.is_none() {
+            if let Some(pool) = self.twap_pools.get(&token) {
+                price = Some(self.twap_price(token, *pool).await?);
+            }
+        }
+        let price = price.ok_or_else(|| anyhow!("no price source for token {:?}", token))?;
+        self.cache(token, price);
+        Ok(price)
+    }
+
+    /// Returns the value, in wei, of a unit of `token`, as quoted in `quote` by `feed`.
+    async fn chainlink_price(
+        &self,
+        token: Address,
+        feed: Address,
+        quote: Quote,
+    ) -> anyhow::Result<f64> {
+        let per_token = self.chainlink_answer(feed).await?;
+        let per_token_in_eth = match quote {
+            Quote::Eth => per_token,
+            Quote::Usd => per_token / self.eth_usd().await?,
+        };
+        let unit = 10f64.powi(self.decimals(token).await? as i32);
+        Ok(per_token_in_eth * WEI_PER_ETH / unit)
+    }
+
+    /// Returns the latest answer of the Chainlink `feed`, scaled by its decimals.
+    async fn chainlink_answer(&self, feed: Address) -> anyhow::Result<f64> {
+        let output = self.call(feed, LATEST_ROUND_DATA_SELECTOR.to_vec()).await?;
+        let words = abi::decode(
+            &[
+                ParamType::Uint(80),
+                ParamType::Int(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(80),
+            ],
+            &output,
+        )?;
+        let (Some(Token::Int(answer)), Some(Token::Uint(updated_at))) =
+            (words.get(1), words.get(3))
+        else {
+            bail!("invalid latestRoundData output from feed {:?}", feed);
+        };
+        let answer = I256::from_raw(*answer);
+        if answer <= I256::zero() {
+            bail!("feed {:?} answered {}", feed, answer);
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let age = now.saturating_sub(updated_at.low_u64());
+        if age > self.max_feed_age.as_secs() {
+            bail!("feed {:?} was last updated {}s ago", feed, age);
+        }
+        let unit = 10f64.powi(self.decimals(feed).await? as i32);
+        Ok(u256_to_f64(answer.into_raw()) / unit)
+    }
+
+    /// Returns the value, in wei, of a unit of `token`, averaged over the TWAP window
+    /// by the Uniswap V3 `pool` trading it against WETH.
+    async fn twap_price(&self, token: Address, pool: Address) -> anyhow::Result<f64> {
+        let output = self.call(pool, TOKEN0_SELECTOR.to_vec()).await?;
+        let token0 = match abi::decode(&[ParamType::Address], &output)?.first() {
+            Some(Token::Address(token0)) => *token0,
+            _ => bail!("invalid token0 output from pool {:?}", pool),
+        };
+
+        let window = self.twap_window.as_secs().max(1) as u32;
+        let mut data = OBSERVE_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(window)),
+            Token::Uint(U256::zero()),
+        ])]));
+        let output = self.call(pool, data).await?;
+        let cumulatives = abi::decode(
+            &[
+                ParamType::Array(Box::new(ParamType::Int(56))),
+                ParamType::Array(Box::new(ParamType::Uint(160))),
+            ],
+            &output,
+        )?;
+        let Some(Token::Array(ticks)) = cumulatives.first() else {
+            bail!("invalid observe output from pool {:?}", pool);
+        };
+        let [Token::Int(start), Token::Int(end)] = ticks.as_slice() else {
+            bail!("invalid observe output from pool {:?}", pool);
+        };
+        let elapsed = I256::from_raw(*end) - I256::from_raw(*start);
+        let average_tick = elapsed.as_i64() as f64 / window as f64;
+        Ok(tick_price(average_tick, token == token0))
+    }
+
+    /// Returns the decimals of `token`, or of a Chainlink feed.
+    async fn decimals(&self, token: Address) -> anyhow::Result<u8> {
+        if let Some(decimals) = self.decimals.lock().unwrap().get(&token) {
+            return Ok(*decimals);
+        }
+        let output = self.call(token, DECIMALS_SELECTOR.to_vec()).await?;
+        let decimals = match abi::decode(&[ParamType::Uint(8)], &output)?.first() {
+            Some(Token::Uint(decimals)) => decimals.low_u32() as u8,
+            _ => bail!("invalid decimals output from {:?}", token),
+        };
+        self.decimals.lock().unwrap().insert(token, decimals);
+        Ok(decimals)
+    }
+
+    async fn call(&self, to: Address, data: Vec<u8>) -> anyhow::Result<Bytes> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(to)
+            .data(Bytes::from(data))
+            .into();
+        Ok(self.provider.call(&tx, None).await?)
+    }
+}
+
+/// Returns the value, in wei, of a unit of a token at a pool tick. Ticks price token0 in
+/// units of token1, WETH being the other token.
+fn tick_price(tick: f64, is_token0: bool) -> f64 {
+    let price = 1.0001f64.powf(tick);
+    match is_token0 {
+        true => price,
+        false => 1.0 / price,
+    }
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    value
+        .0
+        .iter()
+        .rev()
+        .fold(0.0, |acc, limb| acc * 2f64.powi(64) + *limb as f64)
+}
+
+fn f64_to_u256(value: f64) -> U256 {
+    match value {
+        value if value < 1.0 => U256::zero(),
+        value if value < u128::MAX as f64 => U256::from(value as u128),
+        value => U256::from_dec_str(&format!("{:.0}", value)).unwrap_or(U256::MAX),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{providers::Provider, utils::id};
+
+    fn word(value: U256) -> Token {
+        Token::Uint(value)
+    }
+
+    fn round_data(answer: i64, updated_at: u64) -> Bytes {
+        Bytes::from(abi::encode(&[
+            word(U256::one()),
+            Token::Int(I256::from(answer).into_raw()),
+            word(U256::from(updated_at)),
+            word(U256::from(updated_at)),
+            word(U256::one()),
+        ]))
+    }
+
+    fn decimals(decimals: u8) -> Bytes {
+        Bytes::from(abi::encode(&[word(U256::from(decimals))]))
+    }
+
+    #[test]
+    fn selectors_match_signatures() {
+        assert_eq!(LATEST_ROUND_DATA_SELECTOR, id("latestRoundData()"));
+        assert_eq!(DECIMALS_SELECTOR, id("decimals()"));
+        assert_eq!(TOKEN0_SELECTOR, id("token0()"));
+        assert_eq!(OBSERVE_SELECTOR, id("observe(uint32[])"));
+    }
+
+    #[test]
+    fn prices_ticks_against_weth() {
+        // At tick 0 both tokens are worth the same.
+        assert_eq!(tick_price(0.0, true), 1.0);
+        // USDC is token0 of its WETH pool, at about 3000 USDC per ETH.
+        let usdc_price = tick_price(196_256.0, true);
+        assert!((usdc_price * 1e6 / WEI_PER_ETH - 1.0 / 3000.0).abs() < 1e-5);
+        assert!((tick_price(-10.0, false) - tick_price(10.0, true)).abs() < 1e-12);
+        assert_eq!(u256_to_f64(U256::exp10(30)), 1e30);
+        assert_eq!(
+            f64_to_u256(1.5e18),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+    }
+
+    #[tokio::test]
+    async fn converts_profits_with_chainlink_feeds() {
+        let (provider, mock) = Provider::mocked();
+        let (weth, usdc) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let (usdc_feed, eth_usd_feed) = (Address::repeat_byte(3), Address::repeat_byte(4));
+        let prices = PriceService::new(Arc::new(provider), weth)
+            .with_chainlink_feed(usdc, usdc_feed, Quote::Eth)
+            .with_eth_usd_feed(eth_usd_feed);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // Responses are popped last first: the USDC / ETH answer and decimals, the
+        // decimals of USDC, then the ETH / USD answer and decimals.
+        mock.push::<Bytes, _>(decimals(8)).unwrap();
+        mock.push::<Bytes, _>(round_data(300_000_000_000, now))
+            .unwrap();
+        mock.push::<Bytes, _>(decimals(6)).unwrap();
+        mock.push::<Bytes, _>(decimals(18)).unwrap();
+        mock.push::<Bytes, _>(round_data(333_333_333_333_333, now))
+            .unwrap();
+
+        let profit = U256::from(3_000_000_000u64);
+        let wei = prices.to_eth(usdc, profit).await.unwrap();
+        assert_eq!(wei / U256::exp10(15), U256::from(999));
+        let usd = prices.to_usd(usdc, profit).await.unwrap();
+        assert!((usd - 3000.0).abs() < 0.01);
+
+        // Cached prices are reused without any call.
+        assert!(prices
+            .meets(MinProfit::Usd(2500.0), usdc, profit)
+            .await
+            .unwrap());
+        assert!(!prices
+            .meets(MinProfit::Wei(U256::exp10(18)), usdc, profit)
+            .await
+            .unwrap());
+        assert!(prices
+            .meets(MinProfit::Wei(U256::exp10(18)), weth, U256::exp10(18))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_twap_when_feed_is_stale() {
+        let (provider, mock) = Provider::mocked();
+        let (weth, token) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let prices = PriceService::new(Arc::new(provider), weth)
+            .with_chainlink_feed(token, Address::repeat_byte(3), Quote::Eth)
+            .with_twap_pool(token, Address::repeat_byte(4))
+            .with_twap_window(Duration::from_secs(100));
+
+        // A day old answer, then the pool's token0 and tick cumulatives.
+        let observations = Bytes::from(abi::encode(&[
+            Token::Array(vec![
+                Token::Int(I256::from(1_000).into_raw()),
+                Token::Int(I256::from(1_000 + 100 * 6_932).into_raw()),
+            ]),
+            Token::Array(vec![word(U256::zero()), word(U256::zero())]),
+        ]));
+        mock.push::<Bytes, _>(observations).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Address(token)])))
+            .unwrap();
+        mock.push::<Bytes, _>(round_data(1, 86_400)).unwrap();
+
+        // 1.0001^6932 is about 2.
+        let price = prices.wei_per_unit(token).await.unwrap();
+        assert!((price - 2.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::{self, Function, HumanReadableParser, ParamType, Token},
+    types::{Address, H256},
+    utils::{get_create2_address_from_hash, keccak256},
+};
+use matchmaker::types::Hint;
+
+use super::abi_registry::{Protocol, SwapDirection};
+
+/// A swap a hinted transaction likely makes through a known router, inferred from the
+/// function it calls. The pools and direction are only inferred when the hint shares
+/// the calldata of the transaction, and the protocol when it shares its selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LikelySwap {
+    /// Hash of the hint.
+    pub hint_hash: H256,
+    /// Index of the transaction in the hint.
+    pub tx_index: usize,
+    /// Router the transaction calls.
+    pub router: Address,
+    /// Protocol of the pools swapped through, if known.
+    pub protocol: Option<Protocol>,
+    /// Pools the swap likely goes through, in order.
+    pub pools: Vec<Address>,
+    /// Tokens the whole swap sells and buys, if known.
+    pub direction: Option<SwapDirection>,
+}
+
+/// Factory deploying the pools of a protocol, and the hash of their init code, from
+/// which pool addresses are derived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolDeployer {
+    pub factory: Address,
+    pub init_code_hash: H256,
+}
+
+/// A router known to a [RouterClassifier], and the deployers of the pools it swaps
+/// through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownRouter {
+    pub name: &'static str,
+    /// Deployer of the v2 pools the router swaps through, if it swaps through any.
+    pub v2: Option<PoolDeployer>,
+    /// Deployer of the v3 pools the router swaps through, if it swaps through any.
+    pub v3: Option<PoolDeployer>,
+}
+
+/// How the swaps of a router function are read from its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Call {
+    /// A v2 swap along the `address[]` path at argument `path`.
+    V2Path { path: usize },
+    /// A v3 swap through a single pool, whose parameters start with `(tokenIn,
+    /// tokenOut, fee)`.
+    V3Single,
+    /// A v3 swap along the packed path starting its parameters, from the bought token
+    /// for exact output swaps.
+    V3Path { exact_output: bool },
+    /// The commands of the universal router.
+    Execute,
+    /// Calls to the router itself, the `bytes[]` at argument `calls`.
+    Multicall { calls: usize },
+}
+
+impl Call {
+    fn protocol(&self) -> Option<Protocol> {
+        match self {
+            Call::V2Path { .. } => Some(Protocol::UniswapV2),
+            Call::V3Single | Call::V3Path { .. } => Some(Protocol::UniswapV3),
+            Call::Execute | Call::Multicall { .. } => None,
+        }
+    }
+}
+
+/// A hop of a swap path: `token_in` sold for `token_out` through a v3 pool of `fee`,
+/// or a v2 pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hop {
+    token_in: Address,
+    token_out: Address,
+    fee: Option<u32>,
+}
+
+/// A swap decoded from the calldata of a router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RouterSwap {
+    protocol: Option<Protocol>,
+    hops: Vec<Hop>,
+}
+
+/// Universal router commands swapping through v3 and v2 pools.
+const V3_SWAP_EXACT_IN: u8 = 0x00;
+const V3_SWAP_EXACT_OUT: u8 = 0x01;
+const V2_SWAP_EXACT_IN: u8 = 0x08;
+const V2_SWAP_EXACT_OUT: u8 = 0x09;
+/// Bits of a universal router command holding its type.
+const COMMAND_TYPE_MASK: u8 = 0x3f;
+
+/// Classifies the transactions of MEV-Share hints calling known routers, by the
+/// selectors of the Uniswap V2 / V3 routers and the Universal Router, and infers the
+/// pools they swap through and their direction from their calldata, if the hint shares
+/// it. This extends the hints strategies can act on beyond those logging their pools.
+/// The default classifier knows the mainnet Uniswap and Sushiswap routers.
+#[derive(Debug, Clone)]
+pub struct RouterClassifier {
+    routers: HashMap<Address, KnownRouter>,
+    functions: HashMap<[u8; 4], (Function, Call)>,
+}
+
+impl RouterClassifier {
+    /// Create a classifier knowing no routers, decoding the swap functions of the
+    /// Uniswap routers.
+    pub fn empty() -> Self {
+        let functions = router_functions()
+            .into_iter()
+            .map(|(signature, call)| {
+                let function =
+                    HumanReadableParser::parse_function(&format!("function {}", signature))
+                        .expect("router function signatures are valid");
+                (function.short_signature(), (function, call))
+            })
+            .collect();
+        Self {
+            routers: HashMap::new(),
+            functions,
+        }
+    }
+
+    /// Classify the transactions calling `address` as calls to `router`, replacing the
+    /// router at the address if it was known.
+    pub fn register(&mut self, address: Address, router: KnownRouter) {
+        self.routers.insert(address, router);
+    }
+
+    /// Returns the router at `address`, if it is known.
+    pub fn router(&self, address: &Address) -> Option<&KnownRouter> {
+        self.routers.get(address)
+    }
+
+    /// Returns the swaps the transactions of `hint` likely make through known routers.
+    /// Transactions calling a router function which doesn't swap aren't classified.
+    pub fn classify(&self, hint: &Hint) -> Vec<LikelySwap> {
+        let mut likely_swaps = vec![];
+        for (tx_index, tx) in hint.txs.iter().enumerate() {
+            let Some((router_address, router)) =
+                tx.to.and_then(|to| Some((to, self.routers.get(&to)?)))
+            else {
+                continue;
+            };
+            let swaps = match (&tx.calldata, &tx.function_selector) {
+                (Some(calldata), _) => self.decode(calldata, true),
+                // Without calldata, only the protocol can be told from the selector.
+                (None, Some(selector)) => self
+                    .functions
+                    .get(&selector.0)
+                    .map(|(_, call)| RouterSwap {
+                        protocol: call.protocol(),
+                        hops: vec![],
+                    })
+                    .into_iter()
+                    .collect(),
+                (None, None) => continue,
+            };
+            likely_swaps.extend(swaps.into_iter().map(|swap| {
+                LikelySwap {
+                    hint_hash: hint.hash,
+                    tx_index,
+                    router: router_address,
+                    protocol: swap.protocol,
+                    pools: swap
+                        .hops
+                        .iter()
+                        .filter_map(|hop| router.pool(swap.protocol?, hop))
+                        .collect(),
+                    direction: swap
+                        .hops
+                        .first()
+                        .zip(swap.hops.last())
+                        .map(|(first, last)| SwapDirection::Tokens {
+                            token_in: first.token_in,
+                            token_out: last.token_out,
+                        }),
+                }
+            }));
+        }
+        likely_swaps
+    }
+
+    /// Decode the swaps of a call to a router, following multicalls one level deep if
+    /// `nested` calls are allowed. Calls which don't decode have no swaps.
+    fn decode(&self, calldata: &[u8], nested: bool) -> Vec<RouterSwap> {
+        let Some((function, call)) = calldata
+            .get(..4)
+            .and_then(|selector| self.functions.get(selector))
+        else {
+            return vec![];
+        };
+        let Ok(args) = function.decode_input(&calldata[4..]) else {
+            return vec![];
+        };
+        match call {
+            Call::V2Path { path } => args.get(*path).and_then(v2_swap).into_iter().collect(),
+            Call::V3Single => args.first().and_then(v3_single_swap).into_iter().collect(),
+            Call::V3Path { exact_output } => args
+                .first()
+                .and_then(|params| params.clone().into_tuple()?.first()?.clone().into_bytes())
+                .and_then(|path| v3_swap(&path, *exact_output))
+                .into_iter()
+                .collect(),
+            Call::Execute => execute_swaps(&args),
+            Call::Multicall { calls } if nested => args
+                .get(*calls)
+                .cloned()
+                .and_then(Token::into_array)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(Token::into_bytes)
+                .flat_map(|calldata| self.decode(&calldata, false))
+                .collect(),
+            Call::Multicall { .. } => vec![],
+        }
+    }
+}
+
+impl Default for RouterClassifier {
+    fn default() -> Self {
+        let uniswap_v2 = PoolDeployer {
+            factory: address("0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"),
+            init_code_hash: hash(
+                "0x96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f",
+            ),
+        };
+        let sushiswap = PoolDeployer {
+            factory: address("0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac"),
+            init_code_hash: hash(
+                "0xe18a34eb0e04b04f7a0ac29a6e80748dca96319b42c54d679cb821dca90c6303",
+            ),
+        };
+        let uniswap_v3 = PoolDeployer {
+            factory: address("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+            init_code_hash: hash(
+                "0xe34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54",
+            ),
+        };
+        let mut classifier = Self::empty();
+        for (router, name, v2, v3) in [
+            (
+                "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D",
+                "uniswap-v2-router",
+                Some(uniswap_v2),
+                None,
+            ),
+            (
+                "0xE592427A0AEce92De3Edee1F18E0157C05861564",
+                "uniswap-v3-router",
+                None,
+                Some(uniswap_v3),
+            ),
+            (
+                "0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45",
+                "uniswap-v3-router-02",
+                Some(uniswap_v2),
+                Some(uniswap_v3),
+            ),
+            (
+                "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD",
+                "universal-router",
+                Some(uniswap_v2),
+                Some(uniswap_v3),
+            ),
+            (
+                "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F",
+                "sushiswap-router",
+                Some(sushiswap),
+                None,
+            ),
+        ] {
+            classifier.register(address(router), KnownRouter { name, v2, v3 });
+        }
+        classifier
+    }
+}
+
+impl KnownRouter {
+    /// Returns the address of the pool of `protocol` swapping `hop`, if the router swaps
+    /// through pools of the protocol.
+    fn pool(&self, protocol: Protocol, hop: &Hop) -> Option<Address> {
+        let (token0, token1) = match hop.token_in < hop.token_out {
+            true => (hop.token_in, hop.token_out),
+            false => (hop.token_out, hop.token_in),
+        };
+        let (deployer, salt) = match protocol {
+            Protocol::UniswapV2 => (
+                self.v2?,
+                keccak256([token0.as_bytes(), token1.as_bytes()].concat()),
+            ),
+            Protocol::UniswapV3 => (
+                self.v3?,
+                keccak256(abi::encode(&[
+                    Token::Address(token0),
+                    Token::Address(token1),
+                    Token::Uint(hop.fee?.into()),
+                ])),
+            ),
+            Protocol::Curve | Protocol::Balancer => return None,
+        };
+        Some(get_create2_address_from_hash(
+            deployer.factory,
+            salt,
+            deployer.init_code_hash,
+        ))
+    }
+}
+
+/// The functions of the Uniswap routers which swap, by signature.
+fn router_functions() -> Vec<(&'static str, Call)> {
+    vec![
+        // Uniswap V2 router, and its forks.
+        (
+            "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        (
+            "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        ("swapExactETHForTokens(uint256,address[],address,uint256)", Call::V2Path { path: 1 }),
+        (
+            "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        (
+            "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        ("swapETHForExactTokens(uint256,address[],address,uint256)", Call::V2Path { path: 1 }),
+        (
+            "swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        (
+            "swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)",
+            Call::V2Path { path: 1 },
+        ),
+        (
+            "swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+            Call::V2Path { path: 2 },
+        ),
+        // Uniswap V3 router.
+        (
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+            Call::V3Single,
+        ),
+        (
+            "exactOutputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+            Call::V3Single,
+        ),
+        (
+            "exactInput((bytes,address,uint256,uint256,uint256))",
+            Call::V3Path { exact_output: false },
+        ),
+        (
+            "exactOutput((bytes,address,uint256,uint256,uint256))",
+            Call::V3Path { exact_output: true },
+        ),
+        // Uniswap V3 router 02, which drops the deadlines and also swaps through v2.
+        (
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+            Call::V3Single,
+        ),
+        (
+            "exactOutputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+            Call::V3Single,
+        ),
+        ("exactInput((bytes,address,uint256,uint256))", Call::V3Path { exact_output: false }),
+        ("exactOutput((bytes,address,uint256,uint256))", Call::V3Path { exact_output: true }),
+        ("swapExactTokensForTokens(uint256,uint256,address[],address)", Call::V2Path { path: 2 }),
+        ("swapTokensForExactTokens(uint256,uint256,address[],address)", Call::V2Path { path: 2 }),
+        ("multicall(bytes[])", Call::Multicall { calls: 0 }),
+        ("multicall(uint256,bytes[])", Call::Multicall { calls: 1 }),
+        ("multicall(bytes32,bytes[])", Call::Multicall { calls: 1 }),
+        // Universal router.
+        ("execute(bytes,bytes[])", Call::Execute),
+        ("execute(bytes,bytes[],uint256)", Call::Execute),
+    ]
+}
+
+fn address(address: &str) -> Address {
+    address.parse().unwrap()
+}
+
+fn hash(hash: &str) -> H256 {
+    hash.parse().unwrap()
+}
+
+/// A v2 swap along the tokens of `path`.
+fn v2_swap(path: &Token) -> Option<RouterSwap> {
+    let path = path
+        .clone()
+        .into_array()?
+        .into_iter()
+        .map(Token::into_address)
+        .collect::<Option<Vec<_>>>()?;
+    let hops = path
+        .windows(2)
+        .map(|pair| Hop {
+            token_in: pair[0],
+            token_out: pair[1],
+            fee: None,
+        })
+        .collect();
+    Some(RouterSwap {
+        protocol: Some(Protocol::UniswapV2),
+        hops,
+    })
+}
+
+/// A v3 swap through the single pool of `params`, starting with `(tokenIn, tokenOut,
+/// fee)`.
+fn v3_single_swap(params: &Token) -> Option<RouterSwap> {
+    let params = params.clone().into_tuple()?;
+    let hop = Hop {
+        token_in: params.first()?.clone().into_address()?,
+        token_out: params.get(1)?.clone().into_address()?,
+        fee: Some(params.get(2)?.clone().into_uint()?.low_u32()),
+    };
+    Some(RouterSwap {
+        protocol: Some(Protocol::UniswapV3),
+        hops: vec![hop],
+    })
+}
+
+/// A v3 swap along a packed path of tokens and fees, `token (20 bytes) | fee (3 bytes)
+/// | token | ...`, which starts from the bought token for exact output swaps.
+fn v3_swap(path: &[u8], exact_output: bool) -> Option<RouterSwap> {
+    if path.len() < 43 || (path.len() - 20) % 23 != 0 {
+        return None;
+    }
+    let mut hops: Vec<Hop> = (0..(path.len() - 20) / 23)
+        .map(|i| {
+            let hop = &path[i * 23..i * 23 + 43];
+            Hop {
+                token_in: Address::from_slice(&hop[..20]),
+                token_out: Address::from_slice(&hop[23..]),
+                fee: Some(u32::from_be_bytes([0, hop[20], hop[21], hop[22]])),
+            }
+        })
+        .collect();
+    if exact_output {
+        hops.reverse();
+        for hop in hops.iter_mut() {
+            std::mem::swap(&mut hop.token_in, &mut hop.token_out);
+        }
+    }
+    Some(RouterSwap {
+        protocol: Some(Protocol::UniswapV3),
+        hops,
+    })
+}
+
+/// The swaps of the commands of a universal router `execute` call, whose arguments are
+/// the commands, one per byte, and their inputs.
+fn execute_swaps(args: &[Token]) -> Vec<RouterSwap> {
+    let (Some(commands), Some(inputs)) = (
+        args.first().cloned().and_then(Token::into_bytes),
+        args.get(1).cloned().and_then(Token::into_array),
+    ) else {
+        return vec![];
+    };
+    // Swaps take (recipient, amount, amount limit, path, payer is user).
+    let swap_params = |path: ParamType| {
+        [
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            path,
+            ParamType::Bool,
+        ]
+    };
+    commands
+        .iter()
+        .zip(inputs)
+        .filter_map(|(command, input)| {
+            let input = input.into_bytes()?;
+            match command & COMMAND_TYPE_MASK {
+                command @ (V3_SWAP_EXACT_IN | V3_SWAP_EXACT_OUT) => {
+                    let params = abi::decode(&swap_params(ParamType::Bytes), &input).ok()?;
+                    let path = params.get(3)?.clone().into_bytes()?;
+                    v3_swap(&path, command == V3_SWAP_EXACT_OUT)
+                }
+                V2_SWAP_EXACT_IN | V2_SWAP_EXACT_OUT => {
+                    let path = ParamType::Array(Box::new(ParamType::Address));
+                    let params = abi::decode(&swap_params(path), &input).ok()?;
+                    v2_swap(params.get(3)?)
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bytes, U256};
+    use matchmaker::types::{FunctionSelector, HintTransaction};
+
+    const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    const V2_ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+    const V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+    const UNIVERSAL_ROUTER: &str = "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD";
+
+    fn hint(to: &str, calldata: Option<Vec<u8>>, selector: Option<[u8; 4]>) -> Hint {
+        Hint {
+            hash: H256::repeat_byte(1),
+            txs: vec![HintTransaction {
+                to: Some(address(to)),
+                function_selector: selector.map(FunctionSelector),
+                calldata: calldata.map(Bytes::from),
+            }],
+            logs: vec![],
+            gas_used: None,
+            mev_gas_price: None,
+        }
+    }
+
+    fn calldata(classifier: &RouterClassifier, signature: &str, args: &[Token]) -> Vec<u8> {
+        let (function, _) = classifier
+            .functions
+            .values()
+            .find(|(function, _)| function.signature() == signature)
+            .unwrap();
+        function.encode_input(args).unwrap()
+    }
+
+    #[test]
+    fn infers_pools_and_direction_from_calldata() {
+        let classifier = RouterClassifier::default();
+        let (usdc, weth) = (address(USDC), address(WETH));
+        let params = Token::Tuple(vec![
+            Token::Address(weth),
+            Token::Address(usdc),
+            Token::Uint(U256::from(500)),
+            Token::Address(Address::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::exp10(18)),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+        ]);
+        let signature =
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))";
+        let calldata = calldata(&classifier, signature, &[params]);
+
+        let swaps = classifier.classify(&hint(V3_ROUTER, Some(calldata), None));
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].protocol, Some(Protocol::UniswapV3));
+        assert_eq!(
+            swaps[0].pools,
+            vec![address("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")]
+        );
+        assert_eq!(
+            swaps[0].direction,
+            Some(SwapDirection::Tokens {
+                token_in: weth,
+                token_out: usdc
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_universal_router_commands() {
+        let classifier = RouterClassifier::default();
+        let (usdc, weth) = (address(USDC), address(WETH));
+        let input = |path| {
+            abi::encode(&[
+                Token::Address(Address::zero()),
+                Token::Uint(U256::exp10(6)),
+                Token::Uint(U256::zero()),
+                path,
+                Token::Bool(true),
+            ])
+        };
+        let v2_path = Token::Array(vec![Token::Address(usdc), Token::Address(weth)]);
+        // Exact output v3 paths start from the bought token.
+        let v3_path = [weth.as_bytes(), &[0, 0x0b, 0xb8], usdc.as_bytes()].concat();
+        let args = [
+            Token::Bytes(vec![V2_SWAP_EXACT_IN, 0x0c, V3_SWAP_EXACT_OUT]),
+            Token::Array(vec![
+                Token::Bytes(input(v2_path)),
+                Token::Bytes(vec![]),
+                Token::Bytes(input(Token::Bytes(v3_path))),
+            ]),
+        ];
+        let calldata = calldata(&classifier, "execute(bytes,bytes[])", &args);
+
+        let swaps = classifier.classify(&hint(UNIVERSAL_ROUTER, Some(calldata), None));
+        assert_eq!(swaps.len(), 2);
+        assert_eq!(swaps[0].protocol, Some(Protocol::UniswapV2));
+        assert_eq!(
+            swaps[0].pools,
+            vec![address("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc")]
+        );
+        assert_eq!(swaps[1].protocol, Some(Protocol::UniswapV3));
+        assert_eq!(
+            swaps[1].direction,
+            Some(SwapDirection::Tokens {
+                token_in: usdc,
+                token_out: weth
+            })
+        );
+    }
+
+    #[test]
+    fn classifies_selectors_without_calldata() {
+        let classifier = RouterClassifier::default();
+        let selector = ethers::utils::id(
+            "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+        );
+        let swaps = classifier.classify(&hint(V2_ROUTER, None, Some(selector)));
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].protocol, Some(Protocol::UniswapV2));
+        assert!(swaps[0].pools.is_empty());
+        assert_eq!(swaps[0].direction, None);
+
+        // Unknown routers and functions aren't classified.
+        assert!(classifier
+            .classify(&hint(USDC, None, Some(selector)))
+            .is_empty());
+        assert!(classifier
+            .classify(&hint(V2_ROUTER, None, Some([0; 4])))
+            .is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use ethers::types::{Address, U256, U64};
+
+/// Default time a simulation outcome is reused for, about one block.
+pub const DEFAULT_SIMULATION_TTL: Duration = Duration::from_secs(12);
+
+/// Identifies simulations expected to have the same outcome within a block: the pools
+/// an opportunity trades through, the token it starts from, which sets the direction
+/// it trades the pools in, and its size, rounded into a [bucket](size_bucket).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationKey {
+    pub pools: Vec<Address>,
+    pub token_in: Address,
+    pub size_bucket: u32,
+}
+
+impl SimulationKey {
+    pub fn new(pools: Vec<Address>, token_in: Address, size: U256) -> Self {
+        Self {
+            pools,
+            token_in,
+            size_bucket: size_bucket(size),
+        }
+    }
+}
+
+/// Returns the bucket of `size`. Buckets split every power of two in four, so sizes in
+/// a bucket are within about 20% of each other.
+pub fn size_bucket(size: U256) -> u32 {
+    let bits = size.bits() as u32;
+    if bits <= 2 {
+        return size.low_u32();
+    }
+    let fraction = (size >> (bits - 3)).low_u32() & 0b11;
+    bits * 4 + fraction
+}
+
+#[derive(Debug)]
+struct CacheState<K, V> {
+    /// Latest block seen.
+    block: U64,
+    /// Outcomes by key, with the block they were simulated at and when.
+    outcomes: HashMap<K, (U64, Instant, V)>,
+}
+
+/// A short-lived cache of simulation outcomes, so opportunities seen repeatedly within
+/// a block, e.g. through several hints on the same pool, are only simulated once.
+/// Outcomes are forgotten after the ttl, and as soon as a new block is seen, since
+/// they depend on the state they were simulated against.
+#[derive(Debug)]
+pub struct SimulationCache<K, V> {
+    ttl: Duration,
+    state: Mutex<CacheState<K, V>>,
+}
+
+impl<K: Eq + Hash, V: Clone> SimulationCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CacheState {
+                block: U64::zero(),
+                outcomes: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Returns the outcome of the simulation of `key`, if it was simulated at the latest
+    /// block within the ttl.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let state = self.state.lock().unwrap();
+        match state.outcomes.get(key) {
+            Some((block, simulated_at, outcome))
+                if *block >= state.block && simulated_at.elapsed() < self.ttl =>
+            {
+                Some(outcome.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Record the outcome of the simulation of `key` at `block`. Outcomes of blocks
+    /// older than the latest one are dropped.
+    pub fn insert(&self, key: K, block: U64, outcome: V) {
+        let mut state = self.state.lock().unwrap();
+        if block < state.block {
+            return;
+        }
+        state.outcomes.insert(key, (block, Instant::now(), outcome));
+    }
+
+    /// Returns the cached outcome of `key`, or simulates it at `block` with `simulate`
+    /// and caches the outcome. Concurrent misses of the same key are each simulated.
+    pub async fn get_or_simulate<F>(&self, key: K, block: U64, simulate: F) -> V
+    where
+        F: Future<Output = V>,
+    {
+        if let Some(outcome) = self.get(&key) {
+            return outcome;
+        }
+        let outcome = simulate.await;
+        self.insert(key, block, outcome.clone());
+        outcome
+    }
+
+    /// Forget every outcome simulated before block `number`, e.g. on the
+    /// [NewBlock](crate::collectors::block_collector::NewBlock) events of a block
+    /// collector. A block number lower than the latest, e.g. after a reorg, forgets
+    /// every outcome.
+    pub fn on_new_block(&self, number: U64) {
+        let mut state = self.state.lock().unwrap();
+        if number < state.block {
+            state.outcomes.clear();
+        }
+        state.block = number;
+        state.outcomes.retain(|_, (block, _, _)| *block >= number);
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().outcomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for SimulationCache<K, V> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIMULATION_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn buckets_close_sizes_together() {
+        let size = U256::one() << 70;
+        assert_eq!(size_bucket(size), size_bucket(size + size / 10));
+        assert_ne!(size_bucket(size), size_bucket(size * 2));
+        assert_ne!(size_bucket(size), size_bucket(size - size / 5));
+        assert_eq!(size_bucket(U256::zero()), 0);
+        assert!(size_bucket(U256::from(3)) < size_bucket(U256::from(4)));
+    }
+
+    #[tokio::test]
+    async fn reuses_outcomes_within_a_block() {
+        let cache = SimulationCache::default();
+        let simulations = &AtomicUsize::new(0);
+        let simulate = |profit: u64| async move {
+            simulations.fetch_add(1, Ordering::SeqCst);
+            profit
+        };
+        let pool = Address::repeat_byte(1);
+        let key = |size: u64| SimulationKey::new(vec![pool], Address::zero(), U256::from(size));
+
+        cache.on_new_block(U64::from(10));
+        assert_eq!(
+            cache
+                .get_or_simulate(key(1024), U64::from(10), simulate(5))
+                .await,
+            5
+        );
+        // A similar size on the same pool reuses the outcome.
+        assert_eq!(
+            cache
+                .get_or_simulate(key(1100), U64::from(10), simulate(6))
+                .await,
+            5
+        );
+        assert_eq!(simulations.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.len(), 1);
+
+        // Outcomes are forgotten on a new block, and late outcomes of old blocks dropped.
+        cache.on_new_block(U64::from(11));
+        assert!(cache.is_empty());
+        cache.insert(key(1024), U64::from(10), 5);
+        assert_eq!(cache.get(&key(1024)), None);
+        cache.insert(key(1024), U64::from(11), 7);
+        assert_eq!(cache.get(&key(1024)), Some(7));
+
+        // Reorgs forget everything.
+        cache.on_new_block(U64::from(9));
+        assert!(cache.is_empty());
+    }
+}
@@ -1,15 +1,52 @@
 use artemis_core::{
-    collectors::{block_collector::BlockCollector, mempool_collector::MempoolCollector},
-    executors::mempool_executor::{MempoolExecutor, SubmitTxToMempool},
-    types::{Collector, Executor},
+    collectors::{
+        block_collector::{BlockCollector, NewBlock},
+        mempool_collector::MempoolCollector,
+    },
+    engine::{
+        ActionOrder, CollectorRestart, Concurrency, Engine, EngineReport, EngineTask, EventTimeout,
+        RestartPolicy, StrategyRestart, TaskExit,
+    },
+    error::{ArtemisError, Result},
+    executors::{
+        circuit_breaker_executor::{CircuitBreakerExecutor, CircuitState},
+        flashbots_executor::{FlashbotsBundle, FlashbotsExecutor, SimulationMode},
+        mempool_executor::{MempoolExecutor, SubmitTxToMempool},
+        mev_share_executor::{Bundles, MevshareExecutor},
+        noop_executor::NoopExecutor,
+    },
+    test_utils::{
+        run_to_quiescence, wait_for_actions, CapturingExecutor, MockCollector, MockRelay,
+    },
+    types::{
+        AsyncExecutorMap, ChainCollector, ChainExecutor, ChainStrategy, ChainTagged, Collector,
+        CollectorStream, ConcurrentStrategy, Executor, Expiry, FilterStrategy, RaceStrategy,
+        RestartableCollector, Strategy, StrategyMap, Submission, SubmissionReceipt,
+        TryCollectorMap,
+    },
 };
+use async_trait::async_trait;
 use ethers::providers::StreamExt;
 use ethers::{
+    core::rand::thread_rng,
     providers::{Middleware, Provider, Ws},
-    types::{BlockNumber, TransactionRequest, U256},
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, BlockNumber, Bytes, Chain, TransactionRequest,
+        H256, U256, U64,
+    },
     utils::{Anvil, AnvilInstance},
 };
-use std::{sync::Arc, time::Duration};
+use matchmaker::types::{BundleRequest, BundleTx};
+use reqwest::Url;
+use serde_json::json;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::time::sleep;
 
 /// Spawns Anvil and instantiates an Http provider.
@@ -85,3 +122,979 @@ async fn test_mempool_executor_sends_tx_simple() {
     let tx = provider.get_transaction_count(account, None).await.unwrap();
     assert_eq!(tx, 1.into());
 }
+
+/// Returns the wallet of the first anvil account.
+fn anvil_wallet(anvil: &AnvilInstance) -> LocalWallet {
+    LocalWallet::from(anvil.keys()[0].clone()).with_chain_id(anvil.chain_id())
+}
+
+/// Returns a transfer from the first anvil account to itself.
+fn anvil_transfer(anvil: &AnvilInstance) -> TypedTransaction {
+    let account = anvil.addresses()[0];
+    TransactionRequest::new()
+        .from(account)
+        .to(account)
+        .value(1)
+        .nonce(0)
+        .gas(21_000)
+        .gas_price(U256::exp10(9))
+        .chain_id(anvil.chain_id())
+        .into()
+}
+
+/// Test that the MEV-share executor sends `mev_sendBundle` calls, signed with the
+/// flashbots signer, to the relay.
+#[tokio::test]
+async fn test_mev_share_executor_sends_signed_bundles_to_relay() {
+    let (provider, anvil) = spawn_anvil().await;
+    let relay = MockRelay::spawn().await.unwrap();
+    let bundle_hash = H256::repeat_byte(1);
+    relay.respond("mev_sendBundle", json!({ "bundleHash": bundle_hash }));
+    let fb_signer = LocalWallet::new(&mut thread_rng());
+    let executor = MevshareExecutor::from_url(fb_signer.clone(), &relay.url())
+        .with_client(Arc::new(provider.clone()));
+
+    let wallet = anvil_wallet(&anvil);
+    let tx = anvil_transfer(&anvil);
+    let raw = tx.rlp_signed(&wallet.sign_transaction(&tx).await.unwrap());
+    let block = provider.get_block_number().await.unwrap() + 1;
+    let body = vec![
+        BundleTx::TxHash {
+            hash: H256::repeat_byte(2),
+        },
+        BundleTx::Tx {
+            tx: raw.clone(),
+            can_revert: false,
+        },
+    ];
+    let bundles = vec![BundleRequest::make_simple(block, body)];
+    let receipt = Executor::<Bundles>::execute(&executor, bundles)
+        .await
+        .unwrap();
+    assert_eq!(receipt.submissions[0].bundle_hash, Some(bundle_hash));
+
+    let requests = relay.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].flashbots_signer().unwrap(), fb_signer.address());
+    let call = requests[0].json().unwrap();
+    assert_eq!(call["method"], "mev_sendBundle");
+    let bundle = &call["params"][0];
+    assert_eq!(bundle["inclusion"]["block"], json!(block));
+    assert_eq!(bundle["body"][0]["hash"], json!(H256::repeat_byte(2)));
+    assert_eq!(bundle["body"][1]["tx"], json!(raw));
+    assert_eq!(bundle["body"][1]["canRevert"], json!(false));
+}
+
+/// Test that the flashbots executor signs the txs of bundles, and sends them with
+/// `eth_sendBundle`, signed with the flashbots signer, for each target block.
+#[tokio::test]
+async fn test_flashbots_executor_sends_signed_bundles_to_relay() {
+    let (provider, anvil) = spawn_anvil().await;
+    let relay = MockRelay::spawn().await.unwrap();
+    relay.respond(
+        "eth_sendBundle",
+        json!({ "bundleHash": H256::repeat_byte(1) }),
+    );
+    let wallet = anvil_wallet(&anvil);
+    let fb_signer = LocalWallet::new(&mut thread_rng());
+    let executor = FlashbotsExecutor::new(
+        Arc::new(provider.clone()),
+        wallet.clone(),
+        fb_signer.clone(),
+        Url::parse(&relay.url()).unwrap(),
+        "mock",
+    )
+    .with_simulation(SimulationMode::Skip)
+    .with_target_blocks(2);
+
+    let tx = anvil_transfer(&anvil);
+    let raw = tx.rlp_signed(&wallet.sign_transaction(&tx).await.unwrap());
+    let action: FlashbotsBundle = vec![tx];
+    let receipt = executor.execute(action).await.unwrap();
+    assert_eq!(receipt.submissions.len(), 2);
+    assert!(receipt.submissions.iter().all(|s| s.destination == "mock"));
+
+    let requests = relay.requests();
+    assert_eq!(requests.len(), 2);
+    let mut blocks = vec![];
+    for request in requests {
+        assert_eq!(request.flashbots_signer().unwrap(), fb_signer.address());
+        let call = request.json().unwrap();
+        assert_eq!(call["method"], "eth_sendBundle");
+        assert_eq!(call["params"][0]["txs"], json!([raw]));
+        blocks.push(call["params"][0]["blockNumber"].clone());
+    }
+    blocks.dedup();
+    assert_eq!(blocks.len(), 2);
+}
+
+/// Test that a bundle the relay failed to accept is sent again, while a duplicate of
+/// an accepted bundle isn't.
+#[tokio::test]
+async fn test_mev_share_executor_resends_bundles_the_relay_failed() {
+    let relay = MockRelay::spawn().await.unwrap();
+    relay.respond(
+        "mev_sendBundle",
+        json!({ "bundleHash": H256::repeat_byte(1) }),
+    );
+    relay.fail_next(1);
+    let executor = MevshareExecutor::from_url(LocalWallet::new(&mut thread_rng()), &relay.url());
+    let bundle = BundleRequest::make_simple(
+        U64::from(1),
+        vec![BundleTx::Tx {
+            tx: Bytes::from(vec![1]),
+            can_revert: false,
+        }],
+    );
+
+    let failed = Executor::<Bundles>::execute(&executor, vec![bundle.clone()])
+        .await
+        .unwrap();
+    assert!(failed.submissions[0].error.is_some());
+    let retried = Executor::<Bundles>::execute(&executor, vec![bundle.clone()])
+        .await
+        .unwrap();
+    assert!(retried.submissions[0].error.is_none());
+    let duplicate = Executor::<Bundles>::execute(&executor, vec![bundle])
+        .await
+        .unwrap();
+    assert!(duplicate.submissions.is_empty());
+    assert_eq!(relay.requests().len(), 2);
+}
+
+/// Test that a circuit breaker stops sending bundles to a relay which keeps failing,
+/// and probes it again once the probe interval has passed.
+#[tokio::test]
+async fn test_circuit_breaker_stops_sending_to_failing_relay() {
+    let relay = MockRelay::spawn().await.unwrap();
+    relay.respond(
+        "mev_sendBundle",
+        json!({ "bundleHash": H256::repeat_byte(1) }),
+    );
+    relay.fail_next(2);
+    let executor = MevshareExecutor::from_url(LocalWallet::new(&mut thread_rng()), &relay.url());
+    let executor =
+        CircuitBreakerExecutor::new(executor, 2).with_probe_interval(Duration::from_millis(100));
+    let bundle = |byte: u8| {
+        vec![BundleRequest::make_simple(
+            U64::from(1),
+            vec![BundleTx::Tx {
+                tx: Bytes::from(vec![byte]),
+                can_revert: false,
+            }],
+        )]
+    };
+
+    for byte in 0..3 {
+        Executor::<Bundles>::execute(&executor, bundle(byte))
+            .await
+            .unwrap();
+    }
+    assert_eq!(executor.state(), CircuitState::Open);
+    assert_eq!(relay.requests().len(), 2);
+
+    sleep(Duration::from_millis(150)).await;
+    let probe = Executor::<Bundles>::execute(&executor, bundle(3))
+        .await
+        .unwrap();
+    assert!(probe.submissions[0].error.is_none());
+    assert_eq!(executor.state(), CircuitState::Closed);
+    assert_eq!(relay.requests().len(), 3);
+}
+
+/// Strategy that doubles every even event, and ignores odd ones.
+struct DoubleEvens;
+
+#[async_trait]
+impl Strategy<u64, u64> for DoubleEvens {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Option<u64> {
+        (event % 2 == 0).then_some(event * 2)
+    }
+}
+
+/// Test that events pushed into a mock collector flow through the engine to a capturing executor.
+#[tokio::test]
+async fn test_engine_with_mock_collector_and_capturing_executor() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(executor.clone()));
+
+    for event in 0..6 {
+        sender.send(event).unwrap();
+    }
+
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![0, 4, 8]);
+}
+
+/// Test that events and actions which fail to map are dropped, and the others mapped
+/// asynchronously.
+#[tokio::test]
+async fn test_try_collector_map_and_async_executor_map() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let parse = |event: &'static str| async move {
+        event
+            .parse::<u64>()
+            .map_err(|e| ArtemisError::Other(e.into()))
+    };
+    let collector = TryCollectorMap::new(Box::new(collector), parse);
+    let executor = CapturingExecutor::new();
+    let checked = |action: u64| async move {
+        sleep(Duration::from_millis(1)).await;
+        if action >= 10 {
+            return Err(ArtemisError::Rejected(format!(
+                "action {} out of range",
+                action
+            )));
+        }
+        Ok(action)
+    };
+    let mapped = AsyncExecutorMap::new(Box::new(executor.clone()), checked);
+
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(mapped));
+
+    for event in ["2", "x", "4", "6"] {
+        sender.send(event).unwrap();
+    }
+
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![4, 8]);
+}
+
+/// Strategy which forwards each event as an action on the other chain.
+struct CrossChainEcho;
+
+#[async_trait]
+impl Strategy<ChainTagged<u64>, ChainTagged<u64>> for CrossChainEcho {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: ChainTagged<u64>) -> Option<ChainTagged<u64>> {
+        let chain = match event.chain {
+            Chain::Mainnet => Chain::Arbitrum,
+            _ => Chain::Mainnet,
+        };
+        Some(ChainTagged::new(chain, event.inner))
+    }
+}
+
+/// Test that chain tagged events from collectors on different chains are routed to the executor of the right chain.
+#[tokio::test]
+async fn test_engine_routes_chain_tagged_actions_to_chain_executors() {
+    let mainnet_collector = MockCollector::new();
+    let arbitrum_collector = MockCollector::new();
+    let mainnet_sender = mainnet_collector.sender();
+    let arbitrum_sender = arbitrum_collector.sender();
+    let mainnet_executor = CapturingExecutor::new();
+    let arbitrum_executor = CapturingExecutor::new();
+    let all_actions = CapturingExecutor::new();
+
+    let mut engine: Engine<ChainTagged<u64>, ChainTagged<u64>> = Engine::new();
+    engine.add_collector(Box::new(ChainCollector::new(
+        Chain::Mainnet,
+        Box::new(mainnet_collector),
+    )));
+    engine.add_collector(Box::new(ChainCollector::new(
+        Chain::Arbitrum,
+        Box::new(arbitrum_collector),
+    )));
+    engine.add_strategy(Box::new(CrossChainEcho));
+    engine.add_executor(Box::new(ChainExecutor::new(
+        Chain::Mainnet,
+        Box::new(mainnet_executor.clone()),
+    )));
+    engine.add_executor(Box::new(ChainExecutor::new(
+        Chain::Arbitrum,
+        Box::new(arbitrum_executor.clone()),
+    )));
+    engine.add_executor(Box::new(all_actions.clone()));
+
+    mainnet_sender.send(1).unwrap();
+    arbitrum_sender.send(2).unwrap();
+
+    run_to_quiescence(engine, &all_actions, Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(mainnet_executor.actions(), vec![2]);
+    assert_eq!(arbitrum_executor.actions(), vec![1]);
+}
+
+/// Strategy which takes a while to process each event, and forwards it as an action.
+struct SlowEcho;
+
+#[async_trait]
+impl Strategy<u64, u64> for SlowEcho {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Option<u64> {
+        sleep(Duration::from_millis(100)).await;
+        Some(event)
+    }
+}
+
+/// Test that filtered strategies only see matching events, and chained strategies
+/// process the actions of the previous one.
+#[tokio::test]
+async fn test_filter_and_chain_strategies() {
+    let mut filtered = FilterStrategy::new(Box::new(DoubleEvens), |event: &u64| *event > 2);
+    assert_eq!(filtered.process_event(2).await, None);
+    assert_eq!(filtered.process_event(4).await, Some(8));
+
+    let mut chained = ChainStrategy::new(Box::new(DoubleEvens), Box::new(SlowEcho));
+    assert_eq!(chained.process_event(3).await, None);
+    assert_eq!(chained.process_event(4).await, Some(8));
+}
+
+/// Test that mapped strategies only see the events which map to theirs, and have their
+/// actions mapped.
+#[tokio::test]
+async fn test_strategy_map() {
+    let mut mapped = StrategyMap::new(
+        Box::new(DoubleEvens),
+        |event: i64| u64::try_from(event).ok(),
+        |action: u64| action.to_string(),
+    );
+    assert_eq!(mapped.process_event(-2).await, None);
+    assert_eq!(mapped.process_event(3).await, None);
+    assert_eq!(mapped.process_event(4).await, Some("8".to_string()));
+}
+
+/// Test that racing strategies emit the action of the first strategy responding with one.
+#[tokio::test]
+async fn test_race_strategy_emits_first_action() {
+    let mut race: RaceStrategy<u64, u64> =
+        RaceStrategy::new(vec![Box::new(SlowEcho), Box::new(DoubleEvens)]);
+    race.sync_state().await.unwrap();
+
+    // DoubleEvens responds first.
+    assert_eq!(race.process_event(2).await, Some(4));
+    // DoubleEvens ignores odd events, so SlowEcho's action is emitted.
+    assert_eq!(race.process_event(3).await, Some(3));
+}
+
+/// Test that events and actions are dropped once they are older than the max event age.
+#[tokio::test]
+async fn test_engine_drops_stale_events() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new().with_max_event_age(Duration::from_millis(250));
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(SlowEcho));
+    engine.add_executor(Box::new(executor.clone()));
+
+    for event in 0..5 {
+        sender.send(event).unwrap();
+    }
+
+    // Event 2 is processed in time, but its action is already stale when it reaches the
+    // executor, and later events are stale before they reach the strategy.
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(400))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![0, 1]);
+}
+
+/// Test that a strategy falling behind the event channel reports the events it missed,
+/// and the events it processed slower than its deadline.
+#[tokio::test]
+async fn test_engine_reports_slow_strategies() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new()
+        .with_event_channel_capacity(2)
+        .with_strategy_deadline(Duration::from_millis(50));
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(SlowEcho));
+    engine.add_executor(Box::new(executor.clone()));
+    let health = engine.health();
+
+    for event in 0..6 {
+        sender.send(event).unwrap();
+    }
+
+    // Only the last two events fit in the channel by the time the strategy reads it.
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(400))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![4, 5]);
+    let metrics = &health.report().strategies[0];
+    assert_eq!(metrics.events, 2);
+    assert_eq!(metrics.lagged, 4);
+    assert_eq!(metrics.deadline_overruns, 2);
+    assert!(metrics.mean_processing_ms >= 100.0);
+}
+
+/// Concurrent strategy forwarding events as actions. Events from 100 are sequential,
+/// and processed quickly, while the others take longer the smaller they are.
+#[derive(Default)]
+struct ConcurrentEcho {
+    processing: AtomicUsize,
+    /// Whether a sequential event was processed alongside another one.
+    overlapped: AtomicBool,
+}
+
+#[async_trait]
+impl ConcurrentStrategy<u64, u64> for ConcurrentEcho {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&self, event: u64) -> Option<u64> {
+        let processing = self.processing.fetch_add(1, Ordering::SeqCst) + 1;
+        if event >= 100 {
+            self.overlapped.fetch_or(processing > 1, Ordering::SeqCst);
+            sleep(Duration::from_millis(10)).await;
+        } else {
+            let delay_ms = [200, 120, 60, 30][event as usize % 4];
+            sleep(Duration::from_millis(delay_ms)).await;
+        }
+        self.processing.fetch_sub(1, Ordering::SeqCst);
+        Some(event)
+    }
+
+    fn is_sequential(&self, event: &u64) -> bool {
+        *event >= 100
+    }
+}
+
+/// Run a concurrent echo strategy over `events`, returning the actions it produced and
+/// whether a sequential event overlapped another one.
+async fn run_concurrent_echo(events: &[u64], concurrency: Concurrency) -> (Vec<u64>, bool) {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+    let strategy = Arc::new(ConcurrentEcho::default());
+
+    struct Shared(Arc<ConcurrentEcho>);
+
+    #[async_trait]
+    impl ConcurrentStrategy<u64, u64> for Shared {
+        async fn sync_state(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn process_event(&self, event: u64) -> Option<u64> {
+            self.0.process_event(event).await
+        }
+
+        fn is_sequential(&self, event: &u64) -> bool {
+            self.0.is_sequential(event)
+        }
+    }
+
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(collector));
+    engine.add_concurrent_strategy(Box::new(Shared(strategy.clone())), concurrency);
+    engine.add_executor(Box::new(executor.clone()));
+
+    for event in events {
+        sender.send(*event).unwrap();
+    }
+
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(300))
+        .await
+        .unwrap();
+    (actions, strategy.overlapped.load(Ordering::SeqCst))
+}
+
+/// Test that a concurrent strategy processes events at once, sending actions as they
+/// are produced or in event order.
+#[tokio::test]
+async fn test_engine_processes_events_concurrently() {
+    let (actions, _) = run_concurrent_echo(&[0, 1, 2, 3], Concurrency::new(4)).await;
+    assert_eq!(actions, vec![3, 2, 1, 0]);
+
+    let concurrency = Concurrency::new(4).with_action_order(ActionOrder::Event);
+    let (actions, _) = run_concurrent_echo(&[0, 1, 2, 3], concurrency).await;
+    assert_eq!(actions, vec![0, 1, 2, 3]);
+
+    // With room for two events at once, event 2 waits for 1 and event 3 for 0.
+    let (actions, _) = run_concurrent_echo(&[0, 1, 2, 3], Concurrency::new(2)).await;
+    assert_eq!(actions, vec![1, 2, 0, 3]);
+}
+
+/// Test that sequential events are processed on their own, between the events received
+/// before and after them.
+#[tokio::test]
+async fn test_engine_processes_sequential_events_alone() {
+    let (actions, overlapped) = run_concurrent_echo(&[0, 1, 100, 2, 3], Concurrency::new(4)).await;
+    assert_eq!(actions, vec![1, 0, 100, 3, 2]);
+    assert!(!overlapped);
+}
+
+/// Test that in dry-run mode actions only reach the noop executor's simulator.
+#[tokio::test]
+async fn test_engine_dry_run_skips_executors() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+    let simulator = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> =
+        Engine::new().with_dry_run(NoopExecutor::new().with_simulator(Box::new(simulator.clone())));
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(executor.clone()));
+
+    for event in 0..4 {
+        sender.send(event).unwrap();
+    }
+
+    let simulated = run_to_quiescence(engine, &simulator, Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(simulated, vec![0, 4]);
+    assert!(executor.is_empty());
+}
+
+/// Test that a paused strategy drops events until it is resumed.
+#[tokio::test]
+async fn test_engine_pauses_and_resumes_strategy() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(executor.clone()));
+    let control = engine.control();
+
+    let _running = engine.run().await.unwrap();
+    let strategy = control.strategy(0).unwrap();
+    strategy.set_paused(true);
+    sender.send(2).unwrap();
+    sleep(Duration::from_millis(100)).await;
+    assert!(executor.is_empty());
+
+    strategy.set_paused(false);
+    sender.send(4).unwrap();
+    let actions = wait_for_actions(&executor, 1, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![8]);
+}
+
+/// Strategy which echoes events, but panics processing event 13 the first `panics`
+/// times.
+struct PanicsOn13 {
+    panics: usize,
+    syncs: Arc<AtomicUsize>,
+}
+
+impl PanicsOn13 {
+    fn new(panics: usize) -> Self {
+        Self {
+            panics,
+            syncs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for PanicsOn13 {
+    async fn sync_state(&mut self) -> Result<()> {
+        self.syncs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Option<u64> {
+        if event == 13 && self.panics > 0 {
+            self.panics -= 1;
+            panic!("unlucky event");
+        }
+        Some(event)
+    }
+}
+
+/// Test that the running engine reports the tasks which exited, and why, once it is
+/// shut down.
+#[tokio::test]
+async fn test_running_engine_reports_task_exits() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new();
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(PanicsOn13::new(1)));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let mut running = engine.run().await.unwrap();
+    let mut tasks = running.tasks();
+    tasks.sort_by_key(|task| task.to_string());
+    assert_eq!(
+        tasks,
+        vec![
+            EngineTask::Collector(0),
+            EngineTask::Executor(0),
+            EngineTask::Strategy(0)
+        ]
+    );
+
+    // The strategy isn't restarted, so it stops on the first panic.
+    sender.send(13).unwrap();
+    let (task, exit) = running.join_next().await.unwrap();
+    assert_eq!(task, EngineTask::Strategy(0));
+    assert_eq!(
+        exit,
+        TaskExit::Stopped("panicked after 0 restarts: unlucky event".to_string())
+    );
+    assert!(!running.stop(EngineTask::Strategy(0)));
+    assert!(running.stop(EngineTask::Executor(0)));
+    assert_eq!(
+        running.join_next().await,
+        Some((EngineTask::Executor(0), TaskExit::Cancelled))
+    );
+
+    running.shutdown();
+    assert_eq!(
+        running.wait().await,
+        Err(EngineReport {
+            failures: vec![(EngineTask::Strategy(0), exit)],
+        })
+    );
+}
+
+/// Restart panicking strategies once, right away.
+fn restart_once() -> RestartPolicy {
+    RestartPolicy::new(1).with_backoff(Duration::ZERO, Duration::ZERO)
+}
+
+/// Test that a strategy which panics is restarted, and caught up with the events
+/// collected before the one it panicked on.
+#[tokio::test]
+async fn test_engine_replays_events_to_restarted_strategy() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+    let strategy = PanicsOn13::new(1);
+    let syncs = strategy.syncs.clone();
+
+    let mut engine: Engine<u64, u64> = Engine::new()
+        .with_event_replay(3)
+        .with_restart_policy(restart_once());
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(strategy));
+    engine.add_executor(Box::new(executor.clone()));
+    let replay = engine.event_replay().unwrap();
+
+    let _running = engine.run().await.unwrap();
+    for event in [1, 2, 3] {
+        sender.send(event).unwrap();
+    }
+    wait_for_actions(&executor, 3, Duration::from_secs(1))
+        .await
+        .unwrap();
+    sender.send(13).unwrap();
+    wait_for_actions(&executor, 5, Duration::from_secs(1))
+        .await
+        .unwrap();
+    sender.send(5).unwrap();
+    let actions = wait_for_actions(&executor, 6, Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    assert_eq!(actions, vec![1, 2, 3, 2, 3, 5]);
+    assert_eq!(syncs.load(Ordering::SeqCst), 2);
+    assert_eq!(replay.events(), vec![3, 13, 5]);
+}
+
+/// Test that restarts of a panicking strategy are sent back to strategies, and that
+/// the strategy is stopped once it panicked more times than it can be restarted.
+#[tokio::test]
+async fn test_engine_restarts_panicking_strategy_until_limit() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+    let strategy = PanicsOn13::new(usize::MAX);
+    let syncs = strategy.syncs.clone();
+
+    // Every restart becomes event 100 + the number of restarts.
+    let mut engine: Engine<u64, u64> = Engine::new()
+        .with_restart_policy(restart_once())
+        .with_restart_alerts(|restart: StrategyRestart| {
+            assert_eq!(restart.panic, "unlucky event");
+            100 + restart.restarts as u64
+        });
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(strategy));
+    engine.add_executor(Box::new(executor.clone()));
+    let health = engine.health();
+
+    let _running = engine.run().await.unwrap();
+    sender.send(13).unwrap();
+    let actions = wait_for_actions(&executor, 1, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![101]);
+    assert_eq!(syncs.load(Ordering::SeqCst), 2);
+
+    sender.send(13).unwrap();
+    sender.send(1).unwrap();
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(executor.actions(), vec![101]);
+    let report = health.report();
+    assert_eq!(report.strategies[0].restarts, 1);
+    assert!(report.strategies[0].stopped);
+    assert!(!report.is_live());
+}
+
+/// Collector whose every stream emits the number of streams created so far, then
+/// stalls.
+#[derive(Default)]
+struct StallingCollector {
+    streams: AtomicUsize,
+}
+
+#[async_trait]
+impl Collector<u64> for StallingCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let stream = self.streams.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+        Ok(Box::pin(
+            futures::stream::once(async move { stream }).chain(futures::stream::pending()),
+        ))
+    }
+}
+
+impl RestartableCollector<u64> for StallingCollector {}
+
+/// Test that a collector whose stream stalls is restarted, and its restarts sent to
+/// strategies.
+#[tokio::test]
+async fn test_engine_restarts_stalled_collector() {
+    let executor = CapturingExecutor::new();
+
+    // Every restart becomes event 100 + the number of restarts.
+    let mut engine: Engine<u64, u64> =
+        Engine::new().with_collector_restart_alerts(|restart: CollectorRestart| {
+            assert_eq!(restart.stalled_for, Duration::from_millis(50));
+            100 + restart.restarts as u64
+        });
+    engine.add_restartable_collector(
+        Box::new(StallingCollector::default()),
+        Duration::from_millis(50),
+    );
+    engine.add_strategy(Box::new(PanicsOn13::new(0)));
+    engine.add_executor(Box::new(executor.clone()));
+    let health = engine.health();
+
+    let _running = engine.run().await.unwrap();
+    let actions = wait_for_actions(&executor, 4, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions[..4], [1, 101, 2, 102]);
+    assert!(health.report().collectors[0].restarts >= 2);
+}
+
+/// Strategy which echoes events, but hangs processing event 7 the first `hangs` times.
+struct HangsOn7 {
+    hangs: usize,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for HangsOn7 {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Option<u64> {
+        if event == 7 && self.hangs > 0 {
+            self.hangs -= 1;
+            std::future::pending::<()>().await;
+        }
+        Some(event)
+    }
+}
+
+/// Test that a strategy hanging on an event gives up on it once it timed out, after
+/// retrying it, and keeps processing later events.
+#[tokio::test]
+async fn test_engine_times_out_hanging_events() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    let mut engine: Engine<u64, u64> = Engine::new()
+        .with_event_timeout(EventTimeout::new(Duration::from_millis(50)).with_retries(1));
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(HangsOn7 { hangs: 3 }));
+    engine.add_executor(Box::new(executor.clone()));
+    let health = engine.health();
+
+    let _running = engine.run().await.unwrap();
+    // Both attempts at the first event 7 time out, while the retry of the second one
+    // doesn't.
+    for event in [7, 1, 7, 2] {
+        sender.send(event).unwrap();
+    }
+    let actions = wait_for_actions(&executor, 3, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![1, 7, 2]);
+    assert_eq!(health.report().strategies[0].timeouts, 3);
+}
+
+/// Executor which submits actions below 100 to a relay, and drops the others.
+#[derive(Clone)]
+struct RelayExecutor(CapturingExecutor<u64>);
+
+#[async_trait]
+impl Executor<u64> for RelayExecutor {
+    async fn execute(&self, action: u64) -> Result<SubmissionReceipt> {
+        self.0.execute(action).await?;
+        if action >= 100 {
+            return Ok(SubmissionReceipt::default());
+        }
+        Ok(SubmissionReceipt::new(vec![Submission::new(
+            "relay",
+            vec![],
+        )]))
+    }
+}
+
+#[tokio::test]
+async fn test_engine_routes_receipts_back_to_strategies() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    // Every receipt becomes event 100, which the strategy turns into an action.
+    let mut engine: Engine<u64, u64> = Engine::new()
+        .with_receipts(|receipt: SubmissionReceipt| 100 * receipt.submissions.len() as u64);
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(RelayExecutor(executor.clone())));
+
+    let _running = engine.run().await.unwrap();
+    sender.send(2).unwrap();
+    let actions = wait_for_actions(&executor, 2, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![4, 200]);
+}
+
+#[derive(Debug, Clone)]
+enum ChainEvent {
+    Block(NewBlock),
+    Invalidated(usize),
+}
+
+/// Strategy which submits an action for every block, numbered after the block, and
+/// resubmits invalidated submissions as action 10 + their count.
+struct BlockSubmitter;
+
+#[async_trait]
+impl Strategy<ChainEvent, u64> for BlockSubmitter {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: ChainEvent) -> Option<u64> {
+        match event {
+            ChainEvent::Block(block) => Some(block.number.as_u64()),
+            ChainEvent::Invalidated(submissions) => Some(10 + submissions as u64),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_engine_invalidates_submissions_on_reorg() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+    let block = |number: u64, hash: u8, parent_hash: u8| {
+        ChainEvent::Block(NewBlock {
+            hash: H256::repeat_byte(hash),
+            number: U64::from(number),
+            parent_hash: H256::repeat_byte(parent_hash),
+        })
+    };
+
+    let mut engine: Engine<ChainEvent, u64> = Engine::new().with_reorg_invalidation(
+        |event| match event {
+            ChainEvent::Block(block) => Some(block.clone()),
+            _ => None,
+        },
+        |invalidation| ChainEvent::Invalidated(invalidation.submissions.len()),
+    );
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(BlockSubmitter));
+    engine.add_executor(Box::new(RelayExecutor(executor.clone())));
+
+    let _running = engine.run().await.unwrap();
+    sender.send(block(1, 1, 0)).unwrap();
+    wait_for_actions(&executor, 1, Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    // Another block 1 drops the one the first submission was made on.
+    sender.send(block(1, 2, 0)).unwrap();
+    let actions = wait_for_actions(&executor, 3, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![1, 1, 11]);
+}
+
+/// Test that actions are dropped once expired, by block or timestamp, and counted in
+/// the executor's health.
+#[tokio::test]
+async fn test_engine_drops_expired_actions() {
+    let collector = MockCollector::new();
+    let sender = collector.sender();
+    let executor = CapturingExecutor::new();
+
+    // Events of 100 and more are blocks, and actions expire at the block of their
+    // number, except 12, which expired long ago.
+    let mut engine: Engine<u64, u64> = Engine::new().with_action_expiry(
+        |action| match action {
+            12 => Some(Expiry::Timestamp(1)),
+            _ => Some(Expiry::Block(U64::from(*action))),
+        },
+        |event| {
+            (*event >= 100).then(|| NewBlock {
+                hash: H256::zero(),
+                number: U64::from(*event),
+                parent_hash: H256::zero(),
+            })
+        },
+    );
+    let health = engine.health();
+    engine.add_collector(Box::new(collector));
+    engine.add_strategy(Box::new(DoubleEvens));
+    engine.add_executor(Box::new(executor.clone()));
+
+    for event in [60, 100, 60, 40, 6, 70] {
+        sender.send(event).unwrap();
+    }
+
+    let actions = run_to_quiescence(engine, &executor, Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(actions, vec![120, 200, 120, 140]);
+    assert_eq!(health.report().executors[0].expired, 2);
+}
@@ -0,0 +1,211 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use ethers::types::Address;
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+
+use crate::types::{BuilderId, ProtocolVersion, KNOWN_BUILDERS};
+
+/// Public registry of the builders MEV-share bundles can be shared with.
+pub const DEFAULT_BUILDER_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/flashbots/dowg/main/builder-registrations.json";
+
+/// Default interval between two fetches of the builder registry.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A builder bundles can be shared with.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Builder {
+    /// Name the builder is registered under with the matchmaker.
+    pub name: String,
+    /// Address the builder builds blocks with, if known.
+    #[serde(default)]
+    pub address: Option<Address>,
+}
+
+/// Directory of the builders bundles are shared with when they don't name any, kept
+/// up to date with a builder registry. Starts out with the [KNOWN_BUILDERS], so bundles
+/// are shared with them until the registry is first fetched, or if it can't be.
+#[derive(Debug)]
+pub struct BuilderDirectory {
+    http_client: reqwest::Client,
+    registry_url: String,
+    refresh_interval: Duration,
+    builders: RwLock<Vec<Builder>>,
+}
+
+impl Default for BuilderDirectory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuilderDirectory {
+    /// Create a directory of the [KNOWN_BUILDERS], refreshed from the
+    /// [public registry](DEFAULT_BUILDER_REGISTRY_URL) every hour.
+    pub fn new() -> Self {
+        let builders = KNOWN_BUILDERS
+            .iter()
+            .map(|(name, address)| Builder {
+                name: name.to_string(),
+                address: Address::from_str(address).ok(),
+            })
+            .collect();
+        Self {
+            http_client: reqwest::Client::new(),
+            registry_url: DEFAULT_BUILDER_REGISTRY_URL.to_string(),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            builders: RwLock::new(builders),
+        }
+    }
+
+    /// Fetch builders from the registry at `url`, which serves a JSON array of
+    /// builders with their `name`, and optionally their `address`.
+    pub fn with_registry_url(mut self, url: &str) -> Self {
+        self.registry_url = url.to_string();
+        self
+    }
+
+    /// Fetch the registry every `refresh_interval` once
+    /// [spawned](Self::spawn_refresh). Defaults to an hour.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// The builders in the directory.
+    pub fn builders(&self) -> Vec<Builder> {
+        self.builders.read().unwrap().clone()
+    }
+
+    /// The builders in the directory, identified as bundles of `version` expect them:
+    /// by address in [beta-1](ProtocolVersion::Beta1) bundles when it is known, and by
+    /// name otherwise.
+    pub fn builder_ids(&self, version: &ProtocolVersion) -> Vec<BuilderId> {
+        self.builders
+            .read()
+            .unwrap()
+            .iter()
+            .map(|builder| match (version, builder.address) {
+                (ProtocolVersion::Beta1, Some(address)) => BuilderId::Address(address),
+                _ => BuilderId::Name(builder.name.clone()),
+            })
+            .collect()
+    }
+
+    /// Fetch the registry and replace the builders in the directory with the ones it
+    /// lists, returning how many it lists. Builders listed without an address keep the
+    /// one the directory knew them by. An empty registry leaves the directory as is.
+    pub async fn refresh(&self) -> Result<usize, reqwest::Error> {
+        let registered: Vec<Builder> = self
+            .http_client
+            .get(&self.registry_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if registered.is_empty() {
+            return Ok(0);
+        }
+
+        let mut builders = self.builders.write().unwrap();
+        let registered: Vec<Builder> = registered
+            .into_iter()
+            .map(|mut builder| {
+                if builder.address.is_none() {
+                    builder.address = builders
+                        .iter()
+                        .find(|known| known.name == builder.name)
+                        .and_then(|known| known.address);
+                }
+                builder
+            })
+            .collect();
+        *builders = registered;
+        Ok(builders.len())
+    }
+
+    /// Refresh the directory every refresh interval in the background, until the
+    /// returned handle is aborted. Failed fetches keep the current builders.
+    pub fn spawn_refresh(self: &Arc<Self>) -> JoinHandle<()> {
+        let directory = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(directory.refresh_interval);
+            loop {
+                interval.tick().await;
+                let _ = directory.refresh().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    /// Serve `bodies` to consecutive requests.
+    async fn serve(bodies: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            for body in bodies {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let response = format!(
+                    concat!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n",
+                        "Content-Length: {}\r\nConnection: close\r\n\r\n{}"
+                    ),
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn refreshes_builders_from_registry() {
+        let url = serve(vec![
+            r#"[
+                {"name": "flashbots", "rpc": "https://relay.flashbots.net"},
+                {"name": "newbuilder", "address": "0x0000000000000000000000000000000000000042"},
+                {"name": "unaddressed"}
+            ]"#,
+            "[]",
+        ])
+        .await;
+        let directory = BuilderDirectory::new().with_registry_url(&url);
+        assert_eq!(directory.builders().len(), KNOWN_BUILDERS.len());
+
+        assert_eq!(directory.refresh().await.unwrap(), 3);
+        let flashbots = BuilderId::from("flashbots").address().unwrap();
+        let newbuilder = Address::from_low_u64_be(0x42);
+        assert_eq!(
+            directory.builder_ids(&ProtocolVersion::Beta1),
+            vec![
+                BuilderId::Address(flashbots),
+                BuilderId::Address(newbuilder),
+                BuilderId::Name("unaddressed".to_string()),
+            ]
+        );
+        assert_eq!(
+            directory.builder_ids(&ProtocolVersion::V0_1),
+            vec![
+                BuilderId::from("flashbots"),
+                BuilderId::from("newbuilder"),
+                BuilderId::from("unaddressed"),
+            ]
+        );
+
+        // An empty registry doesn't clear the directory.
+        assert_eq!(directory.refresh().await.unwrap(), 0);
+        assert_eq!(directory.builders().len(), 3);
+    }
+}
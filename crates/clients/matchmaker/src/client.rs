@@ -1,22 +1,32 @@
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
-use ethers::{signers::Signer, types::Chain};
+use ethers::{
+    signers::Signer,
+    types::{Chain, H256, U64},
+};
 
 use jsonrpsee::core::client::ClientT;
-use jsonrpsee::core::Error as RpcError;
+/// Error of a request to the matchmaker.
+pub use jsonrpsee::core::Error as RpcError;
 use jsonrpsee::http_client::{transport::HttpBackend, HttpClient, HttpClientBuilder};
 
 use tower::ServiceBuilder;
 
 use crate::{
+    builders::BuilderDirectory,
     flashbots_signer::{FlashbotsSigner, FlashbotsSignerLayer},
-    types::{BundleRequest, SendBundleResponse},
+    history::HistoryClient,
+    types::{BundleRequest, BundleStats, BundleStatsRequest, SendBundleResponse},
 };
 
 /// Matchmaker client to interact with MEV-share
 pub struct Client<S> {
     /// Underlying HTTP client
     pub http_client: HttpClient<FlashbotsSigner<S, HttpBackend>>,
+    /// Client for the hints of past blocks
+    pub history_client: HistoryClient,
+    /// Builders bundles which don't name any are shared with
+    pub builder_directory: Arc<BuilderDirectory>,
     
 
 }
@@ -29,10 +39,14 @@ impl<S: Signer + Clone + 'static> Client<S> {
             Chain::Goerli => "https://relay-goerli.flashbots.net:443",
             _ => panic!("Unsupported chain"),
         };
-        Self::from_url(signer, url)
+        Self {
+            history_client: HistoryClient::new(chain),
+            ..Self::from_url(signer, url)
+        }
     }
 
-    /// Create a new client with the given signer and url
+    /// Create a new client with the given signer and url. Hints of past blocks are
+    /// fetched from mainnet, see [with_history_client](Self::with_history_client).
     pub fn from_url(signer: S, url: &str) -> Self {
         let signing_middleware = FlashbotsSignerLayer::new(Arc::new(signer));
 
@@ -45,19 +59,65 @@ impl<S: Signer + Clone + 'static> Client<S> {
 
 
 
-        Self { http_client }
+        Self {
+            http_client,
+            history_client: HistoryClient::new(Chain::Mainnet),
+            builder_directory: Arc::new(BuilderDirectory::new()),
+        }
+    }
+
+    /// Fetch the hints of past blocks with the given client
+    pub fn with_history_client(mut self, history_client: HistoryClient) -> Self {
+        self.history_client = history_client;
+        self
+    }
+
+    /// Share bundles which don't name builders with the builders in `builder_directory`,
+    /// e.g. one [refreshed](BuilderDirectory::spawn_refresh) from a builder registry,
+    /// instead of the [known builders](crate::types::KNOWN_BUILDERS)
+    pub fn with_builder_directory(mut self, builder_directory: Arc<BuilderDirectory>) -> Self {
+        self.builder_directory = builder_directory;
+        self
     }
 
-    /// Send a bundle to the matchmaker
+    /// Send a bundle to the matchmaker. Bundles which don't name builders are shared
+    /// with the builders in the builder directory at the time they are sent
     pub async fn send_bundle(
         &self,
         bundle: &BundleRequest,
     ) -> Result<SendBundleResponse, RpcError> {
-                    
+        let bundle = self.with_directory_builders(bundle);
         self.http_client.request("mev_sendBundle", [bundle]).await
         
         
     }
-}
 
+    /// Returns `bundle`, shared with the builders in the directory if it names none
+    fn with_directory_builders<'a>(&self, bundle: &'a BundleRequest) -> Cow<'a, BundleRequest> {
+        let names_builders = bundle
+            .privacy
+            .as_ref()
+            .is_some_and(|privacy| privacy.builders.is_some());
+        if names_builders {
+            return Cow::Borrowed(bundle);
+        }
+        let builders = self.builder_directory.builder_ids(&bundle.version);
+        Cow::Owned(bundle.clone().with_builders(builders))
+    }
 
+    /// Get the stats of a sent bundle targeting `block_number`: whether the relay
+    /// received and simulated it, and which builders considered and sealed it
+    pub async fn get_bundle_stats(
+        &self,
+        bundle_hash: H256,
+        block_number: U64,
+    ) -> Result<BundleStats, RpcError> {
+        let request = BundleStatsRequest {
+            bundle_hash,
+            block_number,
+        };
+        self.http_client
+            .request("flashbots_getBundleStatsV2", [request])
+            .await
+    }
+}
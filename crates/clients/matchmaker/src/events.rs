@@ -0,0 +1,223 @@
+use std::{collections::VecDeque, fmt, pin::Pin, time::Duration};
+
+use ethers::types::Chain;
+use futures::{stream, Stream, StreamExt};
+use hyper::body::Bytes;
+use reqwest::header::ACCEPT;
+
+use crate::types::Hint;
+
+/// How long to wait before reconnecting to the event stream by default.
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Error received from an [EventClient] subscription. Subscriptions carry on after
+/// errors.
+#[derive(Debug)]
+pub enum EventError {
+    /// Connecting to the event stream failed, or the connection dropped.
+    Connection(reqwest::Error),
+    /// An event couldn't be parsed as a [Hint].
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::Connection(e) => write!(f, "event stream connection error: {}", e),
+            EventError::Parse(e) => write!(f, "error parsing event: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+/// Client for the server-sent event stream of the hints the matchmaker broadcasts
+#[derive(Debug, Clone)]
+pub struct EventClient {
+    http_client: reqwest::Client,
+    url: String,
+    reconnect_delay: Duration,
+}
+
+impl EventClient {
+    /// Create a new client for the event stream of the given chain
+    pub fn new(chain: Chain) -> Self {
+        let url = match chain {
+            Chain::Mainnet => "https://mev-share.flashbots.net",
+            Chain::Goerli => "https://mev-share-goerli.flashbots.net",
+            _ => panic!("Unsupported chain"),
+        };
+        Self::from_url(url)
+    }
+
+    /// Create a new client with the url of the event stream
+    pub fn from_url(url: &str) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            url: url.to_string(),
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+        }
+    }
+
+    /// Wait `reconnect_delay` before reconnecting after the connection drops. Defaults
+    /// to 1 second.
+    pub fn with_reconnect_delay(mut self, reconnect_delay: Duration) -> Self {
+        self.reconnect_delay = reconnect_delay;
+        self
+    }
+
+    /// Stream the hints broadcast from now on. The stream reconnects whenever the
+    /// connection fails or drops, yielding the error, and never ends.
+    pub fn subscribe(&self) -> impl Stream<Item = Result<Hint, EventError>> + Send + 'static {
+        let subscription = Subscription {
+            client: self.clone(),
+            connection: None,
+            decoder: SseDecoder::default(),
+            reconnecting: false,
+        };
+        stream::unfold(subscription, |mut subscription| async move {
+            let event = subscription.next().await;
+            Some((event, subscription))
+        })
+    }
+
+    async fn connect(&self) -> Result<Connection, reqwest::Error> {
+        let response = self
+            .http_client
+            .get(&self.url)
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(Box::pin(response.bytes_stream()))
+    }
+}
+
+type Connection = Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>;
+
+/// State of a stream returned by [EventClient::subscribe].
+struct Subscription {
+    client: EventClient,
+    connection: Option<Connection>,
+    decoder: SseDecoder,
+    /// Whether the stream connected before, so it waits before connecting again.
+    reconnecting: bool,
+}
+
+impl Subscription {
+    async fn next(&mut self) -> Result<Hint, EventError> {
+        loop {
+            if let Some(data) = self.decoder.next_event() {
+                return serde_json::from_str(&data).map_err(EventError::Parse);
+            }
+            let connection = match &mut self.connection {
+                Some(connection) => connection,
+                None => {
+                    if self.reconnecting {
+                        tokio::time::sleep(self.client.reconnect_delay).await;
+                    }
+                    self.reconnecting = true;
+                    self.connection = Some(
+                        self.client
+                            .connect()
+                            .await
+                            .map_err(EventError::Connection)?,
+                    );
+                    continue;
+                }
+            };
+            match connection.next().await {
+                Some(Ok(chunk)) => self.decoder.push(&chunk),
+                Some(Err(e)) => {
+                    self.disconnect();
+                    return Err(EventError::Connection(e));
+                }
+                None => self.disconnect(),
+            }
+        }
+    }
+
+    /// Drop the connection, and any partially received event.
+    fn disconnect(&mut self) {
+        self.connection = None;
+        self.decoder = SseDecoder::default();
+    }
+}
+
+/// Splits a server-sent event stream into the data of its events, skipping comments
+/// and fields other than `data`.
+#[derive(Debug, Default)]
+struct SseDecoder {
+    /// Received bytes not yet split into events, without carriage returns.
+    buffer: Vec<u8>,
+    events: VecDeque<String>,
+}
+
+impl SseDecoder {
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend(chunk.iter().filter(|&&byte| byte != b'\r'));
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                self.events.push_back(data.join("\n"));
+            }
+        }
+    }
+
+    fn next_event(&mut self) -> Option<String> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    const HINT: &str = r#"{"hash":"0xc7dc06c994400830054ab815732d91275bc1241f9be62b62b687b7882f19b8d4","logs":null,"txs":[{"to":"0x0000c335bc9d5d1af0402cad63fa7f258363d71a","functionSelector":"0x696d2073"}],"mevGasPrice":"0x3b9aca00","gasUsed":"0x5208"}"#;
+
+    #[test]
+    fn decodes_events_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        decoder.push(b": keep-alive\n\ndata: {\"a\":");
+        assert_eq!(decoder.next_event(), None);
+        decoder.push(b"1}\r\n\r\nretry: 1000\n\ndata: x\ndata:y\n\n");
+        assert_eq!(decoder.next_event().as_deref(), Some(r#"{"a":1}"#));
+        assert_eq!(decoder.next_event().as_deref(), Some("x\ny"));
+        assert_eq!(decoder.next_event(), None);
+    }
+
+    #[tokio::test]
+    async fn streams_hints_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // Every connection gets a hint and a malformed event, then is closed.
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let body = format!("data: {}\n\ndata: {{\n\n", HINT);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let client = EventClient::from_url(&url).with_reconnect_delay(Duration::from_millis(10));
+        let events: Vec<_> = client.subscribe().take(4).collect().await;
+        let hint = events[0].as_ref().unwrap();
+        assert_eq!(hint.txs.len(), 1);
+        assert_eq!(hint.gas_used, Some(21000.into()));
+        assert!(matches!(events[1], Err(EventError::Parse(_))));
+        assert_eq!(events[2].as_ref().unwrap(), hint);
+        assert!(matches!(events[3], Err(EventError::Parse(_))));
+    }
+}
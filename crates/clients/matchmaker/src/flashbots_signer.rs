@@ -1,28 +1,64 @@
 //! A layer responsible for implementing flashbots-style authentication
 //! by signing the request body with a private key and adding the signature
 //! to the request headers.
+//!
+//! Bodies are signed as raw bytes, so the layer works for any endpoint
+//! authenticated this way, not only JSON-RPC ones. Clients which don't build
+//! on tower, e.g. REST clients of builder APIs, can sign their request bodies
+//! with [`sign_payload`] and set the [`FLASHBOTS_SIGNATURE_HEADER`] themselves.
 
 use std::{
     sync::Arc,
     task::{Context, Poll},
 };
 
-use ethers::{signers::Signer, types::H256, utils::keccak256};
+use ethers::{
+    signers::Signer,
+    types::{Signature, H256},
+    utils::keccak256,
+};
 use futures_util::future::BoxFuture;
 
-use http::{header::HeaderValue, HeaderName, Request};
+use http::{
+    header::{HeaderValue, CONTENT_LENGTH, TRANSFER_ENCODING},
+    Request,
+};
 use hyper::Body;
 
 use tower::{Layer, Service};
 
+/// Name of the header carrying the signature of a request body.
+pub const FLASHBOTS_SIGNATURE_HEADER: &str = "x-flashbots-signature";
+
+/// Sign `payload` the way flashbots-style endpoints authenticate requests: the
+/// hex encoded keccak256 hash of the payload is signed as an EIP-191 message.
+/// Returns the signature and the value of the [`FLASHBOTS_SIGNATURE_HEADER`],
+/// `<address>:0x<signature>`.
+///
+/// The payload must be the exact bytes sent as the request body, since the
+/// endpoint hashes the body it receives. Requests without a body, e.g. `GET`s,
+/// sign an empty payload.
+pub async fn sign_payload<S: Signer>(
+    signer: &S,
+    payload: &[u8],
+) -> Result<(Signature, HeaderValue), S::Error> {
+    let signature = signer
+        .sign_message(format!("0x{:x}", H256::from(keccak256(payload))))
+        .await?;
+    let header = HeaderValue::from_str(&format!("{:?}:0x{}", signer.address(), signature))
+        .expect("address and signature are valid header characters");
+    Ok((signature, header))
+}
+
 /// Layer that applies [`FlashbotsSigner`] which adds a request header with a signed payload.
 #[derive(Clone)]
-pub(crate) struct FlashbotsSignerLayer<S> {
-    signer: Arc<S> 
+pub struct FlashbotsSignerLayer<S> {
+    signer: Arc<S>,
 }
 
 impl<S> FlashbotsSignerLayer<S> {
-    pub(crate) fn new(signer: Arc<S>) -> Self {
+    /// Create a layer signing request bodies with `signer`.
+    pub fn new(signer: Arc<S>) -> Self {
         FlashbotsSignerLayer { signer }
     }
 }
@@ -69,21 +105,28 @@ where
         let (mut parts, body) = request.into_parts();
 
         Box::pin(async move {
-            let body_bytes = hyper::body::to_bytes(body).await.unwrap();
+            // buffer the body, so the bytes sent are exactly the bytes signed
+            let body_bytes = hyper::body::to_bytes(body)
+                .await
+                .expect("failed to read request body");
 
             // sign request body and insert header
-            let signature = signer
-
-                .sign_message(format!("0x{:x}", H256::from(keccak256(body_bytes.clone()))))
+            let (_, header_val) = sign_payload(signer.as_ref(), &body_bytes)
                 .await
-                .unwrap();
+                .expect("failed to sign request body");
 
-            let header_name = HeaderName::from_static("x-flashbots-signature");
-            let header_val =
-                HeaderValue::from_str(&format!("{:?}:0x{}", signer.address(), signature)).unwrap();
-            parts.headers.insert(header_name, header_val);
+            // `insert` replaces every existing value, so requests which are
+            // retried or were already signed carry a single signature
+            parts.headers.insert(FLASHBOTS_SIGNATURE_HEADER, header_val);
 
-            let request = Request::from_parts(parts, Body::from(body_bytes.clone()));
+            // the buffered body has a known length: send it as such, rather
+            // than chunked, so the connection can be kept alive and reused
+            parts.headers.remove(TRANSFER_ENCODING);
+            parts
+                .headers
+                .insert(CONTENT_LENGTH, HeaderValue::from(body_bytes.len()));
+
+            let request = Request::from_parts(parts, Body::from(body_bytes));
 
             inner.call(request).await
         })
@@ -148,4 +191,103 @@ mod tests {
         assert_eq!(header_address, signer_address);
         assert_eq!(header_signature, expected_signature);
     }
+
+    /// Signer service whose inner service returns the request it received.
+    fn echo_signer(
+        signer: Arc<LocalWallet>,
+    ) -> impl Service<Request<Body>, Response = Request<Body>, Error = Infallible> + Clone {
+        FlashbotsSignerLayer::new(signer).layer(service_fn(|req: Request<Body>| async {
+            Ok::<_, Infallible>(req)
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_sign_payload_recovers_to_signer() {
+        let fb_signer = LocalWallet::new(&mut thread_rng());
+        let payload = br#"{"jsonrpc":"2.0","id":1,"method":"mev_sendBundle","params":[]}"#;
+
+        let (signature, header) = sign_payload(&fb_signer, payload).await.unwrap();
+
+        let message = format!("0x{:x}", H256::from(keccak256(payload)));
+        assert_eq!(signature.recover(message).unwrap(), fb_signer.address());
+        assert_eq!(
+            header.to_str().unwrap(),
+            format!("{:?}:0x{}", fb_signer.address(), signature)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replaces_existing_signature() {
+        let fb_signer = Arc::new(LocalWallet::new(&mut thread_rng()));
+        let request = Request::builder()
+            .header(FLASHBOTS_SIGNATURE_HEADER, "stale")
+            .header(FLASHBOTS_SIGNATURE_HEADER, "duplicate")
+            .body(Body::from("payload"))
+            .unwrap();
+
+        let req = echo_signer(fb_signer.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        let signatures = req
+            .headers()
+            .get_all(FLASHBOTS_SIGNATURE_HEADER)
+            .iter()
+            .collect::<Vec<_>>();
+        let (_, expected) = sign_payload(fb_signer.as_ref(), b"payload").await.unwrap();
+        assert_eq!(signatures, vec![expected]);
+    }
+
+    #[tokio::test]
+    async fn test_sends_buffered_body_with_content_length() {
+        let fb_signer = Arc::new(LocalWallet::new(&mut thread_rng()));
+        let (mut sender, body) = Body::channel();
+        let request = Request::builder()
+            .method("POST")
+            .header(TRANSFER_ENCODING, "chunked")
+            .body(body)
+            .unwrap();
+
+        let send_chunks = async move {
+            sender.send_data("chunk one, ".into()).await.unwrap();
+            sender.send_data("chunk two".into()).await.unwrap();
+        };
+        let signed = echo_signer(fb_signer.clone()).oneshot(request);
+        let (req, _) = tokio::join!(signed, send_chunks);
+        let req = req.unwrap();
+
+        assert!(req.headers().get(TRANSFER_ENCODING).is_none());
+        assert_eq!(req.headers()[CONTENT_LENGTH], "20");
+        let (_, expected) = sign_payload(fb_signer.as_ref(), b"chunk one, chunk two")
+            .await
+            .unwrap();
+        assert_eq!(req.headers()[FLASHBOTS_SIGNATURE_HEADER], expected);
+        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"chunk one, chunk two");
+    }
+
+    #[tokio::test]
+    async fn test_signs_bodyless_requests() {
+        let fb_signer = Arc::new(LocalWallet::new(&mut thread_rng()));
+        let mut svc = echo_signer(fb_signer.clone());
+
+        // requests sent through the same service are each signed over their own body
+        for body in ["", "second"] {
+            let request = Request::builder()
+                .method("GET")
+                .body(Body::from(body))
+                .unwrap();
+            let req = svc.ready().await.unwrap().call(request).await.unwrap();
+
+            let (_, expected) = sign_payload(fb_signer.as_ref(), body.as_bytes())
+                .await
+                .unwrap();
+            assert_eq!(req.headers()[FLASHBOTS_SIGNATURE_HEADER], expected);
+            assert_eq!(
+                req.headers()[CONTENT_LENGTH],
+                body.len().to_string().as_str()
+            );
+        }
+    }
 }
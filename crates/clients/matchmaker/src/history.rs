@@ -0,0 +1,151 @@
+use ethers::types::Chain;
+use futures::{stream, Stream};
+
+use crate::types::{EventHistory, EventHistoryInfo, EventHistoryParams};
+
+/// Client for the MEV-share event history API, which serves the hints broadcast in
+/// past blocks, e.g. for backtesting strategies.
+#[derive(Debug, Clone)]
+pub struct HistoryClient {
+    http_client: reqwest::Client,
+    url: String,
+}
+
+impl HistoryClient {
+    /// Create a new client for the history of the given chain
+    pub fn new(chain: Chain) -> Self {
+        let url = match chain {
+            Chain::Mainnet => "https://mev-share.flashbots.net/api/v1/history",
+            Chain::Goerli => "https://mev-share-goerli.flashbots.net/api/v1/history",
+            _ => panic!("Unsupported chain"),
+        };
+        Self::from_url(url)
+    }
+
+    /// Create a new client with the url of the history endpoint, under which
+    /// `/info` is served
+    pub fn from_url(url: &str) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Get the range of blocks and timestamps hints are stored for
+    pub async fn event_history_info(&self) -> Result<EventHistoryInfo, reqwest::Error> {
+        self.http_client
+            .get(format!("{}/info", self.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Get a single page of hints matching `params`
+    pub async fn event_history(
+        &self,
+        params: &EventHistoryParams,
+    ) -> Result<Vec<EventHistory>, reqwest::Error> {
+        self.http_client
+            .get(&self.url)
+            .query(params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Get every hint matching `params`, one page at a time. Pages hold `params.limit`
+    /// hints, or as many as the API returns by default, starting from `params.offset`.
+    /// The stream ends after the first empty or partial page, or the first error.
+    pub fn event_history_pages(
+        &self,
+        params: EventHistoryParams,
+    ) -> impl Stream<Item = Result<Vec<EventHistory>, reqwest::Error>> + '_ {
+        stream::unfold(Some(params), move |params| async move {
+            let mut params = params?;
+            let page = match self.event_history(&params).await {
+                Ok(page) => page,
+                Err(e) => return Some((Err(e), None)),
+            };
+            if page.is_empty() {
+                return None;
+            }
+            let is_last = params
+                .limit
+                .is_some_and(|limit| (page.len() as u64) < limit);
+            params.offset = Some(params.offset.unwrap_or_default() + page.len() as u64);
+            Some((Ok(page), (!is_last).then_some(params)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serve `hints` hints from a fake history API, paginated by the request's offset
+    /// and limit. Returns the url of the history endpoint.
+    async fn serve_history(hints: u64) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v1/history", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let param = |name: &str| {
+                    request
+                        .split(['?', '&', ' '])
+                        .find_map(|param| param.strip_prefix(&format!("{}=", name)))
+                        .map(|value| value.parse::<u64>().unwrap())
+                };
+                let offset = param("offset").unwrap_or_default();
+                let limit = param("limit").unwrap_or(100);
+                let page: Vec<_> = (offset..hints.min(offset + limit))
+                    .map(|block| {
+                        format!(
+                            r#"{{"block":{},"timestamp":0,"hint":{{"hash":"0x{:064x}"}}}}"#,
+                            block, block
+                        )
+                    })
+                    .collect();
+                let body = format!("[{}]", page.join(","));
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn pages_through_history() {
+        let client = HistoryClient::from_url(&serve_history(5).await);
+        let pages: Vec<Vec<u64>> = client
+            .event_history_pages(EventHistoryParams::default().with_limit(2))
+            .map_ok(|page| page.into_iter().map(|event| event.block).collect())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(pages, vec![vec![0, 1], vec![2, 3], vec![4]]);
+
+        let pages: Vec<_> = client
+            .event_history_pages(EventHistoryParams::default().with_limit(2).with_offset(4))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 1);
+    }
+}
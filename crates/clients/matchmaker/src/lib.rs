@@ -52,8 +52,17 @@
 //! # });
 //! ```
 
+/// Directory of the builders bundles are shared with
+pub mod builders;
 /// Core client implementation
 pub mod client;
-mod flashbots_signer;
+/// Client for the MEV-share event stream
+pub mod events;
+/// Flashbots-style authentication of requests
+pub mod flashbots_signer;
+/// Client for the MEV-share event history API
+pub mod history;
+/// Tracker of the status of sent bundles
+pub mod status;
 /// Core type definitions for the client
 pub mod types;
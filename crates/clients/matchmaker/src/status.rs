@@ -0,0 +1,338 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{H256, U64},
+};
+use futures::{stream, Stream};
+
+use crate::{
+    client::{Client, RpcError},
+    types::BundleStats,
+};
+
+/// How often the stats of tracked bundles are polled by default.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How far a sent bundle got, from being received by the relay to landing on chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BundleStatus {
+    /// The relay received the bundle.
+    Received,
+    /// The relay simulated the bundle.
+    Simulated,
+    /// A builder sealed a block containing the bundle.
+    SealedByBuilder,
+    /// The bundle landed on chain.
+    Landed,
+    /// The last block the bundle targets was built without it.
+    Missed,
+}
+
+impl BundleStatus {
+    /// Returns the status the stats of a bundle show, if any.
+    pub fn from_stats(stats: &BundleStats) -> Option<Self> {
+        if !stats.sealed_by_builders_at.is_empty() {
+            Some(BundleStatus::SealedByBuilder)
+        } else if stats.is_simulated {
+            Some(BundleStatus::Simulated)
+        } else if stats.received_at.is_some() {
+            Some(BundleStatus::Received)
+        } else {
+            None
+        }
+    }
+}
+
+/// A tracked bundle moving to a new [status](BundleStatus).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusUpdate {
+    /// Hash of the bundle.
+    pub bundle_hash: H256,
+    /// Last block the bundle targets.
+    pub target_block: U64,
+    /// The new status of the bundle.
+    pub status: BundleStatus,
+}
+
+/// Error received from a [BundleStatusTracker] subscription. Subscriptions carry on
+/// after errors.
+#[derive(Debug)]
+pub enum StatusError {
+    /// Fetching the stats of a bundle failed.
+    Stats(RpcError),
+    /// Checking whether a bundle landed failed.
+    Provider(String),
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusError::Stats(e) => write!(f, "error fetching bundle stats: {}", e),
+            StatusError::Provider(e) => write!(f, "error checking bundle inclusion: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// A bundle whose status is tracked.
+#[derive(Clone, Debug)]
+struct TrackedBundle {
+    target_block: U64,
+    /// Hashes of the transactions sent with the bundle, not including the ones it
+    /// backruns.
+    tx_hashes: Vec<H256>,
+    status: Option<BundleStatus>,
+}
+
+/// The bundles a [BundleStatusTracker] tracks, shared with whatever sends them, e.g.
+/// an executor.
+#[derive(Clone, Debug, Default)]
+pub struct TrackedBundles(Arc<Mutex<HashMap<H256, TrackedBundle>>>);
+
+impl TrackedBundles {
+    /// Track the bundle `bundle_hash`, made of `tx_hashes` and valid until
+    /// `target_block`. It landed once the last of `tx_hashes` is included on chain.
+    pub fn track(&self, bundle_hash: H256, target_block: U64, tx_hashes: Vec<H256>) {
+        self.0.lock().unwrap().insert(
+            bundle_hash,
+            TrackedBundle {
+                target_block,
+                tx_hashes,
+                status: None,
+            },
+        );
+    }
+
+    /// Number of bundles tracked.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Whether no bundle is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn snapshot(&self) -> Vec<(H256, TrackedBundle)> {
+        let bundles = self.0.lock().unwrap();
+        bundles
+            .iter()
+            .map(|(hash, bundle)| (*hash, bundle.clone()))
+            .collect()
+    }
+
+    /// Record the new status of a bundle, returning the update if it moved forward, or
+    /// if it missed its last target block. Bundles which landed, or can no longer land,
+    /// aren't tracked anymore.
+    fn update(
+        &self,
+        bundle_hash: H256,
+        status: Option<BundleStatus>,
+        head: U64,
+    ) -> Option<StatusUpdate> {
+        let mut bundles = self.0.lock().unwrap();
+        let bundle = bundles.get_mut(&bundle_hash)?;
+        let target_block = bundle.target_block;
+        // A bundle which didn't land by the time its last target block was built can
+        // no longer land.
+        let status = match status {
+            Some(BundleStatus::Landed) => status,
+            _ if head > target_block => Some(BundleStatus::Missed),
+            _ => status,
+        };
+        let update = match status {
+            Some(status) if Some(status) > bundle.status => {
+                bundle.status = Some(status);
+                Some(StatusUpdate {
+                    bundle_hash,
+                    target_block,
+                    status,
+                })
+            }
+            _ => None,
+        };
+        if matches!(
+            bundle.status,
+            Some(BundleStatus::Landed | BundleStatus::Missed)
+        ) {
+            bundles.remove(&bundle_hash);
+        }
+        update
+    }
+}
+
+/// Tracks the status of sent MEV-share bundles, by polling the Flashbots bundle stats
+/// API, and checking whether their transactions landed on chain.
+pub struct BundleStatusTracker<S, M> {
+    client: Arc<Client<S>>,
+    provider: Arc<M>,
+    bundles: TrackedBundles,
+    poll_interval: Duration,
+}
+
+impl<S, M> Clone for BundleStatusTracker<S, M> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            provider: self.provider.clone(),
+            bundles: self.bundles.clone(),
+            poll_interval: self.poll_interval,
+        }
+    }
+}
+
+impl<S, M> BundleStatusTracker<S, M>
+where
+    S: Signer + Clone + 'static,
+    M: Middleware + 'static,
+{
+    /// Create a tracker fetching stats with `client`, and checking inclusion with
+    /// `provider`
+    pub fn new(client: Arc<Client<S>>, provider: Arc<M>) -> Self {
+        Self {
+            client,
+            provider,
+            bundles: TrackedBundles::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Poll the status of tracked bundles every `poll_interval`. Defaults to 2 seconds.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Returns the tracked bundles, to track sent bundles with.
+    pub fn bundles(&self) -> TrackedBundles {
+        self.bundles.clone()
+    }
+
+    /// Stream the status transitions of tracked bundles, polled every poll interval.
+    /// The stream yields errors and carries on, and never ends.
+    pub fn subscribe(
+        &self,
+    ) -> impl Stream<Item = Result<StatusUpdate, StatusError>> + Send + 'static {
+        let tracker = self.clone();
+        stream::unfold(
+            (tracker, VecDeque::new()),
+            |(tracker, mut updates)| async move {
+                loop {
+                    if let Some(update) = updates.pop_front() {
+                        return Some((update, (tracker, updates)));
+                    }
+                    tokio::time::sleep(tracker.poll_interval).await;
+                    updates.extend(tracker.poll().await);
+                }
+            },
+        )
+    }
+
+    /// Poll the status of every tracked bundle once.
+    async fn poll(&self) -> Vec<Result<StatusUpdate, StatusError>> {
+        let head = match self.provider.get_block_number().await {
+            Ok(head) => head,
+            Err(e) => return vec![Err(StatusError::Provider(e.to_string()))],
+        };
+        let mut updates = vec![];
+        for (bundle_hash, bundle) in self.bundles.snapshot() {
+            let status = match self.landed(&bundle).await {
+                Ok(true) => Some(BundleStatus::Landed),
+                Ok(false) => match self
+                    .client
+                    .get_bundle_stats(bundle_hash, bundle.target_block)
+                    .await
+                {
+                    Ok(stats) => BundleStatus::from_stats(&stats),
+                    Err(e) => {
+                        updates.push(Err(StatusError::Stats(e)));
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    updates.push(Err(e));
+                    continue;
+                }
+            };
+            if let Some(update) = self.bundles.update(bundle_hash, status, head) {
+                updates.push(Ok(update));
+            }
+        }
+        updates
+    }
+
+    /// Whether the last transaction of `bundle` was included on chain.
+    async fn landed(&self, bundle: &TrackedBundle) -> Result<bool, StatusError> {
+        let Some(tx_hash) = bundle.tx_hashes.last() else {
+            return Ok(false);
+        };
+        let receipt = self
+            .provider
+            .get_transaction_receipt(*tx_hash)
+            .await
+            .map_err(|e| StatusError::Provider(e.to_string()))?;
+        Ok(receipt.is_some_and(|receipt| receipt.block_number.is_some()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BuilderTimestamp;
+
+    #[test]
+    fn derives_status_from_stats() {
+        let mut stats = BundleStats::default();
+        assert_eq!(BundleStatus::from_stats(&stats), None);
+        stats.received_at = Some("2023-08-08T12:00:00.456Z".to_string());
+        assert_eq!(
+            BundleStatus::from_stats(&stats),
+            Some(BundleStatus::Received)
+        );
+        stats.is_simulated = true;
+        assert_eq!(
+            BundleStatus::from_stats(&stats),
+            Some(BundleStatus::Simulated)
+        );
+        stats
+            .sealed_by_builders_at
+            .push(BuilderTimestamp::default());
+        assert_eq!(
+            BundleStatus::from_stats(&stats),
+            Some(BundleStatus::SealedByBuilder)
+        );
+    }
+
+    #[test]
+    fn reports_transitions_until_bundles_land_or_miss() {
+        let bundles = TrackedBundles::default();
+        let (landing, expiring) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        bundles.track(landing, U64::from(10), vec![H256::repeat_byte(3)]);
+        bundles.track(expiring, U64::from(10), vec![H256::repeat_byte(4)]);
+
+        let head = U64::from(9);
+        let update = bundles.update(landing, Some(BundleStatus::Simulated), head);
+        assert_eq!(update.unwrap().status, BundleStatus::Simulated);
+        // Statuses only move forward.
+        assert!(bundles
+            .update(landing, Some(BundleStatus::Received), head)
+            .is_none());
+        assert!(bundles.update(expiring, None, head).is_none());
+        assert_eq!(bundles.len(), 2);
+
+        let head = U64::from(11);
+        let update = bundles.update(landing, Some(BundleStatus::Landed), head);
+        assert_eq!(update.unwrap().status, BundleStatus::Landed);
+        let update = bundles.update(expiring, Some(BundleStatus::Received), head);
+        assert_eq!(update.unwrap().status, BundleStatus::Missed);
+        assert!(bundles.is_empty());
+    }
+}
@@ -1,10 +1,18 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-use ethers::types::{Bytes, H256, U64, Address};
+use ethers::{
+    types::{Bytes, H256, U256, U64, Address},
+    utils::hex,
+};
 use serde::{Deserialize, Serialize, Serializer, Deserializer, ser::SerializeSeq};
 
-/// A bundle of transactions to send to the matchmaker.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Number of blocks a bundle built with [BundleRequest::make_simple] is valid for.
+pub const DEFAULT_VALIDITY_BLOCKS: u64 = 5;
+
+/// A bundle of transactions to send to the matchmaker. Builders it is shared with are
+/// sent by name or by address depending on its [version](ProtocolVersion), see
+/// [BuilderId].
+#[derive(Clone, Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BundleRequest {
     /// The version of the MEV-share API to use.
@@ -22,8 +30,36 @@ pub struct BundleRequest {
 
 }
 
+impl Serialize for BundleRequest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Request<'a> {
+            version: &'a ProtocolVersion,
+            inclusion: &'a Inclusion,
+            body: &'a Vec<BundleTx>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            validity: &'a Option<Validity>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            privacy: Option<Privacy>,
+        }
+
+        Request {
+            version: &self.version,
+            inclusion: &self.inclusion,
+            body: &self.body,
+            validity: &self.validity,
+            privacy: self
+                .privacy
+                .as_ref()
+                .map(|privacy| privacy.for_version(&self.version)),
+        }
+        .serialize(serializer)
+    }
+}
+
 /// Data used by block builders to check if the bundle should be considered for inclusion.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Inclusion {
     /// The first block the bundle is valid for.
@@ -58,11 +94,11 @@ pub enum BundleTx {
 #[serde(rename_all = "camelCase")]
 pub struct SendBundleResponse {
     /// Hash of the bundle bodies.
-    bundle_hash: H256,
+    pub bundle_hash: H256,
 }
 
 /// The version of the MEV-share API to use.
-#[derive(Deserialize, Debug, Serialize, Clone, Default)]
+#[derive(Deserialize, Debug, Serialize, Clone, Default, PartialEq, Eq)]
 pub enum ProtocolVersion {
     #[default]
     #[serde(rename = "beta-1")]
@@ -117,13 +153,123 @@ pub struct Privacy {
     /// Hints on what data should be shared about the bundle and its transactions
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hints: Option<PrivacyHint>,
-    /// The addresses of the builders that should be allowed to see the bundle.
+    /// The builders that should be allowed to see the bundle. If unset, the
+    /// [Client](crate::client::Client) shares the bundle with every builder in its
+    /// [BuilderDirectory](crate::builders::BuilderDirectory) when sending it.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub builders: Option<Vec<Address>>,
+    pub builders: Option<Vec<BuilderId>>,
+}
+
+impl Privacy {
+    /// Returns the preferences with builders identified as `version` expects.
+    fn for_version(&self, version: &ProtocolVersion) -> Self {
+        let builders = self.builders.as_ref().map(|builders| {
+            builders
+                .iter()
+                .map(|builder| builder.for_version(version))
+                .collect()
+        });
+        Self {
+            hints: self.hints.clone(),
+            builders,
+        }
+    }
+}
+
+/// Names of the builders known to the matchmaker, and the addresses they build blocks
+/// with. A [BuilderDirectory](crate::builders::BuilderDirectory) starts out with them,
+/// until it fetches the current builders from a registry.
+pub const KNOWN_BUILDERS: [(&str, &str); 5] = [
+    ("rsync", "0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326"),
+    ("builder0x69", "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990"),
+    ("beaverbuild.org", "0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5"),
+    ("flashbots", "0xDAFEA492D9c6733ae3d56b7Ed1ADB60692c98Bc5"),
+    ("Titan", "0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97"),
+];
+
+/// A builder a bundle can be shared with, identified by the name it is registered
+/// under with the matchmaker, e.g. "flashbots", or by address. Builders in
+/// [KNOWN_BUILDERS] are sent by name in [v0.1](ProtocolVersion::V0_1) bundles, and by
+/// address in [beta-1](ProtocolVersion::Beta1) bundles, whichever they were given by.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BuilderId {
+    /// Name of the builder.
+    Name(String),
+    /// Address of the builder.
+    Address(Address),
+}
+
+impl BuilderId {
+    /// Returns the name of the builder, looked up in [KNOWN_BUILDERS] if it is
+    /// identified by address.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            BuilderId::Name(name) => Some(name),
+            BuilderId::Address(address) => known_builders()
+                .find(|(_, known)| known == address)
+                .map(|(name, _)| name),
+        }
+    }
+
+    /// Returns the address of the builder, looked up in [KNOWN_BUILDERS] if it is
+    /// identified by name.
+    pub fn address(&self) -> Option<Address> {
+        match self {
+            BuilderId::Name(name) => known_builders()
+                .find(|(known, _)| known == name)
+                .map(|(_, address)| address),
+            BuilderId::Address(address) => Some(*address),
+        }
+    }
+
+    fn for_version(&self, version: &ProtocolVersion) -> Self {
+        let resolved = match version {
+            ProtocolVersion::Beta1 => self.address().map(BuilderId::Address),
+            ProtocolVersion::V0_1 => self.name().map(|name| BuilderId::Name(name.to_string())),
+        };
+        resolved.unwrap_or_else(|| self.clone())
+    }
+}
+
+fn known_builders() -> impl Iterator<Item = (&'static str, Address)> {
+    KNOWN_BUILDERS
+        .iter()
+        .map(|(name, address)| (*name, Address::from_str(address).unwrap()))
+}
+
+impl From<&str> for BuilderId {
+    /// Parses an address, or takes the string as a name otherwise.
+    fn from(id: &str) -> Self {
+        match Address::from_str(id) {
+            Ok(address) if id.starts_with("0x") => BuilderId::Address(address),
+            _ => BuilderId::Name(id.to_string()),
+        }
+    }
+}
+
+impl From<Address> for BuilderId {
+    fn from(address: Address) -> Self {
+        BuilderId::Address(address)
+    }
+}
+
+impl Serialize for BuilderId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BuilderId::Name(name) => serializer.serialize_str(name),
+            BuilderId::Address(address) => address.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BuilderId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(BuilderId::from(String::deserialize(deserializer)?.as_str()))
+    }
 }
 
 /// Hints on what data should be shared about the bundle and its transactions
-#[derive(Clone, Debug, PartialEq, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct PrivacyHint {
     /// The calldata of the bundle's transactions should be shared.
     pub calldata: bool,
@@ -137,10 +283,21 @@ pub struct PrivacyHint {
     pub hash: bool,
     /// The hash of the bundle should be shared.
     pub tx_hash: bool,
+    /// The logs of the bundle's transactions should be shared, limited to the events
+    /// the matchmaker shares by default, such as swaps.
+    pub default_logs: bool,
+    /// Hints this client doesn't know of, kept as received so newer hints aren't lost.
+    pub other: Vec<String>,
 }
 
 #[allow(missing_docs)]
 impl PrivacyHint {
+    /// Hints sharing only the hashes of the bundle's transactions, and nothing of their
+    /// calldata, logs or the contracts they call, so searchers can't sandwich them.
+    pub fn hash_only() -> Self {
+        Self::default().with_hash()
+    }
+
     pub fn with_calldata(mut self) -> Self {
         self.calldata = true;
         self
@@ -171,6 +328,20 @@ impl PrivacyHint {
         self
     }
 
+    pub fn with_default_logs(mut self) -> Self {
+        self.default_logs = true;
+        self
+    }
+
+    /// Share a hint this client doesn't know of by name.
+    pub fn with_other(mut self, hint: impl Into<String>) -> Self {
+        let hint = hint.into();
+        if !self.other.contains(&hint) {
+            self.other.push(hint);
+        }
+        self
+    }
+
     pub fn has_calldata(&self) -> bool {
         self.calldata
     }
@@ -195,6 +366,10 @@ impl PrivacyHint {
         self.tx_hash
     }
 
+    pub fn has_default_logs(&self) -> bool {
+        self.default_logs
+    }
+
     fn num_hints(&self) -> usize {
         let mut num_hints = 0;
         if self.calldata {
@@ -215,7 +390,10 @@ impl PrivacyHint {
         if self.tx_hash {
             num_hints += 1;
         }
-        num_hints
+        if self.default_logs {
+            num_hints += 1;
+        }
+        num_hints + self.other.len()
     }
 }
 
@@ -240,6 +418,12 @@ impl Serialize for PrivacyHint {
         if self.tx_hash {
             seq.serialize_element("tx_hash")?;
         }
+        if self.default_logs {
+            seq.serialize_element("default_logs")?;
+        }
+        for hint in &self.other {
+            seq.serialize_element(hint)?;
+        }
         seq.end()
     }
 }
@@ -256,7 +440,8 @@ impl<'de> Deserialize<'de> for PrivacyHint {
                 "function_selector" => privacy_hint.function_selector = true,
                 "hash" => privacy_hint.hash = true,
                 "tx_hash" => privacy_hint.tx_hash = true,
-                _ => return Err(serde::de::Error::custom("invalid privacy hint")),
+                "default_logs" => privacy_hint.default_logs = true,
+                _ => privacy_hint = privacy_hint.with_other(hint),
             }
         }
         Ok(privacy_hint)
@@ -265,7 +450,10 @@ impl<'de> Deserialize<'de> for PrivacyHint {
 
 
 impl BundleRequest {
-    /// Create a new bundle request.
+    /// Create a new bundle request. It has no privacy preferences or refunds until they
+    /// are set with [with_hints](BundleRequest::with_hints),
+    /// [with_builders](BundleRequest::with_builders) and
+    /// [with_refund](BundleRequest::with_refund).
     pub fn new(
         block_num: U64,
         max_block: Option<U64>,
@@ -282,50 +470,24 @@ impl BundleRequest {
                 max_block,
             },
            body: transactions,
-           validity: Some(Validity
-            {
-                refund: None,
-                refund_config: Some({ vec![RefundConfig{
-                    
-                    address: Address::from_str("0x40").unwrap(),
-                    percent:90,
-
-                   }]
-            
-                }),
-            
-            }),
-            
-            privacy: Some(Privacy
-            {
-                hints: Some(PrivacyHint
-                {
-                    calldata: false,
-                    contract_address: false,
-                    logs: false,
-                    function_selector: false,
-                    hash: false,
-                    tx_hash: false,
-                }), 
-
-                builders: Some(vec![
-                    Address::from_str("0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326").unwrap(), //rysnc builder
-                    Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990").unwrap(), //builder0x69
-                    Address::from_str("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5").unwrap(), //beaverbuild
-                    Address::from_str("0xDAFEA492D9c6733ae3d56b7Ed1ADB60692c98Bc5").unwrap(), //Flashbot builder
-                    Address::from_str("0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97").unwrap(), //Titan builder
-                    
-
-                ]),
-
-            }),
+           validity: None,
+            privacy: None,
         }
     }
 
     /// Helper function to create a simple bundle request with sensible defaults (bundle is valid for the next 5 blocks).
     pub fn make_simple(block_num: U64, transactions: Vec<BundleTx>) -> Self {
-        // bundle is valid for 5 blocks
-        let max_block = block_num.saturating_add(U64::from(30));
+        Self::make_with_validity(block_num, DEFAULT_VALIDITY_BLOCKS, transactions)
+    }
+
+    /// Helper function to create a bundle request which is valid for `num_blocks` blocks,
+    /// starting at `block_num`.
+    pub fn make_with_validity(
+        block_num: U64,
+        num_blocks: u64,
+        transactions: Vec<BundleTx>,
+    ) -> Self {
+        let max_block = block_num.saturating_add(U64::from(num_blocks.saturating_sub(1)));
         Self::new(
             block_num,
             Some(max_block),
@@ -333,11 +495,335 @@ impl BundleRequest {
             transactions,
         )
     }
+
+    /// Share `hints` about the bundle, instead of nothing.
+    pub fn with_hints(mut self, hints: PrivacyHint) -> Self {
+        self.privacy.get_or_insert_with(Privacy::default).hints = Some(hints);
+        self
+    }
+
+    /// Only send the bundle to `builders`, instead of every builder in the directory of
+    /// the client sending it.
+    pub fn with_builders(mut self, builders: Vec<BuilderId>) -> Self {
+        self.privacy.get_or_insert_with(Privacy::default).builders = Some(builders);
+        self
+    }
+
+    /// Require at least `percent` of the bundle's earnings to be refunded to the sender
+    /// of the transaction at `body_idx`.
+    pub fn with_refund(mut self, body_idx: u64, percent: u64) -> Self {
+        self.validity
+            .get_or_insert_with(Validity::default)
+            .refund
+            .get_or_insert_with(Vec::new)
+            .push(Refund { body_idx, percent });
+        self
+    }
+}
+
+impl BundleRequest {
+    /// Checks the bundle for mistakes relays would reject it for, so they can be
+    /// reported before sending it: an empty body, an inclusion block before
+    /// `current_block`, refunds of transactions outside the body, or refund percents
+    /// adding up to more than 100.
+    pub fn validate(&self, current_block: U64) -> Result<(), BundleError> {
+        if self.body.is_empty() {
+            return Err(BundleError::EmptyBody);
+        }
+        if self.inclusion.block < current_block {
+            return Err(BundleError::PastBlock {
+                block: self.inclusion.block,
+                current_block,
+            });
+        }
+        let Some(validity) = &self.validity else {
+            return Ok(());
+        };
+        let refunds = validity.refund.as_deref().unwrap_or_default();
+        if let Some(refund) = refunds
+            .iter()
+            .find(|refund| refund.body_idx >= self.body.len() as u64)
+        {
+            return Err(BundleError::RefundOutOfRange {
+                body_idx: refund.body_idx,
+                body_len: self.body.len(),
+            });
+        }
+        let total: u64 = refunds.iter().map(|refund| refund.percent).sum();
+        if total > 100 {
+            return Err(BundleError::RefundPercent(total));
+        }
+        let configs = validity.refund_config.as_deref().unwrap_or_default();
+        let total: u64 = configs.iter().map(|config| config.percent).sum();
+        if total > 100 {
+            return Err(BundleError::RefundConfigPercent(total));
+        }
+        Ok(())
+    }
+}
+
+/// A mistake in a [BundleRequest], found by [BundleRequest::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleError {
+    /// The bundle has no transactions.
+    EmptyBody,
+    /// The bundle can only be included in blocks which have already been built.
+    PastBlock {
+        /// First block the bundle is valid for.
+        block: U64,
+        /// Block being built.
+        current_block: U64,
+    },
+    /// A refund is requested for a transaction the bundle doesn't have.
+    RefundOutOfRange {
+        /// Index of the refunded transaction.
+        body_idx: u64,
+        /// Number of transactions in the bundle.
+        body_len: usize,
+    },
+    /// The percents of the refunds add up to more than 100.
+    RefundPercent(u64),
+    /// The percents of the refund configs add up to more than 100.
+    RefundConfigPercent(u64),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::EmptyBody => write!(f, "bundle has no transactions"),
+            BundleError::PastBlock {
+                block,
+                current_block,
+            } => write!(
+                f,
+                "bundle targets block {} but block {} is being built",
+                block, current_block
+            ),
+            BundleError::RefundOutOfRange { body_idx, body_len } => write!(
+                f,
+                "refund of transaction {} but bundle has {} transactions",
+                body_idx, body_len
+            ),
+            BundleError::RefundPercent(total) => {
+                write!(f, "refund percents add up to {}, over 100", total)
+            }
+            BundleError::RefundConfigPercent(total) => {
+                write!(f, "refund config percents add up to {}, over 100", total)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+/// A hint about a pending transaction or bundle, as shared by the matchmaker. Which
+/// fields are set depends on the privacy hints of the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hint {
+    /// Hash of the transaction or bundle.
+    pub hash: H256,
+    /// Transactions of the hint, a single one unless it is a bundle.
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub txs: Vec<HintTransaction>,
+    /// Logs emitted by executing the transactions.
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub logs: Vec<HintLog>,
+    /// Gas used by the transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U256>,
+    /// Gas price paid to the matchmaker, above the base fee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mev_gas_price: Option<U256>,
+}
+
+/// A transaction of a [Hint].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HintTransaction {
+    /// Recipient of the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Selector of the function called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_selector: Option<FunctionSelector>,
+    /// Calldata of the transaction.
+    #[serde(default, rename = "callData", skip_serializing_if = "Option::is_none")]
+    pub calldata: Option<Bytes>,
+}
+
+/// A log emitted by the transactions of a [Hint].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintLog {
+    /// Contract which emitted the log.
+    pub address: Address,
+    /// Topics of the log, starting with the event signature.
+    pub topics: Vec<H256>,
+    /// Data of the log, usually stripped by the matchmaker.
+    #[serde(default)]
+    pub data: Bytes,
+}
+
+/// A 4-byte function selector, serialized as hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FunctionSelector(pub [u8; 4]);
+
+impl Serialize for FunctionSelector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.0)))
+    }
+}
+
+impl<'de> Deserialize<'de> for FunctionSelector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let selector = String::deserialize(deserializer)?;
+        let bytes =
+            hex::decode(selector.trim_start_matches("0x")).map_err(serde::de::Error::custom)?;
+        let bytes = <[u8; 4]>::try_from(bytes.as_slice())
+            .map_err(|_| serde::de::Error::custom("expected a 4 byte function selector"))?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Deserializes a missing or null list as an empty one, as the matchmaker omits
+/// fields which aren't shared.
+fn null_as_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A hint broadcast in a past block, served by the event history API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventHistory {
+    /// Block the hint was broadcast in.
+    pub block: u64,
+    /// Unix timestamp (in seconds) at which the hint was broadcast.
+    pub timestamp: u64,
+    /// The hint.
+    pub hint: Hint,
+}
+
+/// Range of the hints served by the event history API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventHistoryInfo {
+    /// Number of hints stored.
+    pub count: u64,
+    /// First block with stored hints.
+    pub min_block: u64,
+    /// Last block with stored hints.
+    pub max_block: u64,
+    /// Unix timestamp of the first stored hint.
+    pub min_timestamp: u64,
+    /// Unix timestamp of the last stored hint.
+    pub max_timestamp: u64,
+    /// Most hints returned by a single request.
+    pub max_limit: u64,
+}
+
+/// Filters of a request to the event history API. Unset bounds are unbounded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventHistoryParams {
+    /// First block to return hints of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_start: Option<u64>,
+    /// Last block to return hints of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_end: Option<u64>,
+    /// Earliest unix timestamp to return hints of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_start: Option<u64>,
+    /// Latest unix timestamp to return hints of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_end: Option<u64>,
+    /// Number of hints to return.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// Number of matching hints to skip.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+}
+
+#[allow(missing_docs)]
+impl EventHistoryParams {
+    pub fn with_block_range(mut self, block_start: u64, block_end: u64) -> Self {
+        self.block_start = Some(block_start);
+        self.block_end = Some(block_end);
+        self
+    }
+
+    pub fn with_timestamp_range(mut self, timestamp_start: u64, timestamp_end: u64) -> Self {
+        self.timestamp_start = Some(timestamp_start);
+        self.timestamp_end = Some(timestamp_end);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
+
+/// Stats of a submitted bundle, as served by the `flashbots_getBundleStatsV2` endpoint.
+/// Timestamps are RFC 3339 strings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStats {
+    /// Whether the bundle is prioritized, based on the searcher's reputation.
+    #[serde(default)]
+    pub is_high_priority: bool,
+    /// Whether the relay simulated the bundle.
+    #[serde(default)]
+    pub is_simulated: bool,
+    /// When the relay simulated the bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulated_at: Option<String>,
+    /// When the relay received the bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<String>,
+    /// Builders which considered the bundle for a block.
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub considered_by_builders_at: Vec<BuilderTimestamp>,
+    /// Builders which sealed a block containing the bundle.
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub sealed_by_builders_at: Vec<BuilderTimestamp>,
+}
+
+/// When a builder, identified by its public key, considered or sealed a bundle.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuilderTimestamp {
+    /// Public key of the builder.
+    pub pubkey: String,
+    /// RFC 3339 timestamp.
+    pub timestamp: String,
+}
+
+/// Parameters of a `flashbots_getBundleStatsV2` request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStatsRequest {
+    /// Hash of the bundle, as returned when it was sent.
+    pub bundle_hash: H256,
+    /// Block the bundle targets.
+    pub block_number: U64,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::types::BundleRequest;
+    use crate::types::{
+        BuilderId, BundleError, BundleRequest, BundleStats, BundleTx, EventHistory,
+        FunctionSelector, Privacy, PrivacyHint, ProtocolVersion, Refund, RefundConfig,
+    };
+    use ethers::types::{Address, H256, U64};
 
     #[test]
     fn can_deserialize() {
@@ -356,4 +842,199 @@ mod tests {
         let res: Result<Vec<BundleRequest>, _> = serde_json::from_str(str);
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn make_with_validity_sets_inclusive_max_block() {
+        let bundle = BundleRequest::make_with_validity(U64::from(100), 2, vec![]);
+        assert_eq!(bundle.inclusion.block, U64::from(100));
+        assert_eq!(bundle.inclusion.max_block, Some(U64::from(101)));
+
+        let bundle = BundleRequest::make_simple(U64::from(100), vec![]);
+        assert_eq!(bundle.inclusion.max_block, Some(U64::from(104)));
+    }
+
+    #[test]
+    fn can_deserialize_event_history() {
+        let str = r#"
+        [{
+            "block": 17891234,
+            "timestamp": 1691500000,
+            "hint": {
+                "hash": "0xc7dc06c994400830054ab815732d91275bc1241f9be62b62b687b7882f19b8d4",
+                "txs": [{
+                    "to": "0x0000c335bc9d5d1af0402cad63fa7f258363d71a",
+                    "functionSelector": "0x696d2073",
+                    "callData": "0x696d20736861726969696969696e67"
+                }],
+                "logs": null,
+                "gasUsed": "0x5208",
+                "mevGasPrice": "0x3b9aca00"
+            }
+        }, {
+            "block": 17891235,
+            "timestamp": 1691500012,
+            "hint": {
+                "hash": "0x8e32bfed609925168302ea538acd839ad34fdcd5d89dff67b19f9717d39abee6",
+                "txs": null,
+                "logs": [{
+                    "address": "0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852",
+                    "topics": ["0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822"],
+                    "data": "0x"
+                }]
+            }
+        }]
+        "#;
+        let history: Vec<EventHistory> = serde_json::from_str(str).unwrap();
+        let hint = &history[0].hint;
+        assert_eq!(
+            hint.txs[0].function_selector,
+            Some(FunctionSelector([0x69, 0x6d, 0x20, 0x73]))
+        );
+        assert!(hint.logs.is_empty());
+        assert_eq!(hint.gas_used, Some(21000.into()));
+        assert!(history[1].hint.txs.is_empty());
+        assert_eq!(history[1].hint.logs.len(), 1);
+        assert_eq!(history[1].hint.mev_gas_price, None);
+    }
+
+    #[test]
+    fn privacy_hints_keep_unknown_hints() {
+        let hints: PrivacyHint =
+            serde_json::from_str(r#"["calldata", "default_logs", "full", "full"]"#).unwrap();
+        assert!(hints.has_calldata());
+        assert!(hints.has_default_logs());
+        assert!(!hints.has_logs());
+        assert_eq!(hints.other, vec!["full"]);
+        assert_eq!(
+            serde_json::to_string(&hints).unwrap(),
+            r#"["calldata","default_logs","full"]"#
+        );
+    }
+
+    #[test]
+    fn serializes_builders_for_protocol_version() {
+        let mut bundle = BundleRequest::make_simple(U64::from(100), vec![]);
+        bundle.privacy = Some(Privacy {
+            hints: None,
+            builders: Some(vec![
+                BuilderId::from("flashbots"),
+                BuilderId::from("0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97"),
+                BuilderId::from("unknown"),
+            ]),
+        });
+        let builders = |bundle: &BundleRequest| {
+            serde_json::to_value(bundle).unwrap()["privacy"]["builders"].clone()
+        };
+        assert_eq!(
+            builders(&bundle),
+            serde_json::json!([
+                "0xdafea492d9c6733ae3d56b7ed1adb60692c98bc5",
+                "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97",
+                "unknown"
+            ])
+        );
+
+        bundle.version = ProtocolVersion::V0_1;
+        assert_eq!(
+            builders(&bundle),
+            serde_json::json!(["flashbots", "Titan", "unknown"])
+        );
+    }
+
+    #[test]
+    fn sets_hash_only_hints_and_trusted_builders() {
+        let bundle = BundleRequest::make_simple(U64::from(100), vec![])
+            .with_hints(PrivacyHint::hash_only())
+            .with_builders(vec![BuilderId::from("flashbots")]);
+        let privacy = &serde_json::to_value(&bundle).unwrap()["privacy"];
+        assert_eq!(privacy["hints"], serde_json::json!(["hash"]));
+        assert_eq!(
+            privacy["builders"],
+            serde_json::json!(["0xdafea492d9c6733ae3d56b7ed1adb60692c98bc5"])
+        );
+    }
+
+    #[test]
+    fn validates_bundles() {
+        let txs = vec![BundleTx::TxHash { hash: H256::zero() }; 2];
+        let mut bundle = BundleRequest::make_simple(U64::from(100), txs);
+        assert_eq!(bundle.validate(U64::from(100)), Ok(()));
+        assert_eq!(
+            bundle.validate(U64::from(101)),
+            Err(BundleError::PastBlock {
+                block: U64::from(100),
+                current_block: U64::from(101),
+            })
+        );
+
+        assert_eq!(bundle.validity, None);
+        let mut bundle = bundle.with_refund(2, 50);
+        assert_eq!(
+            bundle.validate(U64::from(100)),
+            Err(BundleError::RefundOutOfRange {
+                body_idx: 2,
+                body_len: 2
+            })
+        );
+
+        let validity = bundle.validity.as_mut().unwrap();
+        validity.refund = Some(vec![
+            Refund {
+                body_idx: 0,
+                percent: 60,
+            },
+            Refund {
+                body_idx: 1,
+                percent: 50,
+            },
+        ]);
+        assert_eq!(
+            bundle.validate(U64::from(100)),
+            Err(BundleError::RefundPercent(110))
+        );
+
+        let validity = bundle.validity.as_mut().unwrap();
+        validity.refund = None;
+        validity.refund_config = Some(vec![
+            RefundConfig {
+                address: Address::zero(),
+                percent: 90,
+            },
+            RefundConfig {
+                address: Address::zero(),
+                percent: 20,
+            },
+        ]);
+        assert_eq!(
+            bundle.validate(U64::from(100)),
+            Err(BundleError::RefundConfigPercent(110))
+        );
+
+        bundle.body.clear();
+        assert_eq!(bundle.validate(U64::from(100)), Err(BundleError::EmptyBody));
+    }
+
+    #[test]
+    fn can_deserialize_bundle_stats() {
+        let str = r#"
+        {
+            "isHighPriority": true,
+            "isSimulated": true,
+            "simulatedAt": "2023-08-08T12:00:01.123Z",
+            "receivedAt": "2023-08-08T12:00:00.456Z",
+            "consideredByBuildersAt": [{
+                "pubkey": "0x81babeec8c9f2bb9c329fd8a3b176032fe0ab5f3b92a3f44d4575a231c7bd9c31d10b6328ef68ed1e8c02a3dbc8e80f9",
+                "timestamp": "2023-08-08T12:00:01.500Z"
+            }],
+            "sealedByBuildersAt": null
+        }
+        "#;
+        let stats: BundleStats = serde_json::from_str(str).unwrap();
+        assert!(stats.is_simulated);
+        assert_eq!(stats.considered_by_builders_at.len(), 1);
+        assert!(stats.sealed_by_builders_at.is_empty());
+
+        let stats: BundleStats = serde_json::from_str(r#"{"isSimulated": false}"#).unwrap();
+        assert_eq!(stats, BundleStats::default());
+    }
 }
@@ -0,0 +1,17 @@
+use ethers::{prelude::Lazy, types::Address};
+
+/// Address of the mainnet cUSDCv3 Comet market.
+pub static COMET_USDC_ADDRESS: Lazy<Address> = Lazy::new(|| {
+    "0xc3d688b66703497daa19211eedff47f25384cdc3"
+        .parse()
+        .unwrap()
+});
+
+/// Block number at which the mainnet cUSDCv3 market was deployed.
+pub const COMET_USDC_DEPLOYMENT_BLOCK: u64 = 15331586;
+
+/// Number of blocks to query at once when scanning for borrowers.
+pub const LOG_CHUNK_SIZE: u64 = 10_000;
+
+/// Number of accounts to check for liquidatability concurrently.
+pub const LIQUIDITY_CHECK_BATCH_SIZE: usize = 50;
@@ -0,0 +1,22 @@
+#![warn(unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+//! A strategy liquidating underwater Compound V3 (Comet) accounts. We track every
+//! account which has borrowed from a Comet market, and on each collateral price update
+//! check which of them became liquidatable. Liquidatable accounts are absorbed, and the
+//! seized collateral is bought back from the protocol at a discount in the same bundle.
+
+/// This module contains constants used by the strategy.
+pub mod constants;
+
+/// This module contains the math used to value collateral in base token terms.
+pub mod math;
+
+/// This module contains the core strategy implementation.
+pub mod strategy;
+
+/// This module contains the core type definitions for the strategy.
+pub mod types;
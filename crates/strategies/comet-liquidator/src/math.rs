@@ -0,0 +1,62 @@
+use ethers::types::U256;
+
+/// Value `collateral_amount` of a collateral asset in base token units, using the
+/// Comet oracle prices. Prices share the same USD denomination, so they cancel out.
+pub fn collateral_value_in_base(
+    collateral_amount: U256,
+    collateral_price: U256,
+    collateral_scale: U256,
+    base_price: U256,
+    base_scale: U256,
+) -> U256 {
+    if base_price.is_zero() || collateral_scale.is_zero() {
+        return U256::zero();
+    }
+    collateral_amount * collateral_price * base_scale / collateral_scale / base_price
+}
+
+/// Profit, in base token units, of buying `collateral_amount` for `base_amount` and
+/// selling it at the oracle price. Returns `None` if the purchase is at a loss.
+pub fn liquidation_profit(collateral_value: U256, base_amount: U256) -> Option<U256> {
+    collateral_value.checked_sub(base_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_collateral_in_base_units() {
+        // 1 WETH at $2000, valued in USDC (6 decimals) at $1.
+        let price_scale = U256::exp10(8);
+        let value = collateral_value_in_base(
+            U256::exp10(18),
+            U256::from(2000) * price_scale,
+            U256::exp10(18),
+            price_scale,
+            U256::exp10(6),
+        );
+        assert_eq!(value, U256::from(2000) * U256::exp10(6));
+    }
+
+    #[test]
+    fn zero_base_price_values_nothing() {
+        let value = collateral_value_in_base(
+            U256::one(),
+            U256::one(),
+            U256::one(),
+            U256::zero(),
+            U256::one(),
+        );
+        assert!(value.is_zero());
+    }
+
+    #[test]
+    fn liquidation_profit_is_none_at_a_loss() {
+        assert_eq!(
+            liquidation_profit(U256::from(105), U256::from(100)),
+            Some(U256::from(5))
+        );
+        assert_eq!(liquidation_profit(U256::from(95), U256::from(100)), None);
+    }
+}
@@ -0,0 +1,517 @@
+use std::{collections::HashSet, sync::Arc};
+
+use artemis_core::error::{ArtemisError, Result};
+use artemis_core::types::Strategy;
+use async_trait::async_trait;
+use ethers::{
+    contract::{abigen, EthEvent},
+    providers::Middleware,
+    signers::Signer,
+    types::{
+        transaction::eip2718::TypedTransaction, BlockNumber, Eip1559TransactionRequest, Filter,
+        Log, H160, U256,
+    },
+};
+use futures::future::join_all;
+use tracing::{info, warn};
+
+use crate::{
+    constants::{LIQUIDITY_CHECK_BATCH_SIZE, LOG_CHUNK_SIZE},
+    math::{collateral_value_in_base, liquidation_profit},
+    types::{order_by_preference, Action, CollateralAsset, Config, Event},
+};
+
+abigen!(
+    Comet,
+    r#"[
+        function baseToken() external view returns (address)
+        function baseTokenPriceFeed() external view returns (address)
+        function baseScale() external view returns (uint256)
+        function numAssets() external view returns (uint8)
+        function getAssetInfo(uint8 i) external view returns (uint8 offset, address asset, address priceFeed, uint64 scale, uint64 borrowCollateralFactor, uint64 liquidateCollateralFactor, uint64 liquidationFactor, uint128 supplyCap)
+        function getPrice(address priceFeed) external view returns (uint256)
+        function isLiquidatable(address account) external view returns (bool)
+        function collateralBalanceOf(address account, address asset) external view returns (uint128)
+        function quoteCollateral(address asset, uint256 baseAmount) external view returns (uint256)
+        function absorb(address absorber, address[] accounts) external
+        function buyCollateral(address asset, uint256 minAmount, uint256 baseAmount, address recipient) external
+        event Withdraw(address indexed src, address indexed to, uint256 amount)
+        event AbsorbDebt(address indexed absorber, address indexed borrower, uint256 basePaidOut, uint256 usdValue)
+    ]"#;
+
+    IERC20,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+    ]"#;
+
+    IAggregator,
+    r#"[
+        event AnswerUpdated(int256 indexed current, uint256 indexed roundId, uint256 updatedAt)
+    ]"#;
+);
+
+#[derive(Debug, Clone)]
+pub struct CometLiquidator<M, S> {
+    /// Ethers client.
+    client: Arc<M>,
+    /// Comet market contract.
+    comet: Comet<M>,
+    /// Signer whose address absorbs accounts and buys collateral.
+    tx_signer: S,
+    /// Strategy configuration.
+    config: Config,
+    /// Token the market lends, which buys collateral.
+    base_token: H160,
+    /// Price feed of the base token.
+    base_price_feed: H160,
+    /// Power of ten matching the base token decimals.
+    base_scale: U256,
+    /// Collateral assets of the market, ordered by preference.
+    assets: Vec<CollateralAsset>,
+    /// Accounts which have borrowed from the market.
+    borrowers: HashSet<H160>,
+}
+
+impl<M: Middleware + 'static, S: Signer> CometLiquidator<M, S> {
+    pub fn new(client: Arc<M>, tx_signer: S, config: Config) -> Self {
+        let comet = Comet::new(config.comet_address, client.clone());
+        Self {
+            client,
+            comet,
+            tx_signer,
+            config,
+            base_token: H160::zero(),
+            base_price_feed: H160::zero(),
+            base_scale: U256::one(),
+            assets: Vec::new(),
+            borrowers: HashSet::new(),
+        }
+    }
+
+    /// Filter matching the Comet logs the strategy needs to track borrowers.
+    pub fn comet_log_filter(&self) -> Filter {
+        Filter::new()
+            .address(self.config.comet_address)
+            .topic0(vec![
+                WithdrawFilter::signature(),
+                AbsorbDebtFilter::signature(),
+            ])
+    }
+}
+
+/// Filter matching the price updates of the Chainlink `aggregators` behind the price
+/// feeds of a Comet market, or of every aggregator if none are given.
+pub fn price_update_filter(aggregators: Vec<H160>) -> Filter {
+    let filter = Filter::new().topic0(AnswerUpdatedFilter::signature());
+    match aggregators.is_empty() {
+        true => filter,
+        false => filter.address(aggregators),
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action>
+    for CometLiquidator<M, S>
+{
+    // In order to sync this strategy, we need the market configuration and every borrower.
+    async fn sync_state(&mut self) -> Result<()> {
+        self.base_token = self.comet.base_token().call().await?;
+        self.base_price_feed = self.comet.base_token_price_feed().call().await?;
+        self.base_scale = self.comet.base_scale().call().await?;
+
+        let num_assets = self.comet.num_assets().call().await?;
+        let mut assets = Vec::with_capacity(num_assets as usize);
+        for i in 0..num_assets {
+            let (_, asset, price_feed, scale, _, _, _, _) =
+                self.comet.get_asset_info(i).call().await?;
+            assets.push(CollateralAsset {
+                asset,
+                price_feed,
+                scale: U256::from(scale),
+            });
+        }
+        self.assets = order_by_preference(assets, &self.config.collateral_preference);
+
+        let current_block = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(ArtemisError::from_middleware)?
+            .as_u64();
+        let mut start_block = self.config.deployment_block;
+        while start_block <= current_block {
+            let end_block = (start_block + LOG_CHUNK_SIZE - 1).min(current_block);
+            let logs = self
+                .client
+                .get_logs(
+                    &self
+                        .comet_log_filter()
+                        .from_block(BlockNumber::Number(start_block.into()))
+                        .to_block(BlockNumber::Number(end_block.into())),
+                )
+                .await
+                .map_err(ArtemisError::from_middleware)?;
+            for log in logs {
+                self.process_comet_log(log);
+            }
+            start_block = end_block + 1;
+        }
+        info!(
+            "done syncing state, tracking {} borrowers across {} collateral assets",
+            self.borrowers.len(),
+            self.assets.len()
+        );
+
+        Ok(())
+    }
+
+    // Process incoming events, tracking borrowers and liquidating on price updates.
+    async fn process_event(&mut self, event: Event) -> Option<Action> {
+        match event {
+            Event::CometLog(log) => {
+                self.process_comet_log(log);
+                None
+            }
+            Event::PriceUpdate(_) => match self.process_price_update().await {
+                Ok(action) => action,
+                Err(e) => {
+                    warn!("error processing price update: {}", e);
+                    None
+                }
+            },
+        }
+    }
+}
+
+impl<M: Middleware + 'static, S: Signer + 'static> CometLiquidator<M, S> {
+    /// Track accounts which borrow, and forget accounts which were absorbed.
+    fn process_comet_log(&mut self, log: Log) {
+        let Some(topic) = log.topics.first() else {
+            return;
+        };
+        let Some(account) = log.topics.get(1).map(|topic| H160::from(*topic)) else {
+            return;
+        };
+        if *topic == WithdrawFilter::signature() {
+            self.borrowers.insert(account);
+        } else if *topic == AbsorbDebtFilter::signature() {
+            // The borrower is the second indexed topic.
+            if let Some(borrower) = log.topics.get(2) {
+                self.borrowers.remove(&H160::from(*borrower));
+            }
+        }
+    }
+
+    /// Find liquidatable accounts, and build a bundle absorbing them and buying the most
+    /// preferred collateral if that is profitable.
+    async fn process_price_update(&self) -> anyhow::Result<Option<Action>> {
+        let accounts = self.liquidatable_accounts().await;
+        if accounts.is_empty() {
+            return Ok(None);
+        }
+        info!("found {} liquidatable accounts", accounts.len());
+
+        let Some((asset, base_amount, collateral_out)) =
+            self.best_collateral_purchase(&accounts).await?
+        else {
+            return Ok(None);
+        };
+
+        let from = self.tx_signer.address();
+        let absorb = self.comet.absorb(from, accounts).tx;
+        let buy = self
+            .comet
+            .buy_collateral(asset, collateral_out, base_amount, from)
+            .tx;
+        let bundle = self.build_bundle(vec![absorb, buy]).await?;
+        Ok(Some(Action::SubmitBundle(bundle)))
+    }
+
+    /// Check every tracked borrower, in concurrent batches.
+    async fn liquidatable_accounts(&self) -> Vec<H160> {
+        let borrowers: Vec<H160> = self.borrowers.iter().copied().collect();
+        let mut liquidatable = Vec::new();
+        for batch in borrowers.chunks(LIQUIDITY_CHECK_BATCH_SIZE) {
+            let results = join_all(batch.iter().map(|account| async move {
+                let call = self.comet.is_liquidatable(*account);
+                (*account, call.call().await)
+            }))
+            .await;
+            for (account, result) in results {
+                match result {
+                    Ok(true) => liquidatable.push(account),
+                    Ok(false) => {}
+                    Err(e) => warn!("error checking liquidity of {:?}: {}", account, e),
+                }
+            }
+        }
+        liquidatable
+    }
+
+    /// Returns the base token the signer can spend buying collateral: its balance, up
+    /// to what it approved the market to spend.
+    async fn spendable_base(&self) -> anyhow::Result<U256> {
+        let from = self.tx_signer.address();
+        let base_token = IERC20::new(self.base_token, self.client.clone());
+        let balance = base_token.balance_of(from);
+        let allowance = base_token.allowance(from, self.config.comet_address);
+        let (balance, allowance) = futures::try_join!(balance.call(), allowance.call())?;
+        Ok(balance.min(allowance))
+    }
+
+    /// Pick the first collateral asset, in order of preference, which the absorbed
+    /// accounts hold and which can be bought for at least the minimum profit, with the
+    /// base token the signer can spend. Returns the asset, the base amount to spend, and
+    /// the collateral amount received.
+    async fn best_collateral_purchase(
+        &self,
+        accounts: &[H160],
+    ) -> anyhow::Result<Option<(H160, U256, U256)>> {
+        let spendable = self.spendable_base().await?;
+        if spendable.is_zero() {
+            warn!(
+                "signer holds or approved no base token {:?}, can't buy collateral",
+                self.base_token
+            );
+            return Ok(None);
+        }
+        let base_price = self.comet.get_price(self.base_price_feed).call().await?;
+
+        for asset in &self.assets {
+            let mut seized = U256::zero();
+            for account in accounts {
+                let balance = self
+                    .comet
+                    .collateral_balance_of(*account, asset.asset)
+                    .call()
+                    .await?;
+                seized += U256::from(balance);
+            }
+            if seized.is_zero() {
+                continue;
+            }
+
+            let price = self.comet.get_price(asset.price_feed).call().await?;
+            let seized_value =
+                collateral_value_in_base(seized, price, asset.scale, base_price, self.base_scale);
+            let base_amount = seized_value.min(self.config.max_base_amount).min(spendable);
+            if base_amount.is_zero() {
+                continue;
+            }
+
+            // The quote includes the store front discount, so it buys more than oracle value.
+            let collateral_out = self
+                .comet
+                .quote_collateral(asset.asset, base_amount)
+                .call()
+                .await?
+                .min(seized);
+            let collateral_value = collateral_value_in_base(
+                collateral_out,
+                price,
+                asset.scale,
+                base_price,
+                self.base_scale,
+            );
+            match liquidation_profit(collateral_value, base_amount) {
+                Some(profit) if profit >= self.config.min_profit => {
+                    info!(
+                        "liquidation buying {:?} yields {} base profit",
+                        asset.asset, profit
+                    );
+                    return Ok(Some((asset.asset, base_amount, collateral_out)));
+                }
+                _ => continue,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Turn the contract calls into consecutive EIP-1559 transactions from our signer.
+    async fn build_bundle(
+        &self,
+        calls: Vec<TypedTransaction>,
+    ) -> anyhow::Result<Vec<TypedTransaction>> {
+        let from = self.tx_signer.address();
+        let nonce = self.client.get_transaction_count(from, None).await?;
+        let block = self
+            .client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("latest block not found"))?;
+        let base_fee = block.base_fee_per_gas.unwrap_or_default();
+
+        let mut bundle = Vec::with_capacity(calls.len());
+        for (i, call) in calls.into_iter().enumerate() {
+            let mut tx: TypedTransaction = Eip1559TransactionRequest {
+                from: Some(from),
+                to: call.to().cloned(),
+                data: call.data().cloned(),
+                nonce: Some(nonce + i),
+                max_priority_fee_per_gas: Some(self.config.priority_fee),
+                max_fee_per_gas: Some(base_fee * 2 + self.config.priority_fee),
+                chain_id: Some(self.tx_signer.chain_id().into()),
+                ..Default::default()
+            }
+            .into();
+            // Later txs depend on state changed by earlier ones, so gas can't be estimated
+            // ahead of the bundle for every call.
+            let gas = self
+                .client
+                .estimate_gas(&tx, None)
+                .await
+                .unwrap_or_else(|_| U256::from(1_000_000));
+            tx.set_gas(gas * 12 / 10);
+            bundle.push(tx);
+        }
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        abi::{encode, AbiDecode, Token},
+        providers::{MockProvider, Provider},
+        signers::LocalWallet,
+        types::{Block, Bytes, H256, U64},
+    };
+
+    use super::*;
+
+    const COMET: H160 = H160::repeat_byte(1);
+    const COLLATERAL: H160 = H160::repeat_byte(4);
+    const BORROWER: H160 = H160::repeat_byte(6);
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    fn word(token: Token) -> Bytes {
+        Bytes::from(encode(&[token]))
+    }
+
+    /// A liquidator of a USDC market with WETH as its only collateral, and a single
+    /// borrower, making at least 50 USDC per liquidation and spending up to 1000 USDC.
+    fn liquidator(
+        provider: Provider<MockProvider>,
+    ) -> CometLiquidator<Provider<MockProvider>, LocalWallet> {
+        let config = Config {
+            comet_address: COMET,
+            deployment_block: 0,
+            min_profit: U256::from(50) * U256::exp10(6),
+            max_base_amount: U256::from(1000) * U256::exp10(6),
+            collateral_preference: vec![],
+            priority_fee: U256::from(2),
+        };
+        let mut liquidator = CometLiquidator::new(Arc::new(provider), wallet(), config);
+        liquidator.base_token = H160::repeat_byte(2);
+        liquidator.base_price_feed = H160::repeat_byte(3);
+        liquidator.base_scale = U256::exp10(6);
+        liquidator.assets = vec![CollateralAsset {
+            asset: COLLATERAL,
+            price_feed: H160::repeat_byte(5),
+            scale: U256::exp10(18),
+        }];
+        liquidator.borrowers.insert(BORROWER);
+        liquidator
+    }
+
+    /// Push the responses to the calls pricing the purchase of the WETH seized from the
+    /// borrower: 10k USDC spendable, USDC at $1, `seized` WETH at $2000, quoted at
+    /// `quote` WETH for the base amount.
+    fn push_purchase(mock: &MockProvider, seized: U256, quote: U256) {
+        // The mock answers requests last pushed first.
+        let price_scale = U256::exp10(8);
+        let spendable = U256::from(10_000) * U256::exp10(6);
+        mock.push(word(Token::Uint(quote))).unwrap();
+        mock.push(word(Token::Uint(U256::from(2000) * price_scale)))
+            .unwrap();
+        mock.push(word(Token::Uint(seized))).unwrap();
+        mock.push(word(Token::Uint(price_scale))).unwrap();
+        mock.push(word(Token::Uint(spendable))).unwrap();
+        mock.push(word(Token::Uint(spendable))).unwrap();
+    }
+
+    #[tokio::test]
+    async fn buys_collateral_for_up_to_the_max_base_amount() {
+        let (provider, mock) = Provider::mocked();
+        // 1 WETH is worth 2000 USDC, more than the 1000 USDC spent on 0.55 WETH, worth
+        // 1100 USDC.
+        let quote = U256::from(55) * U256::exp10(16);
+        push_purchase(&mock, U256::exp10(18), quote);
+
+        let purchase = liquidator(provider)
+            .best_collateral_purchase(&[BORROWER])
+            .await
+            .unwrap();
+        assert_eq!(
+            purchase,
+            Some((COLLATERAL, U256::from(1000) * U256::exp10(6), quote))
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_purchases_below_the_min_profit() {
+        let (provider, mock) = Provider::mocked();
+        // 0.52 WETH is worth 1040 USDC, 40 USDC more than spent.
+        push_purchase(&mock, U256::exp10(18), U256::from(52) * U256::exp10(16));
+
+        let purchase = liquidator(provider)
+            .best_collateral_purchase(&[BORROWER])
+            .await
+            .unwrap();
+        assert_eq!(purchase, None);
+    }
+
+    #[tokio::test]
+    async fn absorbs_liquidatable_accounts_and_buys_their_collateral() {
+        let (provider, mock) = Provider::mocked();
+        // Requests are made in the order: whether the borrower is liquidatable, the
+        // purchase, the nonce, and the latest block. Gas can't be estimated.
+        let block = Block::<H256> {
+            number: Some(U64::from(100)),
+            base_fee_per_gas: Some(U256::from(10)),
+            ..Default::default()
+        };
+        mock.push(block).unwrap();
+        mock.push(U256::from(7)).unwrap();
+        let quote = U256::from(55) * U256::exp10(16);
+        push_purchase(&mock, U256::exp10(18), quote);
+        mock.push(word(Token::Bool(true))).unwrap();
+
+        let liquidator = liquidator(provider);
+        let Some(Action::SubmitBundle(bundle)) = liquidator.process_price_update().await.unwrap()
+        else {
+            panic!("expected a liquidation bundle");
+        };
+        assert_eq!(bundle.len(), 2);
+        let from = wallet().address();
+        let absorb = AbsorbCall::decode(bundle[0].data().unwrap()).unwrap();
+        assert_eq!(absorb.absorber, from);
+        assert_eq!(absorb.accounts, vec![BORROWER]);
+        let buy = BuyCollateralCall::decode(bundle[1].data().unwrap()).unwrap();
+        assert_eq!(buy.asset, COLLATERAL);
+        assert_eq!(buy.min_amount, quote);
+        assert_eq!(buy.base_amount, U256::from(1000) * U256::exp10(6));
+        assert_eq!(buy.recipient, from);
+        for (i, tx) in bundle.iter().enumerate() {
+            assert_eq!(tx.to_addr(), Some(&COMET));
+            assert_eq!(tx.nonce(), Some(&U256::from(7 + i)));
+            // Gas falls back to 1M, plus the 20% margin.
+            assert_eq!(tx.gas(), Some(&U256::from(1_200_000)));
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_price_updates_without_liquidatable_accounts() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(word(Token::Bool(false))).unwrap();
+
+        let action = liquidator(provider).process_price_update().await.unwrap();
+        assert!(action.is_none());
+    }
+}
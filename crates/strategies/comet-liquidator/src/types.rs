@@ -0,0 +1,81 @@
+use artemis_core::executors::flashbots_executor::FlashbotsBundle;
+use ethers::types::{Log, H160, U256};
+
+/// Core Event enum for the current strategy.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A log emitted by the Comet market, used to track borrowers.
+    CometLog(Log),
+    /// A price feed update, which may have made accounts liquidatable.
+    PriceUpdate(Log),
+}
+
+/// Core Action enum for the current strategy.
+#[derive(Debug, Clone)]
+pub enum Action {
+    SubmitBundle(FlashbotsBundle),
+}
+
+/// Configuration for variables we need to pass to the strategy.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub comet_address: H160,
+    /// Block from which to scan for borrowers on startup.
+    pub deployment_block: u64,
+    /// Minimum profit, in base token units, for a liquidation to be submitted.
+    pub min_profit: U256,
+    /// Maximum amount of base token spent buying collateral in one liquidation.
+    pub max_base_amount: U256,
+    /// Collateral assets to buy, most preferred first. Assets not listed are
+    /// tried after these, in market order.
+    pub collateral_preference: Vec<H160>,
+    /// Priority fee paid by the liquidation transactions.
+    pub priority_fee: U256,
+}
+
+/// A collateral asset supported by the Comet market.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollateralAsset {
+    pub asset: H160,
+    pub price_feed: H160,
+    /// Power of ten matching the asset decimals.
+    pub scale: U256,
+}
+
+/// Order `assets` by `preference`, keeping the market order for assets which
+/// aren't listed in it.
+pub fn order_by_preference(
+    mut assets: Vec<CollateralAsset>,
+    preference: &[H160],
+) -> Vec<CollateralAsset> {
+    assets.sort_by_key(|asset| {
+        preference
+            .iter()
+            .position(|preferred| *preferred == asset.asset)
+            .unwrap_or(preference.len())
+    });
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(n: u64) -> CollateralAsset {
+        CollateralAsset {
+            asset: H160::from_low_u64_be(n),
+            price_feed: H160::zero(),
+            scale: U256::exp10(18),
+        }
+    }
+
+    #[test]
+    fn order_by_preference_puts_preferred_assets_first() {
+        let assets = vec![asset(1), asset(2), asset(3), asset(4)];
+        let ordered = order_by_preference(
+            assets,
+            &[H160::from_low_u64_be(3), H160::from_low_u64_be(1)],
+        );
+        assert_eq!(ordered, vec![asset(3), asset(1), asset(2), asset(4)]);
+    }
+}
@@ -0,0 +1,31 @@
+//! Generates the ethers bindings of the contracts whose ABIs are in `abi/` into
+//! `OUT_DIR`, so the bindings always match the ABIs checked in.
+
+use std::{env, path::PathBuf};
+
+use ethers::contract::Abigen;
+
+/// The contracts to generate bindings for: the name of their binding, and the name of
+/// their ABI in `abi/`, which is also the name of their module.
+const CONTRACTS: [(&str, &str); 5] = [
+    ("BlindArb", "blind_arb"),
+    ("BalancerFlashloan", "balancer_flashloan"),
+    ("IUniswapV2Pair", "i_uniswap_v2_pair"),
+    ("IWETH", "iweth"),
+    ("Owned", "owned"),
+];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=build.rs");
+
+    for (name, module) in CONTRACTS {
+        let abi = manifest_dir.join("abi").join(format!("{}.json", module));
+        println!("cargo:rerun-if-changed={}", abi.display());
+        Abigen::new(name, abi.to_string_lossy())
+            .and_then(Abigen::generate)
+            .and_then(|bindings| bindings.write_to_file(out_dir.join(format!("{}.rs", module))))
+            .unwrap_or_else(|e| panic!("failed to generate the bindings of {}: {}", name, e));
+    }
+}
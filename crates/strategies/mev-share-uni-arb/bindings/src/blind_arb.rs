@@ -1,1038 +0,0 @@
-pub use blind_arb::*;
-#[allow(
-    clippy::enum_variant_names,
-    clippy::too_many_arguments,
-    clippy::upper_case_acronyms,
-    clippy::type_complexity,
-    dead_code,
-    non_camel_case_types,
-)]
-
-#[allow(deprecated)]
-    pub mod blind_arb {
-        fn __abi() -> ::ethers::core::abi::Abi {
-        ::ethers::core::abi::ethabi::Contract {
-            constructor: ::core::option::Option::Some(::ethers::core::abi::ethabi::Constructor {
-                inputs: ::std::vec![
-                    ::ethers::core::abi::ethabi::Param {
-                        name: ::std::borrow::ToOwned::to_owned("_wethAddress"),
-                        kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                        internal_type: ::core::option::Option::Some(
-                            ::std::borrow::ToOwned::to_owned("address"),
-                        ),
-                    },
-                ],
-            }),
-            functions: ::core::convert::From::from([
-                (
-                    ::std::borrow::ToOwned::to_owned("WETH_ADDRESS"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("WETH_ADDRESS"),
-                            inputs: ::std::vec![],
-                            outputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::string::String::new(),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address"),
-                                    ),
-                                },
-                            ],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::View,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("call"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("call"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("_to"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address payable"),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("_value"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Uint(
-                                        256usize,
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("uint256"),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("_data"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Bytes,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("bytes"),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("executeArbitrage"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("executeArbitrage"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("firstPairAddress"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address"),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("secondPairAddress"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address"),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned(
-                                        "percentageToPayToCoinbase",
-                                    ),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Uint(
-                                        256usize,
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("uint256"),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("getAmountIn"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("getAmountIn"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("firstPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("secondPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::string::String::new(),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Uint(
-                                        256usize,
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("uint256"),
-                                    ),
-                                },
-                            ],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("getDenominator"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("getDenominator"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("firstPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("secondPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::string::String::new(),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Uint(
-                                        256usize,
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("uint256"),
-                                    ),
-                                },
-                            ],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("getNumerator"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("getNumerator"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("firstPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("secondPairData"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Tuple(
-                                        ::std::vec![
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Uint(256usize),
-                                            ::ethers::core::abi::ethabi::ParamType::Bool,
-                                        ],
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned(
-                                            "struct IPairReserves.PairReserves",
-                                        ),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::string::String::new(),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Uint(
-                                        256usize,
-                                    ),
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("uint256"),
-                                    ),
-                                },
-                            ],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::View,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("owner"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("owner"),
-                            inputs: ::std::vec![],
-                            outputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::string::String::new(),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address"),
-                                    ),
-                                },
-                            ],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::View,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("renounceOwnership"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("renounceOwnership"),
-                            inputs: ::std::vec![],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("transferOwnership"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("transferOwnership"),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::Param {
-                                    name: ::std::borrow::ToOwned::to_owned("newOwner"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    internal_type: ::core::option::Option::Some(
-                                        ::std::borrow::ToOwned::to_owned("address"),
-                                    ),
-                                },
-                            ],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("withdrawETHToOwner"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned("withdrawETHToOwner"),
-                            inputs: ::std::vec![],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-                (
-                    ::std::borrow::ToOwned::to_owned("withdrawWETHToOwner"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Function {
-                            name: ::std::borrow::ToOwned::to_owned(
-                                "withdrawWETHToOwner",
-                            ),
-                            inputs: ::std::vec![],
-                            outputs: ::std::vec![],
-                            constant: ::core::option::Option::None,
-                            state_mutability: ::ethers::core::abi::ethabi::StateMutability::NonPayable,
-                        },
-                    ],
-                ),
-            ]),
-            events: ::core::convert::From::from([
-                (
-                    ::std::borrow::ToOwned::to_owned("OwnershipTransferred"),
-                    ::std::vec![
-                        ::ethers::core::abi::ethabi::Event {
-                            name: ::std::borrow::ToOwned::to_owned(
-                                "OwnershipTransferred",
-                            ),
-                            inputs: ::std::vec![
-                                ::ethers::core::abi::ethabi::EventParam {
-                                    name: ::std::borrow::ToOwned::to_owned("previousOwner"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    indexed: true,
-                                },
-                                ::ethers::core::abi::ethabi::EventParam {
-                                    name: ::std::borrow::ToOwned::to_owned("newOwner"),
-                                    kind: ::ethers::core::abi::ethabi::ParamType::Address,
-                                    indexed: true,
-                                },
-                            ],
-                            anonymous: false,
-                        },
-                    ],
-                ),
-            ]),
-            errors: ::std::collections::BTreeMap::new(),
-            receive: true,
-            fallback: false,
-        }
-    }
-    ///The parsed JSON ABI of the contract.
-    pub static BLINDARB_ABI: ::ethers::contract::Lazy<::ethers::core::abi::Abi> = ::ethers::contract::Lazy::new(
-        __abi,
-    );
-    pub struct BlindArb<M>(::ethers::contract::Contract<M>);
-    impl<M> ::core::clone::Clone for BlindArb<M> {
-        fn clone(&self) -> Self {
-            Self(::core::clone::Clone::clone(&self.0))
-        }
-    }
-    impl<M> ::core::ops::Deref for BlindArb<M> {
-        type Target = ::ethers::contract::Contract<M>;
-        fn deref(&self) -> &Self::Target {
-            &self.0
-        }
-    }
-    impl<M> ::core::ops::DerefMut for BlindArb<M> {
-        fn deref_mut(&mut self) -> &mut Self::Target {
-            &mut self.0
-        }
-    }
-    impl<M> ::core::fmt::Debug for BlindArb<M> {
-        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-            f.debug_tuple(::core::stringify!(BlindArb))
-                .field(&self.address())
-                .finish()
-        }
-    }
-    impl<M: ::ethers::providers::Middleware> BlindArb<M> {
-        /// Creates a new contract instance with the specified `ethers` client at
-        /// `address`. The contract derefs to a `ethers::Contract` object.
-        pub fn new<T: Into<::ethers::core::types::Address>>(
-            address: T,
-            client: ::std::sync::Arc<M>,
-        ) -> Self {
-            Self(
-                ::ethers::contract::Contract::new(
-                    address.into(),
-                    BLINDARB_ABI.clone(),
-                    client,
-                ),
-            )
-        }
-        ///Calls the contract's `WETH_ADDRESS` (0x040141e5) function
-        pub fn weth_address(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<
-            M,
-            ::ethers::core::types::Address,
-        > {
-            self.0
-                .method_hash([4, 1, 65, 229], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `call` (0x6dbf2fa0) function
-        pub fn call(
-            &self,
-            to: ::ethers::core::types::Address,
-            value: ::ethers::core::types::U256,
-            data: ::ethers::core::types::Bytes,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([109, 191, 47, 160], (to, value, data))
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `executeArbitrage` (0x650f84be) function
-        pub fn execute_arbitrage(
-            &self,
-            first_pair_address: ::ethers::core::types::Address,
-            second_pair_address: ::ethers::core::types::Address,
-            percentage_to_pay_to_coinbase: ::ethers::core::types::U256,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash(
-                    [101, 15, 132, 190],
-                    (
-                        first_pair_address,
-                        second_pair_address,
-                        percentage_to_pay_to_coinbase,
-                    ),
-                )
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `getAmountIn` (0xeb6b2116) function
-        pub fn get_amount_in(
-            &self,
-            first_pair_data: PairReserves,
-            second_pair_data: PairReserves,
-        ) -> ::ethers::contract::builders::ContractCall<M, ::ethers::core::types::U256> {
-            self.0
-                .method_hash([235, 107, 33, 22], (first_pair_data, second_pair_data))
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `getDenominator` (0x1fa7d39b) function
-        pub fn get_denominator(
-            &self,
-            first_pair_data: PairReserves,
-            second_pair_data: PairReserves,
-        ) -> ::ethers::contract::builders::ContractCall<M, ::ethers::core::types::U256> {
-            self.0
-                .method_hash([31, 167, 211, 155], (first_pair_data, second_pair_data))
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `getNumerator` (0x00bb7d67) function
-        pub fn get_numerator(
-            &self,
-            first_pair_data: PairReserves,
-            second_pair_data: PairReserves,
-        ) -> ::ethers::contract::builders::ContractCall<M, ::ethers::core::types::U256> {
-            self.0
-                .method_hash([0, 187, 125, 103], (first_pair_data, second_pair_data))
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `owner` (0x8da5cb5b) function
-        pub fn owner(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<
-            M,
-            ::ethers::core::types::Address,
-        > {
-            self.0
-                .method_hash([141, 165, 203, 91], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `renounceOwnership` (0x715018a6) function
-        pub fn renounce_ownership(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([113, 80, 24, 166], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `transferOwnership` (0xf2fde38b) function
-        pub fn transfer_ownership(
-            &self,
-            new_owner: ::ethers::core::types::Address,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([242, 253, 227, 139], new_owner)
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `withdrawETHToOwner` (0x77432b8c) function
-        pub fn withdraw_eth_to_owner(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([119, 67, 43, 140], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `withdrawWETHToOwner` (0xea3e506c) function
-        pub fn withdraw_weth_to_owner(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([234, 62, 80, 108], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Gets the contract's `OwnershipTransferred` event
-        pub fn ownership_transferred_filter(
-            &self,
-        ) -> ::ethers::contract::builders::Event<
-            ::std::sync::Arc<M>,
-            M,
-            OwnershipTransferredFilter,
-        > {
-            self.0.event()
-        }
-        /// Returns an `Event` builder for all the events of this contract.
-        pub fn events(
-            &self,
-        ) -> ::ethers::contract::builders::Event<
-            ::std::sync::Arc<M>,
-            M,
-            OwnershipTransferredFilter,
-        > {
-            self.0.event_with_filter(::core::default::Default::default())
-        }
-    }
-    impl<M: ::ethers::providers::Middleware> From<::ethers::contract::Contract<M>>
-    for BlindArb<M> {
-        fn from(contract: ::ethers::contract::Contract<M>) -> Self {
-            Self::new(contract.address(), contract.client())
-        }
-    }
-    #[derive(
-        Clone,
-        ::ethers::contract::EthEvent,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethevent(
-        name = "OwnershipTransferred",
-        abi = "OwnershipTransferred(address,address)"
-    )]
-    pub struct OwnershipTransferredFilter {
-        #[ethevent(indexed)]
-        pub previous_owner: ::ethers::core::types::Address,
-        #[ethevent(indexed)]
-        pub new_owner: ::ethers::core::types::Address,
-    }
-    ///Container type for all input parameters for the `WETH_ADDRESS` function with signature `WETH_ADDRESS()` and selector `0x040141e5`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "WETH_ADDRESS", abi = "WETH_ADDRESS()")]
-    pub struct WethAddressCall;
-    ///Container type for all input parameters for the `call` function with signature `call(address,uint256,bytes)` and selector `0x6dbf2fa0`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "call", abi = "call(address,uint256,bytes)")]
-    pub struct CallCall {
-        pub to: ::ethers::core::types::Address,
-        pub value: ::ethers::core::types::U256,
-        pub data: ::ethers::core::types::Bytes,
-    }
-    ///Container type for all input parameters for the `executeArbitrage` function with signature `executeArbitrage(address,address,uint256)` and selector `0x650f84be`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(
-        name = "executeArbitrage",
-        abi = "executeArbitrage(address,address,uint256)"
-    )]
-    pub struct ExecuteArbitrageCall {
-        pub first_pair_address: ::ethers::core::types::Address,
-        pub second_pair_address: ::ethers::core::types::Address,
-        pub percentage_to_pay_to_coinbase: ::ethers::core::types::U256,
-    }
-    ///Container type for all input parameters for the `getAmountIn` function with signature `getAmountIn((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0xeb6b2116`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(
-        name = "getAmountIn",
-        abi = "getAmountIn((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))"
-    )]
-    pub struct GetAmountInCall {
-        pub first_pair_data: PairReserves,
-        pub second_pair_data: PairReserves,
-    }
-    ///Container type for all input parameters for the `getDenominator` function with signature `getDenominator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0x1fa7d39b`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(
-        name = "getDenominator",
-        abi = "getDenominator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))"
-    )]
-    pub struct GetDenominatorCall {
-        pub first_pair_data: PairReserves,
-        pub second_pair_data: PairReserves,
-    }
-    ///Container type for all input parameters for the `getNumerator` function with signature `getNumerator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0x00bb7d67`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(
-        name = "getNumerator",
-        abi = "getNumerator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))"
-    )]
-    pub struct GetNumeratorCall {
-        pub first_pair_data: PairReserves,
-        pub second_pair_data: PairReserves,
-    }
-    ///Container type for all input parameters for the `owner` function with signature `owner()` and selector `0x8da5cb5b`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "owner", abi = "owner()")]
-    pub struct OwnerCall;
-    ///Container type for all input parameters for the `renounceOwnership` function with signature `renounceOwnership()` and selector `0x715018a6`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "renounceOwnership", abi = "renounceOwnership()")]
-    pub struct RenounceOwnershipCall;
-    ///Container type for all input parameters for the `transferOwnership` function with signature `transferOwnership(address)` and selector `0xf2fde38b`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "transferOwnership", abi = "transferOwnership(address)")]
-    pub struct TransferOwnershipCall {
-        pub new_owner: ::ethers::core::types::Address,
-    }
-    ///Container type for all input parameters for the `withdrawETHToOwner` function with signature `withdrawETHToOwner()` and selector `0x77432b8c`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "withdrawETHToOwner", abi = "withdrawETHToOwner()")]
-    pub struct WithdrawETHToOwnerCall;
-    ///Container type for all input parameters for the `withdrawWETHToOwner` function with signature `withdrawWETHToOwner()` and selector `0xea3e506c`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "withdrawWETHToOwner", abi = "withdrawWETHToOwner()")]
-    pub struct WithdrawWETHToOwnerCall;
-    ///Container type for all of the contract's call
-    #[derive(Clone, ::ethers::contract::EthAbiType, Debug, PartialEq, Eq, Hash)]
-    pub enum BlindArbCalls {
-        WethAddress(WethAddressCall),
-        Call(CallCall),
-        ExecuteArbitrage(ExecuteArbitrageCall),
-        GetAmountIn(GetAmountInCall),
-        GetDenominator(GetDenominatorCall),
-        GetNumerator(GetNumeratorCall),
-        Owner(OwnerCall),
-        RenounceOwnership(RenounceOwnershipCall),
-        TransferOwnership(TransferOwnershipCall),
-        WithdrawETHToOwner(WithdrawETHToOwnerCall),
-        WithdrawWETHToOwner(WithdrawWETHToOwnerCall),
-    }
-    impl ::ethers::core::abi::AbiDecode for BlindArbCalls {
-        fn decode(
-            data: impl AsRef<[u8]>,
-        ) -> ::core::result::Result<Self, ::ethers::core::abi::AbiError> {
-            let data = data.as_ref();
-            if let Ok(decoded)
-                = <WethAddressCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::WethAddress(decoded));
-            }
-            if let Ok(decoded)
-                = <CallCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::Call(decoded));
-            }
-            if let Ok(decoded)
-                = <ExecuteArbitrageCall as ::ethers::core::abi::AbiDecode>::decode(
-                    data,
-                ) {
-                return Ok(Self::ExecuteArbitrage(decoded));
-            }
-            if let Ok(decoded)
-                = <GetAmountInCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::GetAmountIn(decoded));
-            }
-            if let Ok(decoded)
-                = <GetDenominatorCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::GetDenominator(decoded));
-            }
-            if let Ok(decoded)
-                = <GetNumeratorCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::GetNumerator(decoded));
-            }
-            if let Ok(decoded)
-                = <OwnerCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::Owner(decoded));
-            }
-            if let Ok(decoded)
-                = <RenounceOwnershipCall as ::ethers::core::abi::AbiDecode>::decode(
-                    data,
-                ) {
-                return Ok(Self::RenounceOwnership(decoded));
-            }
-            if let Ok(decoded)
-                = <TransferOwnershipCall as ::ethers::core::abi::AbiDecode>::decode(
-                    data,
-                ) {
-                return Ok(Self::TransferOwnership(decoded));
-            }
-            if let Ok(decoded)
-                = <WithdrawETHToOwnerCall as ::ethers::core::abi::AbiDecode>::decode(
-                    data,
-                ) {
-                return Ok(Self::WithdrawETHToOwner(decoded));
-            }
-            if let Ok(decoded)
-                = <WithdrawWETHToOwnerCall as ::ethers::core::abi::AbiDecode>::decode(
-                    data,
-                ) {
-                return Ok(Self::WithdrawWETHToOwner(decoded));
-            }
-            Err(::ethers::core::abi::Error::InvalidData.into())
-        }
-    }
-    impl ::ethers::core::abi::AbiEncode for BlindArbCalls {
-        fn encode(self) -> Vec<u8> {
-            match self {
-                Self::WethAddress(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::Call(element) => ::ethers::core::abi::AbiEncode::encode(element),
-                Self::ExecuteArbitrage(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::GetAmountIn(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::GetDenominator(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::GetNumerator(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::Owner(element) => ::ethers::core::abi::AbiEncode::encode(element),
-                Self::RenounceOwnership(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::TransferOwnership(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::WithdrawETHToOwner(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::WithdrawWETHToOwner(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-            }
-        }
-    }
-    impl ::core::fmt::Display for BlindArbCalls {
-        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-            match self {
-                Self::WethAddress(element) => ::core::fmt::Display::fmt(element, f),
-                Self::Call(element) => ::core::fmt::Display::fmt(element, f),
-                Self::ExecuteArbitrage(element) => ::core::fmt::Display::fmt(element, f),
-                Self::GetAmountIn(element) => ::core::fmt::Display::fmt(element, f),
-                Self::GetDenominator(element) => ::core::fmt::Display::fmt(element, f),
-                Self::GetNumerator(element) => ::core::fmt::Display::fmt(element, f),
-                Self::Owner(element) => ::core::fmt::Display::fmt(element, f),
-                Self::RenounceOwnership(element) => ::core::fmt::Display::fmt(element, f),
-                Self::TransferOwnership(element) => ::core::fmt::Display::fmt(element, f),
-                Self::WithdrawETHToOwner(element) => {
-                    ::core::fmt::Display::fmt(element, f)
-                }
-                Self::WithdrawWETHToOwner(element) => {
-                    ::core::fmt::Display::fmt(element, f)
-                }
-            }
-        }
-    }
-    impl ::core::convert::From<WethAddressCall> for BlindArbCalls {
-        fn from(value: WethAddressCall) -> Self {
-            Self::WethAddress(value)
-        }
-    }
-    impl ::core::convert::From<CallCall> for BlindArbCalls {
-        fn from(value: CallCall) -> Self {
-            Self::Call(value)
-        }
-    }
-    impl ::core::convert::From<ExecuteArbitrageCall> for BlindArbCalls {
-        fn from(value: ExecuteArbitrageCall) -> Self {
-            Self::ExecuteArbitrage(value)
-        }
-    }
-    impl ::core::convert::From<GetAmountInCall> for BlindArbCalls {
-        fn from(value: GetAmountInCall) -> Self {
-            Self::GetAmountIn(value)
-        }
-    }
-    impl ::core::convert::From<GetDenominatorCall> for BlindArbCalls {
-        fn from(value: GetDenominatorCall) -> Self {
-            Self::GetDenominator(value)
-        }
-    }
-    impl ::core::convert::From<GetNumeratorCall> for BlindArbCalls {
-        fn from(value: GetNumeratorCall) -> Self {
-            Self::GetNumerator(value)
-        }
-    }
-    impl ::core::convert::From<OwnerCall> for BlindArbCalls {
-        fn from(value: OwnerCall) -> Self {
-            Self::Owner(value)
-        }
-    }
-    impl ::core::convert::From<RenounceOwnershipCall> for BlindArbCalls {
-        fn from(value: RenounceOwnershipCall) -> Self {
-            Self::RenounceOwnership(value)
-        }
-    }
-    impl ::core::convert::From<TransferOwnershipCall> for BlindArbCalls {
-        fn from(value: TransferOwnershipCall) -> Self {
-            Self::TransferOwnership(value)
-        }
-    }
-    impl ::core::convert::From<WithdrawETHToOwnerCall> for BlindArbCalls {
-        fn from(value: WithdrawETHToOwnerCall) -> Self {
-            Self::WithdrawETHToOwner(value)
-        }
-    }
-    impl ::core::convert::From<WithdrawWETHToOwnerCall> for BlindArbCalls {
-        fn from(value: WithdrawWETHToOwnerCall) -> Self {
-            Self::WithdrawWETHToOwner(value)
-        }
-    }
-    ///Container type for all return fields from the `WETH_ADDRESS` function with signature `WETH_ADDRESS()` and selector `0x040141e5`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct WethAddressReturn(pub ::ethers::core::types::Address);
-    ///Container type for all return fields from the `getAmountIn` function with signature `getAmountIn((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0xeb6b2116`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct GetAmountInReturn(pub ::ethers::core::types::U256);
-    ///Container type for all return fields from the `getDenominator` function with signature `getDenominator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0x1fa7d39b`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct GetDenominatorReturn(pub ::ethers::core::types::U256);
-    ///Container type for all return fields from the `getNumerator` function with signature `getNumerator((uint256,uint256,uint256,bool),(uint256,uint256,uint256,bool))` and selector `0x00bb7d67`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct GetNumeratorReturn(pub ::ethers::core::types::U256);
-    ///Container type for all return fields from the `owner` function with signature `owner()` and selector `0x8da5cb5b`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct OwnerReturn(pub ::ethers::core::types::Address);
-    ///`PairReserves(uint256,uint256,uint256,bool)`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct PairReserves {
-        pub reserve_0: ::ethers::core::types::U256,
-        pub reserve_1: ::ethers::core::types::U256,
-        pub price: ::ethers::core::types::U256,
-        pub is_weth_zero: bool,
-    }
-}
@@ -1,179 +0,0 @@
-pub use i_uniswap_v2_pair::*;
-/// This module was auto-generated with ethers-rs Abigen.
-/// More information at: <https://github.com/gakonst/ethers-rs>
-#[allow(
-    clippy::enum_variant_names,
-    clippy::too_many_arguments,
-    clippy::upper_case_acronyms,
-    clippy::type_complexity,
-    dead_code,
-    non_camel_case_types,
-)]
-pub mod i_uniswap_v2_pair {
-    #[rustfmt::skip]
-    const __ABI: &str = "[{\"inputs\":[],\"stateMutability\":\"view\",\"type\":\"function\",\"name\":\"getReserves\",\"outputs\":[{\"internalType\":\"uint112\",\"name\":\"reserve0\",\"type\":\"uint112\",\"components\":[]},{\"internalType\":\"uint112\",\"name\":\"reserve1\",\"type\":\"uint112\",\"components\":[]},{\"internalType\":\"uint32\",\"name\":\"blockTimestampLast\",\"type\":\"uint32\",\"components\":[]}]},{\"inputs\":[{\"internalType\":\"uint256\",\"name\":\"amount0Out\",\"type\":\"uint256\",\"components\":[]},{\"internalType\":\"uint256\",\"name\":\"amount1Out\",\"type\":\"uint256\",\"components\":[]},{\"internalType\":\"address\",\"name\":\"to\",\"type\":\"address\",\"components\":[]},{\"internalType\":\"bytes\",\"name\":\"data\",\"type\":\"bytes\",\"components\":[]}],\"stateMutability\":\"nonpayable\",\"type\":\"function\",\"name\":\"swap\",\"outputs\":[]}]";
-    ///The parsed JSON ABI of the contract.
-    pub static IUNISWAPV2PAIR_ABI: ::ethers::contract::Lazy<::ethers::core::abi::Abi> = ::ethers::contract::Lazy::new(||
-    ::ethers::core::utils::__serde_json::from_str(__ABI).expect("ABI is always valid"));
-    pub struct IUniswapV2Pair<M>(::ethers::contract::Contract<M>);
-    impl<M> ::core::clone::Clone for IUniswapV2Pair<M> {
-        fn clone(&self) -> Self {
-            Self(::core::clone::Clone::clone(&self.0))
-        }
-    }
-    impl<M> ::core::ops::Deref for IUniswapV2Pair<M> {
-        type Target = ::ethers::contract::Contract<M>;
-        fn deref(&self) -> &Self::Target {
-            &self.0
-        }
-    }
-    impl<M> ::core::ops::DerefMut for IUniswapV2Pair<M> {
-        fn deref_mut(&mut self) -> &mut Self::Target {
-            &mut self.0
-        }
-    }
-    impl<M> ::core::fmt::Debug for IUniswapV2Pair<M> {
-        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-            f.debug_tuple(stringify!(IUniswapV2Pair)).field(&self.address()).finish()
-        }
-    }
-    impl<M: ::ethers::providers::Middleware> IUniswapV2Pair<M> {
-        /// Creates a new contract instance with the specified `ethers` client at
-        /// `address`. The contract derefs to a `ethers::Contract` object.
-        pub fn new<T: Into<::ethers::core::types::Address>>(
-            address: T,
-            client: ::std::sync::Arc<M>,
-        ) -> Self {
-            Self(
-                ::ethers::contract::Contract::new(
-                    address.into(),
-                    IUNISWAPV2PAIR_ABI.clone(),
-                    client,
-                ),
-            )
-        }
-        ///Calls the contract's `getReserves` (0x0902f1ac) function
-        pub fn get_reserves(
-            &self,
-        ) -> ::ethers::contract::builders::ContractCall<M, (u128, u128, u32)> {
-            self.0
-                .method_hash([9, 2, 241, 172], ())
-                .expect("method not found (this should never happen)")
-        }
-        ///Calls the contract's `swap` (0x022c0d9f) function
-        pub fn swap(
-            &self,
-            amount_0_out: ::ethers::core::types::U256,
-            amount_1_out: ::ethers::core::types::U256,
-            to: ::ethers::core::types::Address,
-            data: ::ethers::core::types::Bytes,
-        ) -> ::ethers::contract::builders::ContractCall<M, ()> {
-            self.0
-                .method_hash([2, 44, 13, 159], (amount_0_out, amount_1_out, to, data))
-                .expect("method not found (this should never happen)")
-        }
-    }
-    impl<M: ::ethers::providers::Middleware> From<::ethers::contract::Contract<M>>
-    for IUniswapV2Pair<M> {
-        fn from(contract: ::ethers::contract::Contract<M>) -> Self {
-            Self::new(contract.address(), contract.client())
-        }
-    }
-    ///Container type for all input parameters for the `getReserves` function with signature `getReserves()` and selector `0x0902f1ac`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "getReserves", abi = "getReserves()")]
-    pub struct GetReservesCall;
-    ///Container type for all input parameters for the `swap` function with signature `swap(uint256,uint256,address,bytes)` and selector `0x022c0d9f`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthCall,
-        ::ethers::contract::EthDisplay,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    #[ethcall(name = "swap", abi = "swap(uint256,uint256,address,bytes)")]
-    pub struct SwapCall {
-        pub amount_0_out: ::ethers::core::types::U256,
-        pub amount_1_out: ::ethers::core::types::U256,
-        pub to: ::ethers::core::types::Address,
-        pub data: ::ethers::core::types::Bytes,
-    }
-    ///Container type for all of the contract's call
-    #[derive(Clone, ::ethers::contract::EthAbiType, Debug, PartialEq, Eq, Hash)]
-    pub enum IUniswapV2PairCalls {
-        GetReserves(GetReservesCall),
-        Swap(SwapCall),
-    }
-    impl ::ethers::core::abi::AbiDecode for IUniswapV2PairCalls {
-        fn decode(
-            data: impl AsRef<[u8]>,
-        ) -> ::core::result::Result<Self, ::ethers::core::abi::AbiError> {
-            let data = data.as_ref();
-            if let Ok(decoded)
-                = <GetReservesCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::GetReserves(decoded));
-            }
-            if let Ok(decoded)
-                = <SwapCall as ::ethers::core::abi::AbiDecode>::decode(data) {
-                return Ok(Self::Swap(decoded));
-            }
-            Err(::ethers::core::abi::Error::InvalidData.into())
-        }
-    }
-    impl ::ethers::core::abi::AbiEncode for IUniswapV2PairCalls {
-        fn encode(self) -> Vec<u8> {
-            match self {
-                Self::GetReserves(element) => {
-                    ::ethers::core::abi::AbiEncode::encode(element)
-                }
-                Self::Swap(element) => ::ethers::core::abi::AbiEncode::encode(element),
-            }
-        }
-    }
-    impl ::core::fmt::Display for IUniswapV2PairCalls {
-        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-            match self {
-                Self::GetReserves(element) => ::core::fmt::Display::fmt(element, f),
-                Self::Swap(element) => ::core::fmt::Display::fmt(element, f),
-            }
-        }
-    }
-    impl ::core::convert::From<GetReservesCall> for IUniswapV2PairCalls {
-        fn from(value: GetReservesCall) -> Self {
-            Self::GetReserves(value)
-        }
-    }
-    impl ::core::convert::From<SwapCall> for IUniswapV2PairCalls {
-        fn from(value: SwapCall) -> Self {
-            Self::Swap(value)
-        }
-    }
-    ///Container type for all return fields from the `getReserves` function with signature `getReserves()` and selector `0x0902f1ac`
-    #[derive(
-        Clone,
-        ::ethers::contract::EthAbiType,
-        ::ethers::contract::EthAbiCodec,
-        Default,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash
-    )]
-    pub struct GetReservesReturn {
-        pub reserve_0: u128,
-        pub reserve_1: u128,
-        pub block_timestamp_last: u32,
-    }
-}
@@ -1,6 +1,27 @@
-pub mod blind_arb;
-pub mod i_uniswap_v2_pair;
-pub mod iweth;
-pub mod owned;
+//! Bindings of the contracts of the strategy, generated at build time from the ABIs in
+//! `abi/`. To add a contract, add its ABI there and list it in `build.rs`.
 
+/// Generated bindings of the blind arb contract, which backruns between two v2 pairs.
+pub mod blind_arb {
+    include!(concat!(env!("OUT_DIR"), "/blind_arb.rs"));
+}
 
+/// Generated bindings of the arb contract which borrows from balancer flash loans.
+pub mod balancer_flashloan {
+    include!(concat!(env!("OUT_DIR"), "/balancer_flashloan.rs"));
+}
+
+/// Generated bindings of uniswap v2 pairs.
+pub mod i_uniswap_v2_pair {
+    include!(concat!(env!("OUT_DIR"), "/i_uniswap_v2_pair.rs"));
+}
+
+/// Generated bindings of WETH.
+pub mod iweth {
+    include!(concat!(env!("OUT_DIR"), "/iweth.rs"));
+}
+
+/// Generated bindings of solmate's `Owned`.
+pub mod owned {
+    include!(concat!(env!("OUT_DIR"), "/owned.rs"));
+}
@@ -29,6 +29,9 @@ SELECT
     , uniV3_weth_pairs.pair AS v3_pool
     , uniV2_weth_pairs.pair AS v2_pool
     , uniV3_weth_pairs.weth_token0 AS weth_token0
+    -- uniswap v2 charges a 0.3% fee; forks with other fees should override these columns
+    , 30 AS fee_bps
+    , 0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f AS factory
 FROM 
     uniV3_weth_pairs
 JOIN 
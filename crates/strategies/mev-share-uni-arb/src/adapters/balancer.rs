@@ -0,0 +1,242 @@
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    prelude::Lazy,
+    types::{Address, Bytes, H160, H256, U256},
+    utils::id,
+};
+
+use super::{f64_to_u256, u256_to_f64, PoolAdapter, PoolBalances};
+
+/// Address of the Balancer V2 vault, through which all swaps are routed.
+pub static BALANCER_VAULT_ADDRESS: Lazy<Address> = Lazy::new(|| {
+    "0xBA12222222228d8Ba445958a75a0704d566BF2C8"
+        .parse()
+        .unwrap()
+});
+
+/// Balancer only allows swapping in up to 30% of the input balance.
+const MAX_IN_RATIO_PERCENT: u64 = 30;
+
+/// Balancer fees and weights are 18 decimal fixed point numbers.
+const ONE: u64 = 1_000_000_000_000_000_000;
+
+/// Adapter for a Balancer weighted pool.
+#[derive(Debug, Clone)]
+pub struct BalancerWeightedPool {
+    /// Address of the pool.
+    pub address: H160,
+    /// Id of the pool in the vault.
+    pub pool_id: H256,
+    /// Tokens in the pool.
+    pub tokens: Vec<H160>,
+    /// Vault balances for each token.
+    pub balances: PoolBalances,
+    /// Normalized weights for each token (18 decimals, summing to 1e18).
+    pub weights: Vec<U256>,
+    /// Swap fee charged on the input amount (18 decimals).
+    pub swap_fee: U256,
+}
+
+impl BalancerWeightedPool {
+    fn index_of(&self, token: H160) -> Option<usize> {
+        self.tokens.iter().position(|t| *t == token)
+    }
+}
+
+impl PoolAdapter for BalancerWeightedPool {
+    fn address(&self) -> H160 {
+        self.address
+    }
+
+    fn swap_target(&self) -> H160 {
+        *BALANCER_VAULT_ADDRESS
+    }
+
+    fn get_amount_out(&self, token_in: H160, token_out: H160, amount_in: U256) -> Option<U256> {
+        let (i, j) = (self.index_of(token_in)?, self.index_of(token_out)?);
+        let balances = self.balances.get();
+        let (balance_in, balance_out) = (*balances.get(i)?, *balances.get(j)?);
+        if i == j || balance_in.is_zero() || balance_out.is_zero() {
+            return None;
+        }
+        if amount_in > balance_in * MAX_IN_RATIO_PERCENT / 100 {
+            return None;
+        }
+
+        let amount_in = amount_in * (U256::from(ONE) - self.swap_fee) / U256::from(ONE);
+        // Equal weights reduce to constant product, which we can do exactly.
+        if self.weights[i] == self.weights[j] {
+            return Some(balance_out * amount_in / (balance_in + amount_in));
+        }
+
+        // out = balance_out * (1 - (balance_in / (balance_in + amount_in)) ^ (w_in / w_out))
+        let base = u256_to_f64(balance_in) / u256_to_f64(balance_in + amount_in);
+        let exponent = u256_to_f64(self.weights[i]) / u256_to_f64(self.weights[j]);
+        let amount_out = u256_to_f64(balance_out) * (1.0 - base.powf(exponent));
+        Some(f64_to_u256(amount_out).min(balance_out))
+    }
+
+    fn encode_swap(
+        &self,
+        token_in: H160,
+        token_out: H160,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: H160,
+    ) -> Option<Bytes> {
+        self.index_of(token_in)?;
+        self.index_of(token_out)?;
+
+        // SingleSwap(poolId, kind = GIVEN_IN, assetIn, assetOut, amount, userData)
+        let single_swap = Token::Tuple(vec![
+            Token::FixedBytes(self.pool_id.as_bytes().to_vec()),
+            Token::Uint(U256::zero()),
+            Token::Address(token_in),
+            Token::Address(token_out),
+            Token::Uint(amount_in),
+            Token::Bytes(vec![]),
+        ]);
+        // FundManagement(sender, fromInternalBalance, recipient, toInternalBalance)
+        let funds = Token::Tuple(vec![
+            Token::Address(recipient),
+            Token::Bool(false),
+            Token::Address(recipient),
+            Token::Bool(false),
+        ]);
+        let args = encode(&[
+            single_swap,
+            funds,
+            Token::Uint(min_amount_out),
+            Token::Uint(U256::MAX),
+        ]);
+
+        let selector = id("swap((bytes32,uint8,address,address,uint256,bytes),(address,bool,address,bool),uint256,uint256)");
+        Some([selector.to_vec(), args].concat().into())
+    }
+
+    fn balance_calls(&self) -> Vec<(H160, Bytes)> {
+        let args = encode(&[Token::FixedBytes(self.pool_id.as_bytes().to_vec())]);
+        let selector = id("getPoolTokens(bytes32)");
+        vec![(
+            *BALANCER_VAULT_ADDRESS,
+            [selector.to_vec(), args].concat().into(),
+        )]
+    }
+
+    fn update_balances(&self, outputs: &[Bytes]) -> Option<()> {
+        // getPoolTokens returns (address[] tokens, uint256[] balances, uint256 lastChangeBlock).
+        let [output] = outputs else {
+            return None;
+        };
+        let mut decoded = decode(
+            &[
+                ParamType::Array(Box::new(ParamType::Address)),
+                ParamType::Array(Box::new(ParamType::Uint(256))),
+                ParamType::Uint(256),
+            ],
+            output,
+        )
+        .ok()?
+        .into_iter();
+        let tokens = decoded.next()?.into_array()?;
+        let balances = decoded.next()?.into_array()?;
+        // The vault lists the pool's tokens in its own order.
+        let balances = self
+            .tokens
+            .iter()
+            .map(|token| {
+                let index = tokens
+                    .iter()
+                    .position(|listed| listed.clone().into_address() == Some(*token))?;
+                balances.get(index)?.clone().into_uint()
+            })
+            .collect::<Option<Vec<U256>>>()?;
+        self.balances.set(balances);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(weights: [u64; 2]) -> BalancerWeightedPool {
+        BalancerWeightedPool {
+            address: H160::random(),
+            pool_id: H256::random(),
+            tokens: vec![H160::from_low_u64_be(1), H160::from_low_u64_be(2)],
+            balances: vec![U256::exp10(24), U256::exp10(24)].into(),
+            weights: weights
+                .iter()
+                .map(|w| U256::from(*w) * U256::exp10(16))
+                .collect(),
+            swap_fee: U256::exp10(15),
+        }
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let pool = pool([50, 50]);
+        let amount_in = U256::exp10(21);
+        let after_fee = amount_in * 999 / 1000;
+        let expected = U256::exp10(24) * after_fee / (U256::exp10(24) + after_fee);
+        let out = pool.get_amount_out(pool.tokens[0], pool.tokens[1], amount_in);
+        assert_eq!(out, Some(expected));
+    }
+
+    #[test]
+    fn heavier_output_weight_gives_less_output() {
+        let balanced = pool([50, 50]);
+        let skewed = pool([20, 80]);
+        let amount_in = U256::exp10(21);
+        let (a, b) = (balanced.tokens[0], balanced.tokens[1]);
+        assert!(
+            skewed.get_amount_out(a, b, amount_in).unwrap()
+                < balanced.get_amount_out(a, b, amount_in).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_tokens_and_oversized_swaps() {
+        let pool = pool([50, 50]);
+        let (a, b) = (pool.tokens[0], pool.tokens[1]);
+        assert!(pool
+            .get_amount_out(a, H160::random(), U256::one())
+            .is_none());
+        assert!(pool.get_amount_out(a, b, U256::exp10(24)).is_none());
+    }
+
+    #[test]
+    fn updates_balances_from_the_vault() {
+        let pool = pool([50, 50]);
+        let (a, b) = (pool.tokens[0], pool.tokens[1]);
+        let output: Bytes = encode(&[
+            Token::Array(vec![Token::Address(b), Token::Address(a)]),
+            Token::Array(vec![
+                Token::Uint(U256::exp10(23)),
+                Token::Uint(U256::exp10(25)),
+            ]),
+            Token::Uint(U256::one()),
+        ])
+        .into();
+        assert_eq!(pool.balance_calls().len(), 1);
+        assert!(pool.update_balances(&[output]).is_some());
+        assert_eq!(pool.balances.get(), vec![U256::exp10(25), U256::exp10(23)]);
+        assert!(pool.update_balances(&[Bytes::new()]).is_none());
+    }
+
+    #[test]
+    fn encodes_vault_swap_selector() {
+        let pool = pool([50, 50]);
+        let calldata = pool
+            .encode_swap(
+                pool.tokens[0],
+                pool.tokens[1],
+                U256::one(),
+                U256::zero(),
+                H160::random(),
+            )
+            .unwrap();
+        assert_eq!(&calldata[..4], &[0x52, 0xbb, 0xbe, 0x29]);
+    }
+}
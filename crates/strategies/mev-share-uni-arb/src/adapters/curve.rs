@@ -0,0 +1,302 @@
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    types::{Bytes, H160, U256},
+    utils::id,
+};
+
+use super::{PoolAdapter, PoolBalances};
+
+/// Curve fees are expressed with 10 decimals.
+const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+/// Maximum number of newton iterations when solving the invariant.
+const MAX_ITERATIONS: usize = 255;
+
+/// Adapter for a Curve stable pool.
+#[derive(Debug, Clone)]
+pub struct CurveStablePool {
+    /// Address of the pool.
+    pub address: H160,
+    /// Coins in the pool, in index order.
+    pub coins: Vec<H160>,
+    /// Pool balances for each coin, in the coin's own decimals.
+    pub balances: PoolBalances,
+    /// Decimals of each coin.
+    pub decimals: Vec<u8>,
+    /// Amplification coefficient.
+    pub amp: U256,
+    /// Swap fee charged on the output amount (10 decimals).
+    pub fee: U256,
+}
+
+impl CurveStablePool {
+    fn index_of(&self, token: H160) -> Option<usize> {
+        self.coins.iter().position(|c| *c == token)
+    }
+
+    /// Multiplier bringing each coin to 18 decimals.
+    fn rate(&self, i: usize) -> U256 {
+        U256::exp10(18 - self.decimals[i].min(18) as usize)
+    }
+
+    /// Balances normalized to 18 decimals.
+    /// Balances normalized to 18 decimals, or `None` if they overflow.
+    fn xp(&self) -> Option<Vec<U256>> {
+        self.balances
+            .get()
+            .iter()
+            .enumerate()
+            .map(|(i, balance)| balance.checked_mul(self.rate(i)))
+            .collect()
+    }
+
+    /// Solve the stableswap invariant for D. Returns `None` if the pool has no
+    /// amplification, an empty coin, or the solution overflows or doesn't converge.
+    fn get_d(&self, xp: &[U256]) -> Option<U256> {
+        let n = U256::from(xp.len());
+        let sum = xp
+            .iter()
+            .try_fold(U256::zero(), |acc, x| acc.checked_add(*x))?;
+        if sum.is_zero() {
+            return Some(U256::zero());
+        }
+        let ann = self.amp.checked_mul(n)?;
+        let mut d = sum;
+        for _ in 0..MAX_ITERATIONS {
+            let mut d_p = d;
+            for x in xp {
+                d_p = d_p.checked_mul(d)?.checked_div(x.checked_mul(n)?)?;
+            }
+            let d_prev = d;
+            let numerator = ann
+                .checked_mul(sum)?
+                .checked_add(d_p.checked_mul(n)?)?
+                .checked_mul(d)?;
+            let denominator = ann
+                .checked_sub(U256::one())?
+                .checked_mul(d)?
+                .checked_add((n + 1).checked_mul(d_p)?)?;
+            d = numerator.checked_div(denominator)?;
+            if d.abs_diff(d_prev) <= U256::one() {
+                return Some(d);
+            }
+        }
+        None
+    }
+
+    /// Solve for the new balance of coin `j` given the new balance `x` of coin `i`.
+    /// Returns `None` if the solution overflows or doesn't converge.
+    fn get_y(&self, i: usize, j: usize, x: U256, xp: &[U256]) -> Option<U256> {
+        let n = U256::from(xp.len());
+        let d = self.get_d(xp)?;
+        let ann = self.amp.checked_mul(n)?;
+        let mut c = d;
+        let mut sum = U256::zero();
+        for (k, balance) in xp.iter().enumerate() {
+            let balance = match k {
+                _ if k == i => x,
+                _ if k == j => continue,
+                _ => *balance,
+            };
+            sum = sum.checked_add(balance)?;
+            c = c.checked_mul(d)?.checked_div(balance.checked_mul(n)?)?;
+        }
+        c = c.checked_mul(d)?.checked_div(ann.checked_mul(n)?)?;
+        let b = sum.checked_add(d.checked_div(ann)?)?;
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let y_prev = y;
+            let numerator = y.checked_mul(y)?.checked_add(c)?;
+            let denominator = y
+                .checked_mul(U256::from(2))?
+                .checked_add(b)?
+                .checked_sub(d)?;
+            y = numerator.checked_div(denominator)?;
+            if y.abs_diff(y_prev) <= U256::one() {
+                return Some(y);
+            }
+        }
+        None
+    }
+}
+
+impl PoolAdapter for CurveStablePool {
+    fn address(&self) -> H160 {
+        self.address
+    }
+
+    fn swap_target(&self) -> H160 {
+        self.address
+    }
+
+    fn get_amount_out(&self, token_in: H160, token_out: H160, amount_in: U256) -> Option<U256> {
+        let (i, j) = (self.index_of(token_in)?, self.index_of(token_out)?);
+        if i == j {
+            return None;
+        }
+        let xp = self.xp()?;
+        let x = xp
+            .get(i)?
+            .checked_add(amount_in.checked_mul(self.rate(i))?)?;
+        let y = self.get_y(i, j, x, &xp)?;
+        let dy = xp.get(j)?.checked_sub(y)?.checked_sub(U256::one())?;
+        let fee = self.fee.checked_mul(dy)? / U256::from(FEE_DENOMINATOR);
+        Some(dy.checked_sub(fee)? / self.rate(j))
+    }
+
+    fn encode_swap(
+        &self,
+        token_in: H160,
+        token_out: H160,
+        amount_in: U256,
+        min_amount_out: U256,
+        _recipient: H160,
+    ) -> Option<Bytes> {
+        // Curve pays out to msg.sender, so the recipient is implicitly the arb contract.
+        let (i, j) = (self.index_of(token_in)?, self.index_of(token_out)?);
+        let args = encode(&[
+            Token::Int(U256::from(i)),
+            Token::Int(U256::from(j)),
+            Token::Uint(amount_in),
+            Token::Uint(min_amount_out),
+        ]);
+        let selector = id("exchange(int128,int128,uint256,uint256)");
+        Some([selector.to_vec(), args].concat().into())
+    }
+
+    fn balance_calls(&self) -> Vec<(H160, Bytes)> {
+        let selector = id("balances(uint256)");
+        (0..self.coins.len())
+            .map(|i| {
+                let args = encode(&[Token::Uint(U256::from(i))]);
+                (self.address, [selector.to_vec(), args].concat().into())
+            })
+            .collect()
+    }
+
+    fn update_balances(&self, outputs: &[Bytes]) -> Option<()> {
+        if outputs.len() != self.coins.len() {
+            return None;
+        }
+        let balances = outputs
+            .iter()
+            .map(|output| {
+                decode(&[ParamType::Uint(256)], output)
+                    .ok()?
+                    .pop()?
+                    .into_uint()
+            })
+            .collect::<Option<Vec<U256>>>()?;
+        self.balances.set(balances);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_with_balances(balances: [U256; 3]) -> CurveStablePool {
+        CurveStablePool {
+            balances: balances.to_vec().into(),
+            ..pool()
+        }
+    }
+
+    fn pool() -> CurveStablePool {
+        // A 3pool-like pool: DAI (18), USDC (6), USDT (6).
+        CurveStablePool {
+            address: H160::random(),
+            coins: vec![
+                H160::from_low_u64_be(1),
+                H160::from_low_u64_be(2),
+                H160::from_low_u64_be(3),
+            ],
+            balances: vec![
+                U256::exp10(18) * 1_000_000,
+                U256::exp10(6) * 1_000_000,
+                U256::exp10(6) * 1_000_000,
+            ]
+            .into(),
+            decimals: vec![18, 6, 6],
+            amp: U256::from(2000),
+            fee: U256::from(1_000_000),
+        }
+    }
+
+    #[test]
+    fn balanced_swap_is_close_to_one_to_one() {
+        let pool = pool();
+        let amount_in = U256::exp10(18) * 1000;
+        let out = pool
+            .get_amount_out(pool.coins[0], pool.coins[1], amount_in)
+            .unwrap();
+        // 1000 DAI should buy slightly less than 1000 USDC after the 0.01% fee.
+        assert!(out < U256::exp10(6) * 1000);
+        assert!(out > U256::exp10(6) * 999);
+    }
+
+    #[test]
+    fn rejects_degenerate_pools_instead_of_overflowing() {
+        let pool = CurveStablePool {
+            amp: U256::zero(),
+            ..pool()
+        };
+        let amount_in = U256::exp10(18) * 1000;
+        assert!(pool
+            .get_amount_out(pool.coins[0], pool.coins[1], amount_in)
+            .is_none());
+
+        let pool = pool_with_balances([U256::MAX, U256::one(), U256::one()]);
+        assert!(pool
+            .get_amount_out(pool.coins[0], pool.coins[1], amount_in)
+            .is_none());
+    }
+
+    #[test]
+    fn quotes_updated_balances() {
+        let pool = pool();
+        let (dai, usdc) = (pool.coins[0], pool.coins[1]);
+        let amount_in = U256::exp10(18) * 1000;
+        let before = pool.get_amount_out(dai, usdc, amount_in).unwrap();
+
+        // Most DAI was bought out of the pool, so DAI now buys more USDC.
+        let balances = [
+            U256::exp10(18) * 100_000,
+            U256::exp10(6) * 1_900_000,
+            U256::exp10(6) * 1_000_000,
+        ];
+        let outputs: Vec<Bytes> = balances
+            .iter()
+            .map(|balance| encode(&[Token::Uint(*balance)]).into())
+            .collect();
+        assert_eq!(pool.balance_calls().len(), 3);
+        assert!(pool.update_balances(&outputs[..2]).is_none());
+        assert!(pool.update_balances(&outputs).is_some());
+        assert_eq!(pool.balances.get(), balances);
+        assert!(pool.get_amount_out(dai, usdc, amount_in).unwrap() > before);
+    }
+
+    #[test]
+    fn rejects_unknown_tokens() {
+        let pool = pool();
+        assert!(pool
+            .get_amount_out(pool.coins[0], H160::random(), U256::one())
+            .is_none());
+    }
+
+    #[test]
+    fn encodes_exchange_selector() {
+        let pool = pool();
+        let calldata = pool
+            .encode_swap(
+                pool.coins[0],
+                pool.coins[1],
+                U256::one(),
+                U256::zero(),
+                H160::zero(),
+            )
+            .unwrap();
+        assert_eq!(&calldata[..4], &[0x3d, 0xf0, 0x21, 0x24]);
+    }
+}
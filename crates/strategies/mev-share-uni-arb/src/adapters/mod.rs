@@ -0,0 +1,103 @@
+//! Adapters for non-uniswap venues. Each adapter quotes swaps against its own
+//! view of pool state, and encodes the calldata the arb contract forwards to the
+//! venue, so hints touching these pools can be arbed against uniswap v2 / v3.
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+use ethers::types::{Bytes, H160, U256};
+
+use crate::strategy::V2PoolInfo;
+
+/// Adapter for Balancer weighted pools.
+pub mod balancer;
+
+/// Adapter for Curve stable pools.
+pub mod curve;
+
+/// Common interface for quoting and encoding swaps on a venue.
+pub trait PoolAdapter: Debug + Send + Sync {
+    /// Address of the pool, as it appears in MEV-Share hints.
+    fn address(&self) -> H160;
+
+    /// Address which the swap calldata should be sent to.
+    fn swap_target(&self) -> H160;
+
+    /// Quote the amount of `token_out` received for `amount_in` of `token_in`. Returns
+    /// `None` if the pool doesn't trade the pair, or can't fill the amount.
+    fn get_amount_out(&self, token_in: H160, token_out: H160, amount_in: U256) -> Option<U256>;
+
+    /// Encode calldata swapping `amount_in` of `token_in` for at least `min_amount_out`
+    /// of `token_out`, paying out to `recipient`. Returns `None` if the pool doesn't
+    /// trade the pair.
+    fn encode_swap(
+        &self,
+        token_in: H160,
+        token_out: H160,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: H160,
+    ) -> Option<Bytes>;
+
+    /// Calls reading the balances of the pool, as the target and calldata of each.
+    fn balance_calls(&self) -> Vec<(H160, Bytes)>;
+
+    /// Update the balances of the pool from the outputs of its
+    /// [balance_calls](PoolAdapter::balance_calls), in order. Returns `None`, keeping
+    /// the previous balances, if the outputs can't be decoded.
+    fn update_balances(&self, outputs: &[Bytes]) -> Option<()>;
+}
+
+/// Balances of a venue pool, updated as they are re-read so quotes don't go stale.
+#[derive(Debug, Default)]
+pub struct PoolBalances(RwLock<Vec<U256>>);
+
+impl PoolBalances {
+    /// Returns the latest balances.
+    pub fn get(&self) -> Vec<U256> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Replace the balances with `balances`.
+    pub fn set(&self, balances: Vec<U256>) {
+        *self.0.write().unwrap() = balances;
+    }
+}
+
+impl Clone for PoolBalances {
+    fn clone(&self) -> Self {
+        self.get().into()
+    }
+}
+
+impl From<Vec<U256>> for PoolBalances {
+    fn from(balances: Vec<U256>) -> Self {
+        Self(RwLock::new(balances))
+    }
+}
+
+/// A non-uniswap pool paired with a uniswap v2 pool trading the same token.
+#[derive(Debug, Clone)]
+pub struct VenuePool {
+    /// Adapter for the venue pool.
+    pub adapter: Arc<dyn PoolAdapter>,
+    /// The token traded against WETH in both pools.
+    pub token: H160,
+    /// The v2 pool to close the arb against.
+    pub v2_info: V2PoolInfo,
+}
+
+/// Convert a U256 to an f64, losing precision beyond 53 bits.
+pub(crate) fn u256_to_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or(f64::MAX)
+}
+
+/// Convert a non-negative f64 to a U256, rounding down.
+pub(crate) fn f64_to_u256(value: f64) -> U256 {
+    if !value.is_finite() || value <= 0.0 {
+        return U256::zero();
+    }
+    U256::from_dec_str(&format!("{:.0}", value.floor())).unwrap_or_default()
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use ethers::{
+    contract::{abigen, Multicall},
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, H160, H256, U256},
+};
+use tracing::{info, warn};
+
+use crate::adapters::VenuePool;
+use crate::constants::WETH_ADDRESS;
+
+abigen!(
+    IERC20Allowance,
+    r#"[
+        function allowance(address owner, address spender) external view returns (uint256)
+    ]"#;
+);
+
+abigen!(
+    ArbApprovals,
+    r#"[
+        function approveToken(address token, address spender, uint256 amount) external
+    ]"#;
+);
+
+/// An ERC-20 allowance the arb contract needs, for `spender` to pull `token` from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Approval {
+    pub token: H160,
+    pub spender: H160,
+}
+
+impl Approval {
+    pub fn new(token: H160, spender: H160) -> Self {
+        Self { token, spender }
+    }
+
+    /// Returns the approvals arbing against `venue` needs: the venue pulls the WETH
+    /// swapped in from the arb contract.
+    pub fn for_venue(venue: &VenuePool) -> Vec<Self> {
+        vec![Self::new(*WETH_ADDRESS, venue.adapter.swap_target())]
+    }
+}
+
+/// Finds the ERC-20 approvals the arb contract is missing for the tokens and venues
+/// discovered at runtime, and submits them once an operator confirms them.
+///
+/// Allowances are read in a single Multicall per [check](ApprovalManager::check).
+/// Missing approvals are queued as pending rather than granted, since approving a
+/// malicious spender would let it drain the contract: only approvals passed to
+/// [submit](ApprovalManager::submit) are sent, as calls to the owner-only
+/// `approveToken(token, spender, amount)` function of the arb contract.
+#[derive(Debug)]
+pub struct ApprovalManager<M> {
+    client: Arc<M>,
+    arb_contract: Address,
+    /// Address of the Multicall3 contract, if not the canonical one.
+    multicall_address: Option<Address>,
+    /// Allowances below this are considered missing.
+    min_allowance: U256,
+    /// Approvals the contract was found to have, which aren't checked again.
+    granted: Mutex<HashSet<Approval>>,
+    /// Missing approvals awaiting confirmation.
+    pending: Mutex<BTreeSet<Approval>>,
+}
+
+impl<M: Middleware + 'static> ApprovalManager<M> {
+    /// Manage the approvals of `arb_contract`. `client` must be able to sign for its
+    /// owner to submit approvals.
+    pub fn new(client: Arc<M>, arb_contract: Address) -> Self {
+        Self {
+            client,
+            arb_contract,
+            multicall_address: None,
+            min_allowance: U256::MAX >> 1,
+            granted: Mutex::default(),
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Read allowances through the Multicall3 contract at `address`, instead of the
+    /// canonical deployment.
+    pub fn with_multicall(mut self, address: Address) -> Self {
+        self.multicall_address = Some(address);
+        self
+    }
+
+    /// Consider allowances below `min_allowance` missing. Defaults to half of the
+    /// maximum, since approvals are granted in full and only spent down slowly.
+    pub fn with_min_allowance(mut self, min_allowance: U256) -> Self {
+        self.min_allowance = min_allowance;
+        self
+    }
+
+    /// Check which of `approvals` the contract is missing, and queue them as pending.
+    /// Returns the missing approvals. Approvals found granted before aren't checked
+    /// again, and approvals whose allowance can't be read are skipped.
+    pub async fn check(
+        &self,
+        approvals: impl IntoIterator<Item = Approval>,
+    ) -> Result<Vec<Approval>> {
+        let approvals: Vec<Approval> = {
+            let granted = self.granted.lock().unwrap();
+            let mut seen = HashSet::new();
+            approvals
+                .into_iter()
+                .filter(|approval| !granted.contains(approval) && seen.insert(*approval))
+                .collect()
+        };
+        if approvals.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut multicall = Multicall::new(self.client.clone(), self.multicall_address).await?;
+        for approval in &approvals {
+            let token = IERC20Allowance::new(approval.token, self.client.clone());
+            multicall.add_call(token.allowance(self.arb_contract, approval.spender), true);
+        }
+        let allowances = multicall.call_raw().await?;
+
+        let mut missing = vec![];
+        let mut granted = self.granted.lock().unwrap();
+        let mut pending = self.pending.lock().unwrap();
+        for (approval, allowance) in approvals.into_iter().zip(allowances) {
+            let allowance = match allowance.map(|token| token.into_uint()) {
+                Ok(Some(allowance)) => allowance,
+                _ => {
+                    warn!("Error reading the allowance of {:?}, skipping", approval);
+                    continue;
+                }
+            };
+            if allowance >= self.min_allowance {
+                granted.insert(approval);
+                pending.remove(&approval);
+            } else {
+                if pending.insert(approval) {
+                    info!("arb contract is missing approval {:?}", approval);
+                }
+                missing.push(approval);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Returns the missing approvals awaiting confirmation.
+    pub fn pending(&self) -> Vec<Approval> {
+        self.pending.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Build the txs granting the `confirmed` approvals. Approvals which aren't
+    /// pending are refused, so only approvals found missing can be granted.
+    pub fn approval_txs(&self, confirmed: &[Approval]) -> Result<Vec<TypedTransaction>> {
+        let pending = self.pending.lock().unwrap();
+        if let Some(approval) = confirmed.iter().find(|a| !pending.contains(a)) {
+            return Err(anyhow!("approval {:?} isn't pending", approval));
+        }
+        let arb_contract = ArbApprovals::new(self.arb_contract, self.client.clone());
+        Ok(confirmed
+            .iter()
+            .map(|approval| {
+                arb_contract
+                    .approve_token(approval.token, approval.spender, U256::MAX)
+                    .tx
+            })
+            .collect())
+    }
+
+    /// Submit the `confirmed` approvals, which must all be pending, returning the
+    /// hashes of their txs. Approvals are removed from the pending ones once sent,
+    /// and recorded as granted at the next check which finds them.
+    pub async fn submit(&self, confirmed: &[Approval]) -> Result<Vec<H256>> {
+        let mut tx_hashes = vec![];
+        for (approval, tx) in confirmed.iter().zip(self.approval_txs(confirmed)?) {
+            let pending_tx = self
+                .client
+                .send_transaction(tx, None)
+                .await
+                .map_err(|e| anyhow!("Error submitting approval {:?}: {}", approval, e))?;
+            info!(
+                "submitted approval {:?} in {:?}",
+                approval,
+                pending_tx.tx_hash()
+            );
+            tx_hashes.push(pending_tx.tx_hash());
+            self.pending.lock().unwrap().remove(approval);
+        }
+        Ok(tx_hashes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::{encode, AbiDecode, Token},
+        providers::Provider,
+        types::Bytes,
+    };
+
+    /// Encode the result of a Multicall3 `aggregate3` call, `None` for failed calls.
+    fn aggregate3_output(allowances: &[Option<U256>]) -> Bytes {
+        let results = allowances
+            .iter()
+            .map(|allowance| {
+                let return_data = allowance
+                    .map(|allowance| encode(&[Token::Uint(allowance)]))
+                    .unwrap_or_default();
+                Token::Tuple(vec![
+                    Token::Bool(allowance.is_some()),
+                    Token::Bytes(return_data),
+                ])
+            })
+            .collect();
+        encode(&[Token::Array(results)]).into()
+    }
+
+    fn approval(token: u64) -> Approval {
+        Approval::new(H160::from_low_u64_be(token), H160::from_low_u64_be(100))
+    }
+
+    #[tokio::test]
+    async fn queues_missing_approvals_until_confirmed() {
+        let (provider, mock) = Provider::mocked();
+        let manager = ApprovalManager::new(Arc::new(provider), H160::from_low_u64_be(1))
+            .with_multicall(H160::from_low_u64_be(2));
+
+        mock.push::<Bytes, _>(aggregate3_output(&[
+            Some(U256::MAX),
+            Some(U256::zero()),
+            None,
+        ]))
+        .unwrap();
+        let missing = manager
+            .check([approval(10), approval(11), approval(11), approval(12)])
+            .await
+            .unwrap();
+        assert_eq!(missing, vec![approval(11)]);
+        assert_eq!(manager.pending(), vec![approval(11)]);
+
+        // Granted approvals aren't checked again.
+        mock.push::<Bytes, _>(aggregate3_output(&[Some(U256::zero())]))
+            .unwrap();
+        let missing = manager.check([approval(10), approval(13)]).await.unwrap();
+        assert_eq!(missing, vec![approval(13)]);
+        assert_eq!(manager.pending(), vec![approval(11), approval(13)]);
+
+        assert!(manager.approval_txs(&[approval(10)]).is_err());
+        let txs = manager.approval_txs(&[approval(13)]).unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].to_addr(), Some(&H160::from_low_u64_be(1)));
+        let call = ApproveTokenCall::decode(txs[0].data().unwrap()).unwrap();
+        assert_eq!(call.token, approval(13).token);
+        assert_eq!(call.amount, U256::MAX);
+    }
+}
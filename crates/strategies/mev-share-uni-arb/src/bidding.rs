@@ -0,0 +1,226 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use ethers::types::U256;
+
+/// Information about a backrun used to decide how much to bid.
+#[derive(Debug, Clone, Copy)]
+pub struct BidContext {
+    /// Size of the backrun, in units of the loaned token.
+    pub size: U256,
+    /// Expected profit of the backrun in wei, if known.
+    pub expected_profit: Option<U256>,
+}
+
+/// Decides which percentage of the arb profit is paid to the coinbase.
+pub trait BidPolicy: Debug + Send + Sync {
+    /// Returns the percentage (0-100) of profit to pay to the coinbase.
+    fn payment_percentage(&self, ctx: &BidContext) -> u64;
+
+    /// Record whether a previously submitted bundle landed. Policies which don't
+    /// adapt to feedback can ignore this.
+    fn record_inclusion(&self, _included: bool) {}
+}
+
+/// Always bid the same percentage.
+#[derive(Debug, Clone)]
+pub struct FixedBid {
+    pub percentage: u64,
+}
+
+impl BidPolicy for FixedBid {
+    fn payment_percentage(&self, _ctx: &BidContext) -> u64 {
+        self.percentage.min(100)
+    }
+}
+
+/// Bid a base percentage, increasing linearly with the expected profit. Falls back
+/// to the base percentage when the profit is unknown.
+#[derive(Debug, Clone)]
+pub struct LinearBid {
+    /// Percentage bid for zero profit.
+    pub base_percentage: u64,
+    /// Additional percentage bid per ETH of expected profit.
+    pub percentage_per_eth: u64,
+    /// Upper bound on the bid.
+    pub max_percentage: u64,
+}
+
+impl BidPolicy for LinearBid {
+    fn payment_percentage(&self, ctx: &BidContext) -> u64 {
+        let profit_eth = ctx
+            .expected_profit
+            .map(|profit| {
+                (profit / U256::exp10(18))
+                    .min(U256::from(u64::MAX))
+                    .as_u64()
+            })
+            .unwrap_or_default();
+        self.base_percentage
+            .saturating_add(profit_eth.saturating_mul(self.percentage_per_eth))
+            .min(self.max_percentage)
+            .min(100)
+    }
+}
+
+/// Adjust the bid based on recent inclusion feedback: if fewer bundles land than
+/// targeted we bid more, otherwise we bid less.
+#[derive(Debug)]
+pub struct AdaptiveBid {
+    /// Lower bound on the bid.
+    pub min_percentage: u64,
+    /// Upper bound on the bid.
+    pub max_percentage: u64,
+    /// Amount the bid moves after each piece of feedback.
+    pub step: u64,
+    /// Fraction of bundles (0-100) we aim to land.
+    pub target_inclusion_percentage: u64,
+    /// Number of recent results to consider.
+    pub window: usize,
+    state: Mutex<AdaptiveBidState>,
+}
+
+#[derive(Debug)]
+struct AdaptiveBidState {
+    current: u64,
+    recent: VecDeque<bool>,
+}
+
+impl AdaptiveBid {
+    pub fn new(
+        initial_percentage: u64,
+        min_percentage: u64,
+        max_percentage: u64,
+        step: u64,
+        target_inclusion_percentage: u64,
+        window: usize,
+    ) -> Self {
+        Self {
+            min_percentage,
+            max_percentage,
+            step,
+            target_inclusion_percentage,
+            window,
+            state: Mutex::new(AdaptiveBidState {
+                current: initial_percentage.clamp(min_percentage, max_percentage),
+                recent: VecDeque::with_capacity(window),
+            }),
+        }
+    }
+}
+
+impl BidPolicy for AdaptiveBid {
+    fn payment_percentage(&self, _ctx: &BidContext) -> u64 {
+        self.state.lock().unwrap().current.min(100)
+    }
+
+    fn record_inclusion(&self, included: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.recent.len() == self.window {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(included);
+
+        let landed = state.recent.iter().filter(|included| **included).count() as u64;
+        let inclusion_percentage = landed * 100 / state.recent.len() as u64;
+        state.current = if inclusion_percentage < self.target_inclusion_percentage {
+            state.current.saturating_add(self.step)
+        } else {
+            state.current.saturating_sub(self.step)
+        }
+        .clamp(self.min_percentage, self.max_percentage);
+    }
+}
+
+/// Bid more than another policy, e.g. to resubmit an arb whose bundle missed its target
+/// block. The escalated bid is capped, but never lowered below the policy's own.
+#[derive(Debug)]
+pub struct EscalatedBid {
+    /// Policy whose bid is escalated.
+    pub policy: Arc<dyn BidPolicy>,
+    /// Percentage added to the policy's bid.
+    pub escalation: u64,
+    /// Upper bound on the escalated bid.
+    pub max_percentage: u64,
+}
+
+impl BidPolicy for EscalatedBid {
+    fn payment_percentage(&self, ctx: &BidContext) -> u64 {
+        let percentage = self.policy.payment_percentage(ctx);
+        percentage
+            .saturating_add(self.escalation)
+            .min(self.max_percentage)
+            .max(percentage)
+            .min(100)
+    }
+
+    fn record_inclusion(&self, included: bool) {
+        self.policy.record_inclusion(included);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(expected_profit: Option<U256>) -> BidContext {
+        BidContext {
+            size: U256::exp10(18),
+            expected_profit,
+        }
+    }
+
+    #[test]
+    fn linear_bid_scales_with_profit_and_caps() {
+        let policy = LinearBid {
+            base_percentage: 40,
+            percentage_per_eth: 10,
+            max_percentage: 90,
+        };
+        assert_eq!(policy.payment_percentage(&ctx(None)), 40);
+        assert_eq!(
+            policy.payment_percentage(&ctx(Some(U256::exp10(18) * 2))),
+            60
+        );
+        assert_eq!(policy.payment_percentage(&ctx(Some(U256::exp10(20)))), 90);
+    }
+
+    #[test]
+    fn adaptive_bid_moves_with_inclusion_feedback() {
+        let policy = AdaptiveBid::new(40, 20, 80, 5, 50, 4);
+        policy.record_inclusion(false);
+        assert_eq!(policy.payment_percentage(&ctx(None)), 45);
+        policy.record_inclusion(true);
+        policy.record_inclusion(true);
+        assert_eq!(policy.payment_percentage(&ctx(None)), 35);
+    }
+
+    #[test]
+    fn adaptive_bid_stays_within_bounds() {
+        let policy = AdaptiveBid::new(40, 20, 50, 10, 50, 2);
+        for _ in 0..10 {
+            policy.record_inclusion(false);
+        }
+        assert_eq!(policy.payment_percentage(&ctx(None)), 50);
+        for _ in 0..10 {
+            policy.record_inclusion(true);
+        }
+        assert_eq!(policy.payment_percentage(&ctx(None)), 20);
+    }
+
+    #[test]
+    fn escalated_bid_is_capped_above_the_policy() {
+        let escalate = |escalation, max_percentage| EscalatedBid {
+            policy: Arc::new(FixedBid { percentage: 40 }),
+            escalation,
+            max_percentage,
+        };
+        assert_eq!(escalate(10, 90).payment_percentage(&ctx(None)), 50);
+        assert_eq!(escalate(60, 90).payment_percentage(&ctx(None)), 90);
+        // A cap below the policy's bid doesn't lower it.
+        assert_eq!(escalate(10, 30).payment_percentage(&ctx(None)), 40);
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use ethers::types::{H160, U256};
+use serde::Deserialize;
+
+use crate::bidding::{BidPolicy, EscalatedBid, FixedBid};
+use crate::constants::DEFAULT_MAX_RESUBMISSIONS;
+use crate::refund::refund_percent;
+use crate::types::{PrivacyLevel, SubmissionPrivacy};
+
+/// Parameters of the strategy which can be changed while it runs, e.g. read from a
+/// JSON config file by a
+/// [ConfigCollector](artemis_core::collectors::config_collector::ConfigCollector).
+/// Unset fields leave the strategy's behaviour as configured at construction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyConfig {
+    /// Percentage (0-100) of profit paid to the coinbase. Replaces the bid policy with a
+    /// fixed bid when set.
+    pub payment_percentage: Option<u64>,
+    /// Smallest backrun size submitted, in units of the loaned token.
+    pub min_size: Option<u128>,
+    /// Largest backrun size submitted, in units of the loaned token.
+    pub max_size: Option<u128>,
+    /// Pools which may be backrun. When set, only these addresses of a hint are matched
+    /// against the templates.
+    pub enabled_pools: Option<HashSet<H160>>,
+    /// Most arbs submitted per hint, keeping those expected to net the most once the
+    /// coinbase payment and gas are paid. Each arb is submitted as a bundle per target
+    /// block. Every arb is submitted when unset.
+    pub max_arbs_per_hint: Option<usize>,
+    /// Least percent (0-100) of a bundle's earnings refunded to the user whose
+    /// transaction it backruns. Also the refund requested when it can't be estimated
+    /// from the gas the hint shares. No refund is requested then when unset.
+    pub min_refund_percent: Option<u64>,
+    /// Most percent (0-100) of a bundle's earnings refunded to the user. 100 when unset.
+    pub max_refund_percent: Option<u64>,
+    /// Privacy level bundles are submitted at, unless their template has its own.
+    /// Bundles keep the strategy's submission privacy when unset.
+    pub privacy_level: Option<PrivacyLevel>,
+    /// Hints and builders of each privacy level, e.g.
+    /// `{"semi-private": {"hints": ["hash"], "builders": ["flashbots", "titan"]}}`.
+    pub privacy_levels: HashMap<PrivacyLevel, SubmissionPrivacy>,
+    /// Privacy level of the bundles of each template, by template name, overriding
+    /// `privacy_level`.
+    pub template_privacy_levels: HashMap<String, PrivacyLevel>,
+    /// Percentage added to the coinbase payment each time the bundles of a hint miss
+    /// their target block, and its arbs are resubmitted for the next block. Missed
+    /// bundles aren't resubmitted when unset.
+    pub escalation_step: Option<u64>,
+    /// Percentage (0-100) escalated coinbase payments are capped at. 100 when unset.
+    pub max_escalated_payment_percentage: Option<u64>,
+    /// Most times the arbs of a hint are resubmitted after missing their target block.
+    /// Defaults to [DEFAULT_MAX_RESUBMISSIONS].
+    pub max_resubmissions: Option<u32>,
+    /// Blocks during which a pool is claimed by the hint whose bundles were built
+    /// through it, skipping other hints through the pool. 1 allows one opportunity per
+    /// pool per block. Pools aren't throttled when unset.
+    pub pool_cooldown_blocks: Option<u64>,
+}
+
+impl StrategyConfig {
+    /// Returns an error if the parameters are inconsistent.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(percentage) = self.payment_percentage {
+            if percentage > 100 {
+                return Err(anyhow!("payment percentage {} is above 100", percentage));
+            }
+        }
+        if let Some(percentage) = self.max_escalated_payment_percentage {
+            if percentage > 100 {
+                return Err(anyhow!(
+                    "max escalated payment percentage {} is above 100",
+                    percentage
+                ));
+            }
+        }
+        for percentage in self
+            .min_refund_percent
+            .iter()
+            .chain(&self.max_refund_percent)
+        {
+            if *percentage > 100 {
+                return Err(anyhow!("refund percent {} is above 100", percentage));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_refund_percent, self.max_refund_percent) {
+            if min > max {
+                return Err(anyhow!(
+                    "min refund percent {} is above max refund percent {}",
+                    min,
+                    max
+                ));
+            }
+        }
+        if self.max_arbs_per_hint == Some(0) {
+            return Err(anyhow!(
+                "max arbs per hint is 0, so no arb would be submitted"
+            ));
+        }
+        if self.pool_cooldown_blocks == Some(0) {
+            return Err(anyhow!(
+                "pool cooldown is 0 blocks, so no pool would be throttled"
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size) {
+            if min > max {
+                return Err(anyhow!("min size {} is above max size {}", min, max));
+            }
+        }
+        let selected = self
+            .privacy_level
+            .iter()
+            .chain(self.template_privacy_levels.values());
+        for level in selected {
+            if !self.privacy_levels.contains_key(level) {
+                return Err(anyhow!(
+                    "privacy level {:?} has no hints or builders",
+                    level
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the bid policy to size coinbase payments with, given the strategy's own.
+    pub(crate) fn bid_policy(&self, bid_policy: &Arc<dyn BidPolicy>) -> Arc<dyn BidPolicy> {
+        match self.payment_percentage {
+            Some(percentage) => Arc::new(FixedBid { percentage }),
+            None => bid_policy.clone(),
+        }
+    }
+
+    /// Returns the bid policy to resubmit arbs with once their bundles missed `misses`
+    /// target blocks in a row, escalating `bid_policy` by a step per miss.
+    pub(crate) fn escalated_bid_policy(
+        &self,
+        bid_policy: Arc<dyn BidPolicy>,
+        misses: u32,
+    ) -> Arc<dyn BidPolicy> {
+        match self.escalation_step {
+            Some(step) if misses > 0 => Arc::new(EscalatedBid {
+                policy: bid_policy,
+                escalation: step.saturating_mul(misses.into()),
+                max_percentage: self.max_escalated_payment_percentage.unwrap_or(100),
+            }),
+            _ => bid_policy,
+        }
+    }
+
+    /// Whether the arbs of a hint are resubmitted once their bundles missed `misses`
+    /// target blocks in a row.
+    pub(crate) fn resubmits(&self, misses: u32) -> bool {
+        self.escalation_step.is_some()
+            && misses <= self.max_resubmissions.unwrap_or(DEFAULT_MAX_RESUBMISSIONS)
+    }
+
+    /// Returns the percent of a bundle's earnings to refund to the user, given the
+    /// `mev_fee` their hint pays and the `payment` the backrun makes to the coinbase, if
+    /// known. `None` if no refund should be requested.
+    pub(crate) fn refund_percent(
+        &self,
+        mev_fee: Option<U256>,
+        payment: Option<U256>,
+    ) -> Option<u64> {
+        let percent = match mev_fee.zip(payment) {
+            Some((mev_fee, payment)) => refund_percent(
+                mev_fee,
+                payment,
+                self.min_refund_percent.unwrap_or_default(),
+                self.max_refund_percent.unwrap_or(100),
+            ),
+            None => self.min_refund_percent?,
+        };
+        (percent > 0).then_some(percent)
+    }
+
+    /// Whether a backrun of `size` is within the size bounds.
+    pub(crate) fn allows_size(&self, size: U256) -> bool {
+        self.min_size.iter().all(|min| size >= U256::from(*min))
+            && self.max_size.iter().all(|max| size <= U256::from(*max))
+    }
+
+    /// Returns the submission privacy of the bundles of `template`, given the strategy's
+    /// own.
+    pub(crate) fn privacy<'a>(
+        &'a self,
+        template: &str,
+        privacy: &'a SubmissionPrivacy,
+    ) -> &'a SubmissionPrivacy {
+        self.template_privacy_levels
+            .get(template)
+            .or(self.privacy_level.as_ref())
+            .and_then(|level| self.privacy_levels.get(level))
+            .unwrap_or(privacy)
+    }
+
+    /// Whether `address` may be matched against the templates.
+    pub(crate) fn is_enabled(&self, address: &H160) -> bool {
+        self.enabled_pools
+            .iter()
+            .all(|enabled| enabled.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bidding::BidContext;
+    use matchmaker::types::{BuilderId, PrivacyHint};
+
+    #[test]
+    fn parses_and_applies_config() {
+        let config: StrategyConfig = serde_json::from_str(
+            r#"{
+                "payment_percentage": 60,
+                "min_size": 100000,
+                "max_size": 1000000000000000000000,
+                "enabled_pools": ["0x0000000000000000000000000000000000000001"]
+            }"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let default: Arc<dyn BidPolicy> = Arc::new(FixedBid { percentage: 40 });
+        let ctx = BidContext {
+            size: U256::one(),
+            expected_profit: None,
+        };
+        assert_eq!(config.bid_policy(&default).payment_percentage(&ctx), 60);
+        assert!(!config.allows_size(U256::from(99_999)));
+        assert!(config.allows_size(U256::exp10(21)));
+        assert!(!config.allows_size(U256::exp10(21) + 1));
+        assert!(config.is_enabled(&H160::from_low_u64_be(1)));
+        assert!(!config.is_enabled(&H160::from_low_u64_be(2)));
+
+        let unset = StrategyConfig::default();
+        assert_eq!(unset.bid_policy(&default).payment_percentage(&ctx), 40);
+        assert!(unset.allows_size(U256::MAX));
+        assert!(unset.is_enabled(&H160::from_low_u64_be(2)));
+    }
+
+    #[test]
+    fn rejects_inconsistent_config() {
+        let config = StrategyConfig {
+            payment_percentage: Some(101),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = StrategyConfig {
+            min_size: Some(2),
+            max_size: Some(1),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = StrategyConfig {
+            max_arbs_per_hint: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = StrategyConfig {
+            min_refund_percent: Some(50),
+            max_refund_percent: Some(40),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = StrategyConfig {
+            pool_cooldown_blocks: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = StrategyConfig {
+            max_escalated_payment_percentage: Some(101),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(serde_json::from_str::<StrategyConfig>(r#"{"max_sise": 1}"#).is_err());
+
+        let config: StrategyConfig = serde_json::from_str(
+            r#"{"privacy_level": "public", "privacy_levels": {"private": {}}}"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn bounds_refunds() {
+        let config = StrategyConfig {
+            min_refund_percent: Some(10),
+            max_refund_percent: Some(50),
+            ..Default::default()
+        };
+        let (fee, payment) = (Some(U256::from(300)), Some(U256::from(100)));
+        assert_eq!(config.refund_percent(fee, payment), Some(50));
+        assert_eq!(config.refund_percent(None, payment), Some(10));
+        assert_eq!(config.refund_percent(fee, None), Some(10));
+
+        let unset = StrategyConfig::default();
+        assert_eq!(unset.refund_percent(fee, payment), Some(75));
+        assert_eq!(unset.refund_percent(Some(U256::zero()), payment), None);
+        assert_eq!(unset.refund_percent(None, payment), None);
+    }
+
+    #[test]
+    fn selects_privacy_per_template() {
+        let config: StrategyConfig = serde_json::from_str(
+            r#"{
+                "privacy_level": "semi-private",
+                "privacy_levels": {
+                    "semi-private": {"hints": ["hash"], "builders": ["flashbots", "titan"]},
+                    "private": {"builders": ["flashbots"]}
+                },
+                "template_privacy_levels": {"triangular": "private"}
+            }"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let default = SubmissionPrivacy::default();
+        let semi_private = config.privacy("v2-v3-arb", &default);
+        assert_eq!(
+            semi_private.hints,
+            Some(PrivacyHint {
+                hash: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(semi_private.builders.as_ref().map(Vec::len), Some(2));
+        let private = config.privacy("triangular", &default);
+        assert_eq!(private.hints, None);
+        assert_eq!(
+            private.builders,
+            Some(vec![BuilderId::Name("flashbots".to_string())])
+        );
+
+        let unset = StrategyConfig::default();
+        assert_eq!(unset.privacy("triangular", &default), &default);
+    }
+
+    #[test]
+    fn escalates_bids_of_resubmissions() {
+        let config = StrategyConfig {
+            escalation_step: Some(15),
+            max_escalated_payment_percentage: Some(80),
+            max_resubmissions: Some(2),
+            ..Default::default()
+        };
+        config.validate().unwrap();
+
+        let policy: Arc<dyn BidPolicy> = Arc::new(FixedBid { percentage: 40 });
+        let ctx = BidContext {
+            size: U256::one(),
+            expected_profit: None,
+        };
+        let bid = |misses| {
+            config
+                .escalated_bid_policy(policy.clone(), misses)
+                .payment_percentage(&ctx)
+        };
+        assert_eq!(bid(0), 40);
+        assert_eq!(bid(1), 55);
+        assert_eq!(bid(3), 80);
+        assert!(config.resubmits(2));
+        assert!(!config.resubmits(3));
+
+        let unset = StrategyConfig::default();
+        assert_eq!(
+            unset
+                .escalated_bid_policy(policy.clone(), 1)
+                .payment_percentage(&ctx),
+            40
+        );
+        assert!(!unset.resubmits(1));
+    }
+}
@@ -0,0 +1,73 @@
+use std::{collections::HashMap, time::Duration};
+
+use ethers::{prelude::Lazy, types::Address};
+
+/// Address of the WETH contract.
+pub static WETH_ADDRESS: Lazy<Address> = Lazy::new(|| {
+    "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+        .parse()
+        .unwrap()
+});
+
+/// Placeholder address of native ETH, used by Curve pools trading ETH instead of WETH.
+pub static NATIVE_ETH_ADDRESS: Lazy<Address> = Lazy::new(|| {
+    "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE"
+        .parse()
+        .unwrap()
+});
+
+/// Routers of the uniswap v2 / v3 family, by address. Hints calling them without
+/// sharing logs don't reveal the pools they swap through.
+pub static KNOWN_ROUTERS: Lazy<HashMap<Address, &'static str>> = Lazy::new(|| {
+    [
+        (
+            "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D",
+            "uniswap-v2-router",
+        ),
+        (
+            "0xE592427A0AEce92De3Edee1F18E0157C05861564",
+            "uniswap-v3-router",
+        ),
+        (
+            "0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45",
+            "uniswap-v3-router-02",
+        ),
+        (
+            "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD",
+            "universal-router",
+        ),
+        (
+            "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F",
+            "sushiswap-router",
+        ),
+    ]
+    .into_iter()
+    .map(|(address, name)| (address.parse().unwrap(), name))
+    .collect()
+});
+
+/// Maximum number of routes the triangular and multi-hop templates each backrun for a
+/// single hint, so a single hint can't flood the matchmaker.
+pub const MAX_ROUTES_PER_EVENT: usize = 3;
+
+/// Default number of MEV-share hints processed concurrently.
+pub const DEFAULT_MAX_CONCURRENT_HINTS: usize = 8;
+
+/// Default time allowed to generate bundles for a hint, after which it is abandoned.
+pub const DEFAULT_HINT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Age of the latest block past which the shared chain state is assumed stale, e.g.
+/// because the block subscription stalled, and blocks and gas prices are read over RPC.
+/// Two slots, so a missed slot isn't mistaken for a stall.
+pub const MAX_CHAIN_HEAD_AGE: Duration = Duration::from_secs(24);
+
+/// How long the last gas price and latest block read stand in for reads which fail.
+pub const LAST_KNOWN_MAX_AGE: Duration = Duration::from_secs(12);
+
+/// Gas limit of arb txs whose gas couldn't be
+/// [estimated](crate::gas::GasEstimator).
+pub const ARB_TX_GAS_LIMIT: u64 = 400_000;
+
+/// Default number of times the arbs of a hint are resubmitted, with an escalated bid,
+/// after their bundles miss their target block.
+pub const DEFAULT_MAX_RESUBMISSIONS: u32 = 3;
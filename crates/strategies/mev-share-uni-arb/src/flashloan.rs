@@ -0,0 +1,207 @@
+use ethers::{
+    abi::{encode, Token},
+    types::{Bytes, H160, U256},
+    utils::id,
+};
+
+use crate::adapters::balancer::BALANCER_VAULT_ADDRESS;
+
+/// Aave V3 charges a 0.05% premium on simple flash loans.
+const AAVE_V3_FLASHLOAN_FEE_BPS: u64 = 5;
+
+/// A source of flash loaned funds for the arb.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FlashloanProvider {
+    /// Balancer vault flash loans, initiated through the arb contract's `makeFlashLoan`.
+    Balancer,
+    /// Aave V3 `flashLoanSimple` for a single reserve.
+    AaveV3 {
+        /// Address of the Aave V3 pool.
+        pool: H160,
+        /// The reserve asset which can be borrowed.
+        asset: H160,
+        /// The aToken holding the reserve's liquidity.
+        a_token: H160,
+    },
+    /// Uniswap V3 pool `flash`, initiated through the arb contract since the pool
+    /// calls back `msg.sender`.
+    UniswapV3 {
+        /// Address of the v3 pool.
+        pool: H160,
+        /// Token0 of the pool.
+        token0: H160,
+        /// Token1 of the pool.
+        token1: H160,
+        /// Pool fee, in hundredths of a basis point.
+        fee: u32,
+    },
+}
+
+impl FlashloanProvider {
+    /// Returns true if the provider can lend `token`.
+    pub fn can_lend(&self, token: H160) -> bool {
+        match self {
+            FlashloanProvider::Balancer => true,
+            FlashloanProvider::AaveV3 { asset, .. } => *asset == token,
+            FlashloanProvider::UniswapV3 { token0, token1, .. } => {
+                *token0 == token || *token1 == token
+            }
+        }
+    }
+
+    /// Address whose `token` balance is the liquidity available to borrow.
+    pub fn liquidity_holder(&self) -> H160 {
+        match self {
+            FlashloanProvider::Balancer => *BALANCER_VAULT_ADDRESS,
+            FlashloanProvider::AaveV3 { a_token, .. } => *a_token,
+            FlashloanProvider::UniswapV3 { pool, .. } => *pool,
+        }
+    }
+
+    /// Fee charged on the borrowed amount, in basis points.
+    pub fn fee_bps(&self) -> u64 {
+        match self {
+            FlashloanProvider::Balancer => 0,
+            FlashloanProvider::AaveV3 { .. } => AAVE_V3_FLASHLOAN_FEE_BPS,
+            FlashloanProvider::UniswapV3 { fee, .. } => *fee as u64 / 100,
+        }
+    }
+
+    /// Fee charged for borrowing `amount`, rounded up.
+    pub fn fee_for(&self, amount: U256) -> U256 {
+        let fee = amount * self.fee_bps();
+        (fee + 9_999) / 10_000
+    }
+
+    /// Encode the tx target and calldata borrowing `amount` of `token`, with
+    /// `user_data` forwarded to the arb contract's callback. The Balancer provider
+    /// is built from the contract bindings instead, so it returns `None`.
+    pub fn encode(
+        &self,
+        arb_contract: H160,
+        token: H160,
+        amount: U256,
+        user_data: Bytes,
+    ) -> Option<(H160, Bytes)> {
+        match self {
+            FlashloanProvider::Balancer => None,
+            FlashloanProvider::AaveV3 { pool, .. } => {
+                let args = encode(&[
+                    Token::Address(arb_contract),
+                    Token::Address(token),
+                    Token::Uint(amount),
+                    Token::Bytes(user_data.to_vec()),
+                    Token::Uint(U256::zero()),
+                ]);
+                let selector = id("flashLoanSimple(address,address,uint256,bytes,uint16)");
+                Some((*pool, [selector.to_vec(), args].concat().into()))
+            }
+            FlashloanProvider::UniswapV3 { pool, token0, .. } => {
+                let (amount0, amount1) = if *token0 == token {
+                    (amount, U256::zero())
+                } else {
+                    (U256::zero(), amount)
+                };
+                let args = encode(&[
+                    Token::Address(*pool),
+                    Token::Uint(amount0),
+                    Token::Uint(amount1),
+                    Token::Bytes(user_data.to_vec()),
+                ]);
+                let selector = id("makeUniswapV3Flash(address,uint256,uint256,bytes)");
+                Some((arb_contract, [selector.to_vec(), args].concat().into()))
+            }
+        }
+    }
+}
+
+/// Select the cheapest provider which can lend `amount` of `token`, given the
+/// liquidity available at each provider. Ties go to the earlier provider.
+pub fn select_provider(
+    candidates: &[(FlashloanProvider, U256)],
+    token: H160,
+    amount: U256,
+) -> Option<&FlashloanProvider> {
+    candidates
+        .iter()
+        .filter(|(provider, liquidity)| provider.can_lend(token) && *liquidity >= amount)
+        .min_by_key(|(provider, _)| provider.fee_for(amount))
+        .map(|(provider, _)| provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aave(asset: H160) -> FlashloanProvider {
+        FlashloanProvider::AaveV3 {
+            pool: H160::random(),
+            asset,
+            a_token: H160::random(),
+        }
+    }
+
+    #[test]
+    fn selects_cheapest_provider_with_liquidity() {
+        let token = H160::random();
+        let amount = U256::exp10(18);
+        let candidates = vec![
+            (aave(token), U256::exp10(20)),
+            (FlashloanProvider::Balancer, U256::exp10(20)),
+        ];
+        assert_eq!(
+            select_provider(&candidates, token, amount),
+            Some(&FlashloanProvider::Balancer)
+        );
+    }
+
+    #[test]
+    fn skips_providers_without_liquidity_or_asset() {
+        let token = H160::random();
+        let amount = U256::exp10(18);
+        let candidates = vec![
+            (FlashloanProvider::Balancer, U256::exp10(17)),
+            (aave(H160::random()), U256::exp10(20)),
+            (aave(token), U256::exp10(20)),
+        ];
+        assert_eq!(
+            select_provider(&candidates, token, amount),
+            Some(&candidates[2].0)
+        );
+        assert_eq!(select_provider(&candidates, token, U256::exp10(21)), None);
+    }
+
+    #[test]
+    fn uniswap_v3_fee_rounds_up() {
+        let provider = FlashloanProvider::UniswapV3 {
+            pool: H160::random(),
+            token0: H160::random(),
+            token1: H160::random(),
+            fee: 500,
+        };
+        assert_eq!(provider.fee_bps(), 5);
+        assert_eq!(provider.fee_for(U256::from(1)), U256::from(1));
+        assert_eq!(provider.fee_for(U256::from(20_000)), U256::from(10));
+    }
+
+    #[test]
+    fn encodes_provider_specific_calldata() {
+        let (arb, token, pool) = (H160::random(), H160::random(), H160::random());
+        let provider = FlashloanProvider::AaveV3 {
+            pool,
+            asset: token,
+            a_token: H160::random(),
+        };
+        let (to, calldata) = provider
+            .encode(arb, token, U256::one(), Bytes::default())
+            .unwrap();
+        assert_eq!(to, pool);
+        assert_eq!(
+            &calldata[..4],
+            &id("flashLoanSimple(address,address,uint256,bytes,uint16)")
+        );
+        assert!(FlashloanProvider::Balancer
+            .encode(arb, token, U256::one(), Bytes::default())
+            .is_none());
+    }
+}
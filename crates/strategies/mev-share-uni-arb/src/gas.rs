@@ -0,0 +1,187 @@
+//! Estimation of the gas limits of arb txs. Routes through pools with callbacks, e.g.
+//! Balancer or Curve venues, need more gas than a fixed limit allows, while simple v2 /
+//! v3 arbs need much less. The gas of each route is estimated with `eth_estimateGas`
+//! the first time it's arbed, and again once its estimate is stale, so bundles are
+//! still built without RPC calls for most hints.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ethers::{
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, BlockNumber, Bytes, H160, U256, U64},
+};
+use futures::future::join_all;
+use tracing::info;
+
+use crate::constants::ARB_TX_GAS_LIMIT;
+use crate::flashloan::FlashloanProvider;
+
+/// Basis points in a whole.
+const BPS: u64 = 10_000;
+
+/// Default time after which the gas of a route is estimated again.
+pub const DEFAULT_GAS_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Default margin added to gas estimates, in basis points.
+pub const DEFAULT_GAS_SAFETY_MARGIN_BPS: u32 = 2_000;
+
+/// A route arbs are sent through: the flash loan provider, the loaned token and the
+/// user data encoding the pools swapped through. Arbs of a route only differ by size.
+pub type Route = (FlashloanProvider, H160, Bytes);
+
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    /// Estimated gas, or `None` if estimation failed.
+    gas: Option<U256>,
+    estimated_at: Instant,
+}
+
+/// Estimates, and caches, the gas limit of arb txs per route.
+#[derive(Debug)]
+pub struct GasEstimator<M> {
+    client: Arc<M>,
+    refresh_interval: Duration,
+    safety_margin_bps: u32,
+    /// Gas limit of routes whose gas couldn't be estimated.
+    fallback: U256,
+    estimates: Mutex<HashMap<Route, Estimate>>,
+}
+
+impl<M: Middleware + 'static> GasEstimator<M> {
+    /// Create an estimator refreshing estimates every 10 minutes, with a 20% margin.
+    /// Routes whose gas can't be estimated fall back to [ARB_TX_GAS_LIMIT].
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            refresh_interval: DEFAULT_GAS_REFRESH_INTERVAL,
+            safety_margin_bps: DEFAULT_GAS_SAFETY_MARGIN_BPS,
+            fallback: U256::from(ARB_TX_GAS_LIMIT),
+            estimates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Estimate the gas of a route again once its estimate is older than `interval`.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Add `bps` basis points to gas estimates, since the state the arb runs against
+    /// differs from the state it was estimated against.
+    pub fn with_safety_margin_bps(mut self, bps: u32) -> Self {
+        self.safety_margin_bps = bps;
+        self
+    }
+
+    /// Set the gas limit of routes whose gas can't be estimated.
+    pub fn with_fallback_gas_limit(mut self, gas: U256) -> Self {
+        self.fallback = gas;
+        self
+    }
+
+    /// Whether the gas of `route` was never estimated, or was estimated over the
+    /// refresh interval ago.
+    pub fn is_stale(&self, route: &Route) -> bool {
+        match self.estimates.lock().unwrap().get(route) {
+            Some(estimate) => estimate.estimated_at.elapsed() >= self.refresh_interval,
+            None => true,
+        }
+    }
+
+    /// Estimate the gas of `txs` as of `block`, concurrently, caching the estimate of
+    /// each route. Only the first tx of a route is estimated. Routes whose tx fails to
+    /// estimate, e.g. because the arb isn't profitable at `block`, use the fallback
+    /// limit until they're refreshed.
+    pub async fn refresh(&self, txs: Vec<(Route, TypedTransaction)>, block: U64) {
+        let mut routes = HashMap::new();
+        for (route, tx) in txs {
+            routes.entry(route).or_insert(tx);
+        }
+        let estimates = join_all(routes.into_iter().map(|(route, tx)| async move {
+            let gas = self
+                .client
+                .estimate_gas(&tx, Some(BlockNumber::Number(block).into()))
+                .await;
+            (route, gas)
+        }))
+        .await;
+
+        let estimated_at = Instant::now();
+        let mut cache = self.estimates.lock().unwrap();
+        for (route, gas) in estimates {
+            let gas = match gas {
+                Ok(gas) => Some(gas),
+                Err(e) => {
+                    info!("Error estimating gas of route {:?}: {}", route, e);
+                    None
+                }
+            };
+            cache.insert(route, Estimate { gas, estimated_at });
+        }
+    }
+
+    /// Returns the gas limit of arbs through `route`: its estimate plus the safety
+    /// margin, or the fallback limit if it has no estimate.
+    pub fn gas_limit(&self, route: &Route) -> U256 {
+        let estimate = self.estimates.lock().unwrap().get(route).copied();
+        match estimate.and_then(|estimate| estimate.gas) {
+            Some(gas) => gas + gas * U256::from(self.safety_margin_bps) / U256::from(BPS),
+            None => self.fallback,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::TransactionRequest;
+
+    fn route(user_data: u8) -> Route {
+        (
+            FlashloanProvider::Balancer,
+            H160::from_low_u64_be(1),
+            Bytes::from(vec![user_data]),
+        )
+    }
+
+    fn estimator() -> (GasEstimator<Provider<MockProvider>>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        (GasEstimator::new(Arc::new(provider)), mock)
+    }
+
+    #[tokio::test]
+    async fn adds_safety_margin_to_cached_estimates() {
+        let (estimator, mock) = estimator();
+        mock.push(U256::from(150_000)).unwrap();
+        let tx: TypedTransaction = TransactionRequest::new().into();
+
+        // Sizes of a route share its estimate.
+        estimator
+            .refresh(vec![(route(1), tx.clone()), (route(1), tx)], U64::from(1))
+            .await;
+        assert!(!estimator.is_stale(&route(1)));
+        assert_eq!(estimator.gas_limit(&route(1)), U256::from(180_000));
+
+        assert!(estimator.is_stale(&route(2)));
+        assert_eq!(estimator.gas_limit(&route(2)), U256::from(ARB_TX_GAS_LIMIT));
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_estimation_fails() {
+        let (estimator, _mock) = estimator();
+        let estimator = estimator
+            .with_fallback_gas_limit(U256::from(500_000))
+            .with_refresh_interval(Duration::ZERO);
+        let tx: TypedTransaction = TransactionRequest::new().into();
+
+        // The mock has no response queued, so estimation fails.
+        estimator.refresh(vec![(route(1), tx)], U64::from(1)).await;
+        assert_eq!(estimator.gas_limit(&route(1)), U256::from(500_000));
+        assert!(estimator.is_stale(&route(1)));
+    }
+}
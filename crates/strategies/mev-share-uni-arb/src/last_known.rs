@@ -0,0 +1,83 @@
+//! Values last read from the chain, such as the gas price and the latest block, kept so
+//! a hint can still be backrun with them when reading them again fails, rather than
+//! being skipped over a single RPC hiccup.
+
+use std::{
+    fmt::Display,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+/// The last value read successfully, used in place of a failed read for up to `max_age`
+/// after it was read. Older values are too likely to be wrong to be of use, e.g. a gas
+/// price from several blocks ago.
+#[derive(Debug)]
+pub struct LastKnown<T> {
+    /// What the value is, for logs.
+    name: &'static str,
+    value: RwLock<Option<(T, Instant)>>,
+    max_age: Duration,
+}
+
+impl<T: Clone + std::fmt::Debug> LastKnown<T> {
+    pub fn new(name: &'static str, max_age: Duration) -> Self {
+        Self {
+            name,
+            value: RwLock::new(None),
+            max_age,
+        }
+    }
+
+    /// Record `value`, read just now.
+    pub fn record(&self, value: T) {
+        *self.value.write().unwrap() = Some((value, Instant::now()));
+    }
+
+    /// Returns the last value read, if it was read at most `max_age` ago.
+    pub fn get(&self) -> Option<T> {
+        match &*self.value.read().unwrap() {
+            Some((value, read_at)) if read_at.elapsed() <= self.max_age => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a successful `read`, recording it, or else the last value
+    /// read if it is recent enough. The error of `read` is returned otherwise.
+    pub fn or_last_known<E: Display>(&self, read: Result<T, E>) -> Result<T, E> {
+        match read {
+            Ok(value) => {
+                self.record(value.clone());
+                Ok(value)
+            }
+            Err(e) => match self.get() {
+                Some(value) => {
+                    warn!(
+                        "Error reading the {}, using the last known {:?}: {}",
+                        self.name, value, e
+                    );
+                    Ok(value)
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_recent_values() {
+        let gas_price = LastKnown::new("gas price", Duration::from_millis(50));
+        assert_eq!(gas_price.or_last_known(Err("timeout")), Err("timeout"));
+        assert_eq!(gas_price.or_last_known(Ok::<_, &str>(10)), Ok(10));
+        assert_eq!(gas_price.or_last_known(Err("timeout")), Ok(10));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(gas_price.get(), None);
+        assert_eq!(gas_price.or_last_known(Err("timeout")), Err("timeout"));
+    }
+}
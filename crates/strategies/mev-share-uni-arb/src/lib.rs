@@ -3,8 +3,83 @@
 //! that touch a v3 pool that we have a v2 pool for. We then submit a series of backruns
 //! of varying sizes, hoping that one of them will be profitable.
 
+/// This module contains adapters for Balancer and Curve pools.
+pub mod adapters;
+
+/// This module contains the checking and submission of the arb contract's ERC-20 approvals.
+pub mod approvals;
+
+/// This module contains policies deciding how much profit to bid to the coinbase.
+pub mod bidding;
+
+/// This module contains the parameters of the strategy which can be changed while it runs.
+pub mod config;
+
+/// This module contains constants used by the strategy.
+pub mod constants;
+
+/// This module contains the flash loan providers the arb can borrow from.
+pub mod flashloan;
+
+/// This module contains the estimation of the gas limits of arb txs.
+pub mod gas;
+
+/// This module contains the last gas price and block read, used when reading them fails.
+pub mod last_known;
+
+/// This module contains swap math for uniswap v2 style pools.
+pub mod math;
+
+/// This module contains the stores of the pools and routes the strategy arbs.
+pub mod pool_store;
+
+/// This module contains the fetching of pool state for sizing backruns.
+pub mod pool_state;
+
+/// This module contains the ranking of the arbs of a hint by expected net profit.
+pub mod ranking;
+
+/// This module contains the estimation of the refunds requested for backrun users.
+pub mod refund;
+
+/// This module contains the tracking of missed bundles, whose arbs are resubmitted.
+pub mod resubmission;
+
+/// This module contains the reserves of uniswap v2 pools, synced from their `Sync` logs.
+pub mod reserves;
+
+/// This module contains the search of arb cycles through the graph of the pools loaded.
+pub mod routing;
+
+/// This module contains the screening of tokens for transfer taxes, blacklists and pauses.
+pub mod screening;
+
+/// This module contains the pool of wallets signing arb txs in rotation.
+pub mod signers;
+
+/// This module contains solvers for the profit maximizing size of an arb.
+pub mod solver;
+
 /// This module contains the core strategy implementation.
 pub mod strategy;
 
+/// This module contains the strategy and executor sweeping profits from the arb contract.
+pub mod sweep;
+
+/// This module contains the throttling of the opportunities taken through each pool.
+pub mod throttle;
+
+/// This module contains the backrun templates matched against MEV-Share hints.
+pub mod templates;
+
+/// This module contains the templates arb txs are built from without RPC calls.
+pub mod tx_cache;
+
 /// This module contains the core type definitions for the strategy.
 pub mod types;
+
+/// This module contains the WETH wrapping and unwrapping of backruns swapping native ETH.
+pub mod wrapping;
+
+/// This module contains the tick-level state of uniswap v3 pools, synced from their logs.
+pub mod univ3_state;
@@ -0,0 +1,70 @@
+use ethers::types::U256;
+
+/// Fee denominator, fees are expressed in basis points.
+const BPS: u64 = 10_000;
+
+/// Returns true if `fee_bps` is a valid fee, of at most 100%.
+pub fn is_valid_fee(fee_bps: u32) -> bool {
+    fee_bps as u64 <= BPS
+}
+
+/// Returns the amount of output tokens received for `amount_in` input tokens from a
+/// uniswap v2 style pool charging `fee_bps` on the input amount. Returns zero if the
+/// fee is invalid or the amounts overflow.
+pub fn get_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256, fee_bps: u32) -> U256 {
+    if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() {
+        return U256::zero();
+    }
+    checked_amount_out(amount_in, reserve_in, reserve_out, fee_bps).unwrap_or_default()
+}
+
+fn checked_amount_out(
+    amount_in: U256,
+    reserve_in: U256,
+    reserve_out: U256,
+    fee_bps: u32,
+) -> Option<U256> {
+    let fee_multiplier = BPS.checked_sub(fee_bps as u64)?;
+    let amount_in_with_fee = amount_in.checked_mul(fee_multiplier.into())?;
+    let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+    let denominator = reserve_in
+        .checked_mul(BPS.into())?
+        .checked_add(amount_in_with_fee)?;
+    Some(numerator / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amount_out_matches_uniswap_v2_fee() {
+        // Uniswap v2 router: amountInWithFee = amountIn * 997 ...
+        let amount_in = U256::from(1_000_000u64);
+        let reserve_in = U256::from(50_000_000u64);
+        let reserve_out = U256::from(80_000_000u64);
+        let expected = amount_in * 997 * reserve_out / (reserve_in * 1000 + amount_in * 997);
+        assert_eq!(
+            get_amount_out(amount_in, reserve_in, reserve_out, 30),
+            expected
+        );
+    }
+
+    #[test]
+    fn higher_fee_gives_less_output() {
+        let (amount_in, reserve_in, reserve_out) =
+            (U256::exp10(18), U256::exp10(21), U256::exp10(21));
+        assert!(
+            get_amount_out(amount_in, reserve_in, reserve_out, 25)
+                > get_amount_out(amount_in, reserve_in, reserve_out, 30)
+        );
+    }
+
+    #[test]
+    fn invalid_fee_or_overflow_gives_no_output() {
+        let (reserve_in, reserve_out) = (U256::exp10(21), U256::exp10(21));
+        assert!(!is_valid_fee(10_001));
+        assert!(get_amount_out(U256::exp10(18), reserve_in, reserve_out, 10_001).is_zero());
+        assert!(get_amount_out(U256::MAX, reserve_in, reserve_out, 30).is_zero());
+    }
+}
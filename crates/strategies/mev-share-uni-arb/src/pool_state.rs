@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use ethers::{
+    prelude::abigen,
+    providers::Middleware,
+    types::{H160, U64},
+};
+use futures::future::join_all;
+use tracing::info;
+
+use crate::{
+    reserves::ReserveTracker,
+    solver::{PoolState, V3PoolState},
+    templates::{BackrunHint, PoolRef, V3SwapState},
+    univ3_state::UniV3State,
+};
+
+abigen!(
+    IUniswapV2Pair,
+    r#"[
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+    ]"#;
+
+    IUniswapV3Pool,
+    r#"[
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+        function liquidity() external view returns (uint128)
+        function fee() external view returns (uint24)
+        function tickSpacing() external view returns (int24)
+        function tickBitmap(int16 wordPosition) external view returns (uint256)
+        function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized)
+    ]"#;
+);
+
+/// Number of tick bitmap words read on each side of the word holding the current tick.
+/// Each word covers 256 tick spacings, which is far more than a backrun moves the price.
+const TICK_BITMAP_WORDS: i16 = 1;
+
+/// Fetches the state of the pools templates need to size backruns.
+#[derive(Debug)]
+pub struct PoolStateFetcher<M> {
+    client: Arc<M>,
+    /// Fee and tick spacing of v3 pools, which never change.
+    v3_params: Mutex<HashMap<H160, (u32, i32)>>,
+    /// State of the v3 pools synced from their logs, read instead of the node.
+    v3_state: Option<Arc<UniV3State>>,
+    /// Reserves of the v2 pools synced from their logs, read instead of the node.
+    reserves: Option<Arc<ReserveTracker>>,
+}
+
+impl<M: Middleware + 'static> PoolStateFetcher<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            v3_params: Mutex::new(HashMap::new()),
+            v3_state: None,
+            reserves: None,
+        }
+    }
+
+    /// Read the state of the v3 pools watched by `v3_state` locally, instead of fetching
+    /// it from the node.
+    pub fn with_v3_state(mut self, v3_state: Arc<UniV3State>) -> Self {
+        self.set_v3_state(v3_state);
+        self
+    }
+
+    /// Read the reserves of the v2 pools watched by `reserves` locally, as long as they
+    /// are consistent with the block fetched at, instead of fetching them from the node.
+    pub fn with_reserves(mut self, reserves: Arc<ReserveTracker>) -> Self {
+        self.set_reserves(reserves);
+        self
+    }
+
+    pub(crate) fn set_v3_state(&mut self, v3_state: Arc<UniV3State>) {
+        self.v3_state = Some(v3_state);
+    }
+
+    pub(crate) fn set_reserves(&mut self, reserves: Arc<ReserveTracker>) {
+        self.reserves = Some(reserves);
+    }
+
+    /// Fetch the state of `pools` concurrently, with v2 reserves as of the end of `block`,
+    /// the block backruns build on. V3 pools swapped through by the hint get their
+    /// post-swap price and liquidity. Pools which can't be fetched are left out.
+    pub async fn fetch(
+        &self,
+        pools: &[PoolRef],
+        hint: &BackrunHint,
+        block: U64,
+    ) -> HashMap<H160, PoolState> {
+        let states = join_all(pools.iter().map(|pool| async move {
+            let (address, state) = match pool {
+                PoolRef::V2(address) => (*address, self.fetch_v2(*address, block).await),
+                PoolRef::V3(address) => (
+                    *address,
+                    self.fetch_v3(*address, hint.v3_swaps.get(address)).await,
+                ),
+            };
+            match state {
+                Ok(state) => Some((address, state)),
+                Err(e) => {
+                    info!("Error fetching state of pool {:?}: {}", address, e);
+                    None
+                }
+            }
+        }))
+        .await;
+        states.into_iter().flatten().collect()
+    }
+
+    /// Fetch the two tokens a pool swaps between. Uniswap v2 and v3 pools share the
+    /// getters.
+    pub async fn fetch_tokens(&self, address: H160) -> Result<(H160, H160)> {
+        let pair = IUniswapV2Pair::new(address, self.client.clone());
+        let (token0, token1) = (pair.token_0(), pair.token_1());
+        Ok(tokio::try_join!(token0.call(), token1.call())?)
+    }
+
+    async fn fetch_v2(&self, address: H160, block: U64) -> Result<PoolState> {
+        let tracked = self
+            .reserves
+            .as_ref()
+            .and_then(|reserves| reserves.reserves_at(address, block));
+        if let Some((reserve0, reserve1)) = tracked {
+            return Ok(PoolState::V2 { reserve0, reserve1 });
+        }
+        let pair = IUniswapV2Pair::new(address, self.client.clone());
+        let (reserve0, reserve1, _) = pair.get_reserves().block(block).call().await?;
+        Ok(PoolState::V2 {
+            reserve0: reserve0.into(),
+            reserve1: reserve1.into(),
+        })
+    }
+
+    async fn fetch_v3(&self, address: H160, swap: Option<&V3SwapState>) -> Result<PoolState> {
+        let tracked = self
+            .v3_state
+            .as_ref()
+            .and_then(|state| state.state(address));
+        if let Some(mut state) = tracked {
+            // Swaps don't change the initialized ticks, only where the price sits.
+            if let Some(swap) = swap {
+                state.sqrt_price_x96 = swap.sqrt_price_x96;
+                state.tick = swap.tick;
+                state.liquidity = swap.liquidity;
+            }
+            return Ok(PoolState::V3(state));
+        }
+        let pool = IUniswapV3Pool::new(address, self.client.clone());
+        let (fee, tick_spacing) = self.v3_params(&pool).await?;
+        let (sqrt_price_x96, tick, liquidity) = match swap {
+            Some(swap) => (swap.sqrt_price_x96, swap.tick, swap.liquidity),
+            None => {
+                let (sqrt_price_x96, tick, ..) = pool.slot_0().call().await?;
+                let liquidity = pool.liquidity().call().await?;
+                (sqrt_price_x96, tick, liquidity)
+            }
+        };
+        let ticks = self.fetch_ticks(&pool, tick, tick_spacing).await?;
+        Ok(PoolState::V3(V3PoolState {
+            sqrt_price_x96,
+            tick,
+            liquidity,
+            fee,
+            ticks,
+        }))
+    }
+
+    async fn v3_params(&self, pool: &IUniswapV3Pool<M>) -> Result<(u32, i32)> {
+        if let Some(params) = self.v3_params.lock().unwrap().get(&pool.address()) {
+            return Ok(*params);
+        }
+        let fee = pool.fee().call().await?;
+        let tick_spacing = pool.tick_spacing().call().await?;
+        self.v3_params
+            .lock()
+            .unwrap()
+            .insert(pool.address(), (fee, tick_spacing));
+        Ok((fee, tick_spacing))
+    }
+
+    /// Returns the initialized ticks and their net liquidity in the bitmap words around
+    /// `tick`, sorted by tick.
+    async fn fetch_ticks(
+        &self,
+        pool: &IUniswapV3Pool<M>,
+        tick: i32,
+        tick_spacing: i32,
+    ) -> Result<Vec<(i32, i128)>> {
+        let tick_spacing = tick_spacing.max(1);
+        let word = (tick.div_euclid(tick_spacing) >> 8) as i16;
+        let words = (word.saturating_sub(TICK_BITMAP_WORDS)
+            ..=word.saturating_add(TICK_BITMAP_WORDS))
+            .map(|word| async move {
+                Ok::<_, anyhow::Error>((word, pool.tick_bitmap(word).call().await?))
+            });
+
+        let mut initialized = Vec::new();
+        for result in join_all(words).await {
+            let (word, bitmap) = result?;
+            for bit in 0..256 {
+                if bitmap.bit(bit) {
+                    initialized.push(((word as i32) * 256 + bit as i32) * tick_spacing);
+                }
+            }
+        }
+
+        let ticks = join_all(initialized.into_iter().map(|tick| async move {
+            let (_, liquidity_net, ..) = pool.ticks(tick).call().await?;
+            Ok::<_, anyhow::Error>((tick, liquidity_net))
+        }))
+        .await;
+        let mut ticks = ticks.into_iter().collect::<Result<Vec<_>>>()?;
+        ticks.sort_by_key(|(tick, _)| *tick);
+        Ok(ticks)
+    }
+}
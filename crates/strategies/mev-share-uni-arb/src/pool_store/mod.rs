@@ -0,0 +1,434 @@
+//! Stores of the pools and routes the strategy arbs, read when it syncs. Pools can
+//! be kept in csv files, such as the ones bundled with the strategy, or in a SQLite
+//! or Postgres database for pool lists too large to bundle.
+
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+use ethers::types::H160;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+use crate::{
+    math::is_valid_fee,
+    types::{TriangularRouteRecord, V2V3PoolRecord},
+};
+
+/// Store backed by a SQLite database.
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Store backed by a Postgres database.
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+/// Name of the csv file of v3 / v2 pool pairs.
+const V2_V3_POOLS_FILE: &str = "v3_v2_pools.csv";
+
+/// Name of the csv file of triangular routes.
+const TRIANGULAR_ROUTES_FILE: &str = "triangular_routes.csv";
+
+/// Schema of the pool tables, shared by the database stores.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS v2_v3_pools (
+    token_address TEXT NOT NULL,
+    v3_pool TEXT NOT NULL,
+    v2_pool TEXT NOT NULL,
+    weth_token0 BOOLEAN NOT NULL,
+    fee_bps INTEGER NOT NULL,
+    factory TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS triangular_routes (
+    base_token TEXT NOT NULL,
+    base_token_decimals INTEGER NOT NULL,
+    pool_0 TEXT NOT NULL,
+    zero_for_one_0 BOOLEAN NOT NULL,
+    is_v3_0 BOOLEAN NOT NULL,
+    fee_bps_0 INTEGER NOT NULL,
+    pool_1 TEXT NOT NULL,
+    zero_for_one_1 BOOLEAN NOT NULL,
+    is_v3_1 BOOLEAN NOT NULL,
+    fee_bps_1 INTEGER NOT NULL,
+    pool_2 TEXT NOT NULL,
+    zero_for_one_2 BOOLEAN NOT NULL,
+    is_v3_2 BOOLEAN NOT NULL,
+    fee_bps_2 INTEGER NOT NULL
+);
+";
+
+/// Records read from a store, along with the rows which couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loaded<T> {
+    pub records: Vec<T>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+impl<T> From<Vec<T>> for Loaded<T> {
+    fn from(records: Vec<T>) -> Self {
+        Self {
+            records,
+            skipped: vec![],
+        }
+    }
+}
+
+impl<T: CheckedRecord> Loaded<T> {
+    /// Split `records` into the valid ones and the skipped invalid ones, numbering
+    /// rows by their index.
+    fn checked(records: Vec<T>) -> Self {
+        let mut loaded = Self::from(vec![]);
+        for (index, record) in records.into_iter().enumerate() {
+            match record.check() {
+                Ok(()) => loaded.records.push(record),
+                Err(error) => {
+                    warn!("Skipping row {} of the pool store: {}", index, error);
+                    loaded.skipped.push(SkippedRow {
+                        row: index as u64,
+                        error,
+                    });
+                }
+            }
+        }
+        loaded
+    }
+}
+
+/// A record which may be read from a store yet be unusable, e.g. with a fee over 100%.
+trait CheckedRecord {
+    /// Returns why the record is unusable, if it is.
+    fn check(&self) -> Result<(), String>;
+}
+
+impl CheckedRecord for V2V3PoolRecord {
+    fn check(&self) -> Result<(), String> {
+        match is_valid_fee(self.fee_bps) {
+            true => Ok(()),
+            false => Err(format!("invalid fee of {} bps", self.fee_bps)),
+        }
+    }
+}
+
+impl CheckedRecord for TriangularRouteRecord {
+    fn check(&self) -> Result<(), String> {
+        match [self.fee_bps_0, self.fee_bps_1, self.fee_bps_2]
+            .into_iter()
+            .find(|fee_bps| !is_valid_fee(*fee_bps))
+        {
+            Some(fee_bps) => Err(format!("invalid fee of {} bps", fee_bps)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A row of a store which couldn't be read, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// Line of the row in its file, or its index in its table.
+    pub row: u64,
+    pub error: String,
+}
+
+/// A store of the v3 / v2 pool pairs and triangular routes the strategy arbs.
+#[async_trait]
+pub trait PoolStore: Debug + Send + Sync {
+    /// Returns every v3 / v2 pool pair.
+    async fn v2_v3_pools(&self) -> Result<Vec<V2V3PoolRecord>>;
+
+    /// Returns every triangular route.
+    async fn triangular_routes(&self) -> Result<Vec<TriangularRouteRecord>>;
+
+    /// Returns every v3 / v2 pool pair which can be read, skipping malformed rows
+    /// and rows with invalid fees rather than failing on them. Defaults to the valid
+    /// pairs of [v2_v3_pools](PoolStore::v2_v3_pools).
+    async fn load_v2_v3_pools(&self) -> Result<Loaded<V2V3PoolRecord>> {
+        Ok(Loaded::checked(self.v2_v3_pools().await?))
+    }
+
+    /// Returns every triangular route which can be read, skipping malformed rows
+    /// and rows with invalid fees rather than failing on them. Defaults to the valid
+    /// routes of [triangular_routes](PoolStore::triangular_routes).
+    async fn load_triangular_routes(&self) -> Result<Loaded<TriangularRouteRecord>> {
+        Ok(Loaded::checked(self.triangular_routes().await?))
+    }
+
+    /// Replace the stored v3 / v2 pool pairs with `pools`.
+    async fn replace_v2_v3_pools(&self, pools: &[V2V3PoolRecord]) -> Result<()>;
+
+    /// Replace the stored triangular routes with `routes`.
+    async fn replace_triangular_routes(&self, routes: &[TriangularRouteRecord]) -> Result<()>;
+}
+
+/// Open the store at `url`: `sqlite://<path>` for a SQLite database,
+/// `postgres://...` for a Postgres database, or otherwise the path of a directory of
+/// csv files. Database stores are only available with the `sqlite` and `postgres`
+/// features.
+pub async fn open(url: &str) -> Result<Arc<dyn PoolStore>> {
+    if let Some(path) = url.strip_prefix("sqlite://") {
+        #[cfg(feature = "sqlite")]
+        return Ok(Arc::new(sqlite::SqlitePoolStore::open(path)?));
+        #[tokio::test]
+        async fn skips_malformed_csv_rows() {
+            let dir = std::env::temp_dir().join(format!("pool-store-bad-{}", std::process::id()));
+            let store = CsvPoolStore::new(&dir);
+            store
+                .replace_v2_v3_pools(&[pool(2), pool(5)])
+                .await
+                .unwrap();
+            let path = dir.join(V2_V3_POOLS_FILE);
+            let mut lines: Vec<String> = std::fs::read_to_string(&path)
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect();
+            lines.insert(2, "0xnot-an-address,1,2".to_string());
+            std::fs::write(&path, lines.join("\n")).unwrap();
+
+            assert!(store.v2_v3_pools().await.is_err());
+            let loaded = store.load_v2_v3_pools().await.unwrap();
+            assert_eq!(loaded.records, vec![pool(2), pool(5)]);
+            assert_eq!(loaded.skipped.len(), 1);
+            assert_eq!(loaded.skipped[0].row, 3);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+
+        #[cfg(not(feature = "sqlite"))]
+        return Err(anyhow!(
+            "can't open {}, built without the sqlite feature",
+            path
+        ));
+    }
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(Arc::new(postgres::PostgresPoolStore::connect(url).await?));
+        #[cfg(not(feature = "postgres"))]
+        return Err(anyhow!(
+            "can't open {}, built without the postgres feature",
+            url
+        ));
+    }
+    Ok(Arc::new(CsvPoolStore::new(url)))
+}
+
+/// Copy every pool and route from `from` to `to`, replacing the ones `to` held.
+/// Returns the number of pool pairs and routes copied.
+pub async fn import(from: &dyn PoolStore, to: &dyn PoolStore) -> Result<(usize, usize)> {
+    let pools = from.v2_v3_pools().await?;
+    let routes = from.triangular_routes().await?;
+    to.replace_v2_v3_pools(&pools).await?;
+    to.replace_triangular_routes(&routes).await?;
+    Ok((pools.len(), routes.len()))
+}
+
+/// Store of csv files in a directory, named `v3_v2_pools.csv` and
+/// `triangular_routes.csv`. Missing files hold no pools.
+#[derive(Debug, Clone)]
+pub struct CsvPoolStore {
+    dir: PathBuf,
+}
+
+impl CsvPoolStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The csv files bundled with the strategy.
+    pub fn bundled() -> Self {
+        Self::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("resources"))
+    }
+
+    fn read<T: DeserializeOwned>(&self, file: &str) -> Result<Vec<T>> {
+        let path = self.dir.join(file);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let mut reader = csv::Reader::from_path(&path)
+            .with_context(|| format!("Error opening {}", path.display()))?;
+        reader
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .with_context(|| format!("Error reading {}", path.display()))
+    }
+
+    /// Like [read](CsvPoolStore::read), but skips the rows which can't be read or are
+    /// invalid.
+    fn read_lenient<T: DeserializeOwned + CheckedRecord>(&self, file: &str) -> Result<Loaded<T>> {
+        let path = self.dir.join(file);
+        if !path.exists() {
+            return Ok(Loaded::from(vec![]));
+        }
+        let mut reader = csv::Reader::from_path(&path)
+            .with_context(|| format!("Error opening {}", path.display()))?;
+        let mut loaded = Loaded::from(vec![]);
+        for (index, result) in reader.deserialize().enumerate() {
+            match result {
+                Ok(record) => match record.check() {
+                    Ok(()) => loaded.records.push(record),
+                    Err(error) => {
+                        let row = index as u64 + 2;
+                        warn!("Skipping row {} of {}: {}", row, path.display(), error);
+                        loaded.skipped.push(SkippedRow { row, error });
+                    }
+                },
+                Err(e) => {
+                    // Rows are numbered from the header, on line 1.
+                    let row = e
+                        .position()
+                        .map(|position| position.line())
+                        .unwrap_or(index as u64 + 2);
+                    warn!("Skipping row {} of {}: {}", row, path.display(), e);
+                    loaded.skipped.push(SkippedRow {
+                        row,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn write<T: Serialize>(&self, file: &str, records: &[T]) -> Result<()> {
+        let path = self.dir.join(file);
+        std::fs::create_dir_all(&self.dir)?;
+        let mut writer = csv::Writer::from_path(&path)
+            .with_context(|| format!("Error opening {}", path.display()))?;
+        for record in records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PoolStore for CsvPoolStore {
+    async fn v2_v3_pools(&self) -> Result<Vec<V2V3PoolRecord>> {
+        self.read(V2_V3_POOLS_FILE)
+    }
+
+    async fn triangular_routes(&self) -> Result<Vec<TriangularRouteRecord>> {
+        self.read(TRIANGULAR_ROUTES_FILE)
+    }
+
+    async fn load_v2_v3_pools(&self) -> Result<Loaded<V2V3PoolRecord>> {
+        self.read_lenient(V2_V3_POOLS_FILE)
+    }
+
+    async fn load_triangular_routes(&self) -> Result<Loaded<TriangularRouteRecord>> {
+        self.read_lenient(TRIANGULAR_ROUTES_FILE)
+    }
+
+    async fn replace_v2_v3_pools(&self, pools: &[V2V3PoolRecord]) -> Result<()> {
+        self.write(V2_V3_POOLS_FILE, pools)
+    }
+
+    async fn replace_triangular_routes(&self, routes: &[TriangularRouteRecord]) -> Result<()> {
+        self.write(TRIANGULAR_ROUTES_FILE, routes)
+    }
+}
+
+/// Parse an address stored as text by a database store.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+fn parse_address(address: &str) -> Result<H160> {
+    address
+        .parse()
+        .map_err(|e| anyhow!("invalid address {} in pool store: {}", address, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H160;
+
+    fn pool(v3_pool: u64) -> V2V3PoolRecord {
+        V2V3PoolRecord {
+            token_address: H160::from_low_u64_be(1),
+            v3_pool: H160::from_low_u64_be(v3_pool),
+            v2_pool: H160::from_low_u64_be(3),
+            weth_token0: true,
+            fee_bps: 30,
+            factory: H160::from_low_u64_be(4),
+        }
+    }
+
+    #[tokio::test]
+    async fn imports_bundled_pools_into_csv_store() {
+        let bundled = CsvPoolStore::bundled();
+        let dir = std::env::temp_dir().join(format!("pool-store-{}", std::process::id()));
+        let store = open(dir.to_str().unwrap()).await.unwrap();
+        assert!(store.v2_v3_pools().await.unwrap().is_empty());
+
+        let (pools, routes) = import(&bundled, store.as_ref()).await.unwrap();
+        assert_eq!(pools, bundled.v2_v3_pools().await.unwrap().len());
+        assert!(pools > 0);
+        assert_eq!(store.v2_v3_pools().await.unwrap().len(), pools);
+        assert_eq!(store.triangular_routes().await.unwrap().len(), routes);
+
+        // Refreshing replaces the stored pools.
+        store.replace_v2_v3_pools(&[pool(2)]).await.unwrap();
+        assert_eq!(store.v2_v3_pools().await.unwrap(), vec![pool(2)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn skips_malformed_csv_rows() {
+        let dir = std::env::temp_dir().join(format!("pool-store-bad-{}", std::process::id()));
+        let store = CsvPoolStore::new(&dir);
+        store
+            .replace_v2_v3_pools(&[pool(2), pool(5)])
+            .await
+            .unwrap();
+        let path = dir.join(V2_V3_POOLS_FILE);
+        let mut lines: Vec<String> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        lines.insert(2, "0xnot-an-address,1,2".to_string());
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        assert!(store.v2_v3_pools().await.is_err());
+        let loaded = store.load_v2_v3_pools().await.unwrap();
+        assert_eq!(loaded.records, vec![pool(2), pool(5)]);
+        assert_eq!(loaded.skipped.len(), 1);
+        assert_eq!(loaded.skipped[0].row, 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn skips_rows_with_invalid_fees() {
+        let dir = std::env::temp_dir().join(format!("pool-store-fee-{}", std::process::id()));
+        let store = CsvPoolStore::new(&dir);
+        let invalid = V2V3PoolRecord {
+            fee_bps: 10_001,
+            ..pool(5)
+        };
+        store
+            .replace_v2_v3_pools(&[pool(2), invalid.clone()])
+            .await
+            .unwrap();
+
+        let loaded = store.load_v2_v3_pools().await.unwrap();
+        assert_eq!(loaded.records, vec![pool(2)]);
+        assert_eq!(loaded.skipped.len(), 1);
+        assert_eq!(loaded.skipped[0].row, 3);
+
+        // Stores without their own loaders skip them too.
+        let loaded = Loaded::checked(vec![pool(2), invalid]);
+        assert_eq!(loaded.records, vec![pool(2)]);
+        assert_eq!(loaded.skipped[0].row, 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[tokio::test]
+    async fn refuses_stores_without_their_feature() {
+        assert!(open("sqlite://pools.db").await.is_err());
+    }
+}
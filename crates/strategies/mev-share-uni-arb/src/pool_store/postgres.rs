@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tokio_postgres::{Client, NoTls, Row};
+use tracing::warn;
+
+use super::{parse_address, PoolStore, SCHEMA};
+use crate::types::{TriangularRouteRecord, V2V3PoolRecord};
+
+/// Store of pools in a Postgres database.
+pub struct PostgresPoolStore {
+    client: Mutex<Client>,
+}
+
+impl std::fmt::Debug for PostgresPoolStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresPoolStore").finish_non_exhaustive()
+    }
+}
+
+impl PostgresPoolStore {
+    /// Connect to the database at `url`, creating the tables if needed.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .context("Error connecting to pool database")?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!("pool database connection error: {}", e);
+            }
+        });
+        client.batch_execute(SCHEMA).await?;
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+}
+
+/// Read the unsigned integer in column `idx`, stored as a signed INTEGER.
+fn get_u32(row: &Row, idx: usize) -> Result<u32> {
+    Ok(u32::try_from(row.try_get::<_, i32>(idx)?)?)
+}
+
+#[async_trait]
+impl PoolStore for PostgresPoolStore {
+    async fn v2_v3_pools(&self) -> Result<Vec<V2V3PoolRecord>> {
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query(
+                "SELECT token_address, v3_pool, v2_pool, weth_token0, fee_bps, factory
+                 FROM v2_v3_pools",
+                &[],
+            )
+            .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(V2V3PoolRecord {
+                    token_address: parse_address(row.try_get(0)?)?,
+                    v3_pool: parse_address(row.try_get(1)?)?,
+                    v2_pool: parse_address(row.try_get(2)?)?,
+                    weth_token0: row.try_get(3)?,
+                    fee_bps: get_u32(row, 4)?,
+                    factory: parse_address(row.try_get(5)?)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn triangular_routes(&self) -> Result<Vec<TriangularRouteRecord>> {
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query(
+                "SELECT base_token, base_token_decimals,
+                    pool_0, zero_for_one_0, is_v3_0, fee_bps_0,
+                    pool_1, zero_for_one_1, is_v3_1, fee_bps_1,
+                    pool_2, zero_for_one_2, is_v3_2, fee_bps_2
+                 FROM triangular_routes",
+                &[],
+            )
+            .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(TriangularRouteRecord {
+                    base_token: parse_address(row.try_get(0)?)?,
+                    base_token_decimals: u8::try_from(row.try_get::<_, i32>(1)?)?,
+                    pool_0: parse_address(row.try_get(2)?)?,
+                    zero_for_one_0: row.try_get(3)?,
+                    is_v3_0: row.try_get(4)?,
+                    fee_bps_0: get_u32(row, 5)?,
+                    pool_1: parse_address(row.try_get(6)?)?,
+                    zero_for_one_1: row.try_get(7)?,
+                    is_v3_1: row.try_get(8)?,
+                    fee_bps_1: get_u32(row, 9)?,
+                    pool_2: parse_address(row.try_get(10)?)?,
+                    zero_for_one_2: row.try_get(11)?,
+                    is_v3_2: row.try_get(12)?,
+                    fee_bps_2: get_u32(row, 13)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn replace_v2_v3_pools(&self, pools: &[V2V3PoolRecord]) -> Result<()> {
+        let mut client = self.client.lock().await;
+        let transaction = client.transaction().await?;
+        transaction.execute("DELETE FROM v2_v3_pools", &[]).await?;
+        let insert = transaction
+            .prepare(
+                "INSERT INTO v2_v3_pools
+                 (token_address, v3_pool, v2_pool, weth_token0, fee_bps, factory)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .await?;
+        for pool in pools {
+            transaction
+                .execute(
+                    &insert,
+                    &[
+                        &format!("{:?}", pool.token_address),
+                        &format!("{:?}", pool.v3_pool),
+                        &format!("{:?}", pool.v2_pool),
+                        &pool.weth_token0,
+                        &i32::try_from(pool.fee_bps)?,
+                        &format!("{:?}", pool.factory),
+                    ],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn replace_triangular_routes(&self, routes: &[TriangularRouteRecord]) -> Result<()> {
+        let mut client = self.client.lock().await;
+        let transaction = client.transaction().await?;
+        transaction
+            .execute("DELETE FROM triangular_routes", &[])
+            .await?;
+        let insert = transaction
+            .prepare(
+                "INSERT INTO triangular_routes VALUES
+                 ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+            )
+            .await?;
+        for route in routes {
+            transaction
+                .execute(
+                    &insert,
+                    &[
+                        &format!("{:?}", route.base_token),
+                        &i32::from(route.base_token_decimals),
+                        &format!("{:?}", route.pool_0),
+                        &route.zero_for_one_0,
+                        &route.is_v3_0,
+                        &i32::try_from(route.fee_bps_0)?,
+                        &format!("{:?}", route.pool_1),
+                        &route.zero_for_one_1,
+                        &route.is_v3_1,
+                        &i32::try_from(route.fee_bps_1)?,
+                        &format!("{:?}", route.pool_2),
+                        &route.zero_for_one_2,
+                        &route.is_v3_2,
+                        &i32::try_from(route.fee_bps_2)?,
+                    ],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::{path::Path, sync::Mutex};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+
+use super::{parse_address, PoolStore, SCHEMA};
+use crate::types::{TriangularRouteRecord, V2V3PoolRecord};
+
+/// Store of pools in a SQLite database. Queries block, which is fine since the
+/// store is only read when the strategy syncs.
+#[derive(Debug)]
+pub struct SqlitePoolStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqlitePoolStore {
+    /// Open the database at `path`, creating it and its tables if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)
+            .with_context(|| format!("Error opening pool database {}", path.display()))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+#[async_trait]
+impl PoolStore for SqlitePoolStore {
+    async fn v2_v3_pools(&self) -> Result<Vec<V2V3PoolRecord>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT token_address, v3_pool, v2_pool, weth_token0, fee_bps, factory
+             FROM v2_v3_pools",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut pools = vec![];
+        while let Some(row) = rows.next()? {
+            pools.push(V2V3PoolRecord {
+                token_address: parse_address(&row.get::<_, String>(0)?)?,
+                v3_pool: parse_address(&row.get::<_, String>(1)?)?,
+                v2_pool: parse_address(&row.get::<_, String>(2)?)?,
+                weth_token0: row.get(3)?,
+                fee_bps: row.get(4)?,
+                factory: parse_address(&row.get::<_, String>(5)?)?,
+            });
+        }
+        Ok(pools)
+    }
+
+    async fn triangular_routes(&self) -> Result<Vec<TriangularRouteRecord>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT base_token, base_token_decimals,
+                pool_0, zero_for_one_0, is_v3_0, fee_bps_0,
+                pool_1, zero_for_one_1, is_v3_1, fee_bps_1,
+                pool_2, zero_for_one_2, is_v3_2, fee_bps_2
+             FROM triangular_routes",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut routes = vec![];
+        while let Some(row) = rows.next()? {
+            routes.push(TriangularRouteRecord {
+                base_token: parse_address(&row.get::<_, String>(0)?)?,
+                base_token_decimals: row.get(1)?,
+                pool_0: parse_address(&row.get::<_, String>(2)?)?,
+                zero_for_one_0: row.get(3)?,
+                is_v3_0: row.get(4)?,
+                fee_bps_0: row.get(5)?,
+                pool_1: parse_address(&row.get::<_, String>(6)?)?,
+                zero_for_one_1: row.get(7)?,
+                is_v3_1: row.get(8)?,
+                fee_bps_1: row.get(9)?,
+                pool_2: parse_address(&row.get::<_, String>(10)?)?,
+                zero_for_one_2: row.get(11)?,
+                is_v3_2: row.get(12)?,
+                fee_bps_2: row.get(13)?,
+            });
+        }
+        Ok(routes)
+    }
+
+    async fn replace_v2_v3_pools(&self, pools: &[V2V3PoolRecord]) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM v2_v3_pools", [])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO v2_v3_pools
+                 (token_address, v3_pool, v2_pool, weth_token0, fee_bps, factory)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for pool in pools {
+                insert.execute(params![
+                    format!("{:?}", pool.token_address),
+                    format!("{:?}", pool.v3_pool),
+                    format!("{:?}", pool.v2_pool),
+                    pool.weth_token0,
+                    pool.fee_bps,
+                    format!("{:?}", pool.factory),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    async fn replace_triangular_routes(&self, routes: &[TriangularRouteRecord]) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM triangular_routes", [])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO triangular_routes VALUES
+                 (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            )?;
+            for route in routes {
+                insert.execute(params![
+                    format!("{:?}", route.base_token),
+                    route.base_token_decimals,
+                    format!("{:?}", route.pool_0),
+                    route.zero_for_one_0,
+                    route.is_v3_0,
+                    route.fee_bps_0,
+                    format!("{:?}", route.pool_1),
+                    route.zero_for_one_1,
+                    route.is_v3_1,
+                    route.fee_bps_1,
+                    format!("{:?}", route.pool_2),
+                    route.zero_for_one_2,
+                    route.is_v3_2,
+                    route.fee_bps_2,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::cmp::Reverse;
+
+use ethers::types::U256;
+
+/// Returns what an arb expected to make `profit` keeps once it paid `payment_percentage`
+/// of it to the coinbase, and `gas_cost` if it is paid in the same token as the profit.
+pub fn net_profit(profit: U256, payment_percentage: U256, gas_cost: Option<U256>) -> U256 {
+    let payment = profit * payment_percentage.min(U256::from(100)) / 100;
+    (profit - payment).saturating_sub(gas_cost.unwrap_or_default())
+}
+
+/// Keep the `k` arbs with the highest `net_profit`. Arbs without an expected profit, e.g.
+/// sizes submitted blindly, rank after every priced arb, in the order they were given.
+pub fn top_k<T>(arbs: Vec<T>, k: usize, net_profit: impl Fn(&T) -> Option<U256>) -> Vec<T> {
+    let mut ranked: Vec<(Option<Reverse<U256>>, T)> = arbs
+        .into_iter()
+        .map(|arb| (net_profit(&arb).map(Reverse), arb))
+        .collect();
+    // `None` sorts before `Some`, so unpriced arbs are moved after the priced ones.
+    ranked.sort_by_key(|(net, _)| (net.is_none(), *net));
+    ranked.into_iter().take(k).map(|(_, arb)| arb).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nets_out_the_coinbase_payment_and_gas() {
+        let profit = U256::from(1_000);
+        assert_eq!(net_profit(profit, U256::from(40), None), U256::from(600));
+        assert_eq!(
+            net_profit(profit, U256::from(40), Some(U256::from(100))),
+            U256::from(500)
+        );
+        assert_eq!(
+            net_profit(profit, U256::from(90), Some(U256::from(500))),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn keeps_the_most_profitable_arbs() {
+        let arbs = vec![
+            (1, Some(10u64)),
+            (2, None),
+            (3, Some(30)),
+            (4, Some(20)),
+            (5, None),
+        ];
+        let net = |arb: &(u32, Option<u64>)| arb.1.map(U256::from);
+
+        let ids =
+            |arbs: Vec<(u32, Option<u64>)>| arbs.into_iter().map(|arb| arb.0).collect::<Vec<_>>();
+        assert_eq!(ids(top_k(arbs.clone(), 2, net)), vec![3, 4]);
+        assert_eq!(ids(top_k(arbs.clone(), 4, net)), vec![3, 4, 1, 2]);
+        assert_eq!(ids(top_k(arbs, 10, net)), vec![3, 4, 1, 2, 5]);
+    }
+}
@@ -0,0 +1,32 @@
+use ethers::types::U256;
+
+/// Returns the percent (0-100) of a backrun bundle's earnings to refund to the user, as
+/// the share of them the user's transaction brings in: the `mev_fee` it pays the
+/// matchmaker, against the `payment` the backrun makes to the coinbase. The percent is
+/// kept between `floor` and `ceiling`.
+pub fn refund_percent(mev_fee: U256, payment: U256, floor: u64, ceiling: u64) -> u64 {
+    let earnings = mev_fee.saturating_add(payment);
+    let share = if earnings.is_zero() {
+        0
+    } else {
+        (mev_fee.saturating_mul(U256::from(100)) / earnings)
+            .min(U256::from(100))
+            .as_u64()
+    };
+    share.max(floor).min(ceiling).min(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refunds_the_share_the_user_brings_in() {
+        let refund = |fee: u64, payment: u64| refund_percent(fee.into(), payment.into(), 10, 90);
+        assert_eq!(refund(250, 750), 25);
+        assert_eq!(refund(1, 999), 10);
+        assert_eq!(refund(999, 1), 90);
+        assert_eq!(refund(0, 0), 10);
+        assert_eq!(refund_percent(U256::exp10(30), U256::zero(), 0, 100), 100);
+    }
+}
@@ -0,0 +1,261 @@
+//! Local reserves of uniswap v2 pools, kept in sync from their `Sync` logs. Every `Sync`
+//! log carries the full reserves of the pool, so applying the latest one is enough to
+//! know them, and each snapshot is tagged with the block it was taken at so arbs are
+//! only sized against reserves known to hold at the block they build on.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use ethers::{
+    prelude::Lazy,
+    providers::Middleware,
+    types::{Filter, Log, H160, H256, U256, U64},
+    utils::keccak256,
+};
+use futures::future::join_all;
+use tracing::{info, warn};
+
+use crate::pool_state::IUniswapV2Pair;
+
+/// Topic of the `Sync` event of uniswap v2 pairs.
+static V2_SYNC_TOPIC: Lazy<H256> = Lazy::new(|| H256::from(keccak256("Sync(uint112,uint112)")));
+
+/// The reserves of a pool as of a log, or as read at a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveSnapshot {
+    pub reserve0: U256,
+    pub reserve1: U256,
+    /// Block the reserves were last changed in, or read at.
+    pub block: U64,
+    /// Index of the log which set the reserves in `block`, or `U256::MAX` if they were
+    /// read at the end of it.
+    log_index: U256,
+}
+
+impl ReserveSnapshot {
+    fn position(&self) -> (U64, U256) {
+        (self.block, self.log_index)
+    }
+}
+
+/// The reserves of the watched uniswap v2 pools, shared between the tasks applying their
+/// logs and the tasks sizing arbs.
+///
+/// Reserves are only given for a block once a log or block at least as recent was seen,
+/// since until then a `Sync` log of the block may still be on its way.
+#[derive(Debug, Default)]
+pub struct ReserveTracker {
+    /// Latest reserves of each watched pool, or `None` until they are known again after
+    /// a reorg removed a log they were set by.
+    pools: RwLock<HashMap<H160, Option<ReserveSnapshot>>>,
+    /// Most recent block seen, from logs or new blocks.
+    head: RwLock<Option<U64>>,
+}
+
+impl ReserveTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch the pools at `addresses`. Their reserves are known from their next `Sync`
+    /// log, or once [synced](ReserveTracker::sync_pools).
+    pub fn watch(&self, addresses: impl IntoIterator<Item = H160>) {
+        let mut pools = self.pools.write().unwrap();
+        for address in addresses {
+            pools.entry(address).or_default();
+        }
+    }
+
+    /// Returns the addresses of the watched pools.
+    pub fn watched(&self) -> Vec<H160> {
+        self.pools.read().unwrap().keys().copied().collect()
+    }
+
+    /// Returns a filter for the `Sync` logs of every pool, e.g. for a
+    /// [LogCollector](artemis_core::collectors::log_collector::LogCollector). It isn't
+    /// restricted to the watched pools, since those are only known once the strategy
+    /// is synced; logs of other pools are ignored.
+    pub fn filter() -> Filter {
+        Filter::new().topic0(*V2_SYNC_TOPIC)
+    }
+
+    /// Record that `block` was seen, e.g. from a new block event.
+    pub fn observe_block(&self, block: U64) {
+        let mut head = self.head.write().unwrap();
+        match *head {
+            Some(seen) if seen >= block => {}
+            _ => *head = Some(block),
+        }
+    }
+
+    /// Apply a `Sync` log of a watched pool. Returns whether it was applied: logs of
+    /// other events or pools, and logs older than the reserves known, aren't.
+    pub fn apply_log(&self, log: &Log) -> bool {
+        if log.topics.first() != Some(&*V2_SYNC_TOPIC) {
+            return false;
+        }
+        let (Some(block), Some(log_index)) = (log.block_number, log.log_index) else {
+            return false;
+        };
+        let mut pools = self.pools.write().unwrap();
+        let Some(snapshot) = pools.get_mut(&log.address) else {
+            return false;
+        };
+        if log.removed == Some(true) {
+            if let Some(known) = *snapshot {
+                if known.block >= block {
+                    warn!("sync log of pool {:?} removed by a reorg", log.address);
+                    *snapshot = None;
+                }
+            }
+            return false;
+        }
+        if let Some(known) = *snapshot {
+            if (block, log_index) <= known.position() {
+                return false;
+            }
+        }
+
+        // Data is (uint112 reserve0, uint112 reserve1), each padded to a word.
+        let (Some(reserve0), Some(reserve1)) = (log.data.get(..32), log.data.get(32..64)) else {
+            return false;
+        };
+        *snapshot = Some(ReserveSnapshot {
+            reserve0: U256::from_big_endian(reserve0),
+            reserve1: U256::from_big_endian(reserve1),
+            block,
+            log_index,
+        });
+        drop(pools);
+        self.observe_block(block);
+        true
+    }
+
+    /// Returns the latest reserves known of a watched pool, whichever block they are from.
+    pub fn latest(&self, address: H160) -> Option<ReserveSnapshot> {
+        self.pools.read().unwrap().get(&address).copied().flatten()
+    }
+
+    /// Returns the reserves of a watched pool at the end of `block`, i.e. the reserves
+    /// txs of the next block trade against, if they are known to be consistent with it:
+    /// they were set at or before `block`, and `block` was seen since.
+    pub fn reserves_at(&self, address: H160, block: U64) -> Option<(U256, U256)> {
+        match *self.head.read().unwrap() {
+            Some(head) if head >= block => {}
+            _ => return None,
+        }
+        let snapshot = self
+            .latest(address)
+            .filter(|snapshot| snapshot.block <= block)?;
+        Some((snapshot.reserve0, snapshot.reserve1))
+    }
+
+    /// Read the reserves of the watched pools at the latest block. Pools whose reserves
+    /// can't be read are left to their next `Sync` log. Returns how many were read.
+    pub async fn sync_pools<M: Middleware + 'static>(&self, client: Arc<M>) -> usize {
+        let block = match client.get_block_number().await {
+            Ok(block) => block,
+            Err(e) => {
+                info!("Error getting latest block to sync reserves: {}", e);
+                return 0;
+            }
+        };
+        let reads = join_all(self.watched().into_iter().map(|address| {
+            let call = IUniswapV2Pair::new(address, client.clone())
+                .get_reserves()
+                .block(block);
+            async move { (address, call.call().await) }
+        }))
+        .await;
+
+        let mut synced = 0;
+        for (address, result) in reads {
+            match result {
+                Ok((reserve0, reserve1, _)) => {
+                    let read = ReserveSnapshot {
+                        reserve0: reserve0.into(),
+                        reserve1: reserve1.into(),
+                        block,
+                        log_index: U256::MAX,
+                    };
+                    // Logs applied while reading are more recent than the block read.
+                    let mut pools = self.pools.write().unwrap();
+                    let snapshot = pools.entry(address).or_default();
+                    match *snapshot {
+                        Some(known) if known.position() >= read.position() => {}
+                        _ => *snapshot = Some(read),
+                    }
+                    synced += 1;
+                }
+                Err(e) => info!("Error reading reserves of pool {:?}: {}", address, e),
+            }
+        }
+        self.observe_block(block);
+        synced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{abi::Token, types::Bytes};
+
+    fn sync_log(pool: H160, block: u64, log_index: u64, reserves: (u64, u64)) -> Log {
+        Log {
+            address: pool,
+            topics: vec![*V2_SYNC_TOPIC],
+            data: Bytes::from(ethers::abi::encode(&[
+                Token::Uint(reserves.0.into()),
+                Token::Uint(reserves.1.into()),
+            ])),
+            block_number: Some(block.into()),
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn applies_sync_logs_in_order() {
+        let pool = H160::from_low_u64_be(1);
+        let tracker = ReserveTracker::new();
+        assert!(!tracker.apply_log(&sync_log(pool, 10, 0, (1, 2))));
+
+        tracker.watch([pool]);
+        assert!(tracker.apply_log(&sync_log(pool, 10, 3, (1, 2))));
+        assert!(tracker.apply_log(&sync_log(pool, 10, 5, (3, 4))));
+        // Logs older than the reserves known are out of order.
+        assert!(!tracker.apply_log(&sync_log(pool, 10, 4, (5, 6))));
+        assert_eq!(
+            tracker.reserves_at(pool, U64::from(10)),
+            Some((U256::from(3), U256::from(4)))
+        );
+
+        let mut removed = sync_log(pool, 10, 5, (3, 4));
+        removed.removed = Some(true);
+        assert!(!tracker.apply_log(&removed));
+        assert_eq!(tracker.latest(pool), None);
+        // The next sync log restores the reserves.
+        assert!(tracker.apply_log(&sync_log(pool, 11, 0, (7, 8))));
+        assert_eq!(tracker.latest(pool).unwrap().block, U64::from(11));
+    }
+
+    #[test]
+    fn only_gives_reserves_consistent_with_the_block() {
+        let pool = H160::from_low_u64_be(1);
+        let tracker = ReserveTracker::new();
+        tracker.watch([pool]);
+        tracker.apply_log(&sync_log(pool, 10, 0, (1, 2)));
+
+        // Set after the block.
+        assert_eq!(tracker.reserves_at(pool, U64::from(9)), None);
+        // A sync log of block 11 may still be on its way.
+        assert_eq!(tracker.reserves_at(pool, U64::from(11)), None);
+        tracker.observe_block(U64::from(11));
+        assert_eq!(
+            tracker.reserves_at(pool, U64::from(11)),
+            Some((U256::from(1), U256::from(2)))
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use artemis_core::types::SubmissionReceipt;
+use ethers::types::{H256, U64};
+use matchmaker::status::{BundleStatus, StatusUpdate};
+
+use crate::templates::BackrunHint;
+
+/// Number of blocks a hint is remembered for after it was last submitted. Its bundles
+/// are valid for a few blocks, and their status is polled for a few more.
+const MAX_HINT_AGE: u64 = 8;
+
+/// A hint whose arbs may be resubmitted if their bundles miss their target block.
+#[derive(Debug)]
+struct SubmittedHint {
+    hint: BackrunHint,
+    /// Number of times in a row the bundles of the hint missed their target block.
+    misses: u32,
+    /// Last block targeted by the bundle whose miss was counted last.
+    missed_block: Option<U64>,
+    /// Latest block when the hint was last submitted.
+    block: U64,
+}
+
+/// Tracks the bundles submitted for each hint, from the
+/// [receipts](SubmissionReceipt) of the executor and the
+/// [status updates](StatusUpdate) of a bundle status collector, to resubmit the arbs of
+/// hints whose bundles missed their target block.
+#[derive(Debug, Default)]
+pub struct Resubmissions {
+    /// Hints which may be resubmitted, by hash.
+    hints: HashMap<H256, SubmittedHint>,
+    /// Hash of the hint each submitted bundle backruns, by bundle hash.
+    bundles: HashMap<H256, H256>,
+    /// Latest block seen.
+    latest_block: U64,
+}
+
+impl Resubmissions {
+    /// Remember `hint`, so its arbs can be resubmitted once they are submitted.
+    pub fn record_hint(&mut self, hint: &BackrunHint) {
+        let block = self.latest_block;
+        self.hints
+            .entry(hint.tx_hash)
+            .or_insert_with(|| SubmittedHint {
+                hint: hint.clone(),
+                misses: 0,
+                missed_block: None,
+                block,
+            });
+    }
+
+    /// Record the bundles the matchmaker accepted. The first tx of a bundle is the
+    /// backrun hint, so bundles of hints which aren't remembered are ignored.
+    pub fn record_submissions(&mut self, receipt: &SubmissionReceipt) {
+        for submission in &receipt.submissions {
+            let (Some(bundle_hash), Some(hint_hash)) =
+                (submission.bundle_hash, submission.tx_hashes.first())
+            else {
+                continue;
+            };
+            if self.hints.contains_key(hint_hash) {
+                self.bundles.insert(bundle_hash, *hint_hash);
+            }
+        }
+    }
+
+    /// Record a new block, forgetting the hints last submitted too long before it.
+    pub fn on_new_block(&mut self, block: U64) {
+        self.latest_block = self.latest_block.max(block);
+        let latest_block = self.latest_block;
+        self.hints
+            .retain(|_, submitted| submitted.block + MAX_HINT_AGE >= latest_block);
+        let hints = &self.hints;
+        self.bundles
+            .retain(|_, hint_hash| hints.contains_key(hint_hash));
+    }
+
+    /// Record the new status of a bundle. Returns the hint to resubmit, and how many
+    /// target blocks in a row its bundles missed, if the bundle missed its last target
+    /// block. Bundles of a hint missing the same block are only counted once, and hints
+    /// whose bundles landed are forgotten.
+    pub fn record_status(&mut self, update: &StatusUpdate) -> Option<(BackrunHint, u32)> {
+        match update.status {
+            BundleStatus::Landed => {
+                let hint_hash = self.bundles.remove(&update.bundle_hash)?;
+                self.hints.remove(&hint_hash);
+                None
+            }
+            BundleStatus::Missed => {
+                let hint_hash = self.bundles.remove(&update.bundle_hash)?;
+                let submitted = self.hints.get_mut(&hint_hash)?;
+                if submitted.missed_block >= Some(update.target_block) {
+                    return None;
+                }
+                submitted.missed_block = Some(update.target_block);
+                submitted.misses += 1;
+                submitted.block = self.latest_block;
+                Some((submitted.hint.clone(), submitted.misses))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use artemis_core::types::Submission;
+
+    fn submitted(hint: H256, bundle: H256) -> SubmissionReceipt {
+        let submission = Submission::new("mev-share", vec![hint, H256::repeat_byte(9)])
+            .with_bundle_hash(Some(bundle));
+        SubmissionReceipt::new(vec![submission])
+    }
+
+    fn update(bundle_hash: H256, target_block: u64, status: BundleStatus) -> StatusUpdate {
+        StatusUpdate {
+            bundle_hash,
+            target_block: U64::from(target_block),
+            status,
+        }
+    }
+
+    #[test]
+    fn resubmits_hints_whose_bundles_missed() {
+        let mut resubmissions = Resubmissions::default();
+        let hint = BackrunHint {
+            tx_hash: H256::repeat_byte(1),
+            ..Default::default()
+        };
+        let (first, second, third) = (
+            H256::repeat_byte(2),
+            H256::repeat_byte(3),
+            H256::repeat_byte(4),
+        );
+        resubmissions.record_hint(&hint);
+        resubmissions.record_submissions(&submitted(hint.tx_hash, first));
+        resubmissions.record_submissions(&submitted(hint.tx_hash, second));
+
+        let (resubmitted, misses) = resubmissions
+            .record_status(&update(first, 10, BundleStatus::Missed))
+            .unwrap();
+        assert_eq!((resubmitted, misses), (hint.clone(), 1));
+        // Another bundle missing the same block isn't counted again.
+        assert!(resubmissions
+            .record_status(&update(second, 10, BundleStatus::Missed))
+            .is_none());
+
+        resubmissions.record_submissions(&submitted(hint.tx_hash, third));
+        let (_, misses) = resubmissions
+            .record_status(&update(third, 11, BundleStatus::Missed))
+            .unwrap();
+        assert_eq!(misses, 2);
+    }
+
+    #[test]
+    fn forgets_hints_which_landed_or_aged() {
+        let mut resubmissions = Resubmissions::default();
+        let (landed, aged) = (
+            BackrunHint {
+                tx_hash: H256::repeat_byte(1),
+                ..Default::default()
+            },
+            BackrunHint {
+                tx_hash: H256::repeat_byte(2),
+                ..Default::default()
+            },
+        );
+        let bundles = [H256::repeat_byte(3), H256::repeat_byte(4)];
+        resubmissions.record_hint(&landed);
+        resubmissions.record_submissions(&submitted(landed.tx_hash, bundles[0]));
+        resubmissions.record_submissions(&submitted(landed.tx_hash, bundles[1]));
+        assert!(resubmissions
+            .record_status(&update(bundles[0], 10, BundleStatus::Landed))
+            .is_none());
+        assert!(resubmissions
+            .record_status(&update(bundles[1], 10, BundleStatus::Missed))
+            .is_none());
+
+        resubmissions.record_hint(&aged);
+        resubmissions.record_submissions(&submitted(aged.tx_hash, bundles[0]));
+        resubmissions.on_new_block(U64::from(MAX_HINT_AGE + 1));
+        assert!(resubmissions
+            .record_status(&update(bundles[0], 10, BundleStatus::Missed))
+            .is_none());
+    }
+}
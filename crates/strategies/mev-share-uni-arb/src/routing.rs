@@ -0,0 +1,409 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers::types::H160;
+
+use crate::{
+    constants::WETH_ADDRESS,
+    types::{RouteHop, V2V3PoolRecord},
+};
+
+/// Fewest hops of a cycle, e.g. buying on a v3 pool and selling on a v2 pool.
+const MIN_HOPS: usize = 2;
+
+/// Most hops of a cycle. Searching deeper multiplies the routes of every hint.
+pub const MAX_HOPS: usize = 3;
+
+/// A pool swapping between two tokens, an edge of the [TokenGraph].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolEdge {
+    pub pool: H160,
+    pub token0: H160,
+    pub token1: H160,
+    /// Whether the pool is a uniswap v3 pool (otherwise v2).
+    pub is_v3: bool,
+    /// Swap fee in basis points, only used for v2 pools.
+    pub fee_bps: u32,
+}
+
+impl PoolEdge {
+    /// Returns the pool of `hop`, which swaps between `token0` and `token1`.
+    pub fn from_hop(hop: &RouteHop, token0: H160, token1: H160) -> Self {
+        Self {
+            pool: hop.pool,
+            token0,
+            token1,
+            is_v3: hop.is_v3,
+            fee_bps: hop.fee_bps,
+        }
+    }
+
+    /// Returns the token the pool swaps `token` for.
+    fn other(&self, token: H160) -> H160 {
+        if token == self.token0 {
+            self.token1
+        } else {
+            self.token0
+        }
+    }
+
+    /// Returns the hop swapping `token_in` through the pool.
+    fn hop(&self, token_in: H160) -> RouteHop {
+        RouteHop {
+            pool: self.pool,
+            zero_for_one: token_in == self.token0,
+            is_v3: self.is_v3,
+            fee_bps: self.fee_bps,
+        }
+    }
+}
+
+/// A cycle through the [TokenGraph] which starts and ends in a base token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleRoute {
+    /// Token which is flash loaned and repaid at the end of the route.
+    pub base_token: H160,
+    /// Decimals of the base token, used to scale backrun sizes.
+    pub base_token_decimals: u8,
+    /// The swaps making up the route, in order.
+    pub hops: Vec<RouteHop>,
+    /// Tokens the route goes through, starting and ending with the base token.
+    pub tokens: Vec<H160>,
+}
+
+/// Bounds on the cycles searched for each touched pool. Cycles start and end in a base
+/// token (WETH by default), have at most `max_hops` hops, and only go through the
+/// tokens of the touched pool, base tokens and, if set, whitelisted tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSearch {
+    max_hops: usize,
+    /// Decimals of the tokens cycles can start and end in, by token.
+    base_tokens: HashMap<H160, u8>,
+    /// Tokens cycles can go through, if not every token of the graph.
+    tokens: Option<HashSet<H160>>,
+}
+
+impl Default for RouteSearch {
+    fn default() -> Self {
+        Self::new(MAX_HOPS)
+    }
+}
+
+impl RouteSearch {
+    /// Search cycles of up to `max_hops` hops, between 2 and [MAX_HOPS].
+    pub fn new(max_hops: usize) -> Self {
+        Self {
+            max_hops: max_hops.clamp(MIN_HOPS, MAX_HOPS),
+            base_tokens: HashMap::from([(*WETH_ADDRESS, 18)]),
+            tokens: None,
+        }
+    }
+
+    /// Also start and end cycles in `token`, which has `decimals` decimals.
+    pub fn with_base_token(mut self, token: H160, decimals: u8) -> Self {
+        self.base_tokens.insert(token, decimals);
+        self
+    }
+
+    /// Only go through `tokens`, besides base tokens and the tokens of the touched pool.
+    pub fn with_tokens(mut self, tokens: impl IntoIterator<Item = H160>) -> Self {
+        self.tokens = Some(tokens.into_iter().collect());
+        self
+    }
+
+    pub fn max_hops(&self) -> usize {
+        self.max_hops
+    }
+
+    fn allows(&self, token: &H160) -> bool {
+        if self.base_tokens.contains_key(token) {
+            return true;
+        }
+        match &self.tokens {
+            Some(tokens) => tokens.contains(token),
+            None => true,
+        }
+    }
+}
+
+/// Graph of the tokens the strategy knows pools for, with the pools between them as
+/// edges, searched for cycles through the pools hints touch.
+#[derive(Debug, Clone, Default)]
+pub struct TokenGraph {
+    /// Pools swapping each token, by token.
+    edges: HashMap<H160, Vec<PoolEdge>>,
+    /// Pools in the graph, by address.
+    pools: HashMap<H160, PoolEdge>,
+    /// Pools and directions of the cycles other templates already backrun, in the
+    /// order they are swapped through, starting from the lowest address.
+    covered: HashSet<Vec<(H160, bool)>>,
+}
+
+impl TokenGraph {
+    /// Build the graph of the v3 and v2 pools of `records`, trading tokens against WETH.
+    /// Buying a token on the v2 pool of a record and selling it on its v3 pool is left
+    /// to the [V2V3ArbTemplate](crate::templates::V2V3ArbTemplate).
+    pub fn from_v2_v3_pools(records: &[V2V3PoolRecord]) -> Self {
+        let mut graph = Self::default();
+        for record in records {
+            let (token0, token1) = match record.weth_token0 {
+                true => (*WETH_ADDRESS, record.token_address),
+                false => (record.token_address, *WETH_ADDRESS),
+            };
+            let v3_pool = PoolEdge {
+                pool: record.v3_pool,
+                token0,
+                token1,
+                is_v3: true,
+                fee_bps: 0,
+            };
+            let v2_pool = PoolEdge {
+                pool: record.v2_pool,
+                token0,
+                token1,
+                is_v3: false,
+                fee_bps: record.fee_bps,
+            };
+            graph.cover(&[
+                v2_pool.hop(*WETH_ADDRESS),
+                v3_pool.hop(record.token_address),
+            ]);
+            graph.insert(v3_pool);
+            graph.insert(v2_pool);
+        }
+        graph
+    }
+
+    /// Skip the cycle of `hops`, e.g. because another template already backruns it.
+    pub fn cover(&mut self, hops: &[RouteHop]) {
+        self.covered.insert(rotate_to_lowest(hops));
+    }
+
+    /// Add a pool, unless it is already in the graph.
+    pub fn insert(&mut self, edge: PoolEdge) {
+        if self.pools.contains_key(&edge.pool) {
+            return;
+        }
+        for token in [edge.token0, edge.token1] {
+            self.edges.entry(token).or_default().push(edge.clone());
+        }
+        self.pools.insert(edge.pool, edge);
+    }
+
+    /// Returns true if the pool is in the graph.
+    pub fn contains(&self, pool: &H160) -> bool {
+        self.pools.contains_key(pool)
+    }
+
+    /// Returns the number of pools in the graph.
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Returns true if the graph has no pools.
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Returns the cycles swapping through `pool` in either direction, within the bounds
+    /// of `search`, shortest first. Cycles going through no base token, or which are
+    /// [covered](Self::cover), are skipped.
+    pub fn cycles(&self, pool: &H160, search: &RouteSearch) -> Vec<CycleRoute> {
+        let Some(edge) = self.pools.get(pool) else {
+            return vec![];
+        };
+        let mut cycles = Vec::new();
+        for (token_in, token_out) in [(edge.token0, edge.token1), (edge.token1, edge.token0)] {
+            let mut tokens = vec![token_in, token_out];
+            let mut hops = vec![edge.hop(token_in)];
+            self.close_cycles(search, &mut tokens, &mut hops, &mut cycles);
+        }
+        cycles.retain(|cycle| !self.covered.contains(&rotate_to_lowest(&cycle.hops)));
+        cycles.sort_by_key(|cycle| cycle.hops.len());
+        cycles
+    }
+
+    /// Extend the path of `tokens` and `hops` back to its first token, pushing the
+    /// cycles found to `cycles`.
+    fn close_cycles(
+        &self,
+        search: &RouteSearch,
+        tokens: &mut Vec<H160>,
+        hops: &mut Vec<RouteHop>,
+        cycles: &mut Vec<CycleRoute>,
+    ) {
+        let (start, token) = (tokens[0], tokens[tokens.len() - 1]);
+        for edge in self.edges.get(&token).into_iter().flatten() {
+            if hops.iter().any(|hop| hop.pool == edge.pool) {
+                continue;
+            }
+            let next = edge.other(token);
+            hops.push(edge.hop(token));
+            if next == start {
+                if hops.len() >= MIN_HOPS {
+                    cycles.extend(rotate_to_base(search, tokens, hops));
+                }
+            } else if hops.len() < search.max_hops
+                && !tokens.contains(&next)
+                && search.allows(&next)
+            {
+                tokens.push(next);
+                self.close_cycles(search, tokens, hops, cycles);
+                tokens.pop();
+            }
+            hops.pop();
+        }
+    }
+}
+
+/// Returns the pools and directions of the cycle of `hops` starting from the lowest
+/// pool address, so the same cycle compares equal whichever pool it starts from.
+fn rotate_to_lowest(hops: &[RouteHop]) -> Vec<(H160, bool)> {
+    let start = (0..hops.len()).min_by_key(|i| hops[*i].pool).unwrap_or(0);
+    hops[start..]
+        .iter()
+        .chain(&hops[..start])
+        .map(|hop| (hop.pool, hop.zero_for_one))
+        .collect()
+}
+
+/// Returns the cycle of `tokens` and `hops` starting at its first base token, if any.
+/// `tokens` holds the token going into each hop.
+fn rotate_to_base(search: &RouteSearch, tokens: &[H160], hops: &[RouteHop]) -> Option<CycleRoute> {
+    let (start, decimals) = tokens
+        .iter()
+        .enumerate()
+        .find_map(|(i, token)| Some((i, *search.base_tokens.get(token)?)))?;
+    let mut tokens: Vec<H160> = tokens[start..]
+        .iter()
+        .chain(&tokens[..start])
+        .copied()
+        .collect();
+    tokens.push(tokens[0]);
+    Some(CycleRoute {
+        base_token: tokens[0],
+        base_token_decimals: decimals,
+        hops: hops[start..]
+            .iter()
+            .chain(&hops[..start])
+            .cloned()
+            .collect(),
+        tokens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(pool: u64, token0: H160, token1: H160) -> PoolEdge {
+        PoolEdge {
+            pool: H160::from_low_u64_be(pool),
+            token0,
+            token1,
+            is_v3: false,
+            fee_bps: 30,
+        }
+    }
+
+    fn pools(cycle: &CycleRoute) -> Vec<u64> {
+        cycle
+            .hops
+            .iter()
+            .map(|hop| hop.pool.to_low_u64_be())
+            .collect()
+    }
+
+    #[test]
+    fn finds_cycles_through_the_touched_pool_from_a_base_token() {
+        let weth = *WETH_ADDRESS;
+        let (usdc, token) = (H160::repeat_byte(1), H160::repeat_byte(2));
+        let mut graph = TokenGraph::default();
+        // WETH / TOKEN on two pools, and a USDC / TOKEN / WETH triangle.
+        graph.insert(edge(1, token, weth));
+        graph.insert(edge(2, token, weth));
+        graph.insert(edge(3, usdc, token));
+        graph.insert(edge(4, usdc, weth));
+
+        let cycles = graph.cycles(&H160::from_low_u64_be(3), &RouteSearch::default());
+        assert_eq!(cycles.len(), 4);
+        assert!(cycles.iter().all(|cycle| cycle.base_token == weth));
+        assert!(cycles.contains(&CycleRoute {
+            base_token: weth,
+            base_token_decimals: 18,
+            hops: vec![
+                edge(4, usdc, weth).hop(weth),
+                edge(3, usdc, token).hop(usdc),
+                edge(1, token, weth).hop(token),
+            ],
+            tokens: vec![weth, usdc, token, weth],
+        }));
+
+        let cycles = graph.cycles(&H160::from_low_u64_be(1), &RouteSearch::new(2));
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(pools(&cycles[0]), vec![2, 1]);
+        assert_eq!(pools(&cycles[1]), vec![1, 2]);
+    }
+
+    #[test]
+    fn only_goes_through_whitelisted_tokens() {
+        let weth = *WETH_ADDRESS;
+        let (usdc, token, other) = (
+            H160::repeat_byte(1),
+            H160::repeat_byte(2),
+            H160::repeat_byte(3),
+        );
+        let mut graph = TokenGraph::default();
+        graph.insert(edge(1, token, weth));
+        graph.insert(edge(2, usdc, token));
+        graph.insert(edge(3, usdc, weth));
+        graph.insert(edge(4, other, token));
+        graph.insert(edge(5, other, weth));
+
+        // The tokens of the touched pool are always allowed.
+        let search = RouteSearch::default().with_tokens([usdc]);
+        let cycles = graph.cycles(&H160::from_low_u64_be(1), &search);
+        assert_eq!(cycles.len(), 2);
+        assert!(cycles.iter().all(|cycle| cycle.tokens.contains(&usdc)));
+
+        // Cycles start in a base token, even if the touched pool doesn't trade it.
+        let search = RouteSearch::default().with_base_token(usdc, 6);
+        let cycles = graph.cycles(&H160::from_low_u64_be(4), &search);
+        assert_eq!(cycles.len(), 2);
+        assert!(cycles.iter().all(|cycle| cycle.base_token == weth));
+        assert!(graph
+            .cycles(&H160::from_low_u64_be(6), &RouteSearch::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn skips_cycles_other_templates_backrun() {
+        let weth = *WETH_ADDRESS;
+        let (usdc, token) = (H160::repeat_byte(1), H160::repeat_byte(2));
+        let mut graph = TokenGraph::from_v2_v3_pools(&[V2V3PoolRecord {
+            token_address: token,
+            v3_pool: H160::from_low_u64_be(1),
+            v2_pool: H160::from_low_u64_be(2),
+            weth_token0: false,
+            fee_bps: 30,
+            factory: H160::zero(),
+        }]);
+        // Buying on v2 and selling on v3 is left to the v3 / v2 template.
+        let cycles = graph.cycles(&H160::from_low_u64_be(1), &RouteSearch::default());
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(pools(&cycles[0]), vec![1, 2]);
+
+        // A listed route is skipped whichever pool it starts from, but not the other
+        // way round.
+        graph.insert(edge(3, usdc, token));
+        graph.insert(edge(4, usdc, weth));
+        let v2_pool = graph.pools[&H160::from_low_u64_be(2)].clone();
+        graph.cover(&[
+            edge(3, usdc, token).hop(usdc),
+            v2_pool.hop(token),
+            edge(4, usdc, weth).hop(weth),
+        ]);
+        let cycles = graph.cycles(&H160::from_low_u64_be(3), &RouteSearch::default());
+        assert_eq!(cycles.len(), 3);
+        assert!(!cycles.iter().any(|cycle| pools(cycle) == vec![4, 3, 2]));
+    }
+}
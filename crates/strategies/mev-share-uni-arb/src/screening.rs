@@ -0,0 +1,467 @@
+//! Screening of the tokens backruns swap into. Honeypot tokens tax, block or pause
+//! transfers, so arbs through them either revert or lose the tax, and their pools
+//! keep showing up in hints. Tokens are screened once, by looking for the functions
+//! implementing these features in their bytecode, and by simulating a buy and a sell
+//! through their v2 pool.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use ethers::{
+    prelude::{abigen, Lazy},
+    providers::{spoof, Middleware, RawCall},
+    types::{
+        CallConfig, CallFrame, GethDebugBuiltInTracerConfig, GethDebugBuiltInTracerType,
+        GethDebugTracerConfig, GethDebugTracerType, GethDebugTracingCallOptions,
+        GethDebugTracingOptions, GethTrace, GethTraceFrame, H160, H256, U256,
+    },
+    utils::{id, keccak256},
+};
+use serde::Serialize;
+use tracing::info;
+
+use crate::constants::WETH_ADDRESS;
+
+abigen!(
+    IScreenedToken,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+        function transfer(address to, uint256 amount) external returns (bool)
+    ]"#;
+);
+
+/// Basis points in a whole.
+const BPS: u64 = 10_000;
+
+/// Share of the pool's token balance transferred in simulations, in basis points.
+const SIMULATED_AMOUNT_BPS: u64 = 10;
+
+/// Storage slots searched for the balances mapping, which is declared early in most
+/// token contracts.
+const MAX_BALANCE_SLOT: u64 = 10;
+
+/// Address holding the tokens in simulations, which no real account controls.
+static PROBE_ADDRESS: Lazy<H160> =
+    Lazy::new(|| H160::from_slice(&keccak256("mev-share-uni-arb.screening")[12..]));
+
+/// Topic of the ERC20 `Transfer` event.
+static TRANSFER_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256::from(keccak256("Transfer(address,address,uint256)")));
+
+/// Selectors of functions blacklisting accounts.
+static BLACKLIST_SELECTORS: Lazy<Vec<[u8; 4]>> = Lazy::new(|| {
+    [
+        "isBlacklisted(address)",
+        "isBlackListed(address)",
+        "blacklist(address)",
+        "addBlackList(address)",
+        "addToBlacklist(address)",
+        "setBlacklist(address,bool)",
+        "isBot(address)",
+        "setBots(address[])",
+    ]
+    .iter()
+    .map(id)
+    .collect()
+});
+
+/// Selectors of functions pausing transfers.
+static PAUSABLE_SELECTORS: Lazy<Vec<[u8; 4]>> =
+    Lazy::new(|| ["paused()", "pause()"].iter().map(id).collect());
+
+/// Selectors of functions charging or setting transfer taxes.
+static TAX_SELECTORS: Lazy<Vec<[u8; 4]>> = Lazy::new(|| {
+    [
+        "_taxFee()",
+        "setTaxFeePercent(uint256)",
+        "setFees(uint256,uint256)",
+        "setBuyTax(uint256)",
+        "setSellTax(uint256)",
+        "buyTax()",
+        "sellTax()",
+    ]
+    .iter()
+    .map(id)
+    .collect()
+});
+
+/// A reason not to trade a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRisk {
+    /// A simulated buy and sell through the pool lost this share of the amount, in
+    /// basis points.
+    TransferTax { bps: u32 },
+    /// The simulated buy or sell reverted.
+    TransferReverts,
+    /// The bytecode has functions setting transfer taxes.
+    TaxFunctions,
+    /// The bytecode has functions blacklisting accounts.
+    Blacklist,
+    /// The bytecode has functions pausing transfers.
+    Pausable,
+}
+
+/// A token a backrun swaps into, and the v2 pool it's traded on, which the token is
+/// screened against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct TradedToken {
+    pub token: H160,
+    pub pool: H160,
+}
+
+/// Location of the balances mapping of a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BalanceSlot {
+    /// Solidity hashes the key before the slot.
+    Solidity(u64),
+    /// Vyper hashes the slot before the key.
+    Vyper(u64),
+}
+
+impl BalanceSlot {
+    /// Returns the storage key of the balance of `holder`.
+    fn key(&self, holder: H160) -> H256 {
+        let (first, second) = match self {
+            BalanceSlot::Solidity(slot) => (H256::from(holder), H256::from_low_u64_be(*slot)),
+            BalanceSlot::Vyper(slot) => (H256::from_low_u64_be(*slot), H256::from(holder)),
+        };
+        H256::from(keccak256([first.as_bytes(), second.as_bytes()].concat()))
+    }
+}
+
+/// Screens tokens for transfer taxes, blacklists and pausability, caching the result
+/// per token.
+#[derive(Debug)]
+pub struct TokenScreener<M> {
+    client: Arc<M>,
+    /// Tokens which are traded without screening.
+    allowed: HashSet<H160>,
+    /// Largest round trip transfer tax tolerated, in basis points.
+    max_transfer_tax_bps: u32,
+    /// Risks found for each screened token.
+    screens: Mutex<HashMap<H160, Vec<TokenRisk>>>,
+}
+
+impl<M: Middleware + 'static> TokenScreener<M> {
+    /// Create a screener tolerating no transfer tax. Only WETH is allowed unscreened.
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            allowed: HashSet::from([*WETH_ADDRESS]),
+            max_transfer_tax_bps: 0,
+            screens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allow tokens without screening them, e.g. stablecoins which can blacklist and
+    /// pause but are otherwise safe to trade.
+    pub fn with_allowed_tokens(mut self, tokens: impl IntoIterator<Item = H160>) -> Self {
+        self.allowed.extend(tokens);
+        self
+    }
+
+    /// Tolerate round trip transfer taxes of up to `bps` basis points.
+    pub fn with_max_transfer_tax_bps(mut self, bps: u32) -> Self {
+        self.max_transfer_tax_bps = bps;
+        self
+    }
+
+    /// Returns whether `token` is safe to trade on `pool`. Tokens which can't be
+    /// screened aren't, but are screened again next time.
+    pub async fn is_safe(&self, token: H160, pool: H160) -> bool {
+        if self.allowed.contains(&token) {
+            return true;
+        }
+        match self.screen(token, pool).await {
+            Ok(risks) => risks.iter().all(|risk| match risk {
+                TokenRisk::TransferTax { bps } => *bps <= self.max_transfer_tax_bps,
+                _ => false,
+            }),
+            Err(e) => {
+                info!("Error screening token {:?}: {}", token, e);
+                false
+            }
+        }
+    }
+
+    /// Returns the risks of trading `token`, simulating transfers through `pool` the
+    /// first time the token is screened.
+    pub async fn screen(&self, token: H160, pool: H160) -> Result<Vec<TokenRisk>> {
+        if let Some(risks) = self.screens.lock().unwrap().get(&token) {
+            return Ok(risks.clone());
+        }
+
+        let code = self.client.get_code(token, None).await?;
+        let mut risks = bytecode_risks(&code);
+        // Nodes without the debug namespace can only screen bytecode.
+        match self.simulate_round_trip(token, pool).await {
+            Ok(Some(risk)) => risks.push(risk),
+            Ok(None) => {}
+            Err(e) => info!("Error simulating transfers of token {:?}: {}", token, e),
+        }
+        if !risks.is_empty() {
+            info!("token {:?} failed screening: {:?}", token, risks);
+        }
+        self.screens.lock().unwrap().insert(token, risks.clone());
+        Ok(risks)
+    }
+
+    /// Simulate buying a small amount of `token` from `pool`, and selling it back. The
+    /// transfers are simulated directly, since taxes are charged on transfers from
+    /// and to the pool.
+    async fn simulate_round_trip(&self, token: H160, pool: H160) -> Result<Option<TokenRisk>> {
+        let erc20 = IScreenedToken::new(token, self.client.clone());
+        let amount = erc20.balance_of(pool).call().await? * SIMULATED_AMOUNT_BPS / BPS;
+        if amount.is_zero() {
+            return Ok(None);
+        }
+
+        let Some(bought) = self
+            .simulate_transfer(&erc20, pool, *PROBE_ADDRESS, amount, spoof::state())
+            .await?
+        else {
+            return Ok(Some(TokenRisk::TransferReverts));
+        };
+        // Without the balances mapping the sell can't be simulated, so only tax the buy.
+        let Some(slot) = self.balance_slot(&erc20).await? else {
+            return Ok(transfer_tax(amount, bought));
+        };
+        let mut state = spoof::state();
+        state
+            .account(token)
+            .store(slot.key(*PROBE_ADDRESS), to_word(bought));
+        match self
+            .simulate_transfer(&erc20, *PROBE_ADDRESS, pool, bought, state)
+            .await?
+        {
+            Some(sold) => Ok(transfer_tax(amount, sold)),
+            None => Ok(Some(TokenRisk::TransferReverts)),
+        }
+    }
+
+    /// Trace a transfer of `amount` from `from` to `to`, returning the amount `to`
+    /// received, or `None` if the transfer reverted.
+    async fn simulate_transfer(
+        &self,
+        erc20: &IScreenedToken<M>,
+        from: H160,
+        to: H160,
+        amount: U256,
+        state: spoof::State,
+    ) -> Result<Option<U256>> {
+        let tx = erc20.transfer(to, amount).from(from).tx;
+        let options = GethDebugTracingCallOptions {
+            tracing_options: GethDebugTracingOptions {
+                tracer: Some(GethDebugTracerType::BuiltInTracer(
+                    GethDebugBuiltInTracerType::CallTracer,
+                )),
+                tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+                    GethDebugBuiltInTracerConfig::CallTracer(CallConfig {
+                        only_top_call: Some(false),
+                        with_log: Some(true),
+                    }),
+                )),
+                ..Default::default()
+            },
+            state_overrides: Some(state),
+            block_overrides: None,
+        };
+        match self.client.debug_trace_call(tx, None, options).await? {
+            GethTrace::Known(GethTraceFrame::CallTracer(frame)) => {
+                Ok(received_by(&frame, erc20.address(), to))
+            }
+            _ => Err(anyhow!("unexpected trace format")),
+        }
+    }
+
+    /// Find the balances mapping of the token, by overriding the balance slot of the
+    /// probe address in each candidate location until `balanceOf` returns it.
+    async fn balance_slot(&self, erc20: &IScreenedToken<M>) -> Result<Option<BalanceSlot>> {
+        let marker = U256::from_big_endian(&keccak256("mev-share-uni-arb.balance"));
+        for slot in 0..MAX_BALANCE_SLOT {
+            for slot in [BalanceSlot::Solidity(slot), BalanceSlot::Vyper(slot)] {
+                let mut state = spoof::state();
+                state
+                    .account(erc20.address())
+                    .store(slot.key(*PROBE_ADDRESS), to_word(marker));
+                let balance = erc20
+                    .balance_of(*PROBE_ADDRESS)
+                    .call_raw()
+                    .state(&state)
+                    .await?;
+                if balance == marker {
+                    return Ok(Some(slot));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the risks evident from the selectors pushed by `code`. Push data is
+/// skipped, so selectors only match where the code compares against them.
+pub fn bytecode_risks(code: &[u8]) -> Vec<TokenRisk> {
+    let selectors = pushed_selectors(code);
+    let has_any = |candidates: &[[u8; 4]]| candidates.iter().any(|s| selectors.contains(s));
+
+    let mut risks = Vec::new();
+    if has_any(&TAX_SELECTORS) {
+        risks.push(TokenRisk::TaxFunctions);
+    }
+    if has_any(&BLACKLIST_SELECTORS) {
+        risks.push(TokenRisk::Blacklist);
+    }
+    if has_any(&PAUSABLE_SELECTORS) {
+        risks.push(TokenRisk::Pausable);
+    }
+    risks
+}
+
+/// Returns the 4 byte values pushed by `PUSH4` instructions in `code`.
+fn pushed_selectors(code: &[u8]) -> HashSet<[u8; 4]> {
+    const PUSH1: u8 = 0x60;
+    const PUSH4: u8 = 0x63;
+    const PUSH32: u8 = 0x7f;
+
+    let mut selectors = HashSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        if (PUSH1..=PUSH32).contains(&op) {
+            let size = (op - PUSH1 + 1) as usize;
+            if op == PUSH4 {
+                if let Some(selector) = code.get(pc + 1..pc + 5) {
+                    selectors.insert(selector.try_into().unwrap());
+                }
+            }
+            pc += size;
+        }
+        pc += 1;
+    }
+    selectors
+}
+
+/// Returns the amount of `token` transferred to `recipient` in a traced call, or
+/// `None` if the call reverted. Logs of reverted subcalls are discarded.
+fn received_by(frame: &CallFrame, token: H160, recipient: H160) -> Option<U256> {
+    if frame.error.is_some() {
+        return None;
+    }
+    let mut received = U256::zero();
+    for log in frame.logs.iter().flatten() {
+        let topics = log.topics.as_deref().unwrap_or_default();
+        if log.address == Some(token)
+            && topics.len() == 3
+            && topics[0] == *TRANSFER_TOPIC
+            && topics[2] == H256::from(recipient)
+        {
+            let data = log.data.as_deref().unwrap_or_default();
+            received = received.saturating_add(U256::from_big_endian(&data[..data.len().min(32)]));
+        }
+    }
+    for call in frame.calls.iter().flatten() {
+        if let Some(amount) = received_by(call, token, recipient) {
+            received = received.saturating_add(amount);
+        }
+    }
+    Some(received)
+}
+
+/// Returns `value` as a storage word.
+fn to_word(value: U256) -> H256 {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    H256::from(word)
+}
+
+/// Returns the transfer tax risk of receiving `received` out of `sent`, if any.
+fn transfer_tax(sent: U256, received: U256) -> Option<TokenRisk> {
+    if received >= sent {
+        return None;
+    }
+    let bps = (sent - received) * BPS / sent;
+    Some(TokenRisk::TransferTax { bps: bps.as_u32() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bytes, CallLogFrame};
+
+    fn push4(selector: &str) -> Vec<u8> {
+        [&[0x63][..], &id(selector)].concat()
+    }
+
+    #[test]
+    fn finds_risky_selectors_in_bytecode() {
+        let code = [
+            push4("transfer(address,uint256)"),
+            push4("isBlacklisted(address)"),
+            push4("paused()"),
+        ]
+        .concat();
+        assert_eq!(
+            bytecode_risks(&code),
+            vec![TokenRisk::Blacklist, TokenRisk::Pausable]
+        );
+        assert!(bytecode_risks(&push4("transfer(address,uint256)")).is_empty());
+    }
+
+    #[test]
+    fn ignores_selectors_in_push_data() {
+        // PUSH5 whose data happens to hold a PUSH4 of a tax selector.
+        let code = [&[0x64][..], &push4("_taxFee()")].concat();
+        assert!(bytecode_risks(&code).is_empty());
+    }
+
+    fn transfer_log(token: H160, to: H160, amount: u64) -> CallLogFrame {
+        CallLogFrame {
+            address: Some(token),
+            topics: Some(vec![
+                *TRANSFER_TOPIC,
+                H256::from(H160::zero()),
+                H256::from(to),
+            ]),
+            data: Some(Bytes::from(to_word(U256::from(amount)).as_bytes().to_vec())),
+        }
+    }
+
+    #[test]
+    fn sums_transfers_received_outside_reverted_calls() {
+        let (token, recipient) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+        let reverted = CallFrame {
+            error: Some("execution reverted".to_string()),
+            logs: Some(vec![transfer_log(token, recipient, 50)]),
+            ..Default::default()
+        };
+        let frame = CallFrame {
+            logs: Some(vec![
+                transfer_log(token, recipient, 95),
+                transfer_log(token, H160::from_low_u64_be(3), 5),
+            ]),
+            calls: Some(vec![reverted.clone()]),
+            ..Default::default()
+        };
+        assert_eq!(received_by(&frame, token, recipient), Some(U256::from(95)));
+        assert_eq!(received_by(&reverted, token, recipient), None);
+
+        assert_eq!(
+            transfer_tax(U256::from(100), U256::from(95)),
+            Some(TokenRisk::TransferTax { bps: 500 })
+        );
+        assert_eq!(transfer_tax(U256::from(100), U256::from(100)), None);
+    }
+
+    #[test]
+    fn balance_slot_keys_follow_compiler_layouts() {
+        let holder = H160::from_low_u64_be(7);
+        let solidity = H256::from(keccak256(
+            [H256::from(holder).as_bytes(), H256::zero().as_bytes()].concat(),
+        ));
+        assert_eq!(BalanceSlot::Solidity(0).key(holder), solidity);
+        assert_ne!(BalanceSlot::Vyper(0).key(holder), solidity);
+    }
+}
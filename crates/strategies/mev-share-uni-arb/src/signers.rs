@@ -0,0 +1,202 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    RwLock,
+};
+
+use ethers::{
+    signers::Signer,
+    types::{Address, U256},
+};
+
+use crate::tx_cache::NonceCache;
+
+/// A wallet of a [SignerPool], along with its nonce and latest balance.
+#[derive(Debug)]
+pub struct PoolSigner<S> {
+    signer: S,
+    /// Nonce of the wallet as of the latest block.
+    nonce_cache: NonceCache,
+    /// ETH balance of the wallet, as last reported, if any was.
+    balance: RwLock<Option<U256>>,
+}
+
+impl<S: Signer> PoolSigner<S> {
+    fn new(signer: S) -> Self {
+        Self {
+            signer,
+            nonce_cache: NonceCache::default(),
+            balance: RwLock::default(),
+        }
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    pub fn nonce_cache(&self) -> &NonceCache {
+        &self.nonce_cache
+    }
+
+    /// Returns the ETH balance of the wallet, as last reported.
+    pub fn balance(&self) -> Option<U256> {
+        *self.balance.read().unwrap()
+    }
+}
+
+/// The wallets arb txs are signed by, used in rotation so that consecutive bundles
+/// don't contend for the nonce of a single wallet, and so that no single wallet is
+/// associated with every bundle. Wallets whose ETH balance was last reported below
+/// the minimum are skipped until it is replenished; balances are assumed sufficient
+/// until they are reported.
+#[derive(Debug)]
+pub struct SignerPool<S> {
+    signers: Vec<PoolSigner<S>>,
+    /// Index of the wallet to sign the next bundles with, modulo the pool size.
+    next: AtomicUsize,
+    min_balance: U256,
+}
+
+impl<S: Signer> SignerPool<S> {
+    /// Rotate between `signers`, which can't be empty.
+    pub fn new(signers: Vec<S>) -> Self {
+        assert!(
+            !signers.is_empty(),
+            "a signer pool needs at least one signer"
+        );
+        Self {
+            signers: signers.into_iter().map(PoolSigner::new).collect(),
+            next: AtomicUsize::new(0),
+            min_balance: U256::zero(),
+        }
+    }
+
+    /// Skip wallets holding less than `balance` ETH, since they can't pay for gas.
+    pub fn with_min_balance(mut self, balance: U256) -> Self {
+        self.min_balance = balance;
+        self
+    }
+
+    pub(crate) fn set_min_balance(&mut self, balance: U256) {
+        self.min_balance = balance;
+    }
+
+    /// Returns the minimum ETH balance of the wallets used.
+    pub fn min_balance(&self) -> U256 {
+        self.min_balance
+    }
+
+    /// Returns the wallets of the pool, in rotation order.
+    pub fn signers(&self) -> &[PoolSigner<S>] {
+        &self.signers
+    }
+
+    /// Record the ETH balance of `wallet`. Returns whether it belongs to the pool.
+    pub fn record_balance(&self, wallet: Address, balance: U256) -> bool {
+        match self
+            .signers
+            .iter()
+            .find(|signer| signer.address() == wallet)
+        {
+            Some(signer) => {
+                *signer.balance.write().unwrap() = Some(balance);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns whether `signer` can pay for gas, as of its latest balance.
+    pub fn is_funded(&self, signer: &PoolSigner<S>) -> bool {
+        match signer.balance() {
+            Some(balance) => balance >= self.min_balance,
+            None => true,
+        }
+    }
+
+    /// Returns whether `wallet` can pay for gas, or `None` if it isn't in the pool.
+    pub fn is_funded_wallet(&self, wallet: Address) -> Option<bool> {
+        self.signers
+            .iter()
+            .find(|signer| signer.address() == wallet)
+            .map(|signer| self.is_funded(signer))
+    }
+
+    /// Returns the next funded wallet in rotation, or `None` if every wallet is
+    /// underfunded.
+    pub fn next_signer(&self) -> Option<&PoolSigner<S>> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.signers.len();
+        (0..len)
+            .map(|offset| &self.signers[(start + offset) % len])
+            .find(|signer| self.is_funded(signer))
+    }
+
+    /// Returns the addresses of the wallets which can't pay for gas.
+    pub fn underfunded(&self) -> Vec<Address> {
+        self.signers
+            .iter()
+            .filter(|signer| !self.is_funded(signer))
+            .map(PoolSigner::address)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::LocalWallet;
+
+    fn wallets() -> Vec<LocalWallet> {
+        [
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            "8da4ef21b864d2cc526dbdb2a120bd2874c36c9d0a1fb7f8c63d7f7a8b41de8f",
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        ]
+        .iter()
+        .map(|key| key.parse().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn rotates_between_signers() {
+        let wallets = wallets();
+        let pool = SignerPool::new(wallets.clone());
+        let used: Vec<Address> = (0..4)
+            .map(|_| pool.next_signer().unwrap().address())
+            .collect();
+        assert_eq!(
+            used,
+            vec![
+                wallets[0].address(),
+                wallets[1].address(),
+                wallets[2].address(),
+                wallets[0].address(),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_underfunded_signers() {
+        let wallets = wallets();
+        let pool = SignerPool::new(wallets.clone()).with_min_balance(U256::from(10));
+        assert!(pool.record_balance(wallets[1].address(), U256::from(9)));
+        assert!(!pool.record_balance(Address::zero(), U256::from(9)));
+        assert_eq!(pool.underfunded(), vec![wallets[1].address()]);
+
+        let used: Vec<Address> = (0..3)
+            .map(|_| pool.next_signer().unwrap().address())
+            .collect();
+        assert!(!used.contains(&wallets[1].address()));
+
+        pool.record_balance(wallets[0].address(), U256::from(9));
+        pool.record_balance(wallets[2].address(), U256::from(9));
+        assert!(pool.next_signer().is_none());
+
+        pool.record_balance(wallets[1].address(), U256::from(10));
+        assert_eq!(pool.next_signer().unwrap().address(), wallets[1].address());
+    }
+}
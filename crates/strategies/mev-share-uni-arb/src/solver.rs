@@ -0,0 +1,296 @@
+//! Off-chain solvers for the profit maximizing input of two-pool arbs, used instead of
+//! the fixed size ladder whenever the post-hint state of both pools is known.
+
+use ethers::types::{U256, U512};
+
+use crate::{
+    adapters::{f64_to_u256, u256_to_f64},
+    math::get_amount_out,
+};
+
+/// Fee denominator of uniswap v2 style pools, fees are expressed in basis points.
+const BPS: u64 = 10_000;
+
+/// Fee denominator of uniswap v3 pools, fees are expressed in hundredths of a basis point.
+const V3_FEE_DENOMINATOR: f64 = 1_000_000.0;
+
+/// Number of ternary search iterations, each shrinking the search range by a third.
+const SEARCH_ITERATIONS: usize = 128;
+
+/// State of a pool, as needed to quote swaps off-chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolState {
+    V2 { reserve0: U256, reserve1: U256 },
+    V3(V3PoolState),
+}
+
+/// State of a uniswap v3 pool around its current price.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct V3PoolState {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    /// Liquidity in range at the current price.
+    pub liquidity: u128,
+    /// Fee, in hundredths of a basis point.
+    pub fee: u32,
+    /// Initialized ticks near the current tick and their net liquidity, sorted by tick.
+    /// The liquidity past the last known tick is assumed to extend indefinitely.
+    pub ticks: Vec<(i32, i128)>,
+}
+
+impl V3PoolState {
+    /// Quote the output of an exact input swap, crossing initialized ticks. Selling
+    /// token0 for token1 if `zero_for_one`, and token1 for token0 otherwise.
+    pub fn get_amount_out(&self, amount_in: U256, zero_for_one: bool) -> U256 {
+        let fee_multiplier = 1.0 - self.fee as f64 / V3_FEE_DENOMINATOR;
+        let mut remaining = u256_to_f64(amount_in) * fee_multiplier;
+        let mut sqrt_price = u256_to_f64(self.sqrt_price_x96) / 2f64.powi(96);
+        let mut liquidity = self.liquidity as f64;
+        let mut amount_out = 0.0;
+
+        // Ticks crossed in the swap direction, followed by the end of known liquidity.
+        let boundaries: Vec<Option<&(i32, i128)>> = if zero_for_one {
+            self.ticks
+                .iter()
+                .rev()
+                .filter(|(tick, _)| *tick <= self.tick)
+                .map(Some)
+                .chain([None])
+                .collect()
+        } else {
+            self.ticks
+                .iter()
+                .filter(|(tick, _)| *tick > self.tick)
+                .map(Some)
+                .chain([None])
+                .collect()
+        };
+
+        for boundary in boundaries {
+            if remaining <= 0.0 {
+                break;
+            }
+            if liquidity > 0.0 {
+                // Amount in, net of fees, needed to move the price to the boundary.
+                let target = boundary.map(|(tick, _)| sqrt_price_at_tick(*tick));
+                let to_boundary = match (target, zero_for_one) {
+                    (Some(target), true) => liquidity * (1.0 / target - 1.0 / sqrt_price),
+                    (Some(target), false) => liquidity * (target - sqrt_price),
+                    (None, _) => f64::INFINITY,
+                };
+                let step_in = remaining.min(to_boundary);
+                let next_sqrt_price = if zero_for_one {
+                    1.0 / (1.0 / sqrt_price + step_in / liquidity)
+                } else {
+                    sqrt_price + step_in / liquidity
+                };
+                // Equivalent to L * (sqrt(P) - sqrt(P')) and L * (1 / sqrt(P) - 1 / sqrt(P')),
+                // without cancelling out small steps.
+                amount_out += if zero_for_one {
+                    step_in * sqrt_price * next_sqrt_price
+                } else {
+                    step_in / (sqrt_price * next_sqrt_price)
+                };
+                remaining -= step_in;
+            }
+            match boundary {
+                Some((tick, liquidity_net)) => {
+                    sqrt_price = sqrt_price_at_tick(*tick);
+                    let liquidity_net = *liquidity_net as f64;
+                    liquidity = if zero_for_one {
+                        liquidity - liquidity_net
+                    } else {
+                        liquidity + liquidity_net
+                    }
+                    .max(0.0);
+                }
+                None => break,
+            }
+        }
+        f64_to_u256(amount_out)
+    }
+}
+
+/// Returns the square root of the price at `tick`, as a plain (not Q64.96) number.
+pub fn sqrt_price_at_tick(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+/// Returns the input maximizing the profit of buying on v2 pool `a` and selling back on
+/// v2 pool `b`, in closed form, or `None` if the arb isn't profitable. Reserves are
+/// given in swap order: `a_in` and `b_out` are reserves of the input token.
+pub fn optimal_v2_v2_input(
+    a_in: U256,
+    a_out: U256,
+    a_fee_bps: u32,
+    b_in: U256,
+    b_out: U256,
+    b_fee_bps: u32,
+) -> Option<U256> {
+    // Chaining both pools gives a virtual pool with the same fee as `a`, for which the
+    // optimal input is x = (sqrt(ga * gb * a_in * a_out * b_in * b_out) - a_in * b_in)
+    //   / (ga * (b_in + gb * a_out)), with fee multipliers ga and gb.
+    let bps = U512::from(BPS);
+    let ga = U512::from(BPS.checked_sub(a_fee_bps as u64)?);
+    let gb = U512::from(BPS.checked_sub(b_fee_bps as u64)?);
+    let (a_in, a_out, b_in, b_out) = (
+        U512::from(a_in),
+        U512::from(a_out),
+        U512::from(b_in),
+        U512::from(b_out),
+    );
+
+    let root = (ga * gb * a_in * a_out * b_in * b_out).integer_sqrt();
+    let threshold = bps * a_in * b_in;
+    if root <= threshold {
+        return None;
+    }
+    let denominator = ga * (bps * b_in + gb * a_out);
+    if denominator.is_zero() {
+        return None;
+    }
+    U256::try_from((root - threshold) * bps / denominator)
+        .ok()
+        .filter(|input| !input.is_zero())
+}
+
+/// Returns the input, and the profit, maximizing a backrun which buys on a v2 pool and
+/// sells back on a v3 pool, found by ternary search over tick-aware v3 quotes. The
+/// input is bounded by `max_in`. Returns `None` if no input is profitable.
+pub fn optimal_v2_v3_input(
+    v2_reserve_in: U256,
+    v2_reserve_out: U256,
+    v2_fee_bps: u32,
+    v3: &V3PoolState,
+    v3_zero_for_one: bool,
+    max_in: U256,
+) -> Option<(U256, U256)> {
+    let profit = |amount_in: U256| -> f64 {
+        let bought = get_amount_out(amount_in, v2_reserve_in, v2_reserve_out, v2_fee_bps);
+        let sold = v3.get_amount_out(bought, v3_zero_for_one);
+        u256_to_f64(sold) - u256_to_f64(amount_in)
+    };
+
+    // The profit is concave in the input, so a ternary search finds its maximum.
+    let (mut low, mut high) = (0.0, u256_to_f64(max_in));
+    for _ in 0..SEARCH_ITERATIONS {
+        let third = (high - low) / 3.0;
+        let (left, right) = (low + third, high - third);
+        if profit(f64_to_u256(left)) < profit(f64_to_u256(right)) {
+            low = left;
+        } else {
+            high = right;
+        }
+    }
+
+    let amount_in = f64_to_u256((low + high) / 2.0);
+    let bought = get_amount_out(amount_in, v2_reserve_in, v2_reserve_out, v2_fee_bps);
+    let sold = v3.get_amount_out(bought, v3_zero_for_one);
+    if amount_in.is_zero() || sold <= amount_in {
+        return None;
+    }
+    Some((amount_in, sold - amount_in))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v2_v2_profit(x: U256, a: (U256, U256), b: (U256, U256)) -> U256 {
+        let bought = get_amount_out(x, a.0, a.1, 30);
+        get_amount_out(bought, b.0, b.1, 30).saturating_sub(x)
+    }
+
+    #[test]
+    fn v2_v2_input_maximizes_profit() {
+        // Token is cheaper on pool a than on pool b.
+        let a = (U256::exp10(21), U256::exp10(21) * 2_100);
+        let b = (U256::exp10(21) * 1_900, U256::exp10(21));
+        let x = optimal_v2_v2_input(a.0, a.1, 30, b.0, b.1, 30).unwrap();
+
+        let best = v2_v2_profit(x, a, b);
+        assert!(!best.is_zero());
+        for other in [x * 9 / 10, x * 11 / 10, x / 2, x * 2] {
+            assert!(v2_v2_profit(other, a, b) <= best);
+        }
+    }
+
+    #[test]
+    fn v2_v2_input_is_none_without_spread() {
+        let reserves = (U256::exp10(21), U256::exp10(21) * 2_000);
+        assert!(
+            optimal_v2_v2_input(reserves.0, reserves.1, 30, reserves.1, reserves.0, 30).is_none()
+        );
+    }
+
+    /// A v3 pool with the given price of token0 in token1 and a single liquidity range.
+    fn v3_pool(price: f64, liquidity: u128, ticks: Vec<(i32, i128)>) -> V3PoolState {
+        V3PoolState {
+            sqrt_price_x96: f64_to_u256(price.sqrt() * 2f64.powi(96)),
+            tick: (price.ln() / 1.0001f64.ln()).floor() as i32,
+            liquidity,
+            fee: 3_000,
+            ticks,
+        }
+    }
+
+    #[test]
+    fn v3_quote_within_range_matches_constant_product() {
+        let liquidity = 10u128.pow(22);
+        let pool = v3_pool(2_000.0, liquidity, vec![]);
+        // Virtual reserves of a single range: x = L / sqrt(P), y = L * sqrt(P).
+        let sqrt_price = 2_000f64.sqrt();
+        let reserve0 = f64_to_u256(liquidity as f64 / sqrt_price);
+        let reserve1 = f64_to_u256(liquidity as f64 * sqrt_price);
+
+        let amount_in = U256::exp10(18);
+        let quoted = u256_to_f64(pool.get_amount_out(amount_in, true));
+        let expected = u256_to_f64(get_amount_out(amount_in, reserve0, reserve1, 30));
+        assert!((quoted - expected).abs() / expected < 1e-6);
+    }
+
+    #[test]
+    fn v3_quote_loses_output_when_liquidity_ends() {
+        let liquidity = 10u128.pow(20);
+        let unbounded = v3_pool(2_000.0, liquidity, vec![]);
+        let tick = unbounded.tick;
+        // All liquidity is removed just below the current price.
+        let bounded = v3_pool(2_000.0, liquidity, vec![(tick - 10, liquidity as i128)]);
+
+        let amount_in = U256::exp10(20);
+        assert!(
+            bounded.get_amount_out(amount_in, true) < unbounded.get_amount_out(amount_in, true)
+        );
+        assert_eq!(
+            bounded.get_amount_out(U256::exp10(12), true),
+            unbounded.get_amount_out(U256::exp10(12), true)
+        );
+    }
+
+    #[test]
+    fn v2_v3_input_beats_size_ladder() {
+        // WETH is token0 of both pools. The v2 pool sells the token for 1/1900 WETH
+        // while the v3 pool buys it back for 1/2000 WETH, so buying on v2 pays.
+        let (v2_weth, v2_token) = (U256::exp10(21), U256::exp10(21) * 2_100);
+        let v3 = v3_pool(2_000.0, 10u128.pow(22), vec![]);
+
+        let (size, profit) =
+            optimal_v2_v3_input(v2_weth, v2_token, 30, &v3, false, v2_weth).unwrap();
+        assert!(!profit.is_zero());
+
+        for ladder_size in (5..=21).map(U256::exp10) {
+            let bought = get_amount_out(ladder_size, v2_weth, v2_token, 30);
+            let sold = v3.get_amount_out(bought, false);
+            // Allow for rounding between the f64 search and integer quotes.
+            assert!(sold.saturating_sub(ladder_size) <= profit + profit / 1_000);
+        }
+        assert!(size < v2_weth);
+    }
+
+    #[test]
+    fn v2_v3_input_is_none_when_unprofitable() {
+        let (v2_weth, v2_token) = (U256::exp10(21), U256::exp10(21) * 1_900);
+        let v3 = v3_pool(2_000.0, 10u128.pow(22), vec![]);
+        assert!(optimal_v2_v3_input(v2_weth, v2_token, 30, &v3, false, v2_weth).is_none());
+    }
+}
@@ -1,233 +1,1523 @@
-use std::collections::HashMap;
-use std::ops::Add;
-use std::path::PathBuf;
-use std::str::FromStr;
-use std::sync::Arc;
-
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
+use futures::future::{join_all, try_join_all};
 
-use anyhow::Result;
-use artemis_core::types::Strategy;
+use anyhow::anyhow;
+use artemis_core::collectors::inventory_collector::InventoryUpdate;
+use artemis_core::engine::Concurrency;
+use artemis_core::error::Result;
+use artemis_core::types::{ConcurrentStrategy, Reconfigurable, SubmissionReceipt};
+use artemis_core::utilities::chain_state::ChainState;
+use artemis_core::utilities::decision_journal::{Decision, DecisionJournal, DecisionOutcome};
+use artemis_core::utilities::price_service::{MinProfit, PriceService};
+use artemis_core::utilities::simulation_cache::{SimulationCache, SimulationKey};
 
 use ethers::signers::Signer;
+use matchmaker::status::BundleStatus;
 use matchmaker::types::{BundleRequest, BundleTx};
+use serde::Serialize;
+use tokio::time::timeout;
 
 use ethers::providers::Middleware;
-use ethers::types::{Address, H256};
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, Address, BlockNumber, TransactionRequest, H256, U64,
+};
 use ethers::types::{H160, U256};
-use ethers::{
-    abi::{Token, encode},
-    prelude::abigen,
-    types::Bytes};
-use tracing::info;
-
+use ethers::{prelude::abigen, types::Bytes};
+use mev_share_bindings::balancer_flashloan::BalancerFlashloan;
+use tracing::{info, warn};
 
-use crate::types::V2V3PoolRecord;
+use crate::adapters::{PoolAdapter, VenuePool};
+use crate::approvals::Approval;
+use crate::bidding::{BidPolicy, FixedBid};
+use crate::config::StrategyConfig;
+use crate::constants::{
+    ARB_TX_GAS_LIMIT, DEFAULT_HINT_TIMEOUT, DEFAULT_MAX_CONCURRENT_HINTS, KNOWN_ROUTERS,
+    LAST_KNOWN_MAX_AGE, MAX_CHAIN_HEAD_AGE, WETH_ADDRESS,
+};
+use crate::flashloan::{select_provider, FlashloanProvider};
+use crate::gas::{GasEstimator, Route};
+use crate::last_known::LastKnown;
+use crate::pool_state::PoolStateFetcher;
+use crate::pool_store::{CsvPoolStore, PoolStore, SkippedRow};
+use crate::ranking::{net_profit, top_k};
+use crate::reserves::ReserveTracker;
+use crate::resubmission::Resubmissions;
+use crate::routing::{PoolEdge, RouteSearch, TokenGraph};
+use crate::screening::{TokenScreener, TradedToken};
+use crate::signers::{PoolSigner, SignerPool};
+use crate::templates::{
+    payment_percentage, BackrunCandidate, BackrunHint, BackrunTemplate, MultiHopTemplate, PoolRef,
+    TemplateRegistry, TriangularTemplate, V2V3ArbTemplate, VenueArbTemplate,
+};
+use crate::throttle::PoolThrottle;
+use crate::tx_cache::{CalldataTemplate, TxTemplate};
+use crate::types::{BundleTiming, RouteTable, SubmissionPrivacy, TriangularRoute};
+use crate::univ3_state::UniV3State;
 
 use super::types::{Action, Event};
 
-use mev_share_bindings::blind_arb::BlindArb;
-
 abigen!(
-    Balancer_Flashloan,
-    "bindings/src/blind_arb.json";
+    IERC20,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+    ]"#;
 );
 
 /// Information about a uniswap v2 pool.
 #[derive(Debug, Clone)]
 pub struct V2PoolInfo {
+    /// Token the pool trades against WETH.
+    pub token: H160,
     /// Address of the v2 pool.
     pub v2_pool: H160,
     /// Whether the pool has weth as token0.
     pub is_weth_token0: bool,
+    /// Swap fee charged by the pool, in basis points (30 for uniswap v2).
+    pub fee_bps: u32,
+    /// Factory which deployed the pool, identifying the v2 fork.
+    pub factory: H160,
 }
 
-#[derive(Debug, Clone)]
+/// Name the strategy records its decisions under.
+const STRATEGY_NAME: &str = "mev-share-uni-arb";
+
+/// What the strategy found for a hint, recorded to the decision journal.
+#[derive(Debug, Default, Serialize)]
+struct HintDecision {
+    /// Addresses the hint touched.
+    touched: Vec<H160>,
+    /// Known routers the hint called.
+    routers: Vec<H160>,
+    /// Pools whose state was fetched to size backruns.
+    pools: Vec<PoolRef>,
+    /// Candidates produced by the templates, before screening.
+    candidates: Vec<CandidateRecord>,
+    /// Tokens which failed screening.
+    unsafe_tokens: Vec<H160>,
+    /// Number of bundles generated.
+    bundles: usize,
+    /// Number of target blocks in a row the bundles of the hint missed, if it is
+    /// resubmitted.
+    misses: u32,
+}
+
+/// A backrun candidate, as recorded to the decision journal.
+#[derive(Debug, Serialize)]
+struct CandidateRecord {
+    template: &'static str,
+    loan_token: H160,
+    size: U256,
+    expected_profit: Option<U256>,
+    traded_tokens: Vec<TradedToken>,
+}
+
+impl From<&BackrunCandidate> for CandidateRecord {
+    fn from(candidate: &BackrunCandidate) -> Self {
+        Self {
+            template: candidate.template,
+            loan_token: candidate.loan_token,
+            size: candidate.size,
+            expected_profit: candidate.expected_profit,
+            traded_tokens: candidate.traded_tokens.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct MevShareUniArb<M, S> {
+    /// State read by the tasks generating bundles for each hint, through a shared
+    /// reference to the strategy, so it can only be changed while none are running.
+    context: ArbContext<M, S>,
+    /// Number of hints processed at once, each in a task of its own.
+    max_concurrent_hints: usize,
+    /// Time after which a hint whose bundles aren't generated is abandoned.
+    hint_timeout: Duration,
+    /// ERC-20 approvals the arb contract needs for the registered venues.
+    required_approvals: Vec<Approval>,
+    /// Store the pools to arb are read from when syncing.
+    pool_store: Arc<dyn PoolStore>,
+    /// What the last sync loaded, if the strategy synced.
+    last_sync: Option<SyncReport>,
+    /// Hints whose arbs are resubmitted if their bundles miss their target block.
+    resubmissions: Mutex<Resubmissions>,
+    /// Bounds on the cycles searched through the pool graph, if cycles are searched.
+    route_search: Option<RouteSearch>,
+}
+
+/// What syncing the strategy loaded from its pool store, and what it had to skip.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// v3 / v2 pool pairs loaded.
+    pub v2_v3_pools: usize,
+    /// Triangular routes loaded.
+    pub triangular_routes: usize,
+    /// Rows of the store which couldn't be read, and why.
+    pub skipped_rows: Vec<SkippedRow>,
+    /// v3 / v2 pool pairs listed more than once, loaded only once.
+    pub duplicate_pools: usize,
+    /// v2 pools whose reserves were read, if reserves are tracked.
+    pub synced_reserves: Option<usize>,
+}
+
+/// Everything needed to generate bundles for a hint.
+#[derive(Debug)]
+struct ArbContext<M, S> {
     /// Ethers client.
     client: Arc<M>,
-    /// Maps uni v3 pool address to v2 pool information.
-    pool_map: HashMap<H160, V2PoolInfo>,
-    /// Signer for transactions.
-    tx_signer: S,
+    /// Backrun templates registered while configuring the strategy, tried against
+    /// every hint.
+    templates: TemplateRegistry,
+    /// Backrun templates built from the pool store, replaced whenever the strategy
+    /// syncs.
+    synced_templates: RwLock<Arc<TemplateRegistry>>,
+    /// Balancer / Curve pools of the registered venue arbs, whose balances are re-read
+    /// for the hints touching them.
+    venues: Vec<Arc<dyn PoolAdapter>>,
+    /// Wallets signing arb txs in rotation.
+    signers: SignerPool<S>,
     /// Arb contract.
-    arb_contract: Balancer_Flashloan<M>,
+    arb_contract: BalancerFlashloan<M>,
+    /// Flash loan providers to choose between for each bundle.
+    flashloan_providers: Vec<FlashloanProvider>,
+    /// Policy deciding the percentage of profit paid to the coinbase.
+    bid_policy: Arc<dyn BidPolicy>,
+    /// Parameters changed while the strategy runs, read by every hint.
+    config: RwLock<Arc<StrategyConfig>>,
+    /// Which blocks bundles target, and how long they stay valid.
+    bundle_timing: BundleTiming,
+    /// What bundles share, and which builders they are sent to.
+    privacy: SubmissionPrivacy,
+    /// Fetches the pool state templates need to size backruns.
+    pool_states: PoolStateFetcher<M>,
+    /// Screens the tokens backruns swap into.
+    token_screener: TokenScreener<M>,
+    /// Estimates the gas limit of arb txs per route.
+    gas_estimator: GasEstimator<M>,
+    /// Gas price last read, used if reading it fails.
+    gas_price: LastKnown<U256>,
+    /// Number and timestamp of the latest block last read, used if reading it fails.
+    latest_block: LastKnown<(U64, u64)>,
+    /// Journal the decision about every hint is recorded to, if any.
+    journal: Option<Arc<dyn DecisionJournal>>,
+    /// Balances below which no bundles are generated.
+    min_balances: MinBalances,
+    /// Profit below which candidates are dropped, and the prices converting the profits
+    /// of candidates into its unit.
+    min_profit: Option<(MinProfit, Arc<PriceService<M>>)>,
+    /// Whether arbs simulate successfully at the latest block, by pools, loan token and
+    /// size bucket, if arbs are simulated before they are submitted.
+    simulations: Option<SimulationCache<SimulationKey, bool>>,
+    /// Latest balances of the wallet and the arb contract, if any were reported.
+    inventory: RwLock<Option<InventoryUpdate>>,
+    /// State of the v3 pools whose ticks are tracked from their logs, if any are.
+    v3_state: Option<Arc<UniV3State>>,
+    /// Reserves of the v2 pools tracked from their `Sync` logs, if they are.
+    reserves: Option<Arc<ReserveTracker>>,
+    /// Latest block and fees published by a block collector, read instead of the
+    /// client while they are fresh, if any.
+    chain_state: Option<Arc<ChainState>>,
+    /// Hints claiming the pools their bundles go through, if pools are throttled.
+    pool_throttle: PoolThrottle,
+}
+
+/// The target and calldata of an arb tx, and the route it goes through.
+#[derive(Debug)]
+struct ArbCall {
+    /// Name of the template which produced the arb.
+    template: &'static str,
+    /// Pools the arb swaps through.
+    pools: Vec<H160>,
+    route: Route,
+    size: U256,
+    /// Profit the template expects the arb to make, if it priced it.
+    expected_profit: Option<U256>,
+    /// Percent of the bundle's earnings refunded to the backrun user, if any.
+    refund_percent: Option<u64>,
+    /// Identifies simulations of arbs through the same pools, of a similar size.
+    simulation: SimulationKey,
+    to: H160,
+    calldata: Bytes,
+}
+
+impl ArbCall {
+    /// Build the arb tx from `tx_template`. Arbs swapping native ETH wrap and unwrap it
+    /// within the tx, so it is the whole backrun, as simulated and estimated.
+    fn tx(&self, tx_template: &TxTemplate) -> TypedTransaction {
+        tx_template.build(self.to, self.calldata.clone())
+    }
+}
+
+/// Balances the arb contract needs for the strategy to keep bidding. The balances the
+/// wallets need are kept by the signer pool.
+#[derive(Debug, Clone, Copy, Default)]
+struct MinBalances {
+    /// WETH the arb contract needs to trade without a flash loan.
+    contract_weth: U256,
 }
 
 impl<M: Middleware + 'static, S: Signer> MevShareUniArb<M, S> {
     /// Create a new instance of the strategy.
     pub fn new(client: Arc<M>, signer: S, arb_contract_address: Address) -> Self {
-        Self {
+        let context = ArbContext {
             client: client.clone(),
-            pool_map: HashMap::new(),
-            tx_signer: signer,
-            arb_contract: Balancer_Flashloan::new(arb_contract_address, client),
+            templates: TemplateRegistry::default(),
+            synced_templates: RwLock::default(),
+            venues: vec![],
+            flashloan_providers: vec![FlashloanProvider::Balancer],
+            bid_policy: Arc::new(FixedBid { percentage: 40 }),
+            config: RwLock::default(),
+            bundle_timing: BundleTiming::default(),
+            privacy: SubmissionPrivacy::default(),
+            pool_states: PoolStateFetcher::new(client.clone()),
+            token_screener: TokenScreener::new(client.clone()),
+            gas_estimator: GasEstimator::new(client.clone()),
+            gas_price: LastKnown::new("gas price", LAST_KNOWN_MAX_AGE),
+            latest_block: LastKnown::new("latest block", LAST_KNOWN_MAX_AGE),
+            journal: None,
+            min_balances: MinBalances::default(),
+            min_profit: None,
+            simulations: None,
+            inventory: RwLock::default(),
+            v3_state: None,
+            reserves: None,
+            chain_state: None,
+            pool_throttle: PoolThrottle::default(),
+            signers: SignerPool::new(vec![signer]),
+            arb_contract: BalancerFlashloan::new(arb_contract_address, client),
+        };
+        Self {
+            context,
+            max_concurrent_hints: DEFAULT_MAX_CONCURRENT_HINTS,
+            hint_timeout: DEFAULT_HINT_TIMEOUT,
+            required_approvals: vec![],
+            pool_store: Arc::new(CsvPoolStore::bundled()),
+            last_sync: None,
+            resubmissions: Mutex::default(),
+            route_search: None,
         }
     }
+
+    /// Sign arb txs with `signers` in rotation, skipping wallets too underfunded to pay
+    /// for gas, instead of the signer the strategy was created with.
+    pub fn with_signers(mut self, signers: Vec<S>) -> Self {
+        let min_balance = self.context.signers.min_balance();
+        self.context.signers = SignerPool::new(signers).with_min_balance(min_balance);
+        self
+    }
+
+    /// Set the flash loan providers to choose between. Defaults to Balancer only.
+    pub fn with_flashloan_providers(mut self, providers: Vec<FlashloanProvider>) -> Self {
+        self.context.flashloan_providers = providers;
+        self
+    }
+
+    /// Set the policy deciding the coinbase payment percentage. Defaults to a fixed 40%.
+    pub fn with_bid_policy(mut self, bid_policy: Arc<dyn BidPolicy>) -> Self {
+        self.context.bid_policy = bid_policy;
+        self
+    }
+
+    /// Set the target block offset, validity window and laddering of bundles.
+    pub fn with_bundle_timing(mut self, bundle_timing: BundleTiming) -> Self {
+        self.context.bundle_timing = bundle_timing;
+        self
+    }
+
+    /// Set what bundles share and which builders they are sent to, e.g. to only share
+    /// tx hashes with a trusted set of builders. Defaults to sharing nothing with every
+    /// known builder.
+    pub fn with_submission_privacy(mut self, privacy: SubmissionPrivacy) -> Self {
+        self.context.privacy = privacy;
+        self
+    }
+
+    /// Set how many hints are processed concurrently, as part of the strategy's
+    /// [concurrency](Self::concurrency), and how long each may take before it is
+    /// abandoned. Defaults to 8 hints and 2 seconds.
+    pub fn with_hint_concurrency(
+        mut self,
+        max_concurrent_hints: usize,
+        hint_timeout: Duration,
+    ) -> Self {
+        self.max_concurrent_hints = max_concurrent_hints;
+        self.hint_timeout = hint_timeout;
+        self
+    }
+
+    /// Returns the concurrency to add the strategy to an engine with, see
+    /// [add_concurrent_strategy](artemis_core::engine::Engine::add_concurrent_strategy).
+    pub fn concurrency(&self) -> Concurrency {
+        Concurrency::new(self.max_concurrent_hints)
+    }
+
+    /// Set the screener deciding which tokens backruns may swap into. Defaults to a
+    /// screener tolerating no transfer tax, which only allows WETH unscreened.
+    pub fn with_token_screener(mut self, token_screener: TokenScreener<M>) -> Self {
+        self.context.token_screener = token_screener;
+        self
+    }
+
+    /// Set the estimator of the gas limits of arb txs. Defaults to an estimator
+    /// refreshing estimates every 10 minutes with a 20% safety margin.
+    pub fn with_gas_estimator(mut self, gas_estimator: GasEstimator<M>) -> Self {
+        self.context.gas_estimator = gas_estimator;
+        self
+    }
+
+    /// Record the decision about every hint, including why hints were skipped, to
+    /// `journal`.
+    pub fn with_journal(mut self, journal: Arc<dyn DecisionJournal>) -> Self {
+        self.context.journal = Some(journal);
+        self
+    }
+
+    /// Stop signing with a wallet while its ETH balance is below `balance`, as last
+    /// reported by an [InventoryUpdate] event, since it can't pay for gas. Bidding stops
+    /// while every wallet is below it.
+    pub fn with_min_wallet_balance(mut self, balance: U256) -> Self {
+        self.context.signers.set_min_balance(balance);
+        self
+    }
+
+    /// Stop bidding while the WETH balance of the arb contract is below `balance`, as
+    /// last reported by an [InventoryUpdate] event.
+    pub fn with_min_contract_weth(mut self, balance: U256) -> Self {
+        self.context.min_balances.contract_weth = balance;
+        self
+    }
+
+    /// Drop candidates expected to make less than `min_profit`, converting their profits
+    /// from their loan tokens with `prices`. Candidates without an expected profit, or
+    /// whose loan token can't be priced, are kept.
+    pub fn with_min_profit(mut self, min_profit: MinProfit, prices: Arc<PriceService<M>>) -> Self {
+        self.context.min_profit = Some((min_profit, prices));
+        self
+    }
+
+    /// Simulate arbs at the latest block before submitting them, dropping those which
+    /// revert. Outcomes are reused for `ttl`, until the next block seen in a
+    /// [NewBlock](Event::NewBlock) event, so arbs of similar sizes through the same
+    /// pools, e.g. for several hints on a pool, are only simulated once per block.
+    pub fn with_arb_simulation(mut self, ttl: Duration) -> Self {
+        self.context.simulations = Some(SimulationCache::new(ttl));
+        self
+    }
+
+    /// Read the state of the v3 pools watched by `v3_state` locally instead of fetching
+    /// it for every hint, keeping it in sync from [V3PoolLog](Event::V3PoolLog) events,
+    /// e.g. from a log collector with the [filter](UniV3State::filter) of `v3_state`.
+    pub fn with_v3_state(mut self, v3_state: Arc<UniV3State>) -> Self {
+        let context = &mut self.context;
+        context.pool_states.set_v3_state(v3_state.clone());
+        context.v3_state = Some(v3_state);
+        self
+    }
+
+    /// Read the reserves of the v2 pools of the pool map from `reserves` instead of
+    /// fetching them for every hint, keeping them in sync from
+    /// [V2SyncLog](Event::V2SyncLog) events, e.g. from a log collector with the
+    /// [filter](ReserveTracker::filter) of sync logs. The pools are watched, and their
+    /// reserves read, when the strategy is synced.
+    pub fn with_reserve_tracker(mut self, reserves: Arc<ReserveTracker>) -> Self {
+        let context = &mut self.context;
+        context.pool_states.set_reserves(reserves.clone());
+        context.reserves = Some(reserves);
+        self
+    }
+
+    /// Read the latest block and gas price from `chain_state`, e.g. kept up to date by a
+    /// [BlockCollector](artemis_core::collectors::block_collector::BlockCollector), instead
+    /// of making RPC calls for every hint. The client is still read while the state
+    /// holds no recent enough block.
+    pub fn with_chain_state(mut self, chain_state: Arc<ChainState>) -> Self {
+        self.context.chain_state = Some(chain_state);
+        self
+    }
+
+    /// Returns the bid policy. Whether bundles land is reported to it from
+    /// [BundleStatus](Event::BundleStatus) events.
+    pub fn bid_policy(&self) -> Arc<dyn BidPolicy> {
+        self.context.bid_policy.clone()
+    }
+
+    /// Read the pools to arb from `pool_store` when syncing. Defaults to the csv files
+    /// bundled with the strategy.
+    pub fn with_pool_store(mut self, pool_store: Arc<dyn PoolStore>) -> Self {
+        self.pool_store = pool_store;
+        self
+    }
+
+    /// Also backrun the cycles through touched pools found in the graph of the pools
+    /// loaded from the pool store, within the bounds of `search`, e.g. swapping a token
+    /// between two v2 pools, besides the v3 / v2 pairs and routes listed in the store.
+    pub fn with_route_search(mut self, search: RouteSearch) -> Self {
+        self.route_search = Some(search);
+        self
+    }
+
+    /// Register a backrun template, tried against every hint alongside the templates
+    /// loaded from the pool store.
+    pub fn with_template(mut self, template: Arc<dyn BackrunTemplate>) -> Self {
+        self.context.templates.register(template);
+        self
+    }
+
+    /// Register a Balancer / Curve pool trading `token` against WETH, to be arbed
+    /// against the given v2 pool whenever a hint touches it.
+    pub fn add_venue_pool(
+        &mut self,
+        adapter: Arc<dyn PoolAdapter>,
+        token: H160,
+        v2_info: V2PoolInfo,
+    ) {
+        let venue = VenuePool {
+            adapter: adapter.clone(),
+            token,
+            v2_info,
+        };
+        for approval in Approval::for_venue(&venue) {
+            if !self.required_approvals.contains(&approval) {
+                self.required_approvals.push(approval);
+            }
+        }
+        let arb_contract = self.context.arb_contract.address();
+        let context = &mut self.context;
+        context.venues.push(adapter);
+        context
+            .templates
+            .register(Arc::new(VenueArbTemplate::new(venue, arb_contract)));
+    }
+
+    /// Returns the ERC-20 approvals the arb contract needs for the registered venue
+    /// pools, to be checked with an [ApprovalManager](crate::approvals::ApprovalManager).
+    pub fn required_approvals(&self) -> &[Approval] {
+        &self.required_approvals
+    }
+
+    /// Returns what the last sync loaded, or `None` until the strategy synced.
+    pub fn last_sync(&self) -> Option<&SyncReport> {
+        self.last_sync.as_ref()
+    }
 }
 
 #[async_trait]
-impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action>
+impl<M: Middleware + 'static, S: Signer + 'static> ConcurrentStrategy<Event, Action>
     for MevShareUniArb<M, S>
 {
     /// Initialize the strategy. This is called once at startup, and loads
-    /// pool information into memory.
+    /// pool information from the pool store into memory.
     async fn sync_state(&mut self) -> Result<()> {
-        // Read pool information from csv file.
-        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        path.push("resources/v3_v2_pools.csv");
-        let mut reader = csv::Reader::from_path(path)?;
-
-        for record in reader.deserialize() {
-            // Parse records into PoolRecord struct.
-            let record: V2V3PoolRecord = record?;
-            self.pool_map.insert(
-                record.v3_pool,
-                V2PoolInfo {
+        // Read pool information from the pool store. Malformed rows are skipped and
+        // reported, rather than failing the whole sync.
+        let mut report = SyncReport::default();
+        let pools = self.pool_store.load_v2_v3_pools().await?;
+        report.skipped_rows.extend(pools.skipped);
+        let mut graph = self
+            .route_search
+            .is_some()
+            .then(|| TokenGraph::from_v2_v3_pools(&pools.records));
+        let mut pool_map: HashMap<H160, Vec<V2PoolInfo>> = HashMap::new();
+        let mut seen = HashSet::new();
+        for record in pools.records {
+            if !seen.insert((record.v3_pool, record.v2_pool)) {
+                warn!(
+                    "Skipping duplicate pair of v3 pool {:?} and v2 pool {:?}",
+                    record.v3_pool, record.v2_pool
+                );
+                report.duplicate_pools += 1;
+                continue;
+            }
+            report.v2_v3_pools += 1;
+            pool_map
+                .entry(record.v3_pool)
+                .or_default()
+                .push(V2PoolInfo {
+                    token: record.token_address,
                     v2_pool: record.v2_pool,
                     is_weth_token0: record.weth_token0,
-                },
+                    fee_bps: record.fee_bps,
+                    factory: record.factory,
+                });
+        }
+
+        info!(
+            pools = report.v2_v3_pools,
+            duplicates = report.duplicate_pools,
+            "loaded v3 / v2 pool pairs of {} v3 pools",
+            pool_map.len()
+        );
+
+        let routes = self.pool_store.load_triangular_routes().await?;
+        report.skipped_rows.extend(routes.skipped);
+        report.triangular_routes = routes.records.len();
+        let mut route_table = RouteTable::default();
+        for record in routes.records {
+            let route: TriangularRoute = record.into();
+            if let Some(graph) = &mut graph {
+                self.context.add_route_pools(graph, &route).await;
+            }
+            route_table.insert(route);
+        }
+        info!(
+            routes = report.triangular_routes,
+            "loaded triangular routes over {} pools",
+            route_table.len()
+        );
+        if let Some(reserves) = &self.context.reserves {
+            reserves.watch(pool_map.values().flatten().map(|v2_info| v2_info.v2_pool));
+            let synced = reserves.sync_pools(self.context.client.clone()).await;
+            info!("synced the reserves of {} v2 pools", synced);
+            report.synced_reserves = Some(synced);
+        }
+        if !report.skipped_rows.is_empty() {
+            warn!(
+                "skipped {} malformed rows of the pool store",
+                report.skipped_rows.len()
             );
         }
+        self.last_sync = Some(report);
+        // Resyncing replaces the templates of the last sync, while hints being processed
+        // keep the ones they started with.
+        let mut templates = TemplateRegistry::default();
+        templates.register(Arc::new(V2V3ArbTemplate::new(pool_map)));
+        templates.register(Arc::new(TriangularTemplate::new(route_table)));
+        if let Some((graph, search)) = graph.zip(self.route_search.clone()) {
+            info!(
+                "searching cycles of up to {} hops through {} pools",
+                search.max_hops(),
+                graph.len()
+            );
+            templates.register(Arc::new(MultiHopTemplate::new(graph, search)));
+        }
+        *self.context.synced_templates.write().unwrap() = Arc::new(templates);
 
         Ok(())
     }
 
-    // Process incoming events, seeing if we can arb new orders.
-    async fn process_event(&mut self, event: Event) -> Option<Action> {
+    // Process incoming events, seeing if we can arb new orders. Each hint is processed
+    // in a task of its own, which returns the bundles of that hint.
+    async fn process_event(&self, event: Event) -> Option<Action> {
         match event {
-            Event::MEVShareEvent(event) => {
-                info!("Received mev share event: {:?}", event);
-                // skip if event has no logs
-                if event.logs.is_empty() {
-                    return None;
+            Event::ConfigUpdated(update) => {
+                if let Err(e) = self.apply_config(update.config) {
+                    info!(
+                        "Error reconfiguring strategy, keeping the current config: {}",
+                        e
+                    );
                 }
-                let address = event.logs[0].address;
-                // skip if address is not a v3 pool
-                if !self.pool_map.contains_key(&address) {
+                None
+            }
+            Event::SubmissionReceipt(receipt) => {
+                self.context.record_submissions(&receipt);
+                self.resubmissions
+                    .lock()
+                    .unwrap()
+                    .record_submissions(&receipt);
+                None
+            }
+            Event::InventoryUpdate(update) => {
+                self.context.record_inventory(update);
+                None
+            }
+            Event::NewBlock(block) => {
+                if let Some(simulations) = &self.context.simulations {
+                    simulations.on_new_block(block.number);
+                }
+                if let Some(reserves) = &self.context.reserves {
+                    reserves.observe_block(block.number);
+                }
+                self.resubmissions
+                    .lock()
+                    .unwrap()
+                    .on_new_block(block.number);
+                None
+            }
+            Event::V3PoolLog(log) => {
+                if let Some(v3_state) = &self.context.v3_state {
+                    v3_state.apply_log(&log);
+                }
+                None
+            }
+            Event::V2SyncLog(log) => {
+                if let Some(reserves) = &self.context.reserves {
+                    reserves.apply_log(&log);
+                }
+                None
+            }
+            Event::BundleStatus(update) => {
+                // Whether bundles land tells adaptive bid policies if they bid enough.
+                match update.status {
+                    BundleStatus::Landed => self.context.bid_policy.record_inclusion(true),
+                    BundleStatus::Missed => self.context.bid_policy.record_inclusion(false),
+                    _ => {}
+                }
+                let (hint, misses) = self.resubmissions.lock().unwrap().record_status(&update)?;
+                if !self.context.config.read().unwrap().resubmits(misses) {
                     return None;
                 }
-                // if it's a v3 pool we care about, submit bundles
                 info!(
-                    "Found a v3 pool match at address {:?}, submitting bundles",
-                    address
+                    "Bundle {:?} for {:?} missed block {}, resubmitting with an escalated bid",
+                    update.bundle_hash, hint.tx_hash, update.target_block
                 );
-                let bundles = self.generate_bundles(address, event.hash).await;
-                return Some(Action::SubmitBundles(bundles));
+                self.hint_bundles(&hint, misses).await
             }
+            Event::MEVShareEvent(event) => {
+                info!("Received mev share event: {:?}", event);
+                let hint = BackrunHint::from(&event);
+                if self.context.config.read().unwrap().resubmits(1) {
+                    self.resubmissions.lock().unwrap().record_hint(&hint);
+                }
+                self.hint_bundles(&hint, 0).await
+            }
+            Event::CometLog(_) | Event::PriceUpdate(_) => None,
         }
     }
+
+    /// Config updates and new blocks apply to the hints after them only, and the mints
+    /// and burns logged by v3 pools only apply in order, so these events are processed
+    /// on their own.
+    fn is_sequential(&self, event: &Event) -> bool {
+        matches!(
+            event,
+            Event::ConfigUpdated(_) | Event::NewBlock(_) | Event::V3PoolLog(_)
+        )
+    }
+}
+
+impl<M, S> MevShareUniArb<M, S> {
+    /// Apply new parameters to the hints processed from now on. Hints already being
+    /// processed keep the parameters they started with.
+    fn apply_config(&self, config: StrategyConfig) -> Result<()> {
+        config.validate()?;
+        info!("reconfigured strategy: {:?}", config);
+        *self.context.config.write().unwrap() = Arc::new(config);
+        Ok(())
+    }
+}
+
+impl<M, S> Reconfigurable for MevShareUniArb<M, S> {
+    type Config = StrategyConfig;
+
+    fn reconfigure(&mut self, config: StrategyConfig) -> Result<()> {
+        self.apply_config(config)
+    }
 }
 
 impl<M: Middleware + 'static, S: Signer + 'static> MevShareUniArb<M, S> {
-    /// Generate a series of bundles of varying sizes to submit to the matchmaker.
-    pub async fn generate_bundles(&self, v3_address: H160, tx_hash: H256) -> Vec<BundleRequest> {
+    /// Generate bundles for every candidate backrun the registered templates produce
+    /// for the hint, without a timeout.
+    pub async fn generate_bundles(&self, hint: &BackrunHint) -> Vec<BundleRequest> {
+        self.context.generate_bundles(hint, 0).await
+    }
+
+    /// Returns the bundles of `hint`, unless it takes longer than the hint timeout and
+    /// is abandoned.
+    async fn hint_bundles(&self, hint: &BackrunHint, misses: u32) -> Option<Action> {
+        let generating = self.context.generate_bundles(hint, misses);
+        let bundles = match timeout(self.hint_timeout, generating).await {
+            Ok(bundles) => bundles,
+            Err(_) => {
+                warn!(
+                    "Abandoning hint {:?}, its bundles weren't generated within {:?}",
+                    hint.tx_hash, self.hint_timeout
+                );
+                return None;
+            }
+        };
+        // skip if no template had a backrun for the hint
+        if bundles.is_empty() {
+            return None;
+        }
+        Some(Action::SubmitBundles(bundles))
+    }
+}
+
+impl<M: Middleware + 'static, S: Signer + 'static> ArbContext<M, S> {
+    /// Generate bundles for every candidate backrun the registered templates produce
+    /// for the hint: direct v2 / v3 arbs, Balancer / Curve venue arbs, triangular
+    /// routes, and any custom templates. The decision, and why the hint was skipped
+    /// if it was, is recorded to the journal. Hints whose bundles missed `misses` target
+    /// blocks in a row are bid for with an escalated payment.
+    async fn generate_bundles(&self, hint: &BackrunHint, misses: u32) -> Vec<BundleRequest> {
+        let mut decision = HintDecision {
+            touched: hint.touched.clone(),
+            routers: hint.routers.clone(),
+            misses,
+            ..Default::default()
+        };
+        let result = self.try_generate_bundles(hint, &mut decision).await;
+
+        if let Some(journal) = &self.journal {
+            let outcome = match &result {
+                Ok(_) => DecisionOutcome::Acted,
+                Err(reason) => DecisionOutcome::Skipped {
+                    reason: reason.clone(),
+                },
+            };
+            let record = Decision::new(STRATEGY_NAME, format!("{:?}", hint.tx_hash), outcome)
+                .with_details(&decision);
+            if let Err(e) = journal.record(record) {
+                info!("Error recording decision for {:?}: {}", hint.tx_hash, e);
+            }
+        }
+        result.unwrap_or_default()
+    }
+
+    /// Record the matchmaker's response to each submitted bundle to the journal. The
+    /// first tx of a bundle is the backrun hint, so submissions are recorded under the
+    /// same subject as the decision which generated them.
+    fn record_submissions(&self, receipt: &SubmissionReceipt) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        for submission in &receipt.submissions {
+            let Some(hint_hash) = submission.tx_hashes.first() else {
+                continue;
+            };
+            let outcome = match &submission.error {
+                None => DecisionOutcome::Acted,
+                Some(error) => DecisionOutcome::Skipped {
+                    reason: format!("rejected by {}: {}", submission.destination, error),
+                },
+            };
+            let record = Decision::new(STRATEGY_NAME, format!("{:?}", hint_hash), outcome)
+                .with_details(submission);
+            if let Err(e) = journal.record(record) {
+                info!("Error recording submission for {:?}: {}", hint_hash, e);
+            }
+        }
+    }
+
+    /// Record the latest balances of the wallet and the arb contract, logging when the
+    /// strategy stops or resumes bidding because of them.
+    fn record_inventory(&self, update: InventoryUpdate) {
+        let was_underfunded = self.underfunded().is_some();
+        let was_funded = self.signers.is_funded_wallet(update.wallet);
+        self.signers
+            .record_balance(update.wallet, update.wallet_eth);
+        match (was_funded, self.signers.is_funded_wallet(update.wallet)) {
+            (Some(true), Some(false)) => info!(
+                "Skipping wallet {:?}, it holds {} wei of ETH, below the minimum of {}",
+                update.wallet,
+                update.wallet_eth,
+                self.signers.min_balance()
+            ),
+            (Some(false), Some(true)) => {
+                info!(
+                    "Signing with wallet {:?} again, it is replenished",
+                    update.wallet
+                )
+            }
+            _ => {}
+        }
+        *self.inventory.write().unwrap() = Some(update);
+        match (was_underfunded, self.underfunded()) {
+            (false, Some(reason)) => info!("Stopping bidding: {}", reason),
+            (true, None) => info!("Resuming bidding, balances are replenished"),
+            _ => {}
+        }
+    }
+
+    /// Returns why the wallets or the arb contract are too underfunded to bid, as of the
+    /// latest balances reported. Funding is assumed sufficient until balances are.
+    fn underfunded(&self) -> Option<String> {
+        let underfunded = self.signers.underfunded();
+        if underfunded.len() == self.signers.signers().len() {
+            return Some(format!(
+                "every wallet holds less ETH than the minimum of {} wei",
+                self.signers.min_balance()
+            ));
+        }
+        let inventory = self.inventory.read().unwrap();
+        let inventory = inventory.as_ref()?;
+        let min = self.min_balances;
+        let contract_weth = inventory.contract_balance(*WETH_ADDRESS);
+        if contract_weth < min.contract_weth {
+            return Some(format!(
+                "the arb contract holds {} wei of WETH, below the minimum of {}",
+                contract_weth, min.contract_weth
+            ));
+        }
+        None
+    }
+
+    /// Generate the bundles for a hint, filling in `decision` along the way. Returns
+    /// why no bundles were generated otherwise.
+    async fn try_generate_bundles(
+        &self,
+        hint: &BackrunHint,
+        decision: &mut HintDecision,
+    ) -> Result<Vec<BundleRequest>, String> {
+        if let Some(reason) = self.underfunded() {
+            return Err(reason);
+        }
         let mut bundles = Vec::new();
-        let v2_info = self.pool_map.get(&v3_address).unwrap();
-
-        // The sizes of the backruns we want to submit.
-        // TODO: Run some analysis to figure out likely sizes.
-        let sizes = vec![
-            U256::from(100000_u128),
-            U256::from(1000000_u128),
-            U256::from(10000000_u128),
-            U256::from(100000000_u128),
-            U256::from(1000000000_u128),
-            U256::from(10000000000_u128),
-            U256::from(100000000000_u128),
-            U256::from(1000000000000_u128),
-            U256::from(10000000000000_u128),
-            U256::from(100000000000000_u128),
-            U256::from(1000000000000000_u128),
-            U256::from(10000000000000000_u128),
-            U256::from(100000000000000000_u128),
-            U256::from(1000000000000000000_u128),
-        ];
+        let config = self.config.read().unwrap().clone();
+        let mut hint = hint.clone();
+        hint.touched.retain(|address| config.is_enabled(address));
+        if hint.touched.is_empty() {
+            // Hints calling routers without sharing logs don't reveal their pools.
+            let routers: Vec<&str> = hint
+                .routers
+                .iter()
+                .filter_map(|router| KNOWN_ROUTERS.get(router).copied())
+                .collect();
+            if !routers.is_empty() {
+                return Err(format!(
+                    "the hint swaps through {} without sharing which pools",
+                    routers.join(", ")
+                ));
+            }
+            return Err("the hint touched no enabled pool".to_string());
+        }
+
+        // Fetch the pool state templates need to solve for the optimal size, as of the
+        // latest block, which backruns build on.
+        self.refresh_venue_balances(&hint).await;
+        let templates = self.templates();
+        let pools = templates.pools(&hint);
+        let mut blocks = None;
+        if !pools.is_empty() {
+            let (latest_block, target_blocks) = self.blocks().await?;
+            hint.pool_states = self.pool_states.fetch(&pools, &hint, latest_block).await;
+            blocks = Some((latest_block, target_blocks));
+        }
+        decision.pools = pools;
+        let hint = &hint;
+
+        let bid_policy =
+            config.escalated_bid_policy(config.bid_policy(&self.bid_policy), decision.misses);
+        let mut candidates = templates.candidates(hint, bid_policy.as_ref());
+        if candidates.is_empty() {
+            return Err("no template matched the hint".to_string());
+        }
+        decision.candidates = candidates.iter().map(CandidateRecord::from).collect();
+        candidates.retain(|candidate| config.allows_size(candidate.size));
+        if candidates.is_empty() {
+            return Err("every candidate size is outside the configured bounds".to_string());
+        }
+        let candidates = self.profitable_candidates(candidates).await;
+        if candidates.is_empty() {
+            return Err(
+                "every candidate is expected to make less than the minimum profit".to_string(),
+            );
+        }
+        let candidates = self.screen_candidates(candidates, decision).await;
+        if candidates.is_empty() {
+            return Err("every candidate trades a token which failed screening".to_string());
+        }
 
         // Set parameters for the backruns.
-        let payment_percentage = U256::from(40);
-        let bid_gas_price = self.client.get_gas_price().await.unwrap();
-        let block_num = self.client.get_block_number().await.unwrap();
-    
-        for size in sizes {
-            let arb_tx = {
-                // Construct arb tx based on whether the v2 pool has weth as token0.
-                let mut inner = match v2_info.is_weth_token0 {
-                    true => {
-
-                        let userdata_token = Token::Tuple(vec![
-                            Token::Bool(true),
-                            Token::Address(v2_info.v2_pool),
-                            Token::Address(v3_address),
-                            Token::Uint(size),
-                            Token::Uint(payment_percentage), 
-                        ]);
-
-                        let user_data = Bytes::from(encode(&[userdata_token]));
-                        let amounts = vec![size];
-                        let tokens = vec![Address::from_str("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").unwrap()];
-                          self.arb_contract.make_flash_loan(
-                            tokens, 
-                            amounts, 
-                            user_data,
-                            )                      
-                            .tx
-                    }
-                    false => {
-                        
-                        let userdata_token = Token::Tuple(vec![
-                            Token::Bool(false),
-                            Token::Address(v2_info.v2_pool),
-                            Token::Address(v3_address),
-                            Token::Uint(size),
-                            Token::Uint(payment_percentage), 
-                        ]);
-
-                        let user_data = Bytes::from(encode(&[userdata_token]));
-                        let amounts = vec![size];
-                        let tokens = vec![Address::from_str("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").unwrap()];
-                          self.arb_contract.make_flash_loan(
-                            tokens, 
-                            amounts, 
-                            user_data,
-                            )                      
-                            .tx
-                    }
-                };
-                // Set gas parameters (this is a bit hacky)
-                inner.set_gas(400000);
-                inner.set_gas_price(bid_gas_price);
-                let fill = self.client.fill_transaction(&mut inner, None).await;
-
-                match fill {
-                    Ok(_) => {}
+        let (latest_block, target_blocks) = match blocks {
+            Some(blocks) => blocks,
+            None => self.blocks().await?,
+        };
+
+        // Only one hint through a pool has bundles in flight per cooldown, if pools are
+        // throttled. Pools are only claimed once the arbs through them are bundled.
+        let candidates = match config.pool_cooldown_blocks {
+            Some(cooldown) => {
+                let candidates: Vec<BackrunCandidate> = candidates
+                    .into_iter()
+                    .filter(|candidate| {
+                        self.pool_throttle.is_claimable(
+                            &candidate.pools,
+                            hint.tx_hash,
+                            latest_block,
+                            cooldown,
+                        )
+                    })
+                    .collect();
+                if candidates.is_empty() {
+                    return Err(
+                        "every candidate goes through a pool claimed by another hint".to_string(),
+                    );
+                }
+                candidates
+            }
+            None => candidates,
+        };
+        info!(
+            "found {} backrun candidates for {:?}, submitting bundles",
+            candidates.len(),
+            hint.tx_hash
+        );
+        let Some(signer) = self.signers.next_signer() else {
+            return Err("every wallet is too underfunded to pay for gas".to_string());
+        };
+        let tx_template = match self.tx_template(signer, latest_block).await {
+            Ok(tx_template) => tx_template,
+            Err(e) => {
+                info!("Error getting tx parameters: {}", e);
+                return Err(format!("error getting tx parameters: {}", e));
+            }
+        };
+
+        // Look up flash loan liquidity once per loan token.
+        let mut liquidity: HashMap<H160, Vec<(FlashloanProvider, U256)>> = HashMap::new();
+        for candidate in candidates.iter() {
+            if let Entry::Vacant(entry) = liquidity.entry(candidate.loan_token) {
+                entry.insert(self.flashloan_liquidity(candidate.loan_token).await);
+            }
+        }
+
+        // Candidates which only differ by size share their calldata template.
+        let mut calldata_templates = HashMap::new();
+        let arbs: Vec<ArbCall> = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let pools = candidate.pools.clone();
+                let simulation =
+                    SimulationKey::new(candidate.pools, candidate.loan_token, candidate.size);
+                let expected_profit = candidate.expected_profit;
+                let template = candidate.template;
+                self.arb_call(
+                    candidate.loan_token,
+                    candidate.size,
+                    candidate.user_data,
+                    &liquidity[&candidate.loan_token],
+                    &mut calldata_templates,
+                    simulation,
+                )
+                .map(|arb| ArbCall {
+                    template,
+                    pools,
+                    expected_profit,
+                    ..arb
+                })
+            })
+            .collect();
+        let arbs = self.simulate_arbs(arbs, &tx_template, latest_block).await;
+        if arbs.is_empty() {
+            return Err("every arb reverted in simulation".to_string());
+        }
+
+        // Estimate the gas of the routes without a fresh estimate, in one batch.
+        let stale: Vec<(Route, TypedTransaction)> = arbs
+            .iter()
+            .filter(|arb| self.gas_estimator.is_stale(&arb.route))
+            .map(|arb| (arb.route.clone(), arb.tx(&tx_template)))
+            .collect();
+        if !stale.is_empty() {
+            self.gas_estimator.refresh(stale, latest_block).await;
+        }
+
+        // Only submit the arbs expected to net the most, if their number is capped.
+        let arbs = match config.max_arbs_per_hint {
+            Some(max) => top_k(arbs, max, |arb| {
+                let profit = arb.expected_profit?;
+                let payment_percentage =
+                    payment_percentage(bid_policy.as_ref(), arb.size, Some(profit));
+                // Gas is paid in ETH, so it only nets out of profits made in WETH.
+                let gas_cost = (arb.route.1 == *WETH_ADDRESS)
+                    .then(|| self.gas_estimator.gas_limit(&arb.route) * tx_template.gas_price);
+                Some(net_profit(profit, payment_percentage, gas_cost))
+            }),
+            None => arbs,
+        };
+
+        for mut arb in arbs {
+            // The coinbase payment is only comparable with the fee the hint pays, in
+            // wei, for profits made in WETH.
+            let payment = arb
+                .expected_profit
+                .filter(|_| arb.route.1 == *WETH_ADDRESS)
+                .map(|profit| {
+                    let payment_percentage =
+                        payment_percentage(bid_policy.as_ref(), arb.size, Some(profit));
+                    profit * payment_percentage.min(U256::from(100)) / 100
+                });
+            arb.refund_percent = config.refund_percent(hint.mev_fee, payment);
+            let privacy = config.privacy(arb.template, &self.privacy);
+            let pools = arb.pools.clone();
+            let arb_bundles = match self
+                .build_bundles(
+                    signer,
+                    arb,
+                    &tx_template,
+                    &target_blocks,
+                    hint.tx_hash,
+                    privacy,
+                )
+                .await
+            {
+                Ok(arb_bundles) => arb_bundles,
+                Err(e) => {
+                    info!("Error signing arb tx: {}", e);
+                    return Err(format!("error signing arb tx: {}", e));
+                }
+            };
+            // Another hint may have claimed the pools since they were checked.
+            let claimed = match config.pool_cooldown_blocks {
+                Some(cooldown) => {
+                    self.pool_throttle
+                        .try_claim(&pools, hint.tx_hash, latest_block, cooldown)
+                }
+                None => true,
+            };
+            if claimed {
+                bundles.extend(arb_bundles);
+            }
+        }
+        decision.bundles = bundles.len();
+        if bundles.is_empty() {
+            if config.pool_cooldown_blocks.is_some() {
+                return Err(
+                    "no flash loan provider can lend any candidate size, or its pools were \
+                     claimed by another hint"
+                        .to_string(),
+                );
+            }
+            return Err("no flash loan provider can lend any candidate size".to_string());
+        }
+        Ok(bundles)
+    }
+
+    /// Drop arbs which revert when simulated at `block`, if arbs are simulated.
+    async fn simulate_arbs(
+        &self,
+        arbs: Vec<ArbCall>,
+        tx_template: &TxTemplate,
+        block: U64,
+    ) -> Vec<ArbCall> {
+        let Some(simulations) = &self.simulations else {
+            return arbs;
+        };
+        let outcomes = join_all(arbs.iter().map(|arb| {
+            let tx = arb.tx(tx_template);
+            let simulate = async move {
+                let block = Some(BlockNumber::Number(block).into());
+                match self.client.call(&tx, block).await {
+                    Ok(_) => true,
                     Err(e) => {
-                        println!("Error filling tx: {}", e);
-                        continue;
+                        info!("arb reverted in simulation: {}", e);
+                        false
                     }
                 }
+            };
+            simulations.get_or_simulate(arb.simulation.clone(), block, simulate)
+        }))
+        .await;
+        arbs.into_iter()
+            .zip(outcomes)
+            .filter_map(|(arb, succeeds)| succeeds.then_some(arb))
+            .collect()
+    }
 
-                inner
+    /// Drop candidates expected to make less than the minimum profit, if any is set.
+    async fn profitable_candidates(
+        &self,
+        candidates: Vec<BackrunCandidate>,
+    ) -> Vec<BackrunCandidate> {
+        let Some((min_profit, prices)) = &self.min_profit else {
+            return candidates;
+        };
+        let checks = join_all(candidates.iter().map(|candidate| async move {
+            let Some(profit) = candidate.expected_profit else {
+                return true;
             };
-            info!("generated arb tx: {:?}", arb_tx);
-
-            // Sign tx and construct bundle
-            let signature = self.tx_signer.sign_transaction(&arb_tx).await.unwrap();
-            let bytes = arb_tx.rlp_signed(&signature);
-            let txs = vec![
-                BundleTx::TxHash { hash: tx_hash },
-                BundleTx::Tx {
-                    tx: bytes,
-                    can_revert: false,
-                },
-            ];
+            match prices
+                .meets(*min_profit, candidate.loan_token, profit)
+                .await
+            {
+                Ok(meets) => meets,
+                Err(e) => {
+                    info!("Error pricing the profit of a candidate: {}", e);
+                    true
+                }
+            }
+        }))
+        .await;
+        candidates
+            .into_iter()
+            .zip(checks)
+            .filter_map(|(candidate, meets)| meets.then_some(candidate))
+            .collect()
+    }
 
-            // bundle should be valid for next block
-            let bundle = BundleRequest::make_simple(block_num.add(1), txs);
-            info!("submitting bundle: {:?}", bundle);
-            bundles.push(bundle);
+    /// Drop candidates swapping into tokens which fail screening, recording the tokens
+    /// in `decision`.
+    async fn screen_candidates(
+        &self,
+        candidates: Vec<BackrunCandidate>,
+        decision: &mut HintDecision,
+    ) -> Vec<BackrunCandidate> {
+        let mut traded_tokens = HashMap::new();
+        for traded in candidates.iter().flat_map(|c| c.traded_tokens.iter()) {
+            traded_tokens.entry(traded.token).or_insert(traded.pool);
+        }
+        let screens = join_all(traded_tokens.into_iter().map(|(token, pool)| async move {
+            (token, self.token_screener.is_safe(token, pool).await)
+        }))
+        .await;
+        let unsafe_tokens: HashSet<H160> = screens
+            .into_iter()
+            .filter(|(_, is_safe)| !is_safe)
+            .map(|(token, _)| token)
+            .collect();
+        decision.unsafe_tokens = unsafe_tokens.iter().copied().collect();
+
+        candidates
+            .into_iter()
+            .filter(|candidate| {
+                candidate
+                    .traded_tokens
+                    .iter()
+                    .all(|traded| !unsafe_tokens.contains(&traded.token))
+            })
+            .collect()
+    }
+
+    /// Returns the liquidity of `token` available at each configured flash loan provider.
+    async fn flashloan_liquidity(&self, token: H160) -> Vec<(FlashloanProvider, U256)> {
+        // With a single provider there's nothing to choose between, so skip the lookup.
+        if let [provider] = self.flashloan_providers.as_slice() {
+            return vec![(provider.clone(), U256::MAX)];
+        }
+
+        let erc20 = IERC20::new(token, self.client.clone());
+        let mut liquidity = vec![];
+        for provider in self.flashloan_providers.iter() {
+            if !provider.can_lend(token) {
+                continue;
+            }
+            match erc20.balance_of(provider.liquidity_holder()).call().await {
+                Ok(balance) => liquidity.push((provider.clone(), balance)),
+                Err(e) => info!(
+                    "Error getting flash loan liquidity for {:?}: {}",
+                    provider, e
+                ),
+            }
         }
-        bundles
+        liquidity
+    }
+
+    /// Returns the number and timestamp of the latest block.
+    async fn latest_block(&self) -> anyhow::Result<(U64, u64)> {
+        let block = self
+            .client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow!("latest block not found"))?;
+        let number = block
+            .number
+            .ok_or_else(|| anyhow!("latest block has no number"))?;
+        Ok((number, block.timestamp.as_u64()))
+    }
+
+    /// Returns the latest block, and the blocks bundles for the current event should target.
+    /// The latest block is taken from the chain state while it is fresh, and otherwise
+    /// read, with the last block read standing in for it if it can't be.
+    async fn target_blocks(&self) -> anyhow::Result<(U64, Vec<U64>)> {
+        let head = self
+            .chain_state
+            .as_ref()
+            .and_then(|chain_state| chain_state.fresh_head(MAX_CHAIN_HEAD_AGE));
+        let (number, timestamp) = match head {
+            Some(head) => (head.number, head.timestamp),
+            None => self.latest_block.or_last_known(self.latest_block().await)?,
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let since_block = Duration::from_secs(now.saturating_sub(timestamp));
+        Ok((
+            number,
+            self.bundle_timing.target_blocks(number, since_block),
+        ))
+    }
+
+    /// [target_blocks](Self::target_blocks), with its error logged and described as a
+    /// reason for not generating bundles.
+    async fn blocks(&self) -> Result<(U64, Vec<U64>), String> {
+        self.target_blocks().await.map_err(|e| {
+            info!("Error getting latest block: {}", e);
+            format!("error getting latest block: {}", e)
+        })
+    }
+
+    /// Add the pools of `route` to `graph`, reading the tokens of those it doesn't have,
+    /// and leave the route to the triangular template. Pools whose tokens can't be read
+    /// are left out.
+    async fn add_route_pools(&self, graph: &mut TokenGraph, route: &TriangularRoute) {
+        graph.cover(&route.hops);
+        let hops = route.hops.iter().filter(|hop| !graph.contains(&hop.pool));
+        let tokens = join_all(
+            hops.map(|hop| async move { (hop, self.pool_states.fetch_tokens(hop.pool).await) }),
+        )
+        .await;
+        for (hop, tokens) in tokens {
+            match tokens {
+                Ok((token0, token1)) => graph.insert(PoolEdge::from_hop(hop, token0, token1)),
+                Err(e) => warn!("Error reading the tokens of pool {:?}: {}", hop.pool, e),
+            }
+        }
+    }
+
+    /// Returns the templates registered while configuring the strategy, followed by
+    /// those of the last sync.
+    fn templates(&self) -> TemplateRegistry {
+        let mut templates = self.templates.clone();
+        templates.extend(&self.synced_templates.read().unwrap());
+        templates
+    }
+
+    /// Re-read the balances of the venue pools `hint` touches, so they are quoted as of
+    /// the latest block. Pools whose balances can't be read keep their previous ones.
+    async fn refresh_venue_balances(&self, hint: &BackrunHint) {
+        let touched = self
+            .venues
+            .iter()
+            .filter(|venue| hint.touched.contains(&venue.address()));
+        for venue in touched {
+            let calls = venue.balance_calls().into_iter().map(|(to, data)| {
+                let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+                async move { self.client.call(&tx, None).await }
+            });
+            let outputs = match try_join_all(calls).await {
+                Ok(outputs) => outputs,
+                Err(e) => {
+                    warn!("Error reading the balances of {:?}: {}", venue.address(), e);
+                    continue;
+                }
+            };
+            if venue.update_balances(&outputs).is_none() {
+                warn!("Error decoding the balances of {:?}", venue.address());
+            }
+        }
+    }
+
+    /// Encode the tx target and calldata of a flash loan of `size` from `provider`.
+    fn encode_flash_loan(
+        &self,
+        provider: &FlashloanProvider,
+        loan_token: H160,
+        size: U256,
+        user_data: &Bytes,
+    ) -> (H160, Bytes) {
+        let arb_contract = self.arb_contract.address();
+        match provider.encode(arb_contract, loan_token, size, user_data.clone()) {
+            Some(encoded) => encoded,
+            None => {
+                let tx = self
+                    .arb_contract
+                    .make_flash_loan(vec![loan_token], vec![size], user_data.clone())
+                    .tx;
+                (arb_contract, tx.data().cloned().unwrap_or_default())
+            }
+        }
+    }
+
+    /// Returns the gas and nonce parameters shared by every arb tx `signer` signs for the
+    /// current event. The nonce of each wallet is only fetched once per block. The gas
+    /// price is taken from the chain state while it is fresh, and otherwise read, with
+    /// the last gas price read standing in for it if it can't be.
+    async fn tx_template(
+        &self,
+        signer: &PoolSigner<S>,
+        latest_block: U64,
+    ) -> anyhow::Result<TxTemplate> {
+        let from = signer.address();
+        let gas_price = match self.chain_state.as_ref().and_then(|chain_state| {
+            chain_state.fresh_head(MAX_CHAIN_HEAD_AGE)?;
+            chain_state.gas_price()
+        }) {
+            Some(gas_price) => gas_price,
+            None => self
+                .gas_price
+                .or_last_known(self.client.get_gas_price().await)?,
+        };
+        let nonce = match signer.nonce_cache().get(latest_block) {
+            Some(nonce) => nonce,
+            None => {
+                let nonce = self
+                    .client
+                    .get_transaction_count(from, Some(BlockNumber::Number(latest_block).into()))
+                    .await?;
+                signer.nonce_cache().set(latest_block, nonce);
+                nonce
+            }
+        };
+        Ok(TxTemplate {
+            from,
+            nonce,
+            chain_id: signer.signer().chain_id(),
+            gas: U256::from(ARB_TX_GAS_LIMIT),
+            gas_price,
+        })
+    }
+
+    /// Encode a flash loan arb for `loan_token` and `size`, using the cheapest provider
+    /// with enough liquidity. The calldata is patched from a template cached per route,
+    /// so no RPC calls are made. Returns `None` if no provider can lend `size`.
+    fn arb_call(
+        &self,
+        loan_token: H160,
+        size: U256,
+        user_data: Bytes,
+        liquidity: &[(FlashloanProvider, U256)],
+        calldata_templates: &mut HashMap<Route, CalldataTemplate>,
+        simulation: SimulationKey,
+    ) -> Option<ArbCall> {
+        let provider = select_provider(liquidity, loan_token, size)?;
+        let route = (provider.clone(), loan_token, user_data);
+        let calldata_template = calldata_templates.entry(route.clone()).or_insert_with(|| {
+            CalldataTemplate::new(|size| {
+                self.encode_flash_loan(provider, loan_token, size, &route.2)
+            })
+        });
+        let (to, calldata) = calldata_template.with_size(size);
+        Some(ArbCall {
+            template: "",
+            pools: vec![],
+            route,
+            size,
+            expected_profit: None,
+            refund_percent: None,
+            simulation,
+            to,
+            calldata,
+        })
+    }
+
+    /// Build the tx of `arb`, with the gas limit estimated for its route, sign it with
+    /// `signer`, and wrap it in a bundle backrunning `tx_hash` for each target block,
+    /// submitted with `privacy` and refunding the user as the arb asks. Fails if the tx
+    /// can't be signed.
+    async fn build_bundles(
+        &self,
+        signer: &PoolSigner<S>,
+        arb: ArbCall,
+        tx_template: &TxTemplate,
+        target_blocks: &[U64],
+        tx_hash: H256,
+        privacy: &SubmissionPrivacy,
+    ) -> anyhow::Result<Vec<BundleRequest>> {
+        let arb_template = TxTemplate {
+            gas: self.gas_estimator.gas_limit(&arb.route),
+            ..tx_template.clone()
+        };
+        let tx = arb.tx(&arb_template);
+        info!("generated arb tx: {:?}", tx);
+
+        // Sign tx and construct bundle
+        let signature = signer
+            .signer()
+            .sign_transaction(&tx)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        let txs = vec![
+            BundleTx::TxHash { hash: tx_hash },
+            BundleTx::Tx {
+                tx: tx.rlp_signed(&signature),
+                can_revert: false,
+            },
+        ];
+
+        // one bundle per target block, laddered ones only valid for their own block
+        let validity = self.bundle_timing.validity(target_blocks);
+        Ok(target_blocks
+            .iter()
+            .map(|block| {
+                let mut bundle = privacy.apply(BundleRequest::make_with_validity(
+                    *block,
+                    validity,
+                    txs.clone(),
+                ));
+                // The hinted tx is the first of the bundle.
+                if let Some(percent) = arb.refund_percent {
+                    bundle = bundle.with_refund(0, percent);
+                }
+                info!("submitting bundle: {:?}", bundle);
+                bundle
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use artemis_core::utilities::chain_state::ChainHead;
+    use ethers::{providers::Provider, signers::LocalWallet};
+    use matchmaker::types::{Hint, HintLog};
+
+    use super::*;
+
+    /// Template backrunning every hint with a flash loan of 1 WETH.
+    #[derive(Debug)]
+    struct BackrunEveryHint;
+
+    impl BackrunTemplate for BackrunEveryHint {
+        fn name(&self) -> &'static str {
+            "backrun-every-hint"
+        }
+
+        fn candidates(
+            &self,
+            _hint: &BackrunHint,
+            _bid_policy: &dyn BidPolicy,
+        ) -> Vec<BackrunCandidate> {
+            vec![BackrunCandidate {
+                template: self.name(),
+                loan_token: *WETH_ADDRESS,
+                size: U256::exp10(18),
+                user_data: Bytes::default(),
+                pools: vec![],
+                expected_profit: None,
+                traded_tokens: vec![],
+            }]
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_the_bundles_of_a_hint_for_that_hint() {
+        let (provider, mock) = Provider::mocked();
+        // The mock answers requests last pushed first: the nonce of the wallet, then the
+        // gas estimate of the arb.
+        mock.push(U256::from(300_000)).unwrap();
+        mock.push(U256::from(7)).unwrap();
+        let chain_state = Arc::new(ChainState::new());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        chain_state.update_head(ChainHead {
+            number: U64::from(100),
+            timestamp: now,
+            base_fee: Some(U256::from(10)),
+        });
+        chain_state.update_priority_fee(U256::from(1));
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let strategy = MevShareUniArb::new(Arc::new(provider), wallet, Address::from_low_u64_be(1))
+            .with_chain_state(chain_state)
+            .with_template(Arc::new(BackrunEveryHint));
+        let hint = Hint {
+            hash: H256::repeat_byte(1),
+            txs: vec![],
+            logs: vec![HintLog {
+                address: H160::from_low_u64_be(2),
+                ..Default::default()
+            }],
+            gas_used: None,
+            mev_gas_price: None,
+        };
+
+        // No later event is needed for the bundles of the hint to come back.
+        let Some(Action::SubmitBundles(bundles)) =
+            strategy.process_event(Event::MEVShareEvent(hint)).await
+        else {
+            panic!("expected the bundles of the hint");
+        };
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].inclusion.block, U64::from(101));
+        assert!(matches!(
+            bundles[0].body[0],
+            BundleTx::TxHash { hash } if hash == H256::repeat_byte(1)
+        ));
     }
 }
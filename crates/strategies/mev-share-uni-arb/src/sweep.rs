@@ -0,0 +1,308 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use artemis_core::{
+    collectors::inventory_collector::InventoryUpdate,
+    error::{ArtemisError, Result},
+    executors::mev_share_executor::Bundles,
+    types::{Executor, Strategy, SubmissionReceipt},
+};
+use async_trait::async_trait;
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{Address, BlockNumber, U256, U64},
+};
+use matchmaker::types::{BundleRequest, BundleTx, PrivacyHint};
+use mev_share_bindings::blind_arb::BlindArb;
+use tracing::info;
+
+use crate::constants::WETH_ADDRESS;
+use crate::tx_cache::TxTemplate;
+use crate::types::{Action, Event};
+
+/// Minimum time between two sweeps by default.
+const DEFAULT_SWEEP_COOLDOWN: Duration = Duration::from_secs(600);
+
+/// Gas limit of sweep txs.
+const SWEEP_TX_GAS_LIMIT: u64 = 100_000;
+
+/// Number of blocks sweep bundles stay valid for.
+const SWEEP_VALIDITY_BLOCKS: u64 = 5;
+
+/// A request to withdraw the WETH held by the arb contract to its owner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepRequest {
+    pub contract: Address,
+    /// WETH balance of the contract when the sweep was requested.
+    pub amount: U256,
+    /// Block the balance was read at.
+    pub block: U64,
+}
+
+/// A strategy sweeping the profits of the arb contract: whenever an
+/// [InventoryUpdate] reports the contract holds more WETH than `threshold`, a
+/// [SweepRequest] is emitted, at most once per cooldown.
+#[derive(Debug)]
+pub struct AutoSweep {
+    threshold: U256,
+    cooldown: Duration,
+    last_sweep: Option<Instant>,
+}
+
+impl AutoSweep {
+    /// Sweep the arb contract whenever it holds more than `threshold` WETH.
+    pub fn new(threshold: U256) -> Self {
+        Self {
+            threshold,
+            cooldown: DEFAULT_SWEEP_COOLDOWN,
+            last_sweep: None,
+        }
+    }
+
+    /// Wait at least `cooldown` between two sweeps, so a sweep which hasn't landed yet
+    /// isn't requested again. Defaults to 10 minutes.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the sweep to request given the latest balances, if any.
+    fn sweep(&mut self, update: &InventoryUpdate, now: Instant) -> Option<SweepRequest> {
+        let amount = update.contract_balance(*WETH_ADDRESS);
+        if amount <= self.threshold {
+            return None;
+        }
+        if let Some(last_sweep) = self.last_sweep {
+            if now.duration_since(last_sweep) < self.cooldown {
+                return None;
+            }
+        }
+        self.last_sweep = Some(now);
+        info!("sweeping {} wei of WETH from {:?}", amount, update.contract);
+        Some(SweepRequest {
+            contract: update.contract,
+            amount,
+            block: update.block,
+        })
+    }
+}
+
+#[async_trait]
+impl Strategy<Event, Action> for AutoSweep {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: Event) -> Option<Action> {
+        match event {
+            Event::InventoryUpdate(update) => {
+                self.sweep(&update, Instant::now()).map(Action::Sweep)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An executor sending [SweepRequest]s privately: it signs a `withdrawWETHToOwner`
+/// call to the arb contract, and submits it as a bundle through a wrapped bundle
+/// executor, e.g. to MEV-share, so it never reaches the public mempool. Sweeps are
+/// dropped while the gas price is above the cap.
+pub struct SweepExecutor<M, S, E> {
+    client: Arc<M>,
+    tx_signer: S,
+    executor: E,
+    max_gas_price: Option<U256>,
+}
+
+impl<M, S, E> SweepExecutor<M, S, E> {
+    /// Sign sweep txs with `tx_signer`, which must own the arb contract, and submit
+    /// them with `executor`.
+    pub fn new(client: Arc<M>, tx_signer: S, executor: E) -> Self {
+        Self {
+            client,
+            tx_signer,
+            executor,
+            max_gas_price: None,
+        }
+    }
+
+    /// Drop sweeps while the gas price is above `max_gas_price`, in wei.
+    pub fn with_max_gas_price(mut self, max_gas_price: U256) -> Self {
+        self.max_gas_price = Some(max_gas_price);
+        self
+    }
+}
+
+#[async_trait]
+impl<M, S, E> Executor<SweepRequest> for SweepExecutor<M, S, E>
+where
+    M: Middleware + 'static,
+    S: Signer + 'static,
+    E: Executor<Bundles>,
+{
+    /// Build, sign and submit the sweep tx, unless gas is too expensive.
+    async fn execute(&self, action: SweepRequest) -> Result<SubmissionReceipt> {
+        let gas_price = self
+            .client
+            .get_gas_price()
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        if let Some(max_gas_price) = self.max_gas_price {
+            if gas_price > max_gas_price {
+                info!(
+                    "skipping sweep, gas price {} is above the cap of {}",
+                    gas_price, max_gas_price
+                );
+                return Ok(SubmissionReceipt::default());
+            }
+        }
+
+        let from = self.tx_signer.address();
+        let latest_block = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        let nonce = self
+            .client
+            .get_transaction_count(from, Some(BlockNumber::Number(latest_block).into()))
+            .await
+            .map_err(ArtemisError::from_middleware)?;
+        let tx_template = TxTemplate {
+            from,
+            nonce,
+            chain_id: self.tx_signer.chain_id(),
+            gas: U256::from(SWEEP_TX_GAS_LIMIT),
+            gas_price,
+        };
+        let call = BlindArb::new(action.contract, self.client.clone()).withdraw_weth_to_owner();
+        let tx = tx_template.build(action.contract, call.tx.data().cloned().unwrap_or_default());
+        let signature = self
+            .tx_signer
+            .sign_transaction(&tx)
+            .await
+            .map_err(|e| anyhow!("Error signing sweep tx: {}", e))?;
+
+        let bundle = BundleRequest::make_with_validity(
+            latest_block + 1,
+            SWEEP_VALIDITY_BLOCKS,
+            vec![BundleTx::Tx {
+                tx: tx.rlp_signed(&signature),
+                can_revert: false,
+            }],
+        )
+        .with_hints(PrivacyHint::default());
+        self.executor.execute(vec![bundle]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use artemis_core::types::Submission;
+    use ethers::{
+        providers::Provider, signers::LocalWallet, types::transaction::eip2718::TypedTransaction,
+        utils::rlp::Rlp,
+    };
+    use std::{collections::HashMap, sync::Mutex};
+
+    fn inventory(weth: u64) -> InventoryUpdate {
+        InventoryUpdate {
+            block: U64::from(100),
+            contract: Address::from_low_u64_be(1),
+            contract_tokens: HashMap::from([(*WETH_ADDRESS, U256::from(weth))]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sweeps_above_threshold_once_per_cooldown() {
+        let mut sweep = AutoSweep::new(U256::from(10)).with_cooldown(Duration::from_secs(60));
+        let now = Instant::now();
+        assert_eq!(sweep.sweep(&inventory(10), now), None);
+
+        let request = sweep.sweep(&inventory(11), now).unwrap();
+        assert_eq!(request.amount, U256::from(11));
+        assert_eq!(request.contract, Address::from_low_u64_be(1));
+
+        assert_eq!(
+            sweep.sweep(&inventory(20), now + Duration::from_secs(30)),
+            None
+        );
+        assert!(sweep
+            .sweep(&inventory(20), now + Duration::from_secs(60))
+            .is_some());
+    }
+
+    /// Bundle executor recording the bundles it's asked to submit.
+    #[derive(Clone, Default)]
+    struct RecordingExecutor {
+        bundles: Arc<Mutex<Vec<BundleRequest>>>,
+    }
+
+    #[async_trait]
+    impl Executor<Bundles> for RecordingExecutor {
+        async fn execute(&self, action: Bundles) -> Result<SubmissionReceipt> {
+            self.bundles.lock().unwrap().extend(action);
+            Ok(SubmissionReceipt::new(vec![Submission::new(
+                "relay",
+                vec![],
+            )]))
+        }
+    }
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    fn request() -> SweepRequest {
+        SweepRequest {
+            contract: Address::from_low_u64_be(1),
+            amount: U256::from(11),
+            block: U64::from(100),
+        }
+    }
+
+    #[tokio::test]
+    async fn submits_signed_withdrawal_bundle() {
+        let (provider, mock) = Provider::mocked();
+        // The mock answers requests last pushed first.
+        mock.push(U256::from(7)).unwrap();
+        mock.push(U64::from(100)).unwrap();
+        mock.push(U256::from(1)).unwrap();
+        let relay = RecordingExecutor::default();
+        let executor = SweepExecutor::new(Arc::new(provider), wallet(), relay.clone())
+            .with_max_gas_price(U256::from(1));
+
+        let receipt = executor.execute(request()).await.unwrap();
+        assert!(!receipt.is_rejected());
+        let bundles = relay.bundles.lock().unwrap();
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].inclusion.block, U64::from(101));
+        let BundleTx::Tx { tx, .. } = &bundles[0].body[0] else {
+            panic!("expected a signed tx");
+        };
+        let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(tx)).unwrap();
+        assert_eq!(tx.to_addr(), Some(&Address::from_low_u64_be(1)));
+        assert_eq!(tx.nonce(), Some(&U256::from(7)));
+        assert_eq!(tx.data().unwrap()[..4], [0xea, 0x3e, 0x50, 0x6c]);
+    }
+
+    #[tokio::test]
+    async fn drops_sweeps_above_gas_price_cap() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(2)).unwrap();
+        let relay = RecordingExecutor::default();
+        let executor = SweepExecutor::new(Arc::new(provider), wallet(), relay.clone())
+            .with_max_gas_price(U256::from(1));
+
+        assert!(executor.execute(request()).await.unwrap().is_empty());
+        assert!(relay.bundles.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,374 @@
+//! Backrun templates turn a decoded MEV-Share hint into candidate backruns. The
+//! strategy asks every template in its [TemplateRegistry] for candidates, and
+//! builds a flash loan bundle for each of them.
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use artemis_core::utilities::router_classifier::RouterClassifier;
+use ethers::{
+    prelude::Lazy,
+    types::{Bytes, H160, H256, U256},
+    utils::keccak256,
+};
+use matchmaker::types::{Hint, HintLog};
+use serde::Serialize;
+
+use crate::{
+    bidding::{BidContext, BidPolicy},
+    constants::KNOWN_ROUTERS,
+    screening::TradedToken,
+    solver::PoolState,
+};
+
+/// Template backrunning v3 pools against v2 pools trading the same pair.
+pub mod v2_v3;
+
+/// Template buying on a Balancer / Curve pool and selling back into a v2 pool.
+pub mod venue;
+
+/// Template backrunning triangular routes through the touched pool.
+pub mod triangular;
+
+/// Template backrunning the cycles through the touched pool found in the pool graph.
+pub mod multi_hop;
+
+pub use multi_hop::MultiHopTemplate;
+pub use triangular::TriangularTemplate;
+pub use v2_v3::V2V3ArbTemplate;
+pub use venue::VenueArbTemplate;
+
+/// Topic of the uniswap v3 pool `Swap` event.
+pub(crate) static V3_SWAP_TOPIC: Lazy<H256> = Lazy::new(|| {
+    H256::from(keccak256(
+        "Swap(address,address,int256,int256,uint160,uint128,int24)",
+    ))
+});
+
+/// Classifier inferring the pools swapped through from the calldata of router calls.
+static ROUTER_CLASSIFIER: Lazy<RouterClassifier> = Lazy::new(RouterClassifier::default);
+
+/// State of a uniswap v3 pool after the hinted swap, decoded from its `Swap` log when
+/// the hint shares log data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct V3SwapState {
+    pub sqrt_price_x96: U256,
+    pub liquidity: u128,
+    pub tick: i32,
+}
+
+impl V3SwapState {
+    fn from_log(log: &HintLog) -> Option<Self> {
+        if log.topics.first() != Some(&*V3_SWAP_TOPIC) || log.data.len() < 160 {
+            return None;
+        }
+        // Data is (int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128
+        // liquidity, int24 tick), each padded to a word.
+        let word = |i: usize| &log.data[i * 32..(i + 1) * 32];
+        Some(Self {
+            sqrt_price_x96: U256::from_big_endian(word(2)),
+            liquidity: u128::from_be_bytes(word(3)[16..].try_into().ok()?),
+            tick: i32::from_be_bytes(word(4)[28..].try_into().ok()?),
+        })
+    }
+}
+
+/// A pool whose current state a template needs to size its backruns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum PoolRef {
+    V2(H160),
+    V3(H160),
+}
+
+/// The parts of a MEV-Share hint templates match against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackrunHint {
+    /// Hash of the hinted transaction or bundle.
+    pub tx_hash: H256,
+    /// Addresses which emitted logs, or were called directly, deduplicated and in
+    /// the order they appear in the hint. Hints sharing only the `to` address of their
+    /// transactions still touch the pools they call directly.
+    pub touched: Vec<H160>,
+    /// [Known routers](KNOWN_ROUTERS) the hinted transactions call, listed here instead
+    /// of in `touched`. The pools they swap through are only touched if the hint shares
+    /// their logs, or calldata from which the pools can be inferred.
+    pub routers: Vec<H160>,
+    /// Function selectors of the hinted transactions, if shared.
+    pub selectors: Vec<[u8; 4]>,
+    /// Post-swap state of the v3 pools the hint swapped through, if log data is shared.
+    pub v3_swaps: HashMap<H160, V3SwapState>,
+    /// State of the pools templates asked for with [pools](BackrunTemplate::pools),
+    /// fetched by the strategy. Pools in `v3_swaps` carry their post-swap state.
+    pub pool_states: HashMap<H160, PoolState>,
+    /// Fee the hinted transactions pay the matchmaker in wei, their `mevGasPrice` times
+    /// their `gasUsed`, if both are shared.
+    pub mev_fee: Option<U256>,
+}
+
+impl From<&Hint> for BackrunHint {
+    fn from(hint: &Hint) -> Self {
+        let (mut touched, mut routers) = (Vec::new(), Vec::new());
+        let addresses = hint
+            .logs
+            .iter()
+            .map(|log| log.address)
+            .chain(hint.txs.iter().filter_map(|tx| tx.to));
+        for address in addresses {
+            let addresses = match KNOWN_ROUTERS.contains_key(&address) {
+                true => &mut routers,
+                false => &mut touched,
+            };
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+        let inferred = ROUTER_CLASSIFIER
+            .classify(hint)
+            .into_iter()
+            .flat_map(|swap| swap.pools);
+        for pool in inferred {
+            if !touched.contains(&pool) {
+                touched.push(pool);
+            }
+        }
+        let selectors = hint
+            .txs
+            .iter()
+            .filter_map(|tx| tx.function_selector.as_ref().map(|selector| selector.0))
+            .collect();
+        // The last swap through a pool leaves it in its post-hint state.
+        let v3_swaps = hint
+            .logs
+            .iter()
+            .filter_map(|log| Some((log.address, V3SwapState::from_log(log)?)))
+            .collect();
+        Self {
+            tx_hash: hint.hash,
+            touched,
+            routers,
+            selectors,
+            v3_swaps,
+            pool_states: HashMap::new(),
+            mev_fee: hint
+                .mev_gas_price
+                .zip(hint.gas_used)
+                .map(|(gas_price, gas_used)| gas_price.saturating_mul(gas_used)),
+        }
+    }
+}
+
+/// A backrun a template wants submitted: a flash loan of `size` units of
+/// `loan_token`, passing `user_data` to the arb contract callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackrunCandidate {
+    /// Name of the template which produced the candidate.
+    pub template: &'static str,
+    pub loan_token: H160,
+    pub size: U256,
+    pub user_data: Bytes,
+    /// Pools the backrun swaps through, in order.
+    pub pools: Vec<H160>,
+    /// Profit the template expects, if it priced the backrun off-chain.
+    pub expected_profit: Option<U256>,
+    /// Tokens the backrun swaps into, which are screened before it's submitted.
+    pub traded_tokens: Vec<TradedToken>,
+}
+
+/// A strategy for backrunning hints. Implementations should be cheap to call, since
+/// every template is asked about every hint.
+pub trait BackrunTemplate: Debug + Send + Sync {
+    /// Name of the template, used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Returns the pools whose state the template needs in
+    /// [pool_states](BackrunHint::pool_states) to size its backruns for the hint.
+    /// Templates which don't price their backruns can use the default.
+    fn pools(&self, _hint: &BackrunHint) -> Vec<PoolRef> {
+        vec![]
+    }
+
+    /// Returns the backruns to submit for the hint, with the coinbase payment for each
+    /// size decided by `bid_policy`. Returns nothing if the hint isn't relevant.
+    fn candidates(&self, hint: &BackrunHint, bid_policy: &dyn BidPolicy) -> Vec<BackrunCandidate>;
+}
+
+/// The set of templates tried against each hint.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: Vec<Arc<dyn BackrunTemplate>>,
+}
+
+impl TemplateRegistry {
+    pub fn register(&mut self, template: Arc<dyn BackrunTemplate>) {
+        self.templates.push(template);
+    }
+
+    /// Register every template of `other`, after the templates already registered.
+    pub fn extend(&mut self, other: &TemplateRegistry) {
+        self.templates.extend(other.templates.iter().cloned());
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Returns the pools every template needs the state of, deduplicated.
+    pub fn pools(&self, hint: &BackrunHint) -> Vec<PoolRef> {
+        let mut pools = Vec::new();
+        for pool in self
+            .templates
+            .iter()
+            .flat_map(|template| template.pools(hint))
+        {
+            if !pools.contains(&pool) {
+                pools.push(pool);
+            }
+        }
+        pools
+    }
+
+    /// Returns the candidates of every template, in registration order.
+    pub fn candidates(
+        &self,
+        hint: &BackrunHint,
+        bid_policy: &dyn BidPolicy,
+    ) -> Vec<BackrunCandidate> {
+        self.templates
+            .iter()
+            .flat_map(|template| template.candidates(hint, bid_policy))
+            .collect()
+    }
+}
+
+/// Returns the coinbase payment percentage for a backrun of the given size, bidding on
+/// its expected profit if it was computed off-chain.
+pub(crate) fn payment_percentage(
+    bid_policy: &dyn BidPolicy,
+    size: U256,
+    expected_profit: Option<U256>,
+) -> U256 {
+    let ctx = BidContext {
+        size,
+        expected_profit,
+    };
+    U256::from(bid_policy.payment_percentage(&ctx))
+}
+
+/// The WETH sizes of the backruns we want to submit, from 1e5 to 1e18 wei.
+// TODO: Run some analysis to figure out likely sizes.
+pub(crate) fn weth_sizes() -> Vec<U256> {
+    (5..=18).map(U256::exp10).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bidding::FixedBid;
+    use matchmaker::types::{FunctionSelector, HintTransaction};
+
+    #[derive(Debug)]
+    struct EchoTemplate;
+
+    impl BackrunTemplate for EchoTemplate {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn candidates(
+            &self,
+            hint: &BackrunHint,
+            bid_policy: &dyn BidPolicy,
+        ) -> Vec<BackrunCandidate> {
+            hint.touched
+                .iter()
+                .map(|pool| BackrunCandidate {
+                    template: self.name(),
+                    loan_token: *pool,
+                    size: payment_percentage(bid_policy, U256::one(), None),
+                    user_data: Bytes::default(),
+                    pools: vec![*pool],
+                    expected_profit: None,
+                    traded_tokens: vec![],
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn registry_collects_candidates_from_every_template() {
+        let mut registry = TemplateRegistry::default();
+        registry.register(Arc::new(EchoTemplate));
+        registry.register(Arc::new(EchoTemplate));
+
+        let hint = BackrunHint {
+            touched: vec![H160::from_low_u64_be(1)],
+            ..Default::default()
+        };
+        let candidates = registry.candidates(&hint, &FixedBid { percentage: 40 });
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].size, U256::from(40));
+    }
+
+    #[test]
+    fn decodes_v3_swap_state_from_log_data() {
+        let mut data = vec![0u8; 160];
+        data[64 + 31] = 7; // sqrtPriceX96
+        data[96 + 31] = 9; // liquidity
+        data[128..].copy_from_slice(&[0xff; 32]); // tick -1
+        let log = HintLog {
+            address: H160::from_low_u64_be(1),
+            topics: vec![*V3_SWAP_TOPIC],
+            data: data.into(),
+        };
+        let state = V3SwapState::from_log(&log).unwrap();
+        assert_eq!(state.sqrt_price_x96, U256::from(7));
+        assert_eq!(state.liquidity, 9);
+        assert_eq!(state.tick, -1);
+
+        let log = HintLog {
+            data: Bytes::default(),
+            ..log
+        };
+        assert!(V3SwapState::from_log(&log).is_none());
+    }
+
+    #[test]
+    fn matches_hints_sharing_only_the_called_address() {
+        let (pool, router) = (
+            H160::from_low_u64_be(1),
+            "0xE592427A0AEce92De3Edee1F18E0157C05861564"
+                .parse()
+                .unwrap(),
+        );
+        let tx = |to, selector| HintTransaction {
+            to: Some(to),
+            function_selector: Some(FunctionSelector(selector)),
+            calldata: None,
+        };
+        let hint = Hint {
+            hash: H256::repeat_byte(1),
+            txs: vec![
+                tx(pool, [0x02, 0x2c, 0x0d, 0x9f]),
+                tx(router, [0x41, 0x4b, 0xf3, 0x89]),
+            ],
+            logs: vec![],
+            gas_used: None,
+            mev_gas_price: None,
+        };
+        let hint = BackrunHint::from(&hint);
+        assert_eq!(hint.touched, vec![pool]);
+        assert_eq!(hint.routers, vec![router]);
+        assert_eq!(hint.selectors.len(), 2);
+    }
+
+    #[test]
+    fn weth_sizes_span_1e5_to_1e18() {
+        let sizes = weth_sizes();
+        assert_eq!(sizes.len(), 14);
+        assert_eq!(sizes[0], U256::from(100000_u128));
+        assert_eq!(sizes[13], U256::exp10(18));
+    }
+}
@@ -0,0 +1,77 @@
+use super::{
+    payment_percentage,
+    triangular::{encode_route_user_data, triangular_sizes},
+    BackrunCandidate, BackrunHint, BackrunTemplate,
+};
+use crate::{
+    bidding::BidPolicy,
+    constants::MAX_ROUTES_PER_EVENT,
+    routing::{CycleRoute, RouteSearch, TokenGraph},
+    screening::TradedToken,
+};
+
+/// Backruns the cycles of 2 to 3 hops through a touched pool, searched in the graph
+/// of the pools loaded from the pool store, rather than only the listed pairs and
+/// routes. Cycles the v3 / v2 and triangular templates already backrun are skipped.
+/// Shorter cycles are backrun first, capped so a single hint can't flood the
+/// matchmaker.
+#[derive(Debug, Clone, Default)]
+pub struct MultiHopTemplate {
+    graph: TokenGraph,
+    search: RouteSearch,
+}
+
+impl MultiHopTemplate {
+    pub fn new(graph: TokenGraph, search: RouteSearch) -> Self {
+        Self { graph, search }
+    }
+}
+
+impl BackrunTemplate for MultiHopTemplate {
+    fn name(&self) -> &'static str {
+        "multi-hop"
+    }
+
+    fn candidates(&self, hint: &BackrunHint, bid_policy: &dyn BidPolicy) -> Vec<BackrunCandidate> {
+        let mut routes: Vec<CycleRoute> = Vec::new();
+        for pool in &hint.touched {
+            for route in self.graph.cycles(pool, &self.search) {
+                if !routes.contains(&route) {
+                    routes.push(route);
+                }
+            }
+        }
+
+        routes
+            .into_iter()
+            .take(MAX_ROUTES_PER_EVENT)
+            .flat_map(|route| {
+                // Every token but the base token is bought on the pool of the hop into it.
+                let traded_tokens: Vec<TradedToken> = route.tokens[1..route.hops.len()]
+                    .iter()
+                    .zip(&route.hops)
+                    .map(|(token, hop)| TradedToken {
+                        token: *token,
+                        pool: hop.pool,
+                    })
+                    .collect();
+                triangular_sizes(route.base_token_decimals)
+                    .into_iter()
+                    .map(move |size| BackrunCandidate {
+                        template: self.name(),
+                        loan_token: route.base_token,
+                        size,
+                        user_data: encode_route_user_data(
+                            route.base_token,
+                            &route.hops,
+                            size,
+                            payment_percentage(bid_policy, size, None),
+                        ),
+                        pools: route.hops.iter().map(|hop| hop.pool).collect(),
+                        expected_profit: None,
+                        traded_tokens: traded_tokens.clone(),
+                    })
+            })
+            .collect()
+    }
+}
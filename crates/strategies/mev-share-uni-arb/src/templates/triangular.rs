@@ -0,0 +1,111 @@
+use ethers::{
+    abi::{encode, Token},
+    types::{Bytes, H160, U256},
+};
+
+use super::{payment_percentage, BackrunCandidate, BackrunHint, BackrunTemplate};
+use crate::{
+    bidding::BidPolicy,
+    constants::MAX_ROUTES_PER_EVENT,
+    types::{RouteHop, RouteTable, TriangularRoute},
+};
+
+/// Backruns every triangular route which swaps through a touched pool, capped so
+/// a single hint can't flood the matchmaker.
+#[derive(Debug, Clone, Default)]
+pub struct TriangularTemplate {
+    route_table: RouteTable,
+}
+
+impl TriangularTemplate {
+    pub fn new(route_table: RouteTable) -> Self {
+        Self { route_table }
+    }
+}
+
+impl BackrunTemplate for TriangularTemplate {
+    fn name(&self) -> &'static str {
+        "triangular"
+    }
+
+    fn candidates(&self, hint: &BackrunHint, bid_policy: &dyn BidPolicy) -> Vec<BackrunCandidate> {
+        let mut routes: Vec<&TriangularRoute> = Vec::new();
+        for pool in &hint.touched {
+            for route in self.route_table.routes_for(pool) {
+                if !routes.contains(&route) {
+                    routes.push(route);
+                }
+            }
+        }
+
+        routes
+            .into_iter()
+            .take(MAX_ROUTES_PER_EVENT)
+            .flat_map(|route| {
+                triangular_sizes(route.base_token_decimals)
+                    .into_iter()
+                    .map(move |size| BackrunCandidate {
+                        template: self.name(),
+                        loan_token: route.base_token,
+                        size,
+                        user_data: encode_triangular_user_data(
+                            route,
+                            size,
+                            payment_percentage(bid_policy, size, None),
+                        ),
+                        pools: route.hops.iter().map(|hop| hop.pool).collect(),
+                        expected_profit: None,
+                        // Routes only record their pools, so their tokens can't be screened.
+                        traded_tokens: vec![],
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Backrun sizes for a triangular route, ranging from 0.1 to 1,000,000 units of
+/// the base token.
+pub(super) fn triangular_sizes(decimals: u8) -> Vec<U256> {
+    let one = U256::exp10(decimals as usize);
+    (0..8)
+        .map(|i| one * U256::exp10(i) / U256::from(10))
+        .collect()
+}
+
+/// Encode the user data for a triangular route, which the arb contract decodes
+/// in its flash loan callback.
+pub fn encode_triangular_user_data(
+    route: &TriangularRoute,
+    size: U256,
+    payment_percentage: U256,
+) -> Bytes {
+    encode_route_user_data(route.base_token, &route.hops, size, payment_percentage)
+}
+
+/// Encode the user data for a route of any number of `hops` starting and ending in
+/// `base_token`, in the format of [encode_triangular_user_data].
+pub fn encode_route_user_data(
+    base_token: H160,
+    hops: &[RouteHop],
+    size: U256,
+    payment_percentage: U256,
+) -> Bytes {
+    let hops = hops
+        .iter()
+        .map(|hop| {
+            Token::Tuple(vec![
+                Token::Address(hop.pool),
+                Token::Bool(hop.zero_for_one),
+                Token::Bool(hop.is_v3),
+                Token::Uint(U256::from(hop.fee_bps)),
+            ])
+        })
+        .collect();
+    let userdata_token = Token::Tuple(vec![
+        Token::Address(base_token),
+        Token::Array(hops),
+        Token::Uint(size),
+        Token::Uint(payment_percentage),
+    ]);
+    Bytes::from(encode(&[userdata_token]))
+}
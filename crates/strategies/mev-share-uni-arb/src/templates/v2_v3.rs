@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::{encode, Token},
+    types::{Bytes, H160, U256},
+};
+
+use super::{
+    payment_percentage, weth_sizes, BackrunCandidate, BackrunHint, BackrunTemplate, PoolRef,
+};
+use crate::{
+    bidding::BidPolicy,
+    constants::WETH_ADDRESS,
+    screening::TradedToken,
+    solver::{optimal_v2_v3_input, PoolState},
+    strategy::V2PoolInfo,
+};
+
+/// Backruns a touched uni v3 pool against every v2 pool (or v2 fork) trading the
+/// same pair. The arb contract buys the token on the v2 pool and sells it on the v3
+/// pool. When the hint shares the post-swap state of the v3 pool, the backrun is
+/// sized by the [solver](crate::solver), otherwise each of the standard WETH sizes is
+/// submitted.
+#[derive(Debug, Clone, Default)]
+pub struct V2V3ArbTemplate {
+    /// Maps uni v3 pool address to the v2 pools trading the same pair.
+    pool_map: HashMap<H160, Vec<V2PoolInfo>>,
+}
+
+impl V2V3ArbTemplate {
+    pub fn new(pool_map: HashMap<H160, Vec<V2PoolInfo>>) -> Self {
+        Self { pool_map }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool_map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool_map.is_empty()
+    }
+
+    /// Returns the size and expected profit of the backrun of `v3_pool` against the
+    /// v2 pool, if the state of both is known. The size is `None` if the arb isn't
+    /// profitable.
+    fn solve(
+        &self,
+        hint: &BackrunHint,
+        v3_pool: H160,
+        v2_info: &V2PoolInfo,
+    ) -> Option<Option<(U256, U256)>> {
+        let Some(PoolState::V3(v3)) = hint.pool_states.get(&v3_pool) else {
+            return None;
+        };
+        let Some(PoolState::V2 { reserve0, reserve1 }) = hint.pool_states.get(&v2_info.v2_pool)
+        else {
+            return None;
+        };
+        let (weth_reserve, token_reserve) = if v2_info.is_weth_token0 {
+            (*reserve0, *reserve1)
+        } else {
+            (*reserve1, *reserve0)
+        };
+        // Both pools order the pair the same way, so selling the token on v3 swaps
+        // token1 for token0 when WETH is token0.
+        Some(optimal_v2_v3_input(
+            weth_reserve,
+            token_reserve,
+            v2_info.fee_bps,
+            v3,
+            !v2_info.is_weth_token0,
+            weth_reserve,
+        ))
+    }
+
+    fn candidate(
+        &self,
+        v3_pool: H160,
+        v2_info: &V2PoolInfo,
+        size: U256,
+        expected_profit: Option<U256>,
+        bid_policy: &dyn BidPolicy,
+    ) -> BackrunCandidate {
+        // The arb contract picks the swap direction based on whether the v2
+        // pool has weth as token0.
+        let userdata_token = Token::Tuple(vec![
+            Token::Bool(v2_info.is_weth_token0),
+            Token::Address(v2_info.v2_pool),
+            Token::Address(v3_pool),
+            Token::Uint(size),
+            Token::Uint(payment_percentage(bid_policy, size, expected_profit)),
+            Token::Uint(U256::from(v2_info.fee_bps)),
+        ]);
+        BackrunCandidate {
+            template: self.name(),
+            loan_token: *WETH_ADDRESS,
+            size,
+            user_data: Bytes::from(encode(&[userdata_token])),
+            pools: vec![v3_pool, v2_info.v2_pool],
+            expected_profit,
+            traded_tokens: vec![TradedToken {
+                token: v2_info.token,
+                pool: v2_info.v2_pool,
+            }],
+        }
+    }
+}
+
+impl BackrunTemplate for V2V3ArbTemplate {
+    fn name(&self) -> &'static str {
+        "v2-v3-arb"
+    }
+
+    /// The solver needs the v2 pools, and the v3 pools whose post-swap state the hint
+    /// shares. Pre-hint state of the v3 pool wouldn't say anything about the backrun.
+    fn pools(&self, hint: &BackrunHint) -> Vec<PoolRef> {
+        let mut pools = Vec::new();
+        for v3_pool in &hint.touched {
+            if !hint.v3_swaps.contains_key(v3_pool) {
+                continue;
+            }
+            for v2_info in self.pool_map.get(v3_pool).into_iter().flatten() {
+                pools.push(PoolRef::V2(v2_info.v2_pool));
+            }
+            if self.pool_map.contains_key(v3_pool) {
+                pools.push(PoolRef::V3(*v3_pool));
+            }
+        }
+        pools
+    }
+
+    fn candidates(&self, hint: &BackrunHint, bid_policy: &dyn BidPolicy) -> Vec<BackrunCandidate> {
+        let mut candidates = Vec::new();
+        for v3_pool in &hint.touched {
+            for v2_info in self.pool_map.get(v3_pool).into_iter().flatten() {
+                match self.solve(hint, *v3_pool, v2_info) {
+                    Some(Some((size, profit))) => candidates.push(self.candidate(
+                        *v3_pool,
+                        v2_info,
+                        size,
+                        Some(profit),
+                        bid_policy,
+                    )),
+                    // The solver found no profitable size.
+                    Some(None) => {}
+                    None => {
+                        for size in weth_sizes() {
+                            candidates
+                                .push(self.candidate(*v3_pool, v2_info, size, None, bid_policy));
+                        }
+                    }
+                }
+            }
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidding::FixedBid, solver::V3PoolState, templates::V3SwapState};
+
+    #[test]
+    fn matches_any_touched_v3_pool() {
+        let v3_pool = H160::from_low_u64_be(1);
+        let pool_map = HashMap::from([(
+            v3_pool,
+            vec![V2PoolInfo {
+                token: H160::from_low_u64_be(3),
+                v2_pool: H160::from_low_u64_be(2),
+                is_weth_token0: true,
+                fee_bps: 30,
+                factory: H160::zero(),
+            }],
+        )]);
+        let template = V2V3ArbTemplate::new(pool_map);
+        let bid = FixedBid { percentage: 40 };
+
+        // The v3 pool doesn't need to be the first log of the hint.
+        let hint = BackrunHint {
+            touched: vec![H160::from_low_u64_be(9), v3_pool],
+            ..Default::default()
+        };
+        let candidates = template.candidates(&hint, &bid);
+        assert_eq!(candidates.len(), weth_sizes().len());
+        assert!(candidates.iter().all(|c| c.loan_token == *WETH_ADDRESS));
+
+        let hint = BackrunHint {
+            touched: vec![H160::from_low_u64_be(9)],
+            ..Default::default()
+        };
+        assert!(template.candidates(&hint, &bid).is_empty());
+    }
+
+    #[test]
+    fn solves_for_size_when_pool_states_are_known() {
+        let (v3_pool, v2_pool) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+        let pool_map = HashMap::from([(
+            v3_pool,
+            vec![V2PoolInfo {
+                token: H160::from_low_u64_be(3),
+                v2_pool,
+                is_weth_token0: true,
+                fee_bps: 30,
+                factory: H160::zero(),
+            }],
+        )]);
+        let template = V2V3ArbTemplate::new(pool_map);
+        let bid = FixedBid { percentage: 40 };
+
+        // The hinted swap left the v3 pool buying the token for more WETH than v2 sells it.
+        let v3 = V3PoolState {
+            sqrt_price_x96: U256::from(2_000u64).integer_sqrt() * (U256::one() << 96),
+            tick: 75_700,
+            liquidity: 10u128.pow(22),
+            fee: 3_000,
+            ticks: vec![],
+        };
+        let mut hint = BackrunHint {
+            touched: vec![v3_pool],
+            v3_swaps: HashMap::from([(
+                v3_pool,
+                V3SwapState {
+                    sqrt_price_x96: v3.sqrt_price_x96,
+                    liquidity: v3.liquidity,
+                    tick: v3.tick,
+                },
+            )]),
+            ..Default::default()
+        };
+        assert_eq!(
+            template.pools(&hint),
+            vec![PoolRef::V2(v2_pool), PoolRef::V3(v3_pool)]
+        );
+
+        hint.pool_states = HashMap::from([
+            (v3_pool, PoolState::V3(v3)),
+            (
+                v2_pool,
+                PoolState::V2 {
+                    reserve0: U256::exp10(21),
+                    reserve1: U256::exp10(21) * 2_100,
+                },
+            ),
+        ]);
+        let candidates = template.candidates(&hint, &bid);
+        assert_eq!(candidates.len(), 1);
+        assert!(!candidates[0].size.is_zero());
+    }
+}
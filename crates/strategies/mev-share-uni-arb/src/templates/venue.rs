@@ -0,0 +1,308 @@
+use ethers::{
+    abi::{encode, Token},
+    types::{Bytes, H160, U256},
+};
+
+use super::{
+    payment_percentage, weth_sizes, BackrunCandidate, BackrunHint, BackrunTemplate, PoolRef,
+};
+use crate::{
+    adapters::VenuePool,
+    bidding::BidPolicy,
+    constants::{NATIVE_ETH_ADDRESS, WETH_ADDRESS},
+    math::get_amount_out,
+    screening::TradedToken,
+    solver::PoolState,
+    wrapping::NativeEthLeg,
+};
+
+/// Rebalances a touched Balancer / Curve pool against a v2 pool, in each WETH size the
+/// venue can fill: buys the token on the venue pool and sells it back into the v2 pool,
+/// or, once the reserves of the v2 pool are known, buys it on the v2 pool and sells it
+/// into the venue pool.
+#[derive(Debug, Clone)]
+pub struct VenueArbTemplate {
+    venue: VenuePool,
+    /// Recipient of the venue swap, i.e. the arb contract.
+    recipient: H160,
+}
+
+impl VenueArbTemplate {
+    pub fn new(venue: VenuePool, recipient: H160) -> Self {
+        Self { venue, recipient }
+    }
+
+    /// Returns the backrun of `size` WETH buying the token on the venue. Venues trading
+    /// native ETH rather than WETH are paid with loaned WETH the arb contract unwraps.
+    fn buy_on_venue(&self, size: U256, bid_policy: &dyn BidPolicy) -> Option<BackrunCandidate> {
+        let venue = &self.venue;
+        let (token_in, native_eth) = [
+            (*WETH_ADDRESS, None),
+            (*NATIVE_ETH_ADDRESS, Some(NativeEthLeg::In(size))),
+        ]
+        .into_iter()
+        .find(|(token_in, _)| {
+            venue
+                .adapter
+                .get_amount_out(*token_in, venue.token, size)
+                .is_some_and(|out| !out.is_zero())
+        })?;
+        let swap_calldata =
+            venue
+                .adapter
+                .encode_swap(token_in, venue.token, size, U256::zero(), self.recipient)?;
+        Some(self.candidate(
+            size,
+            swap_calldata,
+            true,
+            native_eth,
+            vec![venue.adapter.address(), venue.v2_info.v2_pool],
+            bid_policy,
+        ))
+    }
+
+    /// Returns the backrun of `size` WETH buying the token on the v2 pool and selling
+    /// all of it into the venue. Venues paying out native ETH rather than WETH have the
+    /// arb contract wrap it to repay the loan.
+    fn sell_on_venue(
+        &self,
+        hint: &BackrunHint,
+        size: U256,
+        bid_policy: &dyn BidPolicy,
+    ) -> Option<BackrunCandidate> {
+        let venue = &self.venue;
+        let Some(PoolState::V2 { reserve0, reserve1 }) =
+            hint.pool_states.get(&venue.v2_info.v2_pool)
+        else {
+            return None;
+        };
+        let (weth_reserve, token_reserve) = match venue.v2_info.is_weth_token0 {
+            true => (*reserve0, *reserve1),
+            false => (*reserve1, *reserve0),
+        };
+        let amount_in = get_amount_out(size, weth_reserve, token_reserve, venue.v2_info.fee_bps);
+        if amount_in.is_zero() {
+            return None;
+        }
+        let (token_out, native_eth) =
+            [*WETH_ADDRESS, *NATIVE_ETH_ADDRESS]
+                .into_iter()
+                .find_map(|token_out| {
+                    let out = venue
+                        .adapter
+                        .get_amount_out(venue.token, token_out, amount_in)
+                        .filter(|out| !out.is_zero())?;
+                    let native_eth =
+                        (token_out == *NATIVE_ETH_ADDRESS).then_some(NativeEthLeg::Out(out));
+                    Some((token_out, native_eth))
+                })?;
+        let swap_calldata = venue.adapter.encode_swap(
+            venue.token,
+            token_out,
+            amount_in,
+            U256::zero(),
+            self.recipient,
+        )?;
+        Some(self.candidate(
+            size,
+            swap_calldata,
+            false,
+            native_eth,
+            vec![venue.v2_info.v2_pool, venue.adapter.address()],
+            bid_policy,
+        ))
+    }
+
+    fn candidate(
+        &self,
+        size: U256,
+        swap_calldata: Bytes,
+        buy_on_venue: bool,
+        native_eth: Option<NativeEthLeg>,
+        pools: Vec<H160>,
+        bid_policy: &dyn BidPolicy,
+    ) -> BackrunCandidate {
+        let venue = &self.venue;
+        let userdata_token = Token::Tuple(vec![
+            Token::Address(venue.adapter.swap_target()),
+            Token::Bytes(swap_calldata.to_vec()),
+            Token::Bool(venue.v2_info.is_weth_token0),
+            Token::Address(venue.v2_info.v2_pool),
+            Token::Uint(size),
+            Token::Uint(payment_percentage(bid_policy, size, None)),
+            Token::Uint(U256::from(venue.v2_info.fee_bps)),
+            Token::Bool(buy_on_venue),
+            NativeEthLeg::encode(native_eth),
+        ]);
+        BackrunCandidate {
+            template: self.name(),
+            loan_token: *WETH_ADDRESS,
+            size,
+            user_data: Bytes::from(encode(&[userdata_token])),
+            pools,
+            expected_profit: None,
+            traded_tokens: vec![TradedToken {
+                token: venue.token,
+                pool: venue.v2_info.v2_pool,
+            }],
+        }
+    }
+}
+
+impl BackrunTemplate for VenueArbTemplate {
+    fn name(&self) -> &'static str {
+        "venue-arb"
+    }
+
+    fn pools(&self, hint: &BackrunHint) -> Vec<PoolRef> {
+        match hint.touched.contains(&self.venue.adapter.address()) {
+            true => vec![PoolRef::V2(self.venue.v2_info.v2_pool)],
+            false => vec![],
+        }
+    }
+
+    fn candidates(&self, hint: &BackrunHint, bid_policy: &dyn BidPolicy) -> Vec<BackrunCandidate> {
+        if !hint.touched.contains(&self.venue.adapter.address()) {
+            return vec![];
+        }
+
+        // Sizes the venue can't fill are skipped, in either direction.
+        weth_sizes()
+            .into_iter()
+            .flat_map(|size| {
+                [
+                    self.buy_on_venue(size, bid_policy),
+                    self.sell_on_venue(hint, size, bid_policy),
+                ]
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethers::abi::{decode, ParamType};
+
+    use super::*;
+    use crate::{adapters::PoolAdapter, bidding::FixedBid, strategy::V2PoolInfo};
+
+    /// A venue trading `token` against native ETH only, at 1:1.
+    #[derive(Debug)]
+    struct NativeEthVenue {
+        token: H160,
+    }
+
+    impl PoolAdapter for NativeEthVenue {
+        fn address(&self) -> H160 {
+            H160::from_low_u64_be(1)
+        }
+
+        fn swap_target(&self) -> H160 {
+            self.address()
+        }
+
+        fn get_amount_out(&self, token_in: H160, token_out: H160, amount_in: U256) -> Option<U256> {
+            let pair = [token_in, token_out];
+            (pair.contains(&self.token) && pair.contains(&*NATIVE_ETH_ADDRESS)).then_some(amount_in)
+        }
+
+        fn encode_swap(
+            &self,
+            token_in: H160,
+            token_out: H160,
+            amount_in: U256,
+            _min_amount_out: U256,
+            _recipient: H160,
+        ) -> Option<Bytes> {
+            self.get_amount_out(token_in, token_out, amount_in)
+                .map(|_| Bytes::default())
+        }
+
+        fn balance_calls(&self) -> Vec<(H160, Bytes)> {
+            vec![]
+        }
+
+        fn update_balances(&self, _outputs: &[Bytes]) -> Option<()> {
+            Some(())
+        }
+    }
+
+    fn native_eth_leg(candidate: &BackrunCandidate) -> Token {
+        let tuple = decode(
+            &[ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::Bytes,
+                ParamType::Bool,
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Bool,
+                ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Uint(256)]),
+            ])],
+            &candidate.user_data,
+        )
+        .unwrap();
+        let Token::Tuple(fields) = &tuple[0] else {
+            unreachable!()
+        };
+        fields[8].clone()
+    }
+
+    #[test]
+    fn wraps_and_unwraps_native_eth_in_the_arb_contract() {
+        let token = H160::repeat_byte(2);
+        let v2_pool = H160::from_low_u64_be(2);
+        let template = VenueArbTemplate::new(
+            VenuePool {
+                adapter: Arc::new(NativeEthVenue { token }),
+                token,
+                v2_info: V2PoolInfo {
+                    token,
+                    v2_pool,
+                    is_weth_token0: true,
+                    fee_bps: 30,
+                    factory: H160::zero(),
+                },
+            },
+            H160::zero(),
+        );
+        let mut hint = BackrunHint {
+            touched: vec![H160::from_low_u64_be(1)],
+            ..Default::default()
+        };
+        let bid_policy = FixedBid { percentage: 40 };
+
+        // Without the reserves of the v2 pool, the token is only bought on the venue.
+        let candidates = template.candidates(&hint, &bid_policy);
+        assert_eq!(candidates.len(), weth_sizes().len());
+        assert!(candidates.iter().all(|candidate| {
+            native_eth_leg(candidate)
+                == NativeEthLeg::encode(Some(NativeEthLeg::In(candidate.size)))
+        }));
+
+        let reserve = U256::exp10(18) * 1_000_000;
+        hint.pool_states.insert(
+            v2_pool,
+            PoolState::V2 {
+                reserve0: reserve,
+                reserve1: reserve,
+            },
+        );
+        let candidates = template.candidates(&hint, &bid_policy);
+        let sells: Vec<&BackrunCandidate> = candidates
+            .iter()
+            .filter(|candidate| candidate.pools[0] == v2_pool)
+            .collect();
+        assert_eq!(sells.len(), weth_sizes().len());
+        let size = sells[0].size;
+        let out = get_amount_out(size, reserve, reserve, 30);
+        assert_eq!(
+            native_eth_leg(sells[0]),
+            NativeEthLeg::encode(Some(NativeEthLeg::Out(out)))
+        );
+    }
+}
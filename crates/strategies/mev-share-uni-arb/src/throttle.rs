@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ethers::types::{H160, H256, U64};
+
+/// Throttles the opportunities taken through each pool, so a pool emitting many hints
+/// per block doesn't cause overlapping batches of bundles: the first hint to claim a
+/// pool keeps it for a cooldown, during which other hints through it are skipped.
+#[derive(Debug, Default)]
+pub struct PoolThrottle {
+    /// Hint which last claimed each pool, and the latest block when it did.
+    claims: Mutex<HashMap<H160, (H256, U64)>>,
+}
+
+impl PoolThrottle {
+    /// Returns true if the hint `hint_hash` could claim `pools` at `block`, i.e. no other
+    /// hint claimed any of them fewer than `cooldown` blocks before.
+    pub fn is_claimable(&self, pools: &[H160], hint_hash: H256, block: U64, cooldown: u64) -> bool {
+        let claims = self.claims.lock().unwrap();
+        Self::claimable(&claims, pools, hint_hash, block, cooldown)
+    }
+
+    /// Claim `pools` for the hint `hint_hash` at `block`, for `cooldown` blocks. Returns
+    /// false, claiming none of them, if another hint claimed any of the pools fewer than
+    /// `cooldown` blocks before. Hints can claim their own pools again, which doesn't
+    /// extend their cooldown.
+    pub fn try_claim(&self, pools: &[H160], hint_hash: H256, block: U64, cooldown: u64) -> bool {
+        let mut claims = self.claims.lock().unwrap();
+        if !Self::claimable(&claims, pools, hint_hash, block, cooldown) {
+            return false;
+        }
+        for pool in pools {
+            claims
+                .entry(*pool)
+                .and_modify(|claim| {
+                    if claim.0 != hint_hash {
+                        *claim = (hint_hash, block);
+                    }
+                })
+                .or_insert((hint_hash, block));
+        }
+        true
+    }
+
+    fn claimable(
+        claims: &HashMap<H160, (H256, U64)>,
+        pools: &[H160],
+        hint_hash: H256,
+        block: U64,
+        cooldown: u64,
+    ) -> bool {
+        !pools.iter().any(|pool| {
+            claims.get(pool).is_some_and(|(claimant, claimed_at)| {
+                *claimant != hint_hash && block < *claimed_at + cooldown
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_one_hint_per_pool_per_cooldown() {
+        let throttle = PoolThrottle::default();
+        let (pool, other_pool) = (H160::repeat_byte(1), H160::repeat_byte(2));
+        let (first, second) = (H256::repeat_byte(3), H256::repeat_byte(4));
+        let block = U64::from(10);
+
+        assert!(throttle.try_claim(&[pool], first, block, 2));
+        assert!(throttle.try_claim(&[pool], first, block, 2));
+        assert!(!throttle.try_claim(&[other_pool, pool], second, block + 1, 2));
+        // The throttled hint claimed neither pool.
+        assert!(throttle.try_claim(&[other_pool], first, block + 1, 2));
+        assert!(throttle.try_claim(&[pool], second, block + 2, 2));
+    }
+
+    #[test]
+    fn claiming_again_does_not_extend_the_cooldown() {
+        let throttle = PoolThrottle::default();
+        let pool = H160::repeat_byte(1);
+        let (first, second) = (H256::repeat_byte(3), H256::repeat_byte(4));
+        let block = U64::from(10);
+
+        assert!(throttle.try_claim(&[pool], first, block, 2));
+        assert!(throttle.try_claim(&[pool], first, block + 1, 2));
+        assert!(!throttle.is_claimable(&[pool], second, block + 1, 2));
+        assert!(throttle.is_claimable(&[pool], second, block + 2, 2));
+        // Claiming again once the cooldown is over doesn't hold the pool either.
+        assert!(throttle.try_claim(&[pool], first, block + 2, 2));
+        assert!(throttle.try_claim(&[pool], second, block + 2, 2));
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Mutex;
+
+use ethers::{
+    types::{
+        transaction::eip2718::TypedTransaction, Bytes, Eip1559TransactionRequest, H160, U256, U64,
+    },
+    utils::keccak256,
+};
+
+/// Calldata of a flash loan arb with the loan size left as a parameter, so the variants
+/// of an arb only differ by a patched size instead of being encoded one by one.
+#[derive(Debug, Clone)]
+pub struct CalldataTemplate {
+    to: H160,
+    data: Bytes,
+    /// Offsets of the 32 byte words holding the size.
+    size_offsets: Vec<usize>,
+}
+
+impl CalldataTemplate {
+    /// Build a template from an encoder, by encoding a placeholder size and recording
+    /// where it ends up in the calldata.
+    pub fn new(encode: impl Fn(U256) -> (H160, Bytes)) -> Self {
+        let placeholder = size_placeholder();
+        let (to, data) = encode(U256::from_big_endian(&placeholder));
+        let size_offsets = data
+            .windows(32)
+            .enumerate()
+            .filter(|(_, word)| *word == placeholder)
+            .map(|(offset, _)| offset)
+            .collect();
+        Self {
+            to,
+            data,
+            size_offsets,
+        }
+    }
+
+    /// Returns the tx target and the calldata for `size`.
+    pub fn with_size(&self, size: U256) -> (H160, Bytes) {
+        let mut word = [0u8; 32];
+        size.to_big_endian(&mut word);
+        let mut data = self.data.to_vec();
+        for offset in &self.size_offsets {
+            data[*offset..*offset + 32].copy_from_slice(&word);
+        }
+        (self.to, data.into())
+    }
+}
+
+/// A size no real arb uses, so it can be found in encoded calldata.
+fn size_placeholder() -> [u8; 32] {
+    keccak256("mev-share-uni-arb.size")
+}
+
+/// Everything an arb tx needs besides its target and calldata, shared by all the
+/// bundles generated for a hint so they can be built and signed without RPC calls.
+#[derive(Debug, Clone)]
+pub struct TxTemplate {
+    pub from: H160,
+    pub nonce: U256,
+    pub chain_id: u64,
+    pub gas: U256,
+    pub gas_price: U256,
+}
+
+impl TxTemplate {
+    pub fn build(&self, to: H160, data: Bytes) -> TypedTransaction {
+        Eip1559TransactionRequest::new()
+            .from(self.from)
+            .to(to)
+            .data(data)
+            .nonce(self.nonce)
+            .chain_id(self.chain_id)
+            .gas(self.gas)
+            .max_fee_per_gas(self.gas_price)
+            .max_priority_fee_per_gas(self.gas_price)
+            .into()
+    }
+}
+
+/// The nonce of the signer as of the latest block. Our txs only land in blocks, so it
+/// only needs fetching once per block.
+#[derive(Debug, Default)]
+pub struct NonceCache {
+    latest: Mutex<Option<(U64, U256)>>,
+}
+
+impl NonceCache {
+    /// Returns the cached nonce if it was fetched at `block`.
+    pub fn get(&self, block: U64) -> Option<U256> {
+        match *self.latest.lock().unwrap() {
+            Some((cached_block, nonce)) if cached_block == block => Some(nonce),
+            _ => None,
+        }
+    }
+
+    pub fn set(&self, block: U64, nonce: U256) {
+        let mut latest = self.latest.lock().unwrap();
+        // Hints are processed concurrently, so don't overwrite a newer block's nonce.
+        match *latest {
+            Some((cached_block, _)) if cached_block > block => {}
+            _ => *latest = Some((block, nonce)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flashloan::FlashloanProvider;
+
+    #[test]
+    fn patched_calldata_matches_encoded_calldata() {
+        let arb_contract = H160::random();
+        let token = H160::random();
+        let user_data = Bytes::from(vec![7u8; 70]);
+        let providers = [
+            FlashloanProvider::AaveV3 {
+                pool: H160::random(),
+                asset: token,
+                a_token: H160::random(),
+            },
+            FlashloanProvider::UniswapV3 {
+                pool: H160::random(),
+                token0: H160::random(),
+                token1: token,
+                fee: 500,
+            },
+        ];
+
+        for provider in providers {
+            let encode = |size| {
+                provider
+                    .encode(arb_contract, token, size, user_data.clone())
+                    .unwrap()
+            };
+            let template = CalldataTemplate::new(encode);
+            for size in [U256::zero(), U256::exp10(18), U256::MAX] {
+                assert_eq!(template.with_size(size), encode(size));
+            }
+        }
+    }
+
+    #[test]
+    fn nonce_is_cached_per_block() {
+        let cache = NonceCache::default();
+        cache.set(U64::from(10), U256::from(3));
+        assert_eq!(cache.get(U64::from(10)), Some(U256::from(3)));
+        assert_eq!(cache.get(U64::from(11)), None);
+
+        cache.set(U64::from(9), U256::from(2));
+        assert_eq!(cache.get(U64::from(10)), Some(U256::from(3)));
+    }
+}
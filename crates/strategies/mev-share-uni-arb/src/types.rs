@@ -1,18 +1,164 @@
-use artemis_core::executors::{flashbots_executor::FlashbotsBundle, mev_share_executor::Bundles};
-use ethers::types::H160;
+use std::{collections::HashMap, time::Duration};
 
-use mev_share::sse;
+use artemis_core::{
+    collectors::{
+        block_collector::NewBlock, config_collector::ConfigUpdated,
+        inventory_collector::InventoryUpdate,
+    },
+    executors::{flashbots_executor::FlashbotsBundle, mev_share_executor::Bundles},
+    types::{Expiry, SubmissionReceipt},
+};
+use ethers::types::{Log, H160, U64};
+use matchmaker::status::StatusUpdate;
+use matchmaker::types::{BuilderId, BundleRequest, Hint, PrivacyHint};
+
+use crate::config::StrategyConfig;
+use crate::sweep::SweepRequest;
 
 /// Core Event enum for the current strategy.
 #[derive(Debug, Clone)]
 pub enum Event {
-    MEVShareEvent(sse::Event),
+    MEVShareEvent(Hint),
+    ConfigUpdated(ConfigUpdated<StrategyConfig>),
+    SubmissionReceipt(SubmissionReceipt),
+    InventoryUpdate(InventoryUpdate),
+    NewBlock(NewBlock),
+    /// A `Swap`, `Mint` or `Burn` log of a uniswap v3 pool whose ticks are tracked.
+    V3PoolLog(Log),
+    /// A `Sync` log of a uniswap v2 pool whose reserves are tracked.
+    V2SyncLog(Log),
+    /// A submitted bundle moving to a new status, e.g. missing its target block.
+    BundleStatus(StatusUpdate),
+    /// A log of a Comet market, for a liquidator run alongside the arb.
+    CometLog(Log),
+    /// A price feed update, which may have made Comet accounts liquidatable.
+    PriceUpdate(Log),
 }
 
 /// Core Action enum for the current strategy.
 #[derive(Debug, Clone)]
 pub enum Action {
     SubmitBundles(Bundles),
+    Sweep(SweepRequest),
+    /// A bundle to send to the Flashbots relay, e.g. liquidating Comet accounts.
+    SubmitFlashbotsBundle(FlashbotsBundle),
+}
+
+impl Action {
+    /// When the action expires: bundles once the last block any of them targets was
+    /// built. Sweeps, and Flashbots bundles, which target the blocks after they are
+    /// sent, don't expire.
+    pub fn expires_at(&self) -> Option<Expiry> {
+        match self {
+            Action::SubmitBundles(bundles) => bundles
+                .iter()
+                .map(|bundle| bundle.inclusion.max_block.unwrap_or(bundle.inclusion.block))
+                .max()
+                .map(Expiry::Block),
+            Action::Sweep(_) | Action::SubmitFlashbotsBundle(_) => None,
+        }
+    }
+}
+
+/// Slot time on mainnet.
+const SLOT_TIME: Duration = Duration::from_secs(12);
+
+/// Configuration for which blocks bundles target, and how long they stay valid.
+#[derive(Debug, Clone)]
+pub struct BundleTiming {
+    /// Number of blocks after the latest block to target (1 targets the next block).
+    pub block_offset: u64,
+    /// Number of blocks a bundle stays valid for, starting at its target block. Laddered
+    /// bundles are only valid for their target block, so they don't overlap.
+    pub max_validity: u64,
+    /// If an event arrives with less than this much time left before the next block,
+    /// also submit bundles targeting the block after.
+    pub ladder_threshold: Duration,
+}
+
+impl Default for BundleTiming {
+    fn default() -> Self {
+        Self {
+            block_offset: 1,
+            max_validity: 2,
+            ladder_threshold: Duration::from_secs(2),
+        }
+    }
+}
+
+impl BundleTiming {
+    /// Returns the blocks to target, given the latest block and how long ago it was produced.
+    pub fn target_blocks(&self, latest_block: U64, since_latest_block: Duration) -> Vec<U64> {
+        let target = latest_block + self.block_offset;
+        let remaining = SLOT_TIME.saturating_sub(since_latest_block);
+        if remaining < self.ladder_threshold {
+            vec![target, target + 1]
+        } else {
+            vec![target]
+        }
+    }
+
+    /// Returns the number of blocks bundles targeting `target_blocks` stay valid for: a
+    /// single block if they are laddered, as the next rung covers the block after.
+    pub fn validity(&self, target_blocks: &[U64]) -> u64 {
+        if target_blocks.len() > 1 {
+            1
+        } else {
+            self.max_validity
+        }
+    }
+}
+
+/// How private bundles are kept, each level mapped to the hints and builders of a
+/// [SubmissionPrivacy] by the [config](StrategyConfig::privacy_levels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrivacyLevel {
+    /// Shared widely, e.g. with calldata and logs hinted, to every builder.
+    Public,
+    /// Sharing a few hints, e.g. the tx hashes, with a broad set of builders.
+    SemiPrivate,
+    /// Sharing nothing, with trusted builders only.
+    Private,
+}
+
+/// What bundles share about their transactions, and which builders they are sent to.
+/// By default nothing is shared, and bundles are sent to every builder in the
+/// [directory](matchmaker::builders::BuilderDirectory) of the matchmaker client.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubmissionPrivacy {
+    /// Hints shared about bundles, if not the default of sharing nothing.
+    pub hints: Option<PrivacyHint>,
+    /// Builders bundles are sent to, if not every builder in the directory.
+    pub builders: Option<Vec<BuilderId>>,
+}
+
+impl SubmissionPrivacy {
+    /// Only share the hashes of the transactions of bundles, so they can't be
+    /// sandwiched from their calldata.
+    pub fn with_hash_only_hints(mut self) -> Self {
+        self.hints = Some(PrivacyHint::hash_only());
+        self
+    }
+
+    /// Only send bundles to `builders`, given by name or address.
+    pub fn with_trusted_builders<B: Into<BuilderId>>(
+        mut self,
+        builders: impl IntoIterator<Item = B>,
+    ) -> Self {
+        self.builders = Some(builders.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Apply the preferences to `bundle`.
+    pub fn apply(&self, mut bundle: BundleRequest) -> BundleRequest {
+        bundle = bundle.with_hints(self.hints.clone().unwrap_or_default());
+        if let Some(builders) = &self.builders {
+            bundle = bundle.with_builders(builders.clone());
+        }
+        bundle
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -22,10 +168,224 @@ pub struct PoolRecord {
     pub sushi_pool_address: H160,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct V2V3PoolRecord {
     pub token_address: H160,
     pub v3_pool: H160,
     pub v2_pool: H160,
     pub weth_token0: bool,
+    pub fee_bps: u32,
+    pub factory: H160,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct TriangularRouteRecord {
+    pub base_token: H160,
+    pub base_token_decimals: u8,
+    pub pool_0: H160,
+    pub zero_for_one_0: bool,
+    pub is_v3_0: bool,
+    pub fee_bps_0: u32,
+    pub pool_1: H160,
+    pub zero_for_one_1: bool,
+    pub is_v3_1: bool,
+    pub fee_bps_1: u32,
+    pub pool_2: H160,
+    pub zero_for_one_2: bool,
+    pub is_v3_2: bool,
+    pub fee_bps_2: u32,
+}
+
+/// A single swap in a [TriangularRoute](TriangularRoute).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteHop {
+    /// Address of the pool to swap through.
+    pub pool: H160,
+    /// Whether we swap token0 for token1 in this pool.
+    pub zero_for_one: bool,
+    /// Whether the pool is a uniswap v3 pool (otherwise v2).
+    pub is_v3: bool,
+    /// Swap fee in basis points, only used for v2 pools.
+    pub fee_bps: u32,
+}
+
+/// A three-hop route which starts and ends in the same base token, for example
+/// USDC -> WETH -> TOKEN -> USDC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriangularRoute {
+    /// Token which is flash loaned and repaid at the end of the route.
+    pub base_token: H160,
+    /// Decimals of the base token, used to scale backrun sizes.
+    pub base_token_decimals: u8,
+    /// The swaps making up the route, in order.
+    pub hops: [RouteHop; 3],
+}
+
+impl From<TriangularRouteRecord> for TriangularRoute {
+    fn from(record: TriangularRouteRecord) -> Self {
+        Self {
+            base_token: record.base_token,
+            base_token_decimals: record.base_token_decimals,
+            hops: [
+                RouteHop {
+                    pool: record.pool_0,
+                    zero_for_one: record.zero_for_one_0,
+                    is_v3: record.is_v3_0,
+                    fee_bps: record.fee_bps_0,
+                },
+                RouteHop {
+                    pool: record.pool_1,
+                    zero_for_one: record.zero_for_one_1,
+                    is_v3: record.is_v3_1,
+                    fee_bps: record.fee_bps_1,
+                },
+                RouteHop {
+                    pool: record.pool_2,
+                    zero_for_one: record.zero_for_one_2,
+                    is_v3: record.is_v3_2,
+                    fee_bps: record.fee_bps_2,
+                },
+            ],
+        }
+    }
+}
+
+/// Maps pool addresses to the triangular routes which swap through them.
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable {
+    routes: HashMap<H160, Vec<TriangularRoute>>,
+}
+
+impl RouteTable {
+    /// Add a route, indexing it by every pool it touches.
+    pub fn insert(&mut self, route: TriangularRoute) {
+        for hop in route.hops.iter() {
+            let routes = self.routes.entry(hop.pool).or_default();
+            if !routes.contains(&route) {
+                routes.push(route.clone());
+            }
+        }
+    }
+
+    /// Returns all routes which swap through the given pool.
+    pub fn routes_for(&self, pool: &H160) -> &[TriangularRoute] {
+        self.routes.get(pool).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns true if at least one route swaps through the given pool.
+    pub fn contains(&self, pool: &H160) -> bool {
+        self.routes.contains_key(pool)
+    }
+
+    /// Returns the number of pools covered by the table.
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// Returns true if the table has no routes.
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(pools: [H160; 3]) -> TriangularRoute {
+        TriangularRoute {
+            base_token: H160::random(),
+            base_token_decimals: 6,
+            hops: pools.map(|pool| RouteHop {
+                pool,
+                zero_for_one: true,
+                is_v3: false,
+                fee_bps: 30,
+            }),
+        }
+    }
+
+    #[test]
+    fn applies_submission_privacy() {
+        let bundle = BundleRequest::make_simple(U64::from(100), vec![]);
+        let default = SubmissionPrivacy::default().apply(bundle.clone());
+        let privacy = default.privacy.unwrap();
+        assert_eq!(privacy.hints, Some(PrivacyHint::default()));
+        assert_eq!(privacy.builders, None);
+
+        let private = SubmissionPrivacy::default()
+            .with_hash_only_hints()
+            .with_trusted_builders(["flashbots", "Titan"])
+            .apply(bundle);
+        let privacy = private.privacy.unwrap();
+        assert_eq!(privacy.hints, Some(PrivacyHint::hash_only()));
+        assert_eq!(
+            privacy.builders,
+            Some(vec![BuilderId::from("flashbots"), BuilderId::from("Titan")])
+        );
+    }
+
+    #[test]
+    fn bundles_expire_after_their_last_block() {
+        let bundles = vec![
+            BundleRequest::make_with_validity(U64::from(101), 1, vec![]),
+            BundleRequest::make_with_validity(U64::from(102), 2, vec![]),
+        ];
+        assert_eq!(
+            Action::SubmitBundles(bundles).expires_at(),
+            Some(Expiry::Block(U64::from(103)))
+        );
+        assert_eq!(Action::SubmitBundles(vec![]).expires_at(), None);
+    }
+
+    #[test]
+    fn ladders_target_blocks_near_block_boundary() {
+        let timing = BundleTiming::default();
+        let latest = U64::from(100);
+        assert_eq!(
+            timing.target_blocks(latest, Duration::from_secs(3)),
+            vec![U64::from(101)]
+        );
+        assert_eq!(
+            timing.target_blocks(latest, Duration::from_secs(11)),
+            vec![U64::from(101), U64::from(102)]
+        );
+        // A missed slot puts us past the boundary, so we ladder too.
+        assert_eq!(
+            timing.target_blocks(latest, Duration::from_secs(20)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn laddered_bundles_are_valid_for_a_single_block() {
+        let timing = BundleTiming::default();
+        let latest = U64::from(100);
+        let targets = timing.target_blocks(latest, Duration::from_secs(3));
+        assert_eq!(timing.validity(&targets), timing.max_validity);
+        let targets = timing.target_blocks(latest, Duration::from_secs(11));
+        assert_eq!(timing.validity(&targets), 1);
+    }
+
+    #[test]
+    fn route_table_indexes_every_hop() {
+        let pools = [H160::random(), H160::random(), H160::random()];
+        let mut table = RouteTable::default();
+        table.insert(route(pools));
+
+        assert_eq!(table.len(), 3);
+        for pool in pools.iter() {
+            assert_eq!(table.routes_for(pool).len(), 1);
+        }
+        assert!(table.routes_for(&H160::random()).is_empty());
+    }
+
+    #[test]
+    fn route_table_ignores_duplicate_routes() {
+        let r = route([H160::random(), H160::random(), H160::random()]);
+        let mut table = RouteTable::default();
+        table.insert(r.clone());
+        table.insert(r.clone());
+        assert_eq!(table.routes_for(&r.hops[0].pool).len(), 1);
+    }
 }
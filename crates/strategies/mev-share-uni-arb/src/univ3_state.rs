@@ -0,0 +1,734 @@
+//! Local state of watched uniswap v3 pools: their price, in range liquidity and
+//! initialized ticks, kept in sync from their `Swap`, `Mint` and `Burn` logs. Swaps are
+//! quoted with the same integer math as the pool contract, so quotes are exact without
+//! any RPC call, as long as the swap stays within the tick bitmap words loaded.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Result;
+use ethers::{
+    prelude::Lazy,
+    providers::Middleware,
+    types::{Filter, Log, H160, H256, U256, U512, U64},
+    utils::keccak256,
+};
+use futures::future::join_all;
+use tracing::warn;
+
+use crate::{pool_state::IUniswapV3Pool, solver::V3PoolState, templates::V3_SWAP_TOPIC};
+
+/// Lowest tick of a uniswap v3 pool.
+pub const MIN_TICK: i32 = -887_272;
+
+/// Highest tick of a uniswap v3 pool.
+pub const MAX_TICK: i32 = 887_272;
+
+/// Square root price at [MIN_TICK], as a Q64.96.
+static MIN_SQRT_RATIO: Lazy<U256> = Lazy::new(|| U256::from(4_295_128_739u64));
+
+/// Square root price at [MAX_TICK], as a Q64.96.
+static MAX_SQRT_RATIO: Lazy<U256> =
+    Lazy::new(|| U256::from_dec_str("1461446703485210103287273052203988822378723970342").unwrap());
+
+/// Topic of the `Mint` event of uniswap v3 pools.
+static V3_MINT_TOPIC: Lazy<H256> = Lazy::new(|| {
+    H256::from(keccak256(
+        "Mint(address,address,int24,int24,uint128,uint256,uint256)",
+    ))
+});
+
+/// Topic of the `Burn` event of uniswap v3 pools.
+static V3_BURN_TOPIC: Lazy<H256> = Lazy::new(|| {
+    H256::from(keccak256(
+        "Burn(address,int24,int24,uint128,uint256,uint256)",
+    ))
+});
+
+/// Fee denominator of uniswap v3 pools, fees are expressed in hundredths of a basis point.
+const FEE_DENOMINATOR: u64 = 1_000_000;
+
+/// Multipliers of `TickMath.getSqrtRatioAtTick`, as Q128.128 numbers, for each bit of
+/// the absolute tick past the first.
+const TICK_MULTIPLIERS: [(u32, u128); 19] = [
+    (0x2, 0xfff97272373d413259a46990580e213a),
+    (0x4, 0xfff2e50f5f656932ef12357cf3c7fdcc),
+    (0x8, 0xffe5caca7e10e4e61c3624eaa0941cd0),
+    (0x10, 0xffcb9843d60f6159c9db58835c926644),
+    (0x20, 0xff973b41fa98c081472e6896dfb254c0),
+    (0x40, 0xff2ea16466c96a3843ec78b326b52861),
+    (0x80, 0xfe5dee046a99a2a811c461f1969c3053),
+    (0x100, 0xfcbe86c7900a88aedcffc83b479aa3a4),
+    (0x200, 0xf987a7253ac413176f2b074cf7815e54),
+    (0x400, 0xf3392b0822b70005940c7a398e4b70f3),
+    (0x800, 0xe7159475a2c29b7443b29c7fa6e889d9),
+    (0x1000, 0xd097f3bdfd2022b8845ad8f792aa5825),
+    (0x2000, 0xa9f746462d870fdf8a65dc1f90e061e5),
+    (0x4000, 0x70d869a156d2a1b890bb3df62baf32f7),
+    (0x8000, 0x31be135f97d08fd981231505542fcfa6),
+    (0x10000, 0x9aa508b5b7a84e1c677de54f3e99bc9),
+    (0x20000, 0x5d6af8dedb81196699c329225ee604),
+    (0x40000, 0x2216e584f5fa1ea926041bedfe98),
+    (0x80000, 0x48a170391f7dc42444e8fa2),
+];
+
+/// Returns the square root price at `tick` as a Q64.96, as `TickMath.getSqrtRatioAtTick`.
+pub fn sqrt_ratio_at_tick(tick: i32) -> U256 {
+    let abs_tick = tick.unsigned_abs().min(MAX_TICK as u32);
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        U256::one() << 128
+    };
+    for (bit, multiplier) in TICK_MULTIPLIERS {
+        if abs_tick & bit != 0 {
+            ratio = (ratio * U256::from(multiplier)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // Round up, so the price at a tick is never below the price of the tick.
+    let round_up = !(ratio & U256::from(u32::MAX)).is_zero();
+    (ratio >> 32) + U256::from(round_up as u8)
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256) -> U256 {
+    U256::try_from(a.full_mul(b) / U512::from(denominator)).unwrap_or(U256::MAX)
+}
+
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> U256 {
+    let product = a.full_mul(b);
+    let denominator = U512::from(denominator);
+    let mut quotient = product / denominator;
+    if !(product % denominator).is_zero() {
+        quotient += U512::one();
+    }
+    U256::try_from(quotient).unwrap_or(U256::MAX)
+}
+
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    let round_up = !(a % b).is_zero();
+    a / b + U256::from(round_up as u8)
+}
+
+/// Amount of token0 between two square root prices, as `SqrtPriceMath.getAmount0Delta`.
+fn amount0_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> U256 {
+    let (a, b) = if a > b { (b, a) } else { (a, b) };
+    if a.is_zero() {
+        return U256::zero();
+    }
+    let numerator1 = U256::from(liquidity) << 96;
+    let numerator2 = b - a;
+    if round_up {
+        div_rounding_up(mul_div_rounding_up(numerator1, numerator2, b), a)
+    } else {
+        mul_div(numerator1, numerator2, b) / a
+    }
+}
+
+/// Amount of token1 between two square root prices, as `SqrtPriceMath.getAmount1Delta`.
+fn amount1_delta(a: U256, b: U256, liquidity: u128, round_up: bool) -> U256 {
+    let (a, b) = if a > b { (b, a) } else { (a, b) };
+    let q96 = U256::one() << 96;
+    if round_up {
+        mul_div_rounding_up(U256::from(liquidity), b - a, q96)
+    } else {
+        mul_div(U256::from(liquidity), b - a, q96)
+    }
+}
+
+/// Square root price after swapping in `amount_in`, net of fees, as
+/// `SqrtPriceMath.getNextSqrtPriceFromInput`.
+fn next_sqrt_price_from_input(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount_in: U256,
+    zero_for_one: bool,
+) -> U256 {
+    if amount_in.is_zero() {
+        return sqrt_price;
+    }
+    let numerator1 = U256::from(liquidity) << 96;
+    if zero_for_one {
+        // Rounding up, so the price moves at least as far as the input warrants.
+        let product = numerator1.full_mul(sqrt_price);
+        let denominator = U512::from(numerator1) + amount_in.full_mul(sqrt_price);
+        let mut quotient = product / denominator;
+        if !(product % denominator).is_zero() {
+            quotient += U512::one();
+        }
+        U256::try_from(quotient).unwrap_or(U256::MAX)
+    } else {
+        let quotient = amount_in.full_mul(U256::one() << 96) / U512::from(liquidity);
+        sqrt_price + U256::try_from(quotient).unwrap_or(U256::MAX)
+    }
+}
+
+/// Outcome of swapping within a single tick range.
+struct SwapStep {
+    sqrt_price_next: U256,
+    amount_in: U256,
+    amount_out: U256,
+    fee_amount: U256,
+}
+
+/// Swap up to `amount_remaining` towards `sqrt_price_target`, as `SwapMath.computeSwapStep`
+/// for exact input swaps.
+fn compute_swap_step(
+    sqrt_price: U256,
+    sqrt_price_target: U256,
+    liquidity: u128,
+    amount_remaining: U256,
+    fee: u32,
+) -> SwapStep {
+    let zero_for_one = sqrt_price >= sqrt_price_target;
+    let fee_denominator = U256::from(FEE_DENOMINATOR);
+    let fee = U256::from(fee);
+    let remaining_less_fee = mul_div(amount_remaining, fee_denominator - fee, fee_denominator);
+
+    let to_target = if zero_for_one {
+        amount0_delta(sqrt_price_target, sqrt_price, liquidity, true)
+    } else {
+        amount1_delta(sqrt_price, sqrt_price_target, liquidity, true)
+    };
+    let sqrt_price_next = if remaining_less_fee >= to_target {
+        sqrt_price_target
+    } else {
+        next_sqrt_price_from_input(sqrt_price, liquidity, remaining_less_fee, zero_for_one)
+    };
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        let amount_in = if reached_target {
+            to_target
+        } else {
+            amount0_delta(sqrt_price_next, sqrt_price, liquidity, true)
+        };
+        let amount_out = amount1_delta(sqrt_price_next, sqrt_price, liquidity, false);
+        (amount_in, amount_out)
+    } else {
+        let amount_in = if reached_target {
+            to_target
+        } else {
+            amount1_delta(sqrt_price, sqrt_price_next, liquidity, true)
+        };
+        let amount_out = amount0_delta(sqrt_price, sqrt_price_next, liquidity, false);
+        (amount_in, amount_out)
+    };
+    // Whatever isn't swapped in is kept as fees when the target isn't reached.
+    let fee_amount = if reached_target {
+        mul_div_rounding_up(amount_in, fee, fee_denominator - fee)
+    } else {
+        amount_remaining - amount_in
+    };
+    SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    }
+}
+
+/// Liquidity referencing an initialized tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickInfo {
+    /// Liquidity of the positions starting or ending at the tick.
+    pub liquidity_gross: u128,
+    /// Liquidity added when the price crosses the tick upwards.
+    pub liquidity_net: i128,
+}
+
+/// The state of a uniswap v3 pool, kept in sync from its logs.
+#[derive(Debug, Clone)]
+pub struct V3PoolTracker {
+    sqrt_price_x96: U256,
+    tick: i32,
+    liquidity: u128,
+    /// Fee, in hundredths of a basis point.
+    fee: u32,
+    tick_spacing: i32,
+    /// Initialized ticks within the loaded words.
+    ticks: BTreeMap<i32, TickInfo>,
+    /// Tick bitmap words whose ticks were loaded.
+    words: RangeInclusive<i32>,
+    /// Block and index of the last log applied.
+    last_log: Option<(U64, U256)>,
+    /// Set once a log applied was removed by a reorg, until the pool is synced again.
+    stale: bool,
+}
+
+impl V3PoolTracker {
+    /// Track a pool at the given price, tick and in range liquidity, whose initialized
+    /// ticks within the tick bitmap `words` are added with [with_tick](Self::with_tick).
+    pub fn new(
+        sqrt_price_x96: U256,
+        tick: i32,
+        liquidity: u128,
+        fee: u32,
+        tick_spacing: i32,
+        words: RangeInclusive<i16>,
+    ) -> Self {
+        Self {
+            sqrt_price_x96,
+            tick,
+            liquidity,
+            fee,
+            tick_spacing: tick_spacing.max(1),
+            ticks: BTreeMap::new(),
+            words: i32::from(*words.start())..=i32::from(*words.end()),
+            last_log: None,
+            stale: false,
+        }
+    }
+
+    /// Add an initialized tick.
+    pub fn with_tick(mut self, tick: i32, info: TickInfo) -> Self {
+        self.ticks.insert(tick, info);
+        self
+    }
+
+    /// Returns the state of the pool for the solvers, with every tick loaded.
+    pub fn state(&self) -> V3PoolState {
+        V3PoolState {
+            sqrt_price_x96: self.sqrt_price_x96,
+            tick: self.tick,
+            liquidity: self.liquidity,
+            fee: self.fee,
+            ticks: self
+                .ticks
+                .iter()
+                .map(|(tick, info)| (*tick, info.liquidity_net))
+                .collect(),
+        }
+    }
+
+    /// Quote the output of an exact input swap, selling token0 for token1 if
+    /// `zero_for_one`, and token1 for token0 otherwise. Returns `None` if the swap
+    /// would cross ticks past the loaded words, whose liquidity isn't known.
+    pub fn amount_out(&self, amount_in: U256, zero_for_one: bool) -> Option<U256> {
+        let limit = if zero_for_one {
+            *MIN_SQRT_RATIO + 1
+        } else {
+            *MAX_SQRT_RATIO - 1
+        };
+        let (mut sqrt_price, mut tick, mut liquidity) =
+            (self.sqrt_price_x96, self.tick, self.liquidity);
+        let mut remaining = amount_in;
+        let mut amount_out = U256::zero();
+
+        while !remaining.is_zero() && sqrt_price != limit {
+            let (tick_next, initialized) = self.next_initialized_tick(tick, zero_for_one)?;
+            let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+            let sqrt_price_next = sqrt_ratio_at_tick(tick_next);
+            let target = match zero_for_one {
+                true if sqrt_price_next < limit => limit,
+                false if sqrt_price_next > limit => limit,
+                _ => sqrt_price_next,
+            };
+
+            let step = compute_swap_step(sqrt_price, target, liquidity, remaining, self.fee);
+            sqrt_price = step.sqrt_price_next;
+            remaining = remaining.saturating_sub(step.amount_in + step.fee_amount);
+            amount_out += step.amount_out;
+
+            // Otherwise the input is spent, or the price limit reached.
+            if sqrt_price == sqrt_price_next {
+                if initialized {
+                    let liquidity_net = self.ticks[&tick_next].liquidity_net;
+                    let delta = if zero_for_one {
+                        -liquidity_net
+                    } else {
+                        liquidity_net
+                    };
+                    liquidity = add_delta(liquidity, delta)?;
+                }
+                tick = if zero_for_one {
+                    tick_next - 1
+                } else {
+                    tick_next
+                };
+            }
+        }
+        Some(amount_out)
+    }
+
+    /// Returns the next initialized tick in the swap direction within the bitmap word of
+    /// `tick`, or the end of the word if there is none, and whether it is initialized, as
+    /// `TickBitmap.nextInitializedTickWithinOneWord`.
+    fn next_initialized_tick(&self, tick: i32, lte: bool) -> Option<(i32, bool)> {
+        let spacing = self.tick_spacing;
+        let compressed = tick.div_euclid(spacing);
+        if lte {
+            if !self.words.contains(&(compressed >> 8)) {
+                return None;
+            }
+            let word_start = (compressed - (compressed & 0xff)) * spacing;
+            Some(
+                match self
+                    .ticks
+                    .range(word_start..=compressed * spacing)
+                    .next_back()
+                {
+                    Some((tick, _)) => (*tick, true),
+                    None => (word_start, false),
+                },
+            )
+        } else {
+            let compressed = compressed + 1;
+            if !self.words.contains(&(compressed >> 8)) {
+                return None;
+            }
+            let word_end = (compressed + 255 - (compressed & 0xff)) * spacing;
+            Some(
+                match self.ticks.range(compressed * spacing..=word_end).next() {
+                    Some((tick, _)) => (*tick, true),
+                    None => (word_end, false),
+                },
+            )
+        }
+    }
+
+    /// Returns whether the tick bitmap word holding `tick` was loaded.
+    fn is_loaded(&self, tick: i32) -> bool {
+        self.words
+            .contains(&(tick.div_euclid(self.tick_spacing) >> 8))
+    }
+
+    /// Apply a `Swap`, `Mint` or `Burn` log of the pool. Returns whether the log was
+    /// applied: logs of other events, and logs older than the last one applied, aren't.
+    pub fn apply_log(&mut self, log: &Log) -> bool {
+        let position = log.block_number.zip(log.log_index);
+        if let (Some(position), Some(last)) = (position, self.last_log) {
+            if position <= last {
+                return false;
+            }
+        }
+        if log.removed == Some(true) {
+            warn!("log of pool {:?} removed by a reorg", log.address);
+            self.stale = true;
+            return false;
+        }
+
+        let applied = match log.topics.first() {
+            Some(topic) if topic == &*V3_SWAP_TOPIC => self.apply_swap(log),
+            // Data is (address sender, uint128 amount, uint256 amount0, uint256 amount1).
+            Some(topic) if topic == &*V3_MINT_TOPIC => topic_tick(log, 2)
+                .zip(topic_tick(log, 3))
+                .zip(word_u128(log, 1))
+                .map(|((lower, upper), amount)| self.update_position(lower, upper, amount as i128)),
+            // Data is (uint128 amount, uint256 amount0, uint256 amount1).
+            Some(topic) if topic == &*V3_BURN_TOPIC => topic_tick(log, 1)
+                .zip(topic_tick(log, 2))
+                .zip(word_u128(log, 0))
+                .map(|((lower, upper), amount)| {
+                    self.update_position(lower, upper, -(amount as i128))
+                }),
+            _ => None,
+        };
+        if applied.is_some() {
+            if let Some(position) = position {
+                self.last_log = Some(position);
+            }
+        }
+        applied.is_some()
+    }
+
+    /// Set the price, tick and liquidity the pool was left at by a swap.
+    fn apply_swap(&mut self, log: &Log) -> Option<()> {
+        // Data is (int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128
+        // liquidity, int24 tick), each padded to a word.
+        self.sqrt_price_x96 = U256::from_big_endian(log_word(log, 2)?);
+        self.liquidity = word_u128(log, 3)?;
+        self.tick = i32::from_be_bytes(log_word(log, 4)?[28..].try_into().ok()?);
+        Some(())
+    }
+
+    /// Add `delta` liquidity to a position between `lower` and `upper`, as `Mint` and
+    /// `Burn` do. Ticks outside the loaded words are left out, since the liquidity of
+    /// the other positions referencing them isn't known.
+    fn update_position(&mut self, lower: i32, upper: i32, delta: i128) {
+        for (tick, net_delta) in [(lower, delta), (upper, -delta)] {
+            if !self.is_loaded(tick) {
+                continue;
+            }
+            let info = self.ticks.entry(tick).or_default();
+            info.liquidity_gross = saturating_add_delta(info.liquidity_gross, delta);
+            info.liquidity_net += net_delta;
+            if info.liquidity_gross == 0 {
+                self.ticks.remove(&tick);
+            }
+        }
+        if lower <= self.tick && self.tick < upper {
+            self.liquidity = saturating_add_delta(self.liquidity, delta);
+        }
+    }
+}
+
+/// Returns the `i`th word of the data of `log`.
+fn log_word(log: &Log, i: usize) -> Option<&[u8]> {
+    log.data.get(i * 32..(i + 1) * 32)
+}
+
+/// Returns the `i`th word of the data of `log`, as a uint128.
+fn word_u128(log: &Log, i: usize) -> Option<u128> {
+    Some(u128::from_be_bytes(
+        log_word(log, i)?[16..].try_into().ok()?,
+    ))
+}
+
+/// Returns the `i`th topic of `log`, as an int24.
+fn topic_tick(log: &Log, i: usize) -> Option<i32> {
+    let topic = log.topics.get(i)?;
+    Some(i32::from_be_bytes(topic.as_bytes()[28..].try_into().ok()?))
+}
+
+fn add_delta(liquidity: u128, delta: i128) -> Option<u128> {
+    if delta < 0 {
+        liquidity.checked_sub(delta.unsigned_abs())
+    } else {
+        liquidity.checked_add(delta as u128)
+    }
+}
+
+fn saturating_add_delta(liquidity: u128, delta: i128) -> u128 {
+    if delta < 0 {
+        liquidity.saturating_sub(delta.unsigned_abs())
+    } else {
+        liquidity.saturating_add(delta as u128)
+    }
+}
+
+/// The watched uniswap v3 pools, shared between the tasks applying their logs and the
+/// tasks quoting them.
+#[derive(Debug, Default)]
+pub struct UniV3State {
+    pools: RwLock<HashMap<H160, V3PoolTracker>>,
+}
+
+impl UniV3State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch `pool` at `address`, replacing its previous state if it was watched.
+    pub fn watch(&self, address: H160, pool: V3PoolTracker) {
+        self.pools.write().unwrap().insert(address, pool);
+    }
+
+    /// Returns the addresses of the watched pools.
+    pub fn watched(&self) -> Vec<H160> {
+        self.pools.read().unwrap().keys().copied().collect()
+    }
+
+    /// Returns a filter for the `Swap`, `Mint` and `Burn` logs of the watched pools, e.g.
+    /// for a [LogCollector](artemis_core::collectors::log_collector::LogCollector).
+    pub fn filter(&self) -> Filter {
+        Filter::new().address(self.watched()).topic0(vec![
+            *V3_SWAP_TOPIC,
+            *V3_MINT_TOPIC,
+            *V3_BURN_TOPIC,
+        ])
+    }
+
+    /// Apply a log of a watched pool. Returns whether it was applied.
+    pub fn apply_log(&self, log: &Log) -> bool {
+        match self.pools.write().unwrap().get_mut(&log.address) {
+            Some(pool) => pool.apply_log(log),
+            None => false,
+        }
+    }
+
+    /// Returns the state of a watched pool, unless a reorg removed a log applied to it.
+    pub fn state(&self, address: H160) -> Option<V3PoolState> {
+        let pools = self.pools.read().unwrap();
+        let pool = pools.get(&address).filter(|pool| !pool.stale)?;
+        Some(pool.state())
+    }
+
+    /// Quote an exact input swap through a watched pool, see
+    /// [amount_out](V3PoolTracker::amount_out).
+    pub fn amount_out(&self, address: H160, amount_in: U256, zero_for_one: bool) -> Option<U256> {
+        let pools = self.pools.read().unwrap();
+        let pool = pools.get(&address).filter(|pool| !pool.stale)?;
+        pool.amount_out(amount_in, zero_for_one)
+    }
+
+    /// Read the state of the pool at `address` at the latest block, with its initialized
+    /// ticks within `words` tick bitmap words on each side of the current tick, and watch
+    /// it. Logs are applied from the next block on.
+    pub async fn sync_pool<M: Middleware + 'static>(
+        &self,
+        client: Arc<M>,
+        address: H160,
+        words: i16,
+    ) -> Result<()> {
+        let block = client.get_block_number().await?;
+        let pool = IUniswapV3Pool::new(address, client);
+        let (sqrt_price_x96, tick, ..) = pool.slot_0().block(block).call().await?;
+        let liquidity = pool.liquidity().block(block).call().await?;
+        let fee = pool.fee().call().await?;
+        let tick_spacing = pool.tick_spacing().call().await?.max(1);
+
+        let word = (tick.div_euclid(tick_spacing) >> 8) as i16;
+        let words = word.saturating_sub(words)..=word.saturating_add(words);
+        let bitmaps = join_all(words.clone().map(|word| {
+            let call = pool.tick_bitmap(word).block(block);
+            async move { Ok::<_, anyhow::Error>((word, call.call().await?)) }
+        }))
+        .await;
+        let mut initialized = Vec::new();
+        for result in bitmaps {
+            let (word, bitmap) = result?;
+            for bit in 0..256 {
+                if bitmap.bit(bit) {
+                    initialized.push(((word as i32) * 256 + bit as i32) * tick_spacing);
+                }
+            }
+        }
+        let ticks = join_all(initialized.into_iter().map(|tick| {
+            let call = pool.ticks(tick).block(block);
+            async move {
+                let (liquidity_gross, liquidity_net, ..) = call.call().await?;
+                Ok::<_, anyhow::Error>((
+                    tick,
+                    TickInfo {
+                        liquidity_gross,
+                        liquidity_net,
+                    },
+                ))
+            }
+        }))
+        .await;
+
+        let mut tracker =
+            V3PoolTracker::new(sqrt_price_x96, tick, liquidity, fee, tick_spacing, words);
+        for result in ticks {
+            let (tick, info) = result?;
+            tracker = tracker.with_tick(tick, info);
+        }
+        // Logs of the block read are already reflected in the state.
+        tracker.last_log = Some((block, U256::MAX));
+        self.watch(address, tracker);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::u256_to_f64;
+    use ethers::types::Bytes;
+
+    #[test]
+    fn sqrt_ratio_matches_tick_math() {
+        assert_eq!(sqrt_ratio_at_tick(0), U256::one() << 96);
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK), *MIN_SQRT_RATIO);
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK), *MAX_SQRT_RATIO);
+        assert!(sqrt_ratio_at_tick(-1) < sqrt_ratio_at_tick(0));
+        assert!(sqrt_ratio_at_tick(1) > sqrt_ratio_at_tick(0));
+    }
+
+    /// A pool at tick 0 with a single position of `liquidity` between ticks -600 and 600.
+    fn pool(liquidity: u128) -> V3PoolTracker {
+        V3PoolTracker::new(sqrt_ratio_at_tick(0), 0, liquidity, 3_000, 60, -1..=0)
+            .with_tick(
+                -600,
+                TickInfo {
+                    liquidity_gross: liquidity,
+                    liquidity_net: liquidity as i128,
+                },
+            )
+            .with_tick(
+                600,
+                TickInfo {
+                    liquidity_gross: liquidity,
+                    liquidity_net: -(liquidity as i128),
+                },
+            )
+    }
+
+    #[test]
+    fn exact_quote_agrees_with_float_quote() {
+        let pool = pool(10u128.pow(24));
+        for (amount_in, zero_for_one) in [(U256::exp10(18), true), (U256::exp10(20), false)] {
+            let exact = u256_to_f64(pool.amount_out(amount_in, zero_for_one).unwrap());
+            let float = u256_to_f64(pool.state().get_amount_out(amount_in, zero_for_one));
+            assert!((exact - float).abs() / exact < 1e-6);
+        }
+    }
+
+    #[test]
+    fn quotes_crossing_unloaded_words_are_none() {
+        let pool = pool(10u128.pow(18));
+        assert!(pool.amount_out(U256::exp10(15), true).is_some());
+        // Far more than the position holds, so the swap goes past tick -600 and on
+        // through words whose ticks aren't known.
+        assert!(pool.amount_out(U256::exp10(24), true).is_none());
+        assert!(pool.amount_out(U256::exp10(24), false).is_none());
+    }
+
+    fn log(topics: Vec<H256>, words: Vec<[u8; 32]>, block: u64, index: u64) -> Log {
+        Log {
+            topics,
+            data: Bytes::from(words.concat()),
+            block_number: Some(U64::from(block)),
+            log_index: Some(U256::from(index)),
+            ..Default::default()
+        }
+    }
+
+    fn tick_word(tick: i32) -> [u8; 32] {
+        let mut word = if tick < 0 { [0xff; 32] } else { [0; 32] };
+        word[28..].copy_from_slice(&tick.to_be_bytes());
+        word
+    }
+
+    fn amount_word(amount: u128) -> [u8; 32] {
+        let mut word = [0; 32];
+        word[16..].copy_from_slice(&amount.to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn mints_and_burns_update_liquidity() {
+        let mut pool = pool(1_000);
+        let owner = H256::zero();
+        let mint = log(
+            vec![
+                *V3_MINT_TOPIC,
+                owner,
+                H256(tick_word(-60)),
+                H256(tick_word(120)),
+            ],
+            vec![[0; 32], amount_word(500), [0; 32], [0; 32]],
+            10,
+            0,
+        );
+        assert!(pool.apply_log(&mint));
+        assert_eq!(pool.liquidity, 1_500);
+        assert_eq!(pool.ticks[&-60].liquidity_net, 500);
+        assert_eq!(pool.ticks[&120].liquidity_net, -500);
+        // Logs already applied are skipped.
+        assert!(!pool.apply_log(&mint));
+
+        let burn = log(
+            vec![
+                *V3_BURN_TOPIC,
+                owner,
+                H256(tick_word(-60)),
+                H256(tick_word(120)),
+            ],
+            vec![amount_word(500), [0; 32], [0; 32]],
+            10,
+            1,
+        );
+        assert!(pool.apply_log(&burn));
+        assert_eq!(pool.liquidity, 1_000);
+        assert!(!pool.ticks.contains_key(&-60));
+        assert!(!pool.ticks.contains_key(&120));
+    }
+}
@@ -0,0 +1,49 @@
+use ethers::{abi::Token, types::U256};
+
+/// A leg of a backrun swapping native ETH rather than WETH, e.g. on a Curve pool
+/// trading ETH. The arb contract unwraps the WETH it flash loaned before paying the
+/// venue, or wraps the ETH the venue pays out before repaying the loan, all within the
+/// arb tx, so neither the wallet's WETH nor a tx of its own is involved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeEthLeg {
+    /// The venue takes `amount` of native ETH in, unwrapped from the loaned WETH.
+    In(U256),
+    /// The venue pays `amount` of native ETH out, wrapped into WETH to repay the loan.
+    Out(U256),
+}
+
+impl NativeEthLeg {
+    /// Encode the step the arb contract takes around the venue swap, as a tuple of its
+    /// kind (0 for none, 1 to unwrap before the swap, 2 to wrap after it) and amount.
+    pub fn encode(leg: Option<NativeEthLeg>) -> Token {
+        let (kind, amount) = match leg {
+            None => (0, U256::zero()),
+            Some(NativeEthLeg::In(amount)) => (1, amount),
+            Some(NativeEthLeg::Out(amount)) => (2, amount),
+        };
+        Token::Tuple(vec![Token::Uint(U256::from(kind)), Token::Uint(amount)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_the_step_around_the_venue_swap() {
+        let amount = U256::exp10(18);
+        let step = |kind: u64, amount: U256| {
+            Token::Tuple(vec![Token::Uint(U256::from(kind)), Token::Uint(amount)])
+        };
+
+        assert_eq!(NativeEthLeg::encode(None), step(0, U256::zero()));
+        assert_eq!(
+            NativeEthLeg::encode(Some(NativeEthLeg::In(amount))),
+            step(1, amount)
+        );
+        assert_eq!(
+            NativeEthLeg::encode(Some(NativeEthLeg::Out(amount))),
+            step(2, amount)
+        );
+    }
+}
@@ -11,9 +11,9 @@ use tracing::info;
 
 use crate::constants::FACTORY_DEPLOYMENT_BLOCK;
 use crate::types::Config;
-use anyhow::Result;
 use artemis_core::collectors::block_collector::NewBlock;
 use artemis_core::collectors::opensea_order_collector::OpenseaOrder;
+use artemis_core::error::{ArtemisError, Result};
 use artemis_core::executors::mempool_executor::{GasBidInfo, SubmitTxToMempool};
 use artemis_core::types::Strategy;
 use artemis_core::utilities::state_override_middleware::StateOverrideMiddleware;
@@ -85,7 +85,12 @@ impl<M: Middleware + 'static> Strategy<Event, Action> for OpenseaSudoArb<M> {
         // Block in which the pool factory was deployed.
         let start_block = FACTORY_DEPLOYMENT_BLOCK;
 
-        let current_block = self.client.get_block_number().await?.as_u64();
+        let current_block = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(ArtemisError::from_middleware)?
+            .as_u64();
 
         // Get all Sudo pool addresses deployed in the block range.
         let pool_addresses = self.get_new_pools(start_block, current_block).await?;
@@ -152,7 +157,7 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
     }
 
     /// Process new block events, updating the internal state.
-    async fn process_new_block_event(&mut self, event: NewBlock) -> Result<()> {
+    async fn process_new_block_event(&mut self, event: NewBlock) -> anyhow::Result<()> {
         info!("processing new block {}", event.number);
         // Find new pools tthat were created in the last block.
         let new_pools = self
@@ -213,7 +218,10 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
     }
 
     /// Get quotes for a list of pools.
-    async fn get_quotes_for_pools(&self, pools: Vec<H160>) -> Result<Vec<(H160, SellQuote)>> {
+    async fn get_quotes_for_pools(
+        &self,
+        pools: Vec<H160>,
+    ) -> anyhow::Result<Vec<(H160, SellQuote)>> {
         let quotes = self.quoter.get_multiple_sell_quotes(pools.clone()).await?;
         let res = pools
             .into_iter()
@@ -244,7 +252,11 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
     }
 
     /// Find all pools that were touched in a given block range.
-    async fn get_touched_pools(&self, from_block: u64, to_block: u64) -> Result<Vec<H160>> {
+    async fn get_touched_pools(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<H160>> {
         let address_list = self.pool_bids.keys().cloned().collect::<Vec<_>>();
         let filter = Filter::new()
             .from_block(from_block)
@@ -258,7 +270,11 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
     }
 
     /// Find all pools that were created in a given block range.
-    async fn get_new_pools(&self, from_block: u64, to_block: u64) -> Result<Vec<H160>> {
+    async fn get_new_pools(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<H160>> {
         let mut pool_addresses = vec![];
 
         // Maxium range for a single Alchemy query is 2000 blocks.
@@ -77,20 +77,25 @@ async fn main() -> Result<()> {
         wallet,
         args.arb_contract_address,
     );
-    engine.add_strategy(Box::new(strategy));
+    let concurrency = strategy.concurrency();
+    engine.add_concurrent_strategy(Box::new(strategy), concurrency);
 
     // Set up executor.
     let mev_share_executor = Box::new(MevshareExecutor::new(fb_signer, Chain::Mainnet));
     let mev_share_executor = ExecutorMap::new(mev_share_executor, |action| match action {
         Action::SubmitBundles(bundles) => Some(bundles),
+        Action::Sweep(_) | Action::SubmitFlashbotsBundle(_) => None,
     });
     engine.add_executor(Box::new(mev_share_executor));
 
     // Start engine.
-    if let Ok(mut set) = engine.run().await {
-        while let Some(res) = set.join_next().await {
-            info!("res: {:?}", res);
+    match engine.run().await {
+        Ok(running) => {
+            if let Err(report) = running.wait().await {
+                info!("engine stopped: {}", report);
+            }
         }
+        Err(e) => info!("error starting engine: {}", e),
     }
 
     Ok(())